@@ -0,0 +1,64 @@
+//! Audit log writer
+//!
+//! Writes emitted events through the store in the order they were emitted.
+//! Writing happens on a background task so that emitting an event never
+//! waits for IO, and a failed write is logged rather than failing the
+//! operation that produced the event.
+
+use crate::scheduler::EventEnvelope;
+use crate::store::CronStore;
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot};
+
+/// Work for the writer task
+enum Message {
+    /// Append an event
+    Event(EventEnvelope),
+    /// Signal once every earlier event has been written
+    Flush(oneshot::Sender<()>),
+}
+
+/// Handle to the task appending events to a store's audit log
+#[derive(Clone)]
+pub(crate) struct AuditLog {
+    tx: mpsc::UnboundedSender<Message>,
+}
+
+impl AuditLog {
+    /// Start the writer task. Must be called from within a Tokio runtime.
+    pub(crate) fn spawn(store: Arc<dyn CronStore>) -> Self {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Some(message) = rx.recv().await {
+                match message {
+                    Message::Event(event) => {
+                        if let Err(e) = store.append_event(&event).await {
+                            tracing::warn!(
+                                "Failed to write event {} to audit log: {}",
+                                event.id,
+                                e
+                            );
+                        }
+                    }
+                    Message::Flush(done) => {
+                        let _ = done.send(());
+                    }
+                }
+            }
+        });
+        Self { tx }
+    }
+
+    /// Queue an event for writing
+    pub(crate) fn record(&self, event: &EventEnvelope) {
+        let _ = self.tx.send(Message::Event(event.clone()));
+    }
+
+    /// Wait until every event recorded so far has been written
+    pub(crate) async fn flush(&self) {
+        let (done, written) = oneshot::channel();
+        if self.tx.send(Message::Flush(done)).is_ok() {
+            let _ = written.await;
+        }
+    }
+}
@@ -0,0 +1,432 @@
+//! Builder for a fully configured [`CronManager`]
+//!
+//! Every setting is chosen and checked before the manager, its store or its
+//! background tasks exist, so nothing needs changing between construction
+//! and [`CronManager::start`].
+
+use crate::clock::Clock;
+use crate::middleware::JobMiddleware;
+use crate::notifier::Notifier;
+use crate::process;
+use crate::scheduler::{
+    check_anomaly_threshold, check_coordination, check_default_timeout, check_event_capacity,
+    check_heartbeat_interval, check_max_concurrent_jobs, check_output_storage,
+    check_stall_after_ticks, check_tick_interval, CronManager, DEFAULT_EVENT_CAPACITY,
+    DEFAULT_HEARTBEAT_INTERVAL, DEFAULT_STALL_TICKS, DEFAULT_TICK_INTERVAL,
+};
+use crate::store::{CronStore, FileCronStore};
+use crate::types::{
+    AgentExecutor, Coordination, CronError, EnvMode, HealthThresholds, OutputStorage, Result,
+    RetentionPolicy, DEFAULT_ANOMALY_THRESHOLD,
+};
+use crate::window::TimeWindow;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Builds a [`CronManager`] with every setting chosen up front.
+///
+/// ```ignore
+/// let manager = CronManager::builder("/path/to/workspace")
+///     .with_tick_interval(Duration::from_secs(10))
+///     .with_max_concurrent_jobs(4)
+///     .with_retention(RetentionPolicy::new().with_max_executions_per_job(100))
+///     .build()
+///     .await?;
+/// ```
+pub struct CronManagerBuilder {
+    workspace: String,
+    store: Option<Arc<dyn CronStore>>,
+    tick_interval: Duration,
+    max_concurrent_jobs: Option<usize>,
+    event_capacity: usize,
+    min_interval: Option<Duration>,
+    default_timeout: Option<Duration>,
+    heartbeat_interval: Option<Duration>,
+    default_shell: Option<String>,
+    default_env_mode: EnvMode,
+    output_storage: OutputStorage,
+    health_thresholds: HealthThresholds,
+    anomaly_threshold: f64,
+    stall_after_ticks: u32,
+    restart_on_stall: bool,
+    manifest: Option<(PathBuf, bool)>,
+    retention: RetentionPolicy,
+    audit_log: bool,
+    blackouts: Vec<TimeWindow>,
+    agent_executor: Option<Arc<dyn AgentExecutor>>,
+    notifiers: Vec<Arc<dyn Notifier>>,
+    middlewares: Vec<Arc<dyn JobMiddleware>>,
+    clock: Option<Arc<dyn Clock>>,
+    coordination: Coordination,
+    instance_id: Option<String>,
+}
+
+impl CronManagerBuilder {
+    /// Start building a manager working in `workspace`, with the defaults of
+    /// [`CronManager::new`]
+    pub fn new(workspace: impl Into<String>) -> Self {
+        Self {
+            workspace: workspace.into(),
+            store: None,
+            tick_interval: DEFAULT_TICK_INTERVAL,
+            max_concurrent_jobs: None,
+            event_capacity: DEFAULT_EVENT_CAPACITY,
+            min_interval: None,
+            default_timeout: None,
+            heartbeat_interval: Some(DEFAULT_HEARTBEAT_INTERVAL),
+            default_shell: None,
+            default_env_mode: EnvMode::default(),
+            output_storage: OutputStorage::default(),
+            health_thresholds: HealthThresholds::default(),
+            anomaly_threshold: DEFAULT_ANOMALY_THRESHOLD,
+            stall_after_ticks: DEFAULT_STALL_TICKS,
+            restart_on_stall: false,
+            manifest: None,
+            retention: RetentionPolicy::default(),
+            audit_log: false,
+            blackouts: Vec::new(),
+            agent_executor: None,
+            notifiers: Vec::new(),
+            middlewares: Vec::new(),
+            clock: None,
+            coordination: Coordination::None,
+            instance_id: None,
+        }
+    }
+
+    /// Keep jobs and history in `store` instead of files in the workspace
+    pub fn with_store(mut self, store: Arc<dyn CronStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Set how often the scheduler re-reads the store; see
+    /// [`CronManager::with_tick_interval`]
+    pub fn with_tick_interval(mut self, tick_interval: Duration) -> Self {
+        self.tick_interval = tick_interval;
+        self
+    }
+
+    /// Limit how many jobs execute at the same time; see
+    /// [`CronManager::with_max_concurrent_jobs`]
+    pub fn with_max_concurrent_jobs(mut self, max_concurrent_jobs: usize) -> Self {
+        self.max_concurrent_jobs = Some(max_concurrent_jobs);
+        self
+    }
+
+    /// Set how many events are buffered for each subscriber; see
+    /// [`CronManager::with_event_capacity`]
+    pub fn with_event_capacity(mut self, capacity: usize) -> Self {
+        self.event_capacity = capacity;
+        self
+    }
+
+    /// Reject schedules firing more often than `min_interval`; see
+    /// [`CronManager::set_min_interval`]
+    pub fn with_min_interval(mut self, min_interval: Duration) -> Self {
+        self.min_interval = Some(min_interval);
+        self
+    }
+
+    /// Set the timeout of jobs that don't set their own; see
+    /// [`CronManager::with_default_timeout`]
+    pub fn with_default_timeout(mut self, timeout: Duration) -> Self {
+        self.default_timeout = Some(timeout);
+        self
+    }
+
+    /// Set how often running executions send a heartbeat, or turn
+    /// heartbeats off with `None`; see [`CronManager::with_heartbeat_interval`]
+    pub fn with_heartbeat_interval(mut self, interval: Option<Duration>) -> Self {
+        self.heartbeat_interval = interval;
+        self
+    }
+
+    /// Set the shell running commands of jobs that don't choose their own
+    pub fn with_default_shell(mut self, shell: impl Into<String>) -> Self {
+        self.default_shell = Some(shell.into());
+        self
+    }
+
+    /// Set the environment inherited by jobs that don't choose their own
+    pub fn with_default_env_mode(mut self, env_mode: EnvMode) -> Self {
+        self.default_env_mode = env_mode;
+        self
+    }
+
+    /// Set where execution output is kept
+    pub fn with_output_storage(mut self, storage: OutputStorage) -> Self {
+        self.output_storage = storage;
+        self
+    }
+
+    /// Set the thresholds classifying job health
+    pub fn with_health_thresholds(mut self, thresholds: HealthThresholds) -> Self {
+        self.health_thresholds = thresholds;
+        self
+    }
+
+    /// Set how many standard deviations above a job's mean duration a
+    /// successful run must take to be reported as anomalous
+    pub fn with_anomaly_threshold(mut self, threshold: f64) -> Self {
+        self.anomaly_threshold = threshold;
+        self
+    }
+
+    /// Set after how many tick intervals without the scheduler loop going
+    /// round the watchdog reports it stalled
+    pub fn with_stall_after_ticks(mut self, ticks: u32) -> Self {
+        self.stall_after_ticks = ticks;
+        self
+    }
+
+    /// Have the watchdog replace a stalled scheduler loop with a new one
+    pub fn with_restart_on_stall(mut self, restart: bool) -> Self {
+        self.restart_on_stall = restart;
+        self
+    }
+
+    /// Set the JSON manifest file re-applied on reload, with `prune`
+    /// removing jobs missing from it
+    pub fn with_manifest(mut self, path: impl Into<PathBuf>, prune: bool) -> Self {
+        self.manifest = Some((path.into(), prune));
+        self
+    }
+
+    /// Set how much execution history is kept for each job
+    pub fn with_retention(mut self, retention: RetentionPolicy) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    /// Write every emitted event to the store's audit log
+    pub fn with_audit_log(mut self, enabled: bool) -> Self {
+        self.audit_log = enabled;
+        self
+    }
+
+    /// Set blackout windows during which no job runs on schedule
+    pub fn with_blackouts(mut self, blackouts: Vec<TimeWindow>) -> Self {
+        self.blackouts = blackouts;
+        self
+    }
+
+    /// Set the agent executor for agent-mode jobs
+    pub fn with_agent_executor(mut self, executor: Arc<dyn AgentExecutor>) -> Self {
+        self.agent_executor = Some(executor);
+        self
+    }
+
+    /// Send notifications of job events to `notifier`, besides any already
+    /// added
+    pub fn with_notifier(mut self, notifier: Arc<dyn Notifier>) -> Self {
+        self.notifiers.push(notifier);
+        self
+    }
+
+    /// Run `middleware` around every job attempt, after any already added
+    pub fn with_middleware(mut self, middleware: Arc<dyn JobMiddleware>) -> Self {
+        self.middlewares.push(middleware);
+        self
+    }
+
+    /// Decide when jobs are due by `clock` instead of the wall clock
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Coordinate with other managers sharing the store; see
+    /// [`CronManager::with_coordination`]
+    pub fn with_coordination(mut self, coordination: Coordination) -> Self {
+        self.coordination = coordination;
+        self
+    }
+
+    /// Hold execution leases under `instance_id` instead of a random UUID
+    pub fn with_instance_id(mut self, instance_id: impl Into<String>) -> Self {
+        self.instance_id = Some(instance_id.into());
+        self
+    }
+
+    /// Check the settings are usable and consistent with each other
+    pub fn validate(&self) -> Result<()> {
+        check_tick_interval(self.tick_interval)?;
+        check_max_concurrent_jobs(self.max_concurrent_jobs)?;
+        check_event_capacity(self.event_capacity)?;
+        check_default_timeout(self.default_timeout)?;
+        check_heartbeat_interval(self.heartbeat_interval)?;
+        check_coordination(self.coordination)?;
+        check_output_storage(&self.output_storage)?;
+        check_anomaly_threshold(self.anomaly_threshold)?;
+        check_stall_after_ticks(self.stall_after_ticks)?;
+        if self.retention.max_executions_per_job == Some(0) {
+            return Err(CronError::InvalidConfig(
+                "retention must keep at least one execution per job".to_string(),
+            ));
+        }
+        if self.retention.max_age.is_some_and(|age| age.is_zero()) {
+            return Err(CronError::InvalidConfig(
+                "retention max age must be greater than zero".to_string(),
+            ));
+        }
+        if let Some(shell) = &self.default_shell {
+            process::check_executable(shell)?;
+        }
+        Ok(())
+    }
+
+    /// Build the manager. Fails before touching the workspace if the
+    /// settings don't [`validate`](Self::validate). Must be called from
+    /// within a Tokio runtime when the audit log or notifiers are enabled.
+    pub async fn build(self) -> Result<CronManager> {
+        self.validate()?;
+        let store: Arc<dyn CronStore> = match self.store {
+            Some(store) => store,
+            None => Arc::new(FileCronStore::new(&self.workspace).await?),
+        };
+
+        let mut manager = CronManager::with_store(store, self.workspace)
+            .with_tick_interval(self.tick_interval)?
+            .with_max_concurrent_jobs(self.max_concurrent_jobs)?
+            .with_event_capacity(self.event_capacity)?
+            .with_default_timeout(self.default_timeout)?
+            .with_heartbeat_interval(self.heartbeat_interval)?
+            .with_coordination(self.coordination)?
+            .with_default_shell(self.default_shell)?
+            .with_default_env_mode(self.default_env_mode)
+            .with_output_storage(self.output_storage)?
+            .with_health_thresholds(self.health_thresholds)
+            .with_anomaly_threshold(self.anomaly_threshold)?
+            .with_stall_after_ticks(self.stall_after_ticks)?
+            .with_restart_on_stall(self.restart_on_stall)
+            .with_retention(self.retention)
+            .with_audit_log(self.audit_log)
+            .with_blackouts(self.blackouts);
+        if let Some(clock) = self.clock {
+            manager = manager.with_clock(clock);
+        }
+        if let Some(instance_id) = self.instance_id {
+            manager = manager.with_instance_id(instance_id);
+        }
+        if let Some((path, prune)) = self.manifest {
+            manager = manager.with_manifest(path, prune);
+        }
+        manager.set_min_interval(self.min_interval);
+        if let Some(executor) = self.agent_executor {
+            manager.set_agent_executor(executor);
+        }
+        for middleware in self.middlewares {
+            manager.add_middleware(middleware);
+        }
+        // Notifiers subscribe to the final event channel
+        for notifier in self.notifiers {
+            manager.add_notifier(notifier);
+        }
+        Ok(manager)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::MemoryCronStore;
+    use crate::types::CronJob;
+    use chrono::Utc;
+
+    #[test]
+    fn test_validate_rejects_unusable_settings() {
+        let invalid = [
+            CronManagerBuilder::new("/tmp").with_tick_interval(Duration::ZERO),
+            CronManagerBuilder::new("/tmp").with_max_concurrent_jobs(0),
+            CronManagerBuilder::new("/tmp").with_event_capacity(0),
+            CronManagerBuilder::new("/tmp").with_default_timeout(Duration::ZERO),
+            CronManagerBuilder::new("/tmp").with_heartbeat_interval(Some(Duration::ZERO)),
+            CronManagerBuilder::new("/tmp").with_coordination(Coordination::LeaderElection {
+                lease_ttl: Duration::from_millis(500),
+            }),
+            CronManagerBuilder::new("/tmp")
+                .with_retention(RetentionPolicy::new().with_max_executions_per_job(0)),
+            CronManagerBuilder::new("/tmp")
+                .with_retention(RetentionPolicy::new().with_max_age(Duration::ZERO)),
+            CronManagerBuilder::new("/tmp").with_output_storage(OutputStorage::Files {
+                dir: "/tmp".into(),
+                max_total_bytes: 0,
+            }),
+            CronManagerBuilder::new("/tmp").with_anomaly_threshold(0.0),
+            CronManagerBuilder::new("/tmp").with_anomaly_threshold(f64::NAN),
+            CronManagerBuilder::new("/tmp").with_stall_after_ticks(0),
+        ];
+        for builder in invalid {
+            assert!(matches!(
+                builder.validate(),
+                Err(CronError::InvalidConfig(_))
+            ));
+        }
+        assert!(CronManagerBuilder::new("/tmp")
+            .with_default_shell("/nonexistent/shell")
+            .validate()
+            .is_err());
+        assert!(CronManagerBuilder::new("/tmp").validate().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_build_fails_before_touching_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        let result = CronManager::builder(dir.path().to_string_lossy())
+            .with_tick_interval(Duration::ZERO)
+            .build()
+            .await;
+        assert!(matches!(result, Err(CronError::InvalidConfig(_))));
+        assert!(!dir.path().join(".a3s").exists());
+
+        let manager = CronManager::builder(dir.path().to_string_lossy())
+            .build()
+            .await
+            .unwrap();
+        assert!(dir.path().join(".a3s/cron/jobs.json").exists());
+        assert_eq!(manager.tick_interval(), DEFAULT_TICK_INTERVAL);
+    }
+
+    #[tokio::test]
+    async fn test_build_applies_settings_to_scheduler() {
+        let store = Arc::new(MemoryCronStore::new());
+        let manager = CronManager::builder("/tmp")
+            .with_store(store.clone())
+            .with_tick_interval(Duration::from_secs(5))
+            .with_max_concurrent_jobs(1)
+            .with_event_capacity(500)
+            .with_min_interval(Duration::from_secs(60))
+            .with_default_timeout(Duration::from_secs(30))
+            .with_retention(RetentionPolicy::new().with_max_executions_per_job(10))
+            .with_anomaly_threshold(2.5)
+            .with_stall_after_ticks(5)
+            .with_restart_on_stall(true)
+            .with_audit_log(true)
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(manager.tick_interval(), Duration::from_secs(5));
+        assert_eq!(manager.max_concurrent_jobs(), Some(1));
+        assert_eq!(manager.event_capacity(), 500);
+        assert_eq!(manager.min_interval(), Some(Duration::from_secs(60)));
+        assert_eq!(manager.default_timeout(), Some(Duration::from_secs(30)));
+        assert_eq!(manager.retention().max_executions_per_job, Some(10));
+        assert_eq!(manager.anomaly_threshold(), 2.5);
+        assert_eq!(manager.stall_after_ticks(), 5);
+        assert!(manager.restart_on_stall());
+        assert!(manager.audit_log_enabled());
+
+        // The scheduler loop honours the concurrency limit
+        for name in ["first", "second"] {
+            let mut job: CronJob = manager.add_job(name, "0 0 1 1 *", "sleep 1").await.unwrap();
+            job.next_run = Some(Utc::now() - chrono::Duration::seconds(1));
+            store.save_job(&job).await.unwrap();
+        }
+        manager.start().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(manager.running_executions().len(), 1);
+        manager.stop().await;
+    }
+}
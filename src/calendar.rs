@@ -0,0 +1,154 @@
+//! Holiday calendars
+//!
+//! A calendar is a set of dates (in UTC) on which jobs referring to it must
+//! not run. Calendars are registered on a manager by name and can be
+//! replaced at any time; they are not persisted.
+
+use crate::types::{CronError, Result};
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use std::collections::BTreeSet;
+
+/// A set of excluded dates, e.g. public holidays
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Calendar {
+    dates: BTreeSet<NaiveDate>,
+}
+
+impl Calendar {
+    /// Create a calendar excluding `dates`
+    pub fn new(dates: impl IntoIterator<Item = NaiveDate>) -> Self {
+        Self {
+            dates: dates.into_iter().collect(),
+        }
+    }
+
+    /// Parse a JSON array of `YYYY-MM-DD` dates
+    pub fn from_json(json: &str) -> Result<Self> {
+        let dates: Vec<String> = serde_json::from_str(json)?;
+        dates.iter().map(|date| parse_date(date)).collect()
+    }
+
+    /// Read the dates of the events in an iCalendar file, from their
+    /// `DTSTART` lines. Only the date part of each start is used.
+    pub fn from_ics(ics: &str) -> Result<Self> {
+        ics.lines()
+            .filter_map(|line| {
+                let (name, value) = line.trim().split_once(':')?;
+                let property = name.split(';').next()?;
+                property
+                    .eq_ignore_ascii_case("DTSTART")
+                    .then(|| value.get(..8).unwrap_or(value))
+            })
+            .map(parse_date)
+            .collect()
+    }
+
+    /// Check if the calendar excludes the date of `at`
+    pub fn excludes(&self, at: DateTime<Utc>) -> bool {
+        self.dates.contains(&at.date_naive())
+    }
+
+    /// Start of the first day at or after `at` that isn't excluded
+    pub fn next_open_day(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        if !self.excludes(at) {
+            return at;
+        }
+        let mut day = at.date_naive();
+        while self.dates.contains(&day) {
+            match day.checked_add_days(Days::new(1)) {
+                Some(next) => day = next,
+                None => break,
+            }
+        }
+        day.and_time(chrono::NaiveTime::MIN).and_utc()
+    }
+
+    /// Excluded dates, in order
+    pub fn dates(&self) -> impl Iterator<Item = &NaiveDate> {
+        self.dates.iter()
+    }
+
+    /// Number of excluded dates
+    pub fn len(&self) -> usize {
+        self.dates.len()
+    }
+
+    /// Check if the calendar excludes no dates
+    pub fn is_empty(&self) -> bool {
+        self.dates.is_empty()
+    }
+}
+
+impl FromIterator<NaiveDate> for Calendar {
+    fn from_iter<I: IntoIterator<Item = NaiveDate>>(dates: I) -> Self {
+        Self::new(dates)
+    }
+}
+
+impl IntoIterator for Calendar {
+    type Item = NaiveDate;
+    type IntoIter = std::collections::btree_set::IntoIter<NaiveDate>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.dates.into_iter()
+    }
+}
+
+/// Parse a date written as `YYYY-MM-DD` or `YYYYMMDD`
+fn parse_date(date: &str) -> Result<NaiveDate> {
+    let date = date.trim();
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(date, "%Y%m%d"))
+        .map_err(|_| CronError::InvalidConfig(format!("invalid calendar date '{}'", date)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_from_json() {
+        let calendar = Calendar::from_json(r#"["2026-12-25", "2026-01-01"]"#).unwrap();
+        assert_eq!(
+            calendar.dates().copied().collect::<Vec<_>>(),
+            vec![date(1, 1), date(12, 25)]
+        );
+        assert!(Calendar::from_json(r#"["Christmas"]"#).is_err());
+        assert!(Calendar::from_json("2026-12-25").is_err());
+    }
+
+    #[test]
+    fn test_from_ics() {
+        let ics = "BEGIN:VCALENDAR\r\n\
+                   BEGIN:VEVENT\r\n\
+                   DTSTART;VALUE=DATE:20261225\r\n\
+                   SUMMARY:Christmas Day\r\n\
+                   END:VEVENT\r\n\
+                   BEGIN:VEVENT\r\n\
+                   DTSTART:20260101T000000Z\r\n\
+                   END:VEVENT\r\n\
+                   END:VCALENDAR\r\n";
+        let calendar = Calendar::from_ics(ics).unwrap();
+        assert_eq!(calendar.len(), 2);
+        assert!(calendar.excludes(Utc.with_ymd_and_hms(2026, 12, 25, 9, 0, 0).unwrap()));
+        assert!(calendar.excludes(Utc.with_ymd_and_hms(2026, 1, 1, 23, 59, 0).unwrap()));
+        assert!(!calendar.excludes(Utc.with_ymd_and_hms(2026, 1, 2, 0, 0, 0).unwrap()));
+    }
+
+    #[test]
+    fn test_next_open_day() {
+        let calendar = Calendar::new([date(12, 25), date(12, 26)]);
+        let christmas = Utc.with_ymd_and_hms(2026, 12, 25, 9, 0, 0).unwrap();
+        assert_eq!(
+            calendar.next_open_day(christmas),
+            Utc.with_ymd_and_hms(2026, 12, 27, 0, 0, 0).unwrap()
+        );
+        let before = Utc.with_ymd_and_hms(2026, 12, 24, 9, 0, 0).unwrap();
+        assert_eq!(calendar.next_open_day(before), before);
+    }
+}
@@ -0,0 +1,129 @@
+//! Time source for scheduling decisions
+//!
+//! The scheduler asks its [`Clock`] what time it is whenever it decides when
+//! a job is due, and waits on it between checks. [`SystemClock`] follows the
+//! wall clock; [`MockClock`] only moves when told to, so tests can step
+//! through fire times without sleeping. Executions are stamped with the
+//! clock's time when they start, as their start times feed later scheduling
+//! decisions, but their durations are measured in real time. Event
+//! timestamps always use the wall clock.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// Source of the current time and of timed waits for the scheduler
+#[async_trait]
+pub trait Clock: Send + Sync {
+    /// The current time
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Wait until the clock reaches `deadline`; returns at once if it
+    /// already has
+    async fn sleep_until(&self, deadline: DateTime<Utc>);
+}
+
+/// The wall clock, used unless the manager is given another
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+#[async_trait]
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    async fn sleep_until(&self, deadline: DateTime<Utc>) {
+        let delay = (deadline - Utc::now()).to_std().unwrap_or_default();
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// A clock that stands still until moved with [`advance`](Self::advance) or
+/// [`set`](Self::set), waking anything sleeping past the new time
+#[derive(Debug)]
+pub struct MockClock {
+    now: watch::Sender<DateTime<Utc>>,
+}
+
+impl MockClock {
+    /// Create a clock stopped at `start`
+    pub fn new(start: DateTime<Utc>) -> Self {
+        Self {
+            now: watch::Sender::new(start),
+        }
+    }
+
+    /// Move the clock forward by `by`
+    pub fn advance(&self, by: Duration) {
+        let by = chrono::Duration::from_std(by).unwrap_or(chrono::TimeDelta::MAX);
+        self.now.send_modify(|now| *now += by);
+    }
+
+    /// Move the clock to `at`, which may be earlier than the current time
+    pub fn set(&self, at: DateTime<Utc>) {
+        self.now.send_replace(at);
+    }
+}
+
+#[async_trait]
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.borrow()
+    }
+
+    async fn sleep_until(&self, deadline: DateTime<Utc>) {
+        let mut now = self.now.subscribe();
+        while *now.borrow_and_update() < deadline {
+            if now.changed().await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_mock_clock_wakes_sleepers() {
+        let start = DateTime::from_timestamp(1_800_000_000, 0).unwrap();
+        let clock = Arc::new(MockClock::new(start));
+        assert_eq!(clock.now(), start);
+
+        let deadline = start + chrono::Duration::minutes(1);
+        let sleeper = {
+            let clock = clock.clone();
+            tokio::spawn(async move { clock.sleep_until(deadline).await })
+        };
+
+        clock.advance(Duration::from_secs(30));
+        tokio::task::yield_now().await;
+        assert!(!sleeper.is_finished());
+
+        clock.advance(Duration::from_secs(30));
+        tokio::time::timeout(Duration::from_secs(1), sleeper)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(clock.now(), deadline);
+
+        // Past deadlines return at once
+        clock.set(start);
+        clock.sleep_until(start).await;
+        assert_eq!(clock.now(), start);
+    }
+
+    #[tokio::test]
+    async fn test_system_clock() {
+        let clock = SystemClock;
+        let before = Utc::now();
+        assert!(clock.now() >= before);
+        clock
+            .sleep_until(before - chrono::Duration::seconds(1))
+            .await;
+    }
+}
@@ -0,0 +1,649 @@
+//! JSON-RPC control socket
+//!
+//! [`ControlServer`] lets a CLI drive a long-running scheduler without a
+//! TCP port: it listens on a Unix socket in the workspace's cron directory
+//! ([`socket_path`]) and speaks JSON-RPC 2.0, one message per line.
+//! [`ControlClient`] is the other end.
+//!
+//! | Method | Params | Result |
+//! |---|---|---|
+//! | `list_jobs` | | [`CronJob`]s |
+//! | `add_job` | a [`ManifestJob`] | the [`CronJob`] added |
+//! | `run_job` | `{"id"}` | the [`JobExecution`] |
+//! | `pause` | `{"id"}` | the [`CronJob`] paused |
+//! | `resume` | `{"id"}` | the [`CronJob`] resumed |
+//! | `history` | `{"id", "limit"?}` | [`JobExecution`]s, most recent first |
+//! | `subscribe` | | `true`, then an `event` notification per [`EventEnvelope`] |
+//!
+//! Requests are refused with [`ERROR_NOT_STARTED`] until the manager's
+//! scheduler is started. Failed requests answer with the [`CronError`]'s
+//! message under [`ERROR_REQUEST_FAILED`].
+
+use crate::scheduler::{CronManager, EventEnvelope, SchedulerEvent};
+use crate::types::{CronError, CronJob, JobExecution, ManifestJob, Result};
+use chrono::Utc;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::net::unix::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+/// File name of the socket in the workspace's cron directory
+pub const SOCKET_FILE: &str = "control.sock";
+
+/// Executions returned by `history` unless `limit` is given
+pub const DEFAULT_HISTORY_LIMIT: usize = 20;
+
+/// A message isn't valid JSON
+pub const ERROR_PARSE: i64 = -32700;
+/// A message isn't a JSON-RPC request
+pub const ERROR_INVALID_REQUEST: i64 = -32600;
+/// No method has the requested name
+pub const ERROR_METHOD_NOT_FOUND: i64 = -32601;
+/// The params don't fit the method
+pub const ERROR_INVALID_PARAMS: i64 = -32602;
+/// The manager failed the request
+pub const ERROR_REQUEST_FAILED: i64 = -32000;
+/// The manager's scheduler isn't started
+pub const ERROR_NOT_STARTED: i64 = -32001;
+
+const JSONRPC_VERSION: &str = "2.0";
+
+/// Path of the control socket of the workspace at `workspace`, beside the
+/// file store's data
+pub fn socket_path(workspace: impl AsRef<Path>) -> PathBuf {
+    workspace
+        .as_ref()
+        .join(".a3s")
+        .join("cron")
+        .join(SOCKET_FILE)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Request {
+    jsonrpc: String,
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Response {
+    jsonrpc: String,
+    id: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<RpcError>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Notification {
+    jsonrpc: String,
+    method: String,
+    params: Value,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<CronError> for RpcError {
+    fn from(error: CronError) -> Self {
+        Self::new(ERROR_REQUEST_FAILED, error.to_string())
+    }
+}
+
+/// Params naming a job
+#[derive(Debug, Serialize, Deserialize)]
+struct JobParams {
+    id: String,
+}
+
+/// Params of `history`
+#[derive(Debug, Serialize, Deserialize)]
+struct HistoryParams {
+    id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    limit: Option<usize>,
+}
+
+/// Serialize one line of the protocol
+fn to_line(message: &impl Serialize) -> String {
+    let mut line = serde_json::to_string(message).unwrap_or_default();
+    line.push('\n');
+    line
+}
+
+/// A control socket serving one manager. The socket file is removed when
+/// the server is shut down or dropped.
+pub struct ControlServer {
+    path: PathBuf,
+    accept: JoinHandle<()>,
+    connections: Arc<std::sync::Mutex<Vec<JoinHandle<()>>>>,
+}
+
+impl ControlServer {
+    /// Listen on the control socket of the manager's workspace
+    pub async fn bind(manager: Arc<CronManager>) -> Result<Self> {
+        let path = socket_path(manager.workspace());
+        Self::bind_at(manager, path).await
+    }
+
+    /// Listen on the socket at `path`. A socket file left behind by a server
+    /// that is gone is replaced; one another server listens on is not.
+    pub async fn bind_at(manager: Arc<CronManager>, path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        if path.exists() {
+            if UnixStream::connect(&path).await.is_ok() {
+                return Err(CronError::InvalidConfig(format!(
+                    "a control server is already listening on {}",
+                    path.display()
+                )));
+            }
+            tokio::fs::remove_file(&path).await?;
+        }
+        let listener = UnixListener::bind(&path)?;
+        tracing::info!("Cron control socket listening on {}", path.display());
+
+        let connections = Arc::new(std::sync::Mutex::new(Vec::<JoinHandle<()>>::new()));
+        let tracked = connections.clone();
+        let accept = tokio::spawn(async move {
+            loop {
+                let stream = match listener.accept().await {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        tracing::warn!("Failed to accept control connection: {}", e);
+                        continue;
+                    }
+                };
+                let connection = tokio::spawn(serve_connection(manager.clone(), stream));
+                let mut connections = tracked.lock().unwrap();
+                connections.retain(|handle| !handle.is_finished());
+                connections.push(connection);
+            }
+        });
+
+        Ok(Self {
+            path,
+            accept,
+            connections,
+        })
+    }
+
+    /// Path of the socket
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Stop listening, disconnect every client and remove the socket file
+    pub fn shutdown(self) {
+        // Dropping the server does the work
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        self.accept.abort();
+        for connection in self.connections.lock().unwrap().drain(..) {
+            connection.abort();
+        }
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!(
+                    "Failed to remove control socket {}: {}",
+                    self.path.display(),
+                    e
+                );
+            }
+        }
+    }
+}
+
+/// Answer one client's requests until it disconnects, sending it events
+/// once it subscribes
+async fn serve_connection(manager: Arc<CronManager>, stream: UnixStream) {
+    let (read, mut write) = stream.into_split();
+    let mut lines = BufReader::new(read).lines();
+    let mut events: Option<broadcast::Receiver<EventEnvelope>> = None;
+    loop {
+        let message = tokio::select! {
+            line = lines.next_line() => {
+                let line = match line {
+                    Ok(Some(line)) => line,
+                    Ok(None) => break,
+                    Err(e) => {
+                        tracing::debug!("Control connection failed: {}", e);
+                        break;
+                    }
+                };
+                if line.trim().is_empty() {
+                    continue;
+                }
+                to_line(&handle_line(&manager, &line, &mut events).await)
+            }
+            Some(envelope) = next_event(&mut events) => to_line(&Notification {
+                jsonrpc: JSONRPC_VERSION.to_string(),
+                method: "event".to_string(),
+                params: serde_json::to_value(&envelope).unwrap_or_default(),
+            }),
+        };
+        if write.write_all(message.as_bytes()).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Wait for the next event of a subscribed client; never resolves for one
+/// that isn't. Events the client was too slow for are reported with an
+/// `events_dropped` event.
+async fn next_event(
+    events: &mut Option<broadcast::Receiver<EventEnvelope>>,
+) -> Option<EventEnvelope> {
+    let Some(rx) = events else {
+        return std::future::pending().await;
+    };
+    match rx.recv().await {
+        Ok(envelope) => Some(envelope),
+        Err(broadcast::error::RecvError::Lagged(count)) => {
+            Some(EventEnvelope::new(SchedulerEvent::EventsDropped {
+                count,
+                timestamp: Utc::now(),
+            }))
+        }
+        Err(broadcast::error::RecvError::Closed) => {
+            *events = None;
+            None
+        }
+    }
+}
+
+/// Answer one line of a client
+async fn handle_line(
+    manager: &Arc<CronManager>,
+    line: &str,
+    events: &mut Option<broadcast::Receiver<EventEnvelope>>,
+) -> Response {
+    let request = match serde_json::from_str::<Value>(line) {
+        Ok(value) => value,
+        Err(e) => return respond(Value::Null, Err(RpcError::new(ERROR_PARSE, e.to_string()))),
+    };
+    let request: Request = match serde_json::from_value(request) {
+        Ok(request) => request,
+        Err(e) => {
+            return respond(
+                Value::Null,
+                Err(RpcError::new(ERROR_INVALID_REQUEST, e.to_string())),
+            )
+        }
+    };
+    if request.jsonrpc != JSONRPC_VERSION {
+        return respond(
+            request.id,
+            Err(RpcError::new(
+                ERROR_INVALID_REQUEST,
+                format!("unsupported JSON-RPC version {}", request.jsonrpc),
+            )),
+        );
+    }
+    if !manager.is_running().await {
+        return respond(
+            request.id,
+            Err(RpcError::new(
+                ERROR_NOT_STARTED,
+                "the scheduler is not started",
+            )),
+        );
+    }
+    let result = if request.method == "subscribe" {
+        if events.is_none() {
+            *events = Some(manager.subscribe());
+        }
+        Ok(Value::Bool(true))
+    } else {
+        call(manager, &request.method, request.params).await
+    };
+    respond(request.id, result)
+}
+
+fn respond(id: Value, result: std::result::Result<Value, RpcError>) -> Response {
+    let (result, error) = match result {
+        Ok(result) => (Some(result), None),
+        Err(error) => (None, Some(error)),
+    };
+    Response {
+        jsonrpc: JSONRPC_VERSION.to_string(),
+        id,
+        result,
+        error,
+    }
+}
+
+/// Decode a method's params
+fn params<T: DeserializeOwned>(params: Value) -> std::result::Result<T, RpcError> {
+    serde_json::from_value(params).map_err(|e| RpcError::new(ERROR_INVALID_PARAMS, e.to_string()))
+}
+
+/// Run a method other than `subscribe`
+async fn call(
+    manager: &CronManager,
+    method: &str,
+    params_value: Value,
+) -> std::result::Result<Value, RpcError> {
+    let result = match method {
+        "list_jobs" => serde_json::to_value(manager.list_jobs().await?),
+        "add_job" => {
+            let declared: ManifestJob = params(params_value)?;
+            let job = manager
+                .add_job_with_options(
+                    &declared.name,
+                    &declared.schedule,
+                    &declared.command,
+                    declared.options(),
+                )
+                .await?;
+            serde_json::to_value(job)
+        }
+        "run_job" => {
+            let JobParams { id } = params(params_value)?;
+            serde_json::to_value(manager.run_job(&id).await?)
+        }
+        "pause" => {
+            let JobParams { id } = params(params_value)?;
+            serde_json::to_value(manager.pause_job(&id).await?)
+        }
+        "resume" => {
+            let JobParams { id } = params(params_value)?;
+            serde_json::to_value(manager.resume_job(&id).await?)
+        }
+        "history" => {
+            let HistoryParams { id, limit } = params(params_value)?;
+            if manager.get_job(&id).await?.is_none() {
+                return Err(CronError::JobNotFound(id).into());
+            }
+            let limit = limit.unwrap_or(DEFAULT_HISTORY_LIMIT);
+            serde_json::to_value(manager.get_history(&id, limit).await?)
+        }
+        _ => {
+            return Err(RpcError::new(
+                ERROR_METHOD_NOT_FOUND,
+                format!("unknown method {}", method),
+            ))
+        }
+    };
+    result.map_err(|e| CronError::from(e).into())
+}
+
+/// Client of a [`ControlServer`]. Requests are answered in order, one at a
+/// time.
+pub struct ControlClient {
+    lines: Lines<BufReader<OwnedReadHalf>>,
+    write: OwnedWriteHalf,
+    next_id: u64,
+}
+
+impl ControlClient {
+    /// Connect to the socket at `path`
+    pub async fn connect(path: impl AsRef<Path>) -> Result<Self> {
+        let (read, write) = UnixStream::connect(path).await?.into_split();
+        Ok(Self {
+            lines: BufReader::new(read).lines(),
+            write,
+            next_id: 1,
+        })
+    }
+
+    /// Connect to the control socket of the workspace at `workspace`
+    pub async fn connect_workspace(workspace: impl AsRef<Path>) -> Result<Self> {
+        Self::connect(socket_path(workspace)).await
+    }
+
+    /// Send a request and wait for its result. Failures reported by the
+    /// server are returned as [`CronError::Control`].
+    async fn call<T: DeserializeOwned>(&mut self, method: &str, params: Value) -> Result<T> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let request = Request {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id: Value::from(id),
+            method: method.to_string(),
+            params,
+        };
+        self.write.write_all(to_line(&request).as_bytes()).await?;
+
+        loop {
+            let line = next_line(&mut self.lines).await?;
+            let message: Value = serde_json::from_str(&line)?;
+            // Skip event notifications
+            if message.get("id").is_none() {
+                continue;
+            }
+            let response: Response = serde_json::from_value(message)?;
+            if response.id != id {
+                continue;
+            }
+            if let Some(error) = response.error {
+                return Err(CronError::Control {
+                    code: error.code,
+                    message: error.message,
+                });
+            }
+            return Ok(serde_json::from_value(response.result.unwrap_or_default())?);
+        }
+    }
+
+    /// List every job
+    pub async fn list_jobs(&mut self) -> Result<Vec<CronJob>> {
+        self.call("list_jobs", Value::Null).await
+    }
+
+    /// Add a job
+    pub async fn add_job(&mut self, job: &ManifestJob) -> Result<CronJob> {
+        self.call("add_job", serde_json::to_value(job)?).await
+    }
+
+    /// Run a job now, waiting for it to finish
+    pub async fn run_job(&mut self, id: &str) -> Result<JobExecution> {
+        self.call("run_job", job_params(id)?).await
+    }
+
+    /// Pause a job
+    pub async fn pause(&mut self, id: &str) -> Result<CronJob> {
+        self.call("pause", job_params(id)?).await
+    }
+
+    /// Resume a paused job
+    pub async fn resume(&mut self, id: &str) -> Result<CronJob> {
+        self.call("resume", job_params(id)?).await
+    }
+
+    /// Get up to `limit` of a job's executions, most recent first
+    pub async fn history(&mut self, id: &str, limit: usize) -> Result<Vec<JobExecution>> {
+        let params = HistoryParams {
+            id: id.to_string(),
+            limit: Some(limit),
+        };
+        self.call("history", serde_json::to_value(params)?).await
+    }
+
+    /// Turn the connection into a stream of the scheduler's events
+    pub async fn subscribe(mut self) -> Result<ControlEvents> {
+        self.call::<bool>("subscribe", Value::Null).await?;
+        Ok(ControlEvents {
+            lines: self.lines,
+            _write: self.write,
+        })
+    }
+}
+
+fn job_params(id: &str) -> Result<Value> {
+    Ok(serde_json::to_value(JobParams { id: id.to_string() })?)
+}
+
+/// Read the next line, failing if the server hung up
+async fn next_line(lines: &mut Lines<BufReader<OwnedReadHalf>>) -> Result<String> {
+    lines.next_line().await?.ok_or_else(|| {
+        CronError::Io(std::io::Error::new(
+            std::io::ErrorKind::UnexpectedEof,
+            "control socket closed",
+        ))
+    })
+}
+
+/// Events of a subscribed [`ControlClient`]
+pub struct ControlEvents {
+    lines: Lines<BufReader<OwnedReadHalf>>,
+    /// Kept so the server doesn't see the client hang up
+    _write: OwnedWriteHalf,
+}
+
+impl ControlEvents {
+    /// Wait for the next event; `None` once the server hangs up
+    pub async fn next(&mut self) -> Result<Option<EventEnvelope>> {
+        while let Some(line) = self.lines.next_line().await? {
+            let notification: Notification = match serde_json::from_str(&line) {
+                Ok(notification) => notification,
+                // A response to a request sent before subscribing
+                Err(_) => continue,
+            };
+            if notification.method == "event" {
+                return Ok(Some(serde_json::from_value(notification.params)?));
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::MemoryCronStore;
+    use crate::types::ExecutionStatus;
+    use std::time::Duration;
+
+    async fn create_test_server() -> (tempfile::TempDir, Arc<CronManager>, ControlServer) {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(MemoryCronStore::new());
+        let workspace = dir.path().to_string_lossy().to_string();
+        let manager = Arc::new(CronManager::with_store(store, workspace));
+        let server = ControlServer::bind(manager.clone()).await.unwrap();
+        (dir, manager, server)
+    }
+
+    #[tokio::test]
+    async fn test_requests() {
+        let (dir, manager, server) = create_test_server().await;
+        assert_eq!(server.path(), socket_path(dir.path()));
+        let mut client = ControlClient::connect_workspace(dir.path()).await.unwrap();
+
+        // Refused until the scheduler runs
+        match client.list_jobs().await {
+            Err(CronError::Control { code, .. }) => assert_eq!(code, ERROR_NOT_STARTED),
+            other => panic!("expected a refusal, got {:?}", other.map(|_| ())),
+        }
+        manager.start().await.unwrap();
+
+        let declared = ManifestJob::new("hello", "@never", "echo hello");
+        let job = client.add_job(&declared).await.unwrap();
+        assert_eq!(job.name, "hello");
+        match client.add_job(&declared).await {
+            Err(CronError::Control { code, message }) => {
+                assert_eq!(code, ERROR_REQUEST_FAILED);
+                assert!(message.contains("hello"));
+            }
+            other => panic!("expected a failure, got {:?}", other.map(|_| ())),
+        }
+        assert_eq!(client.list_jobs().await.unwrap().len(), 1);
+
+        let paused = client.pause(&job.id).await.unwrap();
+        assert_eq!(paused.status, crate::JobStatus::Paused);
+        let resumed = client.resume(&job.id).await.unwrap();
+        assert_eq!(resumed.status, crate::JobStatus::Active);
+
+        let execution = client.run_job(&job.id).await.unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Success);
+        let history = client.history(&job.id, 10).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].id, execution.id);
+        assert!(client.history("unknown", 10).await.is_err());
+
+        match client.call::<Value>("unknown", Value::Null).await {
+            Err(CronError::Control { code, .. }) => assert_eq!(code, ERROR_METHOD_NOT_FOUND),
+            other => panic!("expected a failure, got {:?}", other),
+        }
+        match client.call::<Value>("pause", Value::Null).await {
+            Err(CronError::Control { code, .. }) => assert_eq!(code, ERROR_INVALID_PARAMS),
+            other => panic!("expected a failure, got {:?}", other),
+        }
+
+        manager.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_clients_and_cleanup() {
+        let (dir, manager, server) = create_test_server().await;
+        manager.start().await.unwrap();
+        assert!(matches!(
+            ControlServer::bind(manager.clone()).await,
+            Err(CronError::InvalidConfig(_))
+        ));
+
+        let mut events = ControlClient::connect_workspace(dir.path())
+            .await
+            .unwrap()
+            .subscribe()
+            .await
+            .unwrap();
+        let mut client = ControlClient::connect_workspace(dir.path()).await.unwrap();
+        let job = client
+            .add_job(&ManifestJob::new("watched", "@never", "true"))
+            .await
+            .unwrap();
+
+        let event = tokio::time::timeout(Duration::from_secs(5), events.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert!(matches!(event.kind, SchedulerEvent::JobAdded { .. }));
+        assert_eq!(event.kind.job_id(), Some(job.id.as_str()));
+
+        let path = server.path().to_path_buf();
+        server.shutdown();
+        assert!(!path.exists());
+        let next = tokio::time::timeout(Duration::from_secs(5), events.next())
+            .await
+            .unwrap();
+        assert!(matches!(next, Ok(None) | Err(_)));
+        assert!(ControlClient::connect(&path).await.is_err());
+
+        // A socket file left behind is replaced
+        std::os::unix::net::UnixListener::bind(&path).unwrap();
+        let server = ControlServer::bind(manager.clone()).await.unwrap();
+        let mut client = ControlClient::connect(server.path()).await.unwrap();
+        assert_eq!(client.list_jobs().await.unwrap().len(), 1);
+
+        manager.stop().await;
+    }
+}
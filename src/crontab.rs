@@ -0,0 +1,483 @@
+//! Crontab file format
+//!
+//! Parses user crontab lines (`min hour day month weekday command`) so that
+//! existing crontabs can be migrated into the scheduler:
+//!
+//! ```text
+//! SHELL=/bin/bash
+//! # nightly backup
+//! 0 2 * * * /usr/local/bin/backup.sh --full
+//! @hourly curl -fsS https://example.com/ping
+//! ```
+
+use crate::parser::CronExpression;
+use crate::types::{CronError, CronJob, JobStatus, JobType, Result};
+
+/// A single parsed crontab line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CrontabLine {
+    /// A scheduled command
+    Job {
+        /// 5-field cron expression (macros are expanded)
+        schedule: String,
+        /// Command text, exactly as written after the schedule
+        command: String,
+    },
+    /// An environment variable assignment (`NAME=value`)
+    Env {
+        /// Variable name
+        name: String,
+        /// Variable value, with surrounding quotes removed
+        value: String,
+    },
+    /// A comment, without the leading `#`
+    Comment(String),
+    /// An empty line
+    Blank,
+}
+
+/// Expand a crontab `@macro` into a 5-field expression
+pub fn expand_macro(name: &str) -> Option<&'static str> {
+    match name.to_ascii_lowercase().as_str() {
+        "@yearly" | "@annually" => Some("0 0 1 1 *"),
+        "@monthly" => Some("0 0 1 * *"),
+        "@weekly" => Some("0 0 * * 0"),
+        "@daily" | "@midnight" => Some("0 0 * * *"),
+        "@hourly" => Some("0 * * * *"),
+        _ => None,
+    }
+}
+
+/// Parse a single crontab line
+///
+/// # Examples
+///
+/// ```
+/// use a3s_cron::crontab::{parse_line, CrontabLine};
+///
+/// let line = parse_line("*/5 * * * * echo hello").unwrap();
+/// assert_eq!(
+///     line,
+///     CrontabLine::Job {
+///         schedule: "*/5 * * * *".to_string(),
+///         command: "echo hello".to_string(),
+///     }
+/// );
+/// ```
+pub fn parse_line(line: &str) -> Result<CrontabLine> {
+    let trimmed = line.trim();
+
+    if trimmed.is_empty() {
+        return Ok(CrontabLine::Blank);
+    }
+
+    if let Some(comment) = trimmed.strip_prefix('#') {
+        return Ok(CrontabLine::Comment(comment.trim().to_string()));
+    }
+
+    if let Some(env) = parse_env(trimmed) {
+        return Ok(env);
+    }
+
+    if trimmed.starts_with('@') {
+        let (name, command) = split_fields(trimmed, 1);
+        let schedule = expand_macro(name[0]).ok_or_else(|| {
+            CronError::InvalidExpression(format!("Unsupported crontab macro '{}'", name[0]))
+        })?;
+        return job_line(schedule.to_string(), command);
+    }
+
+    let (fields, command) = split_fields(trimmed, 5);
+    if fields.len() < 5 {
+        return Err(CronError::InvalidExpression(format!(
+            "Expected 5 schedule fields followed by a command in '{}'",
+            trimmed
+        )));
+    }
+    let expr = CronExpression::parse(&fields.join(" "))?;
+    job_line(expr.expression, command)
+}
+
+/// Build a job line, rejecting an empty command
+fn job_line(schedule: String, command: &str) -> Result<CrontabLine> {
+    if command.is_empty() {
+        return Err(CronError::InvalidExpression(format!(
+            "Missing command after schedule '{}'",
+            schedule
+        )));
+    }
+    Ok(CrontabLine::Job {
+        schedule,
+        command: command.to_string(),
+    })
+}
+
+/// Split off the first `count` whitespace-separated fields, returning them
+/// and the untouched remainder of the line
+fn split_fields(line: &str, count: usize) -> (Vec<&str>, &str) {
+    let mut fields = Vec::with_capacity(count);
+    let mut rest = line;
+    while fields.len() < count {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            break;
+        }
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        fields.push(&rest[..end]);
+        rest = &rest[end..];
+    }
+    (fields, rest.trim())
+}
+
+/// Parse a `NAME=value` environment assignment
+fn parse_env(line: &str) -> Option<CrontabLine> {
+    let (name, value) = line.split_once('=')?;
+    let name = name.trim();
+    let valid_name = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid_name {
+        return None;
+    }
+
+    let value = value.trim();
+    let value = ['"', '\'']
+        .iter()
+        .find_map(|q| value.strip_prefix(*q).and_then(|v| v.strip_suffix(*q)))
+        .unwrap_or(value);
+
+    Some(CrontabLine::Env {
+        name: name.to_string(),
+        value: value.to_string(),
+    })
+}
+
+/// A job definition read from a crontab document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrontabJob {
+    /// Job name, from the preceding comment or derived from the command
+    pub name: String,
+    /// 5-field cron expression
+    pub schedule: String,
+    /// Shell command
+    pub command: String,
+    /// Environment assignments in effect for this line
+    pub env: Vec<(String, String)>,
+}
+
+/// Parse a whole crontab document into job definitions
+///
+/// Each job is named after the comment on the line directly above it (a
+/// `name:` prefix is stripped), or `job-<hash of command>` otherwise.
+/// Environment assignments apply to every job that follows them; an empty
+/// assignment (`NAME=`) clears the variable again. Names are not
+/// deduplicated here. Errors report the 1-based line number.
+pub fn parse_crontab(content: &str) -> Result<Vec<CrontabJob>> {
+    let mut jobs = Vec::new();
+    let mut env: Vec<(String, String)> = Vec::new();
+    let mut pending_name: Option<String> = None;
+
+    for (idx, line) in content.lines().enumerate() {
+        let parsed = parse_line(line).map_err(|e| {
+            CronError::InvalidExpression(format!("line {}: {}", idx + 1, error_message(e)))
+        })?;
+
+        match parsed {
+            CrontabLine::Blank => pending_name = None,
+            CrontabLine::Comment(text) => {
+                let text = text.strip_prefix("name:").unwrap_or(&text).trim();
+                pending_name = (!text.is_empty()).then(|| text.to_string());
+            }
+            CrontabLine::Env { name, value } => {
+                env.retain(|(k, _)| *k != name);
+                if !value.is_empty() {
+                    env.push((name, value));
+                }
+                pending_name = None;
+            }
+            CrontabLine::Job { schedule, command } => {
+                let name = pending_name
+                    .take()
+                    .unwrap_or_else(|| format!("job-{:08x}", fnv1a(&command) as u32));
+                jobs.push(CrontabJob {
+                    name,
+                    schedule,
+                    command,
+                    env: env.clone(),
+                });
+            }
+        }
+    }
+
+    Ok(jobs)
+}
+
+/// Render jobs as a crontab document
+///
+/// Each job becomes a block headed by a `# name: <job name>` comment, which
+/// [`parse_crontab`] reads back as the job name. Job environment variables are
+/// assigned before the job and cleared after it so they do not leak into
+/// later lines. Jobs that cron cannot run (agent, one-shot, trigger-only,
+/// paused and completed jobs, multi-line commands, and schedules using this
+/// crate's `L`, `W` or `&` extensions) are emitted as commented-out lines so
+/// nothing is silently lost.
+pub fn render_crontab(jobs: &[CronJob]) -> String {
+    let mut out = String::from("# Exported by a3s-cron\n");
+
+    for job in jobs {
+        out.push('\n');
+
+        let schedule = crontab_schedule(&job.schedule);
+        let skip_reason = if job.job_type != JobType::Shell {
+            Some(format!("{} job", job.job_type))
+        } else if job.is_one_shot() {
+            Some("one-shot".to_string())
+        } else if job.is_trigger_only() {
+            Some("trigger-only".to_string())
+        } else if job.status == JobStatus::Paused {
+            Some("paused".to_string())
+        } else if job.status == JobStatus::Completed {
+            Some("completed".to_string())
+        } else if job.status == JobStatus::Disabled {
+            Some("disabled".to_string())
+        } else if job.command.contains('\n') {
+            Some("multi-line command".to_string())
+        } else {
+            schedule.as_ref().err().cloned()
+        };
+
+        if let Some(reason) = skip_reason {
+            out.push_str(&format!("# name: {} ({})\n", job.name, reason));
+            out.push_str(&format!(
+                "# {} {}\n",
+                job.schedule,
+                job.command.replace('\n', " ")
+            ));
+            continue;
+        }
+
+        for (key, value) in &job.env {
+            out.push_str(&format!("{}={}\n", key, quote_env_value(value)));
+        }
+        out.push_str(&format!("# name: {}\n", job.name));
+        let schedule = schedule.as_deref().unwrap_or(&job.schedule);
+        out.push_str(&format!("{} {}\n", schedule, job.command));
+        for (key, _) in &job.env {
+            out.push_str(&format!("{}=\n", key));
+        }
+    }
+
+    out
+}
+
+/// Write a schedule the way cron reads it, or say why it can't be.
+///
+/// `?` becomes `*`, and the `&` both-days prefix is dropped when one of the
+/// day fields is unrestricted, as both mean the same to cron. `L`, `W` and
+/// `&` with both day fields restricted have no crontab equivalent.
+fn crontab_schedule(schedule: &str) -> std::result::Result<String, String> {
+    let expr = CronExpression::parse(schedule)
+        .map_err(|e| format!("invalid schedule: {}", error_message(e)))?;
+    if let Some(month_day) = expr.month_days().first() {
+        return Err(format!("schedule uses {}", month_day));
+    }
+    let mut fields: Vec<String> = expr
+        .expression
+        .split_whitespace()
+        .map(|field| if field == "?" { "*" } else { field }.to_string())
+        .collect();
+    if let Some(weekdays) = fields[4].strip_prefix('&') {
+        let weekdays = if weekdays == "?" { "*" } else { weekdays };
+        if !fields[2].starts_with('*') && !weekdays.starts_with('*') {
+            return Err("schedule requires both day fields to match".to_string());
+        }
+        fields[4] = weekdays.to_string();
+    }
+    Ok(fields.join(" "))
+}
+
+/// Quote an environment value if it has surrounding whitespace or quotes
+fn quote_env_value(value: &str) -> String {
+    if value.trim() != value || value.starts_with(['"', '\'']) {
+        format!("\"{}\"", value)
+    } else {
+        value.to_string()
+    }
+}
+
+/// Inner message of an expression error, without the variant prefix
+fn error_message(err: CronError) -> String {
+    match err {
+        CronError::InvalidExpression(msg) => msg,
+        other => other.to_string(),
+    }
+}
+
+/// Stable FNV-1a hash used to derive job names
+fn fnv1a(s: &str) -> u64 {
+    s.bytes().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_parse_job_line() {
+        let line = parse_line("0 2 * * 1-5   /usr/bin/backup.sh  --full > /dev/null").unwrap();
+        assert_eq!(
+            line,
+            CrontabLine::Job {
+                schedule: "0 2 * * 1-5".to_string(),
+                command: "/usr/bin/backup.sh  --full > /dev/null".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_macro_line() {
+        let line = parse_line("@daily echo hi").unwrap();
+        assert_eq!(
+            line,
+            CrontabLine::Job {
+                schedule: "0 0 * * *".to_string(),
+                command: "echo hi".to_string(),
+            }
+        );
+        assert!(parse_line("@reboot echo hi").is_err());
+        assert!(parse_line("@hourly").is_err());
+    }
+
+    #[test]
+    fn test_parse_env_line() {
+        assert_eq!(
+            parse_line("SHELL=/bin/bash").unwrap(),
+            CrontabLine::Env {
+                name: "SHELL".to_string(),
+                value: "/bin/bash".to_string(),
+            }
+        );
+        assert_eq!(
+            parse_line("MAILTO = \"ops@example.com\"").unwrap(),
+            CrontabLine::Env {
+                name: "MAILTO".to_string(),
+                value: "ops@example.com".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_comment_and_blank() {
+        assert_eq!(
+            parse_line("  # nightly backup").unwrap(),
+            CrontabLine::Comment("nightly backup".to_string())
+        );
+        assert_eq!(parse_line("   ").unwrap(), CrontabLine::Blank);
+    }
+
+    #[test]
+    fn test_parse_invalid_lines() {
+        assert!(parse_line("0 2 * *").is_err());
+        assert!(parse_line("0 2 * * *").is_err());
+        assert!(parse_line("61 2 * * * echo").is_err());
+    }
+
+    #[test]
+    fn test_parse_crontab_document() {
+        let content = "\
+SHELL=/bin/bash
+# name: backup
+0 2 * * * backup.sh
+
+*/5 * * * * ping.sh
+PATH=/usr/bin
+# report
+@weekly report.sh
+";
+        let jobs = parse_crontab(content).unwrap();
+        assert_eq!(jobs.len(), 3);
+
+        assert_eq!(jobs[0].name, "backup");
+        assert_eq!(jobs[0].env, vec![("SHELL".into(), "/bin/bash".into())]);
+
+        assert!(jobs[1].name.starts_with("job-"));
+        assert_eq!(
+            jobs[1].name,
+            parse_crontab("* * * * * ping.sh").unwrap()[0].name
+        );
+
+        assert_eq!(jobs[2].name, "report");
+        assert_eq!(jobs[2].schedule, "0 0 * * 0");
+        assert_eq!(jobs[2].env.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_crontab_reports_line_number() {
+        let err = parse_crontab("0 2 * * * ok.sh\nbad line here\n").unwrap_err();
+        assert!(err.to_string().contains("line 2"));
+    }
+
+    #[test]
+    fn test_empty_assignment_clears_env() {
+        let jobs = parse_crontab("FOO=bar\n* * * * * a\nFOO=\n* * * * * b\n").unwrap();
+        assert_eq!(jobs[0].env, vec![("FOO".into(), "bar".into())]);
+        assert!(jobs[1].env.is_empty());
+    }
+
+    #[test]
+    fn test_render_crontab() {
+        let active = CronJob::new("backup", "0 2 * * *", "backup.sh").with_env("DEST", "/mnt");
+        let mut paused = CronJob::new("cleanup", "0 3 * * *", "cleanup.sh");
+        paused.status = JobStatus::Paused;
+        let mut agent = CronJob::new("review", "0 9 * * 1-5", "Review open PRs");
+        agent.job_type = JobType::Agent;
+
+        let once = CronJob::one_shot("once", Utc::now(), "deploy.sh");
+
+        let out = render_crontab(&[active, paused, agent, once]);
+        assert!(out.contains("DEST=/mnt\n# name: backup\n0 2 * * * backup.sh\nDEST=\n"));
+        assert!(out.contains("# name: cleanup (paused)\n# 0 3 * * * cleanup.sh\n"));
+        assert!(out.contains("# name: review (agent job)\n# 0 9 * * 1-5 Review open PRs\n"));
+        assert!(out.contains("# name: once (one-shot)\n# @at "));
+
+        // Only the active shell job is read back
+        let jobs = parse_crontab(&out).unwrap();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].name, "backup");
+        assert_eq!(jobs[0].env, vec![("DEST".into(), "/mnt".into())]);
+    }
+
+    #[test]
+    fn test_render_crontab_extensions() {
+        let jobs = [
+            CronJob::new("last-day", "0 0 L * *", "a.sh"),
+            CronJob::new("weekday", "0 0 15W * *", "b.sh"),
+            CronJob::new("friday-13th", "0 0 13 * &5", "c.sh"),
+            CronJob::new("fridays", "0 0 * * &5", "d.sh"),
+            CronJob::new("quartz", "0 12 ? * MON-FRI", "e.sh"),
+            CronJob::new("spaced", "0\t12  ? * mon-fri", "f.sh"),
+        ];
+        let out = render_crontab(&jobs);
+        assert!(out.contains("# name: last-day (schedule uses the last day of the month)\n"));
+        assert!(out.contains("# 0 0 L * * a.sh\n"));
+        assert!(out.contains("# name: weekday (schedule uses "));
+        assert!(out.contains(
+            "# name: friday-13th (schedule requires both day fields to match)\n# 0 0 13 * &5 c.sh\n"
+        ));
+        assert!(out.contains("# name: fridays\n0 0 * * 5 d.sh\n"));
+        assert!(out.contains("# name: quartz\n0 12 * * MON-FRI e.sh\n"));
+        assert!(out.contains("# name: spaced\n0 12 * * mon-fri f.sh\n"));
+
+        // Every line written for cron parses as a standard expression
+        let jobs = parse_crontab(&out).unwrap();
+        assert_eq!(jobs.len(), 3);
+        for job in jobs {
+            assert!(!job.schedule.contains(['&', '?', 'L', 'W']));
+        }
+    }
+}
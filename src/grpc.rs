@@ -0,0 +1,1159 @@
+//! gRPC service for remote job management
+//!
+//! [`CronGrpcService`] serves a [`CronManager`] over the `a3s.cron.v1.Cron`
+//! service defined in `proto/cron.proto`, for platforms that talk gRPC
+//! rather than HTTP. The generated messages, server and client live in
+//! [`proto`]; jobs, executions and job declarations convert to and from
+//! them without loss:
+//!
+//! ```ignore
+//! let service = CronGrpcService::new(manager).into_server();
+//! tonic::transport::Server::builder()
+//!     .add_service(service)
+//!     .serve("127.0.0.1:50051".parse()?)
+//!     .await?;
+//! ```
+//!
+//! `Watch` streams the scheduler's events. A stream that falls behind gets
+//! a `Lagged` message counting the events it missed, then carries on.
+
+use crate::scheduler::{CronManager, EventEnvelope};
+use crate::types::{
+    AgentJobConfig, BlackoutPolicy, CommandKind, ConcurrencyPolicy, CronError, CronJob,
+    DurationStats, EnvMode, ExecutionStatus, JobExecution, JobHealth, JobStatus, JobType,
+    ManifestJob, MisfirePolicy, OutputFiles, ResourceLimits, Result, ScheduleInfo, ScheduleKind,
+    Termination, TriggerKind,
+};
+use crate::window::TimeWindow;
+use chrono::{DateTime, NaiveTime, Timelike, Utc, Weekday};
+use futures_util::Stream;
+use prost_types::Timestamp;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tonic::{Request, Response, Status};
+
+/// Messages, server and client generated from `proto/cron.proto`
+#[allow(missing_docs, clippy::all)]
+pub mod proto {
+    tonic::include_proto!("a3s.cron.v1");
+}
+
+use proto::cron_server::{Cron, CronServer};
+
+/// Executions returned by `GetHistory` when the request sets no limit
+pub const DEFAULT_HISTORY_LIMIT: usize = 20;
+
+/// Serves a manager's jobs over gRPC
+pub struct CronGrpcService {
+    manager: Arc<CronManager>,
+}
+
+impl CronGrpcService {
+    /// Create a service managing `manager`'s jobs
+    pub fn new(manager: Arc<CronManager>) -> Self {
+        Self { manager }
+    }
+
+    /// Wrap the service for a tonic server
+    pub fn into_server(self) -> CronServer<Self> {
+        CronServer::new(self)
+    }
+}
+
+/// Status reporting `error`
+fn status(error: CronError) -> Status {
+    let message = error.to_string();
+    match error {
+        CronError::JobNotFound(_)
+        | CronError::ExecutionNotFound(_)
+        | CronError::WorkspaceNotFound(_) => Status::not_found(message),
+        CronError::JobExists(_) => Status::already_exists(message),
+        CronError::JobCompleted(_)
+        | CronError::JobDisabled(_)
+        | CronError::JobPaused(_)
+        | CronError::InBlackout(_)
+        | CronError::ExecutionNotRunning(_)
+        | CronError::AlreadyRunning => Status::failed_precondition(message),
+        CronError::InvalidExpression(_)
+        | CronError::ScheduleTooFrequent { .. }
+        | CronError::TriggerCycle(_)
+        | CronError::InvalidConfig(_)
+        | CronError::Template(_) => Status::invalid_argument(message),
+        CronError::Timeout(_) => Status::deadline_exceeded(message),
+        _ => Status::internal(message),
+    }
+}
+
+type RpcResult<T> = std::result::Result<Response<T>, Status>;
+
+#[tonic::async_trait]
+impl Cron for CronGrpcService {
+    async fn list_jobs(
+        &self,
+        _request: Request<proto::ListJobsRequest>,
+    ) -> RpcResult<proto::ListJobsResponse> {
+        let jobs = self.manager.list_jobs().await.map_err(status)?;
+        Ok(Response::new(proto::ListJobsResponse {
+            jobs: jobs.iter().map(proto::Job::from).collect(),
+        }))
+    }
+
+    async fn get_job(&self, request: Request<proto::JobRequest>) -> RpcResult<proto::Job> {
+        let id = request.into_inner().id;
+        let job = self
+            .manager
+            .get_job(&id)
+            .await
+            .map_err(status)?
+            .ok_or_else(|| status(CronError::JobNotFound(id)))?;
+        Ok(Response::new(proto::Job::from(&job)))
+    }
+
+    async fn add_job(&self, request: Request<proto::AddJobRequest>) -> RpcResult<proto::Job> {
+        let declared = ManifestJob::try_from(request.into_inner()).map_err(status)?;
+        let job = self
+            .manager
+            .add_job_with_options(
+                &declared.name,
+                &declared.schedule,
+                &declared.command,
+                declared.options(),
+            )
+            .await
+            .map_err(status)?;
+        Ok(Response::new(proto::Job::from(&job)))
+    }
+
+    async fn remove_job(
+        &self,
+        request: Request<proto::RemoveJobRequest>,
+    ) -> RpcResult<proto::RemoveJobResponse> {
+        let request = request.into_inner();
+        self.manager
+            .remove_job(&request.id, request.purge_history)
+            .await
+            .map_err(status)?;
+        Ok(Response::new(proto::RemoveJobResponse {}))
+    }
+
+    async fn pause_job(&self, request: Request<proto::JobRequest>) -> RpcResult<proto::Job> {
+        let job = self
+            .manager
+            .pause_job(&request.into_inner().id)
+            .await
+            .map_err(status)?;
+        Ok(Response::new(proto::Job::from(&job)))
+    }
+
+    async fn resume_job(&self, request: Request<proto::JobRequest>) -> RpcResult<proto::Job> {
+        let job = self
+            .manager
+            .resume_job(&request.into_inner().id)
+            .await
+            .map_err(status)?;
+        Ok(Response::new(proto::Job::from(&job)))
+    }
+
+    async fn run_job(
+        &self,
+        request: Request<proto::RunJobRequest>,
+    ) -> RpcResult<proto::RunJobResponse> {
+        let request = request.into_inner();
+        let result = if request.detach {
+            let execution_id = self
+                .manager
+                .run_job_detached(&request.id)
+                .await
+                .map_err(status)?;
+            proto::run_job_response::Result::ExecutionId(execution_id)
+        } else {
+            let execution = self.manager.run_job(&request.id).await.map_err(status)?;
+            proto::run_job_response::Result::Execution(proto::Execution::from(&execution))
+        };
+        Ok(Response::new(proto::RunJobResponse {
+            result: Some(result),
+        }))
+    }
+
+    async fn get_history(
+        &self,
+        request: Request<proto::HistoryRequest>,
+    ) -> RpcResult<proto::HistoryResponse> {
+        let request = request.into_inner();
+        if self
+            .manager
+            .get_job(&request.id)
+            .await
+            .map_err(status)?
+            .is_none()
+        {
+            return Err(status(CronError::JobNotFound(request.id)));
+        }
+        let limit = match request.limit {
+            0 => DEFAULT_HISTORY_LIMIT,
+            limit => limit as usize,
+        };
+        let executions = self
+            .manager
+            .get_history(&request.id, limit)
+            .await
+            .map_err(status)?;
+        Ok(Response::new(proto::HistoryResponse {
+            executions: executions.iter().map(proto::Execution::from).collect(),
+        }))
+    }
+
+    async fn get_execution(
+        &self,
+        request: Request<proto::ExecutionRequest>,
+    ) -> RpcResult<proto::Execution> {
+        let id = request.into_inner().id;
+        let execution = self
+            .manager
+            .get_execution(&id)
+            .await
+            .map_err(status)?
+            .ok_or_else(|| status(CronError::ExecutionNotFound(id)))?;
+        Ok(Response::new(proto::Execution::from(&execution)))
+    }
+
+    type WatchStream =
+        Pin<Box<dyn Stream<Item = std::result::Result<proto::WatchResponse, Status>> + Send>>;
+
+    async fn watch(&self, _request: Request<proto::WatchRequest>) -> RpcResult<Self::WatchStream> {
+        let stream = futures_util::stream::unfold(self.manager.subscribe(), |mut rx| async move {
+            let kind = match rx.recv().await {
+                Ok(envelope) => proto::watch_response::Kind::Event(proto::Event::from(&envelope)),
+                Err(broadcast::error::RecvError::Lagged(count)) => {
+                    proto::watch_response::Kind::Lagged(proto::Lagged {
+                        count,
+                        at: Some(timestamp(Utc::now())),
+                    })
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            };
+            Some((Ok(proto::WatchResponse { kind: Some(kind) }), rx))
+        });
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+// ============================================================================
+// Conversions
+// ============================================================================
+
+/// Error for a message that doesn't describe a valid value
+fn invalid(what: &str) -> CronError {
+    CronError::InvalidConfig(format!("invalid {} in gRPC message", what))
+}
+
+fn timestamp(at: DateTime<Utc>) -> Timestamp {
+    Timestamp {
+        seconds: at.timestamp(),
+        nanos: at.timestamp_subsec_nanos() as i32,
+    }
+}
+
+fn from_timestamp(at: Timestamp, what: &str) -> Result<DateTime<Utc>> {
+    u32::try_from(at.nanos)
+        .ok()
+        .and_then(|nanos| DateTime::from_timestamp(at.seconds, nanos))
+        .ok_or_else(|| invalid(what))
+}
+
+fn required_timestamp(at: Option<Timestamp>, what: &str) -> Result<DateTime<Utc>> {
+    from_timestamp(at.ok_or_else(|| invalid(what))?, what)
+}
+
+fn optional_timestamp(at: Option<Timestamp>, what: &str) -> Result<Option<DateTime<Utc>>> {
+    at.map(|at| from_timestamp(at, what)).transpose()
+}
+
+/// Decode an enum field, rejecting values this version doesn't know
+fn decode<E: TryFrom<i32>>(value: i32, what: &str) -> Result<E> {
+    E::try_from(value).map_err(|_| invalid(what))
+}
+
+/// Map a plain enum to its proto counterpart and back, variant by variant
+macro_rules! enum_conversions {
+    ($crate_type:ident <=> $proto_type:ident { $($variant:ident),+ $(,)? }) => {
+        impl From<$crate_type> for proto::$proto_type {
+            fn from(value: $crate_type) -> Self {
+                match value {
+                    $($crate_type::$variant => proto::$proto_type::$variant,)+
+                }
+            }
+        }
+
+        impl From<proto::$proto_type> for $crate_type {
+            fn from(value: proto::$proto_type) -> Self {
+                match value {
+                    $(proto::$proto_type::$variant => $crate_type::$variant,)+
+                }
+            }
+        }
+    };
+}
+
+enum_conversions!(CommandKind <=> CommandKind { Shell, Exec });
+enum_conversions!(JobType <=> JobType { Shell, Agent, Native });
+enum_conversions!(JobStatus <=> JobStatus {
+    Active,
+    Paused,
+    Running,
+    Completed,
+    Disabled,
+});
+enum_conversions!(BlackoutPolicy <=> BlackoutPolicy { Skip, Defer });
+enum_conversions!(JobHealth <=> JobHealth {
+    Healthy,
+    Degraded,
+    Failing,
+    Stale,
+});
+enum_conversions!(ExecutionStatus <=> ExecutionStatus {
+    Success,
+    Failed,
+    Timeout,
+    Cancelled,
+    Skipped,
+});
+enum_conversions!(Termination <=> Termination { Graceful, Forced });
+
+impl From<Weekday> for proto::Weekday {
+    fn from(day: Weekday) -> Self {
+        match day {
+            Weekday::Mon => proto::Weekday::Monday,
+            Weekday::Tue => proto::Weekday::Tuesday,
+            Weekday::Wed => proto::Weekday::Wednesday,
+            Weekday::Thu => proto::Weekday::Thursday,
+            Weekday::Fri => proto::Weekday::Friday,
+            Weekday::Sat => proto::Weekday::Saturday,
+            Weekday::Sun => proto::Weekday::Sunday,
+        }
+    }
+}
+
+impl From<proto::Weekday> for Weekday {
+    fn from(day: proto::Weekday) -> Self {
+        match day {
+            proto::Weekday::Monday => Weekday::Mon,
+            proto::Weekday::Tuesday => Weekday::Tue,
+            proto::Weekday::Wednesday => Weekday::Wed,
+            proto::Weekday::Thursday => Weekday::Thu,
+            proto::Weekday::Friday => Weekday::Fri,
+            proto::Weekday::Saturday => Weekday::Sat,
+            proto::Weekday::Sunday => Weekday::Sun,
+        }
+    }
+}
+
+impl From<&AgentJobConfig> for proto::AgentJobConfig {
+    fn from(config: &AgentJobConfig) -> Self {
+        Self {
+            model: config.model.clone(),
+            api_key: config.api_key.clone(),
+            workspace: config.workspace.clone(),
+            system_prompt: config.system_prompt.clone(),
+            base_url: config.base_url.clone(),
+        }
+    }
+}
+
+impl From<proto::AgentJobConfig> for AgentJobConfig {
+    fn from(config: proto::AgentJobConfig) -> Self {
+        Self {
+            model: config.model,
+            api_key: config.api_key,
+            workspace: config.workspace,
+            system_prompt: config.system_prompt,
+            base_url: config.base_url,
+        }
+    }
+}
+
+impl From<&EnvMode> for proto::EnvMode {
+    fn from(mode: &EnvMode) -> Self {
+        use proto::env_mode::Kind;
+        let (kind, allow) = match mode {
+            EnvMode::Inherit => (Kind::Inherit, Vec::new()),
+            EnvMode::Clean => (Kind::Clean, Vec::new()),
+            EnvMode::InheritAllow(allow) => (Kind::InheritAllow, allow.clone()),
+        };
+        Self {
+            kind: kind.into(),
+            allow,
+        }
+    }
+}
+
+impl TryFrom<proto::EnvMode> for EnvMode {
+    type Error = CronError;
+
+    fn try_from(mode: proto::EnvMode) -> Result<Self> {
+        use proto::env_mode::Kind;
+        Ok(match decode(mode.kind, "environment mode")? {
+            Kind::Inherit => EnvMode::Inherit,
+            Kind::Clean => EnvMode::Clean,
+            Kind::InheritAllow => EnvMode::InheritAllow(mode.allow),
+        })
+    }
+}
+
+impl From<&ScheduleKind> for proto::ScheduleKind {
+    fn from(kind: &ScheduleKind) -> Self {
+        use proto::schedule_kind::Kind;
+        let (kind, at) = match kind {
+            ScheduleKind::Cron => (Kind::Cron, None),
+            ScheduleKind::At(at) => (Kind::At, Some(timestamp(*at))),
+            ScheduleKind::Never => (Kind::Never, None),
+        };
+        Self {
+            kind: kind.into(),
+            at,
+        }
+    }
+}
+
+impl TryFrom<proto::ScheduleKind> for ScheduleKind {
+    type Error = CronError;
+
+    fn try_from(kind: proto::ScheduleKind) -> Result<Self> {
+        use proto::schedule_kind::Kind;
+        Ok(match decode(kind.kind, "schedule kind")? {
+            Kind::Cron => ScheduleKind::Cron,
+            Kind::At => ScheduleKind::At(required_timestamp(kind.at, "one-shot time")?),
+            Kind::Never => ScheduleKind::Never,
+        })
+    }
+}
+
+impl From<&MisfirePolicy> for proto::MisfirePolicy {
+    fn from(policy: &MisfirePolicy) -> Self {
+        use proto::misfire_policy::Kind;
+        let (kind, max) = match policy {
+            MisfirePolicy::FireOnce => (Kind::FireOnce, 0),
+            MisfirePolicy::Skip => (Kind::Skip, 0),
+            MisfirePolicy::CatchUp { max } => (Kind::CatchUp, *max),
+        };
+        Self {
+            kind: kind.into(),
+            max,
+        }
+    }
+}
+
+impl TryFrom<proto::MisfirePolicy> for MisfirePolicy {
+    type Error = CronError;
+
+    fn try_from(policy: proto::MisfirePolicy) -> Result<Self> {
+        use proto::misfire_policy::Kind;
+        Ok(match decode(policy.kind, "misfire policy")? {
+            Kind::FireOnce => MisfirePolicy::FireOnce,
+            Kind::Skip => MisfirePolicy::Skip,
+            Kind::CatchUp => MisfirePolicy::CatchUp { max: policy.max },
+        })
+    }
+}
+
+impl From<&ConcurrencyPolicy> for proto::ConcurrencyPolicy {
+    fn from(policy: &ConcurrencyPolicy) -> Self {
+        use proto::concurrency_policy::Kind;
+        let (kind, max_queue) = match policy {
+            ConcurrencyPolicy::Allow => (Kind::Allow, 0),
+            ConcurrencyPolicy::Forbid => (Kind::Forbid, 0),
+            ConcurrencyPolicy::Replace => (Kind::Replace, 0),
+            ConcurrencyPolicy::Queue { max_queue } => (Kind::Queue, *max_queue as u64),
+        };
+        Self {
+            kind: kind.into(),
+            max_queue,
+        }
+    }
+}
+
+impl TryFrom<proto::ConcurrencyPolicy> for ConcurrencyPolicy {
+    type Error = CronError;
+
+    fn try_from(policy: proto::ConcurrencyPolicy) -> Result<Self> {
+        use proto::concurrency_policy::Kind;
+        Ok(match decode(policy.kind, "concurrency policy")? {
+            Kind::Allow => ConcurrencyPolicy::Allow,
+            Kind::Forbid => ConcurrencyPolicy::Forbid,
+            Kind::Replace => ConcurrencyPolicy::Replace,
+            Kind::Queue => ConcurrencyPolicy::Queue {
+                max_queue: usize::try_from(policy.max_queue)
+                    .map_err(|_| invalid("queue length"))?,
+            },
+        })
+    }
+}
+
+impl From<&TriggerKind> for proto::TriggerKind {
+    fn from(trigger: &TriggerKind) -> Self {
+        use proto::trigger_kind::Kind;
+        let mut message = Self::default();
+        let kind = match trigger {
+            TriggerKind::Scheduled => Kind::Scheduled,
+            TriggerKind::Manual => Kind::Manual,
+            TriggerKind::Retry { attempt } => {
+                message.attempt = *attempt;
+                Kind::Retry
+            }
+            TriggerKind::Dependency {
+                parent_execution_id,
+            } => {
+                message.parent_execution_id = parent_execution_id.clone();
+                Kind::Dependency
+            }
+            TriggerKind::CatchUp => Kind::CatchUp,
+            TriggerKind::OnCreate => Kind::OnCreate,
+        };
+        message.kind = kind.into();
+        message
+    }
+}
+
+impl TryFrom<proto::TriggerKind> for TriggerKind {
+    type Error = CronError;
+
+    fn try_from(trigger: proto::TriggerKind) -> Result<Self> {
+        use proto::trigger_kind::Kind;
+        Ok(match decode(trigger.kind, "trigger")? {
+            Kind::Scheduled => TriggerKind::Scheduled,
+            Kind::Manual => TriggerKind::Manual,
+            Kind::Retry => TriggerKind::Retry {
+                attempt: trigger.attempt,
+            },
+            Kind::Dependency => TriggerKind::Dependency {
+                parent_execution_id: trigger.parent_execution_id,
+            },
+            Kind::CatchUp => TriggerKind::CatchUp,
+            Kind::OnCreate => TriggerKind::OnCreate,
+        })
+    }
+}
+
+impl From<&ResourceLimits> for proto::ResourceLimits {
+    fn from(limits: &ResourceLimits) -> Self {
+        Self {
+            nice: limits.nice,
+            max_memory_bytes: limits.max_memory_bytes,
+            max_file_size_bytes: limits.max_file_size_bytes,
+            max_open_files: limits.max_open_files,
+        }
+    }
+}
+
+impl From<proto::ResourceLimits> for ResourceLimits {
+    fn from(limits: proto::ResourceLimits) -> Self {
+        Self {
+            nice: limits.nice,
+            max_memory_bytes: limits.max_memory_bytes,
+            max_file_size_bytes: limits.max_file_size_bytes,
+            max_open_files: limits.max_open_files,
+        }
+    }
+}
+
+impl From<NaiveTime> for proto::TimeOfDay {
+    fn from(time: NaiveTime) -> Self {
+        Self {
+            seconds_from_midnight: time.num_seconds_from_midnight(),
+            nanos: time.nanosecond(),
+        }
+    }
+}
+
+impl TryFrom<proto::TimeOfDay> for NaiveTime {
+    type Error = CronError;
+
+    fn try_from(time: proto::TimeOfDay) -> Result<Self> {
+        NaiveTime::from_num_seconds_from_midnight_opt(time.seconds_from_midnight, time.nanos)
+            .ok_or_else(|| invalid("time of day"))
+    }
+}
+
+impl From<&TimeWindow> for proto::TimeWindow {
+    fn from(window: &TimeWindow) -> Self {
+        Self {
+            start: Some(window.start.into()),
+            end: Some(window.end.into()),
+            days: window
+                .days
+                .iter()
+                .map(|day| proto::Weekday::from(*day).into())
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<proto::TimeWindow> for TimeWindow {
+    type Error = CronError;
+
+    fn try_from(window: proto::TimeWindow) -> Result<Self> {
+        let time = |time: Option<proto::TimeOfDay>| -> Result<NaiveTime> {
+            time.ok_or_else(|| invalid("time of day"))?.try_into()
+        };
+        Ok(Self {
+            start: time(window.start)?,
+            end: time(window.end)?,
+            days: window
+                .days
+                .into_iter()
+                .map(|day| decode::<proto::Weekday>(day, "weekday").map(Weekday::from))
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+impl From<&DurationStats> for proto::DurationStats {
+    fn from(stats: &DurationStats) -> Self {
+        Self {
+            count: stats.count,
+            mean_ms: stats.mean_ms,
+            m2: stats.m2,
+        }
+    }
+}
+
+impl From<proto::DurationStats> for DurationStats {
+    fn from(stats: proto::DurationStats) -> Self {
+        Self {
+            count: stats.count,
+            mean_ms: stats.mean_ms,
+            m2: stats.m2,
+        }
+    }
+}
+
+impl From<&ScheduleInfo> for proto::ScheduleInfo {
+    fn from(info: &ScheduleInfo) -> Self {
+        Self {
+            expression: info.expression.clone(),
+            description: info.description.clone(),
+            next_run: info.next_run.map(timestamp),
+            interval_secs: info.interval_secs,
+            min_interval_secs: info.min_interval_secs,
+        }
+    }
+}
+
+impl TryFrom<proto::ScheduleInfo> for ScheduleInfo {
+    type Error = CronError;
+
+    fn try_from(info: proto::ScheduleInfo) -> Result<Self> {
+        Ok(Self {
+            expression: info.expression,
+            description: info.description,
+            next_run: optional_timestamp(info.next_run, "next run")?,
+            interval_secs: info.interval_secs,
+            min_interval_secs: info.min_interval_secs,
+        })
+    }
+}
+
+impl From<&OutputFiles> for proto::OutputFiles {
+    fn from(files: &OutputFiles) -> Self {
+        Self {
+            stdout_path: files.stdout_path.to_string_lossy().to_string(),
+            stdout_bytes: files.stdout_bytes,
+            stderr_path: files.stderr_path.to_string_lossy().to_string(),
+            stderr_bytes: files.stderr_bytes,
+        }
+    }
+}
+
+impl From<proto::OutputFiles> for OutputFiles {
+    fn from(files: proto::OutputFiles) -> Self {
+        Self {
+            stdout_path: files.stdout_path.into(),
+            stdout_bytes: files.stdout_bytes,
+            stderr_path: files.stderr_path.into(),
+            stderr_bytes: files.stderr_bytes,
+        }
+    }
+}
+
+impl From<&CronJob> for proto::Job {
+    fn from(job: &CronJob) -> Self {
+        Self {
+            id: job.id.clone(),
+            name: job.name.clone(),
+            schedule: job.schedule.clone(),
+            command: job.command.clone(),
+            command_kind: proto::CommandKind::from(job.command_kind).into(),
+            program: job.program.clone(),
+            args: job.args.clone(),
+            schedule_kind: Some((&job.schedule_kind).into()),
+            job_type: proto::JobType::from(job.job_type).into(),
+            agent_config: job.agent_config.as_ref().map(Into::into),
+            status: proto::JobStatus::from(job.status).into(),
+            timeout_ms: job.timeout_ms,
+            created_at: Some(timestamp(job.created_at)),
+            updated_at: Some(timestamp(job.updated_at)),
+            last_run: job.last_run.map(timestamp),
+            next_run: job.next_run.map(timestamp),
+            run_count: job.run_count,
+            fail_count: job.fail_count,
+            working_dir: job.working_dir.clone(),
+            env: job
+                .env
+                .iter()
+                .map(|(name, value)| proto::EnvVar {
+                    name: name.clone(),
+                    value: value.clone(),
+                })
+                .collect(),
+            secret_env: job.secret_env.clone(),
+            templated: job.templated,
+            env_mode: job.env_mode.as_ref().map(Into::into),
+            shell: job.shell.clone(),
+            priority: job.priority,
+            min_interval_ms: job.min_interval_ms,
+            expected_duration_ms: job.expected_duration_ms,
+            resource_limits: Some((&job.resource_limits).into()),
+            max_retries: job.max_retries,
+            retry_delay_ms: job.retry_delay_ms,
+            retry_backoff: job.retry_backoff,
+            not_before: job.not_before.map(timestamp),
+            not_after: job.not_after.map(timestamp),
+            max_runs: job.max_runs,
+            count_manual: job.count_manual,
+            manual_run_count: job.manual_run_count,
+            record_history: job.record_history,
+            consecutive_failures: job.consecutive_failures,
+            last_error: job.last_error.clone(),
+            auto_pause_after: job.auto_pause_after,
+            misfire_policy: Some((&job.misfire_policy).into()),
+            concurrency_policy: Some((&job.concurrency_policy).into()),
+            kill_grace_ms: job.kill_grace_ms,
+            jitter_ms: job.jitter_ms,
+            blackouts: job.blackouts.iter().map(Into::into).collect(),
+            blackout_policy: proto::BlackoutPolicy::from(job.blackout_policy).into(),
+            exclude_calendar: job.exclude_calendar.clone(),
+            holiday_policy: proto::BlackoutPolicy::from(job.holiday_policy).into(),
+            allowed_window: job.allowed_window.as_ref().map(Into::into),
+            triggers: job.triggers.clone(),
+            on_failure: job.on_failure.clone(),
+            tags: job.tags.clone(),
+            snoozed_runs: job.snoozed_runs,
+            snoozed_until: job.snoozed_until.map(timestamp),
+            notify_on_change: job.notify_on_change,
+            ignore_whitespace_changes: job.ignore_whitespace_changes,
+            last_output_hash: job.last_output_hash.clone(),
+            duration_stats: Some((&job.duration_stats).into()),
+            schedule_info: job.schedule_info.as_ref().map(Into::into),
+            health: job
+                .health
+                .map(|health| proto::JobHealth::from(health).into()),
+            effective_timeout_ms: job.effective_timeout_ms,
+        }
+    }
+}
+
+impl TryFrom<proto::Job> for CronJob {
+    type Error = CronError;
+
+    fn try_from(job: proto::Job) -> Result<Self> {
+        Ok(Self {
+            id: job.id,
+            name: job.name,
+            schedule: job.schedule,
+            command: job.command,
+            command_kind: decode::<proto::CommandKind>(job.command_kind, "command kind")?.into(),
+            program: job.program,
+            args: job.args,
+            schedule_kind: job.schedule_kind.unwrap_or_default().try_into()?,
+            job_type: decode::<proto::JobType>(job.job_type, "job type")?.into(),
+            agent_config: job.agent_config.map(Into::into),
+            status: decode::<proto::JobStatus>(job.status, "job status")?.into(),
+            timeout_ms: job.timeout_ms,
+            created_at: required_timestamp(job.created_at, "creation time")?,
+            updated_at: required_timestamp(job.updated_at, "update time")?,
+            last_run: optional_timestamp(job.last_run, "last run")?,
+            next_run: optional_timestamp(job.next_run, "next run")?,
+            run_count: job.run_count,
+            fail_count: job.fail_count,
+            working_dir: job.working_dir,
+            env: job
+                .env
+                .into_iter()
+                .map(|var| (var.name, var.value))
+                .collect(),
+            secret_env: job.secret_env,
+            templated: job.templated,
+            env_mode: job.env_mode.map(TryInto::try_into).transpose()?,
+            shell: job.shell,
+            priority: job.priority,
+            min_interval_ms: job.min_interval_ms,
+            expected_duration_ms: job.expected_duration_ms,
+            resource_limits: job.resource_limits.unwrap_or_default().into(),
+            max_retries: job.max_retries,
+            retry_delay_ms: job.retry_delay_ms,
+            retry_backoff: job.retry_backoff,
+            not_before: optional_timestamp(job.not_before, "start of the date window")?,
+            not_after: optional_timestamp(job.not_after, "end of the date window")?,
+            max_runs: job.max_runs,
+            count_manual: job.count_manual,
+            manual_run_count: job.manual_run_count,
+            record_history: job.record_history,
+            consecutive_failures: job.consecutive_failures,
+            last_error: job.last_error,
+            auto_pause_after: job.auto_pause_after,
+            misfire_policy: job.misfire_policy.unwrap_or_default().try_into()?,
+            concurrency_policy: job.concurrency_policy.unwrap_or_default().try_into()?,
+            kill_grace_ms: job.kill_grace_ms,
+            jitter_ms: job.jitter_ms,
+            blackouts: job
+                .blackouts
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_>>()?,
+            blackout_policy: decode::<proto::BlackoutPolicy>(
+                job.blackout_policy,
+                "blackout policy",
+            )?
+            .into(),
+            exclude_calendar: job.exclude_calendar,
+            holiday_policy: decode::<proto::BlackoutPolicy>(job.holiday_policy, "holiday policy")?
+                .into(),
+            allowed_window: job.allowed_window.map(TryInto::try_into).transpose()?,
+            triggers: job.triggers,
+            on_failure: job.on_failure,
+            tags: job.tags,
+            snoozed_runs: job.snoozed_runs,
+            snoozed_until: optional_timestamp(job.snoozed_until, "snooze time")?,
+            notify_on_change: job.notify_on_change,
+            ignore_whitespace_changes: job.ignore_whitespace_changes,
+            last_output_hash: job.last_output_hash,
+            duration_stats: job.duration_stats.unwrap_or_default().into(),
+            schedule_info: job.schedule_info.map(TryInto::try_into).transpose()?,
+            health: job
+                .health
+                .map(|health| decode::<proto::JobHealth>(health, "job health").map(Into::into))
+                .transpose()?,
+            effective_timeout_ms: job.effective_timeout_ms,
+        })
+    }
+}
+
+impl From<&JobExecution> for proto::Execution {
+    fn from(execution: &JobExecution) -> Self {
+        Self {
+            id: execution.id.clone(),
+            job_id: execution.job_id.clone(),
+            status: proto::ExecutionStatus::from(execution.status).into(),
+            attempt: execution.attempt,
+            trigger: Some((&execution.trigger).into()),
+            scheduled_for: execution.scheduled_for.map(timestamp),
+            started_at: Some(timestamp(execution.started_at)),
+            lag_ms: execution.lag_ms,
+            ended_at: execution.ended_at.map(timestamp),
+            duration_ms: execution.duration_ms,
+            exit_code: execution.exit_code,
+            stdout: execution.stdout.clone(),
+            stderr: execution.stderr.clone(),
+            error: execution.error.clone(),
+            termination: execution
+                .termination
+                .map(|termination| proto::Termination::from(termination).into()),
+            triggered_by: execution.triggered_by.clone(),
+            hook_for: execution.hook_for.clone(),
+            overrides: execution.overrides.clone(),
+            output_files: execution.output_files.as_ref().map(Into::into),
+            output_hash: execution.output_hash.clone(),
+            output_changed: execution.output_changed,
+        }
+    }
+}
+
+impl TryFrom<proto::Execution> for JobExecution {
+    type Error = CronError;
+
+    fn try_from(execution: proto::Execution) -> Result<Self> {
+        Ok(Self {
+            id: execution.id,
+            job_id: execution.job_id,
+            status: decode::<proto::ExecutionStatus>(execution.status, "execution status")?.into(),
+            attempt: execution.attempt,
+            trigger: execution.trigger.unwrap_or_default().try_into()?,
+            scheduled_for: optional_timestamp(execution.scheduled_for, "fire time")?,
+            started_at: required_timestamp(execution.started_at, "start time")?,
+            lag_ms: execution.lag_ms,
+            ended_at: optional_timestamp(execution.ended_at, "end time")?,
+            duration_ms: execution.duration_ms,
+            exit_code: execution.exit_code,
+            stdout: execution.stdout,
+            stderr: execution.stderr,
+            error: execution.error,
+            termination: execution
+                .termination
+                .map(|termination| {
+                    decode::<proto::Termination>(termination, "termination").map(Into::into)
+                })
+                .transpose()?,
+            triggered_by: execution.triggered_by,
+            hook_for: execution.hook_for,
+            overrides: execution.overrides,
+            output_files: execution.output_files.map(Into::into),
+            output_hash: execution.output_hash,
+            output_changed: execution.output_changed,
+        })
+    }
+}
+
+impl From<&ManifestJob> for proto::AddJobRequest {
+    fn from(job: &ManifestJob) -> Self {
+        Self {
+            name: job.name.clone(),
+            schedule: job.schedule.clone(),
+            command: job.command.clone(),
+            job_type: proto::JobType::from(job.job_type).into(),
+            agent_config: job.agent_config.as_ref().map(Into::into),
+            env: job.env.clone().into_iter().collect(),
+            working_dir: job.working_dir.clone(),
+            timeout_ms: job.timeout_ms,
+            tags: job.tags.clone(),
+            concurrency_policy: Some((&job.concurrency_policy).into()),
+        }
+    }
+}
+
+impl TryFrom<proto::AddJobRequest> for ManifestJob {
+    type Error = CronError;
+
+    fn try_from(request: proto::AddJobRequest) -> Result<Self> {
+        Ok(Self {
+            job_type: decode::<proto::JobType>(request.job_type, "job type")?.into(),
+            agent_config: request.agent_config.map(Into::into),
+            env: request.env.into_iter().collect(),
+            working_dir: request.working_dir,
+            timeout_ms: request.timeout_ms,
+            tags: request.tags,
+            concurrency_policy: request.concurrency_policy.unwrap_or_default().try_into()?,
+            ..Self::new(request.name, request.schedule, request.command)
+        })
+    }
+}
+
+impl From<&EventEnvelope> for proto::Event {
+    fn from(envelope: &EventEnvelope) -> Self {
+        let data = serde_json::to_value(&envelope.kind).unwrap_or_default();
+        let event_type = data
+            .get("type")
+            .and_then(|event_type| event_type.as_str())
+            .unwrap_or_default()
+            .to_string();
+        Self {
+            id: envelope.id.to_string(),
+            at: Some(timestamp(envelope.at)),
+            r#type: event_type,
+            job_id: envelope.kind.job_id().map(str::to_string),
+            data: data.to_string(),
+        }
+    }
+}
+
+impl TryFrom<proto::Event> for EventEnvelope {
+    type Error = CronError;
+
+    fn try_from(event: proto::Event) -> Result<Self> {
+        Ok(Self {
+            id: event.id.parse().map_err(|_| invalid("event ID"))?,
+            at: required_timestamp(event.at, "event time")?,
+            kind: serde_json::from_str(&event.data)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduler::SchedulerEvent;
+    use crate::store::MemoryCronStore;
+    use proto::cron_client::CronClient;
+    use std::time::Duration;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tokio_stream::StreamExt;
+
+    /// Serve a manager on a loopback port, returning a connected client
+    async fn serve(manager: Arc<CronManager>) -> CronClient<tonic::transport::Channel> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(CronGrpcService::new(manager).into_server())
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+        CronClient::connect(format!("http://{}", addr))
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn test_round_trips() {
+        let mut job = CronJob::new("backup", "0 2 * * *", "echo backup");
+        job.env = vec![("B".into(), "2".into()), ("A".into(), "1".into())];
+        job.env_mode = Some(EnvMode::InheritAllow(vec!["PATH".into()]));
+        job.schedule_kind = ScheduleKind::At(Utc::now());
+        job.misfire_policy = MisfirePolicy::CatchUp { max: 3 };
+        job.concurrency_policy = ConcurrencyPolicy::Queue { max_queue: 4 };
+        job.blackouts = vec![TimeWindow::parse("22:00-06:00").unwrap()];
+        job.allowed_window = Some(TimeWindow {
+            start: NaiveTime::from_hms_nano_opt(9, 30, 0, 1).unwrap(),
+            end: NaiveTime::from_hms_opt(17, 0, 0).unwrap(),
+            days: vec![Weekday::Mon, Weekday::Sun],
+        });
+        job.agent_config = Some(AgentJobConfig {
+            model: "model".into(),
+            api_key: "key".into(),
+            workspace: None,
+            system_prompt: Some("prompt".into()),
+            base_url: None,
+        });
+        job.resource_limits.nice = Some(-5);
+        job.retry_backoff = 1.5;
+        job.last_run = Some(Utc::now());
+        job.duration_stats.record(1500);
+        job.health = Some(JobHealth::Degraded);
+        let decoded = CronJob::try_from(proto::Job::from(&job)).unwrap();
+        assert_eq!(
+            serde_json::to_value(&decoded).unwrap(),
+            serde_json::to_value(&job).unwrap()
+        );
+
+        let mut execution = JobExecution::new(&job.id);
+        execution.trigger = TriggerKind::Dependency {
+            parent_execution_id: "parent".into(),
+        };
+        execution.exit_code = Some(-1);
+        execution.termination = Some(Termination::Forced);
+        execution.output_changed = Some(true);
+        let decoded = JobExecution::try_from(proto::Execution::from(&execution)).unwrap();
+        assert_eq!(
+            serde_json::to_value(&decoded).unwrap(),
+            serde_json::to_value(&execution).unwrap()
+        );
+
+        let mut declared = ManifestJob::new("hello", "@never", "echo hello");
+        declared.env.insert("GREETING".into(), "hi".into());
+        declared.concurrency_policy = ConcurrencyPolicy::Forbid;
+        let decoded = ManifestJob::try_from(proto::AddJobRequest::from(&declared)).unwrap();
+        assert_eq!(decoded, declared);
+
+        let envelope = EventEnvelope::new(SchedulerEvent::JobPaused {
+            job_id: job.id.clone(),
+            job_name: job.name.clone(),
+            timestamp: Utc::now(),
+        });
+        let event = proto::Event::from(&envelope);
+        assert_eq!(event.r#type, "job_paused");
+        assert_eq!(event.job_id.as_deref(), Some(job.id.as_str()));
+        let decoded = EventEnvelope::try_from(event).unwrap();
+        assert_eq!(
+            serde_json::to_value(&decoded).unwrap(),
+            serde_json::to_value(&envelope).unwrap()
+        );
+
+        let mut invalid = proto::Job::from(&job);
+        invalid.status = 42;
+        assert!(matches!(
+            CronJob::try_from(invalid),
+            Err(CronError::InvalidConfig(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_loopback() {
+        let store = Arc::new(MemoryCronStore::new());
+        let manager = Arc::new(CronManager::with_store(store, "/tmp".to_string()));
+        let mut client = serve(manager.clone()).await;
+        let mut events = client
+            .watch(proto::WatchRequest {})
+            .await
+            .unwrap()
+            .into_inner();
+
+        let declared = ManifestJob::new("hello", "@never", "echo hello");
+        let job = client
+            .add_job(proto::AddJobRequest::from(&declared))
+            .await
+            .unwrap()
+            .into_inner();
+        let job = CronJob::try_from(job).unwrap();
+        assert_eq!(job.name, "hello");
+        let status = client
+            .add_job(proto::AddJobRequest::from(&declared))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::AlreadyExists);
+
+        let jobs = client
+            .list_jobs(proto::ListJobsRequest {})
+            .await
+            .unwrap()
+            .into_inner()
+            .jobs;
+        assert_eq!(jobs.len(), 1);
+        let request = || proto::JobRequest { id: job.id.clone() };
+        let paused = client.pause_job(request()).await.unwrap().into_inner();
+        assert_eq!(paused.status(), proto::JobStatus::Paused);
+        client.resume_job(request()).await.unwrap();
+
+        let response = client
+            .run_job(proto::RunJobRequest {
+                id: job.id.clone(),
+                detach: false,
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        let Some(proto::run_job_response::Result::Execution(execution)) = response.result else {
+            panic!("expected an execution, got {:?}", response.result);
+        };
+        let execution = JobExecution::try_from(execution).unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Success);
+        let fetched = client
+            .get_execution(proto::ExecutionRequest {
+                id: execution.id.clone(),
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(fetched.id, execution.id);
+        let history = client
+            .get_history(proto::HistoryRequest {
+                id: job.id.clone(),
+                limit: 0,
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .executions;
+        assert_eq!(history.len(), 1);
+
+        let status = client
+            .get_job(proto::JobRequest {
+                id: "unknown".into(),
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+
+        // The stream saw the job added first
+        let first = tokio::time::timeout(Duration::from_secs(5), events.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        let Some(proto::watch_response::Kind::Event(event)) = first.kind else {
+            panic!("expected an event, got {:?}", first.kind);
+        };
+        assert_eq!(event.r#type, "job_added");
+        assert_eq!(event.job_id, Some(job.id.clone()));
+        let envelope = EventEnvelope::try_from(event).unwrap();
+        assert!(matches!(envelope.kind, SchedulerEvent::JobAdded { .. }));
+    }
+}
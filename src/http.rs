@@ -0,0 +1,490 @@
+//! HTTP management API
+//!
+//! [`router`] exposes a [`CronManager`] as an axum router, for services
+//! that would otherwise each write the same thin HTTP layer over it. Jobs
+//! and executions are sent as [`CronJob`] and [`JobExecution`] JSON, new
+//! jobs are declared like [`ManifestJob`]s, and scheduler events are
+//! streamed as server-sent events.
+//!
+//! | Method | Path | Action |
+//! |---|---|---|
+//! | `GET` | `/jobs` | List jobs |
+//! | `POST` | `/jobs` | Create a job |
+//! | `GET` | `/jobs/{id}` | Get a job |
+//! | `PATCH` | `/jobs/{id}` | Update a job |
+//! | `DELETE` | `/jobs/{id}?purge_history=` | Remove a job |
+//! | `POST` | `/jobs/{id}/pause` | Pause a job |
+//! | `POST` | `/jobs/{id}/resume` | Resume a job |
+//! | `POST` | `/jobs/{id}/run?detach=` | Run a job now, or in the background |
+//! | `GET` | `/jobs/{id}/history?limit=` | Get a job's executions |
+//! | `GET` | `/executions/{id}` | Get an execution |
+//! | `GET` | `/events` | Stream scheduler events |
+//!
+//! Errors are sent as `{"error": "..."}` with a status matching the
+//! [`CronError`]: 404 for unknown jobs and executions, 409 for taken names
+//! and jobs in the wrong state, 422 for invalid schedules.
+
+use crate::scheduler::{CronManager, EventEnvelope, SchedulerEvent};
+use crate::types::{
+    ConcurrencyPolicy, CronError, CronJob, JobExecution, ManifestJob, UpdateJobRequest,
+};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use chrono::Utc;
+use futures_util::Stream;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+/// Executions returned by the history endpoint unless `limit` is given
+pub const DEFAULT_HISTORY_LIMIT: usize = 20;
+
+/// Build a router managing `manager`'s jobs
+pub fn router(manager: Arc<CronManager>) -> Router {
+    Router::new()
+        .route("/jobs", get(list_jobs).post(create_job))
+        .route(
+            "/jobs/{id}",
+            get(get_job).patch(update_job).delete(remove_job),
+        )
+        .route("/jobs/{id}/pause", post(pause_job))
+        .route("/jobs/{id}/resume", post(resume_job))
+        .route("/jobs/{id}/run", post(run_job))
+        .route("/jobs/{id}/history", get(history))
+        .route("/executions/{id}", get(get_execution))
+        .route("/events", get(events))
+        .with_state(manager)
+}
+
+/// Changes to a job; fields left out are kept
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateJobBody {
+    /// New name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// New schedule (cron expression, natural language or `@never`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<String>,
+    /// New command
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// New timeout in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    /// Environment variables replacing the job's own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env: Option<BTreeMap<String, String>>,
+    /// Tags replacing the job's own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    /// New concurrency policy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency_policy: Option<ConcurrencyPolicy>,
+}
+
+impl From<UpdateJobBody> for UpdateJobRequest {
+    fn from(body: UpdateJobBody) -> Self {
+        let mut request = UpdateJobRequest::new();
+        request.name = body.name;
+        request.schedule = body.schedule;
+        request.command = body.command;
+        request.timeout_ms = body.timeout_ms.map(Some);
+        request.env = body.env.map(|env| env.into_iter().collect());
+        request.tags = body.tags;
+        request.concurrency_policy = body.concurrency_policy;
+        request
+    }
+}
+
+/// Query of the remove endpoint
+#[derive(Debug, Default, Deserialize)]
+struct RemoveQuery {
+    /// Delete the job's execution history too
+    #[serde(default)]
+    purge_history: bool,
+}
+
+/// Query of the run endpoint
+#[derive(Debug, Default, Deserialize)]
+struct RunQuery {
+    /// Return at once with the execution's ID instead of waiting for it
+    #[serde(default)]
+    detach: bool,
+}
+
+/// Response of a detached run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetachedRun {
+    /// ID of the execution started
+    pub execution_id: String,
+}
+
+/// Query of the history endpoint
+#[derive(Debug, Default, Deserialize)]
+struct HistoryQuery {
+    /// Most executions returned, most recent first
+    limit: Option<usize>,
+}
+
+/// Body of an error response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorBody {
+    /// What went wrong
+    pub error: String,
+}
+
+/// A [`CronError`] sent as a response
+struct ApiError(CronError);
+
+impl From<CronError> for ApiError {
+    fn from(error: CronError) -> Self {
+        Self(error)
+    }
+}
+
+/// Status code reporting `error`
+fn status_for(error: &CronError) -> StatusCode {
+    match error {
+        CronError::JobNotFound(_)
+        | CronError::ExecutionNotFound(_)
+        | CronError::WorkspaceNotFound(_) => StatusCode::NOT_FOUND,
+        CronError::JobExists(_)
+        | CronError::JobCompleted(_)
+        | CronError::JobDisabled(_)
+        | CronError::JobPaused(_)
+        | CronError::InBlackout(_)
+        | CronError::ExecutionNotRunning(_)
+        | CronError::AlreadyRunning => StatusCode::CONFLICT,
+        CronError::InvalidExpression(_)
+        | CronError::ScheduleTooFrequent { .. }
+        | CronError::TriggerCycle(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        CronError::InvalidConfig(_) | CronError::Template(_) => StatusCode::BAD_REQUEST,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = status_for(&self.0);
+        if status.is_server_error() {
+            tracing::error!("Cron HTTP request failed: {}", self.0);
+        }
+        let body = ErrorBody {
+            error: self.0.to_string(),
+        };
+        (status, Json(body)).into_response()
+    }
+}
+
+type ApiResult<T> = std::result::Result<T, ApiError>;
+
+async fn list_jobs(State(manager): State<Arc<CronManager>>) -> ApiResult<Json<Vec<CronJob>>> {
+    Ok(Json(manager.list_jobs().await?))
+}
+
+async fn create_job(
+    State(manager): State<Arc<CronManager>>,
+    Json(declared): Json<ManifestJob>,
+) -> ApiResult<(StatusCode, Json<CronJob>)> {
+    let job = manager
+        .add_job_with_options(
+            &declared.name,
+            &declared.schedule,
+            &declared.command,
+            declared.options(),
+        )
+        .await?;
+    Ok((StatusCode::CREATED, Json(job)))
+}
+
+async fn get_job(
+    State(manager): State<Arc<CronManager>>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<CronJob>> {
+    let job = manager
+        .get_job(&id)
+        .await?
+        .ok_or(CronError::JobNotFound(id))?;
+    Ok(Json(job))
+}
+
+async fn update_job(
+    State(manager): State<Arc<CronManager>>,
+    Path(id): Path<String>,
+    Json(body): Json<UpdateJobBody>,
+) -> ApiResult<Json<CronJob>> {
+    Ok(Json(
+        manager.update_job_with_request(&id, body.into()).await?,
+    ))
+}
+
+async fn remove_job(
+    State(manager): State<Arc<CronManager>>,
+    Path(id): Path<String>,
+    Query(query): Query<RemoveQuery>,
+) -> ApiResult<StatusCode> {
+    manager.remove_job(&id, query.purge_history).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn pause_job(
+    State(manager): State<Arc<CronManager>>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<CronJob>> {
+    Ok(Json(manager.pause_job(&id).await?))
+}
+
+async fn resume_job(
+    State(manager): State<Arc<CronManager>>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<CronJob>> {
+    Ok(Json(manager.resume_job(&id).await?))
+}
+
+async fn run_job(
+    State(manager): State<Arc<CronManager>>,
+    Path(id): Path<String>,
+    Query(query): Query<RunQuery>,
+) -> ApiResult<Response> {
+    if query.detach {
+        let execution_id = manager.run_job_detached(&id).await?;
+        return Ok((StatusCode::ACCEPTED, Json(DetachedRun { execution_id })).into_response());
+    }
+    Ok(Json(manager.run_job(&id).await?).into_response())
+}
+
+async fn history(
+    State(manager): State<Arc<CronManager>>,
+    Path(id): Path<String>,
+    Query(query): Query<HistoryQuery>,
+) -> ApiResult<Json<Vec<JobExecution>>> {
+    if manager.get_job(&id).await?.is_none() {
+        return Err(CronError::JobNotFound(id).into());
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_HISTORY_LIMIT);
+    Ok(Json(manager.get_history(&id, limit).await?))
+}
+
+async fn get_execution(
+    State(manager): State<Arc<CronManager>>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<JobExecution>> {
+    let execution = manager
+        .get_execution(&id)
+        .await?
+        .ok_or(CronError::ExecutionNotFound(id))?;
+    Ok(Json(execution))
+}
+
+/// Stream every event as JSON, with the event's type as the SSE event name
+/// and its ID as the SSE ID. Events a slow client missed are reported with
+/// an `events_dropped` event.
+async fn events(
+    State(manager): State<Arc<CronManager>>,
+) -> Sse<impl Stream<Item = std::result::Result<Event, axum::Error>>> {
+    let stream = futures_util::stream::unfold(manager.subscribe(), |mut rx| async move {
+        let envelope = match rx.recv().await {
+            Ok(envelope) => envelope,
+            Err(broadcast::error::RecvError::Lagged(count)) => {
+                EventEnvelope::new(SchedulerEvent::EventsDropped {
+                    count,
+                    timestamp: Utc::now(),
+                })
+            }
+            Err(broadcast::error::RecvError::Closed) => return None,
+        };
+        let mut event = Event::default().id(envelope.id.to_string());
+        if let Some(name) = event_name(&envelope.kind) {
+            event = event.event(name);
+        }
+        Some((event.json_data(&envelope), rx))
+    });
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+/// The `type` tag an event is serialized with
+fn event_name(event: &SchedulerEvent) -> Option<String> {
+    match serde_json::to_value(event).ok()?.get("type")? {
+        serde_json::Value::String(name) => Some(name.clone()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::MemoryCronStore;
+    use crate::types::ExecutionStatus;
+    use axum::body::Body;
+    use axum::http::{header, Request};
+    use futures_util::StreamExt;
+    use serde::de::DeserializeOwned;
+    use tower::ServiceExt;
+
+    fn create_test_router() -> Router {
+        let store = Arc::new(MemoryCronStore::new());
+        router(Arc::new(CronManager::with_store(store, "/tmp".to_string())))
+    }
+
+    /// Send a request with an optional JSON body, returning the status and
+    /// the body
+    async fn send(
+        app: &Router,
+        method: &str,
+        uri: &str,
+        body: Option<serde_json::Value>,
+    ) -> (StatusCode, Vec<u8>) {
+        let request = Request::builder().method(method).uri(uri);
+        let request = match body {
+            Some(body) => request
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string())),
+            None => request.body(Body::empty()),
+        }
+        .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, body.to_vec())
+    }
+
+    fn parse<T: DeserializeOwned>(body: &[u8]) -> T {
+        serde_json::from_slice(body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_job_crud() {
+        let app = create_test_router();
+        let backup = serde_json::json!({
+            "name": "backup",
+            "schedule": "0 2 * * *",
+            "command": "echo backup",
+            "tags": ["nightly"],
+        });
+
+        let (status, body) = send(&app, "POST", "/jobs", Some(backup.clone())).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let job: CronJob = parse(&body);
+        assert_eq!(job.name, "backup");
+        assert_eq!(job.tags, ["nightly"]);
+
+        let (status, body) = send(&app, "POST", "/jobs", Some(backup)).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert!(parse::<ErrorBody>(&body).error.contains("backup"));
+        let invalid = serde_json::json!({
+            "name": "broken",
+            "schedule": "not a schedule",
+            "command": "echo",
+        });
+        let (status, _) = send(&app, "POST", "/jobs", Some(invalid)).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+        let (status, body) = send(&app, "GET", "/jobs", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(parse::<Vec<CronJob>>(&body).len(), 1);
+
+        let path = format!("/jobs/{}", job.id);
+        let update = serde_json::json!({ "schedule": "30 3 * * *" });
+        let (status, body) = send(&app, "PATCH", &path, Some(update)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(parse::<CronJob>(&body).schedule, "30 3 * * *");
+
+        let (status, body) = send(&app, "POST", &format!("{}/pause", path), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(parse::<CronJob>(&body).status, crate::JobStatus::Paused);
+        let (status, _) = send(&app, "POST", &format!("{}/run", path), None).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        let (status, body) = send(&app, "POST", &format!("{}/resume", path), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(parse::<CronJob>(&body).status, crate::JobStatus::Active);
+
+        let (status, _) = send(&app, "DELETE", &path, None).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let (status, body) = send(&app, "GET", &path, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert!(parse::<ErrorBody>(&body).error.contains(&job.id));
+        let (status, _) = send(&app, "DELETE", &path, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_runs_and_history() {
+        let app = create_test_router();
+        let declared = serde_json::json!({
+            "name": "hello",
+            "schedule": "@never",
+            "command": "echo hello",
+        });
+        let (_, body) = send(&app, "POST", "/jobs", Some(declared)).await;
+        let job: CronJob = parse(&body);
+        let path = format!("/jobs/{}", job.id);
+
+        let (status, body) = send(&app, "POST", &format!("{}/run", path), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let execution: JobExecution = parse(&body);
+        assert_eq!(execution.status, ExecutionStatus::Success);
+        assert_eq!(execution.stdout.trim(), "hello");
+
+        let uri = format!("/executions/{}", execution.id);
+        let (status, body) = send(&app, "GET", &uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(parse::<JobExecution>(&body).id, execution.id);
+        let (status, _) = send(&app, "GET", "/executions/unknown", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let uri = format!("{}/run?detach=true", path);
+        let (status, body) = send(&app, "POST", &uri, None).await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+        let detached: DetachedRun = parse(&body);
+        assert_ne!(detached.execution_id, execution.id);
+
+        let uri = format!("{}/history?limit=1", path);
+        let (status, body) = send(&app, "GET", &uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(parse::<Vec<JobExecution>>(&body).len(), 1);
+        let (status, _) = send(&app, "GET", "/jobs/unknown/history", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_event_stream() {
+        let store = Arc::new(MemoryCronStore::new());
+        let manager = Arc::new(CronManager::with_store(store, "/tmp".to_string()));
+        let app = router(manager.clone());
+
+        let request = Request::builder()
+            .uri("/events")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "text/event-stream"
+        );
+        let job = manager.add_job("watched", "@never", "true").await.unwrap();
+
+        let mut frames = response.into_body().into_data_stream();
+        let frame = tokio::time::timeout(std::time::Duration::from_secs(5), frames.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        let frame = String::from_utf8(frame.to_vec()).unwrap();
+        assert!(frame.contains("event: job_added"));
+        let data = frame
+            .lines()
+            .find_map(|line| line.strip_prefix("data: "))
+            .unwrap();
+        let envelope: EventEnvelope = serde_json::from_str(data).unwrap();
+        assert_eq!(envelope.kind.job_id(), Some(job.id.as_str()));
+        assert!(frame.contains(&format!("id: {}", envelope.id)));
+    }
+}
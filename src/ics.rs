@@ -0,0 +1,366 @@
+//! iCalendar feed of upcoming runs
+//!
+//! Renders upcoming fire times as an RFC 5545 `VCALENDAR`, one `VEVENT`
+//! per run, so scheduled jobs can be subscribed to from a calendar app:
+//!
+//! ```text
+//! BEGIN:VCALENDAR
+//! VERSION:2.0
+//! PRODID:-//A3S Lab//a3s-cron//EN
+//! BEGIN:VEVENT
+//! UID:<job id>-20300101T020000Z@a3s-cron
+//! DTSTAMP:20291231T120000Z
+//! DTSTART:20300101T020000Z
+//! DURATION:PT1M
+//! SUMMARY:backup
+//! DESCRIPTION:backup.sh --to s3://bucket\nJob ID: <job id>
+//! END:VEVENT
+//! END:VCALENDAR
+//! ```
+//!
+//! Times are in UTC. Text values have backslashes, semicolons, commas and
+//! newlines escaped, and lines longer than 75 octets are folded, as the RFC
+//! requires. Events last the job's expected duration, or its mean run time,
+//! and at least a minute so calendars show them.
+
+use crate::types::CronJob;
+use chrono::{DateTime, Utc};
+
+/// Most events exported for one job
+pub const MAX_EVENTS_PER_JOB: usize = 100;
+
+/// Most events exported in one feed
+pub const MAX_EVENTS: usize = 1000;
+
+/// Product identifier of the feed
+const PRODID: &str = "-//A3S Lab//a3s-cron//EN";
+
+/// Longest content line in octets, not counting the line break
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Shortest event duration in seconds
+const MIN_EVENT_SECS: u64 = 60;
+
+/// Render `runs` as an iCalendar document stamped with `stamp`. Jobs should
+/// have their secrets masked already.
+pub(crate) fn render_calendar(runs: &[(CronJob, DateTime<Utc>)], stamp: DateTime<Utc>) -> String {
+    let mut out = String::new();
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(&mut out, &format!("PRODID:{}", PRODID));
+    push_line(&mut out, "CALSCALE:GREGORIAN");
+    push_line(&mut out, "METHOD:PUBLISH");
+    for (job, fire_time) in runs {
+        push_line(&mut out, "BEGIN:VEVENT");
+        push_line(
+            &mut out,
+            &format!("UID:{}-{}@a3s-cron", job.id, format_time(*fire_time)),
+        );
+        push_line(&mut out, &format!("DTSTAMP:{}", format_time(stamp)));
+        push_line(&mut out, &format!("DTSTART:{}", format_time(*fire_time)));
+        push_line(&mut out, &format!("DURATION:PT{}S", event_secs(job)));
+        push_line(&mut out, &format!("SUMMARY:{}", escape_text(&job.name)));
+        let description = format!("{}\nJob ID: {}", job.command, job.id);
+        push_line(
+            &mut out,
+            &format!("DESCRIPTION:{}", escape_text(&description)),
+        );
+        if !job.tags.is_empty() {
+            let categories: Vec<String> = job.tags.iter().map(|tag| escape_text(tag)).collect();
+            push_line(&mut out, &format!("CATEGORIES:{}", categories.join(",")));
+        }
+        push_line(&mut out, "END:VEVENT");
+    }
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+/// Format a UTC time as an iCalendar `DATE-TIME`
+fn format_time(time: DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// How long an event of `job` lasts, in seconds
+fn event_secs(job: &CronJob) -> u64 {
+    let duration_ms = job.expected_duration_ms.unwrap_or_else(|| {
+        if job.duration_stats.is_empty() {
+            0
+        } else {
+            job.duration_stats.mean_ms as u64
+        }
+    });
+    (duration_ms / 1000).max(MIN_EVENT_SECS)
+}
+
+/// Escape a `TEXT` value: backslashes, semicolons and commas get a
+/// backslash, and line breaks become `\n`
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\r' => {
+                chars.next_if_eq(&'\n');
+                escaped.push_str("\\n");
+            }
+            '\n' => escaped.push_str("\\n"),
+            // Other control characters aren't allowed in text values
+            c if c.is_control() && c != '\t' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Append a content line, folded into lines of at most 75 octets that
+/// continue with a space, never splitting a character
+fn push_line(out: &mut String, line: &str) {
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            octets = 1;
+        }
+        out.push(c);
+        octets += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    /// A component parsed by [`validate`]: its name, its properties with
+    /// text values unescaped, and its subcomponents
+    #[derive(Debug, Default)]
+    pub(crate) struct Component {
+        pub name: String,
+        pub properties: BTreeMap<String, Vec<String>>,
+        pub components: Vec<Component>,
+    }
+
+    impl Component {
+        pub fn get(&self, name: &str) -> Option<&str> {
+            self.properties
+                .get(name)
+                .and_then(|values| values.first())
+                .map(String::as_str)
+        }
+    }
+
+    /// Properties whose value is a single `TEXT`
+    const TEXT_PROPERTIES: [&str; 3] = ["SUMMARY", "DESCRIPTION", "UID"];
+
+    /// Unescape a single `TEXT` value, rejecting bare separators and unknown
+    /// escapes
+    fn unescape_text(value: &str) -> std::result::Result<String, String> {
+        let mut text = String::new();
+        let mut chars = value.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some('\\') => text.push('\\'),
+                    Some(';') => text.push(';'),
+                    Some(',') => text.push(','),
+                    Some('n' | 'N') => text.push('\n'),
+                    other => return Err(format!("bad escape \\{:?} in {:?}", other, value)),
+                },
+                ';' | ',' => return Err(format!("unescaped '{}' in {:?}", c, value)),
+                c if c.is_control() && c != '\t' => {
+                    return Err(format!("control character in {:?}", value))
+                }
+                c => text.push(c),
+            }
+        }
+        Ok(text)
+    }
+
+    /// Parse an iCalendar document, checking line breaks, line lengths,
+    /// component nesting, required properties and text escaping
+    pub(crate) fn validate(ics: &str) -> std::result::Result<Component, String> {
+        let body = ics
+            .strip_suffix("\r\n")
+            .ok_or("document must end with CRLF")?;
+        let mut lines: Vec<String> = Vec::new();
+        for line in body.split("\r\n") {
+            if line.contains('\n') || line.contains('\r') {
+                return Err(format!("bare line break in {:?}", line));
+            }
+            if line.len() > MAX_LINE_OCTETS {
+                return Err(format!("line longer than 75 octets: {:?}", line));
+            }
+            match line.strip_prefix([' ', '\t']) {
+                Some(rest) => lines
+                    .last_mut()
+                    .ok_or("document starts with a continuation")?
+                    .push_str(rest),
+                None => lines.push(line.to_string()),
+            }
+        }
+
+        let mut stack: Vec<Component> = Vec::new();
+        let mut root = None;
+        for line in lines {
+            let (name, value) = line
+                .split_once(':')
+                .ok_or_else(|| format!("no ':' in {:?}", line))?;
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+                return Err(format!("bad property name {:?}", name));
+            }
+            match name {
+                "BEGIN" => stack.push(Component {
+                    name: value.to_string(),
+                    ..Default::default()
+                }),
+                "END" => {
+                    let component = stack.pop().ok_or("END without BEGIN")?;
+                    if component.name != value {
+                        return Err(format!("END:{} closes {}", value, component.name));
+                    }
+                    match stack.last_mut() {
+                        Some(parent) => parent.components.push(component),
+                        None if root.is_none() => root = Some(component),
+                        None => return Err("more than one top-level component".to_string()),
+                    }
+                }
+                _ => {
+                    let value = if TEXT_PROPERTIES.contains(&name) {
+                        unescape_text(value)?
+                    } else {
+                        value.to_string()
+                    };
+                    stack
+                        .last_mut()
+                        .ok_or("property outside a component")?
+                        .properties
+                        .entry(name.to_string())
+                        .or_default()
+                        .push(value);
+                }
+            }
+        }
+        if !stack.is_empty() {
+            return Err("unclosed component".to_string());
+        }
+
+        let calendar = root.ok_or("empty document")?;
+        if calendar.name != "VCALENDAR" {
+            return Err(format!("top-level component is {}", calendar.name));
+        }
+        for required in ["VERSION", "PRODID"] {
+            calendar
+                .get(required)
+                .ok_or_else(|| format!("VCALENDAR without {}", required))?;
+        }
+        for event in &calendar.components {
+            for required in ["UID", "DTSTAMP", "DTSTART"] {
+                event
+                    .get(required)
+                    .ok_or_else(|| format!("VEVENT without {}", required))?;
+            }
+            let start = event.get("DTSTART").unwrap();
+            chrono::NaiveDateTime::parse_from_str(start, "%Y%m%dT%H%M%SZ")
+                .map_err(|e| format!("bad DTSTART {:?}: {}", start, e))?;
+        }
+        Ok(calendar)
+    }
+
+    #[test]
+    fn test_escape_text() {
+        assert_eq!(escape_text("plain"), "plain");
+        assert_eq!(escape_text("a,b;c\\d"), "a\\,b\\;c\\\\d");
+        assert_eq!(
+            escape_text("one\ntwo\r\nthree\rfour"),
+            "one\\ntwo\\nthree\\nfour"
+        );
+        assert_eq!(escape_text("bell\u{7}\ttab"), "bell\ttab");
+        for text in ["a,b;c\\d", "x\\n", "one\ntwo", "\\;,"] {
+            assert_eq!(unescape_text(&escape_text(text)).unwrap(), text);
+        }
+    }
+
+    #[test]
+    fn test_fold_lines() {
+        let mut out = String::new();
+        let line = format!("DESCRIPTION:{}", "é".repeat(60));
+        push_line(&mut out, &line);
+        let physical: Vec<&str> = out.trim_end_matches("\r\n").split("\r\n").collect();
+        assert!(physical.len() > 1);
+        assert!(physical.iter().all(|line| line.len() <= MAX_LINE_OCTETS));
+        assert!(physical[1..].iter().all(|line| line.starts_with(' ')));
+        let unfolded: String = physical
+            .iter()
+            .enumerate()
+            .map(|(i, line)| if i == 0 { *line } else { &line[1..] })
+            .collect();
+        assert_eq!(unfolded, line);
+
+        let mut out = String::new();
+        push_line(&mut out, &"x".repeat(MAX_LINE_OCTETS));
+        assert_eq!(out.len(), MAX_LINE_OCTETS + 2);
+    }
+
+    #[test]
+    fn test_validator_rejects() {
+        let wrap = |event: &str| {
+            format!(
+                "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:x\r\nBEGIN:VEVENT\r\n\
+                 UID:1\r\nDTSTAMP:20300101T000000Z\r\nDTSTART:20300101T000000Z\r\n\
+                 {}END:VEVENT\r\nEND:VCALENDAR\r\n",
+                event
+            )
+        };
+        assert!(validate(&wrap("")).is_ok());
+        assert!(validate(&wrap("SUMMARY:a\\, b\r\n")).is_ok());
+        assert!(validate(&wrap("SUMMARY:a, b\r\n")).is_err());
+        assert!(validate(&wrap("SUMMARY:a; b\r\n")).is_err());
+        assert!(validate(&wrap("SUMMARY:a\\x\r\n")).is_err());
+        assert!(validate(&wrap("SUMMARY:a\nb\r\n")).is_err());
+        assert!(validate(&wrap(&format!("SUMMARY:{}\r\n", "a".repeat(80)))).is_err());
+        assert!(validate(&wrap("END:VTODO\r\n")).is_err());
+        assert!(validate("BEGIN:VCALENDAR\r\nVERSION:2.0\r\nEND:VCALENDAR\r\n").is_err());
+    }
+
+    #[test]
+    fn test_render_calendar() {
+        let stamp = DateTime::parse_from_rfc3339("2030-01-01T01:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut job = CronJob::new(
+            "backup; nightly, all",
+            "0 2 * * *",
+            "tar czf - /data | upload --to s3://bucket,eu\necho done",
+        );
+        job.tags = vec!["env:prod".to_string(), "team,ops".to_string()];
+        job.expected_duration_ms = Some(600_000);
+        let runs = vec![
+            (job.clone(), stamp + chrono::Duration::hours(1)),
+            (job.clone(), stamp + chrono::Duration::hours(25)),
+        ];
+
+        let ics = render_calendar(&runs, stamp);
+        let calendar = validate(&ics).unwrap();
+        assert_eq!(calendar.get("PRODID"), Some(PRODID));
+        assert_eq!(calendar.components.len(), 2);
+        let event = &calendar.components[0];
+        assert_eq!(event.name, "VEVENT");
+        assert_eq!(event.get("SUMMARY"), Some("backup; nightly, all"));
+        assert_eq!(
+            event.get("DESCRIPTION").unwrap(),
+            format!("{}\nJob ID: {}", job.command, job.id)
+        );
+        assert_eq!(event.get("DTSTART"), Some("20300101T020000Z"));
+        assert_eq!(event.get("DTSTAMP"), Some("20300101T010000Z"));
+        assert_eq!(event.get("DURATION"), Some("PT600S"));
+        assert_eq!(event.get("CATEGORIES"), Some("env:prod,team\\,ops"));
+        assert_ne!(event.get("UID"), calendar.components[1].get("UID"));
+
+        let empty = render_calendar(&[], stamp);
+        assert!(validate(&empty).unwrap().components.is_empty());
+    }
+}
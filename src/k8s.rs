@@ -0,0 +1,573 @@
+//! Kubernetes CronJob export
+//!
+//! Renders a job as a `batch/v1` CronJob manifest. The manifest is built
+//! from typed structs and serialized as YAML, so the output is valid YAML
+//! whatever the job contains:
+//!
+//! ```text
+//! apiVersion: batch/v1
+//! kind: CronJob
+//! metadata:
+//!   name: nightly-backup
+//! spec:
+//!   schedule: "0 2 * * *"
+//!   timeZone: Etc/UTC
+//!   ...
+//! ```
+//!
+//! Shell commands run as `[shell, "-c"]` with the command as `args`; exec
+//! jobs run their program with their arguments. Schedules keep the standard
+//! five fields and are evaluated in UTC, like the scheduler does. Schedules
+//! using `L`, `W` or the `&` both-days prefix have no Kubernetes equivalent
+//! and are rejected, as are one-shot and trigger-only jobs, agent and native
+//! jobs, templated jobs and the `Queue` concurrency policy.
+
+use crate::parser::CronExpression;
+use crate::types::{ConcurrencyPolicy, CronError, CronJob, JobType, Result, ScheduleKind};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Longest CronJob name; Kubernetes appends 11 characters for the Jobs it
+/// creates, which must stay within 63
+pub const MAX_CRONJOB_NAME_LEN: usize = 52;
+
+/// Settings for [`CronManager::export_k8s_cronjob`](crate::CronManager::export_k8s_cronjob)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct K8sExportOptions {
+    /// Namespace of the CronJob; the current one when unset
+    pub namespace: Option<String>,
+    /// Labels added to the CronJob
+    pub labels: BTreeMap<String, String>,
+    /// Retries of a failed run; the job's own `max_retries` when unset
+    pub backoff_limit: Option<u32>,
+}
+
+impl K8sExportOptions {
+    /// Create default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the namespace
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    /// Add a label
+    pub fn with_label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.labels.insert(key.into(), value.into());
+        self
+    }
+
+    /// Set the number of retries of a failed run
+    pub fn with_backoff_limit(mut self, backoff_limit: u32) -> Self {
+        self.backoff_limit = Some(backoff_limit);
+        self
+    }
+}
+
+/// A `batch/v1` CronJob
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CronJobManifest {
+    /// Always `batch/v1`
+    pub api_version: String,
+    /// Always `CronJob`
+    pub kind: String,
+    /// Name, namespace and labels
+    pub metadata: ObjectMeta,
+    /// Schedule and the Job to create
+    pub spec: CronJobSpec,
+}
+
+/// Object metadata
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ObjectMeta {
+    /// RFC 1123 name
+    pub name: String,
+    /// Namespace
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    /// Labels
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+}
+
+/// CronJob spec
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CronJobSpec {
+    /// Five-field cron schedule
+    pub schedule: String,
+    /// Time zone the schedule is evaluated in
+    pub time_zone: String,
+    /// `Allow`, `Forbid` or `Replace`
+    pub concurrency_policy: String,
+    /// Whether runs are suspended
+    pub suspend: bool,
+    /// Job created for each run
+    pub job_template: JobTemplateSpec,
+}
+
+/// Template of the Job created for each run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobTemplateSpec {
+    /// Job spec
+    pub spec: JobSpec,
+}
+
+/// Job spec
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobSpec {
+    /// Longest a run may take, in seconds
+    pub active_deadline_seconds: u64,
+    /// Retries of a failed run
+    pub backoff_limit: u32,
+    /// Pod running the command
+    pub template: PodTemplateSpec,
+}
+
+/// Template of the Pod running the command
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PodTemplateSpec {
+    /// Pod spec
+    pub spec: PodSpec,
+}
+
+/// Pod spec
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PodSpec {
+    /// Always `Never`; retries are left to the Job's `backoffLimit`
+    pub restart_policy: String,
+    /// The single container running the command
+    pub containers: Vec<Container>,
+}
+
+/// Container running the command
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Container {
+    /// RFC 1123 name
+    pub name: String,
+    /// Image
+    pub image: String,
+    /// Entrypoint
+    pub command: Vec<String>,
+    /// Arguments to the entrypoint
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// Environment variables
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env: Vec<EnvVar>,
+    /// Working directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<String>,
+}
+
+/// Environment variable
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvVar {
+    /// Name
+    pub name: String,
+    /// Value
+    pub value: String,
+}
+
+impl CronJobManifest {
+    /// Build the CronJob running `job` in `image`. Shell commands run through
+    /// the job's shell, or `default_shell` when it has none. Secret env
+    /// values are masked, so supply them separately, e.g. from a Secret.
+    pub fn from_job(
+        job: &CronJob,
+        image: &str,
+        default_shell: &str,
+        options: &K8sExportOptions,
+    ) -> Result<Self> {
+        if job.job_type != JobType::Shell {
+            return Err(CronError::InvalidConfig(format!(
+                "{} job {} needs the scheduler to run and can't be exported to Kubernetes",
+                job.job_type, job.name
+            )));
+        }
+        if job.templated {
+            return Err(CronError::InvalidConfig(format!(
+                "templated job {} can't be exported to Kubernetes",
+                job.name
+            )));
+        }
+        if image.trim().is_empty() {
+            return Err(CronError::InvalidConfig(
+                "image must not be empty".to_string(),
+            ));
+        }
+        let schedule = match &job.schedule_kind {
+            ScheduleKind::Cron => k8s_schedule(&CronExpression::parse(&job.schedule)?)?,
+            _ => {
+                return Err(CronError::InvalidConfig(format!(
+                    "job {} has no recurring schedule to export to Kubernetes",
+                    job.name
+                )))
+            }
+        };
+        let concurrency_policy = match job.concurrency_policy {
+            ConcurrencyPolicy::Allow => "Allow",
+            ConcurrencyPolicy::Forbid => "Forbid",
+            ConcurrencyPolicy::Replace => "Replace",
+            ConcurrencyPolicy::Queue { .. } => {
+                return Err(CronError::InvalidConfig(format!(
+                    "job {} queues overlapping runs, which Kubernetes can't do",
+                    job.name
+                )))
+            }
+        };
+        let job = job.redacted();
+
+        let (command, args) = if job.is_exec() {
+            (vec![job.program.clone()], job.args.clone())
+        } else {
+            let shell = job.shell.as_deref().unwrap_or(default_shell);
+            (
+                vec![shell.to_string(), "-c".to_string()],
+                vec![job.command.clone()],
+            )
+        };
+        let name = rfc1123_name(&job.name, MAX_CRONJOB_NAME_LEN);
+        let container = Container {
+            name: rfc1123_name(&job.name, 63),
+            image: image.to_string(),
+            command,
+            args,
+            env: job
+                .env
+                .iter()
+                .map(|(name, value)| EnvVar {
+                    name: name.clone(),
+                    value: value.clone(),
+                })
+                .collect(),
+            working_dir: job.working_dir.clone(),
+        };
+
+        Ok(Self {
+            api_version: "batch/v1".to_string(),
+            kind: "CronJob".to_string(),
+            metadata: ObjectMeta {
+                name,
+                namespace: options.namespace.clone(),
+                labels: options.labels.clone(),
+            },
+            spec: CronJobSpec {
+                schedule,
+                time_zone: "Etc/UTC".to_string(),
+                concurrency_policy: concurrency_policy.to_string(),
+                suspend: job.is_paused() || job.is_disabled(),
+                job_template: JobTemplateSpec {
+                    spec: JobSpec {
+                        active_deadline_seconds: job
+                            .effective_timeout_ms
+                            .unwrap_or_else(|| job.resolve_timeout_ms(None))
+                            .div_ceil(1000)
+                            .max(1),
+                        backoff_limit: options.backoff_limit.unwrap_or(job.max_retries),
+                        template: PodTemplateSpec {
+                            spec: PodSpec {
+                                restart_policy: "Never".to_string(),
+                                containers: vec![container],
+                            },
+                        },
+                    },
+                },
+            },
+        })
+    }
+
+    /// Serialize as a YAML document
+    pub fn to_yaml(&self) -> Result<String> {
+        let mut out = String::new();
+        write_yaml(&serde_json::to_value(self)?, 0, &mut out);
+        Ok(out)
+    }
+}
+
+/// Convert a cron expression into a Kubernetes schedule, which has the
+/// standard five fields and no `L`, `W` or `&`
+///
+/// # Examples
+///
+/// ```
+/// use a3s_cron::k8s::k8s_schedule;
+/// use a3s_cron::CronExpression;
+///
+/// let expr = CronExpression::parse("*/15 9-17 * * MON-FRI").unwrap();
+/// assert_eq!(k8s_schedule(&expr).unwrap(), "*/15 9-17 * * 1-5");
+/// ```
+pub fn k8s_schedule(expr: &CronExpression) -> Result<String> {
+    if let Some(month_day) = expr.month_days().first() {
+        return Err(CronError::InvalidExpression(format!(
+            "'{}' uses {}, which Kubernetes schedules can't express",
+            expr.expression, month_day
+        )));
+    }
+    let [minutes, hours, days, months, weekdays] = expr.fields();
+
+    // Kubernetes, like cron, lets either day field match when neither
+    // starts with `*`, and requires both to match otherwise
+    let (days, weekdays) = if expr.matches_either_day() {
+        if days.len() == 31 || weekdays.len() == 7 {
+            ("*".to_string(), "*".to_string())
+        } else {
+            (render_list(days), render_list(weekdays))
+        }
+    } else {
+        let days = render_values(days, 1, 31);
+        let weekdays = render_values(weekdays, 0, 6);
+        if !days.starts_with('*') && !weekdays.starts_with('*') {
+            return Err(CronError::InvalidExpression(format!(
+                "'{}' requires both day fields to match, which Kubernetes schedules can't express",
+                expr.expression
+            )));
+        }
+        (days, weekdays)
+    };
+
+    Ok(format!(
+        "{} {} {} {} {}",
+        render_values(minutes, 0, 59),
+        render_values(hours, 0, 23),
+        days,
+        render_values(months, 1, 12),
+        weekdays
+    ))
+}
+
+/// Turn a job name into an RFC 1123 label of at most `max_len` characters:
+/// lower-case letters, digits and `-`, starting and ending alphanumeric
+pub fn rfc1123_name(name: &str, max_len: usize) -> String {
+    let mut out = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            out.push(c.to_ascii_lowercase());
+        } else if !out.is_empty() && !out.ends_with('-') {
+            out.push('-');
+        }
+    }
+    out.truncate(max_len);
+    let out = out.trim_end_matches('-');
+    if out.is_empty() {
+        "job".to_string()
+    } else {
+        out.to_string()
+    }
+}
+
+/// Render a field's values as `*`, `*/step` or a list of values and ranges
+fn render_values(values: &BTreeSet<u32>, min: u32, max: u32) -> String {
+    if values.len() as u32 == max - min + 1 {
+        return "*".to_string();
+    }
+    let list: Vec<u32> = values.iter().copied().collect();
+    if list.len() >= 2 && list[0] == min {
+        let step = list[1] - list[0];
+        let even = list.windows(2).all(|pair| pair[1] - pair[0] == step);
+        if even && list[list.len() - 1] + step > max {
+            return format!("*/{}", step);
+        }
+    }
+    render_list(values)
+}
+
+/// Render values as a list of values and ranges (`1,5-9`)
+fn render_list(values: &BTreeSet<u32>) -> String {
+    let mut runs: Vec<(u32, u32)> = Vec::new();
+    for &value in values {
+        match runs.last_mut() {
+            Some((_, end)) if *end + 1 == value => *end = value,
+            _ => runs.push((value, value)),
+        }
+    }
+    runs.into_iter()
+        .map(|(start, end)| match end - start {
+            0 => start.to_string(),
+            1 => format!("{},{}", start, end),
+            _ => format!("{}-{}", start, end),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Write `value` as block-style YAML indented by `indent` spaces
+fn write_yaml(value: &Value, indent: usize, out: &mut String) {
+    let pad = " ".repeat(indent);
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                out.push_str(&format!("{}{}:", pad, yaml_string(key)));
+                match value {
+                    Value::Object(map) if !map.is_empty() => {
+                        out.push('\n');
+                        write_yaml(value, indent + 2, out);
+                    }
+                    Value::Array(items) if !items.is_empty() => {
+                        out.push('\n');
+                        write_yaml(value, indent, out);
+                    }
+                    _ => out.push_str(&format!(" {}\n", yaml_scalar(value))),
+                }
+            }
+        }
+        Value::Array(items) if !items.is_empty() => {
+            for item in items {
+                // Render the item one level deeper, then put the dash in
+                // place of the indentation of its first line
+                let mut nested = String::new();
+                write_yaml(item, indent + 2, &mut nested);
+                out.push_str(&format!("{}- {}", pad, &nested[indent + 2..]));
+            }
+        }
+        _ => out.push_str(&format!("{}{}\n", pad, yaml_scalar(value))),
+    }
+}
+
+/// A scalar, or an empty map or list, in flow style
+fn yaml_scalar(value: &Value) -> String {
+    match value {
+        Value::String(s) => yaml_string(s),
+        Value::Object(_) => "{}".to_string(),
+        Value::Array(_) => "[]".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// A string, double-quoted unless it reads as a plain YAML string
+fn yaml_string(s: &str) -> String {
+    let plain = s
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '/')
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./".contains(c))
+        && !matches!(
+            s.to_ascii_lowercase().as_str(),
+            "true" | "false" | "yes" | "no" | "on" | "off" | "null" | "y" | "n"
+        );
+    if plain {
+        s.to_string()
+    } else {
+        // JSON strings are valid YAML double-quoted scalars
+        Value::String(s.to_string()).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::JobStatus;
+
+    fn schedule(expression: &str) -> Result<String> {
+        k8s_schedule(&CronExpression::parse(expression).unwrap())
+    }
+
+    #[test]
+    fn test_k8s_schedule() {
+        assert_eq!(schedule("* * * * *").unwrap(), "* * * * *");
+        assert_eq!(schedule("*/15 * * * *").unwrap(), "*/15 * * * *");
+        assert_eq!(schedule("0 2 * JAN,MAR,JUL SUN").unwrap(), "0 2 * 1,3,7 0");
+        assert_eq!(schedule("0 2 * JAN,JUL *").unwrap(), "0 2 * */6 *");
+        assert_eq!(schedule("0 0 * * 7").unwrap(), "0 0 * * 0");
+        assert_eq!(
+            schedule("0-30/10 5/5 * * *").unwrap(),
+            "0,10,20,30 5,10,15,20 * * *"
+        );
+        assert_eq!(schedule("0 0 */2 * 1-5").unwrap(), "0 0 */2 * 1-5");
+        // Either day field may match
+        assert_eq!(schedule("0 0 13 * 5").unwrap(), "0 0 13 * 5");
+        assert_eq!(schedule("0 0 1-31 * 5").unwrap(), "0 0 * * *");
+
+        for expression in ["0 0 L * *", "0 0 15W * *", "0 0 13 * &5"] {
+            assert!(matches!(
+                schedule(expression),
+                Err(CronError::InvalidExpression(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_rfc1123_name() {
+        assert_eq!(rfc1123_name("Nightly Backup", 52), "nightly-backup");
+        assert_eq!(rfc1123_name("--db__sync.v2--", 52), "db-sync-v2");
+        assert_eq!(rfc1123_name("日本", 52), "job");
+        let long = "a".repeat(50) + "-bcd";
+        assert_eq!(rfc1123_name(&long, 52), "a".repeat(50) + "-b");
+        assert_eq!(rfc1123_name(&("a".repeat(51) + "-b"), 52), "a".repeat(51));
+    }
+
+    #[test]
+    fn test_yaml_strings() {
+        assert_eq!(yaml_string("batch/v1"), "batch/v1");
+        assert_eq!(yaml_string("0 2 * * *"), "\"0 2 * * *\"");
+        assert_eq!(yaml_string("yes"), "\"yes\"");
+        assert_eq!(yaml_string("60"), "\"60\"");
+        assert_eq!(yaml_string("a: b\nc"), "\"a: b\\nc\"");
+    }
+
+    #[test]
+    fn test_snapshot_shell_job() {
+        let mut job = CronJob::new("Nightly Backup", "30 2 * * 1-5", "backup.sh --full | gzip")
+            .with_env("TARGET", "s3://backups/db")
+            .with_env("RETRIES", "3")
+            .with_working_dir("/srv/backup")
+            .with_retries(2, 1_000, 2.0)
+            .with_concurrency_policy(ConcurrencyPolicy::Forbid);
+        job.timeout_ms = Some(90_500);
+        let options = K8sExportOptions::new()
+            .with_namespace("batch")
+            .with_label("team", "data");
+        let manifest =
+            CronJobManifest::from_job(&job, "registry.local/backup:1.2", "sh", &options).unwrap();
+        assert_eq!(
+            manifest.to_yaml().unwrap(),
+            include_str!("../testdata/k8s/nightly-backup.yaml")
+        );
+    }
+
+    #[test]
+    fn test_snapshot_exec_job() {
+        let mut job = CronJob::new("report", "*/10 8-18 13 * FRI", "").with_exec(
+            "/usr/bin/report",
+            vec!["--title".to_string(), "Friday the 13th".to_string()],
+        );
+        job.status = JobStatus::Paused;
+        let manifest =
+            CronJobManifest::from_job(&job, "report:latest", "sh", &K8sExportOptions::new())
+                .unwrap();
+        assert_eq!(
+            manifest.to_yaml().unwrap(),
+            include_str!("../testdata/k8s/report.yaml")
+        );
+    }
+
+    #[test]
+    fn test_from_job_rejects_unsupported_jobs() {
+        let options = K8sExportOptions::new();
+        let mut agent = CronJob::new("agent", "0 * * * *", "summarize");
+        agent.job_type = JobType::Agent;
+        assert!(matches!(
+            CronJobManifest::from_job(&agent, "img", "sh", &options),
+            Err(CronError::InvalidConfig(msg)) if msg.contains("agent job agent")
+        ));
+
+        let never = CronJob::trigger_only("hook", "echo");
+        assert!(CronJobManifest::from_job(&never, "img", "sh", &options).is_err());
+
+        let queued = CronJob::new("queued", "* * * * *", "echo")
+            .with_concurrency_policy(ConcurrencyPolicy::Queue { max_queue: 1 });
+        assert!(CronJobManifest::from_job(&queued, "img", "sh", &options).is_err());
+
+        let job = CronJob::new("ok", "* * * * *", "echo");
+        assert!(CronJobManifest::from_job(&job, " ", "sh", &options).is_err());
+    }
+}
@@ -3,8 +3,19 @@
 //! Provides cron-based task scheduling with:
 //! - Standard 5-field cron syntax parsing
 //! - Natural language schedule parsing (English & Chinese)
+//! - Typed schedule builder as an alternative to expression strings
+//! - Holiday calendars excluding dates from schedules
+//! - Crontab import and export, and an iCalendar feed of upcoming runs
+//! - Declarative manifests, reloadable on SIGHUP (`signals` feature)
 //! - Task persistence and monitoring
+//! - Notifications of failed jobs, e.g. by webhook or email
+//! - Middleware hooks run around every execution
 //! - CRUD operations for job management
+//! - Optional HTTP management API (`http` feature)
+//! - Optional JSON-RPC control socket (`control` feature, Unix only)
+//! - Optional gRPC service (`grpc` feature)
+//! - OpenTelemetry metrics, or Prometheus text exposition (`prometheus` feature)
+//! - Several workspaces' managers run together behind one service
 //!
 //! ## Quick Start
 //!
@@ -28,15 +39,69 @@
 //! manager.start().await?;
 //! ```
 
+mod audit;
+mod builder;
+mod calendar;
+mod clock;
+#[cfg(all(unix, feature = "control"))]
+pub mod control;
+pub mod crontab;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(feature = "http")]
+pub mod http;
+pub mod ics;
+pub mod k8s;
+mod middleware;
 pub mod natural;
+mod notifier;
+mod output;
 mod parser;
+mod process;
+mod runner;
+mod schedule;
 mod scheduler;
+mod service;
 mod store;
+pub mod systemd;
 pub mod telemetry;
+mod template;
 mod types;
+mod window;
 
+pub use builder::CronManagerBuilder;
+pub use calendar::Calendar;
+pub use clock::{Clock, MockClock, SystemClock};
+pub use k8s::K8sExportOptions;
+pub use middleware::{JobMiddleware, SkipReason};
 pub use natural::parse_natural;
-pub use parser::CronExpression;
-pub use scheduler::{CronManager, SchedulerEvent};
-pub use store::{CronStore, FileCronStore, MemoryCronStore};
-pub use types::{AgentExecutor, AgentJobConfig, CronError, CronJob, ExecutionStatus, JobExecution, JobStatus, JobType, Result};
+#[cfg(feature = "webhook")]
+pub use notifier::WebhookNotifier;
+#[cfg(feature = "email")]
+pub use notifier::{EmailNotifier, SmtpSecurity};
+pub use notifier::{Notification, NotificationKind, Notifier};
+pub use parser::{CronExpression, DayMatch, MonthDay};
+pub use schedule::{CronField, ScheduleBuilder};
+pub use scheduler::{
+    CronManager, EventEnvelope, RunOptions, SchedulerEvent, SchedulerHealth, SchedulerState,
+    DEFAULT_EVENT_CAPACITY, DEFAULT_HEARTBEAT_INTERVAL, DEFAULT_STALL_TICKS, DEFAULT_TICK_INTERVAL,
+    MAX_EVENT_ERROR_LEN, MAX_PREVIEW_COUNT, MAX_SIMULATED_RUNS, SIGNAL_SHUTDOWN_TIMEOUT,
+    STATS_WINDOW,
+};
+pub use service::{CronService, WorkspaceEvent};
+pub use store::{
+    CronStore, EventFilter, ExecutionFilter, FileCronStore, MemoryCronStore, MEMORY_EVENT_LIMIT,
+};
+pub use systemd::SystemdUnit;
+pub use types::{
+    AgentExecutor, AgentJobConfig, BlackoutPolicy, BulkResult, CommandKind, ConcurrencyPolicy,
+    Coordination, CronError, CronJob, DurationStats, EnvMode, ExecutionStatus, HealthThresholds,
+    ImportMode, ImportReport, JobContext, JobExecution, JobFilter, JobHealth, JobManifest,
+    JobOptions, JobOutput, JobStats, JobStatus, JobType, JobsExport, ManifestJob, ManifestPlan,
+    MisfirePolicy, NativeJobHandler, OutputFiles, OutputStorage, OutputStream, QueuedExecution,
+    ResourceLimits, Result, RetentionPolicy, RunningExecution, ScheduleInfo, ScheduleKind,
+    SimulatedRun, Termination, TriggerKind, UpdateJobRequest, DEFAULT_ANOMALY_THRESHOLD,
+    DEFAULT_MAX_LOG_BYTES, DEFAULT_SHELL, DEFAULT_TIMEOUT_MS, EXPORT_VERSION, MAX_TAG_LEN,
+    MIN_ANOMALY_SAMPLES, NEVER_SCHEDULE, SECRET_MASK,
+};
+pub use window::TimeWindow;
@@ -0,0 +1,48 @@
+//! Execution middleware
+//!
+//! Middlewares added to a manager see every attempt of every job, whether
+//! scheduled, started by hand or retried, in the order they were added.
+//! `before` can veto an attempt, which is then recorded as skipped; `after`
+//! runs once the attempt has been saved and can't change its outcome.
+
+use crate::types::{CronJob, JobExecution, Result};
+use async_trait::async_trait;
+
+/// Why a middleware vetoed an execution, recorded on the skipped execution
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkipReason(pub String);
+
+impl SkipReason {
+    /// Create a skip reason
+    pub fn new(reason: impl Into<String>) -> Self {
+        Self(reason.into())
+    }
+}
+
+impl std::fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Hook run around job executions
+#[async_trait]
+pub trait JobMiddleware: Send + Sync {
+    /// Name used in logs
+    fn name(&self) -> &str;
+
+    /// Called before an attempt starts. Returning a reason skips it.
+    async fn before(
+        &self,
+        _job: &CronJob,
+        _execution: &JobExecution,
+    ) -> std::result::Result<(), SkipReason> {
+        Ok(())
+    }
+
+    /// Called once a finished attempt has been saved. Errors are logged and
+    /// never fail the job.
+    async fn after(&self, _job: &CronJob, _execution: &JobExecution) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,1010 @@
+//! Job notifications
+//!
+//! Notifiers are told about failed, timed-out, auto-paused and completed
+//! jobs. Each notifier added to a manager gets its own bounded queue and
+//! delivery task, so a slow or unreachable endpoint never holds up job
+//! execution; notifications that don't fit in the queue are dropped.
+
+use crate::scheduler::{EventEnvelope, SchedulerEvent};
+use crate::store::CronStore;
+use crate::types::{ExecutionStatus, JobExecution};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::{broadcast, mpsc};
+use tokio::time::Duration;
+
+#[cfg(feature = "email")]
+pub use email::{EmailNotifier, SmtpSecurity};
+#[cfg(feature = "webhook")]
+pub use webhook::WebhookNotifier;
+
+/// Notifications waiting for delivery to one notifier
+const QUEUE_SIZE: usize = 100;
+
+/// Delivery attempts per notification
+const DELIVERY_ATTEMPTS: u32 = 3;
+
+/// Delay before the first retry of a failed delivery, doubled for each retry
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Characters of stderr included in a notification
+const STDERR_EXCERPT_LEN: usize = 1_000;
+
+/// Trailing lines of stderr included in a notification
+const STDERR_TAIL_LINES: usize = 50;
+
+/// Kind of job event a notification reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    /// A run failed after any retries
+    Failed,
+    /// A run timed out after any retries
+    Timeout,
+    /// A job was paused after consecutive failures
+    AutoPaused,
+    /// A run succeeded
+    Completed,
+}
+
+impl std::fmt::Display for NotificationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NotificationKind::Failed => write!(f, "failed"),
+            NotificationKind::Timeout => write!(f, "timeout"),
+            NotificationKind::AutoPaused => write!(f, "auto_paused"),
+            NotificationKind::Completed => write!(f, "completed"),
+        }
+    }
+}
+
+/// A job event delivered to notifiers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Notification {
+    /// What happened
+    pub event: NotificationKind,
+
+    /// Job ID
+    pub job_id: String,
+
+    /// Job name
+    pub job_name: String,
+
+    /// Execution the event is about, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execution_id: Option<String>,
+
+    /// Execution status
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<ExecutionStatus>,
+
+    /// Execution duration in milliseconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+
+    /// Exit code of the execution's command, if it exited
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+
+    /// Error message of a failed execution
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+
+    /// Start of the execution's stderr
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stderr: Option<String>,
+
+    /// Last lines of the execution's stderr, where errors usually end up
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stderr_tail: Option<String>,
+
+    /// Whether a successful run's output differs from the previous one's,
+    /// for jobs with `notify_on_change` set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_changed: Option<bool>,
+
+    /// When the notification was created
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Notification {
+    /// Create a notification about a job, named after its ID until
+    /// `job_name` is set
+    pub fn new(event: NotificationKind, job_id: impl Into<String>) -> Self {
+        let job_id = job_id.into();
+        Self {
+            event,
+            job_name: job_id.clone(),
+            job_id,
+            execution_id: None,
+            status: None,
+            duration_ms: None,
+            exit_code: None,
+            error: None,
+            stderr: None,
+            stderr_tail: None,
+            output_changed: None,
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// Fill in the details of the execution the notification is about
+    pub fn with_execution(mut self, execution: &JobExecution) -> Self {
+        self.execution_id = Some(execution.id.clone());
+        self.status = Some(execution.status);
+        self.duration_ms = execution.duration_ms;
+        self.exit_code = execution.exit_code;
+        self.error = execution.error.clone();
+        self.stderr = stderr_excerpt(&execution.stderr);
+        self.stderr_tail = stderr_tail(&execution.stderr);
+        self.output_changed = execution.output_changed;
+        self
+    }
+}
+
+/// Receiver of job notifications
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    /// Name used in logs
+    fn name(&self) -> &str;
+
+    /// Whether the notifier wants notifications of this kind
+    fn accepts(&self, _kind: NotificationKind) -> bool {
+        true
+    }
+
+    /// Whether the notifier wants this notification, e.g. only completions
+    /// whose `output_changed` is set. Defaults to [`accepts`](Self::accepts)
+    /// of its kind.
+    fn wants(&self, notification: &Notification) -> bool {
+        self.accepts(notification.event)
+    }
+
+    /// Deliver a notification. Failed deliveries are retried.
+    async fn notify(&self, notification: &Notification) -> std::result::Result<(), String>;
+}
+
+/// Start delivering scheduler events to `notifier`
+pub(crate) fn spawn(
+    notifier: Arc<dyn Notifier>,
+    store: Arc<dyn CronStore>,
+    mut events: broadcast::Receiver<EventEnvelope>,
+) {
+    let (tx, mut rx) = mpsc::channel::<Notification>(QUEUE_SIZE);
+
+    let name = notifier.name().to_string();
+    let filter = notifier.clone();
+    tokio::spawn(async move {
+        loop {
+            let event = match events.recv().await {
+                Ok(envelope) => envelope.kind,
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    tracing::warn!("Notifier {} missed {} events", name, missed);
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+            let Some(notification) = notification_for(&*store, event).await else {
+                continue;
+            };
+            if !filter.wants(&notification) {
+                continue;
+            }
+            if tx.try_send(notification).is_err() {
+                tracing::warn!("Notifier {} queue is full, dropping notification", name);
+            }
+        }
+    });
+
+    tokio::spawn(async move {
+        while let Some(notification) = rx.recv().await {
+            deliver(&*notifier, &notification).await;
+        }
+    });
+}
+
+/// Deliver a notification, retrying with exponential backoff
+async fn deliver(notifier: &dyn Notifier, notification: &Notification) {
+    let mut delay = RETRY_DELAY;
+    for attempt in 1..=DELIVERY_ATTEMPTS {
+        match notifier.notify(notification).await {
+            Ok(()) => return,
+            Err(e) if attempt < DELIVERY_ATTEMPTS => {
+                tracing::debug!(
+                    "Notifier {} failed (attempt {}), retrying: {}",
+                    notifier.name(),
+                    attempt,
+                    e
+                );
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+            Err(e) => tracing::warn!(
+                "Notifier {} failed to deliver {} notification for job {}: {}",
+                notifier.name(),
+                notification.event,
+                notification.job_id,
+                e
+            ),
+        }
+    }
+}
+
+/// Build the notification for a scheduler event, if it warrants one
+async fn notification_for(store: &dyn CronStore, event: SchedulerEvent) -> Option<Notification> {
+    let (kind, job_id, job_name, execution_id) = match event {
+        SchedulerEvent::JobCompleted {
+            job_id,
+            job_name,
+            execution_id,
+            ..
+        } => (
+            NotificationKind::Completed,
+            job_id,
+            job_name,
+            Some(execution_id),
+        ),
+        // Timeouts are also reported as failures once retries are exhausted
+        SchedulerEvent::JobFailed {
+            job_id,
+            job_name,
+            execution_id,
+            ..
+        } => (
+            NotificationKind::Failed,
+            job_id,
+            job_name,
+            Some(execution_id),
+        ),
+        SchedulerEvent::JobAutoPaused {
+            job_id, job_name, ..
+        } => (NotificationKind::AutoPaused, job_id, job_name, None),
+        _ => return None,
+    };
+
+    let mut notification = Notification::new(kind, job_id);
+    notification.job_name = job_name;
+    let Some(execution_id) = execution_id else {
+        return Some(notification);
+    };
+    notification.execution_id = Some(execution_id.clone());
+    let execution = store
+        .load_executions(&notification.job_id, 20)
+        .await
+        .ok()?
+        .into_iter()
+        .find(|execution| execution.id == execution_id);
+    if let Some(execution) = execution {
+        notification.event = match execution.status {
+            ExecutionStatus::Success => NotificationKind::Completed,
+            ExecutionStatus::Timeout => NotificationKind::Timeout,
+            ExecutionStatus::Failed => NotificationKind::Failed,
+            // Cancellation is deliberate, not worth a notification
+            ExecutionStatus::Cancelled | ExecutionStatus::Skipped => return None,
+        };
+        notification = notification.with_execution(&execution);
+    }
+    Some(notification)
+}
+
+/// First part of an execution's stderr, if it wrote any
+fn stderr_excerpt(stderr: &str) -> Option<String> {
+    let stderr = stderr.trim();
+    if stderr.is_empty() {
+        return None;
+    }
+    match stderr.char_indices().nth(STDERR_EXCERPT_LEN) {
+        Some((end, _)) => Some(format!("{}...", &stderr[..end])),
+        None => Some(stderr.to_string()),
+    }
+}
+
+/// Last lines of an execution's stderr, if it wrote any
+fn stderr_tail(stderr: &str) -> Option<String> {
+    let stderr = stderr.trim_end();
+    if stderr.trim_start().is_empty() {
+        return None;
+    }
+    let lines: Vec<&str> = stderr.lines().collect();
+    let start = lines.len().saturating_sub(STDERR_TAIL_LINES);
+    Some(lines[start..].join("\n"))
+}
+
+#[cfg(feature = "email")]
+mod email {
+    use super::{Notification, NotificationKind, Notifier};
+    use crate::types::{CronError, Result};
+    use async_trait::async_trait;
+    use lettre::message::header::ContentType;
+    use lettre::message::Mailbox;
+    use lettre::transport::smtp::authentication::Credentials;
+    use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+    use std::fmt::Write;
+    use tokio::time::Duration;
+
+    /// Default time allowed for each SMTP command
+    const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+    /// How the connection to the SMTP server is secured
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    pub enum SmtpSecurity {
+        /// Upgrade a plain connection with STARTTLS, on port 587 by default
+        #[default]
+        StartTls,
+        /// Connect over TLS, on port 465 by default
+        Tls,
+        /// Send in plain text, on port 25 by default; only for relays on a
+        /// trusted network
+        None,
+    }
+
+    /// Notifier that emails each notification over SMTP, with the
+    /// execution's status, duration, exit code and the last lines of its
+    /// stderr in a plain-text body
+    #[derive(Clone)]
+    pub struct EmailNotifier {
+        name: String,
+        host: String,
+        port: Option<u16>,
+        security: SmtpSecurity,
+        credentials: Option<Credentials>,
+        from: Mailbox,
+        to: Vec<Mailbox>,
+        events: Vec<NotificationKind>,
+        changes_only: bool,
+        timeout: Duration,
+    }
+
+    impl EmailNotifier {
+        /// Create a notifier sending from `from` to every address in `to`
+        /// through the SMTP server at `host`, secured with STARTTLS.
+        /// Addresses may include a display name, as in
+        /// `Cron <cron@example.com>`. By default every kind of notification
+        /// except completions is sent.
+        pub fn new(host: &str, from: &str, to: &[&str]) -> Result<Self> {
+            if host.trim().is_empty() {
+                return Err(CronError::InvalidConfig(
+                    "SMTP host must not be empty".to_string(),
+                ));
+            }
+            if to.is_empty() {
+                return Err(CronError::InvalidConfig(
+                    "email notifier needs at least one recipient".to_string(),
+                ));
+            }
+            Ok(Self {
+                name: format!("smtp://{}", host),
+                host: host.to_string(),
+                port: None,
+                security: SmtpSecurity::default(),
+                credentials: None,
+                from: mailbox(from)?,
+                to: to.iter().map(|to| mailbox(to)).collect::<Result<_>>()?,
+                events: vec![
+                    NotificationKind::Failed,
+                    NotificationKind::Timeout,
+                    NotificationKind::AutoPaused,
+                ],
+                changes_only: false,
+                timeout: DEFAULT_TIMEOUT,
+            })
+        }
+
+        /// Connect to this port instead of the security's default
+        pub fn with_port(mut self, port: u16) -> Self {
+            self.port = Some(port);
+            self
+        }
+
+        /// Secure the connection this way instead of with STARTTLS
+        pub fn with_security(mut self, security: SmtpSecurity) -> Self {
+            self.security = security;
+            self
+        }
+
+        /// Log in to the server with these credentials
+        pub fn with_credentials(
+            mut self,
+            username: impl Into<String>,
+            password: impl Into<String>,
+        ) -> Self {
+            self.credentials = Some(Credentials::new(username.into(), password.into()));
+            self
+        }
+
+        /// Only send notifications of these kinds
+        pub fn with_events(mut self, events: &[NotificationKind]) -> Self {
+            self.events = events.to_vec();
+            self
+        }
+
+        /// Drop completions whose output is the same as the previous run's,
+        /// for jobs with `notify_on_change` set
+        pub fn with_changes_only(mut self) -> Self {
+            self.changes_only = true;
+            self
+        }
+
+        /// Set the time allowed for each SMTP command
+        pub fn with_timeout(mut self, timeout: Duration) -> Self {
+            self.timeout = timeout;
+            self
+        }
+
+        fn transport(&self) -> std::result::Result<AsyncSmtpTransport<Tokio1Executor>, String> {
+            let mut builder = match self.security {
+                SmtpSecurity::StartTls => {
+                    AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&self.host)
+                        .map_err(|e| e.to_string())?
+                }
+                SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&self.host)
+                    .map_err(|e| e.to_string())?,
+                SmtpSecurity::None => {
+                    AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&self.host)
+                }
+            };
+            if let Some(port) = self.port {
+                builder = builder.port(port);
+            }
+            if let Some(credentials) = &self.credentials {
+                builder = builder.credentials(credentials.clone());
+            }
+            Ok(builder.timeout(Some(self.timeout)).build())
+        }
+
+        fn message(&self, notification: &Notification) -> std::result::Result<Message, String> {
+            let mut builder = Message::builder()
+                .from(self.from.clone())
+                .subject(subject(notification))
+                .header(ContentType::TEXT_PLAIN);
+            for to in &self.to {
+                builder = builder.to(to.clone());
+            }
+            builder.body(body(notification)).map_err(|e| e.to_string())
+        }
+    }
+
+    fn mailbox(address: &str) -> Result<Mailbox> {
+        address.parse().map_err(|e| {
+            CronError::InvalidConfig(format!("invalid email address '{}': {}", address, e))
+        })
+    }
+
+    fn subject(notification: &Notification) -> String {
+        let what = match notification.event {
+            NotificationKind::Failed => "failed",
+            NotificationKind::Timeout => "timed out",
+            NotificationKind::AutoPaused => "was paused after repeated failures",
+            NotificationKind::Completed => "succeeded",
+        };
+        format!("[cron] Job {} {}", notification.job_name, what)
+    }
+
+    fn body(notification: &Notification) -> String {
+        let mut body = String::new();
+        let _ = writeln!(
+            body,
+            "Job: {} ({})",
+            notification.job_name, notification.job_id
+        );
+        let _ = writeln!(body, "Event: {}", notification.event);
+        if let Some(status) = notification.status {
+            let _ = writeln!(body, "Status: {}", status);
+        }
+        if let Some(duration_ms) = notification.duration_ms {
+            let _ = writeln!(body, "Duration: {} ms", duration_ms);
+        }
+        if let Some(exit_code) = notification.exit_code {
+            let _ = writeln!(body, "Exit code: {}", exit_code);
+        }
+        if let Some(execution_id) = &notification.execution_id {
+            let _ = writeln!(body, "Execution: {}", execution_id);
+        }
+        let _ = writeln!(body, "Time: {}", notification.timestamp.to_rfc3339());
+        if let Some(error) = &notification.error {
+            let _ = writeln!(body, "Error: {}", error);
+        }
+        if let Some(stderr) = &notification.stderr_tail {
+            let _ = write!(body, "\nLast lines of stderr:\n{}\n", stderr);
+        }
+        body
+    }
+
+    #[async_trait]
+    impl Notifier for EmailNotifier {
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        fn accepts(&self, kind: NotificationKind) -> bool {
+            self.events.contains(&kind)
+        }
+
+        fn wants(&self, notification: &Notification) -> bool {
+            self.accepts(notification.event)
+                && !(self.changes_only && notification.output_changed == Some(false))
+        }
+
+        async fn notify(&self, notification: &Notification) -> std::result::Result<(), String> {
+            let message = self.message(notification)?;
+            self.transport()?
+                .send(message)
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::scheduler::CronManager;
+        use crate::store::MemoryCronStore;
+        use std::sync::Arc;
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+        use tokio::net::TcpListener;
+
+        /// Accept one SMTP session and return the message it carried
+        async fn smtp_stub(listener: TcpListener) -> String {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (read, mut write) = stream.into_split();
+            let mut lines = BufReader::new(read).lines();
+            write.write_all(b"220 localhost ESMTP\r\n").await.unwrap();
+            let mut message = String::new();
+            while let Some(line) = lines.next_line().await.unwrap() {
+                let command = line.to_ascii_uppercase();
+                let reply: &[u8] = if command.starts_with("DATA") {
+                    write.write_all(b"354 End data with .\r\n").await.unwrap();
+                    while let Some(line) = lines.next_line().await.unwrap() {
+                        if line == "." {
+                            break;
+                        }
+                        message.push_str(&line);
+                        message.push('\n');
+                    }
+                    b"250 Queued\r\n"
+                } else if command.starts_with("QUIT") {
+                    write.write_all(b"221 Bye\r\n").await.unwrap();
+                    break;
+                } else {
+                    b"250 OK\r\n"
+                };
+                write.write_all(reply).await.unwrap();
+            }
+            message
+        }
+
+        #[test]
+        fn test_config() {
+            assert!(EmailNotifier::new("", "cron@example.com", &["ops@example.com"]).is_err());
+            assert!(EmailNotifier::new("smtp.example.com", "cron@example.com", &[]).is_err());
+            assert!(
+                EmailNotifier::new("smtp.example.com", "not an address", &["ops@example.com"])
+                    .is_err()
+            );
+            let notifier = EmailNotifier::new(
+                "smtp.example.com",
+                "Cron <cron@example.com>",
+                &["ops@example.com"],
+            )
+            .unwrap();
+            assert_eq!(notifier.name(), "smtp://smtp.example.com");
+            assert!(notifier.accepts(NotificationKind::Failed));
+            assert!(!notifier.accepts(NotificationKind::Completed));
+        }
+
+        #[tokio::test]
+        async fn test_failed_job_email() {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let port = listener.local_addr().unwrap().port();
+            let server = tokio::spawn(smtp_stub(listener));
+
+            let notifier =
+                EmailNotifier::new("127.0.0.1", "Cron <cron@example.com>", &["ops@example.com"])
+                    .unwrap()
+                    .with_security(SmtpSecurity::None)
+                    .with_port(port);
+            let store = Arc::new(MemoryCronStore::new());
+            let manager = CronManager::with_store(store, "/tmp".to_string());
+            manager.add_notifier(Arc::new(notifier));
+            let job = manager
+                .add_job(
+                    "backup",
+                    "@never",
+                    "for i in $(seq 1 60); do echo \"backup error $i\" >&2; done; exit 3",
+                )
+                .await
+                .unwrap();
+            manager.run_job(&job.id).await.unwrap();
+
+            let message = tokio::time::timeout(Duration::from_secs(10), server)
+                .await
+                .unwrap()
+                .unwrap();
+            assert!(message.contains("Subject: [cron] Job backup failed"));
+            assert!(message.contains("To: ops@example.com"));
+            assert!(message.contains("Exit code: 3"));
+            assert!(message.contains("Status: failed"));
+            assert!(message.contains("backup error 60"));
+            assert!(message.contains("backup error 11\n"));
+            assert!(!message.contains("backup error 10\n"));
+        }
+    }
+}
+
+#[cfg(feature = "webhook")]
+mod webhook {
+    use super::{Notification, NotificationKind, Notifier};
+    use crate::types::{CronError, Result};
+    use async_trait::async_trait;
+    use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE};
+    use reqwest::{Certificate, Client, Url};
+    use tokio::time::Duration;
+
+    /// Default time allowed for a webhook request
+    const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Notifier that POSTs each notification as JSON to an `http://` or
+    /// `https://` URL
+    #[derive(Debug, Clone)]
+    pub struct WebhookNotifier {
+        url: Url,
+        events: Vec<NotificationKind>,
+        changes_only: bool,
+        headers: HeaderMap,
+        timeout: Duration,
+        /// Roots trusted on top of the bundled web PKI roots
+        root_certificates: Vec<Certificate>,
+        client: Client,
+    }
+
+    impl WebhookNotifier {
+        /// Create a notifier posting to `url`. By default every kind of
+        /// notification except completions is sent.
+        pub fn new(url: &str) -> Result<Self> {
+            let invalid = |reason: &dyn std::fmt::Display| {
+                CronError::InvalidConfig(format!("invalid webhook URL '{}': {}", url, reason))
+            };
+            let url = Url::parse(url).map_err(|e| invalid(&e))?;
+            if !matches!(url.scheme(), "http" | "https") {
+                return Err(invalid(&"only http:// and https:// URLs are supported"));
+            }
+            if url.host_str().is_none_or(str::is_empty) {
+                return Err(invalid(&"missing host"));
+            }
+
+            Ok(Self {
+                url,
+                events: vec![
+                    NotificationKind::Failed,
+                    NotificationKind::Timeout,
+                    NotificationKind::AutoPaused,
+                ],
+                changes_only: false,
+                headers: HeaderMap::new(),
+                timeout: DEFAULT_TIMEOUT,
+                root_certificates: Vec::new(),
+                client: build_client(&[])?,
+            })
+        }
+
+        /// Only send notifications of these kinds
+        pub fn with_events(mut self, events: &[NotificationKind]) -> Self {
+            self.events = events.to_vec();
+            self
+        }
+
+        /// Drop completions whose output is the same as the previous run's,
+        /// for jobs with `notify_on_change` set
+        pub fn with_changes_only(mut self) -> Self {
+            self.changes_only = true;
+            self
+        }
+
+        /// Add a header to every request, e.g. for authorization. Names and
+        /// values that aren't valid in HTTP, such as ones containing line
+        /// breaks, are rejected.
+        pub fn with_header(mut self, name: &str, value: &str) -> Result<Self> {
+            let invalid = || CronError::InvalidConfig(format!("invalid webhook header {:?}", name));
+            let header = HeaderName::from_bytes(name.as_bytes()).map_err(|_| invalid())?;
+            let value = HeaderValue::from_str(value).map_err(|_| invalid())?;
+            self.headers.append(header, value);
+            Ok(self)
+        }
+
+        /// Trust the PEM-encoded root certificate `pem` for HTTPS endpoints,
+        /// e.g. one signed by a private CA
+        pub fn with_root_certificate(mut self, pem: &[u8]) -> Result<Self> {
+            let certificate = Certificate::from_pem(pem).map_err(|e| {
+                CronError::InvalidConfig(format!("invalid webhook root certificate: {}", e))
+            })?;
+            self.root_certificates.push(certificate);
+            self.client = build_client(&self.root_certificates)?;
+            Ok(self)
+        }
+
+        /// Set the time allowed for each request
+        pub fn with_timeout(mut self, timeout: Duration) -> Self {
+            self.timeout = timeout;
+            self
+        }
+    }
+
+    /// Build an HTTP client trusting `root_certificates` besides the web PKI
+    fn build_client(root_certificates: &[Certificate]) -> Result<Client> {
+        root_certificates
+            .iter()
+            .fold(Client::builder(), |builder, certificate| {
+                builder.add_root_certificate(certificate.clone())
+            })
+            .build()
+            .map_err(|e| CronError::InvalidConfig(format!("failed to build webhook client: {}", e)))
+    }
+
+    #[async_trait]
+    impl Notifier for WebhookNotifier {
+        fn name(&self) -> &str {
+            self.url.as_str()
+        }
+
+        fn accepts(&self, kind: NotificationKind) -> bool {
+            self.events.contains(&kind)
+        }
+
+        fn wants(&self, notification: &Notification) -> bool {
+            self.accepts(notification.event)
+                && !(self.changes_only && notification.output_changed == Some(false))
+        }
+
+        async fn notify(&self, notification: &Notification) -> std::result::Result<(), String> {
+            let body = serde_json::to_vec(notification).map_err(|e| e.to_string())?;
+            let response = self
+                .client
+                .post(self.url.clone())
+                .headers(self.headers.clone())
+                .header(CONTENT_TYPE, "application/json")
+                .body(body)
+                .timeout(self.timeout)
+                .send()
+                .await
+                .map_err(|e| {
+                    if e.is_timeout() {
+                        "request timed out".to_string()
+                    } else {
+                        e.to_string()
+                    }
+                })?;
+            let status = response.status();
+            if status.is_success() {
+                Ok(())
+            } else {
+                Err(format!("webhook responded with status {}", status.as_u16()))
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::types::{ExecutionStatus, JobExecution};
+        use std::sync::Arc;
+        use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+        use tokio::net::TcpListener;
+        use tokio_rustls::rustls::pki_types::PrivatePkcs8KeyDer;
+        use tokio_rustls::rustls::{crypto, ServerConfig};
+        use tokio_rustls::TlsAcceptor;
+
+        #[test]
+        fn test_parse_url() {
+            let notifier = WebhookNotifier::new("http://example.com:8080/hooks/cron").unwrap();
+            assert_eq!(notifier.url.host_str(), Some("example.com"));
+            assert_eq!(notifier.url.port_or_known_default(), Some(8080));
+            assert_eq!(notifier.url.path(), "/hooks/cron");
+
+            let notifier = WebhookNotifier::new("http://example.com").unwrap();
+            assert_eq!(notifier.url.port_or_known_default(), Some(80));
+            assert_eq!(notifier.url.path(), "/");
+
+            let notifier = WebhookNotifier::new("https://example.com/hook").unwrap();
+            assert_eq!(notifier.url.port_or_known_default(), Some(443));
+
+            let notifier = WebhookNotifier::new("http://[::1]:8080/hook").unwrap();
+            assert_eq!(notifier.url.host_str(), Some("[::1]"));
+            assert_eq!(notifier.url.port(), Some(8080));
+
+            assert!(WebhookNotifier::new("ftp://example.com/").is_err());
+            assert!(WebhookNotifier::new("http://:80/").is_err());
+            assert!(WebhookNotifier::new("http://example.com:http/").is_err());
+        }
+
+        #[test]
+        fn test_header_validation() {
+            let notifier = WebhookNotifier::new("http://localhost/").unwrap();
+            assert!(notifier.clone().with_header("X-Token", "secret").is_ok());
+            assert!(notifier
+                .clone()
+                .with_header("X-Token", "secret\r\nX-Injected: yes")
+                .is_err());
+            assert!(notifier.clone().with_header("X-Token", "secret\n").is_err());
+            assert!(notifier
+                .clone()
+                .with_header("X-Token\r\nX-Injected", "yes")
+                .is_err());
+            assert!(notifier.with_header("", "value").is_err());
+        }
+
+        /// Accept one request on `stream`, answer it with `status` and
+        /// return its body
+        async fn serve_once<S>(mut stream: S, status: &str) -> String
+        where
+            S: AsyncRead + AsyncWrite + Unpin,
+        {
+            let mut request = Vec::new();
+            let mut chunk = [0u8; 4096];
+            loop {
+                let n = stream.read(&mut chunk).await.unwrap();
+                request.extend_from_slice(&chunk[..n]);
+                let text = String::from_utf8_lossy(&request).to_string();
+                if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                    let head = head.to_ascii_lowercase();
+                    let length: usize = head
+                        .lines()
+                        .find_map(|line| line.strip_prefix("content-length: "))
+                        .unwrap()
+                        .parse()
+                        .unwrap();
+                    if body.len() >= length {
+                        assert!(head.starts_with("post /hook http/1.1"));
+                        assert!(head.contains("authorization: bearer token"));
+                        assert!(head.contains("content-type: application/json"));
+                        let response = format!(
+                            "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                            status
+                        );
+                        stream.write_all(response.as_bytes()).await.unwrap();
+                        stream.shutdown().await.unwrap();
+                        return body.to_string();
+                    }
+                }
+            }
+        }
+
+        fn failed_notification() -> Notification {
+            let execution =
+                JobExecution::new("job-1").complete(2, String::new(), "disk full\n".to_string());
+            let mut notification =
+                Notification::new(NotificationKind::Failed, "job-1").with_execution(&execution);
+            notification.job_name = "backup".to_string();
+            notification
+        }
+
+        #[tokio::test]
+        async fn test_webhook_payload() {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}/hook", listener.local_addr().unwrap());
+            let server = tokio::spawn(async move {
+                let (stream, _) = listener.accept().await.unwrap();
+                serve_once(stream, "204 No Content").await
+            });
+
+            let notifier = WebhookNotifier::new(&url)
+                .unwrap()
+                .with_header("Authorization", "Bearer token")
+                .unwrap();
+            let notification = failed_notification();
+            notifier.notify(&notification).await.unwrap();
+
+            let body: serde_json::Value = serde_json::from_str(&server.await.unwrap()).unwrap();
+            assert_eq!(body["event"], "failed");
+            assert_eq!(body["job_id"], "job-1");
+            assert_eq!(body["job_name"], "backup");
+            assert_eq!(
+                body["execution_id"],
+                notification.execution_id.unwrap().as_str()
+            );
+            assert_eq!(body["status"], "failed");
+            assert!(body["duration_ms"].is_u64());
+            assert_eq!(body["stderr"], "disk full");
+            assert!(body["timestamp"].is_string());
+            assert_eq!(notification.status, Some(ExecutionStatus::Failed));
+        }
+
+        #[tokio::test]
+        async fn test_webhook_error_status() {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}/hook", listener.local_addr().unwrap());
+            let server = tokio::spawn(async move {
+                let (stream, _) = listener.accept().await.unwrap();
+                serve_once(stream, "500 Internal Server Error").await
+            });
+
+            let notifier = WebhookNotifier::new(&url)
+                .unwrap()
+                .with_header("Authorization", "Bearer token")
+                .unwrap();
+            let result = notifier.notify(&failed_notification()).await;
+            assert_eq!(result, Err("webhook responded with status 500".to_string()));
+            server.await.unwrap();
+        }
+
+        #[tokio::test]
+        async fn test_webhook_https() {
+            let certified =
+                rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+            let key = PrivatePkcs8KeyDer::from(certified.key_pair.serialize_der());
+            let config =
+                ServerConfig::builder_with_provider(Arc::new(crypto::ring::default_provider()))
+                    .with_safe_default_protocol_versions()
+                    .unwrap()
+                    .with_no_client_auth()
+                    .with_single_cert(vec![certified.cert.der().clone()], key.into())
+                    .unwrap();
+            let acceptor = TlsAcceptor::from(Arc::new(config));
+
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let port = listener.local_addr().unwrap().port();
+            let server = tokio::spawn(async move {
+                let (stream, _) = listener.accept().await.unwrap();
+                let stream = acceptor.accept(stream).await.unwrap();
+                serve_once(stream, "200 OK").await
+            });
+
+            let notifier = WebhookNotifier::new(&format!("https://localhost:{}/hook", port))
+                .unwrap()
+                .with_header("Authorization", "Bearer token")
+                .unwrap()
+                .with_root_certificate(certified.cert.pem().as_bytes())
+                .unwrap();
+            notifier.notify(&failed_notification()).await.unwrap();
+
+            let body: serde_json::Value = serde_json::from_str(&server.await.unwrap()).unwrap();
+            assert_eq!(body["job_id"], "job-1");
+        }
+
+        #[test]
+        fn test_default_events() {
+            let notifier = WebhookNotifier::new("http://localhost/").unwrap();
+            assert!(notifier.accepts(NotificationKind::Failed));
+            assert!(!notifier.accepts(NotificationKind::Completed));
+            let notifier = notifier.with_events(&[NotificationKind::Completed]);
+            assert!(notifier.accepts(NotificationKind::Completed));
+            assert!(!notifier.accepts(NotificationKind::Failed));
+
+            let notifier = notifier.with_changes_only();
+            let mut completed = Notification::new(NotificationKind::Completed, "job-1");
+            assert!(notifier.wants(&completed));
+            completed.output_changed = Some(true);
+            assert!(notifier.wants(&completed));
+            completed.output_changed = Some(false);
+            assert!(!notifier.wants(&completed));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stderr_excerpt() {
+        assert_eq!(stderr_excerpt("  \n"), None);
+        assert_eq!(stderr_excerpt("oops\n"), Some("oops".to_string()));
+        let long = "é".repeat(STDERR_EXCERPT_LEN + 10);
+        let excerpt = stderr_excerpt(&long).unwrap();
+        assert_eq!(excerpt.chars().count(), STDERR_EXCERPT_LEN + 3);
+        assert!(excerpt.ends_with("..."));
+    }
+
+    #[test]
+    fn test_stderr_tail() {
+        assert_eq!(stderr_tail("\n \n"), None);
+        assert_eq!(stderr_tail("  oops\n"), Some("  oops".to_string()));
+        let stderr: String = (1..=60).map(|i| format!("line {}\n", i)).collect();
+        let tail = stderr_tail(&stderr).unwrap();
+        assert_eq!(tail.lines().count(), STDERR_TAIL_LINES);
+        assert!(tail.starts_with("line 11\n"));
+        assert!(tail.ends_with("line 60"));
+    }
+}
@@ -0,0 +1,114 @@
+//! Execution output files
+//!
+//! When output is stored in files, each execution's stdout and stderr are
+//! written to `<dir>/<job_id>/<execution_id>.out` and `.err`, and the oldest
+//! files are deleted once the directory grows past its size limit.
+
+use crate::types::OutputFiles;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tokio::fs;
+
+/// Write an execution's output to its files
+pub(crate) async fn write(
+    dir: &Path,
+    job_id: &str,
+    execution_id: &str,
+    stdout: &str,
+    stderr: &str,
+) -> io::Result<OutputFiles> {
+    let job_dir = dir.join(job_id);
+    fs::create_dir_all(&job_dir).await?;
+    let stdout_path = job_dir.join(format!("{}.out", execution_id));
+    let stderr_path = job_dir.join(format!("{}.err", execution_id));
+    fs::write(&stdout_path, stdout).await?;
+    fs::write(&stderr_path, stderr).await?;
+    Ok(OutputFiles {
+        stdout_path,
+        stdout_bytes: stdout.len() as u64,
+        stderr_path,
+        stderr_bytes: stderr.len() as u64,
+    })
+}
+
+/// Read an execution's output back from its files
+pub(crate) async fn read(files: &OutputFiles) -> io::Result<(String, String)> {
+    let stdout = fs::read(&files.stdout_path).await?;
+    let stderr = fs::read(&files.stderr_path).await?;
+    Ok((
+        String::from_utf8_lossy(&stdout).into_owned(),
+        String::from_utf8_lossy(&stderr).into_owned(),
+    ))
+}
+
+/// Delete the oldest output files until those under `dir` take at most
+/// `max_total_bytes`
+pub(crate) async fn prune(dir: &Path, max_total_bytes: u64) -> io::Result<()> {
+    let mut files: Vec<(SystemTime, u64, PathBuf)> = Vec::new();
+    let mut total = 0;
+    let mut job_dirs = match fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    while let Some(job_dir) = job_dirs.next_entry().await? {
+        if !job_dir.file_type().await?.is_dir() {
+            continue;
+        }
+        let mut entries = fs::read_dir(job_dir.path()).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let meta = entry.metadata().await?;
+            if meta.is_file() {
+                total += meta.len();
+                files.push((meta.modified()?, meta.len(), entry.path()));
+            }
+        }
+    }
+
+    files.sort();
+    for (_, len, path) in files {
+        if total <= max_total_bytes {
+            break;
+        }
+        fs::remove_file(&path).await?;
+        total -= len;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_write_read_prune() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = write(dir.path(), "job", "e1", "out\n", "err\n")
+            .await
+            .unwrap();
+        assert_eq!(first.stdout_bytes, 4);
+        assert_eq!(first.stdout_path, dir.path().join("job").join("e1.out"));
+        assert_eq!(
+            read(&first).await.unwrap(),
+            ("out\n".to_string(), "err\n".to_string())
+        );
+
+        // Make the first execution's files the oldest
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        let second = write(dir.path(), "job", "e2", "more out\n", "")
+            .await
+            .unwrap();
+
+        prune(dir.path(), 100).await.unwrap();
+        assert!(first.stdout_path.exists());
+
+        prune(dir.path(), 9).await.unwrap();
+        assert!(!first.stdout_path.exists());
+        assert!(!first.stderr_path.exists());
+        assert!(second.stdout_path.exists());
+        assert!(read(&first).await.is_err());
+
+        prune(&dir.path().join("missing"), 0).await.unwrap();
+    }
+}
@@ -6,7 +6,7 @@
 //! │ ┌───────────── hour (0-23)
 //! │ │ ┌───────────── day of month (1-31)
 //! │ │ │ ┌───────────── month (1-12)
-//! │ │ │ │ ┌───────────── day of week (0-6, 0=Sunday)
+//! │ │ │ │ ┌───────────── day of week (0-7, 0 or 7=Sunday)
 //! │ │ │ │ │
 //! * * * * *
 //! ```
@@ -16,14 +16,120 @@
 //! - `,` - value list separator (e.g., `1,3,5`)
 //! - `-` - range (e.g., `1-5`)
 //! - `/` - step (e.g., `*/5` or `0-30/5`)
+//! - `?` - alias for `*` in the day-of-month and day-of-week fields
+//! - `L` - last day of the month; `L-3` is three days before it (day-of-month)
+//! - `W` - nearest weekday (Mon-Fri) within the same month: `15W`, `LW` (last
+//!   weekday of the month), `L-3W` (weekday nearest to three days before the
+//!   last day)
+//!
+//! As in Vixie cron, when both the day-of-month and day-of-week fields are
+//! restricted (neither starts with `*` or is `?`), a time matches if *either*
+//! field matches: `0 0 13 * 5` fires on the 13th and on every Friday.
+//! Prefix the day-of-week field with `&` to require both instead:
+//! `0 0 13 * &5` fires only on Friday the 13th.
+//!
+//! Months and days of the week may also be given by name (`JAN`-`DEC`,
+//! `SUN`-`SAT`). Quartz-style expressions (seconds field, 1-7 day-of-week
+//! numbering) are accepted through [`CronExpression::parse_quartz`].
 
-use crate::types::{CronError, Result};
-use chrono::{DateTime, Datelike, Duration, TimeZone, Timelike, Utc};
+use crate::types::{CronError, Result, ScheduleInfo};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Timelike, Utc, Weekday};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeSet;
 
+/// How the day-of-month and day-of-week fields combine when both are restricted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DayMatch {
+    /// Fire when either field matches (standard cron semantics)
+    #[default]
+    Either,
+    /// Fire only when both fields match (`&` prefix on the day-of-week field)
+    Both,
+}
+
+/// A day-of-month resolved relative to the month it falls in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MonthDay {
+    /// `offset` days before the last day of the month (`L`, `L-3`), moved to
+    /// the nearest weekday when `weekday` is set (`LW`, `L-3W`)
+    Last { offset: u32, weekday: bool },
+    /// The weekday nearest to the given day of the month (`15W`)
+    NearestWeekday(u32),
+}
+
+impl MonthDay {
+    /// Resolve to a concrete day of the given month, if it exists there
+    pub fn resolve(&self, year: i32, month: u32) -> Option<u32> {
+        let last = days_in_month(year, month);
+        match *self {
+            MonthDay::Last { offset, weekday } => {
+                let day = last.checked_sub(offset).filter(|d| *d >= 1)?;
+                if weekday {
+                    nearest_weekday(year, month, day)
+                } else {
+                    Some(day)
+                }
+            }
+            MonthDay::NearestWeekday(day) if day <= last => nearest_weekday(year, month, day),
+            MonthDay::NearestWeekday(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for MonthDay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MonthDay::Last { offset, weekday } => {
+                let base = if *offset == 0 {
+                    "the last day".to_string()
+                } else {
+                    format!("{} days before the last day", offset)
+                };
+                if *weekday {
+                    write!(
+                        f,
+                        "the weekday nearest {} of the month",
+                        base.trim_start_matches("the ")
+                    )
+                } else {
+                    write!(f, "{} of the month", base)
+                }
+            }
+            MonthDay::NearestWeekday(day) => write!(f, "the weekday nearest day {}", day),
+        }
+    }
+}
+
+/// Number of days in the given month
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .and_then(|d| d.pred_opt())
+        .map(|d| d.day())
+        .unwrap_or(31)
+}
+
+/// Quartz `W` rule: the Monday-Friday nearest to `day`, without leaving the month
+fn nearest_weekday(year: i32, month: u32, day: u32) -> Option<u32> {
+    let date = NaiveDate::from_ymd_opt(year, month, day)?;
+    let last = days_in_month(year, month);
+    Some(match date.weekday() {
+        Weekday::Sat if day > 1 => day - 1,
+        Weekday::Sat => day + 2,
+        Weekday::Sun if day < last => day + 1,
+        Weekday::Sun => day - 2,
+        _ => day,
+    })
+}
+
 /// A parsed cron expression
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CronExpression {
     /// Original expression string
     pub expression: String,
@@ -33,10 +139,22 @@ pub struct CronExpression {
     hours: BTreeSet<u32>,
     /// Allowed days of month (1-31)
     days: BTreeSet<u32>,
+    /// Month-relative days of month (`L`, `LW`, `15W`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    month_days: Vec<MonthDay>,
     /// Allowed months (1-12)
     months: BTreeSet<u32>,
     /// Allowed days of week (0-6, 0=Sunday)
     weekdays: BTreeSet<u32>,
+    /// Whether the day-of-month field restricts the schedule
+    #[serde(default)]
+    days_restricted: bool,
+    /// Whether the day-of-week field restricts the schedule
+    #[serde(default)]
+    weekdays_restricted: bool,
+    /// How restricted day-of-month and day-of-week fields combine
+    #[serde(default)]
+    day_match: DayMatch,
 }
 
 impl CronExpression {
@@ -56,32 +174,145 @@ impl CronExpression {
     /// // Every Monday at 9 AM
     /// let expr = CronExpression::parse("0 9 * * 1").unwrap();
     /// ```
+    ///
+    /// Parsing is lenient about formatting: surrounding whitespace is ignored,
+    /// fields may be separated by any run of spaces or tabs, and month/weekday
+    /// names are case-insensitive. The stored [`expression`](Self::expression)
+    /// is the input as given, less surrounding whitespace. Use
+    /// [`parse_strict`](Self::parse_strict) to reject malformed input instead.
     pub fn parse(expression: &str) -> Result<Self> {
-        let parts: Vec<&str> = expression.split_whitespace().collect();
+        let mut parsed = Self::parse_standard(&normalize(expression))?;
+        parsed.expression = expression.trim().to_string();
+        Ok(parsed)
+    }
+
+    /// Parse a cron expression string with the original, stricter rules
+    ///
+    /// Fields may be separated by any whitespace, as they always could, but
+    /// month and weekday names must be upper-case (`MON`, `JAN`). Unlike
+    /// [`parse`](Self::parse), malformed names are rejected rather than
+    /// normalized.
+    pub fn parse_strict(expression: &str) -> Result<Self> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        Self::parse_standard(&fields.join(" "))
+    }
+
+    /// Parse an already-normalized standard expression
+    fn parse_standard(expression: &str) -> Result<Self> {
+        let parts: Vec<&str> = expression.split(' ').filter(|p| !p.is_empty()).collect();
 
         if parts.len() != 5 {
             return Err(CronError::InvalidExpression(format!(
-                "Expected 5 fields, got {}",
-                parts.len()
+                "Expected 5 fields (minute hour day month weekday), got {} in '{}'",
+                parts.len(),
+                expression
             )));
         }
 
-        let minutes = parse_field(parts[0], 0, 59, "minute")?;
-        let hours = parse_field(parts[1], 0, 23, "hour")?;
-        let days = parse_field(parts[2], 1, 31, "day")?;
-        let months = parse_field(parts[3], 1, 12, "month")?;
-        let weekdays = parse_field(parts[4], 0, 6, "weekday")?;
+        let (weekday_field, day_match) = split_day_match(parts[4]);
+
+        let minutes = parse_field(parts[0], &MINUTE)?;
+        let hours = parse_field(parts[1], &HOUR)?;
+        let (days, month_days) = parse_month_day_field(parts[2])?;
+        let months = parse_field(parts[3], &MONTH)?;
+        // Collapse 7 onto 0 so both spellings of Sunday behave identically
+        let weekdays = parse_day_field(weekday_field, &WEEKDAY)?
+            .into_iter()
+            .map(|d| d % 7)
+            .collect();
 
         Ok(Self {
             expression: expression.to_string(),
             minutes,
             hours,
             days,
+            month_days,
             months,
             weekdays,
+            days_restricted: is_restricted(parts[2]),
+            weekdays_restricted: is_restricted(weekday_field),
+            day_match,
         })
     }
 
+    /// Parse a Quartz-style cron expression
+    ///
+    /// Quartz expressions have 6 or 7 fields (`sec min hour day month weekday
+    /// [year]`) and number the days of the week 1-7 starting with Sunday.
+    /// Only expressions firing at second 0 of every year can be represented.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use a3s_cron::CronExpression;
+    ///
+    /// // Weekdays at noon
+    /// let expr = CronExpression::parse_quartz("0 0 12 ? * MON-FRI").unwrap();
+    /// ```
+    pub fn parse_quartz(expression: &str) -> Result<Self> {
+        let expression = normalize(expression);
+        let parts: Vec<&str> = expression.split(' ').filter(|p| !p.is_empty()).collect();
+
+        if parts.len() != 6 && parts.len() != 7 {
+            return Err(CronError::InvalidExpression(format!(
+                "Expected 6 or 7 fields (second minute hour day month weekday [year]), got {} in '{}'",
+                parts.len(),
+                expression
+            )));
+        }
+
+        let seconds = parse_field(parts[0], &SECOND)?;
+        if seconds != BTreeSet::from([0]) {
+            return Err(CronError::InvalidExpression(format!(
+                "Unsupported second '{}': only schedules firing at second 0 are supported",
+                parts[0]
+            )));
+        }
+        if let Some(year) = parts.get(6) {
+            if *year != "*" && *year != "?" {
+                return Err(CronError::InvalidExpression(format!(
+                    "Unsupported year '{}': only '*' is supported",
+                    year
+                )));
+            }
+        }
+
+        let (weekday_field, day_match) = split_day_match(parts[5]);
+
+        let minutes = parse_field(parts[1], &MINUTE)?;
+        let hours = parse_field(parts[2], &HOUR)?;
+        let (days, month_days) = parse_month_day_field(parts[3])?;
+        let months = parse_field(parts[4], &MONTH)?;
+        let weekdays = parse_day_field(weekday_field, &QUARTZ_WEEKDAY)?
+            .into_iter()
+            .map(|d| d - 1)
+            .collect();
+
+        Ok(Self {
+            expression: expression.to_string(),
+            minutes,
+            hours,
+            days,
+            month_days,
+            months,
+            weekdays,
+            days_restricted: is_restricted(parts[3]),
+            weekdays_restricted: is_restricted(weekday_field),
+            day_match,
+        })
+    }
+
+    /// How the day-of-month and day-of-week fields are combined
+    pub fn day_match(&self) -> DayMatch {
+        self.day_match
+    }
+
+    /// Override how the day-of-month and day-of-week fields are combined
+    pub fn with_day_match(mut self, day_match: DayMatch) -> Self {
+        self.day_match = day_match;
+        self
+    }
+
     /// Calculate the next run time after the given datetime
     pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
         // Start from the next minute
@@ -97,32 +328,131 @@ impl CronExpression {
             )
             .single()?;
 
-        // Search for up to 4 years (to handle leap years and edge cases)
-        let max_iterations = 4 * 366 * 24 * 60;
+        // Search for up to 4 years (to handle leap years and edge cases),
+        // skipping whole months, days and hours that cannot match
+        let limit = current + Duration::days(4 * 366);
 
-        for _ in 0..max_iterations {
-            if self.matches(&current) {
+        while current <= limit {
+            let date = current.date_naive();
+            if !self.months.contains(&current.month()) {
+                let (year, month) = if current.month() == 12 {
+                    (current.year() + 1, 1)
+                } else {
+                    (current.year(), current.month() + 1)
+                };
+                current = Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).single()?;
+            } else if !self.matches_date(date) {
+                current = Utc.from_utc_datetime(&date.succ_opt()?.and_hms_opt(0, 0, 0)?);
+            } else if !self.hours.contains(&current.hour()) {
+                current = Utc.from_utc_datetime(&date.and_hms_opt(current.hour(), 0, 0)?)
+                    + Duration::hours(1);
+            } else if !self.minutes.contains(&current.minute()) {
+                current += Duration::minutes(1);
+            } else {
                 return Some(current);
             }
-            current += Duration::minutes(1);
         }
 
         None
     }
 
+    /// Iterate over fire times strictly after the given datetime
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use a3s_cron::CronExpression;
+    /// use chrono::Utc;
+    ///
+    /// let expr = CronExpression::parse("0 * * * *").unwrap();
+    /// let next_three: Vec<_> = expr.upcoming(Utc::now()).take(3).collect();
+    /// assert_eq!(next_three.len(), 3);
+    /// ```
+    pub fn upcoming(&self, after: DateTime<Utc>) -> impl Iterator<Item = DateTime<Utc>> + '_ {
+        let mut current = after;
+        std::iter::from_fn(move || {
+            let next = self.next_after(current)?;
+            current = next;
+            Some(next)
+        })
+    }
+
+    /// Shortest gap between consecutive fire times among the next `samples`
+    /// occurrences after the given datetime
+    ///
+    /// Returns `None` if the expression fires fewer than two times.
+    pub fn min_interval(&self, after: DateTime<Utc>, samples: usize) -> Option<Duration> {
+        let times: Vec<_> = self.upcoming(after).take(samples.max(2)).collect();
+        times.windows(2).map(|pair| pair[1] - pair[0]).min()
+    }
+
+    /// Summarize the schedule: description, next fire time and the interval
+    /// between runs, estimated from the next `samples` fire times after `now`
+    pub fn info(&self, now: DateTime<Utc>, samples: usize) -> ScheduleInfo {
+        let times: Vec<_> = self.upcoming(now).take(samples.max(2)).collect();
+        let gaps: Vec<i64> = times
+            .windows(2)
+            .map(|pair| (pair[1] - pair[0]).num_seconds())
+            .collect();
+
+        ScheduleInfo {
+            expression: self.expression.clone(),
+            description: self.describe(),
+            next_run: times.first().copied(),
+            interval_secs: (!gaps.is_empty())
+                .then(|| (gaps.iter().sum::<i64>() / gaps.len() as i64) as u64),
+            min_interval_secs: gaps.iter().min().map(|gap| *gap as u64),
+        }
+    }
+
     /// Check if a datetime matches this cron expression
     pub fn matches(&self, dt: &DateTime<Utc>) -> bool {
-        let minute = dt.minute();
-        let hour = dt.hour();
-        let day = dt.day();
-        let month = dt.month();
-        let weekday = dt.weekday().num_days_from_sunday();
+        self.minutes.contains(&dt.minute())
+            && self.hours.contains(&dt.hour())
+            && self.months.contains(&dt.month())
+            && self.matches_date(dt.date_naive())
+    }
+
+    /// Check the day-of-month and day-of-week fields together
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        let day = date.day();
+        let day_matches = self.days.contains(&day)
+            || self
+                .month_days
+                .iter()
+                .any(|md| md.resolve(date.year(), date.month()) == Some(day));
+        let weekday_matches = self
+            .weekdays
+            .contains(&date.weekday().num_days_from_sunday());
+
+        if self.matches_either_day() {
+            day_matches || weekday_matches
+        } else {
+            day_matches && weekday_matches
+        }
+    }
 
-        self.minutes.contains(&minute)
-            && self.hours.contains(&hour)
-            && self.days.contains(&day)
-            && self.months.contains(&month)
-            && self.weekdays.contains(&weekday)
+    /// Month-relative day-of-month entries (`L`, `LW`, `15W`)
+    pub fn month_days(&self) -> &[MonthDay] {
+        &self.month_days
+    }
+
+    /// Allowed minutes, hours, days of month, months and days of week
+    /// (0=Sunday), in field order
+    pub(crate) fn fields(&self) -> [&BTreeSet<u32>; 5] {
+        [
+            &self.minutes,
+            &self.hours,
+            &self.days,
+            &self.months,
+            &self.weekdays,
+        ]
+    }
+
+    /// Whether a day matches if either the day-of-month or the day-of-week
+    /// field does, rather than both
+    pub(crate) fn matches_either_day(&self) -> bool {
+        self.day_match == DayMatch::Either && self.days_restricted && self.weekdays_restricted
     }
 
     /// Get a human-readable description of the schedule
@@ -154,9 +484,12 @@ impl CronExpression {
         }
 
         // Days
-        if self.days.len() < 31 {
+        if !self.days.is_empty() && self.days.len() < 31 {
             parts.push(format!("on days {:?}", self.days));
         }
+        for month_day in &self.month_days {
+            parts.push(format!("on {}", month_day));
+        }
 
         // Months
         if self.months.len() < 12 {
@@ -169,25 +502,222 @@ impl CronExpression {
                 .weekdays
                 .iter()
                 .map(|&d| match d {
-                    0 => "Sun",
-                    1 => "Mon",
-                    2 => "Tue",
-                    3 => "Wed",
-                    4 => "Thu",
-                    5 => "Fri",
-                    6 => "Sat",
+                    0 => "Sunday",
+                    1 => "Monday",
+                    2 => "Tuesday",
+                    3 => "Wednesday",
+                    4 => "Thursday",
+                    5 => "Friday",
+                    6 => "Saturday",
                     _ => "?",
                 })
                 .collect();
-            parts.push(format!("on {}", weekday_names.join(", ")));
+            let joiner = if self.day_match == DayMatch::Either
+                && self.days_restricted
+                && self.weekdays_restricted
+            {
+                "or on"
+            } else {
+                "on"
+            };
+            parts.push(format!("{} {}", joiner, weekday_names.join(", ")));
         }
 
         parts.join(", ")
     }
 }
 
+/// Description of a single cron field: its name, value range and
+/// (optionally) the symbolic names accepted in place of numbers.
+struct FieldSpec {
+    name: &'static str,
+    min: u32,
+    max: u32,
+    /// Symbolic names, mapped to `names_start + index`
+    names: &'static [&'static str],
+    names_start: u32,
+}
+
+const MONTH_NAMES: &[&str] = &[
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+
+const WEEKDAY_NAMES: &[&str] = &["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+const SECOND: FieldSpec = FieldSpec {
+    name: "second",
+    min: 0,
+    max: 59,
+    names: &[],
+    names_start: 0,
+};
+
+const MINUTE: FieldSpec = FieldSpec {
+    name: "minute",
+    min: 0,
+    max: 59,
+    names: &[],
+    names_start: 0,
+};
+
+const HOUR: FieldSpec = FieldSpec {
+    name: "hour",
+    min: 0,
+    max: 23,
+    names: &[],
+    names_start: 0,
+};
+
+const DAY: FieldSpec = FieldSpec {
+    name: "day",
+    min: 1,
+    max: 31,
+    names: &[],
+    names_start: 0,
+};
+
+const MONTH: FieldSpec = FieldSpec {
+    name: "month",
+    min: 1,
+    max: 12,
+    names: MONTH_NAMES,
+    names_start: 1,
+};
+
+/// Standard day of week; both 0 and 7 mean Sunday
+const WEEKDAY: FieldSpec = FieldSpec {
+    name: "weekday",
+    min: 0,
+    max: 7,
+    names: WEEKDAY_NAMES,
+    names_start: 0,
+};
+
+/// Quartz numbers the days of the week 1-7 starting with Sunday
+const QUARTZ_WEEKDAY: FieldSpec = FieldSpec {
+    name: "weekday",
+    min: 1,
+    max: 7,
+    names: WEEKDAY_NAMES,
+    names_start: 1,
+};
+
+/// Trim the expression, collapse whitespace runs into single spaces and
+/// upper-case symbolic names
+pub(crate) fn normalize(expression: &str) -> String {
+    expression
+        .split_whitespace()
+        .map(|part| part.to_ascii_uppercase())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Whether a day field restricts the schedule (i.e. is not `*`-based or `?`)
+fn is_restricted(field: &str) -> bool {
+    !(field.starts_with('*') || field == "?")
+}
+
+/// Split the `&` (match both day fields) prefix off the day-of-week field
+fn split_day_match(field: &str) -> (&str, DayMatch) {
+    match field.strip_prefix('&') {
+        Some(rest) => (rest, DayMatch::Both),
+        None => (field, DayMatch::Either),
+    }
+}
+
+/// Parse the day-of-month or day-of-week field, where `?` ("no specific
+/// value") is accepted as an alias for `*`
+fn parse_day_field(field: &str, spec: &FieldSpec) -> Result<BTreeSet<u32>> {
+    if field == "?" {
+        parse_field("*", spec)
+    } else {
+        parse_field(field, spec)
+    }
+}
+
+/// Parse the day-of-month field, separating month-relative entries (`L`,
+/// `L-3`, `LW`, `L-3W`, `15W`) from plain values
+fn parse_month_day_field(field: &str) -> Result<(BTreeSet<u32>, Vec<MonthDay>)> {
+    let mut month_days = Vec::new();
+    let mut plain = Vec::new();
+
+    for part in field.split(',') {
+        if let Some(month_day) = parse_month_day(part)? {
+            if !month_days.contains(&month_day) {
+                month_days.push(month_day);
+            }
+        } else {
+            plain.push(part);
+        }
+    }
+
+    let days = if plain.is_empty() {
+        BTreeSet::new()
+    } else {
+        parse_day_field(&plain.join(","), &DAY)?
+    };
+    Ok((days, month_days))
+}
+
+/// Parse a single month-relative day-of-month entry, if it is one
+fn parse_month_day(part: &str) -> Result<Option<MonthDay>> {
+    let invalid =
+        || CronError::InvalidExpression(format!("Invalid value '{}' in {}", part, DAY.name));
+
+    if let Some(rest) = part.strip_prefix('L') {
+        let (rest, weekday) = match rest.strip_suffix('W') {
+            Some(rest) => (rest, true),
+            None => (rest, false),
+        };
+        let offset = if rest.is_empty() {
+            0
+        } else {
+            let offset: u32 = rest
+                .strip_prefix('-')
+                .and_then(|n| n.parse().ok())
+                .ok_or_else(invalid)?;
+            if offset > 30 {
+                return Err(CronError::InvalidExpression(format!(
+                    "Offset {} out of range (0-30) in {}",
+                    offset, DAY.name
+                )));
+            }
+            offset
+        };
+        return Ok(Some(MonthDay::Last { offset, weekday }));
+    }
+
+    if let Some(day) = part.strip_suffix('W') {
+        let day: u32 = day.parse().map_err(|_| invalid())?;
+        if !(DAY.min..=DAY.max).contains(&day) {
+            return Err(CronError::InvalidExpression(format!(
+                "Value {} out of range ({}-{}) in {}",
+                day, DAY.min, DAY.max, DAY.name
+            )));
+        }
+        return Ok(Some(MonthDay::NearestWeekday(day)));
+    }
+
+    Ok(None)
+}
+
+/// Parse a single value, either numeric or a symbolic name
+fn parse_value(token: &str, spec: &FieldSpec, what: &str) -> Result<u32> {
+    if let Ok(value) = token.parse::<u32>() {
+        return Ok(value);
+    }
+    spec.names
+        .iter()
+        .position(|name| *name == token)
+        .map(|idx| spec.names_start + idx as u32)
+        .ok_or_else(|| {
+            CronError::InvalidExpression(format!("Invalid {} '{}' in {}", what, token, spec.name))
+        })
+}
+
 /// Parse a single cron field
-fn parse_field(field: &str, min: u32, max: u32, name: &str) -> Result<BTreeSet<u32>> {
+fn parse_field(field: &str, spec: &FieldSpec) -> Result<BTreeSet<u32>> {
+    let (min, max, name) = (spec.min, spec.max, spec.name);
     let mut values = BTreeSet::new();
 
     for part in field.split(',') {
@@ -220,26 +750,18 @@ fn parse_field(field: &str, min: u32, max: u32, name: &str) -> Result<BTreeSet<u
         let (start, end) = if range_part == "*" {
             (min, max)
         } else if let Some(idx) = range_part.find('-') {
-            let start: u32 = range_part[..idx].parse().map_err(|_| {
-                CronError::InvalidExpression(format!(
-                    "Invalid range start '{}' in {}",
-                    &range_part[..idx],
-                    name
-                ))
-            })?;
-            let end: u32 = range_part[idx + 1..].parse().map_err(|_| {
-                CronError::InvalidExpression(format!(
-                    "Invalid range end '{}' in {}",
-                    &range_part[idx + 1..],
-                    name
-                ))
-            })?;
+            let start = parse_value(&range_part[..idx], spec, "range start")?;
+            let end = parse_value(&range_part[idx + 1..], spec, "range end")?;
             (start, end)
         } else {
-            let value: u32 = range_part.parse().map_err(|_| {
-                CronError::InvalidExpression(format!("Invalid value '{}' in {}", range_part, name))
-            })?;
-            (value, value)
+            let value = parse_value(range_part, spec, "value")?;
+            // A single value with a step (e.g. `0/5`) runs from that value
+            // to the end of the field's range
+            if step.is_some() {
+                (value, max)
+            } else {
+                (value, value)
+            }
         };
 
         // Validate range
@@ -404,4 +926,433 @@ mod tests {
         assert!(desc.contains("Mon"));
         assert!(desc.contains("Fri"));
     }
+
+    /// Assert that two expressions produce identical fire times
+    fn assert_same_fire_times(a: &CronExpression, b: &CronExpression) {
+        let mut t_a = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        let mut t_b = t_a;
+        for _ in 0..20 {
+            t_a = a.next_after(t_a).unwrap();
+            t_b = b.next_after(t_b).unwrap();
+            assert_eq!(t_a, t_b, "{} vs {}", a.expression, b.expression);
+        }
+    }
+
+    #[test]
+    fn test_parse_question_mark() {
+        let expr = CronExpression::parse("0 12 ? * 1-5").unwrap();
+        assert_eq!(expr.days.len(), 31);
+        let expr = CronExpression::parse("0 12 1 * ?").unwrap();
+        assert_eq!(expr.weekdays.len(), 7);
+        assert!(CronExpression::parse("? * * * *").is_err());
+    }
+
+    #[test]
+    fn test_parse_names() {
+        let expr = CronExpression::parse("0 9 * JAN-MAR MON-FRI").unwrap();
+        assert_eq!(expr.months, BTreeSet::from([1, 2, 3]));
+        assert_eq!(expr.weekdays, BTreeSet::from([1, 2, 3, 4, 5]));
+        assert!(CronExpression::parse("0 9 * * FOO").is_err());
+    }
+
+    #[test]
+    fn test_parse_value_with_step() {
+        let expr = CronExpression::parse("10/15 * * * *").unwrap();
+        assert_eq!(expr.minutes, BTreeSet::from([10, 25, 40, 55]));
+    }
+
+    #[test]
+    fn test_parse_quartz_matches_standard() {
+        let cases = [
+            ("0 0 12 ? * MON-FRI", "0 12 * * 1-5"),
+            ("0 15 10 ? * *", "15 10 * * *"),
+            ("0 0/5 14 * * ?", "*/5 14 * * *"),
+            ("0 0 12 1 * ?", "0 12 1 * *"),
+            ("0 30 9 ? * 2,4,6", "30 9 * * 1,3,5"),
+            ("0 0 8 ? JAN-MAR 1", "0 8 * 1-3 0"),
+            ("0 0 18 ? * SAT *", "0 18 * * 6"),
+        ];
+        for (quartz, standard) in cases {
+            let q = CronExpression::parse_quartz(quartz).unwrap();
+            let s = CronExpression::parse(standard).unwrap();
+            assert_same_fire_times(&q, &s);
+        }
+    }
+
+    #[test]
+    fn test_parse_quartz_rejects_unsupported() {
+        // Standard 5-field expressions are not Quartz expressions
+        assert!(CronExpression::parse_quartz("0 12 * * 1").is_err());
+        // Non-zero seconds
+        assert!(CronExpression::parse_quartz("30 0 12 * * ?").is_err());
+        // Specific years
+        assert!(CronExpression::parse_quartz("0 0 12 * * ? 2027").is_err());
+        // Quartz weekday numbering is 1-7
+        assert!(CronExpression::parse_quartz("0 0 12 ? * 0").is_err());
+    }
+
+    #[test]
+    fn test_parse_standard_weekday_numbering_unchanged() {
+        // `parse` keeps 0=Sunday numbering; 1 is Monday
+        let expr = CronExpression::parse("0 12 * * 1").unwrap();
+        assert_eq!(expr.weekdays, BTreeSet::from([1]));
+        assert!(CronExpression::parse("0 0 12 ? * MON-FRI").is_err());
+    }
+
+    #[test]
+    fn test_day_fields_or_semantics() {
+        // The 13th OR any Friday
+        let expr = CronExpression::parse("0 0 13 * 5").unwrap();
+        assert_eq!(expr.day_match(), DayMatch::Either);
+
+        // Friday, March 13, 2026
+        assert!(expr.matches(&Utc.with_ymd_and_hms(2026, 3, 13, 0, 0, 0).unwrap()));
+        // Friday, March 6, 2026
+        assert!(expr.matches(&Utc.with_ymd_and_hms(2026, 3, 6, 0, 0, 0).unwrap()));
+        // Monday, April 13, 2026
+        assert!(expr.matches(&Utc.with_ymd_and_hms(2026, 4, 13, 0, 0, 0).unwrap()));
+        // Tuesday, March 10, 2026
+        assert!(!expr.matches(&Utc.with_ymd_and_hms(2026, 3, 10, 0, 0, 0).unwrap()));
+
+        let start = Utc.with_ymd_and_hms(2026, 3, 1, 0, 0, 0).unwrap();
+        let first = expr.next_after(start).unwrap();
+        assert_eq!(first, Utc.with_ymd_and_hms(2026, 3, 6, 0, 0, 0).unwrap());
+        let second = expr.next_after(first).unwrap();
+        assert_eq!(second, Utc.with_ymd_and_hms(2026, 3, 13, 0, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_day_fields_and_semantics() {
+        // Only Friday the 13th
+        let expr = CronExpression::parse("0 0 13 * &5").unwrap();
+        assert_eq!(expr.day_match(), DayMatch::Both);
+
+        assert!(expr.matches(&Utc.with_ymd_and_hms(2026, 3, 13, 0, 0, 0).unwrap()));
+        assert!(!expr.matches(&Utc.with_ymd_and_hms(2026, 3, 6, 0, 0, 0).unwrap()));
+        assert!(!expr.matches(&Utc.with_ymd_and_hms(2026, 4, 13, 0, 0, 0).unwrap()));
+
+        // After Friday, March 13, 2026 the next one is November 13, 2026
+        let start = Utc.with_ymd_and_hms(2026, 3, 13, 0, 0, 0).unwrap();
+        let next = expr.next_after(start).unwrap();
+        assert_eq!(next, Utc.with_ymd_and_hms(2026, 11, 13, 0, 0, 0).unwrap());
+
+        let overridden = CronExpression::parse("0 0 13 * 5")
+            .unwrap()
+            .with_day_match(DayMatch::Both);
+        assert_eq!(overridden.next_after(start), Some(next));
+    }
+
+    #[test]
+    fn test_day_fields_unrestricted_uses_other_field() {
+        // A `*`-based field does not widen the schedule
+        let expr = CronExpression::parse("0 0 */2 * 5").unwrap();
+        assert!(!expr.matches(&Utc.with_ymd_and_hms(2026, 3, 7, 0, 0, 0).unwrap()));
+        assert!(!expr.matches(&Utc.with_ymd_and_hms(2026, 3, 6, 0, 0, 0).unwrap()));
+        assert!(expr.matches(&Utc.with_ymd_and_hms(2026, 3, 13, 0, 0, 0).unwrap()));
+
+        let expr = CronExpression::parse("0 0 ? * 5").unwrap();
+        assert!(expr.matches(&Utc.with_ymd_and_hms(2026, 3, 6, 0, 0, 0).unwrap()));
+        assert!(!expr.matches(&Utc.with_ymd_and_hms(2026, 3, 7, 0, 0, 0).unwrap()));
+    }
+
+    #[test]
+    fn test_sunday_as_seven() {
+        let seven = CronExpression::parse("* * * * 7").unwrap();
+        let zero = CronExpression::parse("* * * * 0").unwrap();
+        assert_eq!(seven.weekdays, BTreeSet::from([0]));
+        assert_same_fire_times(&seven, &zero);
+
+        // Sunday, Feb 8, 2026
+        let sunday = Utc.with_ymd_and_hms(2026, 2, 8, 12, 0, 0).unwrap();
+        assert!(seven.matches(&sunday));
+        assert!(seven.describe().contains("Sunday"));
+    }
+
+    #[test]
+    fn test_sunday_zero_and_seven_list() {
+        let expr = CronExpression::parse("* * * * 0,7").unwrap();
+        assert_eq!(expr.weekdays, BTreeSet::from([0]));
+        assert_eq!(expr.describe().matches("Sunday").count(), 1);
+    }
+
+    #[test]
+    fn test_sunday_in_range_and_step() {
+        // Friday through Sunday
+        let expr = CronExpression::parse("0 0 * * 5-7").unwrap();
+        assert_eq!(expr.weekdays, BTreeSet::from([0, 5, 6]));
+        let desc = expr.describe();
+        assert!(desc.contains("Friday"));
+        assert!(desc.contains("Saturday"));
+        assert!(desc.contains("Sunday"));
+
+        let expr = CronExpression::parse("0 0 * * 7/2").unwrap();
+        assert_eq!(expr.weekdays, BTreeSet::from([0]));
+
+        assert!(CronExpression::parse("0 0 * * 8").is_err());
+    }
+
+    #[test]
+    fn test_parse_tolerates_whitespace() {
+        let expr = CronExpression::parse("  */5\t* * * *  ").unwrap();
+        // The expression is kept as given, less surrounding whitespace
+        assert_eq!(expr.expression, "*/5\t* * * *");
+        assert_eq!(expr.minutes.len(), 12);
+
+        let expr = CronExpression::parse("0  9\t\t*   * 1").unwrap();
+        assert_eq!(expr.weekdays, BTreeSet::from([1]));
+    }
+
+    #[test]
+    fn test_parse_names_case_insensitive() {
+        let expr = CronExpression::parse("0 9 * * mon-FRI").unwrap();
+        assert_eq!(expr.weekdays, BTreeSet::from([1, 2, 3, 4, 5]));
+        assert_eq!(expr.expression, "0 9 * * mon-FRI");
+
+        let expr = CronExpression::parse("0 9 * jan,Feb *").unwrap();
+        assert_eq!(expr.months, BTreeSet::from([1, 2]));
+
+        let expr = CronExpression::parse_quartz("0 0 12 ? * mon-fri").unwrap();
+        assert_eq!(expr.weekdays, BTreeSet::from([1, 2, 3, 4, 5]));
+    }
+
+    #[test]
+    fn test_parse_strict() {
+        assert!(CronExpression::parse_strict("*/5 * * * *").is_ok());
+        assert!(CronExpression::parse_strict("0 9 * * MON-FRI").is_ok());
+
+        // Whitespace was never significant, so stored expressions with tabs
+        // or repeated spaces keep parsing
+        for spaced in [
+            "  */5\t* * * *  ",
+            " */5 * * * *",
+            "*/5  * * * *",
+            "*/5\t* * * *",
+        ] {
+            let expr = CronExpression::parse_strict(spaced).unwrap();
+            assert_eq!(expr.expression, "*/5 * * * *");
+        }
+
+        assert!(CronExpression::parse_strict("0 9 * * mon-FRI").is_err());
+        assert!(CronExpression::parse_strict("0 9 * jan *").is_err());
+        assert!(CronExpression::parse_strict("* * * *").is_err());
+    }
+
+    #[test]
+    fn test_parse_error_messages() {
+        let err = CronExpression::parse("* * *").unwrap_err().to_string();
+        assert!(err.contains("Expected 5 fields"));
+        assert!(err.contains("'* * *'"));
+
+        let err = CronExpression::parse("0 9 * * FUNDAY")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("FUNDAY"));
+        assert!(err.contains("weekday"));
+    }
+
+    #[test]
+    fn test_upcoming() {
+        let expr = CronExpression::parse("0 */6 * * *").unwrap();
+        let start = Utc.with_ymd_and_hms(2026, 2, 5, 10, 30, 0).unwrap();
+        let times: Vec<_> = expr.upcoming(start).take(3).collect();
+        assert_eq!(
+            times,
+            vec![
+                Utc.with_ymd_and_hms(2026, 2, 5, 12, 0, 0).unwrap(),
+                Utc.with_ymd_and_hms(2026, 2, 5, 18, 0, 0).unwrap(),
+                Utc.with_ymd_and_hms(2026, 2, 6, 0, 0, 0).unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_min_interval() {
+        let start = Utc.with_ymd_and_hms(2026, 2, 5, 10, 30, 0).unwrap();
+
+        let expr = CronExpression::parse("*/5 * * * *").unwrap();
+        assert_eq!(expr.min_interval(start, 10), Some(Duration::minutes(5)));
+
+        // The closest pair is not necessarily the first two fire times
+        let expr = CronExpression::parse("0,1 0 * * *").unwrap();
+        assert_eq!(expr.min_interval(start, 10), Some(Duration::minutes(1)));
+    }
+
+    /// Fire dates produced over 2026 by a midnight schedule with the given
+    /// day-of-month field
+    fn fire_days_2026(day_field: &str) -> Vec<(u32, u32)> {
+        let expr = CronExpression::parse(&format!("0 0 {} * *", day_field)).unwrap();
+        let start = Utc.with_ymd_and_hms(2025, 12, 31, 12, 0, 0).unwrap();
+        expr.upcoming(start)
+            .take_while(|t| t.year() == 2026)
+            .map(|t| (t.month(), t.day()))
+            .collect()
+    }
+
+    #[test]
+    fn test_last_day_of_month() {
+        let expected = [
+            (1, 31),
+            (2, 28),
+            (3, 31),
+            (4, 30),
+            (5, 31),
+            (6, 30),
+            (7, 31),
+            (8, 31),
+            (9, 30),
+            (10, 31),
+            (11, 30),
+            (12, 31),
+        ];
+        assert_eq!(fire_days_2026("L"), expected);
+
+        let expected = [
+            (1, 29),
+            (2, 26),
+            (3, 29),
+            (4, 28),
+            (5, 29),
+            (6, 28),
+            (7, 29),
+            (8, 29),
+            (9, 28),
+            (10, 29),
+            (11, 28),
+            (12, 29),
+        ];
+        assert_eq!(fire_days_2026("L-2"), expected);
+    }
+
+    #[test]
+    fn test_last_weekday_of_month() {
+        let expected = [
+            (1, 30),
+            (2, 27),
+            (3, 31),
+            (4, 30),
+            (5, 29),
+            (6, 30),
+            (7, 31),
+            (8, 31),
+            (9, 30),
+            (10, 30),
+            (11, 30),
+            (12, 31),
+        ];
+        assert_eq!(fire_days_2026("LW"), expected);
+    }
+
+    #[test]
+    fn test_last_offset_weekday() {
+        let expected = [
+            (1, 28),
+            (2, 25),
+            (3, 27),
+            (4, 27),
+            (5, 28),
+            (6, 26),
+            (7, 28),
+            (8, 28),
+            (9, 28),
+            (10, 28),
+            (11, 27),
+            (12, 28),
+        ];
+        assert_eq!(fire_days_2026("L-3W"), expected);
+    }
+
+    #[test]
+    fn test_nearest_weekday() {
+        let expected = [
+            (1, 15),
+            (2, 16),
+            (3, 16),
+            (4, 15),
+            (5, 15),
+            (6, 15),
+            (7, 15),
+            (8, 14),
+            (9, 15),
+            (10, 15),
+            (11, 16),
+            (12, 15),
+        ];
+        assert_eq!(fire_days_2026("15W"), expected);
+
+        // Never leaves the month: Saturday the 1st moves forward to Monday
+        // the 3rd (August 2026), Sunday the 31st back to Friday the 29th
+        // (January 2027)
+        let expr = CronExpression::parse("0 0 1W * *").unwrap();
+        let start = Utc.with_ymd_and_hms(2026, 7, 31, 0, 0, 0).unwrap();
+        assert_eq!(
+            expr.next_after(start).unwrap(),
+            Utc.with_ymd_and_hms(2026, 8, 3, 0, 0, 0).unwrap()
+        );
+        let expr = CronExpression::parse("0 0 LW * *").unwrap();
+        let start = Utc.with_ymd_and_hms(2027, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(
+            expr.next_after(start).unwrap(),
+            Utc.with_ymd_and_hms(2027, 1, 29, 0, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_month_day_rollover() {
+        // December -> January
+        let expr = CronExpression::parse("30 17 LW * *").unwrap();
+        let start = Utc.with_ymd_and_hms(2026, 12, 31, 18, 0, 0).unwrap();
+        assert_eq!(
+            expr.next_after(start).unwrap(),
+            Utc.with_ymd_and_hms(2027, 1, 29, 17, 30, 0).unwrap()
+        );
+
+        // Leap-year February
+        let expr = CronExpression::parse("0 0 L 2 *").unwrap();
+        let start = Utc.with_ymd_and_hms(2027, 3, 1, 0, 0, 0).unwrap();
+        assert_eq!(
+            expr.next_after(start).unwrap(),
+            Utc.with_ymd_and_hms(2028, 2, 29, 0, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_month_day_combinations() {
+        // The 1st and the last day
+        assert_eq!(
+            fire_days_2026("1,L")[..4],
+            [(1, 1), (1, 31), (2, 1), (2, 28)]
+        );
+
+        assert!(CronExpression::parse("0 0 L-31 * *").is_err());
+        assert!(CronExpression::parse("0 0 32W * *").is_err());
+        assert!(CronExpression::parse("0 0 LX * *").is_err());
+        assert!(CronExpression::parse("0 0 L-W * *").is_err());
+
+        let expr = CronExpression::parse("0 0 lw * *").unwrap();
+        assert_eq!(
+            expr.month_days(),
+            &[MonthDay::Last {
+                offset: 0,
+                weekday: true
+            }]
+        );
+        assert!(expr.describe().contains("weekday nearest"));
+    }
+
+    #[test]
+    fn test_info() {
+        let expr = CronExpression::parse("0,30 9 * * *").unwrap();
+        let now = Utc.with_ymd_and_hms(2026, 2, 5, 10, 0, 0).unwrap();
+        let info = expr.info(now, 10);
+
+        assert_eq!(info.expression, "0,30 9 * * *");
+        assert_eq!(info.description, expr.describe());
+        assert_eq!(
+            info.next_run,
+            Some(Utc.with_ymd_and_hms(2026, 2, 6, 9, 0, 0).unwrap())
+        );
+        assert_eq!(info.min_interval_secs, Some(30 * 60));
+        // Gaps alternate between 30 minutes and 23.5 hours
+        assert_eq!(info.interval_secs, Some((5 * 1800 + 4 * 84600) / 9));
+    }
 }
@@ -0,0 +1,745 @@
+//! Child process supervision
+//!
+//! Runs a job's command as a child process, capturing its output as it is
+//! produced so that a timeout or cancellation can kill the process and still
+//! keep whatever it printed. On Unix the child leads its own process group so
+//! that anything it spawned is stopped along with it.
+
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::{Child, Command};
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use tokio::time::{Duration, Instant};
+
+use crate::types::{CronError, EnvMode, OutputStream, ResourceLimits, Result, Termination};
+
+/// How long to keep reading output after the process has ended. Background
+/// processes that inherited the pipes can otherwise hold them open forever.
+const OUTPUT_DRAIN: Duration = Duration::from_millis(500);
+
+/// How long output is collected before it is forwarded to an output sink,
+/// so a process printing constantly produces a bounded number of chunks
+const OUTPUT_FLUSH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Most output forwarded in one chunk; the rest of a burst is skipped
+const MAX_OUTPUT_CHUNK: usize = 16 * 1024;
+
+/// Receives a process's output while it runs, in chunks of whole lines
+pub(crate) type OutputSink = Arc<dyn Fn(OutputStream, String) + Send + Sync>;
+
+/// `PATH` of commands run with a clean environment
+const CLEAN_PATH: &str = "/usr/local/bin:/usr/bin:/bin";
+
+/// Build a command running `script` with `shell`, passing the script with
+/// the flag the shell expects (`-Command` for PowerShell, `/C` for cmd,
+/// `-c` otherwise)
+pub(crate) fn shell_command(shell: &str, script: &str) -> Command {
+    let name = Path::new(shell)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(shell)
+        .to_ascii_lowercase();
+    let flag = match name.as_str() {
+        "pwsh" | "powershell" => "-Command",
+        "cmd" => "/C",
+        _ => "-c",
+    };
+    let mut command = Command::new(shell);
+    command.arg(flag).arg(script);
+    command
+}
+
+/// Limit the environment `command` inherits from this process according to
+/// `mode`. Variables set on `command` afterwards are kept.
+pub(crate) fn apply_env_mode(command: &mut Command, mode: &EnvMode) {
+    match mode {
+        EnvMode::Inherit => {}
+        EnvMode::Clean => {
+            command.env_clear().env("PATH", CLEAN_PATH);
+            if let Some(home) = std::env::var_os("HOME") {
+                command.env("HOME", home);
+            }
+        }
+        EnvMode::InheritAllow(names) => {
+            command.env_clear();
+            for name in names {
+                if let Some(value) = std::env::var_os(name) {
+                    command.env(name, value);
+                }
+            }
+        }
+    }
+}
+
+/// Apply `limits` to the process `command` starts, just before it runs
+#[cfg(unix)]
+pub(crate) fn apply_resource_limits(command: &mut Command, limits: &ResourceLimits) {
+    if limits.is_empty() {
+        return;
+    }
+    let limits = *limits;
+    let rlimits = [
+        (libc::RLIMIT_AS, limits.max_memory_bytes),
+        (libc::RLIMIT_FSIZE, limits.max_file_size_bytes),
+        (libc::RLIMIT_NOFILE, limits.max_open_files),
+    ];
+    // SAFETY: the closure runs in the forked child and only makes
+    // async-signal-safe calls, without allocating
+    unsafe {
+        command.pre_exec(move || {
+            if let Some(nice) = limits.nice {
+                if libc::setpriority(libc::PRIO_PROCESS as _, 0, nice) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+            }
+            for (resource, value) in rlimits {
+                let Some(value) = value else {
+                    continue;
+                };
+                let mut limit = libc::rlimit {
+                    rlim_cur: 0,
+                    rlim_max: 0,
+                };
+                if libc::getrlimit(resource, &mut limit) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                // Lowering is always allowed; the hard limit can't be raised
+                let value = (value as libc::rlim_t).min(limit.rlim_max);
+                limit.rlim_cur = value;
+                limit.rlim_max = value;
+                if libc::setrlimit(resource, &limit) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+            }
+            Ok(())
+        });
+    }
+}
+
+/// Resource limits are only enforced on Unix
+#[cfg(not(unix))]
+pub(crate) fn apply_resource_limits(_command: &mut Command, _limits: &ResourceLimits) {}
+
+/// Describe a process killed by `signal`, naming the limit in `limits` it
+/// most likely exceeded
+pub(crate) fn describe_signal(signal: i32, limits: &ResourceLimits) -> String {
+    match exceeded_limit(signal, limits) {
+        Some(limit) => format!("killed: exceeded {} limit (signal {})", limit, signal),
+        None => format!("killed by signal {}", signal),
+    }
+}
+
+#[cfg(unix)]
+fn exceeded_limit(signal: i32, limits: &ResourceLimits) -> Option<&'static str> {
+    match signal {
+        libc::SIGXFSZ if limits.max_file_size_bytes.is_some() => Some("file size"),
+        // Allocations failing under RLIMIT_AS usually end in one of these
+        libc::SIGKILL | libc::SIGSEGV | libc::SIGABRT | libc::SIGBUS
+            if limits.max_memory_bytes.is_some() =>
+        {
+            Some("memory")
+        }
+        _ => None,
+    }
+}
+
+#[cfg(not(unix))]
+fn exceeded_limit(_signal: i32, _limits: &ResourceLimits) -> Option<&'static str> {
+    None
+}
+
+/// Check that `program` is an executable file, given either as a path or as
+/// a name to look up on `PATH`
+pub(crate) fn check_executable(program: &str) -> Result<()> {
+    if program.trim().is_empty() {
+        return Err(CronError::InvalidConfig("shell must not be empty".into()));
+    }
+    let path = Path::new(program);
+    if path.components().count() > 1 {
+        if !path.exists() {
+            return Err(CronError::InvalidConfig(format!(
+                "shell not found: {}",
+                program
+            )));
+        }
+        if !is_executable(path) {
+            return Err(CronError::InvalidConfig(format!(
+                "shell is not executable: {}",
+                program
+            )));
+        }
+        return Ok(());
+    }
+    let found = std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).any(|dir| is_executable(&dir.join(program))))
+        .unwrap_or(false);
+    if found {
+        Ok(())
+    } else {
+        Err(CronError::InvalidConfig(format!(
+            "shell not found on PATH: {}",
+            program
+        )))
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file() || path.with_extension("exe").is_file()
+}
+
+/// How a supervised process ended
+#[derive(Debug)]
+pub(crate) enum Outcome {
+    /// The process exited on its own, or was killed by `signal`
+    Exited {
+        exit_code: i32,
+        signal: Option<i32>,
+        stdout: String,
+        stderr: String,
+    },
+    /// The process ran past its timeout and was stopped
+    TimedOut {
+        stdout: String,
+        stderr: String,
+        /// How the process was stopped (`None` when no process was involved)
+        termination: Option<Termination>,
+    },
+    /// The execution was cancelled and the process killed
+    Cancelled { stdout: String, stderr: String },
+}
+
+impl Outcome {
+    /// The captured stdout and stderr
+    pub(crate) fn output_mut(&mut self) -> (&mut String, &mut String) {
+        match self {
+            Outcome::Exited { stdout, stderr, .. }
+            | Outcome::TimedOut { stdout, stderr, .. }
+            | Outcome::Cancelled { stdout, stderr } => (stdout, stderr),
+        }
+    }
+}
+
+/// Why a process is being stopped early
+enum Stop {
+    Timeout,
+    Cancel,
+}
+
+/// Run `command` to completion, stopping it if it exceeds `timeout` or
+/// `cancel` is signalled. A timed-out process gets `grace` to exit after
+/// SIGTERM before it is killed. Output is also passed to `sink` as it is
+/// produced.
+pub(crate) async fn run(
+    mut command: Command,
+    timeout: Duration,
+    grace: Duration,
+    cancel: &Notify,
+    sink: Option<OutputSink>,
+) -> std::io::Result<Outcome> {
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    #[cfg(unix)]
+    command.process_group(0);
+
+    let mut child = command.spawn()?;
+    let pgid = child.id();
+    let forwarder = |stream| sink.clone().map(|sink| Forwarder::new(sink, stream));
+    let stdout = Capture::start(child.stdout.take(), forwarder(OutputStream::Stdout));
+    let stderr = Capture::start(child.stderr.take(), forwarder(OutputStream::Stderr));
+
+    let stop = tokio::select! {
+        status = child.wait() => {
+            let status = status?;
+            return Ok(Outcome::Exited {
+                exit_code: status.code().unwrap_or(-1),
+                signal: exit_signal(&status),
+                stdout: stdout.finish().await,
+                stderr: stderr.finish().await,
+            });
+        }
+        _ = tokio::time::sleep(timeout) => Stop::Timeout,
+        _ = cancel.notified() => Stop::Cancel,
+    };
+
+    let termination = match stop {
+        Stop::Timeout => Some(terminate(&mut child, pgid, grace).await),
+        Stop::Cancel => {
+            kill(&mut child, pgid).await;
+            None
+        }
+    };
+    let (stdout, stderr) = (stdout.finish().await, stderr.finish().await);
+    Ok(match termination {
+        Some(termination) => Outcome::TimedOut {
+            stdout,
+            stderr,
+            termination: Some(termination),
+        },
+        None => Outcome::Cancelled { stdout, stderr },
+    })
+}
+
+/// Signal that killed a process
+#[cfg(unix)]
+fn exit_signal(status: &std::process::ExitStatus) -> Option<i32> {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal()
+}
+
+#[cfg(not(unix))]
+fn exit_signal(_status: &std::process::ExitStatus) -> Option<i32> {
+    None
+}
+
+/// Ask the child to exit with SIGTERM, killing it if it is still running
+/// after `grace`
+async fn terminate(child: &mut Child, pgid: Option<u32>, grace: Duration) -> Termination {
+    if !grace.is_zero() && signal_group(pgid, Signal::Term) {
+        if let Ok(Ok(_)) = tokio::time::timeout(grace, child.wait()).await {
+            // Don't leave behind anything the child started
+            signal_group(pgid, Signal::Kill);
+            return Termination::Graceful;
+        }
+    }
+    kill(child, pgid).await;
+    Termination::Forced
+}
+
+/// Kill the child's process group and reap the child
+async fn kill(child: &mut Child, pgid: Option<u32>) {
+    if signal_group(pgid, Signal::Kill) {
+        if let Err(e) = child.wait().await {
+            tracing::warn!("Failed to reap child process: {}", e);
+        }
+    } else if let Err(e) = child.kill().await {
+        tracing::warn!("Failed to kill child process: {}", e);
+    }
+}
+
+/// Signals sent to a child's process group
+enum Signal {
+    Term,
+    Kill,
+}
+
+/// Signal the process group led by the child, returning whether it was
+/// delivered. The group outlives its leader while any member is running.
+#[cfg(unix)]
+fn signal_group(pgid: Option<u32>, signal: Signal) -> bool {
+    let signal = match signal {
+        Signal::Term => libc::SIGTERM,
+        Signal::Kill => libc::SIGKILL,
+    };
+    match pgid {
+        // SAFETY: kill(2) has no memory-safety preconditions
+        Some(pgid) => unsafe { libc::kill(-(pgid as libc::pid_t), signal) == 0 },
+        None => false,
+    }
+}
+
+/// Process groups are unavailable; callers fall back to killing the child
+#[cfg(not(unix))]
+fn signal_group(_pgid: Option<u32>, _signal: Signal) -> bool {
+    false
+}
+
+/// Output collected from one of the child's pipes
+struct Capture {
+    buffer: Arc<Mutex<Vec<u8>>>,
+    reader: Option<JoinHandle<()>>,
+}
+
+impl Capture {
+    /// Start reading from the pipe in the background, forwarding what is
+    /// read through `forwarder` if given
+    fn start<R: AsyncRead + Unpin + Send + 'static>(
+        pipe: Option<R>,
+        mut forwarder: Option<Forwarder>,
+    ) -> Self {
+        let buffer = Arc::new(Mutex::new(Vec::new()));
+        let reader = pipe.map(|mut pipe| {
+            let buffer = buffer.clone();
+            tokio::spawn(async move {
+                let mut chunk = [0u8; 8192];
+                let mut flush_at: Option<Instant> = None;
+                loop {
+                    let read = tokio::select! {
+                        read = pipe.read(&mut chunk) => read,
+                        _ = tokio::time::sleep_until(flush_at.unwrap_or_else(Instant::now)),
+                            if flush_at.is_some() =>
+                        {
+                            if let Some(forwarder) = &mut forwarder {
+                                forwarder.flush(false);
+                            }
+                            flush_at = None;
+                            continue;
+                        }
+                    };
+                    let n = match read {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => n,
+                    };
+                    buffer.lock().unwrap().extend_from_slice(&chunk[..n]);
+                    if let Some(forwarder) = &mut forwarder {
+                        forwarder.push(&chunk[..n]);
+                        flush_at.get_or_insert_with(|| Instant::now() + OUTPUT_FLUSH_INTERVAL);
+                    }
+                }
+                if let Some(forwarder) = &mut forwarder {
+                    forwarder.flush(true);
+                }
+            })
+        });
+        Self { buffer, reader }
+    }
+
+    /// Wait briefly for the pipe to close and return what was captured
+    async fn finish(self) -> String {
+        if let Some(mut reader) = self.reader {
+            if tokio::time::timeout(OUTPUT_DRAIN, &mut reader)
+                .await
+                .is_err()
+            {
+                reader.abort();
+            }
+        }
+        let buffer = self.buffer.lock().unwrap();
+        String::from_utf8_lossy(&buffer).to_string()
+    }
+}
+
+/// Batches one pipe's output into chunks for an output sink
+struct Forwarder {
+    sink: OutputSink,
+    stream: OutputStream,
+    pending: Vec<u8>,
+    /// Bytes dropped since the last chunk because it was full
+    skipped: usize,
+}
+
+impl Forwarder {
+    fn new(sink: OutputSink, stream: OutputStream) -> Self {
+        Self {
+            sink,
+            stream,
+            pending: Vec::new(),
+            skipped: 0,
+        }
+    }
+
+    /// Add output to the next chunk, skipping what doesn't fit
+    fn push(&mut self, bytes: &[u8]) {
+        let room = MAX_OUTPUT_CHUNK.saturating_sub(self.pending.len());
+        let taken = bytes.len().min(room);
+        self.pending.extend_from_slice(&bytes[..taken]);
+        self.skipped += bytes.len() - taken;
+    }
+
+    /// Forward the complete lines collected so far, or everything if `all`
+    /// or the chunk is full
+    fn flush(&mut self, all: bool) {
+        let full = self.skipped > 0 || self.pending.len() >= MAX_OUTPUT_CHUNK;
+        let end = if all || full {
+            self.pending.len()
+        } else {
+            match self.pending.iter().rposition(|&b| b == b'\n') {
+                Some(newline) => newline + 1,
+                None => return,
+            }
+        };
+        if end == 0 && self.skipped == 0 {
+            return;
+        }
+        let mut chunk = String::from_utf8_lossy(&self.pending[..end]).into_owned();
+        self.pending.drain(..end);
+        if self.skipped > 0 {
+            chunk.push_str(&format!("...[{} bytes skipped]\n", self.skipped));
+            self.skipped = 0;
+        }
+        (self.sink)(self.stream, chunk);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shell(script: &str) -> Command {
+        shell_command("sh", script)
+    }
+
+    #[test]
+    fn test_shell_command_flag() {
+        let args = |shell: &str| {
+            shell_command(shell, "true")
+                .as_std()
+                .get_args()
+                .map(|arg| arg.to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(args("bash"), ["-c", "true"]);
+        assert_eq!(args("/usr/bin/zsh"), ["-c", "true"]);
+        assert_eq!(args("pwsh"), ["-Command", "true"]);
+        assert_eq!(args("powershell.exe"), ["-Command", "true"]);
+        assert_eq!(args("cmd.exe"), ["/C", "true"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_executable() {
+        assert!(check_executable("sh").is_ok());
+        assert!(check_executable("/bin/sh").is_ok());
+        assert!(check_executable("").is_err());
+        assert!(check_executable("no-such-shell-a3s").is_err());
+        assert!(check_executable("/no/such/shell").is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("not-a-shell");
+        std::fs::write(&file, "").unwrap();
+        let err = check_executable(file.to_str().unwrap()).unwrap_err();
+        assert!(err.to_string().contains("not executable"));
+    }
+
+    #[tokio::test]
+    async fn test_run_exits() {
+        let outcome = run(
+            shell("echo out; echo err >&2; exit 3"),
+            Duration::from_secs(5),
+            Duration::ZERO,
+            &Notify::new(),
+            None,
+        )
+        .await
+        .unwrap();
+        match outcome {
+            Outcome::Exited {
+                exit_code,
+                signal,
+                stdout,
+                stderr,
+            } => {
+                assert_eq!(exit_code, 3);
+                assert_eq!(signal, None);
+                assert_eq!(stdout, "out\n");
+                assert_eq!(stderr, "err\n");
+            }
+            other => panic!("unexpected outcome: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_cancelled() {
+        let cancel = Notify::new();
+        cancel.notify_one();
+        let outcome = run(
+            shell("sleep 60"),
+            Duration::from_secs(5),
+            Duration::ZERO,
+            &cancel,
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(matches!(outcome, Outcome::Cancelled { .. }));
+    }
+
+    async fn timed_out(script: &str, grace: Duration) -> (String, Termination) {
+        let outcome = run(
+            shell(script),
+            Duration::from_millis(500),
+            grace,
+            &Notify::new(),
+            None,
+        )
+        .await
+        .unwrap();
+        match outcome {
+            Outcome::TimedOut {
+                stdout,
+                termination,
+                ..
+            } => (stdout, termination.unwrap()),
+            other => panic!("unexpected outcome: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timeout_without_grace_is_forced() {
+        let (stdout, termination) = timed_out("echo started; exec sleep 60", Duration::ZERO).await;
+        assert_eq!(stdout, "started\n");
+        assert_eq!(termination, Termination::Forced);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_timeout_graceful_exit_on_sigterm() {
+        let (stdout, termination) = timed_out(
+            "trap 'kill $!; echo cleaned up; exit 0' TERM; sleep 60 >/dev/null & wait",
+            Duration::from_secs(5),
+        )
+        .await;
+        assert_eq!(stdout, "cleaned up\n");
+        assert_eq!(termination, Termination::Graceful);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_timeout_forced_after_grace() {
+        let started = std::time::Instant::now();
+        let (_, termination) =
+            timed_out("trap '' TERM; exec sleep 60", Duration::from_millis(300)).await;
+        assert_eq!(termination, Termination::Forced);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    /// Whether `pid` is a running (not zombie) process
+    #[cfg(target_os = "linux")]
+    fn is_alive(pid: &str) -> bool {
+        std::fs::read_to_string(format!("/proc/{}/stat", pid.trim()))
+            .map(|stat| {
+                // The state follows the parenthesised command name
+                let state = stat.rsplit(')').next().unwrap_or("").trim_start();
+                !state.starts_with('Z') && !state.starts_with('X')
+            })
+            .unwrap_or(false)
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_timeout_kills_grandchildren() {
+        let (stdout, _) = timed_out("sleep 60 & echo $!; wait", Duration::ZERO).await;
+        assert!(!stdout.trim().is_empty());
+        assert!(!is_alive(&stdout));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_graceful_exit_kills_leftover_grandchildren() {
+        let (stdout, termination) = timed_out(
+            "trap 'exit 0' TERM; sleep 60 & echo $!; wait",
+            Duration::from_secs(5),
+        )
+        .await;
+        assert_eq!(termination, Termination::Graceful);
+        assert!(!is_alive(&stdout));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_cancel_kills_grandchildren() {
+        let cancel = std::sync::Arc::new(Notify::new());
+        let trigger = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            trigger.notify_one();
+        });
+        let outcome = run(
+            shell("sleep 60 & echo $!; wait"),
+            Duration::from_secs(30),
+            Duration::ZERO,
+            &cancel,
+            None,
+        )
+        .await
+        .unwrap();
+        match outcome {
+            Outcome::Cancelled { stdout, .. } => assert!(!is_alive(&stdout)),
+            other => panic!("unexpected outcome: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_output_sink_batches() {
+        let chunks = Arc::new(Mutex::new(Vec::new()));
+        let sink: OutputSink = {
+            let chunks = chunks.clone();
+            Arc::new(move |stream, chunk| chunks.lock().unwrap().push((stream, chunk)))
+        };
+        let outcome = run(
+            shell("yes line | head -n 200000"),
+            Duration::from_secs(10),
+            Duration::ZERO,
+            &Notify::new(),
+            Some(sink),
+        )
+        .await
+        .unwrap();
+        let Outcome::Exited { stdout, .. } = outcome else {
+            panic!("unexpected outcome: {:?}", outcome);
+        };
+        assert_eq!(stdout.len(), 200000 * 5);
+
+        let chunks = chunks.lock().unwrap();
+        assert!(!chunks.is_empty());
+        assert!(chunks.len() < 50, "{} chunks", chunks.len());
+        for (stream, chunk) in chunks.iter() {
+            assert_eq!(*stream, OutputStream::Stdout);
+            assert!(chunk.ends_with('\n'));
+            assert!(chunk.len() <= MAX_OUTPUT_CHUNK + 40);
+        }
+        assert!(chunks[0].1.starts_with("line\n"));
+        assert!(chunks
+            .iter()
+            .any(|(_, chunk)| chunk.contains("bytes skipped")));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_resource_limits() {
+        let limits = ResourceLimits::new()
+            .with_max_open_files(64)
+            .with_max_memory_bytes(1 << 40);
+        let mut command = shell("ulimit -n; kill -KILL $$");
+        apply_resource_limits(&mut command, &limits);
+        let outcome = run(
+            command,
+            Duration::from_secs(5),
+            Duration::ZERO,
+            &Notify::new(),
+            None,
+        )
+        .await
+        .unwrap();
+        let Outcome::Exited { signal, stdout, .. } = outcome else {
+            panic!("unexpected outcome: {:?}", outcome);
+        };
+        assert_eq!(stdout, "64\n");
+        assert_eq!(signal, Some(libc::SIGKILL));
+        assert_eq!(
+            describe_signal(libc::SIGKILL, &limits),
+            "killed: exceeded memory limit (signal 9)"
+        );
+        assert_eq!(
+            describe_signal(libc::SIGKILL, &ResourceLimits::new()),
+            "killed by signal 9"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_spawn_error() {
+        let result = run(
+            Command::new("/nonexistent/a3s-cron-test"),
+            Duration::from_secs(5),
+            Duration::ZERO,
+            &Notify::new(),
+            None,
+        )
+        .await;
+        assert!(result.is_err());
+    }
+}
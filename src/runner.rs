@@ -0,0 +1,2142 @@
+//! Job execution
+//!
+//! Runs a single job and records the outcome. Shared by manual runs and the
+//! scheduler loop, which executes due jobs concurrently on their own tasks.
+
+use crate::audit::AuditLog;
+use crate::calendar::Calendar;
+use crate::clock::Clock;
+use crate::middleware::JobMiddleware;
+use crate::output;
+use crate::process::{self, Outcome, OutputSink};
+use crate::scheduler::{self, EventEnvelope, SchedulerEvent, MAX_MISSED_RUNS};
+use crate::store::CronStore;
+use crate::telemetry;
+use crate::template::{self, TemplateContext};
+use crate::types::{
+    mask_secrets, output_hash, AgentExecutor, BlackoutPolicy, CommandKind, ConcurrencyPolicy,
+    Coordination, CronJob, DurationStats, EnvMode, ExecutionStatus, JobContext, JobExecution,
+    JobStatus, JobType, MisfirePolicy, NativeJobHandler, OutputFiles, OutputStorage,
+    QueuedExecution, Result, RetentionPolicy, RunningExecution, TriggerKind, DEFAULT_SHELL,
+};
+use crate::window::TimeWindow;
+use chrono::{DateTime, Utc};
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
+use tokio::process::Command;
+use tokio::sync::{broadcast, mpsc, oneshot, Notify, OwnedSemaphorePermit, Semaphore};
+use tokio::time::Duration;
+use tracing::Instrument;
+
+/// Everything needed to execute a job, cheap to clone into spawned tasks
+#[derive(Clone)]
+pub(crate) struct JobRunner {
+    /// Storage backend
+    pub(crate) store: Arc<dyn CronStore>,
+    /// Event broadcaster
+    pub(crate) event_tx: broadcast::Sender<EventEnvelope>,
+    /// Default working directory
+    pub(crate) workspace: String,
+    /// Optional agent executor for agent-mode jobs
+    pub(crate) agent_executor: Option<Arc<dyn AgentExecutor>>,
+    /// Handlers of native jobs, by name
+    pub(crate) handlers: Arc<RwLock<HashMap<String, Arc<dyn NativeJobHandler>>>>,
+    /// Middlewares run around every attempt, in the order they were added
+    pub(crate) middlewares: Arc<RwLock<Vec<Arc<dyn JobMiddleware>>>>,
+    /// Wakes the scheduler loop when jobs or their next run times change
+    pub(crate) wakeup: Arc<Notify>,
+    /// Executions currently in flight, by job id
+    pub(crate) in_flight: InFlightMap,
+    /// Occurrences waiting for a job's execution in flight, by job id
+    pub(crate) pending: PendingMap,
+    /// Permits bounding how many jobs execute at once
+    pub(crate) limit: Option<Arc<Semaphore>>,
+    /// Signalled whenever the last in-flight execution finishes
+    pub(crate) idle: Arc<Notify>,
+    /// Blackout windows applying to every job
+    pub(crate) blackouts: Arc<Vec<TimeWindow>>,
+    /// Holiday calendars jobs can exclude the dates of, by name
+    pub(crate) calendars: Arc<RwLock<HashMap<String, Arc<Calendar>>>>,
+    /// Writer of emitted events to the store's audit log, when enabled
+    pub(crate) audit: Option<AuditLog>,
+    /// Shell for jobs that don't choose one
+    pub(crate) default_shell: Option<String>,
+    /// Environment inherited by jobs that don't choose
+    pub(crate) default_env_mode: EnvMode,
+    /// Timeout of jobs that don't set their own, in milliseconds
+    pub(crate) default_timeout_ms: Option<u64>,
+    /// How often running executions send a heartbeat, if at all
+    pub(crate) heartbeat_interval: Option<Duration>,
+    /// Time source deciding when jobs are next due
+    pub(crate) clock: Arc<dyn Clock>,
+    /// Name this manager holds execution leases under
+    pub(crate) instance_id: String,
+    /// How managers sharing the store avoid running a job twice
+    pub(crate) coordination: Coordination,
+    /// Whether this manager holds the leader lease
+    pub(crate) leading: Arc<AtomicBool>,
+    /// Executions of this manager holding each job's lease, by job id
+    pub(crate) held_leases: Arc<Mutex<HashMap<String, usize>>>,
+    /// Where execution output is kept
+    pub(crate) output_storage: OutputStorage,
+    /// How much execution history is kept
+    pub(crate) retention: RetentionPolicy,
+    /// Standard deviations above a job's mean duration at which a
+    /// successful run is reported as anomalous
+    pub(crate) anomaly_threshold: f64,
+}
+
+/// What started an execution
+#[derive(Debug, Clone)]
+pub(crate) enum Trigger {
+    /// The job came due on its schedule
+    Schedule,
+    /// Started through the API as the given execution, which lists the job
+    /// fields overridden for this run
+    Manual(Box<JobExecution>),
+    /// Started as the given execution right after the job was added
+    OnCreate(Box<JobExecution>),
+    /// Triggered by the successful execution with this id of an upstream job
+    Upstream(String),
+    /// Catching up on the occurrence due at this time, missed while the
+    /// scheduler was stopped
+    CatchUp(DateTime<Utc>),
+}
+
+impl Trigger {
+    /// Value of `CRON_TRIGGER` for executions started this way
+    fn label(&self) -> &'static str {
+        match self {
+            Trigger::Schedule | Trigger::CatchUp(_) => "scheduled",
+            Trigger::Manual(_) => "manual",
+            Trigger::OnCreate(_) => "created",
+            Trigger::Upstream(_) => "triggered",
+        }
+    }
+}
+
+/// How an execution was started, as told to its command
+#[derive(Debug, Clone, Copy)]
+struct Invocation {
+    /// Value of `CRON_TRIGGER`
+    trigger: &'static str,
+    /// Nominal fire time, or the start time of unscheduled runs
+    scheduled_for: DateTime<Utc>,
+}
+
+/// Executions currently in flight, by job id
+type InFlightMap = Arc<Mutex<HashMap<String, Vec<InFlight>>>>;
+
+/// Occurrences waiting for a job's execution in flight, by job id. Locked
+/// after [`InFlightMap`] whenever both are held.
+type PendingMap = Arc<Mutex<HashMap<String, VecDeque<Pending>>>>;
+
+/// An occurrence of a job with the `Queue` concurrency policy waiting for
+/// the job's execution in flight to finish
+pub(crate) struct Pending {
+    /// ID of the execution that will run
+    execution_id: String,
+    /// Job name
+    job_name: String,
+    /// Nominal fire time
+    scheduled_for: DateTime<Utc>,
+    /// When the occurrence was queued
+    queued_at: DateTime<Utc>,
+    /// Receives the occurrence's registry entry when its turn comes
+    turn: oneshot::Sender<Slot>,
+}
+
+/// Outcome of claiming a slot for a new execution
+enum Claim {
+    /// Run now
+    Run(Slot),
+    /// Run once the slot arrives
+    Wait(oneshot::Receiver<Slot>),
+    /// Don't run, for this reason
+    Skip(String),
+}
+
+/// An execution that has started and not yet finished
+pub(crate) struct InFlight {
+    /// Execution ID
+    pub(crate) execution_id: String,
+    /// Job name
+    pub(crate) job_name: String,
+    /// When the current attempt started
+    pub(crate) started_at: DateTime<Utc>,
+    /// Signalled to cancel the execution
+    pub(crate) cancel: Arc<Notify>,
+}
+
+/// An execution's entry in the in-flight registry, removed when dropped so
+/// that early returns and aborted tasks don't leave stale entries
+struct Slot {
+    in_flight: InFlightMap,
+    pending: PendingMap,
+    idle: Arc<Notify>,
+    job_id: String,
+    cancel: Arc<Notify>,
+    released: bool,
+}
+
+impl Slot {
+    /// Remove the entry, returning how many executions of the job remain
+    fn release(mut self) -> usize {
+        self.released = true;
+        self.remove()
+    }
+
+    /// Remove the entry, handing the job over to its next queued occurrence,
+    /// and wake anyone waiting for the runner to go idle
+    fn remove(&self) -> usize {
+        telemetry::record_jobs_running(-1);
+        let mut in_flight = self.in_flight.lock().unwrap();
+        let remaining = match in_flight.get_mut(&self.job_id) {
+            Some(running) => {
+                running.retain(|execution| !Arc::ptr_eq(&execution.cancel, &self.cancel));
+                if running.is_empty() {
+                    self.hand_off(running);
+                }
+                running.len()
+            }
+            None => 0,
+        };
+        if remaining == 0 {
+            in_flight.remove(&self.job_id);
+        }
+        if in_flight.is_empty() {
+            self.idle.notify_waiters();
+        }
+        remaining
+    }
+}
+
+impl Slot {
+    /// Start the job's next queued occurrence whose caller is still waiting,
+    /// registering it in `running` before the lock is released so that no
+    /// other run can slip in between
+    fn hand_off(&self, running: &mut Vec<InFlight>) {
+        let mut pending = self.pending.lock().unwrap();
+        let Some(queue) = pending.get_mut(&self.job_id) else {
+            return;
+        };
+        while let Some(next) = queue.pop_front() {
+            telemetry::record_job_queue_depth(&next.job_name, -1);
+            let cancel = Arc::new(Notify::new());
+            running.push(InFlight {
+                execution_id: next.execution_id,
+                job_name: next.job_name,
+                started_at: Utc::now(),
+                cancel: cancel.clone(),
+            });
+            telemetry::record_jobs_running(1);
+            let slot = Slot {
+                in_flight: self.in_flight.clone(),
+                pending: self.pending.clone(),
+                idle: self.idle.clone(),
+                job_id: self.job_id.clone(),
+                cancel,
+                released: false,
+            };
+            match next.turn.send(slot) {
+                Ok(()) => break,
+                Err(mut slot) => {
+                    // The caller gave up waiting; its entry is undone here
+                    // as the registry is already locked
+                    slot.released = true;
+                    running.pop();
+                    telemetry::record_jobs_running(-1);
+                }
+            }
+        }
+        if queue.is_empty() {
+            pending.remove(&self.job_id);
+        }
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        if !self.released {
+            self.remove();
+        }
+    }
+}
+
+impl JobRunner {
+    /// Execute a job, save the execution and update the job's statistics.
+    /// On success the job's downstream triggers are started; on failure its
+    /// `on_failure` hook.
+    pub(crate) async fn execute(&self, job: &CronJob, trigger: Trigger) -> Result<JobExecution> {
+        let span = tracing::info_span!(
+            "a3s.cron.execute_job",
+            a3s.cron.job_id = %job.id,
+            a3s.cron.job_name = %job.name,
+            a3s.cron.job_status = tracing::field::Empty,
+            a3s.cron.job_duration_ms = tracing::field::Empty,
+        );
+        let exec_start = Instant::now();
+
+        let manual = matches!(trigger, Trigger::Manual(_));
+        let label = trigger.label();
+        let mut execution = match trigger {
+            Trigger::Manual(execution) | Trigger::OnCreate(execution) => *execution,
+            Trigger::Upstream(upstream_execution_id) => {
+                let mut execution = JobExecution::new(&job.id);
+                execution.trigger = TriggerKind::Dependency {
+                    parent_execution_id: upstream_execution_id.clone(),
+                };
+                execution.triggered_by = Some(upstream_execution_id);
+                execution
+            }
+            Trigger::CatchUp(at) => {
+                let mut execution = JobExecution::new(&job.id);
+                execution.trigger = TriggerKind::CatchUp;
+                execution.scheduled_for = Some(at);
+                execution
+            }
+            Trigger::Schedule => self.scheduled_execution(job),
+        };
+        // Start times come from the clock, like the fire times and the
+        // intervals they are compared with
+        execution.started_at = self.clock.now();
+
+        // Manual runs are checked by the manager, as they can be forced
+        if job.min_interval_ms.is_some() && !manual {
+            let current = self.store.load_job(&job.id).await?;
+            if let Some(reason) = current
+                .unwrap_or_else(|| job.clone())
+                .rate_limit(self.clock.now())
+            {
+                return self.skip_execution(job, execution, &reason).await;
+            }
+        }
+        let invocation = Invocation {
+            trigger: label,
+            scheduled_for: execution.scheduled_for.unwrap_or(execution.started_at),
+        };
+        let (slot, execution) = match self.register(job, &execution, invocation.scheduled_for) {
+            Claim::Run(slot) => (slot, execution),
+            Claim::Skip(reason) => return self.skip_execution(job, execution, &reason).await,
+            Claim::Wait(turn) => match turn.await {
+                Ok(slot) => {
+                    let mut execution = execution;
+                    execution.started_at = self.clock.now();
+                    self.track(&job.id, &slot.cancel, &execution);
+                    (slot, execution)
+                }
+                Err(_) => {
+                    return self
+                        .skip_execution(job, execution, "Cancelled while queued")
+                        .await
+                }
+            },
+        };
+
+        let result = self
+            .run_attempts(job, execution, invocation, &slot.cancel)
+            .instrument(span.clone())
+            .await;
+        let remaining = slot.release();
+        let execution = result?;
+        // Vetoed by a middleware before it ever ran
+        if execution.status == ExecutionStatus::Skipped {
+            return Ok(execution);
+        }
+        let execution = self.finish(job, execution, remaining, manual).await?;
+        match execution.status {
+            ExecutionStatus::Success => self.trigger_downstream(job, &execution).await,
+            ExecutionStatus::Failed | ExecutionStatus::Timeout if job.on_failure.is_some() => {
+                let runner = self.clone();
+                let (job, failed) = (job.clone(), execution.clone());
+                tokio::spawn(async move { runner.run_failure_hook(&job, &failed).await });
+            }
+            _ => {}
+        }
+
+        span.record(telemetry::ATTR_JOB_STATUS, status_label(execution.status));
+        span.record(
+            telemetry::ATTR_JOB_DURATION_MS,
+            exec_start.elapsed().as_millis() as i64,
+        );
+
+        Ok(execution)
+    }
+
+    /// New execution of the job's occurrence that is due, if any
+    fn scheduled_execution(&self, job: &CronJob) -> JobExecution {
+        let mut execution = JobExecution::new(&job.id);
+        let now = self.clock.now();
+        execution.started_at = now;
+        execution.scheduled_for = job.next_run.filter(|at| *at <= now);
+        execution
+    }
+
+    /// Run the job, retrying failed or timed-out attempts according to its
+    /// retry policy. Every attempt is saved as its own execution; the last
+    /// one is returned. An attempt vetoed by a middleware is saved as
+    /// skipped: the first one is returned as is, a retry leaves the attempt
+    /// before it as the result.
+    async fn run_attempts(
+        &self,
+        job: &CronJob,
+        mut execution: JobExecution,
+        invocation: Invocation,
+        cancel: &Arc<Notify>,
+    ) -> Result<JobExecution> {
+        let mut previous: Option<JobExecution> = None;
+        loop {
+            if let Some(reason) = self.vetoed(job, &execution).await {
+                return match previous {
+                    None => self.skip_execution(job, execution, &reason).await,
+                    Some(failed) => {
+                        let skipped = execution.skip(&reason);
+                        self.save_execution(job, &skipped).await?;
+                        tracing::info!("Skipped retry of cron job {}: {}", job.id, reason);
+                        self.emit(SchedulerEvent::JobSkipped {
+                            job_id: job.id.clone(),
+                            job_name: job.name.clone(),
+                            reason,
+                            timestamp: Utc::now(),
+                        });
+                        Ok(failed)
+                    }
+                };
+            }
+
+            let attempt = execution.attempt;
+            let attempt_start = Instant::now();
+            let finished = self.run(job, execution, invocation, cancel).await?;
+            self.save_execution(job, &finished).await?;
+            self.after_middlewares(job, &finished).await;
+            telemetry::record_job_execution(
+                &job.name,
+                status_label(finished.status),
+                attempt_start.elapsed().as_secs_f64(),
+            );
+
+            let retryable = matches!(
+                finished.status,
+                ExecutionStatus::Failed | ExecutionStatus::Timeout
+            );
+            if !retryable || attempt > job.max_retries {
+                return Ok(finished);
+            }
+
+            let delay = job.retry_delay(attempt);
+            tracing::info!(
+                "Retrying cron job {} (attempt {}) in {:?}",
+                job.id,
+                attempt + 1,
+                delay
+            );
+            self.emit(SchedulerEvent::JobRetrying {
+                job_id: job.id.clone(),
+                job_name: job.name.clone(),
+                execution_id: finished.id.clone(),
+                attempt: attempt + 1,
+                delay_ms: delay.as_millis() as u64,
+                timestamp: Utc::now(),
+            });
+
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = cancel.notified() => return Ok(finished),
+            }
+
+            execution = JobExecution::new(&job.id);
+            execution.started_at = self.clock.now();
+            execution.attempt = attempt + 1;
+            execution.trigger = TriggerKind::Retry {
+                attempt: attempt + 1,
+            };
+            execution.scheduled_for = finished.scheduled_for;
+            execution.triggered_by = finished.triggered_by.clone();
+            execution.overrides = finished.overrides.clone();
+            self.track(&job.id, cancel, &execution);
+            previous = Some(finished);
+        }
+    }
+
+    /// The calendar the job excludes the dates of, if registered
+    pub(crate) fn calendar_of(&self, job: &CronJob) -> Option<Arc<Calendar>> {
+        let name = job.exclude_calendar.as_ref()?;
+        self.calendars.read().unwrap().get(name).cloned()
+    }
+
+    /// Next run of `job` after `after`, honoring the global blackouts and
+    /// the job's excluded calendar
+    pub(crate) fn next_run_after(
+        &self,
+        job: &CronJob,
+        after: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        job.next_run_after_excluding(after, &self.blackouts, self.calendar_of(job).as_deref())
+    }
+
+    /// Recalculate `job`'s next run after `after`, honoring the global
+    /// blackouts and the job's excluded calendar
+    pub(crate) fn reschedule(&self, job: &mut CronJob, after: DateTime<Utc>) {
+        let calendar = self.calendar_of(job);
+        job.reschedule_excluding(after, &self.blackouts, calendar.as_deref());
+    }
+
+    /// How far behind its fire time a scheduled or caught-up run is starting
+    fn lag(&self, execution: &JobExecution) -> Option<u64> {
+        if !matches!(
+            execution.trigger,
+            TriggerKind::Scheduled | TriggerKind::CatchUp
+        ) {
+            return None;
+        }
+        let lag = self.clock.now() - execution.scheduled_for?;
+        Some(lag.num_milliseconds().max(0) as u64)
+    }
+
+    /// Reason the first middleware vetoing this attempt gave, if any
+    async fn vetoed(&self, job: &CronJob, execution: &JobExecution) -> Option<String> {
+        let middlewares = self.middlewares.read().unwrap().clone();
+        for middleware in middlewares {
+            if let Err(reason) = middleware.before(job, execution).await {
+                tracing::debug!(
+                    "Middleware {} vetoed cron job {}: {}",
+                    middleware.name(),
+                    job.id,
+                    reason
+                );
+                return Some(reason.to_string());
+            }
+        }
+        None
+    }
+
+    /// Tell every middleware about a finished attempt, logging their errors
+    async fn after_middlewares(&self, job: &CronJob, execution: &JobExecution) {
+        let middlewares = self.middlewares.read().unwrap().clone();
+        for middleware in middlewares {
+            if let Err(e) = middleware.after(job, execution).await {
+                tracing::warn!(
+                    "Middleware {} failed after cron job {}: {}",
+                    middleware.name(),
+                    job.id,
+                    e
+                );
+            }
+        }
+    }
+
+    /// Claim a slot for a new execution, due at `scheduled_for`, according
+    /// to the job's concurrency policy
+    fn register(
+        &self,
+        job: &CronJob,
+        execution: &JobExecution,
+        scheduled_for: DateTime<Utc>,
+    ) -> Claim {
+        let mut in_flight = self.in_flight.lock().unwrap();
+        let running = in_flight.entry(job.id.clone()).or_default();
+
+        if !running.is_empty() {
+            match job.concurrency_policy {
+                ConcurrencyPolicy::Allow => {}
+                ConcurrencyPolicy::Forbid => {
+                    return Claim::Skip("Previous run still in progress".to_string())
+                }
+                ConcurrencyPolicy::Queue { max_queue } => {
+                    let mut pending = self.pending.lock().unwrap();
+                    let queue = pending.entry(job.id.clone()).or_default();
+                    if queue.len() >= max_queue {
+                        return Claim::Skip(format!(
+                            "Previous run still in progress and {} run(s) already queued",
+                            queue.len()
+                        ));
+                    }
+                    let (turn, wait) = oneshot::channel();
+                    queue.push_back(Pending {
+                        execution_id: execution.id.clone(),
+                        job_name: job.name.clone(),
+                        scheduled_for,
+                        queued_at: Utc::now(),
+                        turn,
+                    });
+                    telemetry::record_job_queue_depth(&job.name, 1);
+                    tracing::info!(
+                        "Queued execution {} of job {} behind the running one",
+                        execution.id,
+                        job.id
+                    );
+                    return Claim::Wait(wait);
+                }
+                ConcurrencyPolicy::Replace => {
+                    for execution in running.iter() {
+                        tracing::info!(
+                            "Replacing execution {} of job {}",
+                            execution.execution_id,
+                            job.id
+                        );
+                        execution.cancel.notify_one();
+                    }
+                }
+            }
+        }
+
+        drop(in_flight);
+        Claim::Run(self.occupy(job, execution))
+    }
+
+    /// Add an execution to the in-flight registry regardless of the job's
+    /// concurrency policy
+    fn occupy(&self, job: &CronJob, execution: &JobExecution) -> Slot {
+        let mut in_flight = self.in_flight.lock().unwrap();
+        let cancel = Arc::new(Notify::new());
+        in_flight.entry(job.id.clone()).or_default().push(InFlight {
+            execution_id: execution.id.clone(),
+            job_name: job.name.clone(),
+            started_at: execution.started_at,
+            cancel: cancel.clone(),
+        });
+        telemetry::record_jobs_running(1);
+        Slot {
+            in_flight: self.in_flight.clone(),
+            pending: self.pending.clone(),
+            idle: self.idle.clone(),
+            job_id: job.id.clone(),
+            cancel,
+            released: false,
+        }
+    }
+
+    /// Point an in-flight slot at the execution of a new attempt
+    fn track(&self, job_id: &str, cancel: &Arc<Notify>, execution: &JobExecution) {
+        let mut in_flight = self.in_flight.lock().unwrap();
+        if let Some(running) = in_flight.get_mut(job_id) {
+            for current in running.iter_mut() {
+                if Arc::ptr_eq(&current.cancel, cancel) {
+                    current.execution_id = execution.id.clone();
+                    current.started_at = execution.started_at;
+                }
+            }
+        }
+    }
+
+    /// Signal every running execution to stop and drop every queued one,
+    /// returning their IDs
+    pub(crate) fn cancel_all(&self) -> Vec<String> {
+        let in_flight = self.in_flight.lock().unwrap();
+        let mut cancelled: Vec<String> = in_flight
+            .values()
+            .flatten()
+            .map(|execution| {
+                execution.cancel.notify_one();
+                execution.execution_id.clone()
+            })
+            .collect();
+        let mut pending = self.pending.lock().unwrap();
+        for queued in pending.drain().flat_map(|(_, queue)| queue) {
+            telemetry::record_job_queue_depth(&queued.job_name, -1);
+            cancelled.push(queued.execution_id);
+        }
+        cancelled
+    }
+
+    /// Wait until no executions are in flight
+    pub(crate) async fn wait_idle(&self) {
+        loop {
+            let idle = self.idle.notified();
+            tokio::pin!(idle);
+            idle.as_mut().enable();
+            if self.in_flight.lock().unwrap().is_empty() {
+                return;
+            }
+            idle.await;
+        }
+    }
+
+    /// Snapshot of every execution currently in flight, oldest first
+    pub(crate) fn running(&self) -> Vec<RunningExecution> {
+        let now = self.clock.now();
+        let in_flight = self.in_flight.lock().unwrap();
+        let mut running: Vec<RunningExecution> = in_flight
+            .iter()
+            .flat_map(|(job_id, executions)| {
+                executions.iter().map(move |execution| RunningExecution {
+                    execution_id: execution.execution_id.clone(),
+                    job_id: job_id.clone(),
+                    job_name: execution.job_name.clone(),
+                    started_at: execution.started_at,
+                    elapsed_ms: (now - execution.started_at).num_milliseconds().max(0) as u64,
+                })
+            })
+            .collect();
+        running.sort_by_key(|execution| execution.started_at);
+        running
+    }
+
+    /// Snapshot of every occurrence queued behind a running execution of its
+    /// job, in the order they will run
+    pub(crate) fn queued(&self) -> Vec<QueuedExecution> {
+        let pending = self.pending.lock().unwrap();
+        let mut queued: Vec<QueuedExecution> = pending
+            .iter()
+            .flat_map(|(job_id, queue)| {
+                queue.iter().map(move |occurrence| QueuedExecution {
+                    execution_id: occurrence.execution_id.clone(),
+                    job_id: job_id.clone(),
+                    job_name: occurrence.job_name.clone(),
+                    scheduled_for: occurrence.scheduled_for,
+                    queued_at: occurrence.queued_at,
+                })
+            })
+            .collect();
+        // Stable, so each job's occurrences keep their order
+        queued.sort_by_key(|occurrence| occurrence.queued_at);
+        queued
+    }
+
+    /// Whether any execution of the job is in flight
+    pub(crate) fn is_running(&self, job_id: &str) -> bool {
+        self.in_flight.lock().unwrap().contains_key(job_id)
+    }
+
+    /// Signal a running execution to stop, or drop a queued one, returning
+    /// whether it was found
+    pub(crate) fn cancel(&self, execution_id: &str) -> bool {
+        let in_flight = self.in_flight.lock().unwrap();
+        let found = in_flight
+            .values()
+            .flatten()
+            .find(|execution| execution.execution_id == execution_id);
+        if let Some(execution) = found {
+            execution.cancel.notify_one();
+            return true;
+        }
+        let mut pending = self.pending.lock().unwrap();
+        for queue in pending.values_mut() {
+            if let Some(index) = queue.iter().position(|q| q.execution_id == execution_id) {
+                if let Some(queued) = queue.remove(index) {
+                    telemetry::record_job_queue_depth(&queued.job_name, -1);
+                }
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Signal every running execution of a job to stop and drop its queued
+    /// ones, returning their IDs
+    pub(crate) fn cancel_job(&self, job_id: &str) -> Vec<String> {
+        let in_flight = self.in_flight.lock().unwrap();
+        let mut cancelled: Vec<String> = in_flight
+            .get(job_id)
+            .into_iter()
+            .flatten()
+            .map(|execution| {
+                execution.cancel.notify_one();
+                execution.execution_id.clone()
+            })
+            .collect();
+        let queue = self.pending.lock().unwrap().remove(job_id);
+        for queued in queue.into_iter().flatten() {
+            telemetry::record_job_queue_depth(&queued.job_name, -1);
+            cancelled.push(queued.execution_id);
+        }
+        cancelled
+    }
+
+    /// Record a scheduled run that was skipped without executing
+    pub(crate) async fn skip(&self, job: &CronJob, reason: &str) -> Result<JobExecution> {
+        self.skip_execution(job, self.scheduled_execution(job), reason)
+            .await
+    }
+
+    /// Record `execution`, not yet started, as skipped
+    pub(crate) async fn skip_execution(
+        &self,
+        job: &CronJob,
+        execution: JobExecution,
+        reason: &str,
+    ) -> Result<JobExecution> {
+        self.skip_with(job, execution, reason, |_| {}).await
+    }
+
+    /// Record a scheduled run as failed because the scheduler loop panicked
+    /// while handling it
+    pub(crate) async fn record_panic(&self, job: &CronJob, message: &str) -> Result<JobExecution> {
+        let execution = self
+            .scheduled_execution(job)
+            .fail(format!("Scheduler panicked: {}", message));
+        self.save_execution(job, &execution).await?;
+        self.emit(SchedulerEvent::JobFailed {
+            job_id: job.id.clone(),
+            job_name: job.name.clone(),
+            execution_id: execution.id.clone(),
+            exit_code: None,
+            duration_ms: execution.duration_ms,
+            error: scheduler::event_error(execution.error.as_deref().unwrap_or_default()),
+            timestamp: Utc::now(),
+        });
+        Ok(execution)
+    }
+
+    /// Record a scheduled run skipped by the job's snooze, using it up
+    pub(crate) async fn skip_snoozed(&self, job: &CronJob) -> Result<JobExecution> {
+        let reason = match job.snoozed_until {
+            Some(until) if job.snoozed_runs == 0 => format!("Snoozed until {}", until),
+            _ => format!("Snoozed ({} run(s) left to skip)", job.snoozed_runs),
+        };
+        self.skip_with(
+            job,
+            self.scheduled_execution(job),
+            &reason,
+            CronJob::consume_snooze,
+        )
+        .await
+    }
+
+    /// Record a skipped run, applying `update` to the job once it has moved
+    /// past the skipped slot
+    async fn skip_with(
+        &self,
+        job: &CronJob,
+        execution: JobExecution,
+        reason: &str,
+        update: impl FnOnce(&mut CronJob),
+    ) -> Result<JobExecution> {
+        let execution = execution.skip(reason);
+        self.save_execution(job, &execution).await?;
+
+        // Move past the skipped slot so the scheduler doesn't retry it
+        if let Some(mut current) = self.store.load_job(&job.id).await? {
+            self.reschedule(&mut current, self.clock.now());
+            update(&mut current);
+            self.store.save_job(&current).await?;
+            self.wakeup.notify_one();
+        }
+
+        tracing::info!("Skipped cron job {}: {}", job.id, reason);
+        self.emit(SchedulerEvent::JobSkipped {
+            job_id: job.id.clone(),
+            job_name: job.name.clone(),
+            reason: reason.to_string(),
+            timestamp: Utc::now(),
+        });
+        Ok(execution)
+    }
+
+    /// Run the job's command, stopping early if cancelled
+    async fn run(
+        &self,
+        job: &CronJob,
+        mut execution: JobExecution,
+        invocation: Invocation,
+        cancel: &Notify,
+    ) -> Result<JobExecution> {
+        execution.lag_ms = self.lag(&execution);
+        if let Some(lag_ms) = execution.lag_ms {
+            telemetry::record_schedule_delay(&job.name, lag_ms as f64 / 1000.0);
+        }
+
+        // Emit start event
+        self.emit(SchedulerEvent::JobStarted {
+            job_id: job.id.clone(),
+            job_name: job.name.clone(),
+            execution_id: execution.id.clone(),
+            trigger: execution.trigger.clone(),
+            lag_ms: execution.lag_ms,
+            timestamp: Utc::now(),
+        });
+
+        // Update job status to running, moving next_run past the slot being
+        // served so the scheduler can see when the job is next due. The job
+        // is reloaded so changes made since it was picked up are kept.
+        if let Some(mut running_job) = self.store.load_job(&job.id).await? {
+            if running_job.status == JobStatus::Active {
+                running_job.status = JobStatus::Running;
+            }
+            running_job.next_run = self.next_run_after(&running_job, self.clock.now());
+            self.store.save_job(&running_job).await?;
+            self.wakeup.notify_one();
+        }
+
+        let execution_id = execution.id.clone();
+        let started = Instant::now();
+        let launch = self.launch(job, &execution_id, invocation, &[], cancel);
+        tokio::pin!(launch);
+        let overdue = async {
+            match job.expected_duration_ms {
+                Some(expected_ms) => tokio::time::sleep(Duration::from_millis(expected_ms)).await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::pin!(overdue);
+        let mut reported_long = false;
+        let mut heartbeat = self.heartbeat_interval.map(|every| {
+            let mut heartbeat =
+                tokio::time::interval_at(tokio::time::Instant::now() + every, every);
+            heartbeat.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            heartbeat
+        });
+        let result = loop {
+            tokio::select! {
+                result = &mut launch => break result,
+                _ = &mut overdue, if !reported_long => {
+                    reported_long = true;
+                    self.running_long(job, &execution);
+                }
+                _ = async {
+                    match heartbeat.as_mut() {
+                        Some(heartbeat) => heartbeat.tick().await,
+                        None => std::future::pending().await,
+                    }
+                } => self.heartbeat(job, &execution),
+            }
+        };
+        Ok(self.conclude(job, execution, started, result).await)
+    }
+
+    /// Report that an execution is still running. Heartbeats aren't written
+    /// to the audit log.
+    fn heartbeat(&self, job: &CronJob, execution: &JobExecution) {
+        let elapsed_ms = (self.clock.now() - execution.started_at)
+            .num_milliseconds()
+            .max(0) as u64;
+        tracing::debug!(
+            "Cron job {} execution {} still running after {}ms",
+            job.id,
+            execution.id,
+            elapsed_ms
+        );
+        let _ = self
+            .event_tx
+            .send(EventEnvelope::new(SchedulerEvent::JobHeartbeat {
+                job_id: job.id.clone(),
+                job_name: job.name.clone(),
+                execution_id: execution.id.clone(),
+                elapsed_ms,
+                timestamp: Utc::now(),
+            }));
+    }
+
+    /// Report an execution that passed its job's expected duration
+    fn running_long(&self, job: &CronJob, execution: &JobExecution) {
+        let elapsed_ms = (self.clock.now() - execution.started_at)
+            .num_milliseconds()
+            .max(0) as u64;
+        tracing::warn!(
+            "Cron job {} has been running for {}ms, longer than expected",
+            job.id,
+            elapsed_ms
+        );
+        telemetry::record_job_running_long(&job.name);
+        self.emit(SchedulerEvent::JobRunningLong {
+            job_id: job.id.clone(),
+            job_name: job.name.clone(),
+            execution_id: execution.id.clone(),
+            elapsed_ms,
+            timestamp: Utc::now(),
+        });
+    }
+
+    /// Report a successful run that took far longer than the job's earlier
+    /// successful runs
+    fn check_duration(
+        &self,
+        job: &CronJob,
+        execution: &JobExecution,
+        stats: &DurationStats,
+        duration_ms: u64,
+    ) {
+        if !stats.is_anomalous(duration_ms, self.anomaly_threshold) {
+            return;
+        }
+        let baseline_ms = stats.mean_ms.round() as u64;
+        tracing::warn!(
+            "Cron job {} took {}ms, against a usual {}ms",
+            job.id,
+            duration_ms,
+            baseline_ms
+        );
+        telemetry::record_job_duration_anomaly(&job.name);
+        self.emit(SchedulerEvent::JobDurationAnomaly {
+            job_id: job.id.clone(),
+            job_name: job.name.clone(),
+            execution_id: execution.id.clone(),
+            duration_ms,
+            baseline_ms,
+            timestamp: Utc::now(),
+        });
+    }
+
+    /// Run the job's command for the execution `execution_id` with its
+    /// timeout. Its environment gets the standard `CRON_*` variables, then
+    /// the job's own variables, then `extra_env`. Fails if the command could
+    /// not run or its template could not be rendered.
+    async fn launch(
+        &self,
+        job: &CronJob,
+        execution_id: &str,
+        invocation: Invocation,
+        extra_env: &[(&str, String)],
+        cancel: &Notify,
+    ) -> std::io::Result<Outcome> {
+        let timeout = Duration::from_millis(job.resolve_timeout_ms(self.default_timeout_ms));
+        let working_dir = job.working_dir.as_deref().unwrap_or(&self.workspace);
+
+        match job.job_type {
+            JobType::Agent => self.run_agent(job, working_dir, timeout, cancel).await,
+            JobType::Native => {
+                let ctx = JobContext {
+                    job_id: job.id.clone(),
+                    job_name: job.name.clone(),
+                    execution_id: execution_id.to_string(),
+                    scheduled_for: invocation.scheduled_for,
+                    trigger: invocation.trigger.to_string(),
+                    working_dir: working_dir.to_string(),
+                    env: job.env.clone(),
+                };
+                self.run_native(job, ctx, timeout, cancel).await
+            }
+            JobType::Shell => {
+                let rendered;
+                let job = if job.templated {
+                    let context = TemplateContext {
+                        job,
+                        execution_id,
+                        now: Utc::now(),
+                        workspace: &self.workspace,
+                    };
+                    rendered = template::render_job(&context).map_err(|e| {
+                        std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string())
+                    })?;
+                    &rendered
+                } else {
+                    job
+                };
+                let mut command = match job.command_kind {
+                    CommandKind::Exec => {
+                        let mut command = Command::new(&job.program);
+                        command.args(&job.args);
+                        command
+                    }
+                    CommandKind::Shell => {
+                        let shell = job
+                            .shell
+                            .as_deref()
+                            .or(self.default_shell.as_deref())
+                            .unwrap_or(DEFAULT_SHELL);
+                        process::shell_command(shell, &job.command)
+                    }
+                };
+                process::apply_env_mode(
+                    &mut command,
+                    job.env_mode.as_ref().unwrap_or(&self.default_env_mode),
+                );
+                process::apply_resource_limits(&mut command, &job.resource_limits);
+                command
+                    .current_dir(working_dir)
+                    .envs(self.standard_env(job, execution_id, invocation))
+                    .envs(job.env.iter().map(|(k, v)| (k.as_str(), v.as_str())))
+                    .envs(extra_env.iter().map(|(k, v)| (*k, v.as_str())));
+                let grace = Duration::from_millis(job.kill_grace_ms);
+                let sink = self.output_sink(job, execution_id);
+                process::run(command, timeout, grace, cancel, Some(sink)).await
+            }
+        }
+    }
+
+    /// Sink emitting a job's output as [`SchedulerEvent::JobOutput`] events
+    /// while anyone is subscribed. Sent directly rather than through `emit`
+    /// to keep output out of the audit log.
+    fn output_sink(&self, job: &CronJob, execution_id: &str) -> OutputSink {
+        let event_tx = self.event_tx.clone();
+        let (job_id, job_name) = (job.id.clone(), job.name.clone());
+        let execution_id = execution_id.to_string();
+        let secrets = job.secret_values();
+        Arc::new(move |stream, chunk| {
+            if event_tx.receiver_count() == 0 {
+                return;
+            }
+            let chunk = mask_secrets(&chunk, &secrets);
+            let _ = event_tx.send(EventEnvelope::new(SchedulerEvent::JobOutput {
+                job_id: job_id.clone(),
+                job_name: job_name.clone(),
+                execution_id: execution_id.clone(),
+                stream,
+                chunk,
+                timestamp: Utc::now(),
+            }));
+        })
+    }
+
+    /// Variables set for every command, so scripts can tell which run
+    /// invoked them:
+    ///
+    /// - `CRON_JOB_ID`, `CRON_JOB_NAME`: the job
+    /// - `CRON_EXECUTION_ID`: this execution (each retry attempt has its own)
+    /// - `CRON_SCHEDULED_TIME`: nominal fire time in RFC 3339, or the start
+    ///   time of runs not started by the schedule
+    /// - `CRON_TRIGGER`: `scheduled`, `manual`, `created` (run on creation),
+    ///   `triggered` (by an upstream job) or `hook` (an `on_failure` hook)
+    /// - `CRON_WORKSPACE`: the manager's workspace
+    ///
+    /// Agent jobs don't get these, as [`AgentExecutor`] has no way to pass
+    /// them on; native handlers get the same values in their [`JobContext`].
+    fn standard_env(
+        &self,
+        job: &CronJob,
+        execution_id: &str,
+        invocation: Invocation,
+    ) -> [(&'static str, String); 6] {
+        [
+            ("CRON_JOB_ID", job.id.clone()),
+            ("CRON_JOB_NAME", job.name.clone()),
+            ("CRON_EXECUTION_ID", execution_id.to_string()),
+            ("CRON_SCHEDULED_TIME", invocation.scheduled_for.to_rfc3339()),
+            ("CRON_TRIGGER", invocation.trigger.to_string()),
+            ("CRON_WORKSPACE", self.workspace.clone()),
+        ]
+    }
+
+    /// Complete `execution`, whose command was launched at `started`, with
+    /// the outcome of the command, masking the job's secrets and storing the
+    /// output where configured
+    async fn conclude(
+        &self,
+        job: &CronJob,
+        execution: JobExecution,
+        started: Instant,
+        mut result: std::io::Result<Outcome>,
+    ) -> JobExecution {
+        let secrets = job.secret_values();
+        let mut hash = None;
+        if let Ok(outcome) = &mut result {
+            let (stdout, stderr) = outcome.output_mut();
+            *stdout = mask_secrets(stdout, &secrets);
+            *stderr = mask_secrets(stderr, &secrets);
+            // Hashed in full, before it is truncated or moved to a file
+            if job.notify_on_change {
+                hash = Some(output_hash(stdout, job.ignore_whitespace_changes));
+            }
+        }
+        let output_files = self.store_output(job, &execution.id, &mut result).await;
+        let mut execution = self.record(job, execution, result);
+        // Timed by the monotonic clock from the start stamped by the runner's
+        // clock, which may not be the wall clock
+        let elapsed = chrono::Duration::from_std(started.elapsed()).unwrap_or_default();
+        execution.ended_at = Some(execution.started_at + elapsed);
+        execution.duration_ms = Some(elapsed.num_milliseconds() as u64);
+        execution.output_files = output_files;
+        if execution.status == ExecutionStatus::Success {
+            execution.output_changed = hash
+                .as_ref()
+                .map(|hash| job.last_output_hash.as_ref() != Some(hash));
+            execution.output_hash = hash;
+        }
+        execution.error = execution.error.map(|e| mask_secrets(&e, &secrets));
+        execution
+    }
+
+    /// Write the output of a finished command to files when output is kept
+    /// in files, leaving the outcome without output. The output stays in
+    /// the outcome if the files can't be written.
+    async fn store_output(
+        &self,
+        job: &CronJob,
+        execution_id: &str,
+        result: &mut std::io::Result<Outcome>,
+    ) -> Option<OutputFiles> {
+        let OutputStorage::Files {
+            dir,
+            max_total_bytes,
+        } = &self.output_storage
+        else {
+            return None;
+        };
+        let outcome = result.as_mut().ok()?;
+        let (stdout, stderr) = outcome.output_mut();
+        match output::write(dir, &job.id, execution_id, stdout, stderr).await {
+            Ok(files) => {
+                stdout.clear();
+                stderr.clear();
+                if let Err(e) = output::prune(dir, *max_total_bytes).await {
+                    tracing::warn!("Failed to prune output files in {}: {}", dir.display(), e);
+                }
+                Some(files)
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to write output of execution {}, keeping it inline: {}",
+                    execution_id,
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    /// Complete `execution` with the outcome of its command
+    fn record(
+        &self,
+        job: &CronJob,
+        execution: JobExecution,
+        result: std::io::Result<Outcome>,
+    ) -> JobExecution {
+        match result {
+            Ok(Outcome::Exited {
+                exit_code,
+                signal,
+                stdout,
+                stderr,
+            }) => {
+                // Shells report a command killed by a signal as 128 + signal
+                let limits = &job.resource_limits;
+                let signal = signal.or_else(|| {
+                    let shell_reported = job.job_type == JobType::Shell
+                        && job.command_kind == CommandKind::Shell
+                        && !limits.is_empty()
+                        && (129..=192).contains(&exit_code);
+                    shell_reported.then(|| exit_code - 128)
+                });
+                let mut execution = execution.complete(exit_code, stdout, stderr);
+                if let Some(signal) = signal {
+                    execution.error = Some(process::describe_signal(signal, limits));
+                }
+                execution
+            }
+            Ok(Outcome::Cancelled { stdout, stderr }) => {
+                self.emit(SchedulerEvent::JobCancelled {
+                    job_id: job.id.clone(),
+                    job_name: job.name.clone(),
+                    execution_id: execution.id.clone(),
+                    timestamp: Utc::now(),
+                });
+                execution.cancel().with_output(stdout, stderr)
+            }
+            Ok(Outcome::TimedOut {
+                stdout,
+                stderr,
+                termination,
+            }) => {
+                self.emit(SchedulerEvent::JobTimeout {
+                    job_id: job.id.clone(),
+                    job_name: job.name.clone(),
+                    execution_id: execution.id.clone(),
+                    timestamp: Utc::now(),
+                });
+                let execution = execution.timeout().with_output(stdout, stderr);
+                match termination {
+                    Some(termination) => execution.with_termination(termination),
+                    None => execution,
+                }
+            }
+            Err(e) => execution.fail(format!("Failed to execute command: {}", e)),
+        }
+    }
+
+    /// Send the job's prompt to the agent executor
+    async fn run_agent(
+        &self,
+        job: &CronJob,
+        working_dir: &str,
+        timeout: Duration,
+        cancel: &Notify,
+    ) -> std::io::Result<Outcome> {
+        let executor = self.agent_executor.clone().ok_or_else(|| {
+            std::io::Error::other("No agent executor configured for agent-mode cron job")
+        })?;
+        let config = job
+            .agent_config
+            .as_ref()
+            .ok_or_else(|| std::io::Error::other("Agent job missing agent_config"))?;
+
+        let no_output = || (String::new(), String::new());
+        Ok(tokio::select! {
+            result = executor.execute(config, &job.command, working_dir) => match result {
+                Ok(text) => Outcome::Exited { exit_code: 0, signal: None, stdout: text, stderr: String::new() },
+                Err(e) => Outcome::Exited { exit_code: 1, signal: None, stdout: String::new(), stderr: e },
+            },
+            _ = tokio::time::sleep(timeout) => {
+                let (stdout, stderr) = no_output();
+                Outcome::TimedOut { stdout, stderr, termination: None }
+            }
+            _ = cancel.notified() => {
+                let (stdout, stderr) = no_output();
+                Outcome::Cancelled { stdout, stderr }
+            }
+        })
+    }
+
+    /// Run the native handler named by the job's command
+    async fn run_native(
+        &self,
+        job: &CronJob,
+        ctx: JobContext,
+        timeout: Duration,
+        cancel: &Notify,
+    ) -> std::io::Result<Outcome> {
+        let handler = self
+            .handlers
+            .read()
+            .unwrap()
+            .get(&job.command)
+            .cloned()
+            .ok_or_else(|| {
+                std::io::Error::other(format!("No native handler registered as {:?}", job.command))
+            })?;
+
+        let no_output = || (String::new(), String::new());
+        Ok(tokio::select! {
+            result = handler.run(ctx) => match result {
+                Ok(output) => Outcome::Exited { exit_code: 0, signal: None, stdout: output.stdout, stderr: output.stderr },
+                Err(e) => Outcome::Exited { exit_code: 1, signal: None, stdout: String::new(), stderr: e },
+            },
+            _ = tokio::time::sleep(timeout) => {
+                let (stdout, stderr) = no_output();
+                Outcome::TimedOut { stdout, stderr, termination: None }
+            }
+            _ = cancel.notified() => {
+                let (stdout, stderr) = no_output();
+                Outcome::Cancelled { stdout, stderr }
+            }
+        })
+    }
+
+    /// Update the job's statistics after its final attempt
+    async fn finish(
+        &self,
+        job: &CronJob,
+        execution: JobExecution,
+        remaining: usize,
+        manual: bool,
+    ) -> Result<JobExecution> {
+        // Update job statistics on the stored job, keeping any status,
+        // schedule or command change made while it ran
+        let Some(mut updated_job) = self.store.load_job(&job.id).await? else {
+            tracing::info!("Cron job {} was removed while running", job.id);
+            return Ok(execution);
+        };
+        let was_exhausted = updated_job.runs_exhausted();
+        if updated_job.status == JobStatus::Running {
+            updated_job.status = if remaining > 0 {
+                JobStatus::Running
+            } else {
+                JobStatus::Active
+            };
+        }
+        updated_job.last_run = Some(execution.started_at);
+        updated_job.updated_at = Utc::now();
+        if manual {
+            updated_job.manual_run_count += 1;
+        }
+
+        match execution.status {
+            ExecutionStatus::Success => {
+                updated_job.run_count += 1;
+                updated_job.consecutive_failures = 0;
+                if execution.output_hash.is_some() {
+                    updated_job.last_output_hash = execution.output_hash.clone();
+                }
+                self.emit(SchedulerEvent::JobCompleted {
+                    job_id: job.id.clone(),
+                    job_name: job.name.clone(),
+                    execution_id: execution.id.clone(),
+                    exit_code: execution.exit_code,
+                    duration_ms: execution.duration_ms,
+                    output_changed: execution.output_changed,
+                    timestamp: Utc::now(),
+                });
+                if let Some(duration_ms) = execution.duration_ms {
+                    self.check_duration(job, &execution, &updated_job.duration_stats, duration_ms);
+                    updated_job.duration_stats.record(duration_ms);
+                }
+            }
+            // An operator's cancel isn't a failure of the job: JobCancelled
+            // was emitted when the run stopped, and the failure stats stay
+            ExecutionStatus::Cancelled => {}
+            _ => {
+                updated_job.fail_count += 1;
+                updated_job.last_error = Some(
+                    execution
+                        .error
+                        .clone()
+                        .or_else(|| {
+                            execution
+                                .exit_code
+                                .map(|code| format!("exit code {}", code))
+                        })
+                        .unwrap_or_else(|| execution.status.to_string()),
+                );
+                self.emit(SchedulerEvent::JobFailed {
+                    job_id: job.id.clone(),
+                    job_name: job.name.clone(),
+                    execution_id: execution.id.clone(),
+                    exit_code: execution.exit_code,
+                    duration_ms: execution.duration_ms,
+                    error: scheduler::event_error(execution.error.as_deref().unwrap_or_default()),
+                    timestamp: Utc::now(),
+                });
+
+                updated_job.consecutive_failures += 1;
+                let threshold_hit = updated_job
+                    .auto_pause_after
+                    .is_some_and(|limit| updated_job.consecutive_failures >= limit);
+                if threshold_hit
+                    && !matches!(updated_job.status, JobStatus::Paused | JobStatus::Disabled)
+                {
+                    updated_job.status = JobStatus::Paused;
+                    tracing::warn!(
+                        "Auto-paused cron job {} after {} consecutive failures",
+                        job.id,
+                        updated_job.consecutive_failures
+                    );
+                    self.emit(SchedulerEvent::JobAutoPaused {
+                        job_id: job.id.clone(),
+                        job_name: job.name.clone(),
+                        failures: updated_job.consecutive_failures,
+                        timestamp: Utc::now(),
+                    });
+                }
+            }
+        }
+
+        // Calculate next run
+        self.reschedule(&mut updated_job, self.clock.now());
+
+        if updated_job.runs_exhausted() && !was_exhausted {
+            tracing::info!(
+                "Cron job {} completed all {} runs",
+                job.id,
+                updated_job.counted_runs()
+            );
+            self.emit(SchedulerEvent::JobCompletedAllRuns {
+                job_id: job.id.clone(),
+                job_name: job.name.clone(),
+                runs: updated_job.counted_runs(),
+                timestamp: Utc::now(),
+            });
+        }
+
+        self.store.save_job(&updated_job).await?;
+        self.wakeup.notify_one();
+
+        Ok(execution)
+    }
+
+    /// Reset jobs left in `Running` status by a process that stopped
+    /// mid-execution, recording each interrupted run as failed. Jobs with an
+    /// execution in flight in this process are left alone.
+    pub(crate) async fn recover_interrupted(&self) -> Result<()> {
+        let now = self.clock.now();
+
+        for job in self.store.list_jobs().await? {
+            if job.status != JobStatus::Running || self.is_running(&job.id) {
+                continue;
+            }
+            // A job leased by another manager is still running there
+            if !self.acquire_lease(&job.id).await {
+                continue;
+            }
+            let recovered = self.recover_job(&job.id, now).await;
+            self.release_lease(&job.id).await;
+            recovered?;
+        }
+        Ok(())
+    }
+
+    /// Record the interrupted run of a job still left in `Running` status
+    async fn recover_job(&self, job_id: &str, now: DateTime<Utc>) -> Result<()> {
+        let Some(mut job) = self.store.load_job(job_id).await? else {
+            return Ok(());
+        };
+        if job.status != JobStatus::Running {
+            return Ok(());
+        }
+
+        let execution = JobExecution::new(&job.id).fail("Interrupted by shutdown".to_string());
+        self.save_execution(&job, &execution).await?;
+
+        job.status = JobStatus::Active;
+        job.fail_count += 1;
+        job.updated_at = now;
+        // Runs missed since then are left to the misfire policy
+        if job.next_run.is_none() {
+            self.reschedule(&mut job, now);
+        }
+        self.store.save_job(&job).await?;
+
+        tracing::warn!("Recovered interrupted cron job: {} ({})", job.name, job.id);
+        self.emit(SchedulerEvent::JobRecovered {
+            job_id: job.id.clone(),
+            job_name: job.name.clone(),
+            execution_id: execution.id,
+            timestamp: Utc::now(),
+        });
+        Ok(())
+    }
+
+    /// Apply each active job's misfire policy to the runs it missed while the
+    /// scheduler was stopped or paused. Skipped runs are recorded with `reason`.
+    pub(crate) async fn handle_misfires(&self, reason: &str) -> Result<()> {
+        let now = self.clock.now();
+
+        for job in self.store.list_jobs().await? {
+            if job.status != JobStatus::Active {
+                continue;
+            }
+            let Some(next_run) = job.next_run.filter(|next_run| *next_run <= now) else {
+                continue;
+            };
+            // With leases, the first manager to start handles the misfire
+            let Some(job) = self.lease_slot(&job, next_run).await else {
+                continue;
+            };
+            let caught_up = match self.handle_misfire(&job, next_run, now, reason).await {
+                Ok(caught_up) if !caught_up.is_empty() => caught_up,
+                result => {
+                    self.release_lease(&job.id).await;
+                    result?;
+                    continue;
+                }
+            };
+
+            let runner = self.clone();
+            let job_id = job.id;
+            tokio::spawn(async move {
+                let catch_up = async {
+                    for at in caught_up {
+                        let job = match runner.store.load_job(&job_id).await {
+                            Ok(Some(job)) => job,
+                            Ok(None) => break,
+                            Err(e) => {
+                                tracing::error!("Failed to load job {}: {}", job_id, e);
+                                break;
+                            }
+                        };
+                        let trigger = Trigger::CatchUp(at);
+                        if let Err(e) = runner.execute_limited(&job, false, trigger).await {
+                            tracing::error!("Failed to execute job {}: {}", job_id, e);
+                        }
+                    }
+                };
+                runner.leased(&job_id, catch_up).await;
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Apply the job's misfire policy to its runs missed since `next_run`,
+    /// returning the fire times still to catch up on
+    async fn handle_misfire(
+        &self,
+        job: &CronJob,
+        next_run: DateTime<Utc>,
+        now: DateTime<Utc>,
+        reason: &str,
+    ) -> Result<Vec<DateTime<Utc>>> {
+        // A snoozed job's missed run is skipped by the scheduler loop
+        if job.is_snoozed_at(next_run) {
+            return Ok(Vec::new());
+        }
+        // Move past the missed runs first, keeping changes made to the job
+        // since it was listed; FireOnce leaves that to the scheduler loop
+        if job.misfire_policy != MisfirePolicy::FireOnce
+            && self
+                .advance_past(&job.id, next_run, |stored| self.reschedule(stored, now))
+                .await?
+                .is_none()
+        {
+            return Ok(Vec::new());
+        }
+
+        // Only fire times the scheduler loop would have used: date and
+        // allowed windows, excluded calendars and deferral past blackouts
+        // come from next_run_after, and times in skipped blackouts are left
+        // out as the loop leaves them out
+        let mut missed = Vec::new();
+        let mut fire_time = Some(next_run);
+        for _ in 0..MAX_MISSED_RUNS {
+            let Some(at) = fire_time.filter(|at| *at <= now) else {
+                break;
+            };
+            let blacked_out = job.blackout_policy == BlackoutPolicy::Skip
+                && job.blackout_at(at, &self.blackouts).is_some();
+            if !blacked_out {
+                missed.push(at);
+            }
+            fire_time = self.next_run_after(job, at);
+        }
+
+        // Number of missed runs that still execute; FireOnce leaves its
+        // single run to the scheduler loop
+        let executed = match job.misfire_policy {
+            MisfirePolicy::FireOnce => 1,
+            MisfirePolicy::Skip => 0,
+            MisfirePolicy::CatchUp { max } => max as usize,
+        }
+        .min(missed.len());
+
+        // The most recent occurrences are the ones that run
+        let skipped = &missed[..missed.len() - executed];
+        for at in skipped {
+            let mut execution = JobExecution::new(&job.id).skip(reason);
+            execution.scheduled_for = Some(*at);
+            execution.started_at = *at;
+            execution.ended_at = Some(*at);
+            self.save_execution(job, &execution).await?;
+        }
+        if !skipped.is_empty() {
+            tracing::info!(
+                "Cron job {} missed {} run(s), skipped {}",
+                job.id,
+                missed.len(),
+                skipped.len()
+            );
+        }
+
+        if job.misfire_policy == MisfirePolicy::FireOnce {
+            return Ok(Vec::new());
+        }
+        Ok(missed[missed.len() - executed..].to_vec())
+    }
+
+    /// How often the leader renews its lease and followers ask for it,
+    /// under leader election
+    pub(crate) fn leader_renewal(&self) -> Option<Duration> {
+        match self.coordination {
+            Coordination::LeaderElection { lease_ttl } => Some(lease_ttl / 3),
+            _ => None,
+        }
+    }
+
+    /// Under leader election, take or keep the leader lease, announcing
+    /// changes of leadership. On taking over, interrupted runs are recovered
+    /// and, unless `paused`, missed runs handled. Returns whether this
+    /// manager schedules jobs, which it always does without leader election.
+    pub(crate) async fn campaign(&self, paused: bool) -> bool {
+        let Coordination::LeaderElection { lease_ttl } = self.coordination else {
+            return true;
+        };
+        let leading = match self
+            .store
+            .try_acquire_lease(scheduler::LEADER_LEASE, &self.instance_id, lease_ttl)
+            .await
+        {
+            Ok(leading) => leading,
+            Err(e) => {
+                tracing::error!("Failed to acquire the leader lease: {}", e);
+                false
+            }
+        };
+
+        match (self.leading.swap(leading, Ordering::SeqCst), leading) {
+            (false, true) => {
+                tracing::info!("Cron scheduler {} became leader", self.instance_id);
+                self.emit(SchedulerEvent::BecameLeader {
+                    instance_id: self.instance_id.clone(),
+                    timestamp: Utc::now(),
+                });
+                if let Err(e) = self.recover_interrupted().await {
+                    tracing::error!("Failed to recover interrupted jobs: {}", e);
+                }
+                if !paused {
+                    if let Err(e) = self.handle_misfires("Missed while no scheduler led").await {
+                        tracing::error!("Failed to process missed runs: {}", e);
+                    }
+                }
+            }
+            (true, false) => {
+                tracing::warn!("Cron scheduler {} lost leadership", self.instance_id);
+                self.emit(SchedulerEvent::LostLeadership {
+                    instance_id: self.instance_id.clone(),
+                    timestamp: Utc::now(),
+                });
+            }
+            _ => {}
+        }
+        leading
+    }
+
+    /// Give up the leader lease if this manager holds it
+    pub(crate) async fn resign(&self) {
+        if !self.leading.swap(false, Ordering::SeqCst) {
+            return;
+        }
+        if let Err(e) = self
+            .store
+            .release_lease(scheduler::LEADER_LEASE, &self.instance_id)
+            .await
+        {
+            tracing::error!("Failed to release the leader lease: {}", e);
+        }
+        tracing::info!("Cron scheduler {} gave up leadership", self.instance_id);
+        self.emit(SchedulerEvent::LostLeadership {
+            instance_id: self.instance_id.clone(),
+            timestamp: Utc::now(),
+        });
+    }
+
+    /// Duration of job leases, if jobs are leased
+    fn job_lease_ttl(&self) -> Option<Duration> {
+        match self.coordination {
+            Coordination::PerJobLease { lease_ttl } => Some(lease_ttl),
+            _ => None,
+        }
+    }
+
+    /// Take the job's execution lease, or share it with executions of this
+    /// manager already holding it. Always succeeds without leases.
+    pub(crate) async fn acquire_lease(&self, job_id: &str) -> bool {
+        let Some(ttl) = self.job_lease_ttl() else {
+            return true;
+        };
+        match self
+            .store
+            .try_acquire_lease(job_id, &self.instance_id, ttl)
+            .await
+        {
+            Ok(true) => {
+                *self
+                    .held_leases
+                    .lock()
+                    .unwrap()
+                    .entry(job_id.to_string())
+                    .or_default() += 1;
+                true
+            }
+            Ok(false) => false,
+            Err(e) => {
+                tracing::error!("Failed to acquire lease of job {}: {}", job_id, e);
+                false
+            }
+        }
+    }
+
+    /// Let go of a lease taken by [`acquire_lease`](Self::acquire_lease),
+    /// releasing it in the store once no execution of this manager holds it
+    pub(crate) async fn release_lease(&self, job_id: &str) {
+        if self.job_lease_ttl().is_none() {
+            return;
+        }
+        {
+            let mut held = self.held_leases.lock().unwrap();
+            match held.get_mut(job_id) {
+                Some(count) if *count > 1 => {
+                    *count -= 1;
+                    return;
+                }
+                Some(_) => {
+                    held.remove(job_id);
+                }
+                None => return,
+            }
+        }
+        if let Err(e) = self.store.release_lease(job_id, &self.instance_id).await {
+            tracing::error!("Failed to release lease of job {}: {}", job_id, e);
+        }
+    }
+
+    /// Take the lease of the job's occurrence due at `next_run`, returning
+    /// the job as stored if no other manager has claimed the occurrence yet.
+    /// Without leases the job is returned as is.
+    pub(crate) async fn lease_slot(
+        &self,
+        job: &CronJob,
+        next_run: DateTime<Utc>,
+    ) -> Option<CronJob> {
+        if self.job_lease_ttl().is_none() {
+            return Some(job.clone());
+        }
+        if !self.acquire_lease(&job.id).await {
+            tracing::debug!("Job {} is leased by another manager", job.id);
+            return None;
+        }
+        match self.store.load_job(&job.id).await {
+            Ok(Some(current)) if current.next_run == Some(next_run) => Some(current),
+            result => {
+                if let Err(e) = result {
+                    tracing::error!("Failed to load job {}: {}", job.id, e);
+                }
+                self.release_lease(&job.id).await;
+                None
+            }
+        }
+    }
+
+    /// Apply `advance` to the job as stored, moving it past its fire time
+    /// `slot`. The job is reloaded so changes made since it was listed, such
+    /// as a pause or an edit, are kept. Returns the saved job, or None without
+    /// saving if the job was removed, paused or disabled, or has already
+    /// moved past `slot`.
+    pub(crate) async fn advance_past(
+        &self,
+        job_id: &str,
+        slot: DateTime<Utc>,
+        advance: impl FnOnce(&mut CronJob),
+    ) -> Result<Option<CronJob>> {
+        let Some(mut job) = self.store.load_job(job_id).await? else {
+            return Ok(None);
+        };
+        if !matches!(job.status, JobStatus::Active | JobStatus::Running)
+            || job.next_run != Some(slot)
+        {
+            return Ok(None);
+        }
+        advance(&mut job);
+        self.store.save_job(&job).await?;
+        Ok(Some(job))
+    }
+
+    /// Run `work` holding a lease taken by
+    /// [`acquire_lease`](Self::acquire_lease), renewing it until `work`
+    /// finishes and releasing it after
+    pub(crate) async fn leased<T>(&self, job_id: &str, work: impl Future<Output = T>) -> T {
+        let Some(ttl) = self.job_lease_ttl() else {
+            return work.await;
+        };
+        tokio::pin!(work);
+        let mut renewal = tokio::time::interval(ttl / 3);
+        renewal.tick().await;
+        let output = loop {
+            tokio::select! {
+                output = &mut work => break output,
+                _ = renewal.tick() => {
+                    match self.store.renew_lease(job_id, &self.instance_id, ttl).await {
+                        Ok(true) => {}
+                        Ok(false) => tracing::warn!("Lost the lease of job {}", job_id),
+                        Err(e) => tracing::error!("Failed to renew lease of job {}: {}", job_id, e),
+                    }
+                }
+            }
+        };
+        self.release_lease(job_id).await;
+        output
+    }
+
+    /// Execute a job once a concurrency permit is available.
+    ///
+    /// With `bypass_limit` the job runs immediately even when the manager's
+    /// `max_concurrent_jobs` limit is reached.
+    pub(crate) async fn execute_limited(
+        &self,
+        job: &CronJob,
+        bypass_limit: bool,
+        trigger: Trigger,
+    ) -> Result<JobExecution> {
+        let _permit = if bypass_limit {
+            None
+        } else {
+            self.enqueued();
+            self.acquire().await
+        };
+        self.execute(job, trigger).await
+    }
+
+    /// Run `job`'s `on_failure` hook for its `failed` execution. The hook is
+    /// the job of that name, or else an inline command run like `job`, and is
+    /// told about the failure through `CRON_*` environment variables. Hook
+    /// runs are saved but never update job statistics or run hooks of their
+    /// own, so a failing hook can't start a loop.
+    async fn run_failure_hook(&self, job: &CronJob, failed: &JobExecution) {
+        let Some(hook) = &job.on_failure else {
+            return;
+        };
+        let hook_job = match self.store.find_job_by_name(hook).await {
+            Ok(Some(found)) => found,
+            Ok(None) => {
+                let mut inline = job.clone();
+                inline.job_type = JobType::Shell;
+                inline.command_kind = CommandKind::Shell;
+                inline.command = hook.clone();
+                inline
+            }
+            Err(e) => {
+                tracing::error!("Failed to load on_failure job {}: {}", hook, e);
+                return;
+            }
+        };
+
+        let mut execution = JobExecution::new(&hook_job.id);
+        execution.started_at = self.clock.now();
+        execution.trigger = TriggerKind::Dependency {
+            parent_execution_id: failed.id.clone(),
+        };
+        execution.hook_for = Some(failed.id.clone());
+        let error = match &failed.error {
+            Some(error) => error.clone(),
+            None => failed.stderr.trim().to_string(),
+        };
+        let env = [
+            ("CRON_FAILED_JOB", job.name.clone()),
+            (
+                "CRON_EXIT_CODE",
+                failed
+                    .exit_code
+                    .map(|code| code.to_string())
+                    .unwrap_or_default(),
+            ),
+            ("CRON_ERROR", error),
+            ("CRON_EXECUTION_ID", failed.id.clone()),
+        ];
+        let invocation = Invocation {
+            trigger: "hook",
+            scheduled_for: execution.started_at,
+        };
+
+        tracing::info!("Running on_failure hook of cron job {}", job.id);
+        let slot = self.occupy(&hook_job, &execution);
+        let started = Instant::now();
+        let result = self
+            .launch(&hook_job, &execution.id, invocation, &env, &slot.cancel)
+            .await;
+        slot.release();
+        let execution = self.conclude(&hook_job, execution, started, result).await;
+        if let Err(e) = self.save_execution(&hook_job, &execution).await {
+            tracing::error!("Failed to save on_failure hook execution: {}", e);
+        }
+        if execution.status != ExecutionStatus::Success {
+            tracing::warn!(
+                "on_failure hook of cron job {} ended with status {}",
+                job.id,
+                status_label(execution.status)
+            );
+        }
+        self.emit(SchedulerEvent::JobHookFinished {
+            job_id: job.id.clone(),
+            job_name: job.name.clone(),
+            execution_id: failed.id.clone(),
+            hook_execution_id: execution.id,
+            status: execution.status,
+            timestamp: Utc::now(),
+        });
+    }
+
+    /// Start the jobs listed in `job.triggers` after its successful
+    /// `execution`. Each runs on its own task under its own concurrency
+    /// policy; paused, completed and missing jobs are passed over.
+    async fn trigger_downstream(&self, job: &CronJob, execution: &JobExecution) {
+        for reference in &job.triggers {
+            let downstream = match self.store.load_job(reference).await {
+                Ok(Some(found)) => Some(found),
+                Ok(None) => self.store.find_job_by_name(reference).await.ok().flatten(),
+                Err(e) => {
+                    tracing::error!("Failed to load triggered job {}: {}", reference, e);
+                    continue;
+                }
+            };
+            let Some(downstream) = downstream else {
+                tracing::warn!("Cron job {} triggers unknown job {}", job.id, reference);
+                continue;
+            };
+            if matches!(
+                downstream.status,
+                JobStatus::Paused | JobStatus::Completed | JobStatus::Disabled
+            ) {
+                tracing::info!("Not triggering {} job {}", downstream.status, downstream.id);
+                continue;
+            }
+
+            tracing::info!("Cron job {} triggered job {}", job.id, downstream.id);
+            self.emit(SchedulerEvent::JobTriggered {
+                job_id: downstream.id.clone(),
+                job_name: downstream.name.clone(),
+                upstream_job_id: job.id.clone(),
+                upstream_execution_id: execution.id.clone(),
+                timestamp: Utc::now(),
+            });
+            tokio::spawn(self.execute_triggered(downstream, execution.id.clone()));
+        }
+    }
+
+    /// Execute a triggered job. Boxed because it runs `execute`, which
+    /// triggers further jobs in turn.
+    fn execute_triggered(
+        &self,
+        job: CronJob,
+        upstream_execution_id: String,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        let runner = self.clone();
+        Box::pin(async move {
+            let trigger = Trigger::Upstream(upstream_execution_id);
+            if let Err(e) = runner.execute_limited(&job, false, trigger).await {
+                tracing::error!("Failed to execute job {}: {}", job.id, e);
+            }
+        })
+    }
+
+    /// Save an execution record of `job`, unless it doesn't record history,
+    /// then prune its history according to the retention policy. Pruning
+    /// failures are logged, not returned.
+    pub(crate) async fn save_execution(
+        &self,
+        job: &CronJob,
+        execution: &JobExecution,
+    ) -> Result<()> {
+        if !job.record_history {
+            return Ok(());
+        }
+        self.store.save_execution(execution).await?;
+        if let Err(e) = self.apply_retention(&execution.job_id).await {
+            tracing::warn!(
+                "Failed to prune history of cron job {}: {}",
+                execution.job_id,
+                e
+            );
+        }
+        Ok(())
+    }
+
+    /// Delete the executions of a job the retention policy doesn't keep
+    async fn apply_retention(&self, job_id: &str) -> Result<()> {
+        let mut pruned = 0;
+        if let Some(max) = self.retention.max_executions_per_job {
+            pruned += self.store.prune_executions(job_id, max).await?;
+        }
+        if let Some(max_age) = self.retention.max_age {
+            let max_age = chrono::Duration::from_std(max_age).unwrap_or(chrono::TimeDelta::MAX);
+            if let Some(cutoff) = Utc::now().checked_sub_signed(max_age) {
+                pruned += self.store.prune_executions_before(job_id, cutoff).await?;
+            }
+        }
+        if pruned > 0 {
+            tracing::debug!("Pruned {} execution(s) of cron job {}", pruned, job_id);
+        }
+        Ok(())
+    }
+
+    /// Broadcast an event to subscribers and write it to the audit log
+    pub(crate) fn emit(&self, event: SchedulerEvent) {
+        let envelope = EventEnvelope::new(event);
+        if let Some(audit) = &self.audit {
+            audit.record(&envelope);
+        }
+        let _ = self.event_tx.send(envelope);
+    }
+
+    /// Count a job as waiting for a concurrency permit
+    fn enqueued(&self) {
+        if self.limit.is_some() {
+            telemetry::record_jobs_queued(1);
+        }
+    }
+
+    /// Wait for a concurrency permit for a job counted by `enqueued`.
+    /// Returns `None` when no limit is configured.
+    async fn acquire(&self) -> Option<OwnedSemaphorePermit> {
+        let limit = self.limit.clone()?;
+        let permit = limit.acquire_owned().await.ok();
+        telemetry::record_jobs_queued(-1);
+        permit
+    }
+
+    /// Start a task that executes dispatched jobs on their own tasks as
+    /// concurrency permits become available. Each free permit goes to the
+    /// most urgent queued job: highest priority first, then the one due
+    /// earliest, then the one dispatched first.
+    pub(crate) fn dispatcher(&self) -> Dispatcher {
+        let (tx, mut rx) = mpsc::unbounded_channel::<CronJob>();
+        let runner = self.clone();
+        tokio::spawn(async move {
+            let mut queue = BinaryHeap::new();
+            let mut sequence = 0u64;
+            let mut enqueue = |queue: &mut BinaryHeap<Queued>, job| {
+                sequence += 1;
+                queue.push(Queued { job, sequence });
+            };
+            loop {
+                if queue.is_empty() {
+                    match rx.recv().await {
+                        Some(job) => enqueue(&mut queue, job),
+                        None => break,
+                    }
+                }
+                let permit = runner.acquire().await;
+                while let Ok(job) = rx.try_recv() {
+                    enqueue(&mut queue, job);
+                }
+                let Some(Queued { job, .. }) = queue.pop() else {
+                    break;
+                };
+                // The scheduler stopped while the job was queued
+                if rx.is_closed() {
+                    tracing::info!("Dropping queued run of job {} after stop", job.id);
+                    runner.release_lease(&job.id).await;
+                    continue;
+                }
+                let runner = runner.clone();
+                tokio::spawn(async move {
+                    let _permit = permit;
+                    let execution = runner.execute(&job, Trigger::Schedule);
+                    if let Err(e) = runner.leased(&job.id, execution).await {
+                        tracing::error!("Failed to execute job {}: {}", job.id, e);
+                    }
+                });
+            }
+        });
+        Dispatcher {
+            runner: self.clone(),
+            tx,
+        }
+    }
+}
+
+/// Label used for an execution status in telemetry
+fn status_label(status: ExecutionStatus) -> &'static str {
+    match status {
+        ExecutionStatus::Success => "success",
+        ExecutionStatus::Timeout => "timeout",
+        ExecutionStatus::Cancelled => "cancelled",
+        ExecutionStatus::Skipped => "skipped",
+        ExecutionStatus::Failed => "failed",
+    }
+}
+
+/// A dispatched job waiting for a concurrency permit, ordered so that the
+/// most urgent job is the greatest
+struct Queued {
+    job: CronJob,
+    /// Dispatch order
+    sequence: u64,
+}
+
+impl Ord for Queued {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.job
+            .priority
+            .cmp(&other.job.priority)
+            .then_with(|| other.job.next_run.cmp(&self.job.next_run))
+            .then_with(|| other.sequence.cmp(&self.sequence))
+    }
+}
+
+impl PartialOrd for Queued {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Queued {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == std::cmp::Ordering::Equal
+    }
+}
+
+impl Eq for Queued {}
+
+/// Queue of due jobs feeding a runner; see [`JobRunner::dispatcher`]
+pub(crate) struct Dispatcher {
+    runner: JobRunner,
+    tx: mpsc::UnboundedSender<CronJob>,
+}
+
+impl Dispatcher {
+    /// Queue a job for execution
+    pub(crate) fn dispatch(&self, job: CronJob) {
+        self.runner.enqueued();
+        let _ = self.tx.send(job);
+    }
+}
@@ -0,0 +1,267 @@
+//! Typed schedule builder
+//!
+//! Builds [`CronExpression`]s programmatically instead of formatting strings:
+//!
+//! ```
+//! use a3s_cron::{CronExpression, CronField};
+//! use chrono::Weekday::*;
+//!
+//! let expr = CronExpression::builder()
+//!     .minutes(CronField::Every(5))
+//!     .hours(CronField::Range(9, 17))
+//!     .weekdays([Mon, Tue, Wed, Thu, Fri])
+//!     .build()
+//!     .unwrap();
+//!
+//! assert_eq!(expr.expression, "*/5 9-17 * * 1,2,3,4,5");
+//! ```
+
+use crate::parser::{CronExpression, DayMatch};
+use crate::types::{CronError, Result};
+use chrono::Weekday;
+
+/// Value of a single cron field
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum CronField {
+    /// Any value (`*`)
+    #[default]
+    Any,
+    /// A single value (`5`)
+    At(u32),
+    /// Every n-th value across the whole field (`*/5`)
+    Every(u32),
+    /// An inclusive range (`9-17`)
+    Range(u32, u32),
+    /// Every n-th value within an inclusive range (`0-30/10`)
+    RangeEvery(u32, u32, u32),
+    /// A list of values (`1,15`)
+    List(Vec<u32>),
+}
+
+impl CronField {
+    /// Render the field in cron syntax
+    fn render(&self, name: &str) -> Result<String> {
+        Ok(match self {
+            CronField::Any => "*".to_string(),
+            CronField::At(value) => value.to_string(),
+            CronField::Every(step) => format!("*/{}", step),
+            CronField::Range(start, end) => format!("{}-{}", start, end),
+            CronField::RangeEvery(start, end, step) => format!("{}-{}/{}", start, end, step),
+            CronField::List(values) => {
+                if values.is_empty() {
+                    return Err(CronError::InvalidExpression(format!(
+                        "No valid values in {}",
+                        name
+                    )));
+                }
+                values
+                    .iter()
+                    .map(|v| v.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            }
+        })
+    }
+}
+
+/// Builder for [`CronExpression`]
+///
+/// Every field defaults to [`CronField::Any`]. Values are validated by
+/// [`build`](Self::build), which reports the same errors as
+/// [`CronExpression::parse`].
+#[derive(Debug, Clone, Default)]
+pub struct ScheduleBuilder {
+    minutes: CronField,
+    hours: CronField,
+    days: CronField,
+    months: CronField,
+    weekdays: CronField,
+    day_match: DayMatch,
+}
+
+impl ScheduleBuilder {
+    /// Create a builder matching every minute
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the minute field (0-59)
+    pub fn minutes(mut self, field: CronField) -> Self {
+        self.minutes = field;
+        self
+    }
+
+    /// Set the hour field (0-23)
+    pub fn hours(mut self, field: CronField) -> Self {
+        self.hours = field;
+        self
+    }
+
+    /// Set the day-of-month field (1-31)
+    pub fn days(mut self, field: CronField) -> Self {
+        self.days = field;
+        self
+    }
+
+    /// Set the month field (1-12)
+    pub fn months(mut self, field: CronField) -> Self {
+        self.months = field;
+        self
+    }
+
+    /// Restrict the schedule to the given days of the week
+    pub fn weekdays(mut self, weekdays: impl IntoIterator<Item = Weekday>) -> Self {
+        let mut values: Vec<u32> = weekdays
+            .into_iter()
+            .map(|d| d.num_days_from_sunday())
+            .collect();
+        values.sort_unstable();
+        values.dedup();
+        self.weekdays = CronField::List(values);
+        self
+    }
+
+    /// Set the day-of-week field directly (0-7, 0 or 7 = Sunday)
+    pub fn weekday_field(mut self, field: CronField) -> Self {
+        self.weekdays = field;
+        self
+    }
+
+    /// Set how restricted day-of-month and day-of-week fields combine
+    pub fn day_match(mut self, day_match: DayMatch) -> Self {
+        self.day_match = day_match;
+        self
+    }
+
+    /// Render the canonical cron expression string
+    pub fn to_expression(&self) -> Result<String> {
+        let weekday_prefix = match self.day_match {
+            DayMatch::Either => "",
+            DayMatch::Both => "&",
+        };
+        Ok(format!(
+            "{} {} {} {} {}{}",
+            self.minutes.render("minute")?,
+            self.hours.render("hour")?,
+            self.days.render("day")?,
+            self.months.render("month")?,
+            weekday_prefix,
+            self.weekdays.render("weekday")?,
+        ))
+    }
+
+    /// Validate the fields and build the expression
+    pub fn build(&self) -> Result<CronExpression> {
+        CronExpression::parse_strict(&self.to_expression()?)
+    }
+}
+
+impl CronExpression {
+    /// Start building an expression with typed fields
+    pub fn builder() -> ScheduleBuilder {
+        ScheduleBuilder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use Weekday::*;
+
+    #[test]
+    fn test_builder_default_every_minute() {
+        let expr = CronExpression::builder().build().unwrap();
+        assert_eq!(expr.expression, "* * * * *");
+    }
+
+    #[test]
+    fn test_builder_fields() {
+        let expr = CronExpression::builder()
+            .minutes(CronField::Every(5))
+            .hours(CronField::Range(9, 17))
+            .weekdays([Fri, Mon, Wed, Tue, Thu])
+            .build()
+            .unwrap();
+        assert_eq!(expr.expression, "*/5 9-17 * * 1,2,3,4,5");
+
+        let expr = CronExpression::builder()
+            .minutes(CronField::RangeEvery(0, 30, 10))
+            .hours(CronField::At(2))
+            .days(CronField::List(vec![1, 15]))
+            .months(CronField::Range(6, 8))
+            .build()
+            .unwrap();
+        assert_eq!(expr.expression, "0-30/10 2 1,15 6-8 *");
+    }
+
+    #[test]
+    fn test_builder_day_match() {
+        let expr = CronExpression::builder()
+            .minutes(CronField::At(0))
+            .hours(CronField::At(0))
+            .days(CronField::At(13))
+            .weekdays([Fri])
+            .day_match(DayMatch::Both)
+            .build()
+            .unwrap();
+        assert_eq!(expr.expression, "0 0 13 * &5");
+        assert_eq!(expr.day_match(), DayMatch::Both);
+    }
+
+    #[test]
+    fn test_builder_rejects_invalid_values() {
+        let err = CronExpression::builder()
+            .minutes(CronField::At(60))
+            .build()
+            .unwrap_err();
+        assert!(matches!(err, CronError::InvalidExpression(_)));
+        assert!(err.to_string().contains("minute"));
+
+        assert!(CronExpression::builder()
+            .hours(CronField::Range(17, 9))
+            .build()
+            .is_err());
+        assert!(CronExpression::builder()
+            .minutes(CronField::Every(0))
+            .build()
+            .is_err());
+        assert!(CronExpression::builder()
+            .days(CronField::List(vec![]))
+            .build()
+            .is_err());
+        assert!(CronExpression::builder()
+            .days(CronField::At(0))
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_builder_round_trip() {
+        let builders = [
+            CronExpression::builder()
+                .minutes(CronField::Every(5))
+                .hours(CronField::Range(9, 17))
+                .weekdays([Mon, Tue, Wed, Thu, Fri]),
+            CronExpression::builder()
+                .minutes(CronField::At(30))
+                .hours(CronField::At(2))
+                .days(CronField::List(vec![1, 15])),
+            CronExpression::builder()
+                .minutes(CronField::At(0))
+                .months(CronField::RangeEvery(1, 12, 3))
+                .weekday_field(CronField::At(7)),
+        ];
+
+        for builder in builders {
+            let built = builder.build().unwrap();
+            let parsed = CronExpression::parse(&built.expression).unwrap();
+            assert_eq!(built, parsed);
+
+            let start = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+            let a: Vec<_> = built.upcoming(start).take(10).collect();
+            let b: Vec<_> = parsed.upcoming(start).take(10).collect();
+            assert_eq!(a, b);
+        }
+    }
+}
@@ -2,60 +2,724 @@
 //!
 //! Provides background task scheduling and execution management.
 
-use crate::parser::CronExpression;
-use crate::store::{CronStore, FileCronStore};
+use crate::audit::AuditLog;
+use crate::builder::CronManagerBuilder;
+use crate::calendar::Calendar;
+use crate::clock::{Clock, SystemClock};
+use crate::crontab;
+use crate::ics;
+use crate::k8s::{CronJobManifest, K8sExportOptions};
+use crate::middleware::JobMiddleware;
+use crate::notifier::{self, Notifier};
+use crate::output;
+use crate::parser::{self, CronExpression};
+use crate::process;
+use crate::runner::{Dispatcher, JobRunner, Trigger};
+use crate::store::{CronStore, EventFilter, ExecutionFilter, FileCronStore};
+use crate::systemd::{self, SystemdUnit};
 use crate::telemetry;
-use crate::types::{AgentExecutor, AgentJobConfig, CronError, CronJob, JobExecution, JobStatus, JobType, Result};
-use chrono::Utc;
-use std::path::Path;
+use crate::types::{
+    AgentExecutor, AgentJobConfig, BlackoutPolicy, BulkResult, Coordination, CronError, CronJob,
+    DurationStats, EnvMode, ExecutionStatus, HealthThresholds, ImportMode, ImportReport,
+    JobExecution, JobFilter, JobHealth, JobManifest, JobOptions, JobStats, JobStatus, JobType,
+    JobsExport, ManifestJob, ManifestPlan, NativeJobHandler, OutputStorage, OutputStream,
+    QueuedExecution, ResourceLimits, Result, RetentionPolicy, RunningExecution, ScheduleInfo,
+    ScheduleKind, SimulatedRun, TriggerKind, UpdateJobRequest, DEFAULT_ANOMALY_THRESHOLD,
+    DEFAULT_SHELL, EXPORT_VERSION, MAX_TAG_LEN, NEVER_SCHEDULE, SECRET_MASK,
+};
+use crate::window::TimeWindow;
+use chrono::{DateTime, NaiveDate, Utc};
+use futures_util::{FutureExt, Stream};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::panic::AssertUnwindSafe;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::Instant;
-use tokio::process::Command;
-use tokio::sync::{broadcast, RwLock};
-use tokio::time::{interval, Duration};
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::sync::{broadcast, Notify, RwLock, Semaphore};
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+use uuid::Uuid;
+
+/// Number of upcoming fire times sampled when analyzing a schedule
+const SCHEDULE_SAMPLES: usize = 10;
+
+/// Default longest the scheduler sleeps before re-reading the store
+pub const DEFAULT_TICK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Default time between heartbeats of a running execution
+pub const DEFAULT_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Default number of tick intervals the scheduler loop may go without a
+/// tick before the watchdog reports it stalled
+pub const DEFAULT_STALL_TICKS: u32 = 3;
+
+/// Time executions get to finish when SIGTERM or SIGINT shuts the manager
+/// down, once [`CronManager::install_signal_handlers`] is called
+pub const SIGNAL_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default number of events buffered for each subscriber
+pub const DEFAULT_EVENT_CAPACITY: usize = 100;
+
+/// Maximum number of missed runs considered per job on startup
+pub(crate) const MAX_MISSED_RUNS: usize = 1000;
+
+/// Maximum number of fire times returned by the preview APIs
+pub const MAX_PREVIEW_COUNT: usize = 1000;
+
+/// Name of the store lease held by the leader under
+/// [`Coordination::LeaderElection`]
+pub const LEADER_LEASE: &str = "scheduler-leader";
+
+/// Maximum number of runs returned by [`CronManager::simulate`]
+pub const MAX_SIMULATED_RUNS: usize = 10_000;
+
+/// Number of most recent executions [`CronManager::job_stats`] considers
+pub const STATS_WINDOW: usize = 100;
+
+/// Longest error message carried by a `JobFailed` event, in bytes
+pub const MAX_EVENT_ERROR_LEN: usize = 1000;
 
 /// Scheduler events for monitoring
-#[derive(Debug, Clone)]
+///
+/// Every event carries the time it was emitted in `timestamp`. More variants
+/// may be added, so matches need a wildcard arm.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[non_exhaustive]
 pub enum SchedulerEvent {
     /// Scheduler started
-    Started,
+    Started {
+        /// Longest the scheduler sleeps between checks of the store
+        tick_interval: Duration,
+        /// Jobs in the store when the scheduler started
+        #[serde(default)]
+        jobs_loaded: usize,
+        /// Active jobs whose next fire time had already passed
+        #[serde(default)]
+        stale_jobs: usize,
+        timestamp: DateTime<Utc>,
+    },
     /// Scheduler stopped
-    Stopped,
+    Stopped { timestamp: DateTime<Utc> },
+    /// Job was added
+    JobAdded {
+        job_id: String,
+        name: String,
+        timestamp: DateTime<Utc>,
+    },
+    /// Job's definition was changed
+    JobUpdated {
+        job_id: String,
+        job_name: String,
+        /// Names of the changed fields, e.g. `schedule`
+        changed_fields: Vec<String>,
+        timestamp: DateTime<Utc>,
+    },
+    /// Job was paused
+    JobPaused {
+        job_id: String,
+        job_name: String,
+        timestamp: DateTime<Utc>,
+    },
+    /// Job was resumed
+    JobResumed {
+        job_id: String,
+        job_name: String,
+        timestamp: DateTime<Utc>,
+    },
+    /// Job was disabled
+    JobDisabled {
+        job_id: String,
+        job_name: String,
+        timestamp: DateTime<Utc>,
+    },
+    /// Job was enabled again
+    JobEnabled {
+        job_id: String,
+        job_name: String,
+        timestamp: DateTime<Utc>,
+    },
+    /// Job was removed
+    JobRemoved {
+        job_id: String,
+        job_name: String,
+        timestamp: DateTime<Utc>,
+    },
     /// Job started execution
     JobStarted {
         job_id: String,
+        job_name: String,
         execution_id: String,
+        /// What started the execution
+        #[serde(default)]
+        trigger: TriggerKind,
+        /// How long after its fire time a scheduled run started, in
+        /// milliseconds
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        lag_ms: Option<u64>,
+        timestamp: DateTime<Utc>,
     },
     /// Job completed successfully
     JobCompleted {
         job_id: String,
+        job_name: String,
         execution_id: String,
+        exit_code: Option<i32>,
+        duration_ms: Option<u64>,
+        /// Whether the output differs from the previous successful run's,
+        /// for jobs with `notify_on_change` set
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        output_changed: Option<bool>,
+        timestamp: DateTime<Utc>,
     },
     /// Job failed
     JobFailed {
         job_id: String,
+        job_name: String,
         execution_id: String,
+        exit_code: Option<i32>,
+        duration_ms: Option<u64>,
+        /// Error message, truncated to [`MAX_EVENT_ERROR_LEN`] bytes
         error: String,
+        timestamp: DateTime<Utc>,
     },
     /// Job timed out
     JobTimeout {
         job_id: String,
+        job_name: String,
+        execution_id: String,
+        timestamp: DateTime<Utc>,
+    },
+    /// Failed attempt will be retried after a delay
+    JobRetrying {
+        job_id: String,
+        job_name: String,
+        /// The failed execution
+        execution_id: String,
+        /// Number of the upcoming attempt (2 for the first retry)
+        attempt: u32,
+        delay_ms: u64,
+        timestamp: DateTime<Utc>,
+    },
+    /// Job was paused after too many consecutive failures
+    JobAutoPaused {
+        job_id: String,
+        job_name: String,
+        failures: u32,
+        timestamp: DateTime<Utc>,
+    },
+    /// Job reached its `max_runs` limit and was completed
+    JobCompletedAllRuns {
+        job_id: String,
+        job_name: String,
+        runs: u64,
+        timestamp: DateTime<Utc>,
+    },
+    /// Job run was skipped without executing
+    JobSkipped {
+        job_id: String,
+        job_name: String,
+        reason: String,
+        timestamp: DateTime<Utc>,
+    },
+    /// Job was started by the successful run of an upstream job
+    JobTriggered {
+        job_id: String,
+        job_name: String,
+        upstream_job_id: String,
+        upstream_execution_id: String,
+        timestamp: DateTime<Utc>,
+    },
+    /// `on_failure` hook of a job finished handling its failed execution
+    JobHookFinished {
+        job_id: String,
+        job_name: String,
+        execution_id: String,
+        hook_execution_id: String,
+        status: ExecutionStatus,
+        timestamp: DateTime<Utc>,
+    },
+    /// Job left running by a previous process was reset to active, and its
+    /// interrupted run recorded as failed
+    JobRecovered {
+        job_id: String,
+        job_name: String,
+        execution_id: String,
+        timestamp: DateTime<Utc>,
+    },
+    /// Scheduler stopped dispatching due jobs
+    SchedulerPaused { timestamp: DateTime<Utc> },
+    /// Scheduler resumed dispatching due jobs
+    SchedulerResumed { timestamp: DateTime<Utc> },
+    /// This manager took the leader lease and now schedules jobs
+    BecameLeader {
+        instance_id: String,
+        timestamp: DateTime<Utc>,
+    },
+    /// This manager lost or gave up the leader lease and stopped
+    /// scheduling jobs
+    LostLeadership {
+        instance_id: String,
+        timestamp: DateTime<Utc>,
+    },
+    /// The scheduler loop went `stall_after_ticks` tick intervals without
+    /// going round, or died; `restarted` tells whether the watchdog
+    /// replaced it
+    SchedulerStalled {
+        last_tick: Option<DateTime<Utc>>,
+        restarted: bool,
+        timestamp: DateTime<Utc>,
+    },
+    /// The configured manifest was re-applied, e.g. on SIGHUP; `error` is
+    /// set, and nothing changed, if it couldn't be read or applied
+    ManifestReloaded {
+        path: String,
+        created: usize,
+        updated: usize,
+        removed: usize,
+        error: Option<String>,
+        timestamp: DateTime<Utc>,
+    },
+    /// The subscriber fell behind and missed `count` events. Only produced by
+    /// [`CronManager::event_stream`].
+    EventsDropped {
+        count: u64,
+        timestamp: DateTime<Utc>,
+    },
+    /// Running execution passed its job's expected duration; it carries on
+    JobRunningLong {
+        job_id: String,
+        job_name: String,
+        execution_id: String,
+        elapsed_ms: u64,
+        timestamp: DateTime<Utc>,
+    },
+    /// Successful run took far longer than the job's runs usually do
+    JobDurationAnomaly {
+        job_id: String,
+        job_name: String,
+        execution_id: String,
+        duration_ms: u64,
+        /// Mean duration of the job's earlier successful runs
+        baseline_ms: u64,
+        timestamp: DateTime<Utc>,
+    },
+    /// Execution is still running, sent every heartbeat interval until it
+    /// finishes. Not written to the audit log.
+    JobHeartbeat {
+        job_id: String,
+        job_name: String,
+        execution_id: String,
+        elapsed_ms: u64,
+        timestamp: DateTime<Utc>,
+    },
+    /// Running execution was cancelled
+    JobCancelled {
+        job_id: String,
+        job_name: String,
+        execution_id: String,
+        timestamp: DateTime<Utc>,
+    },
+    /// A running shell job printed output. Chunks hold whole lines and are
+    /// batched, so a job printing constantly sends a few chunks per second;
+    /// output beyond the batch size is skipped here but kept in the
+    /// execution's `stdout`/`stderr`. Not written to the audit log.
+    JobOutput {
+        job_id: String,
+        job_name: String,
         execution_id: String,
+        stream: OutputStream,
+        chunk: String,
+        timestamp: DateTime<Utc>,
     },
 }
 
+impl SchedulerEvent {
+    /// When the event was emitted
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        match self {
+            SchedulerEvent::Started { timestamp, .. }
+            | SchedulerEvent::Stopped { timestamp }
+            | SchedulerEvent::JobAdded { timestamp, .. }
+            | SchedulerEvent::JobUpdated { timestamp, .. }
+            | SchedulerEvent::JobPaused { timestamp, .. }
+            | SchedulerEvent::JobResumed { timestamp, .. }
+            | SchedulerEvent::JobDisabled { timestamp, .. }
+            | SchedulerEvent::JobEnabled { timestamp, .. }
+            | SchedulerEvent::JobRemoved { timestamp, .. }
+            | SchedulerEvent::JobStarted { timestamp, .. }
+            | SchedulerEvent::JobCompleted { timestamp, .. }
+            | SchedulerEvent::JobFailed { timestamp, .. }
+            | SchedulerEvent::JobTimeout { timestamp, .. }
+            | SchedulerEvent::JobRetrying { timestamp, .. }
+            | SchedulerEvent::JobAutoPaused { timestamp, .. }
+            | SchedulerEvent::JobCompletedAllRuns { timestamp, .. }
+            | SchedulerEvent::JobSkipped { timestamp, .. }
+            | SchedulerEvent::JobTriggered { timestamp, .. }
+            | SchedulerEvent::JobHookFinished { timestamp, .. }
+            | SchedulerEvent::JobRecovered { timestamp, .. }
+            | SchedulerEvent::SchedulerPaused { timestamp }
+            | SchedulerEvent::SchedulerResumed { timestamp }
+            | SchedulerEvent::BecameLeader { timestamp, .. }
+            | SchedulerEvent::LostLeadership { timestamp, .. }
+            | SchedulerEvent::SchedulerStalled { timestamp, .. }
+            | SchedulerEvent::ManifestReloaded { timestamp, .. }
+            | SchedulerEvent::EventsDropped { timestamp, .. }
+            | SchedulerEvent::JobRunningLong { timestamp, .. }
+            | SchedulerEvent::JobDurationAnomaly { timestamp, .. }
+            | SchedulerEvent::JobHeartbeat { timestamp, .. }
+            | SchedulerEvent::JobCancelled { timestamp, .. }
+            | SchedulerEvent::JobOutput { timestamp, .. } => *timestamp,
+        }
+    }
+
+    /// ID of the job the event is about, if any
+    pub fn job_id(&self) -> Option<&str> {
+        match self {
+            SchedulerEvent::JobAdded { job_id, .. }
+            | SchedulerEvent::JobUpdated { job_id, .. }
+            | SchedulerEvent::JobPaused { job_id, .. }
+            | SchedulerEvent::JobResumed { job_id, .. }
+            | SchedulerEvent::JobDisabled { job_id, .. }
+            | SchedulerEvent::JobEnabled { job_id, .. }
+            | SchedulerEvent::JobRemoved { job_id, .. }
+            | SchedulerEvent::JobStarted { job_id, .. }
+            | SchedulerEvent::JobCompleted { job_id, .. }
+            | SchedulerEvent::JobFailed { job_id, .. }
+            | SchedulerEvent::JobTimeout { job_id, .. }
+            | SchedulerEvent::JobRetrying { job_id, .. }
+            | SchedulerEvent::JobAutoPaused { job_id, .. }
+            | SchedulerEvent::JobCompletedAllRuns { job_id, .. }
+            | SchedulerEvent::JobSkipped { job_id, .. }
+            | SchedulerEvent::JobTriggered { job_id, .. }
+            | SchedulerEvent::JobHookFinished { job_id, .. }
+            | SchedulerEvent::JobRecovered { job_id, .. }
+            | SchedulerEvent::JobRunningLong { job_id, .. }
+            | SchedulerEvent::JobDurationAnomaly { job_id, .. }
+            | SchedulerEvent::JobHeartbeat { job_id, .. }
+            | SchedulerEvent::JobCancelled { job_id, .. }
+            | SchedulerEvent::JobOutput { job_id, .. } => Some(job_id),
+            SchedulerEvent::Started { .. }
+            | SchedulerEvent::Stopped { .. }
+            | SchedulerEvent::SchedulerPaused { .. }
+            | SchedulerEvent::SchedulerResumed { .. }
+            | SchedulerEvent::BecameLeader { .. }
+            | SchedulerEvent::LostLeadership { .. }
+            | SchedulerEvent::SchedulerStalled { .. }
+            | SchedulerEvent::ManifestReloaded { .. }
+            | SchedulerEvent::EventsDropped { .. } => None,
+        }
+    }
+}
+
+/// A scheduler event with a unique ID, ready to be written to a log or
+/// message queue as is
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventEnvelope {
+    /// Unique event ID
+    pub id: Uuid,
+    /// When the event was emitted
+    pub at: DateTime<Utc>,
+    /// The event
+    pub kind: SchedulerEvent,
+}
+
+impl EventEnvelope {
+    /// Wrap an event, giving it a new ID
+    pub fn new(kind: SchedulerEvent) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            at: kind.timestamp(),
+            kind,
+        }
+    }
+}
+
+/// Shorten an error message for an event, keeping whole characters
+pub(crate) fn event_error(error: &str) -> String {
+    if error.len() <= MAX_EVENT_ERROR_LEN {
+        return error.to_string();
+    }
+    let mut end = MAX_EVENT_ERROR_LEN;
+    while !error.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}...[truncated]", &error[..end])
+}
+
+/// Check a scheduler tick interval is usable
+pub(crate) fn check_tick_interval(tick_interval: Duration) -> Result<()> {
+    if tick_interval.is_zero() {
+        return Err(CronError::InvalidConfig(
+            "tick interval must be greater than zero".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Check a watchdog stall threshold is usable
+pub(crate) fn check_stall_after_ticks(ticks: u32) -> Result<()> {
+    if ticks == 0 {
+        return Err(CronError::InvalidConfig(
+            "stall threshold must be at least one tick".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Check a duration anomaly threshold is usable
+pub(crate) fn check_anomaly_threshold(threshold: f64) -> Result<()> {
+    if !threshold.is_finite() || threshold <= 0.0 {
+        return Err(CronError::InvalidConfig(
+            "anomaly threshold must be a positive number".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Check a limit on concurrently executing jobs is usable
+pub(crate) fn check_max_concurrent_jobs(max_concurrent_jobs: Option<usize>) -> Result<()> {
+    if max_concurrent_jobs == Some(0) {
+        return Err(CronError::InvalidConfig(
+            "max concurrent jobs must be greater than zero".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Check an event channel capacity is usable
+pub(crate) fn check_event_capacity(capacity: usize) -> Result<()> {
+    if capacity == 0 {
+        return Err(CronError::InvalidConfig(
+            "event capacity must be greater than zero".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Check a heartbeat interval is usable
+pub(crate) fn check_heartbeat_interval(interval: Option<Duration>) -> Result<()> {
+    if interval.is_some_and(|interval| interval.is_zero()) {
+        return Err(CronError::InvalidConfig(
+            "heartbeat interval must be greater than zero".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Check a coordination mode's lease duration is usable
+pub(crate) fn check_coordination(coordination: Coordination) -> Result<()> {
+    if coordination
+        .lease_ttl()
+        .is_some_and(|ttl| ttl < Duration::from_secs(1))
+    {
+        return Err(CronError::InvalidConfig(
+            "lease ttl must be at least one second".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Check a default job timeout is usable
+pub(crate) fn check_default_timeout(timeout: Option<Duration>) -> Result<()> {
+    if timeout.is_some_and(|timeout| timeout.as_millis() == 0) {
+        return Err(CronError::InvalidConfig(
+            "default timeout must be at least one millisecond".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Check an output storage setting is usable
+pub(crate) fn check_output_storage(storage: &OutputStorage) -> Result<()> {
+    if let OutputStorage::Files {
+        max_total_bytes: 0, ..
+    } = storage
+    {
+        return Err(CronError::InvalidConfig(
+            "output file size limit must be greater than zero".to_string(),
+        ));
+    }
+    Ok(())
+}
+
 /// Cron manager for job scheduling and execution
 pub struct CronManager {
     /// Storage backend
     store: Arc<dyn CronStore>,
     /// Event broadcaster
-    event_tx: broadcast::Sender<SchedulerEvent>,
+    event_tx: broadcast::Sender<EventEnvelope>,
     /// Scheduler running flag
     running: Arc<RwLock<bool>>,
+    /// Whether the scheduler loop holds back due jobs
+    paused: Arc<RwLock<bool>>,
+    /// Scheduler loop task, while started; replaced by the watchdog when it
+    /// restarts a stalled loop
+    loop_handle: Arc<std::sync::Mutex<Option<JoinHandle<()>>>>,
+    /// Task watching the scheduler loop, while started
+    watchdog_handle: std::sync::Mutex<Option<JoinHandle<()>>>,
+    /// When the scheduler loop last went round
+    last_tick: Arc<std::sync::Mutex<Option<DateTime<Utc>>>>,
+    /// Whether the watchdog found the loop stalled
+    stalled: Arc<AtomicBool>,
+    /// Stalled loops the watchdog replaced
+    restarts: Arc<AtomicU64>,
+    /// Tick intervals without a tick after which the loop counts as stalled
+    stall_after_ticks: u32,
+    /// Whether the watchdog restarts a stalled loop
+    restart_on_stall: bool,
+    /// Manifest re-applied on reload, and whether jobs missing from it are
+    /// removed
+    manifest: Option<(PathBuf, bool)>,
+    /// Task answering SIGHUP, SIGTERM and SIGINT, once installed
+    signal_handle: std::sync::Mutex<Option<JoinHandle<()>>>,
+    /// Held while starting or stopping, so a start waits for a stopping
+    /// loop to finish draining
+    lifecycle: tokio::sync::Mutex<()>,
     /// Workspace directory
     workspace: String,
-    /// Optional agent executor for agent-mode jobs
-    agent_executor: Option<Arc<dyn AgentExecutor>>,
+    /// Job executor shared with the scheduler loop
+    runner: JobRunner,
+    /// Minimum allowed interval between a job's scheduled runs
+    min_interval: Option<Duration>,
+    /// Longest the scheduler sleeps between checks of the store
+    tick_interval: Duration,
+    /// Maximum number of jobs executing at once
+    max_concurrent_jobs: Option<usize>,
+    /// Number of events buffered for each subscriber
+    event_capacity: usize,
+    /// Thresholds classifying job health
+    health_thresholds: HealthThresholds,
+}
+
+/// Whether the scheduler is dispatching due jobs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SchedulerState {
+    /// The scheduler loop is not running
+    Stopped,
+    /// Due jobs are dispatched as they come up
+    Running,
+    /// The loop is running but holding back due jobs
+    Paused,
+}
+
+impl std::fmt::Display for SchedulerState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchedulerState::Stopped => write!(f, "stopped"),
+            SchedulerState::Running => write!(f, "running"),
+            SchedulerState::Paused => write!(f, "paused"),
+        }
+    }
+}
+
+/// How the scheduler loop is doing; see [`CronManager::health`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchedulerHealth {
+    /// Whether the scheduler is stopped, running or paused
+    pub state: SchedulerState,
+    /// When the scheduler loop last went round, if it ever started
+    pub last_tick: Option<DateTime<Utc>>,
+    /// Whether the watchdog found the loop stalled and hasn't seen it go
+    /// round since
+    pub stalled: bool,
+    /// Stalled loops the watchdog replaced with new ones
+    pub restarts: u64,
+}
+
+/// Options for a manual job run
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    /// Run immediately even if the manager's concurrency limit is reached
+    pub bypass_limit: bool,
+    /// Run even if the job is in a blackout window
+    pub ignore_blackout: bool,
+    /// Run even if the job ran less than its `min_interval_ms` ago
+    pub force: bool,
+    /// Run even if the job is paused
+    pub force_if_paused: bool,
+    /// Environment variables set for this run only, replacing the job's
+    /// own of the same name
+    pub env_overrides: Vec<(String, String)>,
+    /// Command (or agent prompt) run instead of the job's, for this run only
+    pub command_override: Option<String>,
+    /// Timeout for this run only
+    pub timeout_override: Option<Duration>,
+    /// Save this run to the job's history even if the job doesn't record
+    /// history
+    pub record_history: bool,
+}
+
+impl RunOptions {
+    /// Create default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set an environment variable for this run only
+    pub fn with_env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env_overrides.push((key.into(), value.into()));
+        self
+    }
+
+    /// Run `command` instead of the job's command
+    pub fn with_command(mut self, command: impl Into<String>) -> Self {
+        self.command_override = Some(command.into());
+        self
+    }
+
+    /// Use `timeout` instead of the job's timeout
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout_override = Some(timeout);
+        self
+    }
+
+    /// Run the job even if it is paused
+    pub fn force_if_paused(mut self) -> Self {
+        self.force_if_paused = true;
+        self
+    }
+
+    /// Save this run to the job's history even if the job doesn't record
+    /// history
+    pub fn with_record_history(mut self) -> Self {
+        self.record_history = true;
+        self
+    }
+
+    /// Apply the overrides to a copy of `job`, returning the names of the
+    /// overridden fields
+    fn apply(&self, job: &mut CronJob) -> Result<Vec<String>> {
+        let mut overrides = Vec::new();
+        if let Some(command) = &self.command_override {
+            if job.is_exec() {
+                return Err(CronError::InvalidConfig(format!(
+                    "exec job {} has no shell command to override",
+                    job.name
+                )));
+            }
+            job.command = command.clone();
+            overrides.push("command".to_string());
+        }
+        if let Some(timeout) = self.timeout_override {
+            job.timeout_ms = Some(timeout.as_millis() as u64);
+            overrides.push("timeout_ms".to_string());
+        }
+        for (key, value) in &self.env_overrides {
+            match job.env.iter_mut().find(|(name, _)| name == key) {
+                Some((_, current)) => *current = value.clone(),
+                None => job.env.push((key.clone(), value.clone())),
+            }
+            overrides.push(format!("env.{}", key));
+        }
+        if self.record_history {
+            job.record_history = true;
+        }
+        Ok(overrides)
+    }
 }
 
 impl CronManager {
@@ -63,626 +727,9616 @@ impl CronManager {
     pub async fn new<P: AsRef<Path>>(workspace: P) -> Result<Self> {
         let workspace_str = workspace.as_ref().to_string_lossy().to_string();
         let store = Arc::new(FileCronStore::new(&workspace_str).await?);
-        let (event_tx, _) = broadcast::channel(100);
+        Ok(Self::with_store(store, workspace_str))
+    }
 
-        Ok(Self {
-            store,
-            event_tx,
-            running: Arc::new(RwLock::new(false)),
-            workspace: workspace_str,
-            agent_executor: None,
-        })
+    /// Start building a manager with every setting chosen up front
+    pub fn builder(workspace: impl Into<String>) -> CronManagerBuilder {
+        CronManagerBuilder::new(workspace)
     }
 
     /// Create a cron manager with a custom store
     pub fn with_store(store: Arc<dyn CronStore>, workspace: String) -> Self {
-        let (event_tx, _) = broadcast::channel(100);
+        let (event_tx, _) = broadcast::channel(DEFAULT_EVENT_CAPACITY);
+        let runner = JobRunner {
+            store: store.clone(),
+            event_tx: event_tx.clone(),
+            workspace: workspace.clone(),
+            agent_executor: None,
+            handlers: Arc::default(),
+            middlewares: Arc::default(),
+            wakeup: Arc::new(Notify::new()),
+            in_flight: Arc::default(),
+            pending: Arc::default(),
+            limit: None,
+            idle: Arc::new(Notify::new()),
+            blackouts: Arc::default(),
+            calendars: Arc::default(),
+            audit: None,
+            default_shell: None,
+            default_env_mode: EnvMode::default(),
+            default_timeout_ms: None,
+            heartbeat_interval: Some(DEFAULT_HEARTBEAT_INTERVAL),
+            clock: Arc::new(SystemClock),
+            instance_id: Uuid::new_v4().to_string(),
+            coordination: Coordination::None,
+            leading: Arc::default(),
+            held_leases: Arc::default(),
+            output_storage: OutputStorage::default(),
+            retention: RetentionPolicy::default(),
+            anomaly_threshold: DEFAULT_ANOMALY_THRESHOLD,
+        };
         Self {
             store,
             event_tx,
             running: Arc::new(RwLock::new(false)),
+            paused: Arc::new(RwLock::new(false)),
+            loop_handle: Arc::default(),
+            watchdog_handle: std::sync::Mutex::new(None),
+            last_tick: Arc::default(),
+            stalled: Arc::default(),
+            restarts: Arc::default(),
+            stall_after_ticks: DEFAULT_STALL_TICKS,
+            restart_on_stall: false,
+            manifest: None,
+            signal_handle: std::sync::Mutex::new(None),
+            lifecycle: tokio::sync::Mutex::new(()),
             workspace,
-            agent_executor: None,
+            runner,
+            min_interval: None,
+            tick_interval: DEFAULT_TICK_INTERVAL,
+            max_concurrent_jobs: None,
+            event_capacity: DEFAULT_EVENT_CAPACITY,
+            health_thresholds: HealthThresholds::default(),
         }
     }
 
     /// Set the agent executor for agent-mode cron jobs.
     pub fn set_agent_executor(&mut self, executor: Arc<dyn AgentExecutor>) {
-        self.agent_executor = Some(executor);
+        self.runner.agent_executor = Some(executor);
     }
 
-    /// Subscribe to scheduler events
-    pub fn subscribe(&self) -> broadcast::Receiver<SchedulerEvent> {
-        self.event_tx.subscribe()
+    /// Register the handler run by native jobs whose command is `name`,
+    /// replacing any handler already registered under it. Handlers aren't
+    /// persisted, so register them again after a restart.
+    pub fn register_handler(&self, name: impl Into<String>, handler: Arc<dyn NativeJobHandler>) {
+        self.runner
+            .handlers
+            .write()
+            .unwrap()
+            .insert(name.into(), handler);
     }
 
-    /// Add a new cron job
-    pub async fn add_job(&self, name: &str, schedule: &str, command: &str) -> Result<CronJob> {
-        // Validate schedule
-        let expr = CronExpression::parse(schedule)?;
+    /// Register the holiday calendar jobs refer to as `name`, replacing any
+    /// calendar already registered under it, and move the next runs of the
+    /// jobs excluding it off its dates. Calendars aren't persisted, so
+    /// register them again after a restart.
+    pub async fn register_calendar(
+        &self,
+        name: impl Into<String>,
+        dates: impl IntoIterator<Item = NaiveDate>,
+    ) -> Result<()> {
+        let name = name.into();
+        let calendar = Calendar::new(dates);
+        tracing::info!(
+            "Registered calendar {} with {} date(s)",
+            name,
+            calendar.len()
+        );
+        self.runner
+            .calendars
+            .write()
+            .unwrap()
+            .insert(name.clone(), Arc::new(calendar));
+
+        let now = self.now();
+        for mut job in self.store.list_jobs().await? {
+            if job.exclude_calendar.as_deref() != Some(name.as_str())
+                || !matches!(job.status, JobStatus::Active | JobStatus::Running)
+            {
+                continue;
+            }
+            self.runner.reschedule(&mut job, now);
+            self.store.save_job(&job).await?;
+        }
+        self.runner.wakeup.notify_one();
+        Ok(())
+    }
+
+    /// Get the calendar registered as `name`
+    pub fn calendar(&self, name: &str) -> Option<Arc<Calendar>> {
+        self.runner.calendars.read().unwrap().get(name).cloned()
+    }
 
-        // Check for duplicate name
-        if self.store.find_job_by_name(name).await?.is_some() {
-            return Err(CronError::JobExists(name.to_string()));
+    /// Names of the registered calendars, sorted
+    pub fn calendar_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .runner
+            .calendars
+            .read()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Fail unless a calendar is registered as `name`
+    fn check_calendar(&self, name: Option<&str>) -> Result<()> {
+        match name {
+            Some(name) if !self.runner.calendars.read().unwrap().contains_key(name) => Err(
+                CronError::InvalidConfig(format!("calendar '{}' is not registered", name)),
+            ),
+            _ => Ok(()),
         }
+    }
 
-        // Create job
-        let mut job = CronJob::new(name, schedule, command);
-        job.next_run = expr.next_after(Utc::now());
-        job.working_dir = Some(self.workspace.clone());
+    /// Reject schedules that fire more often than `min_interval`.
+    ///
+    /// Applies to `add_job`, `add_agent_job` and `update_job`; manual runs are
+    /// unaffected. `None` (the default) disables the check.
+    pub fn set_min_interval(&mut self, min_interval: Option<Duration>) {
+        self.min_interval = min_interval;
+    }
 
-        // Save
-        self.store.save_job(&job).await?;
+    /// Get the configured minimum schedule interval
+    pub fn min_interval(&self) -> Option<Duration> {
+        self.min_interval
+    }
 
-        tracing::info!("Added cron job: {} ({})", job.name, job.id);
-        Ok(job)
+    /// Set how often the scheduler re-reads the store (default 60 seconds).
+    ///
+    /// The scheduler wakes at each job's `next_run` and whenever jobs change
+    /// through this manager; the tick interval bounds how long it sleeps
+    /// otherwise, e.g. to notice jobs written to a shared store by another
+    /// process. Must be non-zero.
+    pub fn with_tick_interval(mut self, tick_interval: Duration) -> Result<Self> {
+        check_tick_interval(tick_interval)?;
+        self.tick_interval = tick_interval;
+        Ok(self)
     }
 
-    /// Add a new agent-mode cron job.
+    /// Limit how many jobs execute at the same time (default unlimited).
     ///
-    /// The `command` field is used as the agent prompt. When the job fires,
-    /// it creates a temporary Agent with the given config and sends the prompt.
-    pub async fn add_agent_job(
-        &self,
-        name: &str,
-        schedule: &str,
-        prompt: &str,
-        config: AgentJobConfig,
-    ) -> Result<CronJob> {
-        let expr = CronExpression::parse(schedule)?;
+    /// Due jobs beyond the limit wait for a free slot, first come first
+    /// served by fire time. Manual runs wait too unless started with
+    /// [`RunOptions::bypass_limit`]. Must be non-zero.
+    pub fn with_max_concurrent_jobs(mut self, max_concurrent_jobs: Option<usize>) -> Result<Self> {
+        check_max_concurrent_jobs(max_concurrent_jobs)?;
+        self.max_concurrent_jobs = max_concurrent_jobs;
+        self.runner.limit = max_concurrent_jobs.map(|n| Arc::new(Semaphore::new(n)));
+        Ok(self)
+    }
+
+    /// Set how many events are buffered for each subscriber (default 100).
+    ///
+    /// A subscriber that falls further behind misses the oldest events.
+    /// Raise this when many jobs run per minute. Must be non-zero, and set
+    /// before subscribing or adding notifiers.
+    pub fn with_event_capacity(mut self, capacity: usize) -> Result<Self> {
+        check_event_capacity(capacity)?;
+        let (event_tx, _) = broadcast::channel(capacity);
+        self.runner.event_tx = event_tx.clone();
+        self.event_tx = event_tx;
+        self.event_capacity = capacity;
+        Ok(self)
+    }
 
-        if self.store.find_job_by_name(name).await?.is_some() {
-            return Err(CronError::JobExists(name.to_string()));
+    /// Set the shell running commands of jobs that don't choose their own
+    /// (default `sh`). Fails if the shell can't be found or isn't executable.
+    pub fn with_default_shell(mut self, shell: Option<String>) -> Result<Self> {
+        if let Some(shell) = &shell {
+            process::check_executable(shell)?;
         }
+        self.runner.default_shell = shell;
+        Ok(self)
+    }
 
-        let mut job = CronJob::new(name, schedule, prompt);
-        job.job_type = JobType::Agent;
-        job.agent_config = Some(config);
-        job.next_run = expr.next_after(Utc::now());
-        job.working_dir = Some(self.workspace.clone());
+    /// Get the shell running commands of jobs that don't choose their own
+    pub fn default_shell(&self) -> &str {
+        self.runner
+            .default_shell
+            .as_deref()
+            .unwrap_or(DEFAULT_SHELL)
+    }
 
-        self.store.save_job(&job).await?;
+    /// Set the environment inherited by jobs that don't choose their own
+    /// (default [`EnvMode::Inherit`])
+    pub fn with_default_env_mode(mut self, env_mode: EnvMode) -> Self {
+        self.runner.default_env_mode = env_mode;
+        self
+    }
 
-        tracing::info!("Added agent cron job: {} ({})", job.name, job.id);
-        Ok(job)
+    /// Get the environment inherited by jobs that don't choose their own
+    pub fn default_env_mode(&self) -> &EnvMode {
+        &self.runner.default_env_mode
     }
 
-    /// Get a job by ID
-    pub async fn get_job(&self, id: &str) -> Result<Option<CronJob>> {
-        self.store.load_job(id).await
+    /// Set the timeout of jobs that don't set their own (default
+    /// [`DEFAULT_TIMEOUT_MS`](crate::DEFAULT_TIMEOUT_MS)). It applies when a
+    /// job runs, so changing it affects every inheriting job. Must be
+    /// non-zero.
+    pub fn with_default_timeout(mut self, timeout: Option<Duration>) -> Result<Self> {
+        check_default_timeout(timeout)?;
+        self.runner.default_timeout_ms = timeout.map(|timeout| timeout.as_millis() as u64);
+        Ok(self)
     }
 
-    /// Get a job by name
-    pub async fn get_job_by_name(&self, name: &str) -> Result<Option<CronJob>> {
-        self.store.find_job_by_name(name).await
+    /// Set how often running executions send a
+    /// [`JobHeartbeat`](SchedulerEvent::JobHeartbeat) event (default 60
+    /// seconds), or turn heartbeats off with `None`. Must be non-zero.
+    pub fn with_heartbeat_interval(mut self, interval: Option<Duration>) -> Result<Self> {
+        check_heartbeat_interval(interval)?;
+        self.runner.heartbeat_interval = interval;
+        Ok(self)
     }
 
-    /// List all jobs
-    pub async fn list_jobs(&self) -> Result<Vec<CronJob>> {
-        self.store.list_jobs().await
+    /// Get how often running executions send a heartbeat, if they do
+    pub fn heartbeat_interval(&self) -> Option<Duration> {
+        self.runner.heartbeat_interval
     }
 
-    /// Update a job
-    pub async fn update_job(
-        &self,
-        id: &str,
-        schedule: Option<&str>,
-        command: Option<&str>,
-        timeout_ms: Option<u64>,
-    ) -> Result<CronJob> {
-        let mut job = self
-            .store
-            .load_job(id)
-            .await?
-            .ok_or_else(|| CronError::JobNotFound(id.to_string()))?;
+    /// Set how this manager coordinates with others sharing its store
+    /// (default [`Coordination::None`]); lease durations must be at least a
+    /// second. Stores not implementing leases grant them all.
+    ///
+    /// With [`Coordination::PerJobLease`] a manager only runs a due job
+    /// after taking its lease from the store, and renews it while the job
+    /// runs; managers finding the lease taken leave the run to its holder
+    /// without recording anything.
+    ///
+    /// With [`Coordination::LeaderElection`] only the manager holding the
+    /// store's [`LEADER_LEASE`] runs the scheduler loop, applying misfire
+    /// policies when it takes over. The others keep asking for the lease and
+    /// take over once the leader stops renewing it. Manual runs don't need
+    /// leadership: followers run jobs when asked.
+    pub fn with_coordination(mut self, coordination: Coordination) -> Result<Self> {
+        check_coordination(coordination)?;
+        self.runner.coordination = coordination;
+        Ok(self)
+    }
 
-        if let Some(schedule) = schedule {
-            let expr = CronExpression::parse(schedule)?;
-            job.schedule = schedule.to_string();
-            job.next_run = expr.next_after(Utc::now());
-        }
+    /// Get how this manager coordinates with others sharing its store
+    pub fn coordination(&self) -> Coordination {
+        self.runner.coordination
+    }
 
-        if let Some(command) = command {
-            job.command = command.to_string();
+    /// Check whether this manager schedules jobs: with leader election,
+    /// whether it holds the leader lease; otherwise always true
+    pub fn is_leader(&self) -> bool {
+        match self.runner.coordination {
+            Coordination::LeaderElection { .. } => self.runner.leading.load(Ordering::SeqCst),
+            _ => true,
         }
+    }
 
-        if let Some(timeout) = timeout_ms {
-            job.timeout_ms = timeout;
-        }
+    /// Set the name this manager holds execution leases under (default a
+    /// random UUID); it must differ between managers sharing a store
+    pub fn with_instance_id(mut self, instance_id: impl Into<String>) -> Self {
+        self.runner.instance_id = instance_id.into();
+        self
+    }
 
-        job.updated_at = Utc::now();
-        self.store.save_job(&job).await?;
+    /// Get the name this manager holds execution leases under
+    pub fn instance_id(&self) -> &str {
+        &self.runner.instance_id
+    }
 
-        tracing::info!("Updated cron job: {} ({})", job.name, job.id);
-        Ok(job)
+    /// Get the workspace directory jobs run in by default
+    pub fn workspace(&self) -> &str {
+        &self.workspace
     }
 
-    /// Pause a job
-    pub async fn pause_job(&self, id: &str) -> Result<CronJob> {
-        let mut job = self
-            .store
-            .load_job(id)
-            .await?
-            .ok_or_else(|| CronError::JobNotFound(id.to_string()))?;
+    /// Set the clock deciding when jobs are due (default the wall clock),
+    /// e.g. a [`MockClock`](crate::MockClock) stepping a test through fire
+    /// times. Set it before adding jobs or starting the scheduler.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.runner.clock = clock;
+        self
+    }
 
-        job.status = JobStatus::Paused;
-        job.updated_at = Utc::now();
-        self.store.save_job(&job).await?;
+    /// Current time on the manager's clock
+    pub fn now(&self) -> DateTime<Utc> {
+        self.runner.clock.now()
+    }
 
-        tracing::info!("Paused cron job: {} ({})", job.name, job.id);
-        Ok(job)
+    /// Get the timeout of jobs that don't set their own, if configured
+    pub fn default_timeout(&self) -> Option<Duration> {
+        self.runner.default_timeout_ms.map(Duration::from_millis)
     }
 
-    /// Resume a paused job
-    pub async fn resume_job(&self, id: &str) -> Result<CronJob> {
-        let mut job = self
-            .store
-            .load_job(id)
-            .await?
-            .ok_or_else(|| CronError::JobNotFound(id.to_string()))?;
+    /// Set where execution output is kept (default [`OutputStorage::Inline`]).
+    ///
+    /// With [`OutputStorage::Files`], execution records hold only the paths
+    /// and sizes of the files; read the output with
+    /// [`get_execution_output`](Self::get_execution_output).
+    pub fn with_output_storage(mut self, storage: OutputStorage) -> Result<Self> {
+        check_output_storage(&storage)?;
+        self.runner.output_storage = storage;
+        Ok(self)
+    }
 
-        job.status = JobStatus::Active;
-        job.updated_at = Utc::now();
+    /// Get where execution output is kept
+    pub fn output_storage(&self) -> &OutputStorage {
+        &self.runner.output_storage
+    }
 
-        // Recalculate next run
-        if let Ok(expr) = CronExpression::parse(&job.schedule) {
-            job.next_run = expr.next_after(Utc::now());
-        }
+    /// Set the thresholds classifying job health
+    pub fn with_health_thresholds(mut self, thresholds: HealthThresholds) -> Self {
+        self.health_thresholds = thresholds;
+        self
+    }
 
-        self.store.save_job(&job).await?;
+    /// Get the thresholds classifying job health
+    pub fn health_thresholds(&self) -> &HealthThresholds {
+        &self.health_thresholds
+    }
 
-        tracing::info!("Resumed cron job: {} ({})", job.name, job.id);
-        Ok(job)
+    /// Set after how many tick intervals without the scheduler loop going
+    /// round the watchdog reports it stalled (default
+    /// [`DEFAULT_STALL_TICKS`]). Set it before starting the scheduler.
+    pub fn with_stall_after_ticks(mut self, ticks: u32) -> Result<Self> {
+        check_stall_after_ticks(ticks)?;
+        self.stall_after_ticks = ticks;
+        Ok(self)
     }
 
-    /// Remove a job
-    pub async fn remove_job(&self, id: &str) -> Result<()> {
-        let job = self
-            .store
-            .load_job(id)
-            .await?
-            .ok_or_else(|| CronError::JobNotFound(id.to_string()))?;
+    /// Get after how many silent tick intervals the loop counts as stalled
+    pub fn stall_after_ticks(&self) -> u32 {
+        self.stall_after_ticks
+    }
 
-        self.store.delete_job(id).await?;
+    /// Have the watchdog replace a stalled scheduler loop with a new one
+    /// (default off, only reporting the stall). Set it before starting the
+    /// scheduler.
+    pub fn with_restart_on_stall(mut self, restart: bool) -> Self {
+        self.restart_on_stall = restart;
+        self
+    }
 
-        tracing::info!("Removed cron job: {} ({})", job.name, job.id);
-        Ok(())
+    /// Check if the watchdog restarts a stalled scheduler loop
+    pub fn restart_on_stall(&self) -> bool {
+        self.restart_on_stall
     }
 
-    /// Get execution history for a job
-    pub async fn get_history(&self, job_id: &str, limit: usize) -> Result<Vec<JobExecution>> {
-        self.store.load_executions(job_id, limit).await
+    /// Set the JSON [`JobManifest`] file
+    /// [`reload_manifest`](Self::reload_manifest) re-applies, with `prune`
+    /// removing jobs missing from it. It isn't applied until then.
+    pub fn with_manifest(mut self, path: impl Into<PathBuf>, prune: bool) -> Self {
+        self.manifest = Some((path.into(), prune));
+        self
     }
 
-    /// Manually run a job
-    pub async fn run_job(&self, id: &str) -> Result<JobExecution> {
-        let job = self
-            .store
-            .load_job(id)
-            .await?
-            .ok_or_else(|| CronError::JobNotFound(id.to_string()))?;
+    /// Get the manifest file re-applied on reload, if configured
+    pub fn manifest_path(&self) -> Option<&Path> {
+        self.manifest.as_ref().map(|(path, _)| path.as_path())
+    }
 
-        self.execute_job(&job).await
+    /// Set how many standard deviations above a job's mean duration a
+    /// successful run must take to be reported with a `JobDurationAnomaly`
+    /// event (default [`DEFAULT_ANOMALY_THRESHOLD`]). Jobs are only checked
+    /// once they have [`MIN_ANOMALY_SAMPLES`](crate::MIN_ANOMALY_SAMPLES)
+    /// successful runs.
+    pub fn with_anomaly_threshold(mut self, threshold: f64) -> Result<Self> {
+        check_anomaly_threshold(threshold)?;
+        self.runner.anomaly_threshold = threshold;
+        Ok(self)
     }
 
-    /// Execute a job
-    async fn execute_job(&self, job: &CronJob) -> Result<JobExecution> {
-        let span = tracing::info_span!(
-            "a3s.cron.execute_job",
-            a3s.cron.job_id = %job.id,
-            a3s.cron.job_name = %job.name,
-            a3s.cron.job_status = tracing::field::Empty,
-            a3s.cron.job_duration_ms = tracing::field::Empty,
-        );
-        let _guard = span.enter();
-        let exec_start = Instant::now();
+    /// Get how many standard deviations above a job's mean duration a run
+    /// must take to be reported as anomalous
+    pub fn anomaly_threshold(&self) -> f64 {
+        self.runner.anomaly_threshold
+    }
 
-        let mut execution = JobExecution::new(&job.id);
+    /// Set how much execution history is kept for each job (default all).
+    ///
+    /// The policy is applied to a job's history each time one of its
+    /// executions is saved.
+    pub fn with_retention(mut self, retention: RetentionPolicy) -> Self {
+        self.runner.retention = retention;
+        self
+    }
 
-        // Emit start event
-        let _ = self.event_tx.send(SchedulerEvent::JobStarted {
-            job_id: job.id.clone(),
-            execution_id: execution.id.clone(),
-        });
+    /// Get how much execution history is kept for each job
+    pub fn retention(&self) -> &RetentionPolicy {
+        &self.runner.retention
+    }
 
-        // Update job status to running
-        let mut running_job = job.clone();
-        running_job.status = JobStatus::Running;
-        self.store.save_job(&running_job).await?;
+    /// Write every emitted event to the store's audit log (default off).
+    ///
+    /// Writes happen in the background and never fail the operation that
+    /// emitted the event. Must be called from within a Tokio runtime.
+    pub fn with_audit_log(mut self, enabled: bool) -> Self {
+        self.runner.audit = enabled.then(|| AuditLog::spawn(self.store.clone()));
+        self
+    }
 
-        // Execute command with timeout
-        let timeout = Duration::from_millis(job.timeout_ms);
-        let working_dir = job.working_dir.as_deref().unwrap_or(&self.workspace);
+    /// Check if emitted events are written to the audit log
+    pub fn audit_log_enabled(&self) -> bool {
+        self.runner.audit.is_some()
+    }
 
-        // Result type: Ok(Ok((exit_code, stdout, stderr))) or Ok(Err(io_err)) or Err(timeout)
-        let result: std::result::Result<
-            std::result::Result<(i32, String, String), std::io::Error>,
-            tokio::time::error::Elapsed,
-        > = match job.job_type {
-            JobType::Agent => {
-                let agent_executor = self.agent_executor.clone();
-                let agent_config = job.agent_config.clone();
-                let prompt = job.command.clone();
-                let wd = working_dir.to_string();
-
-                tokio::time::timeout(timeout, async move {
-                    let executor = agent_executor.ok_or_else(|| {
-                        std::io::Error::new(
-                            std::io::ErrorKind::Other,
-                            "No agent executor configured for agent-mode cron job",
-                        )
-                    })?;
-                    let config = agent_config.ok_or_else(|| {
-                        std::io::Error::new(
-                            std::io::ErrorKind::Other,
-                            "Agent job missing agent_config",
-                        )
-                    })?;
-                    match executor.execute(&config, &prompt, &wd).await {
-                        Ok(text) => Ok((0, text, String::new())),
-                        Err(e) => Ok((1, String::new(), e)),
-                    }
-                })
-                .await
-            }
-            JobType::Shell => {
-                tokio::time::timeout(timeout, async {
-                    let output = Command::new("sh")
-                        .arg("-c")
-                        .arg(&job.command)
-                        .current_dir(working_dir)
-                        .envs(job.env.iter().map(|(k, v)| (k.as_str(), v.as_str())))
-                        .output()
-                        .await?;
-                    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-                    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-                    let exit_code = output.status.code().unwrap_or(-1);
-                    Ok((exit_code, stdout, stderr))
-                })
-                .await
-            }
+    /// Get events from the audit log, optionally only those about one job,
+    /// most recent first
+    pub async fn get_audit_log(
+        &self,
+        job_id: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<EventEnvelope>> {
+        if let Some(audit) = &self.runner.audit {
+            audit.flush().await;
+        }
+        let filter = EventFilter {
+            job_id: job_id.map(str::to_string),
+            ..EventFilter::default()
         };
+        self.store.load_events(&filter, limit).await
+    }
 
-        // Process result
-        execution = match result {
-            Ok(Ok((exit_code, stdout, stderr))) => {
-                execution.complete(exit_code, stdout, stderr)
-            }
-            Ok(Err(e)) => execution.fail(format!("Failed to execute command: {}", e)),
-            Err(_) => {
-                let _ = self.event_tx.send(SchedulerEvent::JobTimeout {
-                    job_id: job.id.clone(),
-                    execution_id: execution.id.clone(),
+    /// Get the number of events buffered for each subscriber
+    pub fn event_capacity(&self) -> usize {
+        self.event_capacity
+    }
+
+    /// Set blackout windows during which no job runs on schedule.
+    ///
+    /// They apply on top of each job's own windows, following the job's
+    /// [`BlackoutPolicy`]. Manual runs inside a window are refused unless
+    /// started with [`RunOptions::ignore_blackout`].
+    pub fn with_blackouts(mut self, blackouts: Vec<TimeWindow>) -> Self {
+        self.runner.blackouts = Arc::new(blackouts);
+        self
+    }
+
+    /// Get the manager-wide blackout windows
+    pub fn blackouts(&self) -> &[TimeWindow] {
+        &self.runner.blackouts
+    }
+
+    /// Get the configured concurrency limit
+    pub fn max_concurrent_jobs(&self) -> Option<usize> {
+        self.max_concurrent_jobs
+    }
+
+    /// Get the effective scheduler tick interval
+    pub fn tick_interval(&self) -> Duration {
+        self.tick_interval
+    }
+
+    /// Validate a schedule against the minimum interval, if one is configured
+    fn check_min_interval(&self, expr: &CronExpression) -> Result<()> {
+        let Some(min_interval) = self.min_interval else {
+            return Ok(());
+        };
+        // Look at several upcoming fire times: the closest pair is not
+        // necessarily the first one (e.g. `0,1 0 * * *`)
+        if let Some(interval) = expr.min_interval(self.now(), SCHEDULE_SAMPLES) {
+            let interval_secs = interval.num_seconds().max(0) as u64;
+            if interval_secs < min_interval.as_secs() {
+                return Err(CronError::ScheduleTooFrequent {
+                    interval_secs,
+                    min_interval_secs: min_interval.as_secs(),
                 });
-                execution.timeout()
             }
-        };
+        }
+        Ok(())
+    }
 
-        // Save execution
-        self.store.save_execution(&execution).await?;
+    /// Validate a schedule without creating anything.
+    ///
+    /// Applies the same checks as `add_job` (expression syntax, minimum
+    /// interval) and reports the normalized expression, a description, the
+    /// next fire time and the interval between runs. Does not touch the store
+    /// and works whether or not the scheduler is running.
+    pub fn validate_schedule(&self, schedule: &str) -> Result<ScheduleInfo> {
+        let expr = CronExpression::parse(schedule)?;
+        self.check_min_interval(&expr)?;
+        Ok(expr.info(self.now(), SCHEDULE_SAMPLES))
+    }
 
-        // Update job statistics
-        let mut updated_job = job.clone();
-        updated_job.status = JobStatus::Active;
-        updated_job.last_run = Some(execution.started_at);
-        updated_job.updated_at = Utc::now();
+    /// Send notifications of job events to `notifier`. Delivery runs on its
+    /// own task with a bounded queue, so a slow notifier never delays jobs.
+    ///
+    /// Must be called from within a Tokio runtime.
+    pub fn add_notifier(&self, notifier: Arc<dyn Notifier>) {
+        tracing::info!("Added notifier {}", notifier.name());
+        notifier::spawn(notifier, self.store.clone(), self.event_tx.subscribe());
+    }
 
-        if execution.status == crate::types::ExecutionStatus::Success {
-            updated_job.run_count += 1;
-            let _ = self.event_tx.send(SchedulerEvent::JobCompleted {
-                job_id: job.id.clone(),
-                execution_id: execution.id.clone(),
-            });
+    /// Run `middleware` around every attempt of every job, after any added
+    /// before it. Middlewares aren't persisted, so add them again after a
+    /// restart.
+    pub fn add_middleware(&self, middleware: Arc<dyn JobMiddleware>) {
+        tracing::info!("Added middleware {}", middleware.name());
+        self.runner.middlewares.write().unwrap().push(middleware);
+    }
+
+    /// Stream of scheduler events. A subscriber that falls behind gets an
+    /// [`SchedulerEvent::EventsDropped`] counting the events it missed rather
+    /// than an error. The stream ends when the manager is dropped.
+    pub fn event_stream(&self) -> impl Stream<Item = SchedulerEvent> + Send + 'static {
+        futures_util::stream::unfold(self.event_tx.subscribe(), |mut rx| async move {
+            let event = match rx.recv().await {
+                Ok(envelope) => envelope.kind,
+                Err(broadcast::error::RecvError::Lagged(count)) => SchedulerEvent::EventsDropped {
+                    count,
+                    timestamp: Utc::now(),
+                },
+                Err(broadcast::error::RecvError::Closed) => return None,
+            };
+            Some((event, rx))
+        })
+    }
+
+    /// Subscribe to scheduler events
+    pub fn subscribe(&self) -> broadcast::Receiver<EventEnvelope> {
+        self.event_tx.subscribe()
+    }
+
+    /// Add a new cron job
+    pub async fn add_job(&self, name: &str, schedule: &str, command: &str) -> Result<CronJob> {
+        self.add_job_with_options(name, schedule, command, JobOptions::default())
+            .await
+    }
+
+    /// Add a new cron job with additional options.
+    ///
+    /// Every option is validated, along with the schedule and the name,
+    /// before the job is saved; `add_job`, `add_job_exec`, `add_agent_job`
+    /// and `add_native_job` all go through here, and one-shot jobs get the
+    /// same treatment.
+    pub async fn add_job_with_options(
+        &self,
+        name: &str,
+        schedule: &str,
+        command: &str,
+        options: JobOptions,
+    ) -> Result<CronJob> {
+        let (job, _) = self.create_job(name, schedule, command, options).await?;
+        Ok(job)
+    }
+
+    /// Add a job like [`add_job_with_options`](Self::add_job_with_options),
+    /// also returning the id of the execution started on a background task
+    /// when the `run_immediately` option is set.
+    ///
+    /// The immediate run goes through the job's concurrency policy and rate
+    /// limit like any other; the job is added even if it fails.
+    pub async fn create_job(
+        &self,
+        name: &str,
+        schedule: &str,
+        command: &str,
+        options: JobOptions,
+    ) -> Result<(CronJob, Option<String>)> {
+        // Validate schedule; "@never" makes a trigger-only job
+        let (job, info) = if is_never(schedule) {
+            (CronJob::trigger_only(name, command), None)
         } else {
-            updated_job.fail_count += 1;
-            let _ = self.event_tx.send(SchedulerEvent::JobFailed {
-                job_id: job.id.clone(),
-                execution_id: execution.id.clone(),
-                error: execution.error.clone().unwrap_or_default(),
-            });
+            let info = self.validate_schedule(schedule)?;
+            (CronJob::new(name, &info.expression, command), Some(info))
+        };
+        self.insert_job(job, info, options).await
+    }
+
+    /// Apply `options` to `job`, a new job with its name, schedule and
+    /// command set, and save it once everything is validated
+    async fn insert_job(
+        &self,
+        job: CronJob,
+        info: Option<ScheduleInfo>,
+        options: JobOptions,
+    ) -> Result<(CronJob, Option<String>)> {
+        let run_immediately = options.run_immediately;
+        let name = job.name.as_str();
+        check_job_type(&options)?;
+        check_window(options.not_before, options.not_after)?;
+        if let Some(window) = &options.allowed_window {
+            check_allowed_window(window)?;
+        }
+        if let Some(shell) = &options.shell {
+            process::check_executable(shell)?;
+        }
+        check_tags(&options.tags)?;
+        self.check_calendar(options.exclude_calendar.as_deref())?;
+        warn_unenforced_limits(name, &options.resource_limits);
+
+        self.check_name(name, &job.id).await?;
+
+        let now = self.now();
+        let mut job = job
+            .with_window(options.not_before, options.not_after)
+            .with_jitter(options.jitter_ms)
+            .with_blackouts(options.blackouts, options.blackout_policy)
+            .with_allowed_window(options.allowed_window)
+            .with_triggers(options.triggers)
+            .with_on_failure(options.on_failure)
+            .with_tags(options.tags)
+            .with_shell(options.shell)
+            .with_env_mode(options.env_mode)
+            .with_templated(options.templated)
+            .with_secret_env(options.secret_env)
+            .with_priority(options.priority)
+            .with_min_interval(options.min_interval_ms)
+            .with_expected_duration(options.expected_duration_ms)
+            .with_resource_limits(options.resource_limits)
+            .with_auto_pause_after(options.auto_pause_after)
+            .with_misfire_policy(options.misfire_policy)
+            .with_concurrency_policy(options.concurrency_policy);
+        job.exclude_calendar = options.exclude_calendar;
+        job.holiday_policy = options.holiday_policy;
+        if let Some(record_history) = options.record_history {
+            job = job.with_record_history(record_history);
+        }
+        job =
+            job.with_notify_on_change(options.notify_on_change, options.ignore_whitespace_changes);
+        job.job_type = options.job_type;
+        job.agent_config = options.agent_config;
+        if let Some((program, args)) = options.exec {
+            job = job.with_exec(program, args);
+        }
+        job.env = options.env;
+        job.working_dir = Some(
+            options
+                .working_dir
+                .unwrap_or_else(|| self.workspace.clone()),
+        );
+        if let Some(timeout_ms) = options.timeout_ms {
+            job.timeout_ms = Some(timeout_ms);
+        }
+        if let Some(kill_grace_ms) = options.kill_grace_ms {
+            job.kill_grace_ms = kill_grace_ms;
+        }
+        if let Some((max_retries, retry_delay_ms, backoff)) = options.retries {
+            job = job.with_retries(max_retries, retry_delay_ms, backoff);
+        }
+        if options.max_runs.is_some() {
+            let count_manual = options.count_manual.unwrap_or(job.count_manual);
+            job = job.with_max_runs(options.max_runs, count_manual);
+        }
+        job.created_at = now;
+        job.updated_at = now;
+        self.check_triggers(&job).await?;
+        // A one-shot time already past is due as soon as the scheduler sees it
+        let after = match job.schedule_kind {
+            ScheduleKind::At(when) => now.min(when - chrono::Duration::seconds(1)),
+            _ => now,
+        };
+        self.runner.reschedule(&mut job, after);
+
+        // Save
+        self.store.save_job(&job).await?;
+
+        self.runner.wakeup.notify_one();
+        tracing::info!("Added {} cron job: {} ({})", job.job_type, job.name, job.id);
+        self.job_added(&job);
+        let execution_id = run_immediately.then(|| self.run_on_create(&job));
+        job.schedule_info = info;
+        Ok((job, execution_id))
+    }
+
+    /// Start the first run of a job just added on a background task,
+    /// returning the id of its execution
+    fn run_on_create(&self, job: &CronJob) -> String {
+        let mut execution = JobExecution::new(&job.id);
+        execution.trigger = TriggerKind::OnCreate;
+        let execution_id = execution.id.clone();
+        let runner = self.runner.clone();
+        let job = job.clone();
+        tokio::spawn(async move {
+            let trigger = Trigger::OnCreate(Box::new(execution));
+            if let Err(e) = runner.execute_limited(&job, false, trigger).await {
+                tracing::error!("Run on creation of cron job {} failed: {}", job.id, e);
+            }
+        });
+        execution_id
+    }
+
+    /// Add a job that spawns `program` with `args` directly instead of
+    /// running a command through a shell, so nothing in the arguments is
+    /// interpreted
+    pub async fn add_job_exec(
+        &self,
+        name: &str,
+        schedule: &str,
+        program: &str,
+        args: Vec<String>,
+    ) -> Result<CronJob> {
+        let options = JobOptions::new().with_exec(program, args);
+        self.add_job_with_options(name, schedule, "", options).await
+    }
+
+    /// Replace the jobs (by id or name) a job triggers after each successful
+    /// run. Fails if a target doesn't exist or the triggers would form a cycle.
+    pub async fn set_job_triggers(&self, id: &str, triggers: Vec<String>) -> Result<CronJob> {
+        self.update_job_with_request(id, UpdateJobRequest::new().with_triggers(triggers))
+            .await
+    }
+
+    /// Set or clear the job (by name) or inline command run after each
+    /// failed or timed-out run of a job
+    pub async fn set_job_on_failure(&self, id: &str, hook: Option<String>) -> Result<CronJob> {
+        self.update_job_with_request(id, UpdateJobRequest::new().with_on_failure(hook))
+            .await
+    }
+
+    /// Set or clear the shell running a job's command. Fails if the shell
+    /// can't be found or isn't executable.
+    pub async fn set_job_shell(&self, id: &str, shell: Option<String>) -> Result<CronJob> {
+        self.update_job_with_request(id, UpdateJobRequest::new().with_shell(shell))
+            .await
+    }
+
+    /// Turn expansion of `{{ ... }}` tags in a job's command and env values
+    /// on or off
+    pub async fn set_job_templated(&self, id: &str, templated: bool) -> Result<CronJob> {
+        self.update_job_with_request(id, UpdateJobRequest::new().with_templated(templated))
+            .await
+    }
+
+    /// Turn saving of a job's executions to its history on or off. Runs
+    /// not recorded still update the job's statistics and emit events.
+    pub async fn set_job_record_history(&self, id: &str, record_history: bool) -> Result<CronJob> {
+        let request = UpdateJobRequest::new().with_record_history(record_history);
+        self.update_job_with_request(id, request).await
+    }
+
+    /// Turn reporting of output changes between a job's successful runs on
+    /// or off, optionally ignoring whitespace-only differences
+    pub async fn set_job_notify_on_change(
+        &self,
+        id: &str,
+        enabled: bool,
+        ignore_whitespace: bool,
+    ) -> Result<CronJob> {
+        let request = UpdateJobRequest::new().with_notify_on_change(enabled, ignore_whitespace);
+        self.update_job_with_request(id, request).await
+    }
+
+    /// Set a job's priority among jobs due at the same time, higher first
+    pub async fn set_job_priority(&self, id: &str, priority: i32) -> Result<CronJob> {
+        self.update_job_with_request(id, UpdateJobRequest::new().with_priority(priority))
+            .await
+    }
+
+    /// Set the operating system limits applied to a job's process. They are
+    /// only enforced on Unix.
+    pub async fn set_job_resource_limits(
+        &self,
+        id: &str,
+        limits: ResourceLimits,
+    ) -> Result<CronJob> {
+        self.update_job_with_request(id, UpdateJobRequest::new().with_resource_limits(limits))
+            .await
+    }
+
+    /// Set or clear the least time between the starts of two runs of a job.
+    /// Runs coming sooner, however triggered, are skipped.
+    pub async fn set_job_min_interval(
+        &self,
+        id: &str,
+        min_interval_ms: Option<u64>,
+    ) -> Result<CronJob> {
+        let request = UpdateJobRequest::new().with_min_interval(min_interval_ms);
+        self.update_job_with_request(id, request).await
+    }
+
+    /// Set or clear how long a job's runs normally take at most. Runs still
+    /// going after this long are reported with a `JobRunningLong` event.
+    pub async fn set_job_expected_duration(
+        &self,
+        id: &str,
+        expected_duration_ms: Option<u64>,
+    ) -> Result<CronJob> {
+        let request = UpdateJobRequest::new().with_expected_duration(expected_duration_ms);
+        self.update_job_with_request(id, request).await
+    }
+
+    /// Skip the job's next `occurrences` scheduled runs, replacing any
+    /// snoozed run count. Each is recorded as a skipped execution; manual
+    /// runs still go ahead.
+    pub async fn snooze_job(&self, id: &str, occurrences: u32) -> Result<CronJob> {
+        if occurrences == 0 {
+            return Err(CronError::InvalidConfig(
+                "a snooze must skip at least one run".to_string(),
+            ));
         }
+        let mut job = self
+            .store
+            .load_job(id)
+            .await?
+            .ok_or_else(|| CronError::JobNotFound(id.to_string()))?;
+
+        job.snoozed_runs = occurrences;
+        job.updated_at = Utc::now();
+        self.store.save_job(&job).await?;
+
+        tracing::info!(
+            "Snoozed cron job {} ({}) for {} run(s)",
+            job.name,
+            job.id,
+            occurrences
+        );
+        self.job_updated(&job, &["snoozed_runs"]);
+        Ok(job)
+    }
 
-        // Calculate next run
-        if let Ok(expr) = CronExpression::parse(&updated_job.schedule) {
-            updated_job.next_run = expr.next_after(Utc::now());
+    /// Skip the job's scheduled runs before `until`. Each is recorded as a
+    /// skipped execution; manual runs still go ahead.
+    pub async fn snooze_until(&self, id: &str, until: DateTime<Utc>) -> Result<CronJob> {
+        if until <= self.now() {
+            return Err(CronError::InvalidConfig(format!(
+                "snooze time {} is in the past",
+                until
+            )));
         }
+        let mut job = self
+            .store
+            .load_job(id)
+            .await?
+            .ok_or_else(|| CronError::JobNotFound(id.to_string()))?;
 
-        self.store.save_job(&updated_job).await?;
+        job.snoozed_until = Some(until);
+        job.updated_at = Utc::now();
+        self.store.save_job(&job).await?;
 
-        // Record telemetry
-        let duration = exec_start.elapsed();
-        let status_str = if execution.status == crate::types::ExecutionStatus::Success {
-            "success"
-        } else if execution.status == crate::types::ExecutionStatus::Timeout {
-            "timeout"
-        } else {
-            "failed"
-        };
-        span.record(telemetry::ATTR_JOB_STATUS, status_str);
-        span.record(telemetry::ATTR_JOB_DURATION_MS, duration.as_millis() as i64);
-        telemetry::record_job_execution(&job.name, status_str, duration.as_secs_f64());
+        tracing::info!("Snoozed cron job {} ({}) until {}", job.name, job.id, until);
+        self.job_updated(&job, &["snoozed_until"]);
+        Ok(job)
+    }
+
+    /// Clear a job's snooze, so its next scheduled run goes ahead
+    pub async fn unsnooze_job(&self, id: &str) -> Result<CronJob> {
+        let mut job = self
+            .store
+            .load_job(id)
+            .await?
+            .ok_or_else(|| CronError::JobNotFound(id.to_string()))?;
+
+        job.snoozed_runs = 0;
+        job.snoozed_until = None;
+        job.updated_at = Utc::now();
+        self.store.save_job(&job).await?;
 
-        Ok(execution)
+        tracing::info!("Unsnoozed cron job: {} ({})", job.name, job.id);
+        self.job_updated(&job, &["snoozed_runs", "snoozed_until"]);
+        Ok(job)
     }
 
-    /// Start the scheduler background task
-    pub async fn start(&self) -> Result<()> {
-        let mut running = self.running.write().await;
-        if *running {
+    /// Replace the names of environment variables whose values are masked as
+    /// secrets in a job's output, events and the job itself
+    pub async fn set_job_secret_env(&self, id: &str, names: Vec<String>) -> Result<CronJob> {
+        let request = UpdateJobRequest::new().with_secret_env(names);
+        Ok(self.update_job_with_request(id, request).await?.redacted())
+    }
+
+    /// Set or clear the environment a job's command inherits
+    pub async fn set_job_env_mode(&self, id: &str, env_mode: Option<EnvMode>) -> Result<CronJob> {
+        self.update_job_with_request(id, UpdateJobRequest::new().with_env_mode(env_mode))
+            .await
+    }
+
+    /// Check that every job `job` triggers exists and that triggering can't
+    /// loop back to `job`
+    async fn check_triggers(&self, job: &CronJob) -> Result<()> {
+        if job.triggers.is_empty() {
             return Ok(());
         }
-        *running = true;
-        drop(running);
+        let mut jobs = self.store.list_jobs().await?;
+        jobs.retain(|other| other.id != job.id);
+        jobs.push(job.clone());
+        check_triggers_among(&jobs, job)
+    }
 
-        let _ = self.event_tx.send(SchedulerEvent::Started);
-        tracing::info!("Cron scheduler started");
+    /// Add a job that runs once at `when` and then completes.
+    ///
+    /// A time in the past is rejected unless `run_if_past` is set, in which
+    /// case the job runs as soon as the scheduler sees it.
+    pub async fn add_one_shot_job(
+        &self,
+        name: &str,
+        when: DateTime<Utc>,
+        command: &str,
+        run_if_past: bool,
+    ) -> Result<CronJob> {
+        self.add_one_shot_job_with_options(name, when, command, run_if_past, JobOptions::default())
+            .await
+    }
 
-        let store = self.store.clone();
-        let event_tx = self.event_tx.clone();
-        let running = self.running.clone();
-        let workspace = self.workspace.clone();
-        let agent_executor = self.agent_executor.clone();
+    /// Add a one-shot job like [`add_one_shot_job`](Self::add_one_shot_job)
+    /// with additional options, validated as by
+    /// [`add_job_with_options`](Self::add_job_with_options)
+    pub async fn add_one_shot_job_with_options(
+        &self,
+        name: &str,
+        when: DateTime<Utc>,
+        command: &str,
+        run_if_past: bool,
+        options: JobOptions,
+    ) -> Result<CronJob> {
+        if when <= self.now() && !run_if_past {
+            return Err(CronError::InvalidConfig(format!(
+                "one-shot time {} is in the past",
+                when.to_rfc3339()
+            )));
+        }
 
-        tokio::spawn(async move {
-            let mut ticker = interval(Duration::from_secs(60));
+        let job = CronJob::one_shot(name, when, command);
+        let info = ScheduleInfo {
+            expression: job.schedule.clone(),
+            description: format!("Once at {}", when.to_rfc3339()),
+            next_run: Some(when),
+            interval_secs: None,
+            min_interval_secs: None,
+        };
+        let (job, _) = self.insert_job(job, Some(info), options).await?;
+        Ok(job)
+    }
 
-            loop {
-                ticker.tick().await;
-                telemetry::record_scheduler_tick();
+    /// Add a new agent-mode cron job.
+    ///
+    /// The `command` field is used as the agent prompt. When the job fires,
+    /// it creates a temporary Agent with the given config and sends the prompt.
+    pub async fn add_agent_job(
+        &self,
+        name: &str,
+        schedule: &str,
+        prompt: &str,
+        config: AgentJobConfig,
+    ) -> Result<CronJob> {
+        let options = JobOptions::new().with_agent_config(config);
+        self.add_job_with_options(name, schedule, prompt, options)
+            .await
+    }
 
-                // Check if still running
-                if !*running.read().await {
-                    break;
-                }
+    /// Add a native job running the handler registered as `handler`.
+    ///
+    /// The handler may be registered later; runs before then fail.
+    pub async fn add_native_job(
+        &self,
+        name: &str,
+        schedule: &str,
+        handler: &str,
+    ) -> Result<CronJob> {
+        if !self.runner.handlers.read().unwrap().contains_key(handler) {
+            tracing::warn!(
+                "Cron job {} uses native handler {} which isn't registered yet",
+                name,
+                handler
+            );
+        }
+        let options = JobOptions::new().with_job_type(JobType::Native);
+        self.add_job_with_options(name, schedule, handler, options)
+            .await
+    }
 
-                // Get all active jobs
-                let jobs = match store.list_jobs().await {
-                    Ok(jobs) => jobs,
-                    Err(e) => {
-                        tracing::error!("Failed to list jobs: {}", e);
-                        continue;
-                    }
-                };
+    /// Import jobs from a crontab document.
+    ///
+    /// Creates one shell job per schedule line. Jobs are named after the
+    /// comment on the preceding line, or after a hash of the command; names
+    /// that are already taken get a numeric suffix (`backup-2`). Environment
+    /// assignments apply to the jobs that follow them. Every line is validated
+    /// before any job is created.
+    pub async fn import_crontab<R: AsyncRead + Unpin>(
+        &self,
+        mut reader: R,
+    ) -> Result<Vec<CronJob>> {
+        let mut content = String::new();
+        reader.read_to_string(&mut content).await?;
+
+        let entries = crontab::parse_crontab(&content)?;
+        let mut taken: HashSet<String> = self
+            .store
+            .list_jobs()
+            .await?
+            .into_iter()
+            .map(|j| j.name)
+            .collect();
+
+        // Name and validate every entry before creating any job
+        let mut named = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let mut name = entry.name.clone();
+            let mut suffix = 2;
+            while taken.contains(&name) {
+                name = format!("{}-{}", entry.name, suffix);
+                suffix += 1;
+            }
+            if name.trim().is_empty() {
+                return Err(CronError::InvalidConfig(
+                    "job name must not be empty".to_string(),
+                ));
+            }
+            self.validate_schedule(&entry.schedule)?;
+            taken.insert(name.clone());
+            named.push((name, entry));
+        }
+
+        let mut jobs = Vec::with_capacity(named.len());
+        for (name, entry) in named {
+            let options = JobOptions {
+                env: entry.env,
+                ..JobOptions::default()
+            };
+            let job = self
+                .add_job_with_options(&name, &entry.schedule, &entry.command, options)
+                .await?;
+            jobs.push(job);
+        }
+
+        tracing::info!("Imported {} cron jobs from crontab", jobs.len());
+        Ok(jobs)
+    }
+
+    /// Export all jobs as a crontab document.
+    ///
+    /// Active shell jobs become regular crontab lines preceded by a
+    /// `# name: <job name>` comment; agent and paused jobs are emitted as
+    /// commented-out lines. The output can be read back with
+    /// [`import_crontab`](Self::import_crontab).
+    pub async fn export_crontab(&self) -> Result<String> {
+        let mut jobs = self.store.list_jobs().await?;
+        jobs.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.name.cmp(&b.name)));
+        Ok(crontab::render_crontab(&jobs))
+    }
+
+    /// Preview the next `count` fire times of a schedule, starting from now.
+    ///
+    /// Validates the expression with the same errors as `add_job`. `count` is
+    /// capped at [`MAX_PREVIEW_COUNT`].
+    pub fn preview_schedule(&self, schedule: &str, count: usize) -> Result<Vec<DateTime<Utc>>> {
+        let expr = CronExpression::parse(schedule)?;
+        Ok(expr
+            .upcoming(self.now())
+            .take(count.min(MAX_PREVIEW_COUNT))
+            .collect())
+    }
+
+    /// Preview the next `count` fire times of an existing job, starting from now.
+    ///
+    /// Fire times outside the job's allowed window are left out and deferred
+    /// runs shown at their deferred time. `count` is capped at
+    /// [`MAX_PREVIEW_COUNT`].
+    pub async fn preview_job(&self, id: &str, count: usize) -> Result<Vec<DateTime<Utc>>> {
+        let job = self
+            .store
+            .load_job(id)
+            .await?
+            .ok_or_else(|| CronError::JobNotFound(id.to_string()))?;
+
+        // Follow the job's own rules: date window, allowed window, excluded
+        // calendar and deferral past blackouts
+        let mut upcoming = Vec::new();
+        let mut after = self.now();
+        while upcoming.len() < count.min(MAX_PREVIEW_COUNT) {
+            let Some(next) = self.runner.next_run_after(&job, after) else {
+                break;
+            };
+            upcoming.push(next);
+            after = next;
+        }
+        Ok(upcoming)
+    }
+
+    /// Replay every active job's schedule over `range` without running
+    /// anything, returning the runs the scheduler would start in order.
+    ///
+    /// Fire times from `range.0` up to but excluding `range.1` are
+    /// considered. Date and allowed windows, excluded calendars, blackouts,
+    /// snoozes and `max_runs` are applied as the scheduler applies them. At most
+    /// [`MAX_SIMULATED_RUNS`] runs are returned; see
+    /// [`simulate_with_limit`](Self::simulate_with_limit).
+    pub async fn simulate(
+        &self,
+        range: (DateTime<Utc>, DateTime<Utc>),
+    ) -> Result<Vec<SimulatedRun>> {
+        self.simulate_with_limit(range, MAX_SIMULATED_RUNS).await
+    }
+
+    /// Like [`simulate`](Self::simulate), returning at most the first
+    /// `limit` runs
+    pub async fn simulate_with_limit(
+        &self,
+        range: (DateTime<Utc>, DateTime<Utc>),
+        limit: usize,
+    ) -> Result<Vec<SimulatedRun>> {
+        let (start, end) = range;
+        if end < start {
+            return Err(CronError::InvalidConfig(format!(
+                "simulation range ends ({}) before it starts ({})",
+                end, start
+            )));
+        }
+
+        // The first `limit` runs overall are among each job's first `limit`
+        let mut runs = Vec::new();
+        for job in self.store.list_jobs().await? {
+            if matches!(job.status, JobStatus::Active | JobStatus::Running) {
+                runs.extend(self.simulate_job(job, start, end, limit));
+            }
+        }
+        runs.sort_by(|a, b| {
+            a.fire_time
+                .cmp(&b.fire_time)
+                .then(a.job_name.cmp(&b.job_name))
+        });
+        runs.truncate(limit);
+        Ok(runs)
+    }
+
+    /// Runs of `job` from `start` up to `end`, at most `limit` of them
+    fn simulate_job(
+        &self,
+        mut job: CronJob,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        limit: usize,
+    ) -> Vec<SimulatedRun> {
+        let blackouts = &self.runner.blackouts;
+        let mut remaining = job
+            .max_runs
+            .map_or(usize::MAX, |max_runs| {
+                max_runs.saturating_sub(job.counted_runs()) as usize
+            })
+            .min(limit);
+
+        let mut runs = Vec::new();
+        let mut after = start - chrono::Duration::seconds(1);
+        while remaining > 0 {
+            let Some(fire_time) = self.runner.next_run_after(&job, after) else {
+                break;
+            };
+            if fire_time >= end {
+                break;
+            }
+            after = fire_time;
+            if fire_time < start {
+                continue;
+            }
+
+            // Skipped like the scheduler loop skips them
+            if job.is_snoozed_at(fire_time) {
+                job.next_run = self.runner.next_run_after(&job, fire_time);
+                job.consume_snooze();
+                continue;
+            }
+            if job.blackout_policy == BlackoutPolicy::Skip
+                && job.blackout_at(fire_time, blackouts).is_some()
+            {
+                continue;
+            }
+
+            runs.push(SimulatedRun {
+                job_id: job.id.clone(),
+                job_name: job.name.clone(),
+                fire_time,
+            });
+            remaining -= 1;
+        }
+        runs
+    }
+
+    /// Render the runs of active jobs over the next `range` as an iCalendar
+    /// feed, one event per run. See the [`ics`](crate::ics) module for the
+    /// format.
+    ///
+    /// Only jobs matching `filter` are included, with their secrets masked.
+    /// Fire times are computed as in [`simulate`](Self::simulate), keeping
+    /// the first [`MAX_EVENTS_PER_JOB`](ics::MAX_EVENTS_PER_JOB) runs of each
+    /// job and the first [`MAX_EVENTS`](ics::MAX_EVENTS) overall.
+    pub async fn export_ics(&self, range: Duration, filter: Option<&JobFilter>) -> Result<String> {
+        let now = self.now();
+        let end = chrono::Duration::from_std(range)
+            .ok()
+            .and_then(|range| now.checked_add_signed(range))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC);
+        let every_job = JobFilter::default();
+        let filter = filter.unwrap_or(&every_job);
+
+        let mut runs = Vec::new();
+        for job in self.store.list_jobs_filtered(filter).await? {
+            if !matches!(job.status, JobStatus::Active | JobStatus::Running) {
+                continue;
+            }
+            let redacted = job.redacted();
+            runs.extend(
+                self.simulate_job(job, now, end, ics::MAX_EVENTS_PER_JOB)
+                    .into_iter()
+                    .map(|run| (redacted.clone(), run.fire_time)),
+            );
+        }
+        runs.sort_by(|(a, a_time), (b, b_time)| a_time.cmp(b_time).then(a.name.cmp(&b.name)));
+        runs.truncate(ics::MAX_EVENTS);
+        Ok(ics::render_calendar(&runs, now))
+    }
+
+    /// Get a job by ID, with its secrets masked and its effective timeout
+    pub async fn get_job(&self, id: &str) -> Result<Option<CronJob>> {
+        Ok(self
+            .store
+            .load_job(id)
+            .await?
+            .map(|job| self.resolved(job.redacted())))
+    }
+
+    /// Get a job by name, with its secrets masked and its effective timeout
+    pub async fn get_job_by_name(&self, name: &str) -> Result<Option<CronJob>> {
+        Ok(self
+            .store
+            .find_job_by_name(name)
+            .await?
+            .map(|job| self.resolved(job.redacted())))
+    }
+
+    /// Fill in the job's effective timeout
+    fn resolved(&self, mut job: CronJob) -> CronJob {
+        job.effective_timeout_ms = Some(job.resolve_timeout_ms(self.runner.default_timeout_ms));
+        job
+    }
+
+    /// List all jobs, with their secrets masked
+    pub async fn list_jobs(&self) -> Result<Vec<CronJob>> {
+        self.list_jobs_filtered(&JobFilter::default()).await
+    }
+
+    /// List jobs matching `filter`, with their secrets masked
+    pub async fn list_jobs_filtered(&self, filter: &JobFilter) -> Result<Vec<CronJob>> {
+        Ok(self
+            .store
+            .list_jobs_filtered(filter)
+            .await?
+            .into_iter()
+            .map(|job| self.resolved(job.redacted()))
+            .collect())
+    }
+
+    /// List jobs matching `filter` like
+    /// [`list_jobs_filtered`](Self::list_jobs_filtered), with their health.
+    /// Each job's recent history is read to assess it.
+    pub async fn list_jobs_with_health(&self, filter: &JobFilter) -> Result<Vec<CronJob>> {
+        let mut jobs = self.list_jobs_filtered(filter).await?;
+        for job in &mut jobs {
+            job.health = Some(self.assess_health(job).await?);
+        }
+        Ok(jobs)
+    }
+
+    /// List jobs that aren't healthy, with their health
+    pub async fn list_unhealthy_jobs(&self) -> Result<Vec<CronJob>> {
+        let mut jobs = self.list_jobs_with_health(&JobFilter::default()).await?;
+        jobs.retain(|job| job.health != Some(JobHealth::Healthy));
+        Ok(jobs)
+    }
+
+    /// Get the health of a job
+    pub async fn job_health(&self, id: &str) -> Result<JobHealth> {
+        let job = self
+            .store
+            .load_job(id)
+            .await?
+            .ok_or_else(|| CronError::JobNotFound(id.to_string()))?;
+        self.assess_health(&job).await
+    }
+
+    /// Classify a job's health from its recent executions
+    async fn assess_health(&self, job: &CronJob) -> Result<JobHealth> {
+        let thresholds = &self.health_thresholds;
+        // Skipped runs don't count toward the window, so load some spare
+        let executions = self
+            .store
+            .load_executions(&job.id, thresholds.window.saturating_mul(2))
+            .await?;
+        Ok(thresholds.assess(job, &executions, self.now()))
+    }
+
+    /// Update a job's schedule, command or timeout.
+    ///
+    /// Shorthand for [`update_job_with_request`](Self::update_job_with_request).
+    pub async fn update_job(
+        &self,
+        id: &str,
+        schedule: Option<&str>,
+        command: Option<&str>,
+        timeout_ms: Option<u64>,
+    ) -> Result<CronJob> {
+        let request = UpdateJobRequest {
+            schedule: schedule.map(str::to_string),
+            command: command.map(str::to_string),
+            timeout_ms: timeout_ms.map(Some),
+            ..Default::default()
+        };
+        self.update_job_with_request(id, request).await
+    }
+
+    /// Update a job, keeping its id and history. `JobUpdated` lists the
+    /// fields given in the request. Nothing is changed if any field is
+    /// invalid.
+    pub async fn update_job_with_request(
+        &self,
+        id: &str,
+        request: UpdateJobRequest,
+    ) -> Result<CronJob> {
+        let mut job = self
+            .store
+            .load_job(id)
+            .await?
+            .ok_or_else(|| CronError::JobNotFound(id.to_string()))?;
+
+        let old_name = job.name.clone();
+        let changed_fields = self.apply_update(&mut job, request).await?;
+        job.updated_at = self.now();
+        self.store.save_job(&job).await?;
+
+        self.runner.wakeup.notify_one();
+        tracing::info!("Updated cron job: {} ({})", job.name, job.id);
+        self.job_updated(&job, &changed_fields);
+        if job.name != old_name {
+            self.rename_references(&old_name, &job.name).await?;
+        }
+        Ok(job)
+    }
+
+    /// Rename a job, keeping its id, history and statistics. Triggers and
+    /// `on_failure` hooks naming the job follow it to the new name.
+    pub async fn rename_job(&self, id: &str, new_name: &str) -> Result<CronJob> {
+        self.update_job_with_request(id, UpdateJobRequest::new().with_name(new_name))
+            .await
+    }
+
+    /// Point triggers and `on_failure` hooks naming the job `old_name` at
+    /// `new_name`
+    async fn rename_references(&self, old_name: &str, new_name: &str) -> Result<()> {
+        for mut job in self.store.list_jobs().await? {
+            let mut changed_fields = Vec::new();
+            if job.triggers.iter().any(|target| target == old_name) {
+                for target in &mut job.triggers {
+                    if target == old_name {
+                        *target = new_name.to_string();
+                    }
+                }
+                changed_fields.push("triggers");
+            }
+            if job.on_failure.as_deref() == Some(old_name) {
+                job.on_failure = Some(new_name.to_string());
+                changed_fields.push("on_failure");
+            }
+            if changed_fields.is_empty() {
+                continue;
+            }
+            job.updated_at = Utc::now();
+            self.store.save_job(&job).await?;
+            tracing::info!(
+                "Renamed {} to {} in cron job {} ({})",
+                old_name,
+                new_name,
+                job.name,
+                job.id
+            );
+            self.job_updated(&job, &changed_fields);
+        }
+        Ok(())
+    }
+
+    /// Copy a job under a new name, with a fresh id, no run history and
+    /// `overrides` applied. The copy is scheduled from now on, independently
+    /// of the source.
+    pub async fn clone_job(
+        &self,
+        source_id: &str,
+        new_name: &str,
+        overrides: Option<UpdateJobRequest>,
+    ) -> Result<CronJob> {
+        let source = self
+            .store
+            .load_job(source_id)
+            .await?
+            .ok_or_else(|| CronError::JobNotFound(source_id.to_string()))?;
+
+        let now = self.now();
+        let mut job = CronJob {
+            id: Uuid::new_v4().to_string(),
+            name: new_name.to_string(),
+            // A copy of a paused or disabled job keeps its status; anything
+            // else starts over
+            status: match source.status {
+                status @ (JobStatus::Paused | JobStatus::Disabled) => status,
+                _ => JobStatus::Active,
+            },
+            created_at: now,
+            updated_at: now,
+            last_run: None,
+            next_run: None,
+            run_count: 0,
+            fail_count: 0,
+            manual_run_count: 0,
+            consecutive_failures: 0,
+            last_error: None,
+            snoozed_runs: 0,
+            snoozed_until: None,
+            // The copy's output and durations are compared with its own runs
+            last_output_hash: None,
+            duration_stats: DurationStats::default(),
+            schedule_info: None,
+            health: None,
+            ..source
+        };
+        self.check_name(&job.name, &job.id).await?;
+        if let Some(overrides) = overrides {
+            self.apply_update(&mut job, overrides).await?;
+        }
+        self.check_triggers(&job).await?;
+        self.runner.reschedule(&mut job, self.now());
+
+        self.store.save_job(&job).await?;
+
+        self.runner.wakeup.notify_one();
+        tracing::info!(
+            "Cloned cron job {} as: {} ({})",
+            source_id,
+            job.name,
+            job.id
+        );
+        self.job_added(&job);
+        Ok(job)
+    }
+
+    /// Check that `name` can be given to the job `id`: non-empty and not
+    /// taken by another job
+    async fn check_name(&self, name: &str, id: &str) -> Result<()> {
+        if name.trim().is_empty() {
+            return Err(CronError::InvalidConfig(
+                "job name must not be empty".to_string(),
+            ));
+        }
+        match self.store.find_job_by_name(name).await? {
+            Some(other) if other.id != id => Err(CronError::JobExists(name.to_string())),
+            _ => Ok(()),
+        }
+    }
+
+    /// Apply `request` to `job` without saving it, returning the names of
+    /// the fields given
+    async fn apply_update(
+        &self,
+        job: &mut CronJob,
+        request: UpdateJobRequest,
+    ) -> Result<Vec<&'static str>> {
+        let mut changed_fields = Vec::new();
+        let mut reschedule = false;
+        if let Some(name) = request.name {
+            self.check_name(&name, &job.id).await?;
+            changed_fields.push("name");
+            job.name = name;
+        }
+
+        if let Some(schedule) = request.schedule.as_deref() {
+            changed_fields.push("schedule");
+            if is_never(schedule) {
+                job.schedule = NEVER_SCHEDULE.to_string();
+                job.schedule_kind = ScheduleKind::Never;
+            } else {
+                let info = self.validate_schedule(schedule)?;
+                job.schedule = info.expression;
+                job.schedule_kind = ScheduleKind::Cron;
+            }
+            reschedule = true;
+        }
+
+        if let Some(command) = request.command {
+            if job.is_exec() {
+                return Err(CronError::InvalidConfig(format!(
+                    "exec job {} has no shell command to update",
+                    job.name
+                )));
+            }
+            changed_fields.push("command");
+            job.command = command;
+        }
+
+        if let Some(timeout) = request.timeout_ms {
+            changed_fields.push("timeout_ms");
+            job.timeout_ms = timeout;
+        }
+
+        if let Some(env) = request.env {
+            changed_fields.push("env");
+            job.env = env;
+        }
+
+        if let Some(working_dir) = request.working_dir {
+            changed_fields.push("working_dir");
+            job.working_dir = working_dir;
+        }
+
+        if let Some(config) = request.agent_config {
+            if job.job_type != JobType::Agent {
+                return Err(CronError::InvalidConfig(format!(
+                    "{} job {} has no agent configuration",
+                    job.job_type, job.name
+                )));
+            }
+            changed_fields.push("agent_config");
+            job.agent_config = Some(config);
+        }
+
+        if let Some(policy) = request.concurrency_policy {
+            changed_fields.push("concurrency_policy");
+            job.concurrency_policy = policy;
+        }
+
+        if let Some(tags) = request.tags {
+            check_tags(&tags)?;
+            changed_fields.push("tags");
+            job.tags = tags;
+        }
+
+        if request.not_before.is_some() || request.not_after.is_some() {
+            let not_before = request.not_before.unwrap_or(job.not_before);
+            let not_after = request.not_after.unwrap_or(job.not_after);
+            check_window(not_before, not_after)?;
+            if request.not_before.is_some() {
+                changed_fields.push("not_before");
+            }
+            if request.not_after.is_some() {
+                changed_fields.push("not_after");
+            }
+            job.not_before = not_before;
+            job.not_after = not_after;
+            reschedule = true;
+        }
+
+        if let Some(blackouts) = request.blackouts {
+            changed_fields.push("blackouts");
+            job.blackouts = blackouts;
+            reschedule = true;
+        }
+
+        if let Some(policy) = request.blackout_policy {
+            changed_fields.push("blackout_policy");
+            job.blackout_policy = policy;
+            reschedule = true;
+        }
+
+        if let Some(calendar) = request.exclude_calendar {
+            self.check_calendar(calendar.as_deref())?;
+            changed_fields.push("exclude_calendar");
+            job.exclude_calendar = calendar;
+            reschedule = true;
+        }
+
+        if let Some(policy) = request.holiday_policy {
+            changed_fields.push("holiday_policy");
+            job.holiday_policy = policy;
+            reschedule = true;
+        }
+
+        if let Some(on_failure) = request.on_failure {
+            changed_fields.push("on_failure");
+            job.on_failure = on_failure;
+        }
+
+        if let Some(shell) = request.shell {
+            if let Some(shell) = &shell {
+                process::check_executable(shell)?;
+            }
+            changed_fields.push("shell");
+            job.shell = shell;
+        }
+
+        if let Some(env_mode) = request.env_mode {
+            changed_fields.push("env_mode");
+            job.env_mode = env_mode;
+        }
+
+        if let Some(names) = request.secret_env {
+            changed_fields.push("secret_env");
+            job.secret_env = names;
+        }
+
+        if let Some(templated) = request.templated {
+            changed_fields.push("templated");
+            job.templated = templated;
+        }
+
+        if let Some(priority) = request.priority {
+            changed_fields.push("priority");
+            job.priority = priority;
+        }
+
+        if let Some(min_interval_ms) = request.min_interval_ms {
+            changed_fields.push("min_interval_ms");
+            job.min_interval_ms = min_interval_ms;
+        }
+
+        if let Some(expected_duration_ms) = request.expected_duration_ms {
+            changed_fields.push("expected_duration_ms");
+            job.expected_duration_ms = expected_duration_ms;
+        }
+
+        if let Some(limits) = request.resource_limits {
+            warn_unenforced_limits(&job.name, &limits);
+            changed_fields.push("resource_limits");
+            job.resource_limits = limits;
+        }
+
+        if let Some(record_history) = request.record_history {
+            changed_fields.push("record_history");
+            job.record_history = record_history;
+        }
+
+        if let Some(enabled) = request.notify_on_change {
+            changed_fields.push("notify_on_change");
+            job.notify_on_change = enabled;
+            if !enabled {
+                job.last_output_hash = None;
+            }
+        }
+
+        if let Some(ignore_whitespace) = request.ignore_whitespace_changes {
+            changed_fields.push("ignore_whitespace_changes");
+            job.ignore_whitespace_changes = ignore_whitespace;
+        }
+
+        if let Some(window) = request.allowed_window {
+            if let Some(window) = &window {
+                check_allowed_window(window)?;
+            }
+            changed_fields.push("allowed_window");
+            job.allowed_window = window;
+            reschedule = true;
+        }
+
+        if let Some(jitter_ms) = request.jitter_ms {
+            changed_fields.push("jitter_ms");
+            job.jitter_ms = jitter_ms;
+            reschedule = true;
+        }
+
+        if let Some((max_retries, retry_delay_ms, backoff)) = request.retries {
+            changed_fields.extend(["max_retries", "retry_delay_ms", "retry_backoff"]);
+            job.max_retries = max_retries;
+            job.retry_delay_ms = retry_delay_ms;
+            job.retry_backoff = backoff;
+        }
+
+        if let Some(max_runs) = request.max_runs {
+            changed_fields.push("max_runs");
+            job.max_runs = max_runs;
+        }
+
+        if let Some(count_manual) = request.count_manual {
+            changed_fields.push("count_manual");
+            job.count_manual = count_manual;
+        }
+
+        if let Some(failures) = request.auto_pause_after {
+            changed_fields.push("auto_pause_after");
+            job.auto_pause_after = failures;
+        }
+
+        if let Some(policy) = request.misfire_policy {
+            changed_fields.push("misfire_policy");
+            job.misfire_policy = policy;
+        }
+
+        if let Some(kill_grace_ms) = request.kill_grace_ms {
+            changed_fields.push("kill_grace_ms");
+            job.kill_grace_ms = kill_grace_ms;
+        }
+
+        // Checked last, against the job's final name
+        if let Some(triggers) = request.triggers {
+            changed_fields.push("triggers");
+            job.triggers = triggers;
+            self.check_triggers(job).await?;
+        }
+
+        if reschedule {
+            self.runner.reschedule(job, self.now());
+        }
+        Ok(changed_fields)
+    }
+
+    /// Set or clear the date window a job may run in
+    pub async fn set_job_window(
+        &self,
+        id: &str,
+        not_before: Option<DateTime<Utc>>,
+        not_after: Option<DateTime<Utc>>,
+    ) -> Result<CronJob> {
+        let request = UpdateJobRequest::new().with_window(not_before, not_after);
+        self.update_job_with_request(id, request).await
+    }
+
+    /// Replace a job's blackout windows and their policy
+    pub async fn set_job_blackouts(
+        &self,
+        id: &str,
+        blackouts: Vec<TimeWindow>,
+        policy: BlackoutPolicy,
+    ) -> Result<CronJob> {
+        let request = UpdateJobRequest::new().with_blackouts(blackouts, policy);
+        self.update_job_with_request(id, request).await
+    }
+
+    /// Set or clear the calendar whose dates a job doesn't run on, and how
+    /// fire times on them are handled. Fails if no calendar is registered
+    /// under the name.
+    pub async fn set_job_calendar(
+        &self,
+        id: &str,
+        calendar: Option<String>,
+        policy: BlackoutPolicy,
+    ) -> Result<CronJob> {
+        let request = UpdateJobRequest::new().with_calendar(calendar, policy);
+        self.update_job_with_request(id, request).await
+    }
+
+    /// Pause a job.
+    ///
+    /// Fails with [`CronError::JobDisabled`] for disabled jobs.
+    pub async fn pause_job(&self, id: &str) -> Result<CronJob> {
+        let mut job = self
+            .store
+            .load_job(id)
+            .await?
+            .ok_or_else(|| CronError::JobNotFound(id.to_string()))?;
+
+        if job.is_disabled() {
+            return Err(CronError::JobDisabled(job.id));
+        }
+        job.status = JobStatus::Paused;
+        job.updated_at = Utc::now();
+        self.store.save_job(&job).await?;
+
+        tracing::info!("Paused cron job: {} ({})", job.name, job.id);
+        self.emit(SchedulerEvent::JobPaused {
+            job_id: job.id.clone(),
+            job_name: job.name.clone(),
+            timestamp: Utc::now(),
+        });
+        Ok(job)
+    }
+
+    /// Resume a paused job.
+    ///
+    /// Fails with [`CronError::JobCompleted`] for completed jobs; use
+    /// [`reset_and_resume_job`](Self::reset_and_resume_job) to run a job
+    /// that used up its `max_runs` again, or reopen its date window.
+    /// Fails with [`CronError::JobDisabled`] for disabled jobs; use
+    /// [`enable_job`](Self::enable_job) instead.
+    pub async fn resume_job(&self, id: &str) -> Result<CronJob> {
+        let job = self
+            .store
+            .load_job(id)
+            .await?
+            .ok_or_else(|| CronError::JobNotFound(id.to_string()))?;
+
+        if job.is_completed() {
+            return Err(CronError::JobCompleted(job.id));
+        }
+        if job.is_disabled() {
+            return Err(CronError::JobDisabled(job.id));
+        }
+        Ok(self.job_resumed(self.activate(job).await?))
+    }
+
+    /// Disable a job. Unlike a paused job, a disabled job is not run by the
+    /// scheduler, by triggers or by hand until it is enabled again.
+    pub async fn disable_job(&self, id: &str) -> Result<CronJob> {
+        let mut job = self
+            .store
+            .load_job(id)
+            .await?
+            .ok_or_else(|| CronError::JobNotFound(id.to_string()))?;
+
+        job.status = JobStatus::Disabled;
+        job.updated_at = Utc::now();
+        self.store.save_job(&job).await?;
+
+        tracing::info!("Disabled cron job: {} ({})", job.name, job.id);
+        self.emit(SchedulerEvent::JobDisabled {
+            job_id: job.id.clone(),
+            job_name: job.name.clone(),
+            timestamp: Utc::now(),
+        });
+        Ok(job)
+    }
+
+    /// Enable a disabled job, scheduling its next run. Jobs that are not
+    /// disabled are returned unchanged.
+    pub async fn enable_job(&self, id: &str) -> Result<CronJob> {
+        let job = self
+            .store
+            .load_job(id)
+            .await?
+            .ok_or_else(|| CronError::JobNotFound(id.to_string()))?;
+
+        if !job.is_disabled() {
+            return Ok(job);
+        }
+        let job = self.activate(job).await?;
+        tracing::info!("Enabled cron job: {} ({})", job.name, job.id);
+        self.emit(SchedulerEvent::JobEnabled {
+            job_id: job.id.clone(),
+            job_name: job.name.clone(),
+            timestamp: Utc::now(),
+        });
+        Ok(job)
+    }
+
+    /// Reset a job's run counters and resume it, restarting its `max_runs`
+    pub async fn reset_and_resume_job(&self, id: &str) -> Result<CronJob> {
+        let mut job = self
+            .store
+            .load_job(id)
+            .await?
+            .ok_or_else(|| CronError::JobNotFound(id.to_string()))?;
+
+        job.run_count = 0;
+        job.fail_count = 0;
+        job.manual_run_count = 0;
+        Ok(self.job_resumed(self.activate(job).await?))
+    }
+
+    /// Broadcast an event to subscribers
+    fn emit(&self, event: SchedulerEvent) {
+        self.runner.emit(event);
+    }
+
+    /// Announce a new job
+    fn job_added(&self, job: &CronJob) {
+        self.emit(SchedulerEvent::JobAdded {
+            job_id: job.id.clone(),
+            name: job.name.clone(),
+            timestamp: Utc::now(),
+        });
+    }
+
+    /// Announce a change to a job's definition
+    fn job_updated(&self, job: &CronJob, changed_fields: &[&str]) {
+        self.emit(SchedulerEvent::JobUpdated {
+            job_id: job.id.clone(),
+            job_name: job.name.clone(),
+            changed_fields: changed_fields
+                .iter()
+                .map(|field| field.to_string())
+                .collect(),
+            timestamp: Utc::now(),
+        });
+    }
+
+    /// Mark a job active and schedule its next run
+    async fn activate(&self, mut job: CronJob) -> Result<CronJob> {
+        job.status = JobStatus::Active;
+        job.consecutive_failures = 0;
+        job.updated_at = Utc::now();
+
+        // Recalculate next run
+        self.runner.reschedule(&mut job, self.now());
+
+        self.store.save_job(&job).await?;
+
+        self.runner.wakeup.notify_one();
+        Ok(job)
+    }
+
+    /// Announce a resumed job
+    fn job_resumed(&self, job: CronJob) -> CronJob {
+        tracing::info!("Resumed cron job: {} ({})", job.name, job.id);
+        self.emit(SchedulerEvent::JobResumed {
+            job_id: job.id.clone(),
+            job_name: job.name.clone(),
+            timestamp: Utc::now(),
+        });
+        job
+    }
+
+    /// Export every job definition as a versioned JSON document, without
+    /// execution history. Secret env values and agent API keys are masked,
+    /// so fill them in before importing, or import with masked secrets
+    /// allowed and set them again afterwards.
+    pub async fn export_jobs(&self) -> Result<String> {
+        let mut jobs = self.store.list_jobs().await?;
+        jobs.sort_by(|a, b| a.name.cmp(&b.name));
+        let jobs = jobs
+            .iter()
+            .map(|job| {
+                let mut job = job.redacted();
+                if let Some(config) = &mut job.agent_config {
+                    config.api_key = SECRET_MASK.to_string();
+                }
+                job.health = None;
+                job
+            })
+            .collect();
+        let export = JobsExport {
+            version: EXPORT_VERSION,
+            exported_at: Utc::now(),
+            jobs,
+        };
+        Ok(serde_json::to_string_pretty(&export)?)
+    }
+
+    /// Import jobs exported by [`export_jobs`](Self::export_jobs).
+    ///
+    /// Jobs whose name is taken are handled per `mode`. Imported jobs get new
+    /// IDs unless `preserve_ids` is set; an overwritten job keeps its own ID
+    /// either way, and triggers naming a job by its exported ID follow it to
+    /// its new one. Run counters and statistics are reset and next runs
+    /// recomputed. Every job is validated before any is written, so a bad
+    /// entry imports nothing.
+    ///
+    /// Jobs still holding secrets masked by the export are rejected unless
+    /// `allow_masked` is set, in which case they are imported as they are
+    /// and their secrets must be set again before they run.
+    pub async fn import_jobs(
+        &self,
+        json: &str,
+        mode: ImportMode,
+        preserve_ids: bool,
+        allow_masked: bool,
+    ) -> Result<ImportReport> {
+        let export: JobsExport = serde_json::from_str(json)?;
+        if export.version != EXPORT_VERSION {
+            return Err(CronError::InvalidConfig(format!(
+                "unsupported export version {} (expected {})",
+                export.version, EXPORT_VERSION
+            )));
+        }
+
+        // Validate everything before writing anything
+        let existing = self.store.list_jobs().await?;
+        let mut seen = HashSet::new();
+        let mut report = ImportReport::default();
+        let mut planned = Vec::new();
+        // Exported ID of each job to the ID it ends up with
+        let mut ids = HashMap::new();
+        for mut job in export.jobs {
+            if job.name.trim().is_empty() {
+                return Err(CronError::InvalidConfig(
+                    "job name must not be empty".to_string(),
+                ));
+            }
+            if !seen.insert(job.name.clone()) {
+                return Err(CronError::InvalidConfig(format!(
+                    "job {} appears more than once",
+                    job.name
+                )));
+            }
+            if job.schedule_kind == ScheduleKind::Cron {
+                job.schedule = self.validate_schedule(&job.schedule)?.expression;
+            }
+            check_tags(&job.tags)?;
+            if has_masked_secrets(&job) {
+                if !allow_masked {
+                    return Err(CronError::InvalidConfig(format!(
+                        "job {} has secrets masked by the export; fill them in or allow masked secrets",
+                        job.name
+                    )));
+                }
+                tracing::warn!("Importing cron job {} with masked secrets", job.name);
+            }
+
+            let exported_id = job.id.clone();
+            let taken = existing.iter().find(|other| other.name == job.name);
+            match (taken, mode) {
+                (Some(other), ImportMode::Skip) => {
+                    ids.insert(exported_id, other.id.clone());
+                    report.skipped.push(job.name);
+                    continue;
+                }
+                (Some(_), ImportMode::Fail) => return Err(CronError::JobExists(job.name)),
+                (Some(other), ImportMode::Overwrite) => job.id = other.id.clone(),
+                (None, _) if preserve_ids => {
+                    if let Some(other) = existing.iter().find(|other| other.id == job.id) {
+                        return Err(CronError::InvalidConfig(format!(
+                            "job {} has the ID of existing job {}",
+                            job.name, other.name
+                        )));
+                    }
+                }
+                (None, _) => job.id = Uuid::new_v4().to_string(),
+            }
+            ids.insert(exported_id, job.id.clone());
+
+            if job.status == JobStatus::Running {
+                job.status = JobStatus::Active;
+            }
+            job.updated_at = self.now();
+            job.last_run = None;
+            job.run_count = 0;
+            job.fail_count = 0;
+            job.manual_run_count = 0;
+            job.consecutive_failures = 0;
+            job.last_error = None;
+            job.snoozed_runs = 0;
+            job.snoozed_until = None;
+            job.last_output_hash = None;
+            job.duration_stats = DurationStats::default();
+            if job.status != JobStatus::Completed {
+                self.runner.reschedule(&mut job, self.now());
+            }
+            planned.push((job, taken.is_some()));
+        }
+
+        // Point triggers at the imported jobs' new IDs, then check them
+        // against the jobs there will be once everything is written
+        for (job, _) in &mut planned {
+            for reference in &mut job.triggers {
+                if let Some(id) = ids.get(reference.as_str()) {
+                    *reference = id.clone();
+                }
+            }
+        }
+        let mut after: Vec<CronJob> = existing
+            .into_iter()
+            .filter(|other| !planned.iter().any(|(job, _)| job.id == other.id))
+            .collect();
+        after.extend(planned.iter().map(|(job, _)| job.clone()));
+        for (job, _) in &planned {
+            check_triggers_among(&after, job)?;
+        }
+
+        for (job, overwrite) in planned {
+            self.store.save_job(&job).await?;
+            if overwrite {
+                self.job_updated(&job, &["definition"]);
+                report.overwritten.push(job.name);
+            } else {
+                self.job_added(&job);
+                report.created.push(job.name);
+            }
+        }
+        self.runner.wakeup.notify_one();
+
+        tracing::info!(
+            "Imported cron jobs: {} created, {} overwritten, {} skipped",
+            report.created.len(),
+            report.overwritten.len(),
+            report.skipped.len()
+        );
+        Ok(report)
+    }
+
+    /// Converge the store to `manifest`, keyed by job name: missing jobs are
+    /// created, jobs whose definition differs are updated, and with `prune`
+    /// jobs not in the manifest are removed (keeping their history). Status
+    /// and run history of existing jobs are left alone.
+    ///
+    /// Every declared job is validated before anything is written. With
+    /// `dry_run` the returned plan is computed but nothing is written.
+    pub async fn apply_manifest(
+        &self,
+        manifest: &JobManifest,
+        prune: bool,
+        dry_run: bool,
+    ) -> Result<ManifestPlan> {
+        let existing = self.store.list_jobs().await?;
+        let mut seen = HashSet::new();
+        let mut plan = ManifestPlan::default();
+        let mut creates = Vec::new();
+        let mut updates = Vec::new();
+        for declared in &manifest.jobs {
+            if declared.name.trim().is_empty() {
+                return Err(CronError::InvalidConfig(
+                    "job name must not be empty".to_string(),
+                ));
+            }
+            if !seen.insert(declared.name.as_str()) {
+                return Err(CronError::InvalidConfig(format!(
+                    "job {} appears more than once",
+                    declared.name
+                )));
+            }
+            check_job_type(&declared.options())?;
+            check_tags(&declared.tags)?;
+
+            match existing.iter().find(|job| job.name == declared.name) {
+                None => {
+                    if !is_never(&declared.schedule) {
+                        self.validate_schedule(&declared.schedule)?;
+                    }
+                    plan.created.push(declared.name.clone());
+                    creates.push(declared);
+                }
+                Some(job) => match self.manifest_changes(declared, job)? {
+                    Some(request) => {
+                        plan.updated.push(declared.name.clone());
+                        updates.push((job.id.clone(), request));
+                    }
+                    None => plan.unchanged.push(declared.name.clone()),
+                },
+            }
+        }
+        let removals: Vec<&CronJob> = if prune {
+            existing
+                .iter()
+                .filter(|job| !seen.contains(job.name.as_str()))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        plan.removed = removals.iter().map(|job| job.name.clone()).collect();
+
+        if dry_run {
+            return Ok(plan);
+        }
+        // Triggers are set once every declared job exists, so they may name
+        // jobs declared later
+        for declared in creates {
+            let mut options = declared.options();
+            let triggers = std::mem::take(&mut options.triggers);
+            let job = self
+                .add_job_with_options(
+                    &declared.name,
+                    &declared.schedule,
+                    &declared.command,
+                    options,
+                )
+                .await?;
+            if !triggers.is_empty() {
+                updates.push((job.id, UpdateJobRequest::new().with_triggers(triggers)));
+            }
+        }
+        for (id, request) in updates {
+            self.update_job_with_request(&id, request).await?;
+        }
+        for job in removals {
+            self.remove_job(&job.id, false).await?;
+        }
+
+        tracing::info!(
+            "Applied job manifest: {} created, {} updated, {} removed, {} unchanged",
+            plan.created.len(),
+            plan.updated.len(),
+            plan.removed.len(),
+            plan.unchanged.len()
+        );
+        Ok(plan)
+    }
+
+    /// Read a JSON [`JobManifest`] from `path` and
+    /// [`apply_manifest`](Self::apply_manifest) it
+    pub async fn apply_manifest_file(
+        &self,
+        path: impl AsRef<Path>,
+        prune: bool,
+        dry_run: bool,
+    ) -> Result<ManifestPlan> {
+        let content = tokio::fs::read_to_string(path).await?;
+        let manifest: JobManifest = serde_json::from_str(&content)?;
+        self.apply_manifest(&manifest, prune, dry_run).await
+    }
+
+    /// Re-apply the manifest set with [`with_manifest`](Self::with_manifest),
+    /// as SIGHUP does once [`install_signal_handlers`] is called; hosts
+    /// handling signals themselves call it from their own handler. The
+    /// outcome, changes or error, is logged and emitted as a
+    /// `ManifestReloaded` event.
+    ///
+    /// [`install_signal_handlers`]: Self::install_signal_handlers
+    pub async fn reload_manifest(&self) -> Result<ManifestPlan> {
+        let (path, prune) = self.manifest.clone().ok_or_else(|| {
+            CronError::InvalidConfig("no manifest is configured to reload".to_string())
+        })?;
+        let result = self.apply_manifest_file(&path, prune, false).await;
+        let (created, updated, removed, error) = match &result {
+            Ok(plan) => {
+                tracing::info!("Reloaded job manifest {}", path.display());
+                (
+                    plan.created.len(),
+                    plan.updated.len(),
+                    plan.removed.len(),
+                    None,
+                )
+            }
+            Err(e) => {
+                tracing::error!("Failed to reload job manifest {}: {}", path.display(), e);
+                (0, 0, 0, Some(e.to_string()))
+            }
+        };
+        self.emit(SchedulerEvent::ManifestReloaded {
+            path: path.to_string_lossy().to_string(),
+            created,
+            updated,
+            removed,
+            error,
+            timestamp: Utc::now(),
+        });
+        result
+    }
+
+    /// Update turning `job` into its declaration, or `None` if they match
+    fn manifest_changes(
+        &self,
+        declared: &ManifestJob,
+        job: &CronJob,
+    ) -> Result<Option<UpdateJobRequest>> {
+        if declared.job_type != job.job_type || job.is_exec() {
+            return Err(CronError::InvalidConfig(format!(
+                "job {} can't be changed into a {} job by a manifest",
+                job.name, declared.job_type
+            )));
+        }
+
+        let mut request = UpdateJobRequest::new();
+        let mut changed = false;
+        let (kind, schedule) = if is_never(&declared.schedule) {
+            (ScheduleKind::Never, NEVER_SCHEDULE.to_string())
+        } else {
+            let info = self.validate_schedule(&declared.schedule)?;
+            (ScheduleKind::Cron, info.expression)
+        };
+        // Schedules differing only in spacing or name case are the same
+        if job.schedule_kind != kind
+            || parser::normalize(&job.schedule) != parser::normalize(&schedule)
+        {
+            request = request.with_schedule(schedule);
+            changed = true;
+        }
+        if job.command != declared.command {
+            request = request.with_command(declared.command.clone());
+            changed = true;
+        }
+        if job.timeout_ms != declared.timeout_ms {
+            request.timeout_ms = Some(declared.timeout_ms);
+            changed = true;
+        }
+        let env: BTreeMap<&str, &str> = job
+            .env
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect();
+        let declared_env: BTreeMap<&str, &str> = declared
+            .env
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect();
+        if env != declared_env || env.len() != job.env.len() {
+            request = request.with_env(declared.env.clone().into_iter().collect());
+            changed = true;
+        }
+        let working_dir = job.working_dir.as_deref().unwrap_or(&self.workspace);
+        let declared_dir = declared.working_dir.as_deref().unwrap_or(&self.workspace);
+        if working_dir != declared_dir {
+            request = request.with_working_dir(declared.working_dir.clone());
+            changed = true;
+        }
+        if job.agent_config != declared.agent_config {
+            if let Some(config) = &declared.agent_config {
+                request = request.with_agent_config(config.clone());
+                changed = true;
+            }
+        }
+        if job.concurrency_policy != declared.concurrency_policy {
+            request = request.with_concurrency_policy(declared.concurrency_policy);
+            changed = true;
+        }
+        if job.tags != declared.tags {
+            request = request.with_tags(declared.tags.clone());
+            changed = true;
+        }
+        if job.kill_grace_ms != declared.kill_grace_ms.unwrap_or_default() {
+            request = request.with_kill_grace_ms(declared.kill_grace_ms.unwrap_or_default());
+            changed = true;
+        }
+        let retries = (
+            declared.max_retries,
+            declared.retry_delay_ms,
+            declared.retry_backoff,
+        );
+        if (job.max_retries, job.retry_delay_ms, job.retry_backoff) != retries {
+            request.retries = Some(retries);
+            changed = true;
+        }
+        if job.max_runs != declared.max_runs || job.count_manual != declared.count_manual {
+            request = request.with_max_runs(declared.max_runs, declared.count_manual);
+            changed = true;
+        }
+        if job.auto_pause_after != declared.auto_pause_after {
+            request = request.with_auto_pause_after(declared.auto_pause_after);
+            changed = true;
+        }
+        if job.misfire_policy != declared.misfire_policy {
+            request = request.with_misfire_policy(declared.misfire_policy);
+            changed = true;
+        }
+        if job.not_before != declared.not_before || job.not_after != declared.not_after {
+            request = request.with_window(declared.not_before, declared.not_after);
+            changed = true;
+        }
+        if job.jitter_ms != declared.jitter_ms {
+            request = request.with_jitter(declared.jitter_ms);
+            changed = true;
+        }
+        if job.blackouts != declared.blackouts || job.blackout_policy != declared.blackout_policy {
+            request = request.with_blackouts(declared.blackouts.clone(), declared.blackout_policy);
+            changed = true;
+        }
+        if job.exclude_calendar != declared.exclude_calendar
+            || job.holiday_policy != declared.holiday_policy
+        {
+            request =
+                request.with_calendar(declared.exclude_calendar.clone(), declared.holiday_policy);
+            changed = true;
+        }
+        if job.allowed_window != declared.allowed_window {
+            request = request.with_allowed_window(declared.allowed_window.clone());
+            changed = true;
+        }
+        if job.triggers != declared.triggers {
+            request = request.with_triggers(declared.triggers.clone());
+            changed = true;
+        }
+        if job.on_failure != declared.on_failure {
+            request = request.with_on_failure(declared.on_failure.clone());
+            changed = true;
+        }
+        if job.shell != declared.shell {
+            request = request.with_shell(declared.shell.clone());
+            changed = true;
+        }
+        if job.env_mode != declared.env_mode {
+            request = request.with_env_mode(declared.env_mode.clone());
+            changed = true;
+        }
+        if job.secret_env != declared.secret_env {
+            request = request.with_secret_env(declared.secret_env.clone());
+            changed = true;
+        }
+        if job.templated != declared.templated {
+            request = request.with_templated(declared.templated);
+            changed = true;
+        }
+        if job.priority != declared.priority {
+            request = request.with_priority(declared.priority);
+            changed = true;
+        }
+        if job.min_interval_ms != declared.min_interval_ms {
+            request = request.with_min_interval(declared.min_interval_ms);
+            changed = true;
+        }
+        if job.expected_duration_ms != declared.expected_duration_ms {
+            request = request.with_expected_duration(declared.expected_duration_ms);
+            changed = true;
+        }
+        if job.resource_limits != declared.resource_limits {
+            request = request.with_resource_limits(declared.resource_limits);
+            changed = true;
+        }
+        if job.record_history != declared.record_history {
+            request = request.with_record_history(declared.record_history);
+            changed = true;
+        }
+        if job.notify_on_change != declared.notify_on_change
+            || job.ignore_whitespace_changes != declared.ignore_whitespace_changes
+        {
+            request = request.with_notify_on_change(
+                declared.notify_on_change,
+                declared.ignore_whitespace_changes,
+            );
+            changed = true;
+        }
+        Ok(changed.then_some(request))
+    }
+
+    /// Render a job as a systemd timer and service unit pair. See the
+    /// [`systemd`](crate::systemd) module for how schedules convert and which
+    /// jobs can't be exported.
+    pub async fn export_systemd_unit(&self, job_id: &str) -> Result<SystemdUnit> {
+        let job = self
+            .store
+            .load_job(job_id)
+            .await?
+            .ok_or_else(|| CronError::JobNotFound(job_id.to_string()))?;
+        systemd::render_unit(&self.resolved(job), self.default_shell())
+    }
+
+    /// Render a job as a Kubernetes CronJob manifest in YAML, running in
+    /// `image`. See the [`k8s`](crate::k8s) module for how jobs convert and
+    /// which can't be exported.
+    pub async fn export_k8s_cronjob(
+        &self,
+        job_id: &str,
+        image: &str,
+        options: &K8sExportOptions,
+    ) -> Result<String> {
+        let job = self
+            .store
+            .load_job(job_id)
+            .await?
+            .ok_or_else(|| CronError::JobNotFound(job_id.to_string()))?;
+        let job = self.resolved(job);
+        CronJobManifest::from_job(&job, image, self.default_shell(), options)?.to_yaml()
+    }
+
+    /// Pause the active or running jobs matching `filter`, going on past
+    /// jobs that fail
+    pub async fn pause_jobs(&self, filter: &JobFilter) -> Result<BulkResult> {
+        let mut result = BulkResult::default();
+        for job in self.store.list_jobs_filtered(filter).await? {
+            if matches!(job.status, JobStatus::Active | JobStatus::Running) {
+                result.record(&job.id, &self.pause_job(&job.id).await);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Resume the paused jobs matching `filter`, going on past jobs that fail
+    pub async fn resume_jobs(&self, filter: &JobFilter) -> Result<BulkResult> {
+        let mut result = BulkResult::default();
+        for job in self.store.list_jobs_filtered(filter).await? {
+            if job.is_paused() {
+                result.record(&job.id, &self.resume_job(&job.id).await);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Remove the jobs matching `filter`, going on past jobs that fail. An
+    /// empty filter is rejected so a mistake can't remove every job.
+    pub async fn remove_jobs(&self, filter: &JobFilter, purge_history: bool) -> Result<BulkResult> {
+        if filter.is_empty() {
+            return Err(CronError::InvalidConfig(
+                "refusing to remove jobs with an empty filter".to_string(),
+            ));
+        }
+        let mut result = BulkResult::default();
+        for job in self.store.list_jobs_filtered(filter).await? {
+            result.record(&job.id, &self.remove_job(&job.id, purge_history).await);
+        }
+        Ok(result)
+    }
+
+    /// Remove a job, also deleting its execution history if `purge_history`
+    /// is set
+    pub async fn remove_job(&self, id: &str, purge_history: bool) -> Result<()> {
+        let job = self
+            .store
+            .load_job(id)
+            .await?
+            .ok_or_else(|| CronError::JobNotFound(id.to_string()))?;
+
+        self.store.delete_job(id).await?;
+        if purge_history {
+            self.store.delete_executions(id).await?;
+        }
+
+        tracing::info!("Removed cron job: {} ({})", job.name, job.id);
+        self.emit(SchedulerEvent::JobRemoved {
+            job_id: job.id,
+            job_name: job.name.clone(),
+            timestamp: Utc::now(),
+        });
+        Ok(())
+    }
+
+    /// Get execution history for a job
+    pub async fn get_history(&self, job_id: &str, limit: usize) -> Result<Vec<JobExecution>> {
+        self.store.load_executions(job_id, limit).await
+    }
+
+    /// Get the executions of a job matching `filter`, most recent first
+    pub async fn query_history(
+        &self,
+        job_id: &str,
+        filter: &ExecutionFilter,
+    ) -> Result<Vec<JobExecution>> {
+        self.store.query_executions(job_id, filter).await
+    }
+
+    /// Get aggregates of a job's last [`STATS_WINDOW`] executions
+    pub async fn job_stats(&self, id: &str) -> Result<JobStats> {
+        let job = self
+            .store
+            .load_job(id)
+            .await?
+            .ok_or_else(|| CronError::JobNotFound(id.to_string()))?;
+        let executions = self.store.load_executions(id, STATS_WINDOW).await?;
+        Ok(JobStats::compute(&job.redacted(), &executions))
+    }
+
+    /// Get an execution by ID, e.g. one named by an event
+    pub async fn get_execution(&self, execution_id: &str) -> Result<Option<JobExecution>> {
+        self.store.load_execution(execution_id).await
+    }
+
+    /// Delete all but the `keep_last` most recent executions of a job,
+    /// returning how many were deleted
+    pub async fn purge_history(&self, job_id: &str, keep_last: usize) -> Result<usize> {
+        let pruned = self.store.prune_executions(job_id, keep_last).await?;
+        tracing::debug!("Pruned {} execution(s) of cron job {}", pruned, job_id);
+        Ok(pruned)
+    }
+
+    /// Delete the executions of a job started before `cutoff`, returning how
+    /// many were deleted
+    pub async fn purge_history_before(&self, job_id: &str, cutoff: DateTime<Utc>) -> Result<usize> {
+        let pruned = self.store.prune_executions_before(job_id, cutoff).await?;
+        tracing::debug!("Pruned {} execution(s) of cron job {}", pruned, job_id);
+        Ok(pruned)
+    }
+
+    /// Get the stdout and stderr of an execution, reading them from its
+    /// output files if it has any
+    pub async fn get_execution_output(&self, execution_id: &str) -> Result<(String, String)> {
+        let execution = self
+            .get_execution(execution_id)
+            .await?
+            .ok_or_else(|| CronError::ExecutionNotFound(execution_id.to_string()))?;
+        match &execution.output_files {
+            Some(files) => Ok(output::read(files).await?),
+            None => Ok((execution.stdout, execution.stderr)),
+        }
+    }
+
+    /// Manually run a job. Under leader election followers run it too.
+    pub async fn run_job(&self, id: &str) -> Result<JobExecution> {
+        self.run_job_with(id, RunOptions::default()).await
+    }
+
+    /// Manually run the job with the given name
+    pub async fn run_job_by_name(&self, name: &str) -> Result<JobExecution> {
+        let job = self
+            .store
+            .find_job_by_name(name)
+            .await?
+            .ok_or_else(|| CronError::JobNotFound(name.to_string()))?;
+        self.run_job(&job.id).await
+    }
+
+    /// Manually run a job with options. Overrides apply to this execution
+    /// only and are listed in its [`overrides`](JobExecution::overrides);
+    /// the stored job is left unchanged.
+    ///
+    /// Fails with [`CronError::JobPaused`] for paused jobs unless
+    /// `force_if_paused` is set.
+    pub async fn run_job_with(&self, id: &str, options: RunOptions) -> Result<JobExecution> {
+        let (job, execution) = self.prepare_run(id, &options).await?;
+        if !options.force {
+            if let Some(reason) = job.rate_limit(self.now()) {
+                return self.runner.skip_execution(&job, execution, &reason).await;
+            }
+        }
+        self.runner
+            .execute_limited(
+                &job,
+                options.bypass_limit,
+                Trigger::Manual(Box::new(execution)),
+            )
+            .await
+    }
+
+    /// Manually run a job on a background task, returning the id of its
+    /// execution without waiting for it to finish.
+    ///
+    /// The run is listed by [`running_executions`](Self::running_executions)
+    /// and can be cancelled while in flight; follow it through events or poll
+    /// [`get_execution`](Self::get_execution), which finds it once finished.
+    pub async fn run_job_detached(&self, id: &str) -> Result<String> {
+        self.run_job_detached_with(id, RunOptions::default()).await
+    }
+
+    /// Manually run a job with options on a background task, returning the
+    /// id of its execution. See [`run_job_with`](Self::run_job_with).
+    pub async fn run_job_detached_with(&self, id: &str, options: RunOptions) -> Result<String> {
+        let (job, execution) = self.prepare_run(id, &options).await?;
+        if !options.force {
+            if let Some(reason) = job.rate_limit(self.now()) {
+                let skipped = self.runner.skip_execution(&job, execution, &reason).await?;
+                return Ok(skipped.id);
+            }
+        }
+
+        let execution_id = execution.id.clone();
+        let runner = self.runner.clone();
+        tokio::spawn(async move {
+            let trigger = Trigger::Manual(Box::new(execution));
+            if let Err(e) = runner
+                .execute_limited(&job, options.bypass_limit, trigger)
+                .await
+            {
+                tracing::error!("Detached run of cron job {} failed: {}", job.id, e);
+            }
+        });
+        Ok(execution_id)
+    }
+
+    /// Load a job for a manual run, checking it may run now and applying the
+    /// options' overrides. Returns the job with the execution to record.
+    async fn prepare_run(&self, id: &str, options: &RunOptions) -> Result<(CronJob, JobExecution)> {
+        let mut job = self
+            .store
+            .load_job(id)
+            .await?
+            .ok_or_else(|| CronError::JobNotFound(id.to_string()))?;
+
+        if job.is_disabled() {
+            return Err(CronError::JobDisabled(job.id));
+        }
+        if job.is_paused() && !options.force_if_paused {
+            return Err(CronError::JobPaused(job.id));
+        }
+        let overrides = options.apply(&mut job)?;
+        if let Some(window) = job.blackout_at(self.now(), &self.runner.blackouts) {
+            if !options.ignore_blackout {
+                return Err(CronError::InBlackout(format!("{} ({})", job.id, window)));
+            }
+            tracing::warn!(
+                "Running cron job {} during blackout window {}",
+                job.id,
+                window
+            );
+        }
+
+        if !self.is_leader() {
+            tracing::info!("Running cron job {} manually on a follower", job.id);
+        }
+        if !overrides.is_empty() {
+            tracing::info!(
+                "Running cron job {} with overrides: {}",
+                job.id,
+                overrides.join(", ")
+            );
+        }
+        let mut execution = JobExecution::new(&job.id);
+        execution.started_at = self.now();
+        execution.trigger = TriggerKind::Manual;
+        execution.overrides = overrides;
+        Ok((job, execution))
+    }
+
+    /// List every execution currently in flight, oldest first
+    pub fn running_executions(&self) -> Vec<RunningExecution> {
+        self.runner.running()
+    }
+
+    /// List every occurrence queued behind a running execution of its job by
+    /// the `Queue` concurrency policy, in the order they were queued
+    pub fn queued_executions(&self) -> Vec<QueuedExecution> {
+        self.runner.queued()
+    }
+
+    /// Check whether any execution of the job is in flight
+    pub fn is_job_running(&self, job_id: &str) -> bool {
+        self.runner.is_running(job_id)
+    }
+
+    /// Cancel a running execution, killing its process. The execution is
+    /// recorded as cancelled once it has stopped.
+    pub async fn cancel_execution(&self, execution_id: &str) -> Result<()> {
+        if self.runner.cancel(execution_id) {
+            tracing::info!("Cancelling execution {}", execution_id);
+            Ok(())
+        } else {
+            Err(CronError::ExecutionNotRunning(execution_id.to_string()))
+        }
+    }
+
+    /// Cancel every running execution of a job, returning the IDs of the
+    /// executions that were cancelled
+    pub async fn cancel_job_executions(&self, job_id: &str) -> Result<Vec<String>> {
+        if self.store.load_job(job_id).await?.is_none() {
+            return Err(CronError::JobNotFound(job_id.to_string()));
+        }
+        let cancelled = self.runner.cancel_job(job_id);
+        tracing::info!(
+            "Cancelling {} execution(s) of job {}",
+            cancelled.len(),
+            job_id
+        );
+        Ok(cancelled)
+    }
+
+    /// Start the scheduler background task.
+    ///
+    /// Before the loop begins, jobs interrupted by a crash are recovered and
+    /// active jobs whose next fire time passed while the scheduler was
+    /// stopped are brought up to date by their misfire policy, with the new
+    /// fire times saved; under leader election, once the manager leads. Fails with [`CronError::AlreadyRunning`] if the
+    /// scheduler is already running; a start racing a [`stop`](Self::stop)
+    /// waits for the old loop to exit first.
+    pub async fn start(&self) -> Result<()> {
+        let _lifecycle = self.lifecycle.lock().await;
+        if *self.running.read().await || self.is_running().await {
+            return Err(CronError::AlreadyRunning);
+        }
+
+        let jobs = self.store.list_jobs().await?;
+        let now = self.now();
+        for job in &jobs {
+            if let Some(name) = &job.exclude_calendar {
+                if self.calendar(name).is_none() {
+                    tracing::warn!(
+                        "Cron job {} excludes calendar {}, which is not registered",
+                        job.id,
+                        name
+                    );
+                }
+            }
+        }
+        let stale_jobs = jobs
+            .iter()
+            .filter(|job| job.status == JobStatus::Active)
+            .filter(|job| job.next_run.is_some_and(|next_run| next_run <= now))
+            .count();
+        *self.running.write().await = true;
+
+        let tick_interval = self.tick_interval;
+        self.emit(SchedulerEvent::Started {
+            tick_interval,
+            jobs_loaded: jobs.len(),
+            stale_jobs,
+            timestamp: Utc::now(),
+        });
+        tracing::info!(
+            "Cron scheduler started with {} job(s), {} overdue (tick interval {:?})",
+            jobs.len(),
+            stale_jobs,
+            tick_interval
+        );
+
+        // Under leader election this waits until the manager leads
+        let leader_election = matches!(
+            self.runner.coordination,
+            Coordination::LeaderElection { .. }
+        );
+        if !leader_election {
+            if let Err(e) = self.runner.recover_interrupted().await {
+                tracing::error!("Failed to recover interrupted jobs: {}", e);
+            }
+        }
+
+        // While paused, missed runs are handled on resume
+        if !leader_election && !*self.paused.read().await {
+            if let Err(e) = self
+                .runner
+                .handle_misfires("Missed while the scheduler was stopped")
+                .await
+            {
+                tracing::error!("Failed to process missed runs: {}", e);
+            }
+        }
+
+        *self.last_tick.lock().unwrap() = Some(Utc::now());
+        self.stalled.store(false, Ordering::SeqCst);
+        let scheduler_loop = SchedulerLoop {
+            store: self.store.clone(),
+            running: self.running.clone(),
+            paused: self.paused.clone(),
+            runner: self.runner.clone(),
+            tick_interval,
+            last_tick: self.last_tick.clone(),
+        };
+        *self.loop_handle.lock().unwrap() = Some(scheduler_loop.clone().spawn());
+        let watchdog = Watchdog {
+            scheduler_loop,
+            loop_handle: self.loop_handle.clone(),
+            stall_after_ticks: self.stall_after_ticks,
+            restart: self.restart_on_stall,
+            stalled: self.stalled.clone(),
+            restarts: self.restarts.clone(),
+        };
+        *self.watchdog_handle.lock().unwrap() = Some(tokio::spawn(watchdog.run()));
+
+        Ok(())
+    }
+
+    /// Stop the scheduler
+    pub async fn stop(&self) {
+        if self.stop_loop().await {
+            self.emit(SchedulerEvent::Stopped {
+                timestamp: Utc::now(),
+            });
+            tracing::info!("Cron scheduler stopped");
+        }
+    }
+
+    /// Hold back all due jobs without changing any job's status.
+    ///
+    /// Runs that come due while paused are handled by each job's misfire
+    /// policy on [`resume_scheduler`](Self::resume_scheduler). Manual runs
+    /// and executions already in flight are unaffected.
+    pub async fn pause_scheduler(&self) {
+        let mut paused = self.paused.write().await;
+        if *paused {
+            return;
+        }
+        *paused = true;
+        self.emit(SchedulerEvent::SchedulerPaused {
+            timestamp: Utc::now(),
+        });
+        tracing::info!("Cron scheduler paused");
+    }
+
+    /// Resume dispatching due jobs after [`pause_scheduler`](Self::pause_scheduler)
+    pub async fn resume_scheduler(&self) {
+        let mut paused = self.paused.write().await;
+        if !*paused {
+            return;
+        }
+        *paused = false;
+        drop(paused);
+
+        if self.is_running().await && self.is_leader() {
+            if let Err(e) = self
+                .runner
+                .handle_misfires("Missed while the scheduler was paused")
+                .await
+            {
+                tracing::error!("Failed to process missed runs: {}", e);
+            }
+        }
+        self.runner.wakeup.notify_one();
+        self.emit(SchedulerEvent::SchedulerResumed {
+            timestamp: Utc::now(),
+        });
+        tracing::info!("Cron scheduler resumed");
+    }
+
+    /// Get whether the scheduler is stopped, running or paused
+    pub async fn scheduler_state(&self) -> SchedulerState {
+        if !self.is_running().await {
+            SchedulerState::Stopped
+        } else if *self.paused.read().await {
+            SchedulerState::Paused
+        } else {
+            SchedulerState::Running
+        }
+    }
+
+    /// Stop the scheduler and wait for running executions to finish.
+    ///
+    /// No new runs start once this is called. Executions still running after
+    /// `timeout` are cancelled; their IDs are returned. `Stopped` is emitted
+    /// once every execution has ended.
+    pub async fn shutdown(&self, timeout: Duration) -> Vec<String> {
+        let was_running = self.stop_loop().await;
+
+        let mut cancelled = Vec::new();
+        if tokio::time::timeout(timeout, self.runner.wait_idle())
+            .await
+            .is_err()
+        {
+            cancelled = self.runner.cancel_all();
+            tracing::info!(
+                "Cancelling {} execution(s) still running at shutdown",
+                cancelled.len()
+            );
+            self.runner.wait_idle().await;
+        }
+
+        if was_running {
+            self.emit(SchedulerEvent::Stopped {
+                timestamp: Utc::now(),
+            });
+        }
+        tracing::info!("Cron scheduler shut down");
+        cancelled
+    }
+
+    /// Answer SIGHUP by [reloading the manifest](Self::reload_manifest), and
+    /// SIGTERM or SIGINT by [shutting down](Self::shutdown) with
+    /// [`SIGNAL_SHUTDOWN_TIMEOUT`]; the host still decides when to exit,
+    /// e.g. on the `Stopped` event. Calling it again does nothing.
+    ///
+    /// Fails, without panicking, outside a Tokio runtime or if the signals
+    /// can't be listened for; hosts that handle signals themselves call
+    /// `reload_manifest` and `shutdown` from their own handlers instead.
+    #[cfg(all(unix, feature = "signals"))]
+    pub fn install_signal_handlers(self: &Arc<Self>) -> Result<()> {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut handle = self.signal_handle.lock().unwrap();
+        if handle.is_some() {
+            return Ok(());
+        }
+        if tokio::runtime::Handle::try_current().is_err() {
+            return Err(CronError::InvalidConfig(
+                "signal handlers must be installed within a Tokio runtime".to_string(),
+            ));
+        }
+        let listen = |kind: SignalKind, name: &str| {
+            signal(kind).map_err(|e| {
+                tracing::warn!("Failed to listen for {}: {}", name, e);
+                CronError::Io(e)
+            })
+        };
+        let mut hangup = listen(SignalKind::hangup(), "SIGHUP")?;
+        let mut terminate = listen(SignalKind::terminate(), "SIGTERM")?;
+        let mut interrupt = listen(SignalKind::interrupt(), "SIGINT")?;
+
+        // The task mustn't keep the manager alive
+        let manager = Arc::downgrade(self);
+        *handle = Some(tokio::spawn(async move {
+            loop {
+                let reload = tokio::select! {
+                    _ = hangup.recv() => true,
+                    _ = terminate.recv() => false,
+                    _ = interrupt.recv() => false,
+                };
+                let Some(manager) = manager.upgrade() else {
+                    break;
+                };
+                if !reload {
+                    tracing::info!("Shutting down cron scheduler on signal");
+                    manager.shutdown(SIGNAL_SHUTDOWN_TIMEOUT).await;
+                    break;
+                }
+                if manager.manifest.is_none() {
+                    tracing::warn!("Ignoring SIGHUP: no job manifest is configured");
+                    continue;
+                }
+                // The outcome is logged and emitted
+                let _ = manager.reload_manifest().await;
+            }
+        }));
+        tracing::info!("Installed cron signal handlers");
+        Ok(())
+    }
+
+    /// Stop the scheduler loop and wait for it to exit. Returns whether it
+    /// was running.
+    async fn stop_loop(&self) -> bool {
+        let _lifecycle = self.lifecycle.lock().await;
+        *self.running.write().await = false;
+        self.runner.wakeup.notify_one();
+
+        if let Some(watchdog) = self.watchdog_handle.lock().unwrap().take() {
+            watchdog.abort();
+        }
+        let handle = self.loop_handle.lock().unwrap().take();
+        match handle {
+            Some(handle) => {
+                // A stalled loop would never see the flag
+                if self.stalled.swap(false, Ordering::SeqCst) {
+                    handle.abort();
+                }
+                match handle.await {
+                    Err(e) if !e.is_cancelled() => {
+                        tracing::error!("Cron scheduler loop failed: {}", e)
+                    }
+                    _ => {}
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Report how the scheduler loop is doing: its state, when it last went
+    /// round, and whether the watchdog found it stalled
+    pub async fn health(&self) -> SchedulerHealth {
+        SchedulerHealth {
+            state: self.scheduler_state().await,
+            last_tick: *self.last_tick.lock().unwrap(),
+            stalled: self.stalled.load(Ordering::SeqCst),
+            restarts: self.restarts.load(Ordering::SeqCst),
+        }
+    }
+
+    /// Check if the scheduler loop is running
+    pub async fn is_running(&self) -> bool {
+        self.loop_handle
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|handle| !handle.is_finished())
+    }
+}
+
+impl Drop for CronManager {
+    /// Abort the scheduler loop, if started, without waiting for it or for
+    /// executions in flight; [`stop`](CronManager::stop) shuts down in
+    /// order. A leader dropped this way keeps its lease until it expires.
+    fn drop(&mut self) {
+        if let Some(watchdog) = self.watchdog_handle.get_mut().unwrap().take() {
+            watchdog.abort();
+        }
+        if let Some(signals) = self.signal_handle.get_mut().unwrap().take() {
+            signals.abort();
+        }
+        if let Some(handle) = self.loop_handle.lock().unwrap().take() {
+            handle.abort();
+        }
+    }
+}
+
+/// Everything the scheduler loop needs, kept so the watchdog can start the
+/// loop again
+#[derive(Clone)]
+struct SchedulerLoop {
+    store: Arc<dyn CronStore>,
+    running: Arc<RwLock<bool>>,
+    paused: Arc<RwLock<bool>>,
+    runner: JobRunner,
+    tick_interval: Duration,
+    /// When the loop last went round, by the wall clock
+    last_tick: Arc<std::sync::Mutex<Option<DateTime<Utc>>>>,
+}
+
+impl SchedulerLoop {
+    /// Run the loop on its own task
+    fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(self.run())
+    }
+
+    /// Dispatch due jobs until the scheduler stops
+    async fn run(self) {
+        let runner = &self.runner;
+        let tick_interval = self.tick_interval;
+        let dispatcher = runner.dispatcher();
+        let renewal = runner.leader_renewal();
+
+        // Scheduled fire time of each job's most recent launch, so a job
+        // still in flight is not launched twice for the same slot
+        let mut fired: HashMap<String, DateTime<Utc>> = HashMap::new();
+
+        loop {
+            telemetry::record_scheduler_tick();
+            *self.last_tick.lock().unwrap() = Some(Utc::now());
+
+            // Check if still running
+            if !*self.running.read().await {
+                break;
+            }
+
+            // Under leader election only the leader goes on; the others
+            // keep asking for the lease
+            let is_paused = *self.paused.read().await;
+            if !runner.campaign(is_paused).await {
+                tokio::select! {
+                    _ = sleep_for(renewal) => {}
+                    _ = runner.wakeup.notified() => {}
+                }
+                continue;
+            }
+
+            // Leave due jobs untouched until the scheduler is resumed
+            if is_paused {
+                let until = runner.clock.now()
+                    + chrono::Duration::from_std(tick_interval).unwrap_or(chrono::TimeDelta::MAX);
+                tokio::select! {
+                    _ = runner.clock.sleep_until(until) => {}
+                    _ = tokio::time::sleep(tick_interval) => {}
+                    _ = sleep_for(renewal) => {}
+                    _ = runner.wakeup.notified() => {}
+                }
+                continue;
+            }
+
+            // A panic while going through the jobs costs this tick only
+            let wakeup = match AssertUnwindSafe(self.tick(&dispatcher, &mut fired))
+                .catch_unwind()
+                .await
+            {
+                Ok(wakeup) => wakeup,
+                Err(panic) => {
+                    tracing::error!("Cron scheduler tick panicked: {}", panic_message(&*panic));
+                    runner.clock.now()
+                        + chrono::Duration::from_std(tick_interval)
+                            .unwrap_or(chrono::TimeDelta::MAX)
+                }
+            };
+
+            // Sleep until the earliest fire time, or until jobs change. The
+            // loop goes round at least every tick interval of wall-clock
+            // time, so the watchdog can tell it is alive.
+            tokio::select! {
+                _ = runner.clock.sleep_until(wakeup) => {}
+                _ = tokio::time::sleep(tick_interval) => {}
+                _ = sleep_for(renewal) => {}
+                _ = runner.wakeup.notified() => {}
+            }
+        }
+        runner.resign().await;
+    }
+
+    /// Go through the jobs once, dispatching those due. Returns when the
+    /// loop should next look.
+    async fn tick(
+        &self,
+        dispatcher: &Dispatcher,
+        fired: &mut HashMap<String, DateTime<Utc>>,
+    ) -> DateTime<Utc> {
+        let jobs = match self.store.list_jobs().await {
+            Ok(jobs) => jobs,
+            Err(e) => {
+                tracing::error!("Failed to list jobs: {}", e);
+                Vec::new()
+            }
+        };
+
+        let now = self.runner.clock.now();
+        fired.retain(|id, _| jobs.iter().any(|job| &job.id == id));
+        let mut wakeup =
+            now + chrono::Duration::from_std(self.tick_interval).unwrap_or(chrono::TimeDelta::MAX);
+
+        let mut due = Vec::new();
+        for job in jobs {
+            // A panic handling one job is recorded as a failed run of it,
+            // and the rest of the jobs are still handled
+            let checked = AssertUnwindSafe(self.check_job(job.clone(), now, fired, &mut wakeup))
+                .catch_unwind()
+                .await;
+            match checked {
+                Ok(Some(job)) => due.push(job),
+                Ok(None) => {}
+                Err(panic) => {
+                    let message = panic_message(&*panic);
+                    tracing::error!(
+                        "Cron scheduler panicked handling job {}: {}",
+                        job.id,
+                        message
+                    );
+                    // Don't go back to the slot every tick
+                    if let Some(next_run) = job.next_run {
+                        fired.insert(job.id.clone(), next_run);
+                    }
+                    if let Err(e) = self.runner.record_panic(&job, &message).await {
+                        tracing::error!("Failed to record panic of job {}: {}", job.id, e);
+                    }
+                }
+            }
+        }
+
+        // Run due jobs on their own tasks so a slow job can't delay the
+        // rest; when concurrency is limited they queue by priority, then by
+        // fire time
+        due.sort_by_key(|job| (std::cmp::Reverse(job.priority), job.next_run));
+        for job in due {
+            dispatcher.dispatch(job);
+        }
+        wakeup
+    }
+
+    /// Decide what to do with a job this tick, returning it if it should be
+    /// dispatched now and bringing `wakeup` forward to its next fire time
+    async fn check_job(
+        &self,
+        job: CronJob,
+        now: DateTime<Utc>,
+        fired: &mut HashMap<String, DateTime<Utc>>,
+        wakeup: &mut DateTime<Utc>,
+    ) -> Option<CronJob> {
+        let runner = &self.runner;
+        let store = &self.store;
+
+        // Skip paused and disabled jobs; running ones are left to their
+        // concurrency policy
+        if !matches!(job.status, JobStatus::Active | JobStatus::Running) {
+            return None;
+        }
+        let Some(next_run) = job.next_run else {
+            // Complete idle jobs whose date window has closed
+            if job.status == JobStatus::Active && job.not_after.is_some_and(|end| end <= now) {
+                let mut job = job;
+                runner.reschedule(&mut job, now);
+                if let Err(e) = store.save_job(&job).await {
+                    tracing::error!("Failed to complete job {}: {}", job.id, e);
+                }
+            }
+            return None;
+        };
+        if fired.get(&job.id) == Some(&next_run) {
+            return None;
+        }
+
+        // next_run stays the nominal time; jitter only delays dispatch
+        let dispatch_at = job.dispatch_time(next_run);
+        if dispatch_at > now {
+            *wakeup = (*wakeup).min(dispatch_at);
+            return None;
+        }
+
+        // With leases only one manager handles the slot; the others retry
+        // each tick until it is claimed or the holder's lease expires
+        let job = runner.lease_slot(&job, next_run).await?;
+        if job.is_snoozed_at(next_run) {
+            fired.insert(job.id.clone(), next_run);
+            if let Err(e) = runner.skip_snoozed(&job).await {
+                tracing::error!("Failed to skip job {}: {}", job.id, e);
+            }
+            runner.release_lease(&job.id).await;
+            return None;
+        }
+        if let Some(window) = job.blackout_at(next_run, &runner.blackouts) {
+            match job.blackout_policy {
+                BlackoutPolicy::Skip => {
+                    fired.insert(job.id.clone(), next_run);
+                    let reason = format!("Fire time is in blackout window {}", window);
+                    if let Err(e) = runner.skip(&job, &reason).await {
+                        tracing::error!("Failed to skip job {}: {}", job.id, e);
+                    }
+                }
+                BlackoutPolicy::Defer => {
+                    let deferred = job.defer_past_blackouts(next_run, &runner.blackouts);
+                    let defer = |stored: &mut CronJob| stored.next_run = Some(deferred);
+                    match runner.advance_past(&job.id, next_run, defer).await {
+                        Ok(Some(_)) => *wakeup = (*wakeup).min(deferred),
+                        Ok(None) => {}
+                        Err(e) => tracing::error!("Failed to defer job {}: {}", job.id, e),
+                    }
+                }
+            }
+            runner.release_lease(&job.id).await;
+            return None;
+        }
+
+        // Claim the slot before the job starts: next_run moves past it right
+        // away, even while the job waits for a concurrency permit, and
+        // `fired` covers stores that fail to save. Overlap with a run still
+        // in flight is then up to the job's concurrency policy. The job is
+        // reloaded for the claim, so a pause or edit made since it was listed
+        // is kept, and a job paused or removed meanwhile doesn't run.
+        fired.insert(job.id.clone(), next_run);
+        let advance = |stored: &mut CronJob| stored.next_run = runner.next_run_after(stored, now);
+        match runner.advance_past(&job.id, next_run, advance).await {
+            Ok(Some(mut claimed)) => {
+                // The dispatched job keeps the slot it was scheduled for
+                claimed.next_run = Some(next_run);
+                Some(claimed)
+            }
+            Ok(None) => {
+                runner.release_lease(&job.id).await;
+                None
+            }
+            Err(e) => {
+                tracing::error!("Failed to advance job {}: {}", job.id, e);
+                Some(job)
+            }
+        }
+    }
+}
+
+/// Watches the scheduler loop, reporting it once it stops going round and,
+/// if asked to, starting it again
+struct Watchdog {
+    scheduler_loop: SchedulerLoop,
+    loop_handle: Arc<std::sync::Mutex<Option<JoinHandle<()>>>>,
+    /// Tick intervals without a tick after which the loop counts as stalled
+    stall_after_ticks: u32,
+    /// Whether a stalled loop is replaced by a new one
+    restart: bool,
+    stalled: Arc<AtomicBool>,
+    restarts: Arc<AtomicU64>,
+}
+
+impl Watchdog {
+    /// Check on the loop every tick interval until the scheduler stops
+    async fn run(self) {
+        let tick_interval = self.scheduler_loop.tick_interval;
+        let stall_after = tick_interval
+            .checked_mul(self.stall_after_ticks)
+            .unwrap_or(Duration::MAX);
+        let mut checks =
+            tokio::time::interval_at(tokio::time::Instant::now() + tick_interval, tick_interval);
+        checks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            checks.tick().await;
+            if !*self.scheduler_loop.running.read().await {
+                break;
+            }
+            let last_tick = *self.scheduler_loop.last_tick.lock().unwrap();
+            let silent = last_tick
+                .is_none_or(|at| (Utc::now() - at).to_std().unwrap_or_default() > stall_after);
+            // A loop that ended while the scheduler runs died
+            let exited = self
+                .loop_handle
+                .lock()
+                .unwrap()
+                .as_ref()
+                .is_none_or(|handle| handle.is_finished());
+            if !silent && !exited {
+                self.stalled.store(false, Ordering::SeqCst);
+                continue;
+            }
+            // Each stall is reported once, unless the loop is restarted
+            if self.stalled.swap(true, Ordering::SeqCst) && !self.restart {
+                continue;
+            }
+
+            tracing::error!("Cron scheduler loop stalled; last tick at {:?}", last_tick);
+            if self.restart {
+                let handle = self.scheduler_loop.clone().spawn();
+                if let Some(stalled) = self.loop_handle.lock().unwrap().replace(handle) {
+                    stalled.abort();
+                }
+                *self.scheduler_loop.last_tick.lock().unwrap() = Some(Utc::now());
+                self.restarts.fetch_add(1, Ordering::SeqCst);
+                self.stalled.store(false, Ordering::SeqCst);
+                tracing::warn!("Restarted stalled cron scheduler loop");
+            }
+            self.scheduler_loop
+                .runner
+                .emit(SchedulerEvent::SchedulerStalled {
+                    last_tick,
+                    restarted: self.restart,
+                    timestamp: Utc::now(),
+                });
+        }
+    }
+}
+
+/// Message carried by a panic, if it is a string
+fn panic_message(panic: &(dyn std::any::Any + Send)) -> String {
+    panic
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+/// Sleep for `duration`, or forever if there is none
+async fn sleep_for(duration: Option<Duration>) {
+    match duration {
+        Some(duration) => tokio::time::sleep(duration).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Check if a schedule string asks for no scheduled runs
+fn is_never(schedule: &str) -> bool {
+    schedule.trim().eq_ignore_ascii_case(NEVER_SCHEDULE)
+}
+
+/// Find a triggered job by id, then by name
+fn resolve_trigger<'a>(jobs: &'a [CronJob], reference: &str) -> Option<&'a CronJob> {
+    jobs.iter()
+        .find(|job| job.id == reference)
+        .or_else(|| jobs.iter().find(|job| job.name == reference))
+}
+
+/// Whether `job` holds secrets masked by [`CronManager::export_jobs`]
+fn has_masked_secrets(job: &CronJob) -> bool {
+    let redacted = !job.secret_env.is_empty()
+        && (job.command.contains(SECRET_MASK)
+            || job.env.iter().any(|(_, value)| value.contains(SECRET_MASK)));
+    redacted
+        || job
+            .agent_config
+            .as_ref()
+            .is_some_and(|config| config.api_key == SECRET_MASK)
+}
+
+/// Check that every job `job` triggers is among `jobs`, which include `job`,
+/// and that triggering can't loop back to `job`
+fn check_triggers_among(jobs: &[CronJob], job: &CronJob) -> Result<()> {
+    for reference in &job.triggers {
+        if resolve_trigger(jobs, reference).is_none() {
+            return Err(CronError::InvalidConfig(format!(
+                "triggered job not found: {}",
+                reference
+            )));
+        }
+    }
+    match find_trigger_cycle(jobs, job) {
+        Some(cycle) => Err(CronError::TriggerCycle(cycle.join(" -> "))),
+        None => Ok(()),
+    }
+}
+
+/// Find a chain of triggers leading from `start` back to itself, returned as
+/// job names from `start` to `start`
+fn find_trigger_cycle(jobs: &[CronJob], start: &CronJob) -> Option<Vec<String>> {
+    fn visit<'a>(
+        jobs: &'a [CronJob],
+        job: &'a CronJob,
+        start: &CronJob,
+        path: &mut Vec<&'a CronJob>,
+        visited: &mut HashSet<&'a str>,
+    ) -> bool {
+        for reference in &job.triggers {
+            let Some(next) = resolve_trigger(jobs, reference) else {
+                continue;
+            };
+            path.push(next);
+            if next.id == start.id {
+                return true;
+            }
+            if visited.insert(&next.id) && visit(jobs, next, start, path, visited) {
+                return true;
+            }
+            path.pop();
+        }
+        false
+    }
+
+    let mut path = vec![start];
+    let mut visited = HashSet::new();
+    visit(jobs, start, start, &mut path, &mut visited)
+        .then(|| path.iter().map(|job| job.name.clone()).collect())
+}
+
+/// Reject job types missing the settings they need, or given settings they
+/// can't use
+fn check_job_type(options: &JobOptions) -> Result<()> {
+    match (options.job_type, &options.agent_config) {
+        (JobType::Agent, None) => {
+            return Err(CronError::InvalidConfig(
+                "agent jobs need an agent configuration".to_string(),
+            ))
+        }
+        (JobType::Shell | JobType::Native, Some(_)) => {
+            return Err(CronError::InvalidConfig(format!(
+                "{} jobs have no agent configuration",
+                options.job_type
+            )))
+        }
+        _ => {}
+    }
+    if let Some((program, _)) = &options.exec {
+        if options.job_type != JobType::Shell {
+            return Err(CronError::InvalidConfig(format!(
+                "{} jobs can't spawn a program",
+                options.job_type
+            )));
+        }
+        if program.trim().is_empty() {
+            return Err(CronError::InvalidConfig(
+                "program must not be empty".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Warn that resource limits set on a job won't be enforced on this platform
+fn warn_unenforced_limits(job_name: &str, limits: &ResourceLimits) {
+    if cfg!(not(unix)) && !limits.is_empty() {
+        tracing::warn!(
+            "Resource limits of cron job {} are ignored on this platform",
+            job_name
+        );
+    }
+}
+
+/// Reject allowed windows that cross midnight; a job can only be confined
+/// to a range within the day
+fn check_allowed_window(window: &TimeWindow) -> Result<()> {
+    if window.crosses_midnight() {
+        return Err(CronError::InvalidConfig(format!(
+            "allowed window {} is inverted: start must be before end",
+            window
+        )));
+    }
+    Ok(())
+}
+
+/// Reject tags that are empty, too long or contain whitespace
+fn check_tags(tags: &[String]) -> Result<()> {
+    for tag in tags {
+        if tag.is_empty() || tag.len() > MAX_TAG_LEN || tag.chars().any(char::is_whitespace) {
+            return Err(CronError::InvalidConfig(format!(
+                "invalid tag {:?}: tags must be 1 to {} bytes without whitespace",
+                tag, MAX_TAG_LEN
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Reject date windows that end before they start
+fn check_window(not_before: Option<DateTime<Utc>>, not_after: Option<DateTime<Utc>>) -> Result<()> {
+    match (not_before, not_after) {
+        (Some(start), Some(end)) if start > end => Err(CronError::InvalidConfig(
+            "not_before must not be later than not_after".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::store::MemoryCronStore;
+    use crate::types::{
+        ConcurrencyPolicy, ExecutionStatus, JobContext, JobOutput, MisfirePolicy,
+        DEFAULT_TIMEOUT_MS, MIN_ANOMALY_SAMPLES,
+    };
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn create_test_manager() -> CronManager {
+        let store = Arc::new(MemoryCronStore::new());
+        CronManager::with_store(store, "/tmp".to_string())
+    }
+
+    #[tokio::test]
+    async fn test_add_job() {
+        let manager = create_test_manager();
+
+        let job = manager
+            .add_job("test-job", "*/5 * * * *", "echo hello")
+            .await
+            .unwrap();
+
+        assert_eq!(job.name, "test-job");
+        assert_eq!(job.schedule, "*/5 * * * *");
+        assert_eq!(job.command, "echo hello");
+        assert!(job.next_run.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_add_duplicate_name() {
+        let manager = create_test_manager();
+
+        manager
+            .add_job("unique", "* * * * *", "echo 1")
+            .await
+            .unwrap();
+
+        let result = manager.add_job("unique", "* * * * *", "echo 2").await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_add_invalid_schedule() {
+        let manager = create_test_manager();
+
+        let result = manager.add_job("bad", "invalid", "echo").await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_job() {
+        let manager = create_test_manager();
+
+        let job = manager
+            .add_job("findme", "* * * * *", "echo")
+            .await
+            .unwrap();
+
+        let found = manager.get_job(&job.id).await.unwrap();
+        assert!(found.is_some());
+        assert_eq!(found.unwrap().name, "findme");
+    }
+
+    #[tokio::test]
+    async fn test_list_jobs() {
+        let manager = create_test_manager();
+
+        for i in 1..=3 {
+            manager
+                .add_job(&format!("job-{}", i), "* * * * *", "echo")
+                .await
+                .unwrap();
+        }
+
+        let jobs = manager.list_jobs().await.unwrap();
+        assert_eq!(jobs.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_update_job() {
+        let manager = create_test_manager();
+
+        let job = manager
+            .add_job("updatable", "* * * * *", "echo v1")
+            .await
+            .unwrap();
+
+        let updated = manager
+            .update_job(&job.id, Some("0 * * * *"), Some("echo v2"), Some(30000))
+            .await
+            .unwrap();
+
+        assert_eq!(updated.schedule, "0 * * * *");
+        assert_eq!(updated.command, "echo v2");
+        assert_eq!(updated.timeout_ms, Some(30000));
+    }
+
+    #[tokio::test]
+    async fn test_add_job_with_full_options() {
+        let manager = create_test_manager();
+        let mut rx = manager.subscribe();
+        let job = manager
+            .add_job_with_options(
+                "configured",
+                "0 2 * * *",
+                "echo $TARGET; pwd",
+                JobOptions::new()
+                    .with_env("TARGET", "prod")
+                    .with_working_dir("/")
+                    .with_timeout_ms(5_000)
+                    .with_retries(2, 10, 2.0)
+                    .with_max_runs(3, false)
+                    .with_concurrency_policy(ConcurrencyPolicy::Forbid),
+            )
+            .await
+            .unwrap();
+
+        let stored = manager.store.load_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(stored.env, vec![("TARGET".to_string(), "prod".to_string())]);
+        assert_eq!(stored.working_dir.as_deref(), Some("/"));
+        assert_eq!(stored.timeout_ms, Some(5_000));
+        assert_eq!(stored.max_retries, 2);
+        assert_eq!(stored.max_runs, Some(3));
+        assert!(!stored.count_manual);
+        assert_eq!(stored.concurrency_policy, ConcurrencyPolicy::Forbid);
+
+        // Added in one step, without intermediate updates
+        assert!(matches!(
+            rx.recv().await.unwrap().kind,
+            SchedulerEvent::JobAdded { .. }
+        ));
+        assert!(rx.try_recv().is_err());
+
+        let execution = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(execution.stdout, "prod\n/\n");
+
+        // Validated before anything is saved
+        for options in [
+            JobOptions::new().with_job_type(JobType::Agent),
+            JobOptions::new()
+                .with_agent_config(create_agent_config())
+                .with_exec("ls", vec![]),
+            JobOptions::new().with_exec(" ", vec![]),
+        ] {
+            assert!(matches!(
+                manager
+                    .add_job_with_options("invalid", "0 2 * * *", "true", options)
+                    .await,
+                Err(CronError::InvalidConfig(_))
+            ));
+        }
+        assert!(manager.get_job_by_name("invalid").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_rename_job() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = CronManager::new(dir.path()).await.unwrap();
+        let job = manager
+            .add_job("nightly", "0 2 * * *", "echo run")
+            .await
+            .unwrap();
+        let upstream = manager
+            .add_job_with_options(
+                "upstream",
+                "0 1 * * *",
+                "true",
+                JobOptions::new()
+                    .with_trigger("nightly")
+                    .with_on_failure("nightly"),
+            )
+            .await
+            .unwrap();
+        manager.add_job("taken", "0 3 * * *", "true").await.unwrap();
+        manager.run_job(&job.id).await.unwrap();
+
+        assert!(matches!(
+            manager.rename_job(&job.id, "taken").await,
+            Err(CronError::JobExists(_))
+        ));
+        assert!(matches!(
+            manager.rename_job(&job.id, " ").await,
+            Err(CronError::InvalidConfig(_))
+        ));
+        assert!(matches!(
+            manager.rename_job("missing", "other").await,
+            Err(CronError::JobNotFound(_))
+        ));
+
+        let mut rx = manager.subscribe();
+        let renamed = manager.rename_job(&job.id, "nightly-backup").await.unwrap();
+        assert_eq!(renamed.id, job.id);
+        assert!(renamed.updated_at > job.updated_at);
+        match rx.recv().await.unwrap().kind {
+            SchedulerEvent::JobUpdated {
+                job_id,
+                changed_fields,
+                ..
+            } => {
+                assert_eq!(job_id, job.id);
+                assert_eq!(changed_fields, vec!["name"]);
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+
+        assert!(manager.get_job_by_name("nightly").await.unwrap().is_none());
+        let found = manager
+            .get_job_by_name("nightly-backup")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.id, job.id);
+        assert_eq!(found.run_count, 1);
+        assert_eq!(manager.get_history(&job.id, 10).await.unwrap().len(), 1);
+
+        let upstream = manager.get_job(&upstream.id).await.unwrap().unwrap();
+        assert_eq!(upstream.triggers, vec!["nightly-backup"]);
+        assert_eq!(upstream.on_failure.as_deref(), Some("nightly-backup"));
+    }
+
+    #[tokio::test]
+    async fn test_clone_job() {
+        let manager = create_test_manager();
+        let source = manager
+            .add_job_with_options(
+                "deploy-prod",
+                "0 3 * * *",
+                "echo $TARGET",
+                JobOptions::new()
+                    .with_env("TARGET", "prod")
+                    .with_timeout_ms(5_000)
+                    .with_notify_on_change(false),
+            )
+            .await
+            .unwrap();
+        manager.run_job(&source.id).await.unwrap();
+        let ran = manager.get_job(&source.id).await.unwrap().unwrap();
+        assert!(ran.last_output_hash.is_some());
+        assert_eq!(ran.duration_stats.count, 1);
+
+        assert!(matches!(
+            manager.clone_job(&source.id, "deploy-prod", None).await,
+            Err(CronError::JobExists(_))
+        ));
+
+        let mut rx = manager.subscribe();
+        let overrides = UpdateJobRequest::new()
+            .with_env(vec![("TARGET".to_string(), "staging".to_string())])
+            .with_priority(-1)
+            .with_notify_on_change(false, false);
+        let clone = manager
+            .clone_job(&source.id, "deploy-staging", Some(overrides))
+            .await
+            .unwrap();
+        match rx.recv().await.unwrap().kind {
+            SchedulerEvent::JobAdded { job_id, name, .. } => {
+                assert_eq!(job_id, clone.id);
+                assert_eq!(name, "deploy-staging");
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+        assert_ne!(clone.id, source.id);
+        assert_eq!(clone.schedule, "0 3 * * *");
+        assert_eq!(clone.timeout_ms, Some(5_000));
+        assert_eq!(clone.run_count, 0);
+        assert_eq!(clone.last_run, None);
+        assert_eq!(clone.last_output_hash, None);
+        assert!(clone.duration_stats.is_empty());
+        assert_eq!(clone.priority, -1);
+        assert!(!clone.notify_on_change);
+        assert!(source.notify_on_change);
+        assert!(clone.next_run.is_some());
+        assert!(manager.get_history(&clone.id, 10).await.unwrap().is_empty());
+
+        let execution = manager.run_job(&clone.id).await.unwrap();
+        assert_eq!(execution.stdout, "staging\n");
+        let source = manager.get_job(&source.id).await.unwrap().unwrap();
+        assert_eq!(source.run_count, 1);
+        assert_eq!(source.env, vec![("TARGET".to_string(), "prod".to_string())]);
+
+        // Agent jobs keep their own copy of the configuration
+        let agent = manager
+            .add_agent_job("review", "0 9 * * *", "Review PRs", create_agent_config())
+            .await
+            .unwrap();
+        let mut config = create_agent_config();
+        config.model = "other-model".to_string();
+        let copy = manager
+            .clone_job(
+                &agent.id,
+                "review-copy",
+                Some(UpdateJobRequest::new().with_agent_config(config)),
+            )
+            .await
+            .unwrap();
+        assert_eq!(copy.job_type, JobType::Agent);
+        assert_eq!(copy.agent_config.unwrap().model, "other-model");
+        let agent = manager.get_job(&agent.id).await.unwrap().unwrap();
+        assert_eq!(agent.agent_config.unwrap().model, "test-model");
+    }
+
+    #[tokio::test]
+    async fn test_update_job_with_request() {
+        let manager = create_test_manager();
+        let job = manager
+            .add_job("original", "* * * * *", "echo v1")
+            .await
+            .unwrap();
+        manager.add_job("taken", "* * * * *", "true").await.unwrap();
+
+        assert!(matches!(
+            manager
+                .update_job_with_request(&job.id, UpdateJobRequest::new().with_name("taken"))
+                .await,
+            Err(CronError::JobExists(_))
+        ));
+        // Invalid requests change nothing
+        assert!(matches!(
+            manager
+                .update_job_with_request(
+                    &job.id,
+                    UpdateJobRequest::new()
+                        .with_name("renamed")
+                        .with_agent_config(create_agent_config()),
+                )
+                .await,
+            Err(CronError::InvalidConfig(_))
+        ));
+        assert_eq!(
+            manager.get_job(&job.id).await.unwrap().unwrap().name,
+            "original"
+        );
+
+        let mut rx = manager.subscribe();
+        let updated = manager
+            .update_job_with_request(
+                &job.id,
+                UpdateJobRequest::new()
+                    .with_name("renamed")
+                    .with_env(vec![("GREETING".to_string(), "hi".to_string())])
+                    .with_working_dir(Some("/".to_string()))
+                    .with_concurrency_policy(ConcurrencyPolicy::Forbid),
+            )
+            .await
+            .unwrap();
+        assert_eq!(updated.id, job.id);
+        assert_eq!(updated.name, "renamed");
+        assert_eq!(updated.working_dir.as_deref(), Some("/"));
+        assert_eq!(updated.concurrency_policy, ConcurrencyPolicy::Forbid);
+        match rx.recv().await.unwrap().kind {
+            SchedulerEvent::JobUpdated { changed_fields, .. } => assert_eq!(
+                changed_fields,
+                vec!["name", "env", "working_dir", "concurrency_policy"]
+            ),
+            other => panic!("unexpected event: {:?}", other),
+        }
+
+        // Options change together, or not at all
+        let now = Utc::now();
+        let blackout = TimeWindow::parse("01:00-02:00").unwrap();
+        assert!(matches!(
+            manager
+                .update_job_with_request(
+                    &job.id,
+                    UpdateJobRequest::new()
+                        .with_priority(5)
+                        .with_window(Some(now), Some(now - chrono::Duration::days(1))),
+                )
+                .await,
+            Err(CronError::InvalidConfig(_))
+        ));
+        assert!(matches!(
+            manager
+                .update_job_with_request(
+                    &job.id,
+                    UpdateJobRequest::new()
+                        .with_priority(5)
+                        .with_triggers(vec!["missing".to_string()]),
+                )
+                .await,
+            Err(CronError::InvalidConfig(_))
+        ));
+        assert_eq!(manager.get_job(&job.id).await.unwrap().unwrap().priority, 0);
+        let updated = manager
+            .update_job_with_request(
+                &job.id,
+                UpdateJobRequest::new()
+                    .with_priority(5)
+                    .with_blackouts(vec![blackout.clone()], BlackoutPolicy::Defer)
+                    .with_triggers(vec!["taken".to_string()])
+                    .with_min_interval(Some(1_000)),
+            )
+            .await
+            .unwrap();
+        assert_eq!(updated.priority, 5);
+        assert_eq!(updated.blackouts, vec![blackout]);
+        assert_eq!(updated.blackout_policy, BlackoutPolicy::Defer);
+        assert_eq!(updated.triggers, vec!["taken".to_string()]);
+        assert_eq!(updated.min_interval_ms, Some(1_000));
+        match rx.recv().await.unwrap().kind {
+            SchedulerEvent::JobUpdated { changed_fields, .. } => assert_eq!(
+                changed_fields,
+                vec![
+                    "blackouts",
+                    "blackout_policy",
+                    "priority",
+                    "min_interval_ms",
+                    "triggers"
+                ]
+            ),
+            other => panic!("unexpected event: {:?}", other),
+        }
+        manager
+            .update_job_with_request(
+                &job.id,
+                UpdateJobRequest::new()
+                    .with_blackouts(Vec::new(), BlackoutPolicy::Skip)
+                    .with_triggers(Vec::new())
+                    .with_min_interval(None),
+            )
+            .await
+            .unwrap();
+
+        manager
+            .update_job_with_request(
+                &job.id,
+                UpdateJobRequest::new().with_command("echo $GREETING; pwd"),
+            )
+            .await
+            .unwrap();
+        let execution = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(execution.stdout, "hi\n/\n");
+        assert!(manager.get_job_by_name("original").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_pause_resume() {
+        let manager = create_test_manager();
+
+        let job = manager
+            .add_job("pausable", "* * * * *", "echo")
+            .await
+            .unwrap();
+
+        // Pause
+        let paused = manager.pause_job(&job.id).await.unwrap();
+        assert_eq!(paused.status, JobStatus::Paused);
+
+        // Resume
+        let resumed = manager.resume_job(&job.id).await.unwrap();
+        assert_eq!(resumed.status, JobStatus::Active);
+    }
+
+    #[tokio::test]
+    async fn test_remove_job() {
+        let manager = create_test_manager();
+
+        let job = manager
+            .add_job("removable", "* * * * *", "echo")
+            .await
+            .unwrap();
+
+        manager.remove_job(&job.id, true).await.unwrap();
+
+        let found = manager.get_job(&job.id).await.unwrap();
+        assert!(found.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_get_execution() {
+        let manager = create_test_manager();
+        let job = manager
+            .add_job("lookup", "* * * * *", "echo hi")
+            .await
+            .unwrap();
+        let mut events = manager.subscribe();
+        manager.run_job(&job.id).await.unwrap();
+
+        let execution_id = loop {
+            if let SchedulerEvent::JobCompleted { execution_id, .. } =
+                events.recv().await.unwrap().kind
+            {
+                break execution_id;
+            }
+        };
+        let execution = manager.get_execution(&execution_id).await.unwrap().unwrap();
+        assert_eq!(execution.job_id, job.id);
+        assert_eq!(execution.stdout.trim(), "hi");
+        assert!(manager.get_execution("missing").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_query_history() {
+        let manager = create_test_manager();
+        let job = manager.add_job("mixed", "* * * * *", "true").await.unwrap();
+        manager.run_job(&job.id).await.unwrap();
+        manager
+            .update_job(&job.id, None, Some("false"), None)
+            .await
+            .unwrap();
+        let failed = manager.run_job(&job.id).await.unwrap();
+
+        let filter = ExecutionFilter::new().with_status(ExecutionStatus::Failed);
+        let history = manager.query_history(&job.id, &filter).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].id, failed.id);
+
+        let filter = ExecutionFilter::new().with_trigger(TriggerKind::Manual);
+        let history = manager.query_history(&job.id, &filter).await.unwrap();
+        assert_eq!(history.len(), 2);
+        let filter = ExecutionFilter::new().with_trigger(TriggerKind::Scheduled);
+        let history = manager.query_history(&job.id, &filter).await.unwrap();
+        assert!(history.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_job_stats() {
+        let manager = create_test_manager();
+        let job = manager.add_job("stats", "* * * * *", "true").await.unwrap();
+        manager.run_job(&job.id).await.unwrap();
+        manager
+            .update_job(&job.id, None, Some("exit 3"), None)
+            .await
+            .unwrap();
+        let failed = manager.run_job(&job.id).await.unwrap();
+
+        let stats = manager.job_stats(&job.id).await.unwrap();
+        assert_eq!(stats.total_runs, 2);
+        assert_eq!(stats.sampled, 2);
+        assert_eq!(stats.success_rate, 0.5);
+        assert_eq!(stats.consecutive_failures, 1);
+        assert!(stats.avg_duration_ms.is_some());
+        assert_eq!(stats.last_failure, Some(failed.started_at));
+        assert_eq!(stats.last_error.as_deref(), Some("exit code 3"));
+
+        // The last error is cached on the job itself
+        let listed = manager.list_jobs().await.unwrap();
+        assert_eq!(listed[0].last_error.as_deref(), Some("exit code 3"));
+        assert!(matches!(
+            manager.job_stats("missing").await,
+            Err(CronError::JobNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_job_health() {
+        let store = Arc::new(MemoryCronStore::new());
+        let manager = CronManager::with_store(store.clone(), "/tmp".to_string())
+            .with_health_thresholds(HealthThresholds::default().with_failing_after(2));
+
+        // Save executions with these statuses, most recent first
+        let add = |name: &'static str, statuses: Vec<ExecutionStatus>| {
+            let manager = &manager;
+            let store = store.clone();
+            async move {
+                let job = manager.add_job(name, "* * * * *", "true").await.unwrap();
+                for (age, status) in statuses.into_iter().enumerate() {
+                    let mut execution = JobExecution::new(&job.id);
+                    execution.status = status;
+                    execution.started_at = Utc::now() - chrono::Duration::minutes(age as i64);
+                    store.save_execution(&execution).await.unwrap();
+                }
+                job
+            }
+        };
+        use ExecutionStatus::{Failed, Skipped, Success, Timeout};
+        let healthy = add("healthy", vec![Success, Skipped, Success]).await;
+        let degraded = add("degraded", vec![Success, Failed, Success]).await;
+        let failing = add("failing", vec![Timeout, Skipped, Failed, Success]).await;
+        let stale = add("stale", vec![Success]).await;
+        let mut overdue = stale.clone();
+        overdue.next_run = Some(Utc::now() - chrono::Duration::minutes(10));
+        store.save_job(&overdue).await.unwrap();
+
+        for (job, health) in [
+            (&healthy, JobHealth::Healthy),
+            (&degraded, JobHealth::Degraded),
+            (&failing, JobHealth::Failing),
+            (&stale, JobHealth::Stale),
+        ] {
+            assert_eq!(manager.job_health(&job.id).await.unwrap(), health);
+        }
+
+        // Plain listings don't read every job's history
+        let listed = manager.list_jobs().await.unwrap();
+        assert!(listed.iter().all(|job| job.health.is_none()));
+        let listed = manager
+            .list_jobs_with_health(&JobFilter::default())
+            .await
+            .unwrap();
+        assert!(listed.iter().all(|job| job.health.is_some()));
+        let mut unhealthy: Vec<String> = manager
+            .list_unhealthy_jobs()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|job| format!("{}={}", job.name, job.health.unwrap()))
+            .collect();
+        unhealthy.sort();
+        assert_eq!(
+            unhealthy,
+            ["degraded=degraded", "failing=failing", "stale=stale"]
+        );
+
+        // Health is never stored
+        let stored = store.load_job(&healthy.id).await.unwrap().unwrap();
+        assert!(stored.health.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_job_running_long() {
+        let manager = create_test_manager();
+        let slow = manager
+            .add_job_with_options(
+                "slow",
+                "* * * * *",
+                "sleep 1",
+                JobOptions::default().with_expected_duration_ms(200),
+            )
+            .await
+            .unwrap();
+        let fast = manager.add_job("fast", "* * * * *", "true").await.unwrap();
+        manager
+            .set_job_expected_duration(&fast.id, Some(5000))
+            .await
+            .unwrap();
+        let mut events = manager.subscribe();
+
+        let execution = manager.run_job(&slow.id).await.unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Success);
+        manager.run_job(&fast.id).await.unwrap();
+
+        let mut reports = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            if let SchedulerEvent::JobRunningLong {
+                job_id,
+                execution_id,
+                elapsed_ms,
+                ..
+            } = event.kind
+            {
+                reports.push((job_id, execution_id, elapsed_ms));
+            }
+        }
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].0, slow.id);
+        assert_eq!(reports[0].1, execution.id);
+        assert!(reports[0].2 >= 200);
+    }
+
+    #[tokio::test]
+    async fn test_job_duration_anomaly() {
+        let manager = create_test_manager();
+        let job = manager
+            .add_job("steady", "0 0 1 1 *", "true")
+            .await
+            .unwrap();
+        let young = manager.add_job("young", "0 0 1 1 *", "true").await.unwrap();
+        let slow = || RunOptions::new().with_command("sleep 0.5");
+        for _ in 0..MIN_ANOMALY_SAMPLES {
+            manager.run_job(&job.id).await.unwrap();
+        }
+        manager.run_job(&young.id).await.unwrap();
+        let mut events = manager.subscribe();
+
+        let execution = manager.run_job_with(&job.id, slow()).await.unwrap();
+        manager.run_job_with(&young.id, slow()).await.unwrap();
+
+        let mut anomalies = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            if let SchedulerEvent::JobDurationAnomaly {
+                job_id,
+                execution_id,
+                duration_ms,
+                baseline_ms,
+                ..
+            } = event.kind
+            {
+                anomalies.push((job_id, execution_id, duration_ms, baseline_ms));
+            }
+        }
+        // The young job has too few runs to judge
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].0, job.id);
+        assert_eq!(anomalies[0].1, execution.id);
+        assert!(anomalies[0].2 >= 500);
+        assert!(anomalies[0].3 < 500);
+
+        // The slow run joins the stats kept on the job
+        let stats = manager
+            .get_job(&job.id)
+            .await
+            .unwrap()
+            .unwrap()
+            .duration_stats;
+        assert_eq!(stats.count, MIN_ANOMALY_SAMPLES + 1);
+    }
+
+    #[tokio::test]
+    async fn test_list_jobs_by_tags() {
+        let manager = create_test_manager();
+        let add = |name: &'static str, tags: &'static [&'static str]| {
+            let options = tags
+                .iter()
+                .fold(JobOptions::default(), |options, tag| options.with_tag(*tag));
+            manager.add_job_with_options(name, "* * * * *", "echo", options)
+        };
+        add("payments-prod", &["team:payments", "env:prod"])
+            .await
+            .unwrap();
+        let staging = add("payments-staging", &["team:payments", "env:staging"])
+            .await
+            .unwrap();
+        add("search-prod", &["team:search", "env:prod"])
+            .await
+            .unwrap();
+        add("untagged", &[]).await.unwrap();
+        manager.pause_job(&staging.id).await.unwrap();
+
+        let names = |filter: JobFilter| {
+            let manager = &manager;
+            async move {
+                let mut names: Vec<String> = manager
+                    .list_jobs_filtered(&filter)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|job| job.name)
+                    .collect();
+                names.sort();
+                names
+            }
+        };
+        let tags = |tags: &[&str]| tags.iter().map(|tag| tag.to_string()).collect();
+
+        assert_eq!(
+            names(JobFilter::new().with_tags_any(tags(&["team:payments"]))).await,
+            ["payments-prod", "payments-staging"]
+        );
+        assert_eq!(
+            names(JobFilter::new().with_tags_all(tags(&["team:payments", "env:prod"]))).await,
+            ["payments-prod"]
+        );
+        assert_eq!(
+            names(JobFilter::new().with_tags_any(tags(&["env:prod", "env:staging"]))).await,
+            ["payments-prod", "payments-staging", "search-prod"]
+        );
+        assert_eq!(
+            names(
+                JobFilter::new()
+                    .with_tags_any(tags(&["team:payments"]))
+                    .with_status(JobStatus::Active)
+            )
+            .await,
+            ["payments-prod"]
+        );
+        assert_eq!(
+            names(
+                JobFilter::new()
+                    .with_name_contains("prod")
+                    .with_job_type(JobType::Shell)
+            )
+            .await,
+            ["payments-prod", "search-prod"]
+        );
+        assert!(names(JobFilter::new().with_job_type(JobType::Agent))
+            .await
+            .is_empty());
+
+        // Tags are replaced by updates, and validated
+        let request = UpdateJobRequest::new().with_tags(tags(&["team:search"]));
+        manager
+            .update_job_with_request(&staging.id, request)
+            .await
+            .unwrap();
+        assert_eq!(
+            names(JobFilter::new().with_tags_any(tags(&["team:search"]))).await,
+            ["payments-staging", "search-prod"]
+        );
+        for bad in ["", "has space", &"x".repeat(MAX_TAG_LEN + 1)] {
+            let request = UpdateJobRequest::new().with_tags(vec![bad.to_string()]);
+            assert!(matches!(
+                manager.update_job_with_request(&staging.id, request).await,
+                Err(CronError::InvalidConfig(_))
+            ));
+            let options = JobOptions::default().with_tag(bad);
+            assert!(matches!(
+                manager
+                    .add_job_with_options("bad", "* * * * *", "echo", options)
+                    .await,
+                Err(CronError::InvalidConfig(_))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bulk_operations() {
+        let manager = create_test_manager();
+        let mut ids = Vec::new();
+        for (name, env) in [("a", "env:prod"), ("b", "env:prod"), ("c", "env:dev")] {
+            let options = JobOptions::default().with_tag(env);
+            let job = manager
+                .add_job_with_options(name, "* * * * *", "echo", options)
+                .await
+                .unwrap();
+            ids.push(job.id);
+        }
+        let prod = JobFilter::new().with_tags_any(vec!["env:prod".to_string()]);
+        let mut events = manager.subscribe();
+
+        let paused = manager.pause_jobs(&prod).await.unwrap();
+        assert!(paused.is_success());
+        assert_eq!(paused.succeeded.len(), 2);
+        let dev = manager.get_job(&ids[2]).await.unwrap().unwrap();
+        assert_eq!(dev.status, JobStatus::Active);
+
+        // Jobs already paused are left alone
+        let again = manager.pause_jobs(&prod).await.unwrap();
+        assert!(again.succeeded.is_empty());
+
+        let resumed = manager.resume_jobs(&prod).await.unwrap();
+        assert_eq!(resumed.succeeded.len(), 2);
+
+        let mut paused_events = 0;
+        let mut resumed_events = 0;
+        while let Ok(event) = events.try_recv() {
+            match event.kind {
+                SchedulerEvent::JobPaused { .. } => paused_events += 1,
+                SchedulerEvent::JobResumed { .. } => resumed_events += 1,
+                _ => {}
+            }
+        }
+        assert_eq!((paused_events, resumed_events), (2, 2));
+
+        assert!(matches!(
+            manager.remove_jobs(&JobFilter::new(), true).await,
+            Err(CronError::InvalidConfig(_))
+        ));
+        assert!(matches!(
+            manager
+                .remove_jobs(&JobFilter::new().excluding_disabled(), true)
+                .await,
+            Err(CronError::InvalidConfig(_))
+        ));
+        let removed = manager.remove_jobs(&prod, true).await.unwrap();
+        assert_eq!(removed.succeeded.len(), 2);
+        let remaining = manager.list_jobs().await.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id, ids[2]);
+    }
+
+    #[tokio::test]
+    async fn test_export_import_jobs() {
+        let manager = create_test_manager();
+        let options = JobOptions::default()
+            .with_tag("env:prod")
+            .with_env("TOKEN", "hunter2")
+            .with_secret_env("TOKEN");
+        let backup = manager
+            .add_job_with_options("backup", "0 2 * * *", "backup.sh", options)
+            .await
+            .unwrap();
+        let report = manager
+            .add_job("report", "@never", "report.sh")
+            .await
+            .unwrap();
+        manager.pause_job(&report.id).await.unwrap();
+        manager
+            .add_agent_job("agent", "0 * * * *", "summarize", create_agent_config())
+            .await
+            .unwrap();
+        manager.run_job(&backup.id).await.unwrap();
+
+        let json = manager.export_jobs().await.unwrap();
+        assert!(!json.contains("hunter2"));
+        assert!(!json.contains("test-key"));
+        let exported: JobsExport = serde_json::from_str(&json).unwrap();
+        assert_eq!(exported.version, EXPORT_VERSION);
+        assert_eq!(exported.jobs.len(), 3);
+
+        // Wipe, then import with the original IDs. Masked secrets are only
+        // imported when allowed.
+        for job in manager.list_jobs().await.unwrap() {
+            manager.remove_job(&job.id, true).await.unwrap();
+        }
+        assert!(matches!(
+            manager
+                .import_jobs(&json, ImportMode::Fail, true, false)
+                .await,
+            Err(CronError::InvalidConfig(_))
+        ));
+        assert!(manager.list_jobs().await.unwrap().is_empty());
+        let report = manager
+            .import_jobs(&json, ImportMode::Fail, true, true)
+            .await
+            .unwrap();
+        assert_eq!(report.created, ["agent", "backup", "report"]);
+
+        let mut imported = manager.list_jobs().await.unwrap();
+        imported.sort_by(|a, b| a.name.cmp(&b.name));
+        for (job, original) in imported.iter().zip(&exported.jobs) {
+            assert_eq!(job.id, original.id);
+            assert_eq!(job.name, original.name);
+            assert_eq!(job.schedule, original.schedule);
+            assert_eq!(job.command, original.command);
+            assert_eq!(job.tags, original.tags);
+            assert_eq!(job.status, original.status);
+            assert_eq!(job.run_count, 0);
+            assert!(job.duration_stats.is_empty());
+        }
+        assert!(imported[1].next_run.is_some());
+        assert_eq!(imported[2].status, JobStatus::Paused);
+
+        // Name conflicts follow the mode
+        assert!(matches!(
+            manager
+                .import_jobs(&json, ImportMode::Fail, false, true)
+                .await,
+            Err(CronError::JobExists(_))
+        ));
+        let report = manager
+            .import_jobs(&json, ImportMode::Skip, false, true)
+            .await
+            .unwrap();
+        assert_eq!(report.skipped.len(), 3);
+        let report = manager
+            .import_jobs(&json, ImportMode::Overwrite, false, true)
+            .await
+            .unwrap();
+        assert_eq!(report.overwritten.len(), 3);
+        assert_eq!(manager.list_jobs().await.unwrap().len(), 3);
+        let job = manager.get_job(&backup.id).await.unwrap().unwrap();
+        assert_eq!(job.name, "backup");
+    }
+
+    #[tokio::test]
+    async fn test_import_jobs_is_all_or_nothing() {
+        let manager = create_test_manager();
+        let mut export = JobsExport {
+            version: EXPORT_VERSION,
+            exported_at: Utc::now(),
+            jobs: vec![
+                CronJob::new("good", "* * * * *", "echo"),
+                CronJob::new("bad", "not a schedule", "echo"),
+            ],
+        };
+        let json = serde_json::to_string(&export).unwrap();
+        assert!(manager
+            .import_jobs(&json, ImportMode::Fail, false, false)
+            .await
+            .is_err());
+        assert!(manager.list_jobs().await.unwrap().is_empty());
+
+        export.version = EXPORT_VERSION + 1;
+        export.jobs.truncate(1);
+        let json = serde_json::to_string(&export).unwrap();
+        assert!(matches!(
+            manager
+                .import_jobs(&json, ImportMode::Fail, false, false)
+                .await,
+            Err(CronError::InvalidConfig(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_import_jobs_remaps_triggers() {
+        let manager = create_test_manager();
+        let downstream = manager
+            .add_job("downstream", "@never", "echo")
+            .await
+            .unwrap();
+        let options = JobOptions::new().with_trigger(downstream.id.clone());
+        manager
+            .add_job_with_options("upstream", "0 2 * * *", "echo", options)
+            .await
+            .unwrap();
+        let json = manager.export_jobs().await.unwrap();
+
+        // New IDs: the trigger follows the downstream job
+        let target = create_test_manager();
+        target
+            .import_jobs(&json, ImportMode::Fail, false, false)
+            .await
+            .unwrap();
+        let imported = target.get_job_by_name("downstream").await.unwrap().unwrap();
+        let upstream = target.get_job_by_name("upstream").await.unwrap().unwrap();
+        assert_ne!(imported.id, downstream.id);
+        assert_eq!(upstream.triggers, vec![imported.id]);
+
+        // A trigger pointing nowhere fails the import before anything is
+        // written
+        let mut export: JobsExport = serde_json::from_str(&json).unwrap();
+        export.jobs.retain(|job| job.name == "upstream");
+        let json = serde_json::to_string(&export).unwrap();
+        let target = create_test_manager();
+        assert!(matches!(
+            target
+                .import_jobs(&json, ImportMode::Fail, false, false)
+                .await,
+            Err(CronError::InvalidConfig(_))
+        ));
+        assert!(target.list_jobs().await.unwrap().is_empty());
+    }
+
+    fn test_manifest() -> JobManifest {
+        JobManifest {
+            jobs: vec![
+                ManifestJob::new("backup", "0 2 * * *", "backup.sh")
+                    .with_env("TARGET", "s3")
+                    .with_tag("nightly"),
+                ManifestJob::new("hook", "@never", "notify.sh").with_timeout_ms(5_000),
+            ],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_apply_manifest_is_idempotent() {
+        let manager = create_test_manager();
+        let manifest = test_manifest();
+
+        let plan = manager
+            .apply_manifest(&manifest, false, false)
+            .await
+            .unwrap();
+        assert_eq!(plan.created, vec!["backup", "hook"]);
+        assert!(plan.updated.is_empty() && plan.unchanged.is_empty());
+        let backup = manager
+            .store
+            .find_job_by_name("backup")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(backup.env, vec![("TARGET".to_string(), "s3".to_string())]);
+        assert!(backup.has_tag("nightly"));
+        let hook = manager
+            .store
+            .find_job_by_name("hook")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(hook.schedule_kind, ScheduleKind::Never);
+        assert_eq!(hook.timeout_ms, Some(5_000));
+
+        let plan = manager
+            .apply_manifest(&manifest, true, false)
+            .await
+            .unwrap();
+        assert!(plan.is_noop());
+        assert_eq!(plan.unchanged, vec!["backup", "hook"]);
+        let again = manager
+            .store
+            .find_job_by_name("backup")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(again.updated_at, backup.updated_at);
+    }
+
+    #[tokio::test]
+    async fn test_apply_manifest_schedule_case() {
+        let manager = create_test_manager();
+        let manifest = JobManifest {
+            jobs: vec![ManifestJob::new("weekly", "0 9 * * mon", "report.sh")],
+        };
+        manager
+            .apply_manifest(&manifest, false, false)
+            .await
+            .unwrap();
+        let job = manager
+            .store
+            .find_job_by_name("weekly")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(job.schedule, "0 9 * * mon");
+        let plan = manager
+            .apply_manifest(&manifest, false, false)
+            .await
+            .unwrap();
+        assert!(plan.is_noop());
+
+        // A job added by hand in another spelling matches too
+        manager.remove_job(&job.id, true).await.unwrap();
+        manager
+            .add_job("weekly", "0  9 * * MON", "report.sh")
+            .await
+            .unwrap();
+        let plan = manager
+            .apply_manifest(&manifest, false, false)
+            .await
+            .unwrap();
+        assert!(plan.is_noop());
+    }
+
+    #[tokio::test]
+    async fn test_apply_manifest_job_options() {
+        let manager = create_test_manager();
+        let blackout = TimeWindow::parse("01:00-02:00").unwrap();
+        // "report" triggers a job declared after it
+        let manifest = JobManifest {
+            jobs: vec![
+                ManifestJob::new("report", "0 3 * * *", "report.sh")
+                    .with_blackout(blackout.clone())
+                    .with_blackout_policy(BlackoutPolicy::Defer)
+                    .with_trigger("publish")
+                    .with_retries(3, 500, 2.0)
+                    .with_priority(10),
+                ManifestJob::new("publish", "@never", "publish.sh"),
+            ],
+        };
+
+        let plan = manager
+            .apply_manifest(&manifest, false, false)
+            .await
+            .unwrap();
+        assert_eq!(plan.created, vec!["report", "publish"]);
+        let report = manager
+            .store
+            .find_job_by_name("report")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(report.blackouts, vec![blackout]);
+        assert_eq!(report.blackout_policy, BlackoutPolicy::Defer);
+        assert_eq!(report.triggers, vec!["publish".to_string()]);
+        assert_eq!(
+            (
+                report.max_retries,
+                report.retry_delay_ms,
+                report.retry_backoff
+            ),
+            (3, 500, 2.0)
+        );
+        assert_eq!(report.priority, 10);
+
+        let plan = manager
+            .apply_manifest(&manifest, false, false)
+            .await
+            .unwrap();
+        assert!(plan.is_noop());
+
+        // Options changed by hand converge back to their declaration
+        manager
+            .update_job_with_request(
+                &report.id,
+                UpdateJobRequest::new()
+                    .with_blackouts(Vec::new(), BlackoutPolicy::Skip)
+                    .with_triggers(Vec::new())
+                    .with_retries(0, 1_000, 1.0),
+            )
+            .await
+            .unwrap();
+        let plan = manager
+            .apply_manifest(&manifest, false, false)
+            .await
+            .unwrap();
+        assert_eq!(plan.updated, vec!["report"]);
+        let converged = manager.get_job(&report.id).await.unwrap().unwrap();
+        assert_eq!(converged.blackouts, report.blackouts);
+        assert_eq!(converged.blackout_policy, report.blackout_policy);
+        assert_eq!(converged.triggers, report.triggers);
+        assert_eq!(converged.max_retries, 3);
+        let plan = manager
+            .apply_manifest(&manifest, false, false)
+            .await
+            .unwrap();
+        assert!(plan.is_noop());
+
+        let json = serde_json::to_string(&manifest.jobs[0]).unwrap();
+        let declared: ManifestJob = serde_json::from_str(&json).unwrap();
+        assert_eq!(declared, manifest.jobs[0]);
+
+        // Options left out of a declaration are reset to their defaults
+        let mut manifest = manifest;
+        manifest.jobs[0] = ManifestJob::new("report", "0 3 * * *", "report.sh");
+        manager
+            .apply_manifest(&manifest, false, false)
+            .await
+            .unwrap();
+        let reset = manager.get_job(&report.id).await.unwrap().unwrap();
+        assert!(reset.blackouts.is_empty() && reset.triggers.is_empty());
+        assert_eq!(reset.max_retries, 0);
+        assert_eq!(reset.priority, 0);
+    }
+
+    #[tokio::test]
+    async fn test_apply_manifest_updates_changed_fields() {
+        let manager = create_test_manager();
+        manager
+            .apply_manifest(&test_manifest(), false, false)
+            .await
+            .unwrap();
+        let before = manager
+            .store
+            .find_job_by_name("backup")
+            .await
+            .unwrap()
+            .unwrap();
+        let mut events = manager.subscribe();
+
+        let mut manifest = test_manifest();
+        manifest.jobs[0] =
+            ManifestJob::new("backup", "30 2 * * *", "backup.sh").with_tag("nightly");
+        let plan = manager
+            .apply_manifest(&manifest, false, false)
+            .await
+            .unwrap();
+        assert_eq!(plan.updated, vec!["backup"]);
+        assert_eq!(plan.unchanged, vec!["hook"]);
+
+        let after = manager
+            .store
+            .find_job_by_name("backup")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(after.id, before.id);
+        assert_eq!(after.schedule, "30 2 * * *");
+        assert!(after.env.is_empty());
+        match events.recv().await.unwrap().kind {
+            SchedulerEvent::JobUpdated { changed_fields, .. } => {
+                assert_eq!(changed_fields, vec!["schedule", "env"]);
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+
+        // A job can't change type through a manifest
+        manifest.jobs[0] = ManifestJob::new("backup", "30 2 * * *", "summarize")
+            .with_agent_config(create_agent_config());
+        assert!(matches!(
+            manager.apply_manifest(&manifest, false, false).await,
+            Err(CronError::InvalidConfig(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_apply_manifest_prune_and_dry_run() {
+        let manager = create_test_manager();
+        manager
+            .add_job("manual", "* * * * *", "echo")
+            .await
+            .unwrap();
+        let manifest = test_manifest();
+
+        let plan = manager.apply_manifest(&manifest, true, true).await.unwrap();
+        assert_eq!(plan.created, vec!["backup", "hook"]);
+        assert_eq!(plan.removed, vec!["manual"]);
+        let names: Vec<String> = manager
+            .list_jobs()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|job| job.name)
+            .collect();
+        assert_eq!(names, vec!["manual"]);
+
+        let plan = manager
+            .apply_manifest(&manifest, false, false)
+            .await
+            .unwrap();
+        assert!(plan.removed.is_empty());
+        assert_eq!(manager.list_jobs().await.unwrap().len(), 3);
+
+        let plan = manager
+            .apply_manifest(&manifest, true, false)
+            .await
+            .unwrap();
+        assert_eq!(plan.removed, vec!["manual"]);
+        assert!(manager
+            .store
+            .find_job_by_name("manual")
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_apply_manifest_validates_before_writing() {
+        let manager = create_test_manager();
+        let mut manifest = test_manifest();
+        manifest
+            .jobs
+            .push(ManifestJob::new("broken", "not a schedule", "echo"));
+        assert!(manager
+            .apply_manifest(&manifest, false, false)
+            .await
+            .is_err());
+        assert!(manager.list_jobs().await.unwrap().is_empty());
+
+        let mut manifest = test_manifest();
+        manifest
+            .jobs
+            .push(ManifestJob::new("backup", "* * * * *", "echo"));
+        assert!(matches!(
+            manager.apply_manifest(&manifest, false, false).await,
+            Err(CronError::InvalidConfig(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_apply_manifest_file() {
+        let manager = create_test_manager();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("jobs.json");
+        std::fs::write(
+            &path,
+            r#"{"jobs": [{"name": "ping", "schedule": "*/5 * * * *", "command": "ping.sh",
+                "env": {"HOST": "example.com"}, "tags": ["net"]}]}"#,
+        )
+        .unwrap();
+
+        let plan = manager
+            .apply_manifest_file(&path, false, false)
+            .await
+            .unwrap();
+        assert_eq!(plan.created, vec!["ping"]);
+        let job = manager
+            .store
+            .find_job_by_name("ping")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(job.timeout_ms, None);
+        assert!(manager
+            .apply_manifest_file(&path, false, false)
+            .await
+            .unwrap()
+            .is_noop());
+    }
+
+    /// Wait for the next `ManifestReloaded` event
+    async fn next_reload(rx: &mut broadcast::Receiver<EventEnvelope>) -> SchedulerEvent {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let event = rx.recv().await.unwrap().kind;
+                if matches!(event, SchedulerEvent::ManifestReloaded { .. }) {
+                    return event;
+                }
+            }
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_reload_manifest() {
+        assert!(matches!(
+            create_test_manager().reload_manifest().await,
+            Err(CronError::InvalidConfig(_))
+        ));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("jobs.json");
+        let manager = create_test_manager().with_manifest(&path, true);
+        assert_eq!(manager.manifest_path(), Some(path.as_path()));
+        manager.add_job("manual", "@never", "echo").await.unwrap();
+        let mut rx = manager.subscribe();
+        std::fs::write(&path, serde_json::to_string(&test_manifest()).unwrap()).unwrap();
+
+        let plan = manager.reload_manifest().await.unwrap();
+        assert_eq!(plan.created, vec!["backup", "hook"]);
+        match next_reload(&mut rx).await {
+            SchedulerEvent::ManifestReloaded {
+                created,
+                updated,
+                removed,
+                error,
+                ..
+            } => assert_eq!((created, updated, removed, error), (2, 0, 1, None)),
+            _ => unreachable!(),
+        }
+
+        // A broken manifest changes nothing
+        std::fs::write(&path, "{").unwrap();
+        assert!(manager.reload_manifest().await.is_err());
+        match next_reload(&mut rx).await {
+            SchedulerEvent::ManifestReloaded { created, error, .. } => {
+                assert_eq!(created, 0);
+                assert!(error.unwrap().contains("Serialization"));
+            }
+            _ => unreachable!(),
+        }
+        assert_eq!(manager.list_jobs().await.unwrap().len(), 2);
+    }
+
+    #[cfg(all(unix, feature = "signals"))]
+    #[tokio::test]
+    async fn test_signal_handlers() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("jobs.json");
+        std::fs::write(&path, serde_json::to_string(&test_manifest()).unwrap()).unwrap();
+        let manager = Arc::new(create_test_manager().with_manifest(&path, false));
+        manager.install_signal_handlers().unwrap();
+        // Installing again does nothing
+        manager.install_signal_handlers().unwrap();
+        manager.start().await.unwrap();
+        let mut rx = manager.subscribe();
+
+        unsafe { libc::kill(libc::getpid(), libc::SIGHUP) };
+        match next_reload(&mut rx).await {
+            SchedulerEvent::ManifestReloaded { created, .. } => assert_eq!(created, 2),
+            _ => unreachable!(),
+        }
+
+        unsafe { libc::kill(libc::getpid(), libc::SIGTERM) };
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !matches!(
+                rx.recv().await.unwrap().kind,
+                SchedulerEvent::Stopped { .. }
+            ) {}
+        })
+        .await
+        .unwrap();
+        assert!(!manager.is_running().await);
+    }
+
+    #[tokio::test]
+    async fn test_export_systemd_unit() {
+        let manager = create_test_manager()
+            .with_default_shell(Some("bash".into()))
+            .unwrap();
+        let job = manager
+            .add_job("nightly", "0 3 * * *", "echo done")
+            .await
+            .unwrap();
+        let unit = manager.export_systemd_unit(&job.id).await.unwrap();
+        assert_eq!(unit.name, "nightly");
+        assert!(unit.timer.contains("OnCalendar=*-*-* 03:00:00 UTC\n"));
+        assert!(unit.service.contains("WorkingDirectory=/tmp\n"));
+        assert!(unit.service.contains("ExecStart=bash -c \"echo done\"\n"));
+
+        let agent = manager
+            .add_agent_job("agent", "0 * * * *", "summarize", create_agent_config())
+            .await
+            .unwrap();
+        assert!(matches!(
+            manager.export_systemd_unit(&agent.id).await,
+            Err(CronError::InvalidConfig(_))
+        ));
+        assert!(matches!(
+            manager.export_systemd_unit("missing").await,
+            Err(CronError::JobNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_export_k8s_cronjob() {
+        let manager = create_test_manager();
+        let job = manager
+            .add_job("Nightly Sync", "0 3 * * *", "sync.sh")
+            .await
+            .unwrap();
+        let yaml = manager
+            .export_k8s_cronjob(&job.id, "sync:1.0", &K8sExportOptions::new())
+            .await
+            .unwrap();
+        assert!(yaml.contains("  name: nightly-sync\n"));
+        assert!(yaml.contains("  schedule: \"0 3 * * *\"\n"));
+        assert!(yaml.contains("            - sync.sh\n"));
+
+        let at = Utc::now() + chrono::Duration::hours(1);
+        let once = manager
+            .add_one_shot_job("once", at, "echo", false)
+            .await
+            .unwrap();
+        assert!(matches!(
+            manager
+                .export_k8s_cronjob(&once.id, "img", &K8sExportOptions::new())
+                .await,
+            Err(CronError::InvalidConfig(_))
+        ));
+        assert!(matches!(
+            manager
+                .export_k8s_cronjob("missing", "img", &K8sExportOptions::new())
+                .await,
+            Err(CronError::JobNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_run_job_with_overrides() {
+        let manager = create_test_manager();
+        let job = manager
+            .add_job_with_options(
+                "greet",
+                "0 0 1 1 *",
+                "echo $GREETING $NAME",
+                JobOptions::new().with_env("GREETING", "hello"),
+            )
+            .await
+            .unwrap();
+
+        let execution = manager.run_job_by_name("greet").await.unwrap();
+        assert_eq!(execution.stdout.trim(), "hello");
+        assert!(execution.overrides.is_empty());
+
+        let options = RunOptions::new()
+            .with_env("GREETING", "hi")
+            .with_env("NAME", "there")
+            .with_timeout(Duration::from_secs(5));
+        let execution = manager.run_job_with(&job.id, options).await.unwrap();
+        assert_eq!(execution.stdout.trim(), "hi there");
+        assert_eq!(
+            execution.overrides,
+            vec!["timeout_ms", "env.GREETING", "env.NAME"]
+        );
+
+        let options = RunOptions::new().with_command("echo replaced");
+        let execution = manager.run_job_with(&job.id, options).await.unwrap();
+        assert_eq!(execution.stdout.trim(), "replaced");
+        let stored = manager.get_execution(&execution.id).await.unwrap().unwrap();
+        assert_eq!(stored.overrides, vec!["command"]);
+
+        // Nothing is persisted onto the job
+        let stored = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(stored.command, "echo $GREETING $NAME");
+        assert_eq!(
+            stored.env,
+            vec![("GREETING".to_string(), "hello".to_string())]
+        );
+        assert_eq!(stored.timeout_ms, job.timeout_ms);
+
+        assert!(matches!(
+            manager.run_job_by_name("missing").await,
+            Err(CronError::JobNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_run_paused_job_needs_force() {
+        let manager = create_test_manager();
+        let job = manager
+            .add_job("paused", "0 0 1 1 *", "true")
+            .await
+            .unwrap();
+        manager.pause_job(&job.id).await.unwrap();
+
+        let err = manager.run_job_by_name("paused").await.unwrap_err();
+        assert!(matches!(err, CronError::JobPaused(_)));
+        assert!(err.to_string().contains("force_if_paused"));
+
+        let execution = manager
+            .run_job_with(&job.id, RunOptions::new().force_if_paused())
+            .await
+            .unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Success);
+        assert!(manager.get_job(&job.id).await.unwrap().unwrap().is_paused());
+    }
+
+    #[tokio::test]
+    async fn test_default_timeout_inherited() {
+        let manager = create_test_manager()
+            .with_default_timeout(Some(Duration::from_millis(200)))
+            .unwrap();
+        assert_eq!(manager.default_timeout(), Some(Duration::from_millis(200)));
+        let job = manager
+            .add_job("slow", "0 0 1 1 *", "sleep 5")
+            .await
+            .unwrap();
+        assert_eq!(job.timeout_ms, None);
+
+        let stored = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(stored.effective_timeout_ms, Some(200));
+        let json = serde_json::to_value(&stored).unwrap();
+        assert_eq!(json["effective_timeout_ms"], 200);
+        assert!(json.get("timeout_ms").is_none());
+
+        let started = std::time::Instant::now();
+        let execution = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Timeout);
+        assert!(started.elapsed() < Duration::from_secs(3));
+
+        // The job's own timeout wins until it is reset to the default
+        let request = UpdateJobRequest::new().with_timeout_ms(10_000);
+        manager
+            .update_job_with_request(&job.id, request)
+            .await
+            .unwrap();
+        let listed = manager.list_jobs().await.unwrap();
+        assert_eq!(listed[0].effective_timeout_ms, Some(10_000));
+        let request = UpdateJobRequest::new().with_default_timeout();
+        manager
+            .update_job_with_request(&job.id, request)
+            .await
+            .unwrap();
+        let stored = manager.get_job_by_name("slow").await.unwrap().unwrap();
+        assert_eq!(stored.timeout_ms, None);
+        assert_eq!(stored.effective_timeout_ms, Some(200));
+
+        let fallback = create_test_manager();
+        let job = fallback
+            .add_job("plain", "0 0 1 1 *", "true")
+            .await
+            .unwrap();
+        let stored = fallback.get_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(stored.effective_timeout_ms, Some(DEFAULT_TIMEOUT_MS));
+        assert!(matches!(
+            create_test_manager().with_default_timeout(Some(Duration::ZERO)),
+            Err(CronError::InvalidConfig(_))
+        ));
+    }
+
+    /// Manager on a mock clock stopped at a whole minute
+    fn create_mock_clock_manager() -> (CronManager, Arc<MockClock>, DateTime<Utc>) {
+        let start = DateTime::from_timestamp(1_899_999_960, 0).unwrap();
+        let clock = Arc::new(MockClock::new(start));
+        let manager = CronManager::with_store(Arc::new(MemoryCronStore::new()), "/tmp".to_string())
+            .with_clock(clock.clone());
+        (manager, clock, start)
+    }
+
+    async fn scheduled_times(manager: &CronManager, job_id: &str) -> Vec<DateTime<Utc>> {
+        let mut times: Vec<DateTime<Utc>> = manager
+            .get_history(job_id, 100)
+            .await
+            .unwrap()
+            .iter()
+            .filter(|e| e.status == ExecutionStatus::Success)
+            .filter_map(|e| e.scheduled_for)
+            .collect();
+        times.sort();
+        times
+    }
+
+    #[tokio::test]
+    async fn test_mock_clock_min_interval() {
+        let (manager, clock, start) = create_mock_clock_manager();
+        let options = JobOptions::new().with_min_interval_ms(600_000);
+        let job = manager
+            .add_job_with_options("limited", "0 0 1 1 *", "true", options)
+            .await
+            .unwrap();
+
+        let first = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(first.status, ExecutionStatus::Success);
+        assert_eq!(first.started_at, start);
+        let stored = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(stored.last_run, Some(start));
+        let limited = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(limited.status, ExecutionStatus::Skipped);
+
+        // The interval passes on the manager's clock, not the wall clock
+        clock.advance(Duration::from_secs(601));
+        let second = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(second.status, ExecutionStatus::Success);
+    }
+
+    #[tokio::test]
+    async fn test_mock_clock_stale_health() {
+        let (manager, clock, start) = create_mock_clock_manager();
+        let job = manager.add_job("tick", "* * * * *", "true").await.unwrap();
+        assert_eq!(job.next_run, Some(start + chrono::Duration::minutes(1)));
+        assert_eq!(
+            manager.job_health(&job.id).await.unwrap(),
+            JobHealth::Healthy
+        );
+
+        // Overdue by more than the default five minutes on the manager's
+        // clock, with the scheduler stopped
+        clock.advance(Duration::from_secs(7 * 60));
+        assert_eq!(manager.job_health(&job.id).await.unwrap(), JobHealth::Stale);
+    }
+
+    #[tokio::test]
+    async fn test_mock_clock_fires_each_slot_once() {
+        let (manager, clock, start) = create_mock_clock_manager();
+        let job = manager.add_job("tick", "* * * * *", "true").await.unwrap();
+        assert_eq!(job.next_run, Some(start + chrono::Duration::minutes(1)));
+
+        let mut rx = manager.subscribe();
+        manager.start().await.unwrap();
+
+        // Nothing is due until the clock moves
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(manager.get_history(&job.id, 10).await.unwrap().is_empty());
+
+        for _ in 0..3 {
+            clock.advance(Duration::from_secs(60));
+            wait_for_completions(&mut rx, 1).await;
+        }
+
+        // Moving within a slot, or back to one that already fired, runs
+        // nothing more
+        clock.advance(Duration::from_secs(30));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        clock.set(start + chrono::Duration::minutes(3));
+        manager.runner.wakeup.notify_one();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        manager.stop().await;
+
+        let expected: Vec<DateTime<Utc>> = (1..=3)
+            .map(|i| start + chrono::Duration::minutes(i))
+            .collect();
+        assert_eq!(scheduled_times(&manager, &job.id).await, expected);
+        let job = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(job.run_count, 3);
+        assert_eq!(job.next_run, Some(start + chrono::Duration::minutes(4)));
+    }
+
+    #[tokio::test]
+    async fn test_claim_keeps_changes_made_since_listing() {
+        let (manager, _clock, start) = create_mock_clock_manager();
+        let job = manager
+            .add_job("claimed", "* * * * *", "true")
+            .await
+            .unwrap();
+        let slot = job.next_run.unwrap();
+        let scheduler_loop = SchedulerLoop {
+            store: manager.store.clone(),
+            running: manager.running.clone(),
+            paused: manager.paused.clone(),
+            runner: manager.runner.clone(),
+            tick_interval: Duration::from_secs(1),
+            last_tick: manager.last_tick.clone(),
+        };
+        let mut fired = HashMap::new();
+        let mut wakeup = start + chrono::Duration::hours(1);
+
+        // Paused after the loop listed the job: the claim neither runs it
+        // nor resumes it
+        let listed = manager.get_job(&job.id).await.unwrap().unwrap();
+        manager.pause_job(&job.id).await.unwrap();
+        let claimed = scheduler_loop
+            .check_job(listed, slot, &mut fired, &mut wakeup)
+            .await;
+        assert!(claimed.is_none());
+        let stored = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(stored.status, JobStatus::Paused);
+
+        // Edited after the loop listed the job: the edit is kept and runs
+        manager.resume_job(&job.id).await.unwrap();
+        let listed = manager.get_job(&job.id).await.unwrap().unwrap();
+        let slot = listed.next_run.unwrap();
+        manager.set_job_priority(&job.id, 5).await.unwrap();
+        let claimed = scheduler_loop
+            .check_job(listed, slot, &mut HashMap::new(), &mut wakeup)
+            .await
+            .unwrap();
+        assert_eq!(claimed.priority, 5);
+        assert_eq!(claimed.next_run, Some(slot));
+        let stored = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(stored.priority, 5);
+        assert!(stored.next_run.unwrap() > slot);
+    }
+
+    #[tokio::test]
+    async fn test_mock_clock_misfire() {
+        let (manager, clock, start) = create_mock_clock_manager();
+        let options = JobOptions::new().with_misfire_policy(MisfirePolicy::CatchUp { max: 2 });
+        let job = manager
+            .add_job_with_options("missed", "* * * * *", "true", options)
+            .await
+            .unwrap();
+
+        // Five fire times pass while the scheduler is stopped
+        clock.advance(Duration::from_secs(5 * 60));
+        let mut rx = manager.subscribe();
+        manager.start().await.unwrap();
+        wait_for_completions(&mut rx, 2).await;
+
+        // The catch-up run of the current slot isn't fired again by the loop
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(
+            count_by_status(&manager, &job.id, ExecutionStatus::Skipped).await,
+            3
+        );
+        assert_eq!(
+            scheduled_times(&manager, &job.id).await,
+            vec![
+                start + chrono::Duration::minutes(4),
+                start + chrono::Duration::minutes(5)
+            ]
+        );
+
+        clock.advance(Duration::from_secs(60));
+        wait_for_completions(&mut rx, 1).await;
+        manager.stop().await;
+
+        assert_eq!(
+            count_by_status(&manager, &job.id, ExecutionStatus::Success).await,
+            3
+        );
+        let job = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(job.next_run, Some(start + chrono::Duration::minutes(7)));
+    }
+
+    /// Store that panics saving the job named in `panic_on` and never
+    /// answers job listings while `hang` is set
+    #[derive(Default)]
+    struct FaultyStore {
+        inner: Arc<MemoryCronStore>,
+        panic_on: std::sync::Mutex<Option<String>>,
+        hang: AtomicBool,
+    }
+
+    #[async_trait::async_trait]
+    impl CronStore for FaultyStore {
+        async fn save_job(&self, job: &CronJob) -> Result<()> {
+            if self.panic_on.lock().unwrap().as_deref() == Some(job.name.as_str()) {
+                panic!("cannot save {}", job.name);
+            }
+            self.inner.save_job(job).await
+        }
+        async fn load_job(&self, id: &str) -> Result<Option<CronJob>> {
+            self.inner.load_job(id).await
+        }
+        async fn delete_job(&self, id: &str) -> Result<()> {
+            self.inner.delete_job(id).await
+        }
+        async fn list_jobs(&self) -> Result<Vec<CronJob>> {
+            if self.hang.load(Ordering::SeqCst) {
+                std::future::pending::<()>().await;
+            }
+            self.inner.list_jobs().await
+        }
+        async fn job_exists(&self, id: &str) -> Result<bool> {
+            self.inner.job_exists(id).await
+        }
+        async fn find_job_by_name(&self, name: &str) -> Result<Option<CronJob>> {
+            self.inner.find_job_by_name(name).await
+        }
+        async fn save_execution(&self, execution: &JobExecution) -> Result<()> {
+            self.inner.save_execution(execution).await
+        }
+        async fn load_executions(&self, job_id: &str, limit: usize) -> Result<Vec<JobExecution>> {
+            self.inner.load_executions(job_id, limit).await
+        }
+        async fn query_executions(
+            &self,
+            job_id: &str,
+            filter: &ExecutionFilter,
+        ) -> Result<Vec<JobExecution>> {
+            self.inner.query_executions(job_id, filter).await
+        }
+        async fn load_execution(&self, execution_id: &str) -> Result<Option<JobExecution>> {
+            self.inner.load_execution(execution_id).await
+        }
+        async fn delete_executions(&self, job_id: &str) -> Result<()> {
+            self.inner.delete_executions(job_id).await
+        }
+        async fn prune_executions(&self, job_id: &str, keep_last: usize) -> Result<usize> {
+            self.inner.prune_executions(job_id, keep_last).await
+        }
+        async fn prune_executions_before(
+            &self,
+            job_id: &str,
+            cutoff: DateTime<Utc>,
+        ) -> Result<usize> {
+            self.inner.prune_executions_before(job_id, cutoff).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_survives_panicking_job() {
+        let store = Arc::new(FaultyStore::default());
+        let manager = CronManager::with_store(store.clone(), "/tmp".to_string());
+        let bomb = manager.add_job("bomb", "0 0 1 1 *", "true").await.unwrap();
+        let fine = manager.add_job("fine", "0 0 1 1 *", "true").await.unwrap();
+        make_due(&store.inner, &bomb).await;
+        make_due(&store.inner, &fine).await;
+        *store.panic_on.lock().unwrap() = Some("bomb".to_string());
+        let mut rx = manager.subscribe();
+
+        manager.start().await.unwrap();
+        wait_for_completions(&mut rx, 1).await;
+        let health = manager.health().await;
+        assert_eq!(health.state, SchedulerState::Running);
+        assert!(health.last_tick.is_some());
+        assert!(!health.stalled);
+
+        // The panic was recorded against the job it happened on
+        let history = manager.get_history(&bomb.id, 10).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].status, ExecutionStatus::Failed);
+        assert_eq!(
+            history[0].error.as_deref(),
+            Some("Scheduler panicked: cannot save bomb")
+        );
+        let history = manager.get_history(&fine.id, 10).await.unwrap();
+        assert_eq!(history[0].status, ExecutionStatus::Success);
+        assert!(manager.is_running().await);
+        manager.stop().await;
+    }
+
+    /// Wait for the watchdog to report a stall, returning whether it
+    /// restarted the loop
+    async fn wait_for_stall(rx: &mut broadcast::Receiver<EventEnvelope>) -> bool {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let SchedulerEvent::SchedulerStalled { restarted, .. } =
+                    rx.recv().await.unwrap().kind
+                {
+                    return restarted;
+                }
+            }
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_watchdog_reports_stalled_loop() {
+        // Only reported by default, and stopping doesn't wait on the loop
+        let store = Arc::new(FaultyStore::default());
+        let manager = CronManager::with_store(store.clone(), "/tmp".to_string())
+            .with_tick_interval(Duration::from_millis(50))
+            .unwrap()
+            .with_stall_after_ticks(2)
+            .unwrap();
+        let mut rx = manager.subscribe();
+        manager.start().await.unwrap();
+        store.hang.store(true, Ordering::SeqCst);
+        assert!(!wait_for_stall(&mut rx).await);
+        let health = manager.health().await;
+        assert!(health.stalled);
+        assert_eq!(health.restarts, 0);
+        tokio::time::timeout(Duration::from_secs(5), manager.stop())
+            .await
+            .unwrap();
+
+        // Restarted when asked to
+        let store = Arc::new(FaultyStore::default());
+        let manager = CronManager::with_store(store.clone(), "/tmp".to_string())
+            .with_tick_interval(Duration::from_millis(50))
+            .unwrap()
+            .with_stall_after_ticks(2)
+            .unwrap()
+            .with_restart_on_stall(true);
+        let job = manager.add_job("after", "0 0 1 1 *", "true").await.unwrap();
+        let mut rx = manager.subscribe();
+        manager.start().await.unwrap();
+        store.hang.store(true, Ordering::SeqCst);
+        assert!(wait_for_stall(&mut rx).await);
+        store.hang.store(false, Ordering::SeqCst);
+
+        make_due(&store.inner, &job).await;
+        wait_for_completions(&mut rx, 1).await;
+        let health = manager.health().await;
+        assert!(health.restarts >= 1);
+        assert!(!health.stalled);
+        manager.stop().await;
+    }
+
+    /// Store whose job listings are a little stale, so that managers
+    /// sharing it all see a due job before any of them claims it
+    struct SlowListStore(Arc<MemoryCronStore>);
+
+    #[async_trait::async_trait]
+    impl CronStore for SlowListStore {
+        async fn save_job(&self, job: &CronJob) -> Result<()> {
+            self.0.save_job(job).await
+        }
+        async fn load_job(&self, id: &str) -> Result<Option<CronJob>> {
+            self.0.load_job(id).await
+        }
+        async fn delete_job(&self, id: &str) -> Result<()> {
+            self.0.delete_job(id).await
+        }
+        async fn list_jobs(&self) -> Result<Vec<CronJob>> {
+            let jobs = self.0.list_jobs().await?;
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Ok(jobs)
+        }
+        async fn job_exists(&self, id: &str) -> Result<bool> {
+            self.0.job_exists(id).await
+        }
+        async fn find_job_by_name(&self, name: &str) -> Result<Option<CronJob>> {
+            self.0.find_job_by_name(name).await
+        }
+        async fn save_execution(&self, execution: &JobExecution) -> Result<()> {
+            self.0.save_execution(execution).await
+        }
+        async fn load_executions(&self, job_id: &str, limit: usize) -> Result<Vec<JobExecution>> {
+            self.0.load_executions(job_id, limit).await
+        }
+        async fn query_executions(
+            &self,
+            job_id: &str,
+            filter: &ExecutionFilter,
+        ) -> Result<Vec<JobExecution>> {
+            self.0.query_executions(job_id, filter).await
+        }
+        async fn load_execution(&self, execution_id: &str) -> Result<Option<JobExecution>> {
+            self.0.load_execution(execution_id).await
+        }
+        async fn delete_executions(&self, job_id: &str) -> Result<()> {
+            self.0.delete_executions(job_id).await
+        }
+        async fn prune_executions(&self, job_id: &str, keep_last: usize) -> Result<usize> {
+            self.0.prune_executions(job_id, keep_last).await
+        }
+        async fn prune_executions_before(
+            &self,
+            job_id: &str,
+            cutoff: DateTime<Utc>,
+        ) -> Result<usize> {
+            self.0.prune_executions_before(job_id, cutoff).await
+        }
+        async fn try_acquire_lease(
+            &self,
+            job_id: &str,
+            holder: &str,
+            ttl: Duration,
+        ) -> Result<bool> {
+            self.0.try_acquire_lease(job_id, holder, ttl).await
+        }
+        async fn renew_lease(&self, job_id: &str, holder: &str, ttl: Duration) -> Result<bool> {
+            self.0.renew_lease(job_id, holder, ttl).await
+        }
+        async fn release_lease(&self, job_id: &str, holder: &str) -> Result<()> {
+            self.0.release_lease(job_id, holder).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_leases_shared_store_runs_once() {
+        let start = DateTime::from_timestamp(1_899_999_960, 0).unwrap();
+        let clock = Arc::new(MockClock::new(start));
+        let store = Arc::new(MemoryCronStore::new());
+        let managers: Vec<CronManager> = ["a", "b"]
+            .into_iter()
+            .map(|instance_id| {
+                CronManager::with_store(Arc::new(SlowListStore(store.clone())), "/tmp".to_string())
+                    .with_clock(clock.clone())
+                    .with_instance_id(instance_id)
+                    .with_coordination(Coordination::PerJobLease {
+                        lease_ttl: Duration::from_secs(30),
+                    })
+                    .unwrap()
+            })
+            .collect();
+        let job = managers[0]
+            .add_job("shared", "* * * * *", "true")
+            .await
+            .unwrap();
+        for manager in &managers {
+            manager.start().await.unwrap();
+        }
+
+        for fired in 1..=3 {
+            clock.advance(Duration::from_secs(60));
+            // Wait for the run to finish updating the job too
+            tokio::time::timeout(Duration::from_secs(5), async {
+                loop {
+                    let current = store.load_job(&job.id).await.unwrap().unwrap();
+                    if current.status == JobStatus::Active
+                        && scheduled_times(&managers[0], &job.id).await.len() >= fired
+                    {
+                        break;
+                    }
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                }
+            })
+            .await
+            .unwrap();
+        }
+        // Give a second run of any slot the chance to show up
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        for manager in &managers {
+            manager.stop().await;
+        }
+
+        let expected: Vec<DateTime<Utc>> = (1..=3)
+            .map(|i| start + chrono::Duration::minutes(i))
+            .collect();
+        assert_eq!(scheduled_times(&managers[0], &job.id).await, expected);
+        assert_eq!(managers[1].get_history(&job.id, 10).await.unwrap().len(), 3);
+
+        // Every lease was released
+        assert!(store
+            .try_acquire_lease(&job.id, "c", Duration::from_secs(30))
+            .await
+            .unwrap());
+    }
+
+    async fn wait_for_leadership(rx: &mut broadcast::Receiver<EventEnvelope>, instance_id: &str) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let SchedulerEvent::BecameLeader {
+                    instance_id: id, ..
+                } = rx.recv().await.unwrap().kind
+                {
+                    if id == instance_id {
+                        return;
+                    }
+                }
+            }
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_leader_election_failover() {
+        let start = DateTime::from_timestamp(1_899_999_960, 0).unwrap();
+        let clock = Arc::new(MockClock::new(start));
+        let store = Arc::new(MemoryCronStore::new());
+        let coordination = Coordination::LeaderElection {
+            lease_ttl: Duration::from_secs(1),
+        };
+        let new_manager = |instance_id: &str| {
+            CronManager::with_store(store.clone(), "/tmp".to_string())
+                .with_clock(clock.clone())
+                .with_instance_id(instance_id)
+                .with_coordination(coordination)
+                .unwrap()
+        };
+        let leader = new_manager("a");
+        let follower = new_manager("b");
+        let mut leader_rx = leader.subscribe();
+        let mut follower_rx = follower.subscribe();
+
+        leader.start().await.unwrap();
+        wait_for_leadership(&mut leader_rx, "a").await;
+        follower.start().await.unwrap();
+        assert!(leader.is_leader());
+        assert!(!follower.is_leader());
+
+        let job = leader.add_job("ticker", "* * * * *", "true").await.unwrap();
+        clock.advance(Duration::from_secs(60));
+        wait_for_completions(&mut leader_rx, 1).await;
+
+        // Followers still run jobs when asked
+        let manual = follower.run_job(&job.id).await.unwrap();
+        assert_eq!(manual.status, ExecutionStatus::Success);
+        assert!(!follower.is_leader());
+
+        // The follower takes over once the dead leader's lease expires
+        drop(leader);
+        wait_for_leadership(&mut follower_rx, "b").await;
+        assert!(follower.is_leader());
+        clock.advance(Duration::from_secs(60));
+        wait_for_completions(&mut follower_rx, 1).await;
+
+        let expected: Vec<DateTime<Utc>> = (1..=2)
+            .map(|i| start + chrono::Duration::minutes(i))
+            .collect();
+        assert_eq!(scheduled_times(&follower, &job.id).await, expected);
+
+        // Stopping gives the lease up
+        follower.stop().await;
+        assert!(!follower.is_leader());
+        assert!(store
+            .try_acquire_lease(LEADER_LEASE, "c", Duration::from_secs(1))
+            .await
+            .unwrap());
+    }
+
+    /// Middleware vetoing jobs with its tag, and retries once `retries` is
+    /// set, and failing every `after` call it counts
+    struct TagVeto {
+        tag: String,
+        retries: bool,
+        after_calls: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl JobMiddleware for TagVeto {
+        fn name(&self) -> &str {
+            "tag-veto"
+        }
+
+        async fn before(
+            &self,
+            job: &CronJob,
+            execution: &JobExecution,
+        ) -> std::result::Result<(), crate::middleware::SkipReason> {
+            if job.tags.contains(&self.tag) {
+                return Err(crate::middleware::SkipReason::new(format!(
+                    "tagged {}",
+                    self.tag
+                )));
+            }
+            if self.retries && execution.attempt > 1 {
+                return Err(crate::middleware::SkipReason::new("no retries"));
+            }
+            Ok(())
+        }
+
+        async fn after(&self, _job: &CronJob, _execution: &JobExecution) -> Result<()> {
+            self.after_calls.fetch_add(1, Ordering::SeqCst);
+            Err(CronError::Execution("after hook broke".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_middleware_vetoes_and_observes_executions() {
+        let manager = create_test_manager();
+        let veto = Arc::new(TagVeto {
+            tag: "frozen".to_string(),
+            retries: false,
+            after_calls: AtomicUsize::new(0),
+        });
+        manager.add_middleware(veto.clone());
+        let frozen = manager
+            .add_job_with_options(
+                "frozen",
+                "0 0 1 1 *",
+                "echo hi",
+                JobOptions::new().with_tag("frozen"),
+            )
+            .await
+            .unwrap();
+        let ok = manager.add_job("ok", "0 0 1 1 *", "echo ok").await.unwrap();
+
+        // A veto records a skipped run without touching the job's statistics
+        let skipped = manager.run_job(&frozen.id).await.unwrap();
+        assert_eq!(skipped.status, ExecutionStatus::Skipped);
+        assert_eq!(skipped.error.as_deref(), Some("tagged frozen"));
+        let history = manager.get_history(&frozen.id, 10).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].status, ExecutionStatus::Skipped);
+        let frozen = manager.get_job(&frozen.id).await.unwrap().unwrap();
+        assert_eq!(frozen.status, JobStatus::Active);
+        assert_eq!((frozen.run_count, frozen.fail_count), (0, 0));
+        assert_eq!(veto.after_calls.load(Ordering::SeqCst), 0);
+
+        // A failing after hook doesn't fail the job
+        let execution = manager.run_job(&ok.id).await.unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Success);
+        assert_eq!(veto.after_calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_middleware_vetoes_retries() {
+        let manager = create_test_manager();
+        manager.add_middleware(Arc::new(TagVeto {
+            tag: "frozen".to_string(),
+            retries: true,
+            after_calls: AtomicUsize::new(0),
+        }));
+        let job = manager
+            .add_job_with_options(
+                "flaky",
+                "0 0 1 1 *",
+                "exit 1",
+                JobOptions::new().with_retries(2, 10, 1.0),
+            )
+            .await
+            .unwrap();
+
+        // The vetoed retry is recorded, and the failed attempt is the result
+        let execution = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Failed);
+        let mut history = manager.get_history(&job.id, 10).await.unwrap();
+        history.sort_by_key(|e| e.attempt);
+        let statuses: Vec<_> = history.iter().map(|e| (e.attempt, e.status)).collect();
+        assert_eq!(
+            statuses,
+            vec![(1, ExecutionStatus::Failed), (2, ExecutionStatus::Skipped)]
+        );
+        let job = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(job.status, JobStatus::Active);
+        assert_eq!(job.fail_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_run_immediately_on_create() {
+        let manager = create_test_manager();
+        let mut rx = manager.subscribe();
+        let (job, execution_id) = manager
+            .create_job(
+                "sync",
+                "0 0 1 1 *",
+                "echo synced",
+                JobOptions::new().with_run_immediately(),
+            )
+            .await
+            .unwrap();
+        let execution_id = execution_id.unwrap();
+        wait_for_completions(&mut rx, 1).await;
+
+        let execution = manager.get_execution(&execution_id).await.unwrap().unwrap();
+        assert_eq!(execution.trigger, TriggerKind::OnCreate);
+        assert_eq!(execution.status, ExecutionStatus::Success);
+        assert_eq!(execution.stdout.trim(), "synced");
+        // The schedule is followed afterwards
+        let stored = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(stored.next_run, job.next_run);
+        assert_eq!((stored.run_count, stored.manual_run_count), (1, 0));
+
+        // A failing first run leaves the job in place
+        let (failing, execution_id) = manager
+            .create_job(
+                "broken",
+                "0 0 1 1 *",
+                "exit 3",
+                JobOptions::new().with_run_immediately(),
+            )
+            .await
+            .unwrap();
+        let execution_id = execution_id.unwrap();
+        let failed = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let Some(execution) = manager.get_execution(&execution_id).await.unwrap() {
+                    return execution;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(failed.status, ExecutionStatus::Failed);
+        assert!(manager.get_job(&failing.id).await.unwrap().is_some());
+
+        // Without the option nothing runs
+        let (_, execution_id) = manager
+            .create_job("later", "0 0 1 1 *", "echo later", JobOptions::new())
+            .await
+            .unwrap();
+        assert!(execution_id.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_start_brings_stale_jobs_up_to_date() {
+        let (manager, clock, start) = create_mock_clock_manager();
+        let missed = manager
+            .add_job_with_options(
+                "missed",
+                "* * * * *",
+                "true",
+                JobOptions::new().with_misfire_policy(MisfirePolicy::Skip),
+            )
+            .await
+            .unwrap();
+        manager
+            .add_job("yearly", "0 0 1 1 *", "true")
+            .await
+            .unwrap();
+        clock.advance(Duration::from_secs(600));
+
+        let mut rx = manager.subscribe();
+        manager.start().await.unwrap();
+        match rx.recv().await.unwrap().kind {
+            SchedulerEvent::Started {
+                jobs_loaded,
+                stale_jobs,
+                ..
+            } => assert_eq!((jobs_loaded, stale_jobs), (2, 1)),
+            other => panic!("unexpected event: {:?}", other),
+        }
+        // The new fire time is saved before the loop starts
+        let missed = manager.get_job(&missed.id).await.unwrap().unwrap();
+        assert_eq!(missed.next_run, Some(start + chrono::Duration::minutes(11)));
+        assert_eq!(
+            count_by_status(&manager, &missed.id, ExecutionStatus::Skipped).await,
+            10
+        );
+
+        assert!(matches!(
+            manager.start().await,
+            Err(CronError::AlreadyRunning)
+        ));
+        manager.stop().await;
+        manager.start().await.unwrap();
+        manager.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_record_history_off_keeps_statistics() {
+        let manager = create_test_manager();
+        let job = manager
+            .add_job_with_options(
+                "chatty",
+                "0 0 1 1 *",
+                "exit 1",
+                JobOptions::new().with_record_history(false),
+            )
+            .await
+            .unwrap();
+        assert!(!job.record_history);
+
+        let execution = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Failed);
+        assert!(manager.get_history(&job.id, 10).await.unwrap().is_empty());
+        assert!(manager
+            .get_execution(&execution.id)
+            .await
+            .unwrap()
+            .is_none());
+        let stored = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(stored.fail_count, 1);
+        assert_eq!(stored.last_run, Some(execution.started_at));
+        assert_eq!(stored.last_error.as_deref(), Some("exit code 1"));
+
+        // A manual run can ask to be recorded anyway
+        let recorded = manager
+            .run_job_with(&job.id, RunOptions::new().with_record_history())
+            .await
+            .unwrap();
+        let history = manager.get_history(&job.id, 10).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].id, recorded.id);
+
+        let job = manager.set_job_record_history(&job.id, true).await.unwrap();
+        assert!(job.record_history);
+        manager.run_job(&job.id).await.unwrap();
+        assert_eq!(manager.get_history(&job.id, 10).await.unwrap().len(), 2);
+        let stored = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(stored.fail_count, 3);
+    }
+
+    #[tokio::test]
+    async fn test_schedule_lag() {
+        let (manager, clock, _) = create_mock_clock_manager();
+        let job = manager.add_job("tick", "* * * * *", "true").await.unwrap();
+        let mut rx = manager.subscribe();
+        manager.start().await.unwrap();
+
+        // The clock jumps half a minute past the fire time
+        clock.advance(Duration::from_secs(90));
+        let lag_ms = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let SchedulerEvent::JobStarted { lag_ms, .. } = rx.recv().await.unwrap().kind {
+                    return lag_ms;
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(lag_ms, Some(30_000));
+        wait_for_completions(&mut rx, 1).await;
+        manager.stop().await;
+
+        // Manual runs have no fire time to lag behind
+        let manual = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(manual.lag_ms, None);
+
+        let history = manager.get_history(&job.id, 10).await.unwrap();
+        let lags: Vec<_> = history.iter().map(|e| e.lag_ms).collect();
+        assert!(lags.contains(&Some(30_000)));
+        let stats = manager.job_stats(&job.id).await.unwrap();
+        assert_eq!(stats.avg_lag_ms, Some(30_000));
+        assert_eq!(stats.max_lag_ms, Some(30_000));
+    }
+
+    #[tokio::test]
+    async fn test_holiday_calendar() {
+        use chrono::TimeZone;
+
+        // The clock starts on 2030-03-17
+        let (manager, _clock, _) = create_mock_clock_manager();
+        let day = |d: u32| NaiveDate::from_ymd_opt(2030, 3, d).unwrap();
+        let at = |d: u32, h: u32| Utc.with_ymd_and_hms(2030, 3, d, h, 0, 0).unwrap();
+
+        // Unknown calendars are refused up front
+        let options = JobOptions::new().with_exclude_calendar("holidays");
+        assert!(matches!(
+            manager
+                .add_job_with_options("report", "0 9 * * *", "true", options.clone())
+                .await,
+            Err(CronError::InvalidConfig(_))
+        ));
+
+        manager
+            .register_calendar("holidays", [day(18), day(19)])
+            .await
+            .unwrap();
+        assert_eq!(manager.calendar_names(), vec!["holidays".to_string()]);
+        let skipped = manager
+            .add_job_with_options("report", "0 9 * * *", "true", options.clone())
+            .await
+            .unwrap();
+        assert_eq!(skipped.next_run, Some(at(20, 9)));
+        assert_eq!(
+            manager.preview_job(&skipped.id, 2).await.unwrap(),
+            vec![at(20, 9), at(21, 9)]
+        );
+        let deferred = manager
+            .add_job_with_options(
+                "deferred",
+                "0 9 * * *",
+                "true",
+                options.with_holiday_policy(BlackoutPolicy::Defer),
+            )
+            .await
+            .unwrap();
+        assert_eq!(deferred.next_run, Some(at(20, 0)));
+
+        let runs = manager.simulate((at(18, 0), at(21, 0))).await.unwrap();
+        let times: Vec<_> = runs
+            .iter()
+            .map(|run| (run.job_name.as_str(), run.fire_time))
+            .collect();
+        assert_eq!(
+            times,
+            vec![
+                ("deferred", at(20, 0)),
+                ("deferred", at(20, 9)),
+                ("report", at(20, 9))
+            ]
+        );
+
+        // Reloading the calendar reschedules the jobs excluding it
+        manager
+            .register_calendar("holidays", [day(18)])
+            .await
+            .unwrap();
+        let skipped = manager.get_job(&skipped.id).await.unwrap().unwrap();
+        assert_eq!(skipped.next_run, Some(at(19, 9)));
+
+        let plain = manager.add_job("plain", "0 9 * * *", "true").await.unwrap();
+        assert_eq!(plain.next_run, Some(at(18, 9)));
+        assert!(manager
+            .set_job_calendar(&plain.id, Some("other".to_string()), BlackoutPolicy::Skip)
+            .await
+            .is_err());
+        let plain = manager
+            .set_job_calendar(
+                &plain.id,
+                Some("holidays".to_string()),
+                BlackoutPolicy::Skip,
+            )
+            .await
+            .unwrap();
+        assert_eq!(plain.next_run, Some(at(19, 9)));
+    }
+
+    #[tokio::test]
+    async fn test_notify_on_change() {
+        let manager = create_test_manager();
+        let job = manager
+            .add_job_with_options(
+                "monitor",
+                "0 0 1 1 *",
+                "echo 'disk ok'",
+                JobOptions::new().with_notify_on_change(true),
+            )
+            .await
+            .unwrap();
+        assert!(job.notify_on_change);
+        assert!(job.ignore_whitespace_changes);
+        // Any call enables the feature; the flag only picks the comparison
+        let options = JobOptions::new().with_notify_on_change(false);
+        assert!(options.notify_on_change);
+        assert!(!options.ignore_whitespace_changes);
+        let mut rx = manager.subscribe();
+        let run =
+            |command: &str| manager.run_job_with(&job.id, RunOptions::new().with_command(command));
+
+        // The first run has nothing to compare with
+        let first = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(first.output_changed, Some(true));
+        let output_changed = loop {
+            if let SchedulerEvent::JobCompleted { output_changed, .. } =
+                rx.recv().await.unwrap().kind
+            {
+                break output_changed;
+            }
+        };
+        assert_eq!(output_changed, Some(true));
+
+        let same = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(same.output_changed, Some(false));
+        let spaced = run("echo 'disk   ok  '").await.unwrap();
+        assert_eq!(spaced.output_changed, Some(false));
+        // Failed runs are not compared and leave the stored hash alone
+        let failed = run("echo 'disk full'; exit 1").await.unwrap();
+        assert_eq!(failed.output_changed, None);
+        let changed = run("echo 'disk full'").await.unwrap();
+        assert_eq!(changed.output_changed, Some(true));
+
+        // Whitespace counts once it isn't ignored
+        manager
+            .set_job_notify_on_change(&job.id, true, false)
+            .await
+            .unwrap();
+        assert_eq!(
+            run("echo 'disk  full'").await.unwrap().output_changed,
+            Some(true)
+        );
+        let job = manager
+            .set_job_notify_on_change(&job.id, false, false)
+            .await
+            .unwrap();
+        assert_eq!(job.last_output_hash, None);
+        assert_eq!(manager.run_job(&job.id).await.unwrap().output_changed, None);
+    }
+
+    #[tokio::test]
+    async fn test_export_ics() {
+        let (manager, _clock, start) = create_mock_clock_manager();
+        let options = JobOptions::new()
+            .with_tag("env:prod")
+            .with_env("TOKEN", "hunter2")
+            .with_secret_env("TOKEN");
+        let backup = manager
+            .add_job_with_options(
+                "backup, full; nightly",
+                "0 2 * * *",
+                "upload --token hunter2 --to s3://a,b\\c",
+                options,
+            )
+            .await
+            .unwrap();
+        manager
+            .add_job("minutely", "* * * * *", "true")
+            .await
+            .unwrap();
+        let paused = manager
+            .add_job("paused", "0 * * * *", "true")
+            .await
+            .unwrap();
+        manager.pause_job(&paused.id).await.unwrap();
+
+        let ics = manager
+            .export_ics(Duration::from_secs(3 * 86_400), None)
+            .await
+            .unwrap();
+        assert!(!ics.contains("hunter2"));
+        let calendar = crate::ics::tests::validate(&ics).unwrap();
+        let events = |name: &str| {
+            calendar
+                .components
+                .iter()
+                .filter(|event| event.get("SUMMARY") == Some(name))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(events("minutely").len(), crate::ics::MAX_EVENTS_PER_JOB);
+        assert!(events("paused").is_empty());
+        let backups = events("backup, full; nightly");
+        assert_eq!(backups.len(), 3);
+        assert_eq!(
+            backups[0].get("DESCRIPTION").unwrap(),
+            format!(
+                "upload --token {} --to s3://a,b\\c\nJob ID: {}",
+                SECRET_MASK, backup.id
+            )
+        );
+        let first = manager.preview_job(&backup.id, 1).await.unwrap()[0];
+        assert!(first > start);
+        assert_eq!(
+            backups[0].get("DTSTART").unwrap(),
+            first.format("%Y%m%dT%H%M%SZ").to_string()
+        );
+
+        let filter = JobFilter::new().with_tags_any(vec!["env:prod".to_string()]);
+        let ics = manager
+            .export_ics(Duration::from_secs(3 * 86_400), Some(&filter))
+            .await
+            .unwrap();
+        let calendar = crate::ics::tests::validate(&ics).unwrap();
+        assert_eq!(calendar.components.len(), 3);
+
+        // The overall cap applies across jobs
+        for i in 0..15 {
+            manager
+                .add_job(&format!("minutely-{}", i), "* * * * *", "true")
+                .await
+                .unwrap();
+        }
+        let ics = manager
+            .export_ics(Duration::from_secs(86_400), None)
+            .await
+            .unwrap();
+        let calendar = crate::ics::tests::validate(&ics).unwrap();
+        assert_eq!(calendar.components.len(), crate::ics::MAX_EVENTS);
+    }
+
+    #[tokio::test]
+    async fn test_simulate() {
+        let manager = create_test_manager();
+        let start = DateTime::from_timestamp(1_899_997_200, 0).unwrap();
+        let end = start + chrono::Duration::hours(1);
+
+        let minutely = manager
+            .add_job("minutely", "* * * * *", "true")
+            .await
+            .unwrap();
+        let quarterly = manager
+            .add_job("quarterly", "*/15 * * * *", "true")
+            .await
+            .unwrap();
+        let paused = manager
+            .add_job("paused", "* * * * *", "true")
+            .await
+            .unwrap();
+        manager.pause_job(&paused.id).await.unwrap();
+        let options = JobOptions::new().with_max_runs(3, true);
+        let limited = manager
+            .add_job_with_options("limited", "*/10 * * * *", "true", options)
+            .await
+            .unwrap();
+        manager.snooze_job(&limited.id, 1).await.unwrap();
+
+        let runs = manager.simulate((start, end)).await.unwrap();
+        let count = |id: &str| runs.iter().filter(|run| run.job_id == id).count();
+        assert_eq!(count(&minutely.id), 60);
+        assert_eq!(count(&quarterly.id), 4);
+        assert_eq!(count(&paused.id), 0);
+        assert_eq!(runs.len(), 67);
+        assert!(runs.windows(2).all(|w| w[0].fire_time <= w[1].fire_time));
+        assert_eq!(runs[0].fire_time, start);
+        assert!(runs.iter().all(|run| run.fire_time < end));
+
+        // The snoozed occurrence is skipped before the three allowed runs
+        let limited_times: Vec<DateTime<Utc>> = runs
+            .iter()
+            .filter(|run| run.job_id == limited.id)
+            .map(|run| run.fire_time)
+            .collect();
+        let expected: Vec<DateTime<Utc>> = (1..=3)
+            .map(|i| start + chrono::Duration::minutes(10 * i))
+            .collect();
+        assert_eq!(limited_times, expected);
+
+        // Nothing was run or changed
+        let job = manager.get_job(&minutely.id).await.unwrap().unwrap();
+        assert_eq!(job.next_run, minutely.next_run);
+        assert!(manager
+            .get_history(&minutely.id, 10)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let capped = manager.simulate_with_limit((start, end), 5).await.unwrap();
+        assert_eq!(capped, runs[..5].to_vec());
+
+        assert!(matches!(
+            manager.simulate((end, start)).await,
+            Err(CronError::InvalidConfig(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_purge_history() {
+        let manager = create_test_manager();
+        let job = manager
+            .add_job("purged", "* * * * *", "echo")
+            .await
+            .unwrap();
+        for _ in 0..3 {
+            manager.run_job(&job.id).await.unwrap();
+        }
+
+        assert_eq!(manager.purge_history(&job.id, 1).await.unwrap(), 2);
+        assert_eq!(manager.get_history(&job.id, 10).await.unwrap().len(), 1);
+        let cutoff = Utc::now() + chrono::Duration::seconds(1);
+        assert_eq!(
+            manager.purge_history_before(&job.id, cutoff).await.unwrap(),
+            1
+        );
+        assert!(manager.get_history(&job.id, 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_retention_policy() {
+        let manager = create_test_manager()
+            .with_retention(RetentionPolicy::new().with_max_executions_per_job(2));
+        let job = manager
+            .add_job("retained", "* * * * *", "echo")
+            .await
+            .unwrap();
+        for _ in 0..4 {
+            manager.run_job(&job.id).await.unwrap();
+        }
+        let history = manager.get_history(&job.id, 10).await.unwrap();
+        assert_eq!(history.len(), 2);
+
+        // Executions older than the maximum age are dropped on the next save
+        let manager = create_test_manager()
+            .with_retention(RetentionPolicy::new().with_max_age(Duration::from_secs(3600)));
+        let job = manager.add_job("aged", "* * * * *", "echo").await.unwrap();
+        let mut old = JobExecution::new(&job.id);
+        old.started_at = Utc::now() - chrono::Duration::hours(2);
+        manager.store.save_execution(&old).await.unwrap();
+        manager.run_job(&job.id).await.unwrap();
+        let history = manager.get_history(&job.id, 10).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_ne!(history[0].id, old.id);
+    }
+
+    #[tokio::test]
+    async fn test_remove_job_history() {
+        let manager = create_test_manager();
+
+        for purge_history in [false, true] {
+            let job = manager
+                .add_job("removable", "* * * * *", "echo")
+                .await
+                .unwrap();
+            manager.run_job(&job.id).await.unwrap();
+
+            manager.remove_job(&job.id, purge_history).await.unwrap();
+            let history = manager.get_history(&job.id, 10).await.unwrap();
+            assert_eq!(history.is_empty(), purge_history);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_disable_and_enable_job() {
+        let store = Arc::new(MemoryCronStore::new());
+        let manager = CronManager::with_store(store.clone(), "/tmp".to_string());
+        let mut events = manager.subscribe();
+        let job = manager
+            .add_job("disabled", "* * * * *", "echo")
+            .await
+            .unwrap();
+        let other = manager.add_job("other", "* * * * *", "echo").await.unwrap();
+
+        let disabled = manager.disable_job(&job.id).await.unwrap();
+        assert_eq!(disabled.status, JobStatus::Disabled);
+        assert!(matches!(
+            manager.run_job(&job.id).await,
+            Err(CronError::JobDisabled(_))
+        ));
+        assert!(matches!(
+            manager.resume_job(&job.id).await,
+            Err(CronError::JobDisabled(_))
+        ));
+        assert!(matches!(
+            manager.pause_job(&job.id).await,
+            Err(CronError::JobDisabled(_))
+        ));
+
+        // The tick leaves disabled jobs alone
+        make_due(&store, &disabled).await;
+        manager.start().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        manager.stop().await;
+        assert!(manager.get_history(&job.id, 10).await.unwrap().is_empty());
+
+        let listed = manager
+            .list_jobs_filtered(&JobFilter::new().excluding_disabled())
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, other.id);
+        let listed = manager
+            .list_jobs_filtered(&JobFilter::new().with_status(JobStatus::Disabled))
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, job.id);
+
+        let enabled = manager.enable_job(&job.id).await.unwrap();
+        assert_eq!(enabled.status, JobStatus::Active);
+        assert!(enabled.next_run.is_some());
+
+        let mut kinds = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            match event.kind {
+                SchedulerEvent::JobDisabled { .. } => kinds.push("disabled"),
+                SchedulerEvent::JobEnabled { .. } => kinds.push("enabled"),
+                SchedulerEvent::JobResumed { .. } => kinds.push("resumed"),
+                _ => {}
+            }
+        }
+        assert_eq!(kinds, ["disabled", "enabled"]);
+    }
+
+    #[tokio::test]
+    async fn test_run_job() {
+        let manager = create_test_manager();
+
+        let job = manager
+            .add_job("runnable", "* * * * *", "echo hello")
+            .await
+            .unwrap();
+
+        let execution = manager.run_job(&job.id).await.unwrap();
+        assert!(execution.stdout.contains("hello"));
+    }
+
+    #[tokio::test]
+    async fn test_run_job_failure() {
+        let manager = create_test_manager();
+
+        let job = manager
+            .add_job("failing", "* * * * *", "exit 1")
+            .await
+            .unwrap();
+
+        let execution = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(execution.status, crate::types::ExecutionStatus::Failed);
+    }
+
+    #[tokio::test]
+    async fn test_run_job_timeout_kills_process() {
+        let manager = create_test_manager();
+
+        let job = manager
+            .add_job("slow", "* * * * *", "echo $$; exec sleep 60")
+            .await
+            .unwrap();
+        manager
+            .update_job(&job.id, None, None, Some(500))
+            .await
+            .unwrap();
+
+        let started = std::time::Instant::now();
+        let execution = manager.run_job(&job.id).await.unwrap();
+        assert!(started.elapsed() < std::time::Duration::from_secs(10));
+        assert_eq!(execution.status, crate::types::ExecutionStatus::Timeout);
+
+        // Output printed before the kill is kept
+        let pid: u32 = execution.stdout.trim().parse().unwrap();
+        let alive = std::process::Command::new("kill")
+            .arg("-0")
+            .arg(pid.to_string())
+            .stderr(std::process::Stdio::null())
+            .status()
+            .unwrap()
+            .success();
+        assert!(!alive, "process {} still running after timeout", pid);
+    }
+
+    #[tokio::test]
+    async fn test_get_history() {
+        let manager = create_test_manager();
+
+        let job = manager
+            .add_job("historical", "* * * * *", "echo test")
+            .await
+            .unwrap();
+
+        // Run job multiple times
+        for _ in 0..3 {
+            manager.run_job(&job.id).await.unwrap();
+        }
+
+        let history = manager.get_history(&job.id, 10).await.unwrap();
+        assert_eq!(history.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_event_subscription() {
+        let manager = create_test_manager();
+        let mut rx = manager.subscribe();
+
+        let before = Utc::now();
+        let job = manager
+            .add_job("evented", "* * * * *", "echo test")
+            .await
+            .unwrap();
+
+        let event = rx.try_recv().unwrap().kind;
+        assert!(event.timestamp() >= before);
+        match event {
+            SchedulerEvent::JobAdded { job_id, name, .. } => {
+                assert_eq!(job_id, job.id);
+                assert_eq!(name, "evented");
+            }
+            _ => panic!("Expected JobAdded event"),
+        }
+
+        // Run job
+        manager.run_job(&job.id).await.unwrap();
+
+        // Check events
+        let event = rx.try_recv().unwrap().kind;
+        match event {
+            SchedulerEvent::JobStarted { job_id, .. } => {
+                assert_eq!(job_id, job.id);
+            }
+            _ => panic!("Expected JobStarted event"),
+        }
+        while rx.try_recv().is_ok() {}
+
+        manager
+            .update_job(&job.id, None, Some("echo changed"), Some(5_000))
+            .await
+            .unwrap();
+        match rx.try_recv().unwrap().kind {
+            SchedulerEvent::JobUpdated {
+                job_id,
+                changed_fields,
+                ..
+            } => {
+                assert_eq!(job_id, job.id);
+                assert_eq!(changed_fields, vec!["command", "timeout_ms"]);
+            }
+            other => panic!("Expected JobUpdated event, got {:?}", other),
+        }
+
+        manager.pause_job(&job.id).await.unwrap();
+        assert!(matches!(
+            rx.try_recv().unwrap().kind,
+            SchedulerEvent::JobPaused { job_id, .. } if job_id == job.id
+        ));
+        manager.resume_job(&job.id).await.unwrap();
+        assert!(matches!(
+            rx.try_recv().unwrap().kind,
+            SchedulerEvent::JobResumed { job_id, .. } if job_id == job.id
+        ));
+        manager.remove_job(&job.id, true).await.unwrap();
+        assert!(matches!(
+            rx.try_recv().unwrap().kind,
+            SchedulerEvent::JobRemoved { job_id, .. } if job_id == job.id
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_event_envelope_payload() {
+        let manager = create_test_manager();
+        let job = manager
+            .add_job("failing", "0 0 1 1 *", "exit 7")
+            .await
+            .unwrap();
+        let mut rx = manager.subscribe();
+        let execution = manager.run_job(&job.id).await.unwrap();
+
+        let envelope = loop {
+            let envelope = rx.try_recv().unwrap();
+            if matches!(envelope.kind, SchedulerEvent::JobFailed { .. }) {
+                break envelope;
+            }
+        };
+        assert_eq!(envelope.at, envelope.kind.timestamp());
+
+        let json = serde_json::to_value(&envelope).unwrap();
+        assert_eq!(json["id"], envelope.id.to_string());
+        assert_eq!(json["kind"]["type"], "job_failed");
+        assert_eq!(json["kind"]["job_id"], job.id);
+        assert_eq!(json["kind"]["job_name"], "failing");
+        assert_eq!(json["kind"]["execution_id"], execution.id);
+        assert_eq!(json["kind"]["exit_code"], 7);
+        assert_eq!(json["kind"]["duration_ms"], execution.duration_ms.unwrap());
+
+        let parsed: EventEnvelope = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.id, envelope.id);
+        match parsed.kind {
+            SchedulerEvent::JobFailed {
+                job_name,
+                exit_code,
+                ..
+            } => {
+                assert_eq!(job_name, "failing");
+                assert_eq!(exit_code, Some(7));
+            }
+            other => panic!("Expected JobFailed event, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_event_error_truncated() {
+        assert_eq!(event_error("short"), "short");
+        let long = "é".repeat(MAX_EVENT_ERROR_LEN);
+        let truncated = event_error(&long);
+        assert!(truncated.ends_with("...[truncated]"));
+        assert!(truncated.len() <= MAX_EVENT_ERROR_LEN + "...[truncated]".len());
+    }
+
+    #[tokio::test]
+    async fn test_event_stream_reports_lag() {
+        use futures_util::StreamExt;
+
+        assert!(create_test_manager().with_event_capacity(0).is_err());
+        let manager = create_test_manager().with_event_capacity(2).unwrap();
+        assert_eq!(manager.event_capacity(), 2);
+        let mut events = Box::pin(manager.event_stream());
+
+        for i in 0..5 {
+            manager
+                .add_job(&format!("job-{}", i), "0 0 1 1 *", "echo")
+                .await
+                .unwrap();
+        }
+
+        match events.next().await.unwrap() {
+            SchedulerEvent::EventsDropped { count, .. } => assert_eq!(count, 3),
+            other => panic!("Expected EventsDropped event, got {:?}", other),
+        }
+        for expected in ["job-3", "job-4"] {
+            match events.next().await.unwrap() {
+                SchedulerEvent::JobAdded { name, .. } => assert_eq!(name, expected),
+                other => panic!("Expected JobAdded event, got {:?}", other),
+            }
+        }
+
+        // The stream keeps going after a lag
+        manager.add_job("job-5", "0 0 1 1 *", "echo").await.unwrap();
+        assert!(matches!(
+            events.next().await.unwrap(),
+            SchedulerEvent::JobAdded { name, .. } if name == "job-5"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_audit_log() {
+        let manager = create_test_manager();
+        assert!(!manager.audit_log_enabled());
+        manager
+            .add_job("unaudited", "0 0 1 1 *", "echo")
+            .await
+            .unwrap();
+        assert!(manager.get_audit_log(None, 10).await.unwrap().is_empty());
+
+        let manager = create_test_manager().with_audit_log(true);
+        assert!(manager.audit_log_enabled());
+        let job = manager
+            .add_job("audited", "0 0 1 1 *", "echo")
+            .await
+            .unwrap();
+        let other = manager.add_job("other", "0 0 1 1 *", "echo").await.unwrap();
+        manager.pause_job(&job.id).await.unwrap();
+        manager
+            .run_job_with(&job.id, RunOptions::new().force_if_paused())
+            .await
+            .unwrap();
+        manager.remove_job(&job.id, true).await.unwrap();
+
+        let log = manager.get_audit_log(Some(&job.id), 100).await.unwrap();
+        let kinds: Vec<_> = log
+            .iter()
+            .rev()
+            .map(|event| match &event.kind {
+                SchedulerEvent::JobAdded { .. } => "added",
+                SchedulerEvent::JobPaused { .. } => "paused",
+                SchedulerEvent::JobStarted { .. } => "started",
+                SchedulerEvent::JobCompleted { .. } => "completed",
+                SchedulerEvent::JobRemoved { .. } => "removed",
+                _ => "other",
+            })
+            .collect();
+        assert_eq!(
+            kinds,
+            vec!["added", "paused", "started", "completed", "removed"]
+        );
+
+        let log = manager.get_audit_log(None, 100).await.unwrap();
+        assert_eq!(log.len(), 6);
+        assert!(log
+            .iter()
+            .any(|event| event.kind.job_id() == Some(other.id.as_str())));
+    }
+
+    #[tokio::test]
+    async fn test_min_interval_rejects_frequent_schedule() {
+        let mut manager = create_test_manager();
+        manager.set_min_interval(Some(Duration::from_secs(3600)));
+
+        let result = manager.add_job("too-often", "*/5 * * * *", "echo").await;
+        assert!(matches!(
+            result,
+            Err(CronError::ScheduleTooFrequent {
+                interval_secs: 300,
+                min_interval_secs: 3600
+            })
+        ));
+
+        // Two fire times a minute apart, once a day
+        let result = manager.add_job("burst", "0,1 0 * * *", "echo").await;
+        assert!(matches!(
+            result,
+            Err(CronError::ScheduleTooFrequent {
+                interval_secs: 60,
+                ..
+            })
+        ));
+
+        let result = manager
+            .add_agent_job("agent", "* * * * *", "prompt", create_agent_config())
+            .await;
+        assert!(matches!(result, Err(CronError::ScheduleTooFrequent { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_min_interval_allows_and_update() {
+        let mut manager = create_test_manager();
+        manager.set_min_interval(Some(Duration::from_secs(3600)));
+
+        let job = manager
+            .add_job("hourly", "0 * * * *", "echo hello")
+            .await
+            .unwrap();
+
+        let result = manager
+            .update_job(&job.id, Some("*/10 * * * *"), None, None)
+            .await;
+        assert!(matches!(result, Err(CronError::ScheduleTooFrequent { .. })));
+        let unchanged = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(unchanged.schedule, "0 * * * *");
+
+        // Manual runs are not subject to the interval
+        let execution = manager.run_job(&job.id).await.unwrap();
+        assert!(execution.stdout.contains("hello"));
+    }
+
+    #[tokio::test]
+    async fn test_import_crontab() {
+        let manager = create_test_manager();
+        manager
+            .add_job("backup", "0 0 * * *", "existing.sh")
+            .await
+            .unwrap();
+
+        let content = "\
+SHELL=/bin/bash
+# backup
+0 2 * * * backup.sh --full
+# backup
+0 3 * * * backup.sh --incremental
+
+@hourly echo ping
+";
+        let mut events = manager.subscribe();
+        let jobs = manager.import_crontab(content.as_bytes()).await.unwrap();
+        assert_eq!(jobs.len(), 3);
+        // Each job is added with its env in one go
+        for _ in 0..3 {
+            let event = events.recv().await.unwrap().kind;
+            assert!(
+                matches!(event, SchedulerEvent::JobAdded { .. }),
+                "{:?}",
+                event
+            );
+        }
+        assert!(events.try_recv().is_err());
+
+        assert_eq!(jobs[0].name, "backup-2");
+        assert_eq!(jobs[0].schedule, "0 2 * * *");
+        assert_eq!(jobs[0].command, "backup.sh --full");
+        assert_eq!(jobs[0].env, vec![("SHELL".into(), "/bin/bash".into())]);
+        assert_eq!(jobs[1].name, "backup-3");
+        assert!(jobs[2].name.starts_with("job-"));
+        assert_eq!(jobs[2].schedule, "0 * * * *");
+
+        let stored = manager.get_job_by_name("backup-2").await.unwrap().unwrap();
+        assert_eq!(stored.env.len(), 1);
+        assert_eq!(stored.job_type, JobType::Shell);
+        assert_eq!(manager.list_jobs().await.unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_import_crontab_validates_before_creating() {
+        let manager = create_test_manager();
+
+        let content = "0 2 * * * ok.sh\n99 * * * * broken.sh\n";
+        let result = manager.import_crontab(content.as_bytes()).await;
+        assert!(result.is_err());
+        assert!(manager.list_jobs().await.unwrap().is_empty());
+
+        let mut manager = create_test_manager();
+        manager.set_min_interval(Some(Duration::from_secs(3600)));
+        let content = "0 2 * * * ok.sh\n* * * * * often.sh\n";
+        let result = manager.import_crontab(content.as_bytes()).await;
+        assert!(matches!(result, Err(CronError::ScheduleTooFrequent { .. })));
+        assert!(manager.list_jobs().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_export_crontab_round_trip() {
+        let manager = create_test_manager();
+        let backup = manager
+            .add_job("backup", "0 2 * * *", "backup.sh --full")
+            .await
+            .unwrap();
+        let mut with_env = manager
+            .add_job(
+                "report",
+                "*/15 9-17 * * 1-5",
+                "report.sh | mail -s report ops",
+            )
+            .await
+            .unwrap();
+        with_env.env = vec![("LANG".into(), "C".into())];
+        manager.store.save_job(&with_env).await.unwrap();
+        let paused = manager
+            .add_job("cleanup", "0 3 * * *", "cleanup.sh")
+            .await
+            .unwrap();
+        manager.pause_job(&paused.id).await.unwrap();
+        manager
+            .add_agent_job("review", "0 9 * * 1-5", "Review PRs", create_agent_config())
+            .await
+            .unwrap();
+
+        let exported = manager.export_crontab().await.unwrap();
+        assert!(exported.contains("# 0 3 * * * cleanup.sh"));
+        assert!(exported.contains("# 0 9 * * 1-5 Review PRs"));
+
+        let target = create_test_manager();
+        let imported = target.import_crontab(exported.as_bytes()).await.unwrap();
+        assert_eq!(imported.len(), 2);
+
+        let restored = target.get_job_by_name("backup").await.unwrap().unwrap();
+        assert_eq!(restored.schedule, backup.schedule);
+        assert_eq!(restored.command, backup.command);
+        assert!(restored.env.is_empty());
+
+        let restored = target.get_job_by_name("report").await.unwrap().unwrap();
+        assert_eq!(restored.schedule, "*/15 9-17 * * 1-5");
+        assert_eq!(restored.command, "report.sh | mail -s report ops");
+        assert_eq!(restored.env, vec![("LANG".to_string(), "C".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn test_preview_schedule() {
+        let manager = create_test_manager();
+
+        let times = manager.preview_schedule("0 * * * *", 5).unwrap();
+        assert_eq!(times.len(), 5);
+        assert!(times[0] > Utc::now());
+        assert!(times
+            .windows(2)
+            .all(|w| w[1] - w[0] == chrono::Duration::hours(1)));
+
+        let times = manager.preview_schedule("* * * * *", 5000).unwrap();
+        assert_eq!(times.len(), MAX_PREVIEW_COUNT);
+
+        let err = manager.preview_schedule("61 * * * *", 3).unwrap_err();
+        let add_err = manager
+            .add_job("bad", "61 * * * *", "echo")
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), add_err.to_string());
+    }
+
+    #[tokio::test]
+    async fn test_preview_job() {
+        let manager = create_test_manager();
+        let job = manager
+            .add_job("daily", "30 2 * * *", "echo")
+            .await
+            .unwrap();
+
+        let times = manager.preview_job(&job.id, 3).await.unwrap();
+        assert_eq!(times.len(), 3);
+        assert_eq!(Some(times[0]), job.next_run);
+
+        let result = manager.preview_job("missing", 3).await;
+        assert!(matches!(result, Err(CronError::JobNotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_validate_schedule() {
+        let mut manager = create_test_manager();
+
+        let info = manager.validate_schedule("  */15\t* * * *").unwrap();
+        assert_eq!(info.expression, "*/15\t* * * *");
+        assert_eq!(info.interval_secs, Some(900));
+        assert_eq!(info.min_interval_secs, Some(900));
+        assert!(info.next_run.unwrap() > Utc::now());
+        assert!(!info.description.is_empty());
+
+        assert!(manager.validate_schedule("not a schedule").is_err());
+        manager.set_min_interval(Some(Duration::from_secs(3600)));
+        assert!(matches!(
+            manager.validate_schedule("*/15 * * * *"),
+            Err(CronError::ScheduleTooFrequent { .. })
+        ));
+
+        // Validation never touches the store
+        assert!(manager.list_jobs().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_add_job_returns_schedule_info() {
+        let manager = create_test_manager();
+
+        let job = manager
+            .add_job("informed", "0 9 * * mon-fri", "echo")
+            .await
+            .unwrap();
+        let info = job.schedule_info.clone().unwrap();
+        assert_eq!(job.schedule, "0 9 * * mon-fri");
+        assert_eq!(info.expression, job.schedule);
+        assert_eq!(info.next_run, job.next_run);
+
+        // Computed, not persisted
+        let stored = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert!(stored.schedule_info.is_none());
+    }
+
+    /// Mark a job as overdue so the first scheduler tick picks it up
+    async fn make_due(store: &Arc<MemoryCronStore>, job: &CronJob) {
+        let mut job = job.clone();
+        job.next_run = Some(Utc::now() - chrono::Duration::seconds(1));
+        store.save_job(&job).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_due_jobs_run_concurrently() {
+        let store = Arc::new(MemoryCronStore::new());
+        let manager = CronManager::with_store(store.clone(), "/tmp".to_string());
+
+        let slow = manager
+            .add_job("slow", "* * * * *", "sleep 3")
+            .await
+            .unwrap();
+        let fast = manager
+            .add_job("fast", "* * * * *", "echo fast")
+            .await
+            .unwrap();
+        make_due(&store, &slow).await;
+        make_due(&store, &fast).await;
+
+        let mut rx = manager.subscribe();
+        manager.start().await.unwrap();
+
+        let mut started = HashSet::new();
+        let mut fast_completed = false;
+        tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                match rx.recv().await.unwrap().kind {
+                    SchedulerEvent::JobStarted { job_id, .. } => {
+                        started.insert(job_id);
+                    }
+                    SchedulerEvent::JobCompleted { job_id, .. } if job_id == fast.id => {
+                        fast_completed = true;
+                    }
+                    SchedulerEvent::JobCompleted { job_id, .. } if job_id == slow.id => break,
+                    _ => {}
+                }
+            }
+        })
+        .await
+        .unwrap();
+        manager.stop().await;
+
+        // Both jobs started, and the fast one finished, before the slow one did
+        assert!(started.contains(&slow.id));
+        assert!(started.contains(&fast.id));
+        assert!(fast_completed);
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_wakes_at_next_run() {
+        let store = Arc::new(MemoryCronStore::new());
+        let manager = CronManager::with_store(store.clone(), "/tmp".to_string());
+
+        let mut job = manager.add_job("soon", "* * * * *", "true").await.unwrap();
+        let fire_at = Utc::now() + chrono::Duration::seconds(2);
+        job.next_run = Some(fire_at);
+        store.save_job(&job).await.unwrap();
+
+        let mut rx = manager.subscribe();
+        manager.start().await.unwrap();
+
+        let started_at = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let SchedulerEvent::JobStarted { .. } = rx.recv().await.unwrap().kind {
+                    return Utc::now();
+                }
+            }
+        })
+        .await
+        .expect("job should fire within a couple of seconds of next_run");
+        manager.stop().await;
+
+        assert!(started_at >= fire_at);
+        assert!(started_at - fire_at < chrono::Duration::seconds(2));
+    }
+
+    #[tokio::test]
+    async fn test_start_recovers_interrupted_jobs() {
+        let store = Arc::new(MemoryCronStore::new());
+        let manager = CronManager::with_store(store.clone(), "/tmp".to_string());
+        let job = manager
+            .add_job("crashed", "0 0 1 1 *", "true")
+            .await
+            .unwrap();
+        let mut crashed = job.clone();
+        crashed.status = JobStatus::Running;
+        store.save_job(&crashed).await.unwrap();
+
+        let mut rx = manager.subscribe();
+        manager.start().await.unwrap();
+        manager.stop().await;
+
+        let recovered = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(recovered.status, JobStatus::Active);
+        assert_eq!(recovered.fail_count, 1);
+        assert_eq!(recovered.next_run, job.next_run);
+
+        let history = manager.get_history(&job.id, 10).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].status, ExecutionStatus::Failed);
+        assert_eq!(history[0].error.as_deref(), Some("Interrupted by shutdown"));
+
+        let mut recovered_events = 0;
+        while let Ok(envelope) = rx.try_recv() {
+            let event = envelope.kind;
+            if let SchedulerEvent::JobRecovered {
+                job_id,
+                execution_id,
+                ..
+            } = event
+            {
+                assert_eq!(job_id, job.id);
+                assert_eq!(execution_id, history[0].id);
+                recovered_events += 1;
+            }
+        }
+        assert_eq!(recovered_events, 1);
+    }
+
+    #[tokio::test]
+    async fn test_start_leaves_running_jobs_alone() {
+        let manager = Arc::new(create_test_manager());
+        let job = manager
+            .add_job("long", "0 0 1 1 *", "sleep 30")
+            .await
+            .unwrap();
+        let _handle = run_in_background(&manager, &job.id).await;
+
+        manager.start().await.unwrap();
+        manager.shutdown(Duration::ZERO).await;
+
+        let history = manager.get_history(&job.id, 10).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].status, ExecutionStatus::Cancelled);
+    }
+
+    #[tokio::test]
+    async fn test_execution_records_trigger() {
+        let store = Arc::new(MemoryCronStore::new());
+        let manager = CronManager::with_store(store.clone(), "/tmp".to_string());
+        let job = manager.add_job("due", "0 0 1 1 *", "true").await.unwrap();
+        let due = DateTime::from_timestamp(Utc::now().timestamp() - 1, 0).unwrap();
+        let mut stored = job.clone();
+        stored.next_run = Some(due);
+        store.save_job(&stored).await.unwrap();
+
+        let mut rx = manager.subscribe();
+        manager.start().await.unwrap();
+        let trigger = loop {
+            if let SchedulerEvent::JobStarted { trigger, .. } = rx.recv().await.unwrap().kind {
+                break trigger;
+            }
+        };
+        assert_eq!(trigger, TriggerKind::Scheduled);
+        wait_for_completions(&mut rx, 1).await;
+        manager.stop().await;
+
+        let history = manager.get_history(&job.id, 10).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].trigger, TriggerKind::Scheduled);
+        assert_eq!(history[0].scheduled_for, Some(due));
+        assert!(history[0].started_at >= due);
+
+        let execution = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(execution.trigger, TriggerKind::Manual);
+        assert_eq!(execution.scheduled_for, None);
+    }
+
+    #[tokio::test]
+    async fn test_long_job_fires_once_per_slot() {
+        let store = Arc::new(MemoryCronStore::new());
+        let manager = CronManager::with_store(store.clone(), "/tmp".to_string())
+            .with_tick_interval(Duration::from_secs(2))
+            .unwrap();
+
+        let job = manager
+            .add_job("long", "0 0 1 1 *", "sleep 5")
+            .await
+            .unwrap();
+        make_due(&store, &job).await;
+
+        let mut rx = manager.subscribe();
+        manager.start().await.unwrap();
+
+        // next_run moves past the slot as soon as the job is picked up
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        let stored = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert!(stored.next_run.unwrap() > Utc::now());
+
+        // Several ticks pass while the job is still running
+        tokio::time::sleep(Duration::from_millis(5000)).await;
+        manager.stop().await;
+
+        let mut starts = 0;
+        while let Ok(envelope) = rx.try_recv() {
+            let event = envelope.kind;
+            if let SchedulerEvent::JobStarted { .. } = event {
+                starts += 1;
+            }
+        }
+        assert_eq!(starts, 1);
+    }
+
+    #[tokio::test]
+    async fn test_jitter_delays_dispatch() {
+        let store = Arc::new(MemoryCronStore::new());
+        let manager = CronManager::with_store(store.clone(), "/tmp".to_string());
+        let job = manager
+            .add_job_with_options(
+                "jittered",
+                "0 0 1 1 *",
+                "true",
+                JobOptions::new().with_jitter_ms(1000),
+            )
+            .await
+            .unwrap();
+        assert_eq!(job.jitter_ms, Some(1000));
+
+        let fire_at = Utc::now() + chrono::Duration::milliseconds(200);
+        let mut due = job.clone();
+        due.next_run = Some(fire_at);
+        store.save_job(&due).await.unwrap();
+        let dispatch_at = due.dispatch_time(fire_at);
+
+        let mut rx = manager.subscribe();
+        manager.start().await.unwrap();
+        let started_at = tokio::time::timeout(Duration::from_secs(3), async {
+            loop {
+                if let SchedulerEvent::JobStarted { .. } = rx.recv().await.unwrap().kind {
+                    return Utc::now();
+                }
+            }
+        })
+        .await
+        .unwrap();
+        wait_for_completions(&mut rx, 1).await;
+        manager.stop().await;
+
+        assert!(started_at >= dispatch_at);
+        assert!(started_at - dispatch_at < chrono::Duration::milliseconds(500));
+
+        // The execution records the real start; next_run stays nominal
+        let history = manager.get_history(&job.id, 1).await.unwrap();
+        assert!(history[0].started_at >= dispatch_at);
+        let stored = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(stored.next_run, job.next_run);
+    }
+
+    /// A window from a minute ago until `ahead` from now
+    fn window_around_now(ahead: chrono::Duration) -> TimeWindow {
+        let now = Utc::now().time();
+        TimeWindow::new(now - chrono::Duration::minutes(1), now + ahead).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_blackout_skips_run() {
+        let store = Arc::new(MemoryCronStore::new());
+        let manager = CronManager::with_store(store.clone(), "/tmp".to_string());
+        let job = manager
+            .add_job_with_options(
+                "blacked-out",
+                "0 0 1 1 *",
+                "true",
+                JobOptions::new().with_blackout(window_around_now(chrono::Duration::minutes(5))),
+            )
+            .await
+            .unwrap();
+        make_due(&store, &job).await;
+
+        manager.start().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        manager.stop().await;
+
+        let history = manager.get_history(&job.id, 10).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].status, ExecutionStatus::Skipped);
+        assert!(history[0]
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("blackout window"));
+        let job = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert!(job.next_run.unwrap() > Utc::now());
+    }
+
+    #[tokio::test]
+    async fn test_snooze_skips_scheduled_runs() {
+        let store = Arc::new(MemoryCronStore::new());
+        let manager = CronManager::with_store(store.clone(), "/tmp".to_string());
+        let job = manager
+            .add_job("snoozed", "0 0 1 1 *", "true")
+            .await
+            .unwrap();
+        assert!(matches!(
+            manager.snooze_job(&job.id, 0).await,
+            Err(CronError::InvalidConfig(_))
+        ));
+        let job = manager.snooze_job(&job.id, 2).await.unwrap();
+        assert_eq!(job.snoozed_runs, 2);
+        make_due(&store, &job).await;
+
+        manager.start().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        manager.stop().await;
+
+        let history = manager.get_history(&job.id, 10).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].status, ExecutionStatus::Skipped);
+        assert!(history[0].error.as_deref().unwrap().contains("Snoozed"));
+        let listed = manager.list_jobs().await.unwrap();
+        assert_eq!(listed[0].snoozed_runs, 1);
+        assert!(listed[0].next_run.unwrap() > Utc::now());
+
+        // Manual runs go ahead and leave the snooze alone
+        let execution = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Success);
+        let job = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(job.snoozed_runs, 1);
+
+        let job = manager.unsnooze_job(&job.id).await.unwrap();
+        assert_eq!(job.snoozed_runs, 0);
+        assert!(job.snoozed_until.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_snooze_until() {
+        let store = Arc::new(MemoryCronStore::new());
+        let manager = CronManager::with_store(store.clone(), "/tmp".to_string());
+        let job = manager
+            .add_job("snoozed", "0 0 1 1 *", "true")
+            .await
+            .unwrap();
+        let past = Utc::now() - chrono::Duration::minutes(1);
+        assert!(manager.snooze_until(&job.id, past).await.is_err());
+
+        let until = Utc::now() + chrono::Duration::hours(1);
+        let job = manager.snooze_until(&job.id, until).await.unwrap();
+        assert_eq!(job.snoozed_until, Some(until));
+        make_due(&store, &job).await;
+
+        manager.start().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        manager.stop().await;
+
+        let history = manager.get_history(&job.id, 10).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].status, ExecutionStatus::Skipped);
+        // The next run is past the snooze, so it is used up
+        let job = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert!(job.snoozed_until.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_blackout_defers_run() {
+        let store = Arc::new(MemoryCronStore::new());
+        let manager = CronManager::with_store(store.clone(), "/tmp".to_string())
+            .with_blackouts(vec![window_around_now(chrono::Duration::seconds(1))]);
+        let window_end = Utc::now()
+            .date_naive()
+            .and_time(manager.blackouts()[0].end)
+            .and_utc();
+        let job = manager
+            .add_job_with_options(
+                "deferred",
+                "0 0 1 1 *",
+                "true",
+                JobOptions::new().with_blackout_policy(BlackoutPolicy::Defer),
+            )
+            .await
+            .unwrap();
+        make_due(&store, &job).await;
+
+        let mut rx = manager.subscribe();
+        manager.start().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let deferred = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(deferred.next_run, Some(window_end));
+
+        wait_for_completions(&mut rx, 1).await;
+        manager.stop().await;
+        let history = manager.get_history(&job.id, 10).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert!(history[0].started_at >= window_end);
+    }
+
+    #[tokio::test]
+    async fn test_allowed_window() {
+        let manager = create_test_manager();
+        let window = TimeWindow::parse("09:00-10:00").unwrap();
+        let job = manager
+            .add_job_with_options(
+                "business",
+                "*/30 * * * *",
+                "true",
+                JobOptions::new().with_allowed_window(window.clone()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(job.allowed_window, Some(window.clone()));
+
+        let preview = manager.preview_job(&job.id, 6).await.unwrap();
+        assert_eq!(preview.len(), 6);
+        for at in &preview {
+            assert!(window.contains(*at));
+        }
+        assert_eq!(job.next_run, Some(preview[0]));
+
+        let inverted = TimeWindow::parse("17:00-09:00").unwrap();
+        let err = manager
+            .add_job_with_options(
+                "inverted",
+                "*/30 * * * *",
+                "true",
+                JobOptions::new().with_allowed_window(inverted),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, CronError::InvalidConfig(_)));
+        assert!(err.to_string().contains("inverted"));
+    }
+
+    #[tokio::test]
+    async fn test_manual_run_in_blackout_needs_override() {
+        let manager = create_test_manager()
+            .with_blackouts(vec![window_around_now(chrono::Duration::minutes(5))]);
+        let job = manager
+            .add_job("maintenance", "* * * * *", "echo ok")
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            manager.run_job(&job.id).await,
+            Err(CronError::InBlackout(_))
+        ));
+
+        let execution = manager
+            .run_job_with(
+                &job.id,
+                RunOptions {
+                    ignore_blackout: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Success);
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_does_not_double_fire() {
+        let store = Arc::new(MemoryCronStore::new());
+        let manager = CronManager::with_store(store.clone(), "/tmp".to_string());
+
+        // A yearly schedule can't come due again while the test runs
+        let job = manager
+            .add_job("once", "0 0 1 1 *", "sleep 1")
+            .await
+            .unwrap();
+        make_due(&store, &job).await;
+
+        let mut rx = manager.subscribe();
+        manager.start().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        manager.stop().await;
+
+        let mut starts = 0;
+        while let Ok(envelope) = rx.try_recv() {
+            let event = envelope.kind;
+            if let SchedulerEvent::JobStarted { .. } = event {
+                starts += 1;
+            }
+        }
+        assert_eq!(starts, 1);
+
+        let job = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(job.run_count, 1);
+        assert!(job.next_run.unwrap() > Utc::now());
+    }
+
+    #[tokio::test]
+    async fn test_tick_interval() {
+        let manager = create_test_manager();
+        assert_eq!(manager.tick_interval(), DEFAULT_TICK_INTERVAL);
+
+        assert!(matches!(
+            create_test_manager().with_tick_interval(Duration::ZERO),
+            Err(CronError::InvalidConfig(_))
+        ));
+
+        let manager = manager.with_tick_interval(Duration::from_secs(1)).unwrap();
+        assert_eq!(manager.tick_interval(), Duration::from_secs(1));
+
+        let mut rx = manager.subscribe();
+        manager.start().await.unwrap();
+        match rx.recv().await.unwrap().kind {
+            SchedulerEvent::Started { tick_interval, .. } => {
+                assert_eq!(tick_interval, Duration::from_secs(1))
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+        manager.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_tick_interval_picks_up_external_changes() {
+        let store = Arc::new(MemoryCronStore::new());
+        let manager = CronManager::with_store(store.clone(), "/tmp".to_string())
+            .with_tick_interval(Duration::from_secs(1))
+            .unwrap();
+        let mut rx = manager.subscribe();
+        manager.start().await.unwrap();
+
+        // Written straight to the store, so only the next tick can notice it
+        let mut job = CronJob::new("external", "* * * * *", "true");
+        job.next_run = Some(Utc::now());
+        store.save_job(&job).await.unwrap();
+
+        tokio::time::timeout(Duration::from_secs(3), async {
+            loop {
+                if let SchedulerEvent::JobStarted { job_id, .. } = rx.recv().await.unwrap().kind {
+                    assert_eq!(job_id, job.id);
+                    break;
+                }
+            }
+        })
+        .await
+        .unwrap();
+        manager.stop().await;
+    }
+
+    /// Start a manual run in the background and wait until it is in flight
+    async fn run_in_background(
+        manager: &Arc<CronManager>,
+        job_id: &str,
+    ) -> tokio::task::JoinHandle<Result<JobExecution>> {
+        let mut rx = manager.subscribe();
+        let (manager, job_id) = (manager.clone(), job_id.to_string());
+        let handle = tokio::spawn(async move { manager.run_job(&job_id).await });
+        while !matches!(
+            rx.recv().await.unwrap().kind,
+            SchedulerEvent::JobStarted { .. }
+        ) {}
+        handle
+    }
+
+    #[tokio::test]
+    async fn test_cancel_execution() {
+        let manager = Arc::new(create_test_manager());
+        let job = manager
+            .add_job("long", "* * * * *", "sleep 30")
+            .await
+            .unwrap();
+
+        let mut rx = manager.subscribe();
+        let (runner, job_id) = (manager.clone(), job.id.clone());
+        let handle = tokio::spawn(async move { runner.run_job(&job_id).await });
+        let execution_id = loop {
+            if let SchedulerEvent::JobStarted { execution_id, .. } = rx.recv().await.unwrap().kind {
+                break execution_id;
+            }
+        };
+
+        let started = std::time::Instant::now();
+        manager.cancel_execution(&execution_id).await.unwrap();
+        let execution = handle.await.unwrap().unwrap();
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert_eq!(execution.id, execution_id);
+        assert_eq!(execution.status, ExecutionStatus::Cancelled);
+
+        let (mut cancelled, mut failed) = (false, false);
+        while let Ok(envelope) = rx.try_recv() {
+            let event = envelope.kind;
+            cancelled |= matches!(event, SchedulerEvent::JobCancelled { .. });
+            failed |= matches!(event, SchedulerEvent::JobFailed { .. });
+        }
+        assert!(cancelled);
+        assert!(!failed);
+
+        // A cancel isn't counted as a failure
+        let job = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(job.fail_count, 0);
+        assert_eq!(job.consecutive_failures, 0);
+        assert!(job.last_error.is_none());
+
+        // Finished executions can't be cancelled again
+        assert!(matches!(
+            manager.cancel_execution(&execution_id).await,
+            Err(CronError::ExecutionNotRunning(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_run_job_detached() {
+        let manager = create_test_manager();
+        let job = manager
+            .add_job("long", "* * * * *", "sleep 30")
+            .await
+            .unwrap();
+
+        let mut rx = manager.subscribe();
+        let started = std::time::Instant::now();
+        let execution_id = manager.run_job_detached(&job.id).await.unwrap();
+        assert!(started.elapsed() < Duration::from_secs(1));
+        loop {
+            if let SchedulerEvent::JobStarted {
+                execution_id: id, ..
+            } = rx.recv().await.unwrap().kind
+            {
+                assert_eq!(id, execution_id);
+                break;
+            }
+        }
+
+        let running = manager.running_executions();
+        assert_eq!(running.len(), 1);
+        assert_eq!(running[0].execution_id, execution_id);
+        assert!(manager
+            .get_execution(&execution_id)
+            .await
+            .unwrap()
+            .is_none());
+
+        manager.cancel_execution(&execution_id).await.unwrap();
+        loop {
+            if let SchedulerEvent::JobCancelled { .. } = rx.recv().await.unwrap().kind {
+                break;
+            }
+        }
+        let execution = loop {
+            match manager.get_execution(&execution_id).await.unwrap() {
+                Some(execution) => break execution,
+                None => tokio::time::sleep(Duration::from_millis(10)).await,
+            }
+        };
+        assert_eq!(execution.status, ExecutionStatus::Cancelled);
+        assert!(manager.running_executions().is_empty());
+
+        assert!(matches!(
+            manager.run_job_detached("missing").await,
+            Err(CronError::JobNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_heartbeats_while_running() {
+        let manager = create_test_manager()
+            .with_heartbeat_interval(Some(Duration::from_millis(500)))
+            .unwrap();
+        let job = manager
+            .add_job("batch", "0 0 1 1 *", "sleep 2")
+            .await
+            .unwrap();
+
+        let mut rx = manager.subscribe();
+        let execution = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Success);
+
+        let mut heartbeats = Vec::new();
+        while let Ok(envelope) = rx.try_recv() {
+            if let SchedulerEvent::JobHeartbeat {
+                job_id,
+                execution_id,
+                elapsed_ms,
+                ..
+            } = envelope.kind
+            {
+                assert_eq!(job_id, job.id);
+                assert_eq!(execution_id, execution.id);
+                heartbeats.push(elapsed_ms);
+            }
+        }
+        assert!(!heartbeats.is_empty());
+        assert!(heartbeats.len() <= 4);
+        assert!(heartbeats.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(heartbeats[0] >= 400);
+
+        // Heartbeats stop with the execution
+        tokio::time::sleep(Duration::from_millis(700)).await;
+        assert!(rx.try_recv().is_err());
+
+        assert!(matches!(
+            create_test_manager().with_heartbeat_interval(Some(Duration::ZERO)),
+            Err(CronError::InvalidConfig(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_running_executions() {
+        let manager = Arc::new(create_test_manager());
+        let job = manager
+            .add_job("long", "* * * * *", "sleep 30")
+            .await
+            .unwrap();
+        assert!(manager.running_executions().is_empty());
+        assert!(!manager.is_job_running(&job.id));
+
+        let handle = run_in_background(&manager, &job.id).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let running = manager.running_executions();
+        assert_eq!(running.len(), 1);
+        assert_eq!(running[0].job_id, job.id);
+        assert_eq!(running[0].job_name, "long");
+        assert!(running[0].elapsed_ms >= 50);
+        assert!(manager.is_job_running(&job.id));
+
+        manager
+            .cancel_execution(&running[0].execution_id)
+            .await
+            .unwrap();
+        handle.await.unwrap().unwrap();
+        assert!(manager.running_executions().is_empty());
+        assert!(!manager.is_job_running(&job.id));
+    }
+
+    #[tokio::test]
+    async fn test_running_execution_removed_when_aborted() {
+        let manager = Arc::new(create_test_manager());
+        let job = manager
+            .add_job("long", "* * * * *", "sleep 30")
+            .await
+            .unwrap();
+
+        let handle = run_in_background(&manager, &job.id).await;
+        assert!(manager.is_job_running(&job.id));
+        handle.abort();
+        let _ = handle.await;
+        assert!(manager.running_executions().is_empty());
+        assert!(!manager.is_job_running(&job.id));
+    }
+
+    #[tokio::test]
+    async fn test_stop_wakes_loop() {
+        let manager = create_test_manager();
+        let mut rx = manager.subscribe();
+        manager.start().await.unwrap();
+        assert!(manager.is_running().await);
+
+        // Returns well before the 60 second tick
+        tokio::time::timeout(Duration::from_secs(1), manager.stop())
+            .await
+            .unwrap();
+        assert!(!manager.is_running().await);
+
+        let mut stopped = false;
+        while let Ok(envelope) = rx.try_recv() {
+            let event = envelope.kind;
+            stopped |= matches!(event, SchedulerEvent::Stopped { .. });
+        }
+        assert!(stopped);
+
+        // Can be restarted
+        manager.start().await.unwrap();
+        assert!(manager.is_running().await);
+        manager.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_waits_for_executions() {
+        let manager = Arc::new(create_test_manager());
+        let job = manager
+            .add_job("short", "* * * * *", "sleep 0.3")
+            .await
+            .unwrap();
+        manager.start().await.unwrap();
+        let handle = run_in_background(&manager, &job.id).await;
+
+        let started = std::time::Instant::now();
+        let cancelled = manager.shutdown(Duration::from_secs(5)).await;
+        assert!(cancelled.is_empty());
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(!manager.is_running().await);
+        assert!(manager.running_executions().is_empty());
+
+        let execution = handle.await.unwrap().unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Success);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_cancels_after_timeout() {
+        let manager = Arc::new(create_test_manager());
+        let job = manager
+            .add_job("long", "* * * * *", "sleep 30")
+            .await
+            .unwrap();
+        manager.start().await.unwrap();
+        let handle = run_in_background(&manager, &job.id).await;
+        let mut rx = manager.subscribe();
+
+        let started = std::time::Instant::now();
+        let cancelled = manager.shutdown(Duration::from_millis(200)).await;
+        assert_eq!(cancelled.len(), 1);
+        assert!(started.elapsed() < Duration::from_secs(2));
+
+        let execution = handle.await.unwrap().unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Cancelled);
+
+        // Stopped follows the cancellation
+        let mut events = Vec::new();
+        while let Ok(envelope) = rx.try_recv() {
+            let event = envelope.kind;
+            events.push(event);
+        }
+        let cancelled_at = events
+            .iter()
+            .position(|event| matches!(event, SchedulerEvent::JobCancelled { .. }))
+            .unwrap();
+        let stopped_at = events
+            .iter()
+            .position(|event| matches!(event, SchedulerEvent::Stopped { .. }))
+            .unwrap();
+        assert!(cancelled_at < stopped_at);
+    }
+
+    #[tokio::test]
+    async fn test_pause_during_run_is_kept() {
+        let manager = Arc::new(create_test_manager());
+        let job = manager
+            .add_job("sleepy", "* * * * *", "sleep 0.5")
+            .await
+            .unwrap();
+
+        let handle = run_in_background(&manager, &job.id).await;
+        manager.pause_job(&job.id).await.unwrap();
+        let execution = handle.await.unwrap().unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Success);
+
+        let job = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(job.status, JobStatus::Paused);
+        assert_eq!(job.run_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_update_during_run_is_kept() {
+        let manager = Arc::new(create_test_manager());
+        let job = manager
+            .add_job("sleepy", "* * * * *", "sleep 0.5")
+            .await
+            .unwrap();
+
+        let handle = run_in_background(&manager, &job.id).await;
+        manager
+            .update_job(&job.id, Some("0 3 * * *"), Some("echo updated"), None)
+            .await
+            .unwrap();
+        handle.await.unwrap().unwrap();
+
+        let job = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(job.schedule, "0 3 * * *");
+        assert_eq!(job.command, "echo updated");
+        assert_eq!(job.status, JobStatus::Active);
+        assert_eq!(job.run_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_remove_during_run_is_kept() {
+        let manager = Arc::new(create_test_manager());
+        let job = manager
+            .add_job("sleepy", "* * * * *", "sleep 0.5")
+            .await
+            .unwrap();
+
+        let handle = run_in_background(&manager, &job.id).await;
+        manager.remove_job(&job.id, true).await.unwrap();
+        handle.await.unwrap().unwrap();
+
+        assert!(manager.get_job(&job.id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cancel_unknown_execution() {
+        let manager = create_test_manager();
+        assert!(matches!(
+            manager.cancel_execution("missing").await,
+            Err(CronError::ExecutionNotRunning(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_job_executions() {
+        let manager = Arc::new(create_test_manager());
+        let job = manager
+            .add_job("long", "* * * * *", "sleep 30")
+            .await
+            .unwrap();
+        let first = run_in_background(&manager, &job.id).await;
+        let second = run_in_background(&manager, &job.id).await;
+
+        let started = std::time::Instant::now();
+        let cancelled = manager.cancel_job_executions(&job.id).await.unwrap();
+        assert_eq!(cancelled.len(), 2);
+        for handle in [first, second] {
+            let execution = handle.await.unwrap().unwrap();
+            assert_eq!(execution.status, ExecutionStatus::Cancelled);
+        }
+        assert!(started.elapsed() < Duration::from_secs(1));
+
+        assert!(manager
+            .cancel_job_executions(&job.id)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(matches!(
+            manager.cancel_job_executions("missing").await,
+            Err(CronError::JobNotFound(_))
+        ));
+    }
+
+    async fn add_policy_job(
+        manager: &CronManager,
+        store: &Arc<MemoryCronStore>,
+        policy: ConcurrencyPolicy,
+    ) -> CronJob {
+        let job = manager
+            .add_job(&policy.to_string(), "* * * * *", "sleep 1")
+            .await
+            .unwrap()
+            .with_concurrency_policy(policy);
+        store.save_job(&job).await.unwrap();
+        job
+    }
+
+    #[tokio::test]
+    async fn test_concurrency_policy_forbid() {
+        let store = Arc::new(MemoryCronStore::new());
+        let manager = Arc::new(CronManager::with_store(store.clone(), "/tmp".to_string()));
+        let job = add_policy_job(&manager, &store, ConcurrencyPolicy::Forbid).await;
+
+        let first = run_in_background(&manager, &job.id).await;
+        let mut rx = manager.subscribe();
+        let second = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(second.status, ExecutionStatus::Skipped);
+        match rx.recv().await.unwrap().kind {
+            SchedulerEvent::JobSkipped { job_id, reason, .. } => {
+                assert_eq!(job_id, job.id);
+                assert!(reason.contains("still in progress"));
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+
+        let first = first.await.unwrap().unwrap();
+        assert_eq!(first.status, ExecutionStatus::Success);
+
+        let history = manager.get_history(&job.id, 10).await.unwrap();
+        assert_eq!(history.len(), 2);
+        let job = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(job.status, JobStatus::Active);
+        assert_eq!(job.run_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_concurrency_policy_allow() {
+        let store = Arc::new(MemoryCronStore::new());
+        let manager = Arc::new(CronManager::with_store(store.clone(), "/tmp".to_string()));
+        let job = add_policy_job(&manager, &store, ConcurrencyPolicy::Allow).await;
+
+        let first = run_in_background(&manager, &job.id).await;
+        let second = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(second.status, ExecutionStatus::Success);
+        assert_eq!(
+            first.await.unwrap().unwrap().status,
+            ExecutionStatus::Success
+        );
+    }
+
+    #[tokio::test]
+    async fn test_concurrency_policy_replace() {
+        let store = Arc::new(MemoryCronStore::new());
+        let manager = Arc::new(CronManager::with_store(store.clone(), "/tmp".to_string()));
+        let job = add_policy_job(&manager, &store, ConcurrencyPolicy::Replace).await;
+
+        let first = run_in_background(&manager, &job.id).await;
+        let second = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(second.status, ExecutionStatus::Success);
+
+        let first = first.await.unwrap().unwrap();
+        assert_eq!(first.status, ExecutionStatus::Cancelled);
+        assert!(first.ended_at.unwrap() <= second.ended_at.unwrap());
+
+        let job = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(job.status, JobStatus::Active);
+    }
+
+    #[tokio::test]
+    async fn test_concurrency_policy_queue() {
+        let store = Arc::new(MemoryCronStore::new());
+        let manager = Arc::new(CronManager::with_store(store.clone(), "/tmp".to_string()));
+        let policy = ConcurrencyPolicy::Queue { max_queue: 2 };
+        let mut job = add_policy_job(&manager, &store, policy).await;
+        job.command = "sleep 1.5".to_string();
+        store.save_job(&job).await.unwrap();
+
+        // A slow job firing faster than it runs: one runs, two queue and
+        // the rest are dropped
+        let mut runs = vec![run_in_background(&manager, &job.id).await];
+        for _ in 0..4 {
+            let manager = manager.clone();
+            let job_id = job.id.clone();
+            runs.push(tokio::spawn(async move { manager.run_job(&job_id).await }));
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+
+        let queued = manager.queued_executions();
+        assert_eq!(queued.len(), 2);
+        assert!(queued.iter().all(|q| q.job_id == job.id));
+        assert!(queued[0].scheduled_for <= queued[1].scheduled_for);
+        assert_eq!(manager.running_executions().len(), 1);
+
+        let mut executions = Vec::new();
+        for run in runs {
+            executions.push(run.await.unwrap().unwrap());
+        }
+        let (mut ran, skipped): (Vec<_>, Vec<_>) = executions
+            .into_iter()
+            .partition(|e| e.status == ExecutionStatus::Success);
+        assert_eq!(ran.len(), 3);
+        assert_eq!(skipped.len(), 2);
+        assert!(skipped.iter().all(|e| e.status == ExecutionStatus::Skipped
+            && e.error.as_deref().unwrap().contains("queued")));
+
+        // Serially, each starting once the previous one ended
+        ran.sort_by_key(|e| e.started_at);
+        for pair in ran.windows(2) {
+            assert!(pair[0].ended_at.unwrap() <= pair[1].started_at);
+        }
+        assert!(manager.queued_executions().is_empty());
+        assert!(!manager.is_job_running(&job.id));
+        let job = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(job.status, JobStatus::Active);
+        assert_eq!(job.run_count, 3);
+    }
+
+    #[tokio::test]
+    async fn test_max_concurrent_jobs() {
+        assert!(matches!(
+            create_test_manager().with_max_concurrent_jobs(Some(0)),
+            Err(CronError::InvalidConfig(_))
+        ));
+
+        let store = Arc::new(MemoryCronStore::new());
+        let manager = CronManager::with_store(store.clone(), "/tmp".to_string())
+            .with_max_concurrent_jobs(Some(1))
+            .unwrap();
+        assert_eq!(manager.max_concurrent_jobs(), Some(1));
+
+        let first = manager
+            .add_job("first", "* * * * *", "sleep 1")
+            .await
+            .unwrap();
+        let second = manager
+            .add_job("second", "* * * * *", "true")
+            .await
+            .unwrap();
+        // The earlier fire time is served first
+        let mut job = first.clone();
+        job.next_run = Some(Utc::now() - chrono::Duration::seconds(2));
+        store.save_job(&job).await.unwrap();
+        make_due(&store, &second).await;
+
+        let mut rx = manager.subscribe();
+        manager.start().await.unwrap();
+
+        let mut order = Vec::new();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while order.len() < 4 {
+                match rx.recv().await.unwrap().kind {
+                    SchedulerEvent::JobStarted { job_id, .. } => order.push(("start", job_id)),
+                    SchedulerEvent::JobCompleted { job_id, .. } => order.push(("end", job_id)),
+                    _ => {}
+                }
+            }
+        })
+        .await
+        .unwrap();
+        manager.stop().await;
+
+        assert_eq!(
+            order,
+            vec![
+                ("start", first.id.clone()),
+                ("end", first.id.clone()),
+                ("start", second.id.clone()),
+                ("end", second.id.clone()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_priority_order_under_limit() {
+        let store = Arc::new(MemoryCronStore::new());
+        let manager = CronManager::with_store(store.clone(), "/tmp".to_string())
+            .with_max_concurrent_jobs(Some(1))
+            .unwrap();
+        let mut names = HashMap::new();
+        for (name, priority) in [("low", 0), ("critical", 10), ("medium", 5)] {
+            let job = manager
+                .add_job_with_options(
+                    name,
+                    "* * * * *",
+                    "sleep 0.2",
+                    JobOptions::default().with_priority(priority),
+                )
+                .await
+                .unwrap();
+            assert_eq!(job.priority, priority);
+            make_due(&store, &job).await;
+            names.insert(job.id, name);
+        }
+
+        let mut rx = manager.subscribe();
+        manager.start().await.unwrap();
+        let mut started = Vec::new();
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while started.len() < 3 {
+                if let SchedulerEvent::JobStarted { job_id, .. } = rx.recv().await.unwrap().kind {
+                    started.push(names[&job_id]);
+                }
+            }
+        })
+        .await
+        .unwrap();
+        manager.stop().await;
+        assert_eq!(started, vec!["critical", "medium", "low"]);
+
+        let low = manager.get_job_by_name("low").await.unwrap().unwrap();
+        let low = manager.set_job_priority(&low.id, 20).await.unwrap();
+        assert_eq!(low.priority, 20);
+    }
+
+    #[tokio::test]
+    async fn test_run_job_respects_limit_unless_bypassed() {
+        let manager = Arc::new(
+            create_test_manager()
+                .with_max_concurrent_jobs(Some(1))
+                .unwrap(),
+        );
+        let slow = manager
+            .add_job("slow", "* * * * *", "sleep 1")
+            .await
+            .unwrap();
+        let fast = manager.add_job("fast", "* * * * *", "true").await.unwrap();
+
+        let running = run_in_background(&manager, &slow.id).await;
+
+        let bypassed = manager
+            .run_job_with(
+                &fast.id,
+                RunOptions {
+                    bypass_limit: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert!(!running.is_finished());
+
+        let limited = manager.run_job(&fast.id).await.unwrap();
+        let slow_execution = running.await.unwrap().unwrap();
+        assert!(bypassed.ended_at.unwrap() < slow_execution.ended_at.unwrap());
+        assert!(limited.started_at >= slow_execution.ended_at.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_retry_until_exhausted() {
+        let store = Arc::new(MemoryCronStore::new());
+        let manager = CronManager::with_store(store.clone(), "/tmp".to_string());
+        let job = manager
+            .add_job("flaky", "* * * * *", "exit 1")
+            .await
+            .unwrap()
+            .with_retries(2, 10, 2.0);
+        store.save_job(&job).await.unwrap();
+
+        let mut rx = manager.subscribe();
+        let execution = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Failed);
+        assert_eq!(execution.attempt, 3);
+
+        let mut delays = Vec::new();
+        while let Ok(envelope) = rx.try_recv() {
+            let event = envelope.kind;
+            if let SchedulerEvent::JobRetrying {
+                attempt, delay_ms, ..
+            } = event
+            {
+                delays.push((attempt, delay_ms));
+            }
+        }
+        assert_eq!(delays, vec![(2, 10), (3, 20)]);
+
+        let mut attempts: Vec<(u32, TriggerKind)> = manager
+            .get_history(&job.id, 10)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|e| (e.attempt, e.trigger))
+            .collect();
+        attempts.sort_unstable_by_key(|(attempt, _)| *attempt);
+        assert_eq!(
+            attempts,
+            vec![
+                (1, TriggerKind::Manual),
+                (2, TriggerKind::Retry { attempt: 2 }),
+                (3, TriggerKind::Retry { attempt: 3 }),
+            ]
+        );
+        let filter = ExecutionFilter::new().with_trigger(TriggerKind::Retry { attempt: 0 });
+        let retries = manager.query_history(&job.id, &filter).await.unwrap();
+        assert_eq!(retries.len(), 2);
+
+        let job = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(job.fail_count, 1);
+        assert_eq!(job.run_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_retry_succeeds_on_later_attempt() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(MemoryCronStore::new());
+        let manager =
+            CronManager::with_store(store.clone(), dir.path().to_string_lossy().to_string());
+        let job = manager
+            .add_job(
+                "eventually",
+                "* * * * *",
+                "test -f marker && exit 0; touch marker; exit 1",
+            )
+            .await
+            .unwrap()
+            .with_retries(3, 10, 1.0);
+        store.save_job(&job).await.unwrap();
+
+        let execution = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Success);
+        assert_eq!(execution.attempt, 2);
+        assert_eq!(manager.get_history(&job.id, 10).await.unwrap().len(), 2);
+
+        let job = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(job.run_count, 1);
+        assert_eq!(job.fail_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_auto_pause_after_consecutive_failures() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(MemoryCronStore::new());
+        let manager =
+            CronManager::with_store(store.clone(), dir.path().to_string_lossy().to_string());
+        let job = manager
+            .add_job("broken", "* * * * *", "test -f fixed")
+            .await
+            .unwrap()
+            .with_auto_pause_after(Some(2));
+        store.save_job(&job).await.unwrap();
+
+        let mut rx = manager.subscribe();
+        manager.run_job(&job.id).await.unwrap();
+        let stored = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(stored.consecutive_failures, 1);
+        assert_eq!(stored.status, JobStatus::Active);
+
+        manager.run_job(&job.id).await.unwrap();
+        let stored = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(stored.consecutive_failures, 2);
+        assert_eq!(stored.status, JobStatus::Paused);
+
+        let mut auto_paused = Vec::new();
+        while let Ok(envelope) = rx.try_recv() {
+            let event = envelope.kind;
+            if let SchedulerEvent::JobAutoPaused {
+                job_id, failures, ..
+            } = event
+            {
+                auto_paused.push((job_id, failures));
+            }
+        }
+        assert_eq!(auto_paused, vec![(job.id.clone(), 2)]);
+
+        // Operators can still test a fix manually; the job stays paused
+        std::fs::write(dir.path().join("fixed"), "").unwrap();
+        assert!(matches!(
+            manager.run_job(&job.id).await,
+            Err(CronError::JobPaused(_))
+        ));
+        let execution = manager
+            .run_job_with(&job.id, RunOptions::new().force_if_paused())
+            .await
+            .unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Success);
+        let stored = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(stored.consecutive_failures, 0);
+        assert_eq!(stored.status, JobStatus::Paused);
+
+        let resumed = manager.resume_job(&job.id).await.unwrap();
+        assert_eq!(resumed.status, JobStatus::Active);
+    }
+
+    #[tokio::test]
+    async fn test_resume_resets_consecutive_failures() {
+        let store = Arc::new(MemoryCronStore::new());
+        let manager = CronManager::with_store(store.clone(), "/tmp".to_string());
+        let job = manager
+            .add_job("failing", "* * * * *", "exit 1")
+            .await
+            .unwrap()
+            .with_auto_pause_after(Some(1));
+        store.save_job(&job).await.unwrap();
+
+        manager.run_job(&job.id).await.unwrap();
+        assert!(manager.get_job(&job.id).await.unwrap().unwrap().is_paused());
+
+        let resumed = manager.resume_job(&job.id).await.unwrap();
+        assert_eq!(resumed.consecutive_failures, 0);
+    }
+
+    /// Add an every-minute job whose last five occurrences were missed
+    async fn add_missed_job(
+        manager: &CronManager,
+        store: &Arc<MemoryCronStore>,
+        policy: MisfirePolicy,
+    ) -> CronJob {
+        let now = Utc::now().timestamp();
+        let minute = DateTime::from_timestamp(now - now % 60, 0).unwrap();
+        let mut job = manager
+            .add_job("missed", "* * * * *", "true")
+            .await
+            .unwrap()
+            .with_misfire_policy(policy);
+        job.next_run = Some(minute - chrono::Duration::minutes(4));
+        store.save_job(&job).await.unwrap();
+        job
+    }
+
+    async fn count_by_status(
+        manager: &CronManager,
+        job_id: &str,
+        status: ExecutionStatus,
+    ) -> usize {
+        manager
+            .get_history(job_id, 100)
+            .await
+            .unwrap()
+            .iter()
+            .filter(|e| e.status == status)
+            .count()
+    }
+
+    /// Wait for `count` JobCompleted events
+    async fn wait_for_completions(rx: &mut broadcast::Receiver<EventEnvelope>, count: usize) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            let mut completed = 0;
+            while completed < count {
+                if let SchedulerEvent::JobCompleted { .. } = rx.recv().await.unwrap().kind {
+                    completed += 1;
+                }
+            }
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_misfire_skip() {
+        let store = Arc::new(MemoryCronStore::new());
+        let manager = CronManager::with_store(store.clone(), "/tmp".to_string());
+        let job = add_missed_job(&manager, &store, MisfirePolicy::Skip).await;
+
+        manager.start().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        manager.stop().await;
+
+        assert_eq!(
+            count_by_status(&manager, &job.id, ExecutionStatus::Skipped).await,
+            5
+        );
+        assert_eq!(
+            count_by_status(&manager, &job.id, ExecutionStatus::Success).await,
+            0
+        );
+        let job = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert!(job.next_run.unwrap() > Utc::now());
+        assert_eq!(job.run_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_pause_scheduler_holds_due_jobs() {
+        let store = Arc::new(MemoryCronStore::new());
+        let manager = CronManager::with_store(store.clone(), "/tmp".to_string());
+        let job = manager
+            .add_job("due", "* * * * *", "echo due")
+            .await
+            .unwrap();
+
+        let mut rx = manager.subscribe();
+        let mut state_rx = manager.subscribe();
+        assert_eq!(manager.scheduler_state().await, SchedulerState::Stopped);
+        manager.start().await.unwrap();
+        manager.pause_scheduler().await;
+        assert_eq!(manager.scheduler_state().await, SchedulerState::Paused);
+
+        // Manual runs still work while paused
+        let execution = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Success);
+
+        make_due(&store, &job).await;
+        manager.runner.wakeup.notify_one();
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(
+            count_by_status(&manager, &job.id, ExecutionStatus::Success).await,
+            1
+        );
+        let stored = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(stored.status, JobStatus::Active);
+
+        manager.resume_scheduler().await;
+        assert_eq!(manager.scheduler_state().await, SchedulerState::Running);
+        wait_for_completions(&mut rx, 2).await;
+        manager.stop().await;
+
+        let mut events = Vec::new();
+        while let Ok(envelope) = state_rx.try_recv() {
+            let event = envelope.kind;
+            events.push(event);
+        }
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, SchedulerEvent::SchedulerPaused { .. })));
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, SchedulerEvent::SchedulerResumed { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_resume_scheduler_applies_misfire_policy() {
+        let store = Arc::new(MemoryCronStore::new());
+        let manager = CronManager::with_store(store.clone(), "/tmp".to_string());
+        manager.start().await.unwrap();
+        manager.pause_scheduler().await;
+        let job = add_missed_job(&manager, &store, MisfirePolicy::Skip).await;
+
+        manager.resume_scheduler().await;
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        manager.stop().await;
+
+        assert_eq!(
+            count_by_status(&manager, &job.id, ExecutionStatus::Skipped).await,
+            5
+        );
+        assert_eq!(
+            count_by_status(&manager, &job.id, ExecutionStatus::Success).await,
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_misfire_fire_once() {
+        let store = Arc::new(MemoryCronStore::new());
+        let manager = CronManager::with_store(store.clone(), "/tmp".to_string());
+        let job = add_missed_job(&manager, &store, MisfirePolicy::FireOnce).await;
+
+        let mut rx = manager.subscribe();
+        manager.start().await.unwrap();
+        wait_for_completions(&mut rx, 1).await;
+        manager.stop().await;
+
+        assert_eq!(
+            count_by_status(&manager, &job.id, ExecutionStatus::Skipped).await,
+            4
+        );
+        assert_eq!(
+            count_by_status(&manager, &job.id, ExecutionStatus::Success).await,
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_misfire_catch_up_skips_blackouts() {
+        let store = Arc::new(MemoryCronStore::new());
+        let manager = CronManager::with_store(store.clone(), "/tmp".to_string());
+        let mut job = add_missed_job(&manager, &store, MisfirePolicy::CatchUp { max: 10 }).await;
+        let first = job.next_run.unwrap();
+        let blacked_out = first + chrono::Duration::minutes(1);
+        job.blackouts = vec![TimeWindow::new(
+            blacked_out.time(),
+            (blacked_out + chrono::Duration::minutes(1)).time(),
+        )
+        .unwrap()];
+        store.save_job(&job).await.unwrap();
+
+        let mut rx = manager.subscribe();
+        manager.start().await.unwrap();
+        wait_for_completions(&mut rx, 4).await;
+        manager.stop().await;
+
+        // The run in the blackout would never have fired, so it isn't
+        // caught up
+        let history = manager.get_history(&job.id, 10).await.unwrap();
+        let mut caught_up: Vec<DateTime<Utc>> = history
+            .iter()
+            .filter(|e| e.status == ExecutionStatus::Success)
+            .filter_map(|e| e.scheduled_for)
+            .collect();
+        caught_up.sort();
+        let expected: Vec<DateTime<Utc>> = [0, 2, 3, 4]
+            .into_iter()
+            .map(|i| first + chrono::Duration::minutes(i))
+            .collect();
+        assert_eq!(caught_up, expected);
+        assert_eq!(
+            count_by_status(&manager, &job.id, ExecutionStatus::Skipped).await,
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_misfire_catch_up() {
+        let store = Arc::new(MemoryCronStore::new());
+        let manager = CronManager::with_store(store.clone(), "/tmp".to_string());
+        let job = add_missed_job(&manager, &store, MisfirePolicy::CatchUp { max: 2 }).await;
+
+        let mut rx = manager.subscribe();
+        manager.start().await.unwrap();
+        wait_for_completions(&mut rx, 2).await;
+        manager.stop().await;
+
+        assert_eq!(
+            count_by_status(&manager, &job.id, ExecutionStatus::Skipped).await,
+            3
+        );
+        assert_eq!(
+            count_by_status(&manager, &job.id, ExecutionStatus::Success).await,
+            2
+        );
+
+        // Every missed occurrence is recorded against its nominal fire time
+        let first = job.next_run.unwrap();
+        let mut history = manager.get_history(&job.id, 10).await.unwrap();
+        history.sort_by_key(|e| e.scheduled_for);
+        let recorded: Vec<(Option<DateTime<Utc>>, TriggerKind)> = history
+            .into_iter()
+            .map(|e| (e.scheduled_for, e.trigger))
+            .collect();
+        let expected: Vec<(Option<DateTime<Utc>>, TriggerKind)> = (0..5)
+            .map(|i| {
+                let trigger = if i < 3 {
+                    TriggerKind::Scheduled
+                } else {
+                    TriggerKind::CatchUp
+                };
+                (Some(first + chrono::Duration::minutes(i)), trigger)
+            })
+            .collect();
+        assert_eq!(recorded, expected);
+
+        let job = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(job.run_count, 2);
+        assert!(job.next_run.unwrap() > Utc::now());
+    }
+
+    #[tokio::test]
+    async fn test_add_job_with_window() {
+        let manager = create_test_manager();
+        let start = (Utc::now() + chrono::Duration::days(30)).timestamp();
+        let start = DateTime::from_timestamp(start - start % 3600, 0).unwrap();
+
+        let job = manager
+            .add_job_with_options(
+                "campaign",
+                "0 * * * *",
+                "echo",
+                JobOptions::new()
+                    .with_not_before(start)
+                    .with_not_after(start + chrono::Duration::days(31)),
+            )
+            .await
+            .unwrap();
+        assert_eq!(job.not_before, Some(start));
+        assert_eq!(job.next_run, Some(start));
+        assert_eq!(job.status, JobStatus::Active);
+
+        let err = manager
+            .add_job_with_options(
+                "backwards",
+                "0 * * * *",
+                "echo",
+                JobOptions::new()
+                    .with_not_before(start)
+                    .with_not_after(start - chrono::Duration::days(1)),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, CronError::InvalidConfig(_)));
+    }
+
+    #[tokio::test]
+    async fn test_job_completes_when_window_closes() {
+        let manager = create_test_manager();
+        let job = manager
+            .add_job("ending", "* * * * *", "true")
+            .await
+            .unwrap();
+
+        // Closing the window in the past completes the job immediately
+        let past = Utc::now() - chrono::Duration::minutes(5);
+        let job = manager
+            .set_job_window(&job.id, None, Some(past))
+            .await
+            .unwrap();
+        assert_eq!(job.status, JobStatus::Completed);
+        assert!(job.next_run.is_none());
+
+        // Reopening it reactivates the job
+        let job = manager.set_job_window(&job.id, None, None).await.unwrap();
+        assert_eq!(job.status, JobStatus::Active);
+        assert!(job.next_run.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_max_runs_completes_job() {
+        let store = Arc::new(MemoryCronStore::new());
+        let manager = CronManager::with_store(store.clone(), "/tmp".to_string());
+        let job = manager
+            .add_job("twice", "* * * * *", "true")
+            .await
+            .unwrap()
+            .with_max_runs(Some(2), true);
+        store.save_job(&job).await.unwrap();
+
+        let mut rx = manager.subscribe();
+        manager.run_job(&job.id).await.unwrap();
+        assert!(manager.get_job(&job.id).await.unwrap().unwrap().is_active());
+        manager.run_job(&job.id).await.unwrap();
+
+        let completed = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(completed.status, JobStatus::Completed);
+        assert!(completed.next_run.is_none());
+        assert_eq!(completed.manual_run_count, 2);
+
+        let mut events = Vec::new();
+        while let Ok(envelope) = rx.try_recv() {
+            let event = envelope.kind;
+            if let SchedulerEvent::JobCompletedAllRuns { job_id, runs, .. } = event {
+                events.push((job_id, runs));
+            }
+        }
+        assert_eq!(events, vec![(job.id.clone(), 2)]);
+
+        assert!(matches!(
+            manager.resume_job(&job.id).await,
+            Err(CronError::JobCompleted(_))
+        ));
+        let reset = manager.reset_and_resume_job(&job.id).await.unwrap();
+        assert_eq!(reset.status, JobStatus::Active);
+        assert_eq!(reset.run_count, 0);
+        assert!(reset.next_run.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_max_runs_ignores_manual_runs_when_configured() {
+        let store = Arc::new(MemoryCronStore::new());
+        let manager = CronManager::with_store(store.clone(), "/tmp".to_string());
+        let job = manager
+            .add_job("scheduled-only", "* * * * *", "true")
+            .await
+            .unwrap()
+            .with_max_runs(Some(1), false);
+        store.save_job(&job).await.unwrap();
+
+        manager.run_job(&job.id).await.unwrap();
+        manager.run_job(&job.id).await.unwrap();
+        assert!(manager.get_job(&job.id).await.unwrap().unwrap().is_active());
+
+        // The scheduled run counts and completes the job
+        make_due(&store, &manager.get_job(&job.id).await.unwrap().unwrap()).await;
+        let mut rx = manager.subscribe();
+        manager.start().await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !matches!(
+                rx.recv().await.unwrap().kind,
+                SchedulerEvent::JobCompletedAllRuns { .. }
+            ) {}
+        })
+        .await
+        .unwrap();
+        manager.stop().await;
+
+        let job = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert!(job.is_completed());
+        assert_eq!(job.run_count, 3);
+    }
+
+    #[tokio::test]
+    async fn test_one_shot_job_runs_once() {
+        let manager = create_test_manager();
+        let when = Utc::now() + chrono::Duration::seconds(1);
+        let job = manager
+            .add_one_shot_job("once", when, "true", false)
+            .await
+            .unwrap();
+        assert_eq!(job.next_run, Some(when));
+        assert_eq!(job.schedule_kind, ScheduleKind::At(when));
+        assert_eq!(manager.preview_job(&job.id, 5).await.unwrap(), vec![when]);
+
+        let mut rx = manager.subscribe();
+        manager.start().await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !matches!(
+                rx.recv().await.unwrap().kind,
+                SchedulerEvent::JobCompleted { .. }
+            ) {}
+        })
+        .await
+        .unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        manager.stop().await;
+
+        let job = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert!(job.is_completed());
+        assert!(job.next_run.is_none());
+        assert_eq!(job.run_count, 1);
+        assert_eq!(manager.get_history(&job.id, 10).await.unwrap().len(), 1);
+        assert!(manager.preview_job(&job.id, 5).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_one_shot_job_with_options() {
+        let (manager, _clock, start) = create_mock_clock_manager();
+        let when = start + chrono::Duration::hours(1);
+        let options = JobOptions::new()
+            .with_env("TARGET", "s3")
+            .with_timeout_ms(5_000)
+            .with_tag("once");
+        let job = manager
+            .add_one_shot_job_with_options("once", when, "true", false, options)
+            .await
+            .unwrap();
+        assert_eq!(job.schedule_kind, ScheduleKind::At(when));
+        assert_eq!(job.next_run, Some(when));
+        assert_eq!(job.created_at, start);
+        assert_eq!(job.env, vec![("TARGET".to_string(), "s3".to_string())]);
+        assert_eq!(job.timeout_ms, Some(5_000));
+        assert!(job.has_tag("once"));
+        let info = job.schedule_info.unwrap();
+        assert_eq!(info.next_run, Some(when));
+        assert_eq!(info.interval_secs, None);
+
+        // Options are validated like any other job's
+        let options = JobOptions::new().with_tag("");
+        assert!(manager
+            .add_one_shot_job_with_options("bad", when, "true", false, options)
+            .await
+            .is_err());
+        assert!(matches!(
+            manager.add_one_shot_job("once", when, "true", false).await,
+            Err(CronError::JobExists(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_one_shot_job_in_the_past() {
+        let manager = create_test_manager();
+        let past = Utc::now() - chrono::Duration::minutes(1);
+
+        assert!(matches!(
+            manager.add_one_shot_job("late", past, "true", false).await,
+            Err(CronError::InvalidConfig(_))
+        ));
+
+        let job = manager
+            .add_one_shot_job("late", past, "true", true)
+            .await
+            .unwrap();
+        let mut rx = manager.subscribe();
+        manager.start().await.unwrap();
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while !matches!(
+                rx.recv().await.unwrap().kind,
+                SchedulerEvent::JobStarted { .. }
+            ) {}
+        })
+        .await
+        .expect("past one-shot job should run immediately");
+        manager.stop().await;
+        assert_eq!(job.next_run, Some(past));
+    }
+
+    async fn add_chained_jobs(manager: &CronManager, upstream_command: &str) -> (CronJob, CronJob) {
+        let downstream = manager
+            .add_job("downstream", "@never", "echo downstream")
+            .await
+            .unwrap();
+        let upstream = manager
+            .add_job_with_options(
+                "upstream",
+                "0 0 1 1 *",
+                upstream_command,
+                JobOptions::default().with_trigger("downstream"),
+            )
+            .await
+            .unwrap();
+        (upstream, downstream)
+    }
+
+    #[tokio::test]
+    async fn test_trigger_only_job() {
+        let manager = create_test_manager();
+        let (_, downstream) = add_chained_jobs(&manager, "echo upstream").await;
+        assert_eq!(downstream.schedule_kind, ScheduleKind::Never);
+        assert!(downstream.next_run.is_none());
+        assert!(manager
+            .preview_job(&downstream.id, 3)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let job = manager
+            .update_job(&downstream.id, Some("*/5 * * * *"), None, None)
+            .await
+            .unwrap();
+        assert!(job.next_run.is_some());
+        let job = manager
+            .update_job(&downstream.id, Some("@never"), None, None)
+            .await
+            .unwrap();
+        assert!(job.next_run.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_success_triggers_downstream() {
+        let manager = create_test_manager();
+        let (upstream, downstream) = add_chained_jobs(&manager, "echo upstream").await;
+        let mut rx = manager.subscribe();
+
+        let execution = manager.run_job(&upstream.id).await.unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Success);
+
+        let triggered = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let SchedulerEvent::JobTriggered {
+                    job_id,
+                    upstream_job_id,
+                    upstream_execution_id,
+                    ..
+                } = rx.recv().await.unwrap().kind
+                {
+                    return (job_id, upstream_job_id, upstream_execution_id);
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            triggered,
+            (
+                downstream.id.clone(),
+                upstream.id.clone(),
+                execution.id.clone()
+            )
+        );
+
+        wait_for_completions(&mut rx, 1).await;
+        let history = manager.get_history(&downstream.id, 10).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].status, ExecutionStatus::Success);
+        assert_eq!(
+            history[0].trigger,
+            TriggerKind::Dependency {
+                parent_execution_id: execution.id.clone()
+            }
+        );
+        assert_eq!(history[0].triggered_by, Some(execution.id));
+    }
+
+    #[tokio::test]
+    async fn test_failure_does_not_trigger_downstream() {
+        let manager = create_test_manager();
+        let (upstream, downstream) = add_chained_jobs(&manager, "exit 1").await;
+
+        let execution = manager.run_job(&upstream.id).await.unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Failed);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        assert!(manager
+            .get_history(&downstream.id, 10)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_trigger_validation() {
+        let manager = create_test_manager();
+        let (upstream, downstream) = add_chained_jobs(&manager, "echo upstream").await;
+
+        let result = manager
+            .set_job_triggers(&downstream.id, vec![upstream.name.clone()])
+            .await;
+        match result {
+            Err(CronError::TriggerCycle(cycle)) => {
+                assert_eq!(cycle, "downstream -> upstream -> downstream")
+            }
+            other => panic!("expected a trigger cycle, got {:?}", other),
+        }
+
+        let result = manager
+            .add_job_with_options(
+                "self",
+                "@never",
+                "echo self",
+                JobOptions::default().with_trigger("self"),
+            )
+            .await;
+        assert!(matches!(result, Err(CronError::TriggerCycle(_))));
+
+        let result = manager
+            .set_job_triggers(&upstream.id, vec!["missing".to_string()])
+            .await;
+        assert!(matches!(result, Err(CronError::InvalidConfig(_))));
+
+        // Triggers can also name jobs by id
+        let job = manager
+            .set_job_triggers(&upstream.id, vec![downstream.id.clone()])
+            .await
+            .unwrap();
+        assert_eq!(job.triggers, vec![downstream.id]);
+    }
+
+    async fn wait_for_hook(rx: &mut broadcast::Receiver<EventEnvelope>) -> (String, String) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let SchedulerEvent::JobHookFinished {
+                    execution_id,
+                    hook_execution_id,
+                    ..
+                } = rx.recv().await.unwrap().kind
+                {
+                    return (execution_id, hook_execution_id);
+                }
+            }
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_on_failure_inline_command() {
+        let manager = create_test_manager();
+        let job = manager
+            .add_job_with_options(
+                "flaky",
+                "0 0 1 1 *",
+                "echo boom >&2; exit 3",
+                JobOptions::default().with_on_failure(
+                    "echo \"$CRON_FAILED_JOB $CRON_EXIT_CODE $CRON_ERROR $CRON_EXECUTION_ID\"",
+                ),
+            )
+            .await
+            .unwrap();
+        let mut rx = manager.subscribe();
+
+        let failed = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(failed.status, ExecutionStatus::Failed);
+        let (execution_id, hook_execution_id) = wait_for_hook(&mut rx).await;
+        assert_eq!(execution_id, failed.id);
+
+        let history = manager.get_history(&job.id, 10).await.unwrap();
+        let hook = history
+            .iter()
+            .find(|execution| execution.id == hook_execution_id)
+            .unwrap();
+        assert!(hook.is_hook());
+        assert_eq!(hook.hook_for, Some(failed.id.clone()));
+        assert_eq!(hook.status, ExecutionStatus::Success);
+        assert_eq!(hook.stdout, format!("flaky 3 boom {}\n", failed.id));
+
+        // The hook's success doesn't count as a run of the job
+        let job = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(job.run_count, 0);
+        assert_eq!(job.fail_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_on_failure_job_does_not_recurse() {
+        let manager = create_test_manager();
+        // A hook that fails and names itself as its own hook
+        let escalate = manager
+            .add_job_with_options(
+                "escalate",
+                "@never",
+                "exit 1",
+                JobOptions::default().with_on_failure("escalate"),
+            )
+            .await
+            .unwrap();
+        let job = manager
+            .add_job_with_options(
+                "flaky",
+                "0 0 1 1 *",
+                "exit 1",
+                JobOptions::default().with_on_failure("escalate"),
+            )
+            .await
+            .unwrap();
+        let mut rx = manager.subscribe();
+
+        manager.run_job(&job.id).await.unwrap();
+        wait_for_hook(&mut rx).await;
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let history = manager.get_history(&escalate.id, 10).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].status, ExecutionStatus::Failed);
+        assert!(history[0].is_hook());
+        let escalate = manager.get_job(&escalate.id).await.unwrap().unwrap();
+        assert_eq!(escalate.fail_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_on_failure_not_run_on_success() {
+        let manager = create_test_manager();
+        let job = manager.add_job("ok", "0 0 1 1 *", "echo ok").await.unwrap();
+        let job = manager
+            .set_job_on_failure(&job.id, Some("echo hook".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(job.on_failure.as_deref(), Some("echo hook"));
+
+        manager.run_job(&job.id).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(manager.get_history(&job.id, 10).await.unwrap().len(), 1);
+    }
+
+    /// Notifier that records what it is sent, failing the first
+    /// `failures` deliveries
+    struct RecordingNotifier {
+        failures: std::sync::atomic::AtomicU32,
+        received: tokio::sync::mpsc::UnboundedSender<crate::notifier::Notification>,
+    }
+
+    #[async_trait::async_trait]
+    impl Notifier for RecordingNotifier {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        async fn notify(
+            &self,
+            notification: &crate::notifier::Notification,
+        ) -> std::result::Result<(), String> {
+            use std::sync::atomic::Ordering;
+            let failures = self.failures.load(Ordering::SeqCst);
+            if failures > 0 {
+                self.failures.store(failures - 1, Ordering::SeqCst);
+                return Err("unavailable".to_string());
+            }
+            let _ = self.received.send(notification.clone());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_notifier_receives_failures_with_retry() {
+        use crate::notifier::NotificationKind;
+
+        let manager = create_test_manager();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        manager.add_notifier(Arc::new(RecordingNotifier {
+            failures: 1.into(),
+            received: tx,
+        }));
+        let job = manager
+            .add_job("flaky", "0 0 1 1 *", "echo oops >&2; exit 1")
+            .await
+            .unwrap();
+        let ok = manager.add_job("ok", "0 0 1 1 *", "echo ok").await.unwrap();
+
+        let failed = manager.run_job(&job.id).await.unwrap();
+        let notification = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(notification.event, NotificationKind::Failed);
+        assert_eq!(notification.job_name, "flaky");
+        assert_eq!(notification.execution_id, Some(failed.id));
+        assert_eq!(notification.status, Some(ExecutionStatus::Failed));
+        assert_eq!(notification.stderr.as_deref(), Some("oops"));
+
+        manager.run_job(&ok.id).await.unwrap();
+        let notification = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(notification.event, NotificationKind::Completed);
+        assert_eq!(notification.job_name, "ok");
+    }
+
+    #[tokio::test]
+    async fn test_notifier_reports_timeouts() {
+        use crate::notifier::NotificationKind;
+
+        let manager = create_test_manager();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        manager.add_notifier(Arc::new(RecordingNotifier {
+            failures: 0.into(),
+            received: tx,
+        }));
+        let job = manager
+            .add_job("slow", "0 0 1 1 *", "sleep 5")
+            .await
+            .unwrap();
+        manager
+            .update_job(&job.id, None, None, Some(100))
+            .await
+            .unwrap();
+
+        manager.run_job(&job.id).await.unwrap();
+        let notification = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(notification.event, NotificationKind::Timeout);
+        assert_eq!(notification.status, Some(ExecutionStatus::Timeout));
+    }
+
+    #[tokio::test]
+    async fn test_job_shell() {
+        if process::check_executable("bash").is_err() {
+            return;
+        }
+        let manager = create_test_manager();
+        let script = "arr=(a b); echo ${arr[1]}";
+        let job = manager
+            .add_job_with_options(
+                "bash-job",
+                "0 0 1 1 *",
+                script,
+                JobOptions::default().with_shell("bash"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(job.shell.as_deref(), Some("bash"));
 
-                let now = Utc::now();
+        let execution = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Success);
+        assert_eq!(execution.stdout, "b\n");
+
+        // Plain sh has no arrays, unless it is bash itself
+        let sh_is_bash = std::fs::canonicalize("/bin/sh")
+            .map(|path| path.ends_with("bash"))
+            .unwrap_or(false);
+        let job = manager.set_job_shell(&job.id, None).await.unwrap();
+        let execution = manager.run_job(&job.id).await.unwrap();
+        if !sh_is_bash {
+            assert_eq!(execution.status, ExecutionStatus::Failed);
+        }
+    }
 
-                for job in jobs {
-                    // Skip non-active jobs
-                    if job.status != JobStatus::Active {
-                        continue;
-                    }
+    #[tokio::test]
+    async fn test_default_shell() {
+        if process::check_executable("bash").is_err() {
+            return;
+        }
+        let manager = create_test_manager();
+        assert_eq!(manager.default_shell(), "sh");
+        let manager = manager.with_default_shell(Some("bash".into())).unwrap();
+        assert_eq!(manager.default_shell(), "bash");
 
-                    // Check if job should run
-                    if let Some(next_run) = job.next_run {
-                        if next_run <= now {
-                            // Create a temporary manager for execution
-                            let manager = CronManager {
-                                store: store.clone(),
-                                event_tx: event_tx.clone(),
-                                running: running.clone(),
-                                workspace: workspace.clone(),
-                                agent_executor: agent_executor.clone(),
-                            };
-
-                            if let Err(e) = manager.execute_job(&job).await {
-                                tracing::error!("Failed to execute job {}: {}", job.id, e);
-                            }
-                        }
-                    }
-                }
-            }
+        let job = manager
+            .add_job("bash-job", "0 0 1 1 *", "echo $BASH_VERSION")
+            .await
+            .unwrap();
+        let execution = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Success);
+        assert!(!execution.stdout.is_empty());
+    }
 
-            let _ = event_tx.send(SchedulerEvent::Stopped);
-            tracing::info!("Cron scheduler stopped");
-        });
+    #[tokio::test]
+    async fn test_missing_shell_rejected() {
+        let manager = create_test_manager();
+        let err = manager
+            .add_job_with_options(
+                "job",
+                "0 0 1 1 *",
+                "echo hi",
+                JobOptions::default().with_shell("no-such-shell-a3s"),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, CronError::InvalidConfig(_)));
+        assert!(err.to_string().contains("no-such-shell-a3s"));
+        assert!(manager.list_jobs().await.unwrap().is_empty());
 
-        Ok(())
+        let job = manager
+            .add_job("job", "0 0 1 1 *", "echo hi")
+            .await
+            .unwrap();
+        assert!(manager
+            .set_job_shell(&job.id, Some("/no/such/shell".into()))
+            .await
+            .is_err());
+        assert!(create_test_manager()
+            .with_default_shell(Some("no-such-shell-a3s".into()))
+            .is_err());
     }
 
-    /// Stop the scheduler
-    pub async fn stop(&self) {
-        let mut running = self.running.write().await;
-        *running = false;
-    }
+    #[tokio::test]
+    async fn test_exec_job_passes_args_literally() {
+        let manager = create_test_manager();
+        let args = vec![
+            "%s\\n".to_string(),
+            "a; echo injected".to_string(),
+            "$HOME".to_string(),
+            "`id`".to_string(),
+            "*".to_string(),
+            "x | y > z".to_string(),
+        ];
+        let job = manager
+            .add_job_exec("exec-job", "0 0 1 1 *", "printf", args)
+            .await
+            .unwrap();
+        assert!(job.is_exec());
+        assert_eq!(job.program, "printf");
 
-    /// Check if scheduler is running
-    pub async fn is_running(&self) -> bool {
-        *self.running.read().await
-    }
-}
+        let execution = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Success);
+        assert_eq!(
+            execution.stdout,
+            "a; echo injected\n$HOME\n`id`\n*\nx | y > z\n"
+        );
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::store::MemoryCronStore;
+        let err = manager
+            .update_job(&job.id, None, Some("echo hi"), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, CronError::InvalidConfig(_)));
 
-    fn create_test_manager() -> CronManager {
-        let store = Arc::new(MemoryCronStore::new());
-        CronManager::with_store(store, "/tmp".to_string())
+        assert!(manager
+            .add_job_exec("empty", "0 0 1 1 *", " ", Vec::new())
+            .await
+            .is_err());
     }
 
     #[tokio::test]
-    async fn test_add_job() {
+    async fn test_exec_job_missing_program_fails() {
         let manager = create_test_manager();
-
         let job = manager
-            .add_job("test-job", "*/5 * * * *", "echo hello")
+            .add_job_exec("missing", "0 0 1 1 *", "no-such-program-a3s", Vec::new())
             .await
             .unwrap();
-
-        assert_eq!(job.name, "test-job");
-        assert_eq!(job.schedule, "*/5 * * * *");
-        assert_eq!(job.command, "echo hello");
-        assert!(job.next_run.is_some());
+        let execution = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Failed);
     }
 
     #[tokio::test]
-    async fn test_add_duplicate_name() {
+    async fn test_env_mode() {
+        std::env::set_var("A3S_CRON_TEST_SENTINEL", "leaked");
+        std::env::set_var("A3S_CRON_TEST_ALLOWED", "allowed");
         let manager = create_test_manager();
+        let mut job = manager
+            .add_job("env-job", "0 0 1 1 *", "printenv")
+            .await
+            .unwrap();
+        job.env = vec![("OWN".into(), "own".into())];
+        manager.store.save_job(&job).await.unwrap();
+
+        let stdout = manager.run_job(&job.id).await.unwrap().stdout;
+        assert!(stdout.contains("A3S_CRON_TEST_SENTINEL=leaked"));
+        assert!(stdout.contains("OWN=own"));
 
         manager
-            .add_job("unique", "* * * * *", "echo 1")
+            .set_job_env_mode(&job.id, Some(EnvMode::Clean))
             .await
             .unwrap();
+        let stdout = manager.run_job(&job.id).await.unwrap().stdout;
+        assert!(!stdout.contains("A3S_CRON_TEST_SENTINEL"));
+        assert!(stdout.contains("OWN=own"));
+        assert!(stdout.contains("PATH="));
 
-        let result = manager.add_job("unique", "* * * * *", "echo 2").await;
-        assert!(result.is_err());
+        manager
+            .set_job_env_mode(
+                &job.id,
+                Some(EnvMode::InheritAllow(vec!["A3S_CRON_TEST_ALLOWED".into()])),
+            )
+            .await
+            .unwrap();
+        let stdout = manager.run_job(&job.id).await.unwrap().stdout;
+        assert!(!stdout.contains("A3S_CRON_TEST_SENTINEL"));
+        assert!(stdout.contains("A3S_CRON_TEST_ALLOWED=allowed"));
+        assert!(stdout.contains("OWN=own"));
     }
 
     #[tokio::test]
-    async fn test_add_invalid_schedule() {
-        let manager = create_test_manager();
+    async fn test_default_env_mode() {
+        std::env::set_var("A3S_CRON_TEST_SENTINEL", "leaked");
+        let manager = create_test_manager().with_default_env_mode(EnvMode::Clean);
+        assert_eq!(manager.default_env_mode(), &EnvMode::Clean);
 
-        let result = manager.add_job("bad", "invalid", "echo").await;
-        assert!(result.is_err());
+        let job = manager
+            .add_job("env-job", "0 0 1 1 *", "printenv")
+            .await
+            .unwrap();
+        let stdout = manager.run_job(&job.id).await.unwrap().stdout;
+        assert!(!stdout.contains("A3S_CRON_TEST_SENTINEL"));
+
+        // The job's own mode wins over the default
+        manager
+            .set_job_env_mode(&job.id, Some(EnvMode::Inherit))
+            .await
+            .unwrap();
+        let stdout = manager.run_job(&job.id).await.unwrap().stdout;
+        assert!(stdout.contains("A3S_CRON_TEST_SENTINEL=leaked"));
     }
 
     #[tokio::test]
-    async fn test_get_job() {
+    async fn test_templated_job() {
         let manager = create_test_manager();
-
-        let job = manager
-            .add_job("findme", "* * * * *", "echo")
+        let mut job = manager
+            .add_job_with_options(
+                "report",
+                "0 0 1 1 *",
+                "echo {{ job.name }} {{ now | date '%Y' }} $WHERE {{ execution.id }}",
+                JobOptions::default().with_templated(),
+            )
             .await
             .unwrap();
+        job.env = vec![("WHERE".into(), "{{ workspace }}".into())];
+        manager.store.save_job(&job).await.unwrap();
 
-        let found = manager.get_job(&job.id).await.unwrap();
-        assert!(found.is_some());
-        assert_eq!(found.unwrap().name, "findme");
+        let execution = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Success);
+        assert_eq!(
+            execution.stdout,
+            format!("report {} /tmp {}\n", Utc::now().format("%Y"), execution.id)
+        );
+
+        // Without templating, tags are left alone
+        let job = manager.set_job_templated(&job.id, false).await.unwrap();
+        manager
+            .update_job(&job.id, None, Some("echo {{ job.name }}"), None)
+            .await
+            .unwrap();
+        let execution = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(execution.stdout, "{{ job.name }}\n");
     }
 
     #[tokio::test]
-    async fn test_list_jobs() {
+    async fn test_template_error_fails_execution() {
         let manager = create_test_manager();
+        let job = manager
+            .add_job_with_options(
+                "broken",
+                "0 0 1 1 *",
+                "touch /tmp/a3s-never-{{ nope }}",
+                JobOptions::default().with_templated(),
+            )
+            .await
+            .unwrap();
 
-        for i in 1..=3 {
-            manager
-                .add_job(&format!("job-{}", i), "* * * * *", "echo")
-                .await
-                .unwrap();
-        }
+        let execution = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Failed);
+        let error = execution.error.unwrap();
+        assert!(error.contains("unknown variable: nope"), "{}", error);
+        assert!(!Path::new("/tmp/a3s-never-").exists());
+    }
 
-        let jobs = manager.list_jobs().await.unwrap();
-        assert_eq!(jobs.len(), 3);
+    /// `CRON_*` variables printed by a job running `env`
+    fn cron_env(stdout: &str) -> HashMap<String, String> {
+        stdout
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .filter(|(key, _)| key.starts_with("CRON_"))
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
     }
 
     #[tokio::test]
-    async fn test_update_job() {
+    async fn test_standard_env_vars() {
         let manager = create_test_manager();
+        let mut job = manager
+            .add_job("env-job", "0 0 1 1 *", "env")
+            .await
+            .unwrap();
+        let execution = manager.run_job(&job.id).await.unwrap();
+
+        let env = cron_env(&execution.stdout);
+        assert_eq!(env["CRON_JOB_ID"], job.id);
+        assert_eq!(env["CRON_JOB_NAME"], "env-job");
+        assert_eq!(env["CRON_EXECUTION_ID"], execution.id);
+        assert_eq!(env["CRON_TRIGGER"], "manual");
+        assert_eq!(env["CRON_WORKSPACE"], "/tmp");
+        let scheduled = DateTime::parse_from_rfc3339(&env["CRON_SCHEDULED_TIME"]).unwrap();
+        assert_eq!(scheduled, execution.started_at);
+
+        // The job's own variables win
+        job.env = vec![("CRON_WORKSPACE".into(), "custom".into())];
+        manager.store.save_job(&job).await.unwrap();
+        let execution = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(cron_env(&execution.stdout)["CRON_WORKSPACE"], "custom");
+    }
 
+    #[tokio::test]
+    async fn test_standard_env_vars_scheduled() {
+        let store = Arc::new(MemoryCronStore::new());
+        let manager = CronManager::with_store(store.clone(), "/tmp".to_string());
         let job = manager
-            .add_job("updatable", "* * * * *", "echo v1")
+            .add_job("env-job", "* * * * *", "env")
             .await
             .unwrap();
-
-        let updated = manager
-            .update_job(&job.id, Some("0 * * * *"), Some("echo v2"), Some(30000))
+        make_due(&store, &job).await;
+        let due = store
+            .load_job(&job.id)
             .await
+            .unwrap()
+            .unwrap()
+            .next_run
             .unwrap();
 
-        assert_eq!(updated.schedule, "0 * * * *");
-        assert_eq!(updated.command, "echo v2");
-        assert_eq!(updated.timeout_ms, 30000);
+        let mut rx = manager.subscribe();
+        manager.start().await.unwrap();
+        wait_for_completions(&mut rx, 1).await;
+        manager.stop().await;
+
+        let execution = manager.get_history(&job.id, 1).await.unwrap().remove(0);
+        let env = cron_env(&execution.stdout);
+        assert_eq!(env["CRON_TRIGGER"], "scheduled");
+        assert_eq!(env["CRON_SCHEDULED_TIME"], due.to_rfc3339());
     }
 
     #[tokio::test]
-    async fn test_pause_resume() {
-        let manager = create_test_manager();
-
+    async fn test_job_output_streamed() {
+        let manager = Arc::new(create_test_manager());
         let job = manager
-            .add_job("pausable", "* * * * *", "echo")
+            .add_job(
+                "chatty",
+                "0 0 1 1 *",
+                "echo one; echo oops >&2; sleep 1; echo two",
+            )
             .await
             .unwrap();
 
-        // Pause
-        let paused = manager.pause_job(&job.id).await.unwrap();
-        assert_eq!(paused.status, JobStatus::Paused);
+        let mut rx = manager.subscribe();
+        let handle = run_in_background(&manager, &job.id).await;
 
-        // Resume
-        let resumed = manager.resume_job(&job.id).await.unwrap();
-        assert_eq!(resumed.status, JobStatus::Active);
+        // The first line arrives while the job is still running
+        let first = tokio::time::timeout(Duration::from_millis(800), async {
+            loop {
+                if let SchedulerEvent::JobOutput {
+                    job_id,
+                    stream: OutputStream::Stdout,
+                    chunk,
+                    ..
+                } = rx.recv().await.unwrap().kind
+                {
+                    return (job_id, chunk);
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(first, (job.id.clone(), "one\n".to_string()));
+
+        let execution = handle.await.unwrap().unwrap();
+        assert_eq!(execution.stdout, "one\ntwo\n");
+
+        let mut rest = Vec::new();
+        while let Ok(envelope) = rx.try_recv() {
+            if let SchedulerEvent::JobOutput {
+                execution_id,
+                stream,
+                chunk,
+                ..
+            } = envelope.kind
+            {
+                assert_eq!(execution_id, execution.id);
+                rest.push((stream, chunk));
+            }
+        }
+        assert!(rest.contains(&(OutputStream::Stderr, "oops\n".to_string())));
+        assert!(rest.contains(&(OutputStream::Stdout, "two\n".to_string())));
     }
 
     #[tokio::test]
-    async fn test_remove_job() {
-        let manager = create_test_manager();
-
+    async fn test_output_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = create_test_manager()
+            .with_output_storage(OutputStorage::files_in(dir.path()))
+            .unwrap();
         let job = manager
-            .add_job("removable", "* * * * *", "echo")
+            .add_job(
+                "loud",
+                "0 0 1 1 *",
+                "head -c 20000 /dev/zero | tr '\\0' x; echo oops >&2",
+            )
             .await
             .unwrap();
 
-        manager.remove_job(&job.id).await.unwrap();
+        let execution = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Success);
+        assert!(execution.stdout.is_empty());
+        let files = execution.output_files.clone().unwrap();
+        assert_eq!(files.stdout_bytes, 20_000);
+        assert_eq!(
+            files.stdout_path,
+            dir.path()
+                .join("cron/logs")
+                .join(&job.id)
+                .join(format!("{}.out", execution.id))
+        );
 
-        let found = manager.get_job(&job.id).await.unwrap();
-        assert!(found.is_none());
+        let history = manager.get_history(&job.id, 10).await.unwrap();
+        assert_eq!(history[0].output_files, Some(files));
+
+        // Output is kept in full, not truncated like inline output
+        let (stdout, stderr) = manager.get_execution_output(&execution.id).await.unwrap();
+        assert_eq!(stdout, "x".repeat(20_000));
+        assert_eq!(stderr, "oops\n");
+
+        assert!(matches!(
+            manager.get_execution_output("missing").await,
+            Err(CronError::ExecutionNotFound(_))
+        ));
     }
 
     #[tokio::test]
-    async fn test_run_job() {
-        let manager = create_test_manager();
-
+    async fn test_output_files_pruned() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = create_test_manager()
+            .with_output_storage(OutputStorage::Files {
+                dir: dir.path().to_path_buf(),
+                max_total_bytes: 10,
+            })
+            .unwrap();
         let job = manager
-            .add_job("runnable", "* * * * *", "echo hello")
+            .add_job("job", "0 0 1 1 *", "echo 1234567")
             .await
             .unwrap();
 
-        let execution = manager.run_job(&job.id).await.unwrap();
-        assert!(execution.stdout.contains("hello"));
+        let first = manager.run_job(&job.id).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let second = manager.run_job(&job.id).await.unwrap();
+        assert!(!first.output_files.unwrap().stdout_path.exists());
+        assert!(second.output_files.unwrap().stdout_path.exists());
+        assert!(manager.get_execution_output(&first.id).await.is_err());
+
+        assert!(create_test_manager()
+            .with_output_storage(OutputStorage::Files {
+                dir: dir.path().to_path_buf(),
+                max_total_bytes: 0,
+            })
+            .is_err());
     }
 
     #[tokio::test]
-    async fn test_run_job_failure() {
+    async fn test_inline_output() {
         let manager = create_test_manager();
-
+        assert_eq!(manager.output_storage(), &OutputStorage::Inline);
         let job = manager
-            .add_job("failing", "* * * * *", "exit 1")
+            .add_job("job", "0 0 1 1 *", "echo hi")
             .await
             .unwrap();
-
         let execution = manager.run_job(&job.id).await.unwrap();
-        assert_eq!(execution.status, crate::types::ExecutionStatus::Failed);
+        assert!(execution.output_files.is_none());
+        assert_eq!(
+            manager.get_execution_output(&execution.id).await.unwrap(),
+            ("hi\n".to_string(), String::new())
+        );
     }
 
     #[tokio::test]
-    async fn test_get_history() {
+    async fn test_secrets_masked() {
         let manager = create_test_manager();
-
         let job = manager
-            .add_job("historical", "* * * * *", "echo test")
+            .add_job_with_options(
+                "leaky",
+                "0 0 1 1 *",
+                "echo token=$TOKEN; echo \"Bearer $TOKEN$TOKEN\" >&2; echo s3cr3t-inline; exit 1",
+                JobOptions::default().with_secret_env("TOKEN"),
+            )
             .await
             .unwrap();
+        let mut stored = manager.store.load_job(&job.id).await.unwrap().unwrap();
+        stored.env = vec![("TOKEN".into(), "s3cr3t".into())];
+        manager.store.save_job(&stored).await.unwrap();
 
-        // Run job multiple times
-        for _ in 0..3 {
-            manager.run_job(&job.id).await.unwrap();
+        let mut rx = manager.subscribe();
+        let execution = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Failed);
+        assert_eq!(execution.stdout, "token=***\n***-inline\n");
+        assert_eq!(execution.stderr, "Bearer ******\n");
+
+        let history = manager.get_history(&job.id, 1).await.unwrap();
+        assert!(!history[0].stdout.contains("s3cr3t"));
+        assert!(!history[0].stderr.contains("s3cr3t"));
+
+        // Errors in events are scrubbed too
+        manager
+            .update_job(&job.id, None, Some("echo {{ s3cr3t }}"), None)
+            .await
+            .unwrap();
+        manager.set_job_templated(&job.id, true).await.unwrap();
+        let execution = manager.run_job(&job.id).await.unwrap();
+        assert!(execution.error.unwrap().contains("unknown variable: ***"));
+
+        let mut failed_errors = Vec::new();
+        while let Ok(envelope) = rx.try_recv() {
+            let event = envelope.kind;
+            if let SchedulerEvent::JobFailed { error, .. } = &event {
+                failed_errors.push(error.clone());
+            }
+            assert!(!serde_json::to_string(&event).unwrap().contains("s3cr3t"));
         }
+        assert_eq!(failed_errors.len(), 2);
+        assert!(failed_errors[1].contains("unknown variable: ***"));
 
-        let history = manager.get_history(&job.id, 10).await.unwrap();
-        assert_eq!(history.len(), 3);
+        // The job as returned by the manager doesn't leak the value either
+        let job = manager
+            .update_job(
+                &job.id,
+                None,
+                Some("curl -H 'Authorization: s3cr3t' example.com"),
+                None,
+            )
+            .await
+            .unwrap();
+        let shown = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(shown.command, "curl -H 'Authorization: ***' example.com");
+        assert_eq!(shown.env, vec![("TOKEN".into(), "***".into())]);
+        let listed = manager.list_jobs().await.unwrap();
+        assert!(!listed[0].command.contains("s3cr3t"));
     }
 
     #[tokio::test]
-    async fn test_event_subscription() {
+    async fn test_job_min_interval() {
         let manager = create_test_manager();
-        let mut rx = manager.subscribe();
-
         let job = manager
-            .add_job("evented", "* * * * *", "echo test")
+            .add_job_with_options(
+                "limited",
+                "0 0 1 1 *",
+                "echo ran",
+                JobOptions::default().with_min_interval_ms(60_000),
+            )
+            .await
+            .unwrap();
+        let downstream = manager
+            .add_job_with_options(
+                "limited-downstream",
+                NEVER_SCHEDULE,
+                "true",
+                JobOptions::default().with_min_interval_ms(60_000),
+            )
+            .await
+            .unwrap();
+        manager
+            .set_job_triggers(&job.id, vec![downstream.id.clone()])
             .await
             .unwrap();
 
-        // Run job
-        manager.run_job(&job.id).await.unwrap();
-
-        // Check events
-        let event = rx.try_recv().unwrap();
-        match event {
-            SchedulerEvent::JobStarted { job_id, .. } => {
-                assert_eq!(job_id, job.id);
+        let mut rx = manager.subscribe();
+        let first = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(first.status, ExecutionStatus::Success);
+        // Let the downstream run triggered by the first one finish
+        tokio::time::sleep(Duration::from_millis(300)).await;
+
+        // Too soon: skipped, and observable as a JobSkipped event
+        let second = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(second.status, ExecutionStatus::Skipped);
+        assert!(second.error.unwrap().starts_with("rate limited"));
+
+        // Forced runs bypass the limit and trigger the downstream job, which
+        // is rate limited in turn
+        let forced = manager
+            .run_job_with(
+                &job.id,
+                RunOptions {
+                    force: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(forced.status, ExecutionStatus::Success);
+        tokio::time::sleep(Duration::from_millis(300)).await;
+
+        let mut skipped = Vec::new();
+        while let Ok(envelope) = rx.try_recv() {
+            if let SchedulerEvent::JobSkipped { job_id, reason, .. } = envelope.kind {
+                assert!(reason.starts_with("rate limited"), "{}", reason);
+                skipped.push(job_id);
             }
-            _ => panic!("Expected JobStarted event"),
         }
+        assert_eq!(skipped, vec![job.id.clone(), downstream.id.clone()]);
+        assert_eq!(
+            count_by_status(&manager, &downstream.id, ExecutionStatus::Success).await,
+            1
+        );
+
+        // Without a minimum interval, runs aren't limited
+        manager.set_job_min_interval(&job.id, None).await.unwrap();
+        let again = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(again.status, ExecutionStatus::Success);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_job_resource_limits() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = create_test_manager();
+        let limits = ResourceLimits::new().with_max_file_size_bytes(1024);
+        let mut job = manager
+            .add_job_with_options(
+                "big-writer",
+                "0 0 1 1 *",
+                "head -c 65536 /dev/zero > big.bin",
+                JobOptions::default().with_resource_limits(limits),
+            )
+            .await
+            .unwrap();
+        assert_eq!(job.resource_limits, limits);
+        job.working_dir = Some(dir.path().to_string_lossy().to_string());
+        manager.store.save_job(&job).await.unwrap();
+
+        let execution = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Failed);
+        assert_eq!(
+            execution.error.as_deref(),
+            Some("killed: exceeded file size limit (signal 25)")
+        );
+        assert!(std::fs::metadata(dir.path().join("big.bin")).unwrap().len() <= 1024);
+
+        // Without limits the write succeeds
+        manager
+            .set_job_resource_limits(&job.id, ResourceLimits::default())
+            .await
+            .unwrap();
+        let execution = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Success);
     }
 
     // --- Agent-mode tests ---
@@ -724,7 +10378,12 @@ mod tests {
         let manager = create_test_manager();
 
         let job = manager
-            .add_agent_job("agent-task", "*/5 * * * *", "Refactor auth module", create_agent_config())
+            .add_agent_job(
+                "agent-task",
+                "*/5 * * * *",
+                "Refactor auth module",
+                create_agent_config(),
+            )
             .await
             .unwrap();
 
@@ -745,7 +10404,12 @@ mod tests {
             .unwrap();
 
         let result = manager
-            .add_agent_job("unique-agent", "* * * * *", "prompt2", create_agent_config())
+            .add_agent_job(
+                "unique-agent",
+                "* * * * *",
+                "prompt2",
+                create_agent_config(),
+            )
             .await;
         assert!(result.is_err());
     }
@@ -760,7 +10424,12 @@ mod tests {
         }));
 
         let job = manager
-            .add_agent_job("agent-run", "* * * * *", "Refactor auth", create_agent_config())
+            .add_agent_job(
+                "agent-run",
+                "* * * * *",
+                "Refactor auth",
+                create_agent_config(),
+            )
             .await
             .unwrap();
 
@@ -779,7 +10448,12 @@ mod tests {
         }));
 
         let job = manager
-            .add_agent_job("agent-fail", "* * * * *", "Bad prompt", create_agent_config())
+            .add_agent_job(
+                "agent-fail",
+                "* * * * *",
+                "Bad prompt",
+                create_agent_config(),
+            )
             .await
             .unwrap();
 
@@ -798,7 +10472,11 @@ mod tests {
 
         let execution = manager.run_job(&job.id).await.unwrap();
         assert_eq!(execution.status, crate::types::ExecutionStatus::Failed);
-        assert!(execution.error.as_deref().unwrap_or("").contains("No agent executor"));
+        assert!(execution
+            .error
+            .as_deref()
+            .unwrap_or("")
+            .contains("No agent executor"));
     }
 
     #[tokio::test]
@@ -813,4 +10491,113 @@ mod tests {
         assert_eq!(job.job_type, JobType::Shell);
         assert!(job.agent_config.is_none());
     }
+
+    /// Native handler echoing its context, optionally failing or stalling
+    struct EchoHandler {
+        calls: AtomicUsize,
+        delay: Duration,
+        fail: bool,
+    }
+
+    impl EchoHandler {
+        fn new() -> Self {
+            Self {
+                calls: AtomicUsize::new(0),
+                delay: Duration::ZERO,
+                fail: false,
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl NativeJobHandler for EchoHandler {
+        async fn run(&self, ctx: JobContext) -> std::result::Result<JobOutput, String> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(self.delay).await;
+            if self.fail {
+                return Err(format!("{} failed", ctx.job_name));
+            }
+            Ok(JobOutput::new(format!(
+                "{} {} {}",
+                ctx.job_name, ctx.trigger, ctx.execution_id
+            )))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_native_job() {
+        let manager = create_test_manager();
+        let job = manager
+            .add_native_job("native", "0 0 1 1 *", "echo")
+            .await
+            .unwrap();
+        assert_eq!(job.job_type, JobType::Native);
+
+        // Unregistered handlers fail the run instead of panicking
+        let execution = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Failed);
+        assert!(execution
+            .error
+            .unwrap()
+            .contains("No native handler registered as \"echo\""));
+
+        let handler = Arc::new(EchoHandler::new());
+        manager.register_handler("echo", handler.clone());
+        let mut rx = manager.subscribe();
+        let execution = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Success);
+        assert_eq!(execution.stdout, format!("native manual {}", execution.id));
+        assert_eq!(handler.calls.load(Ordering::SeqCst), 1);
+        assert!(matches!(
+            rx.recv().await.unwrap().kind,
+            SchedulerEvent::JobStarted { .. }
+        ));
+        assert!(matches!(
+            rx.recv().await.unwrap().kind,
+            SchedulerEvent::JobCompleted { .. }
+        ));
+
+        manager.register_handler(
+            "echo",
+            Arc::new(EchoHandler {
+                fail: true,
+                ..EchoHandler::new()
+            }),
+        );
+        let execution = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Failed);
+        assert_eq!(execution.stderr, "native failed");
+
+        // Native jobs share the shell jobs' timeout
+        manager.register_handler(
+            "echo",
+            Arc::new(EchoHandler {
+                delay: Duration::from_secs(30),
+                ..EchoHandler::new()
+            }),
+        );
+        let mut stalled = manager.get_job(&job.id).await.unwrap().unwrap();
+        stalled.timeout_ms = Some(100);
+        manager.store.save_job(&stalled).await.unwrap();
+        let execution = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Timeout);
+    }
+
+    #[tokio::test]
+    async fn test_native_job_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = CronManager::new(dir.path()).await.unwrap();
+        let job = manager
+            .add_native_job("persisted", "0 0 1 1 *", "echo")
+            .await
+            .unwrap();
+        drop(manager);
+
+        let manager = CronManager::new(dir.path()).await.unwrap();
+        manager.register_handler("echo", Arc::new(EchoHandler::new()));
+        let loaded = manager.get_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(loaded.job_type, JobType::Native);
+        let execution = manager.run_job(&job.id).await.unwrap();
+        assert_eq!(execution.status, ExecutionStatus::Success);
+    }
 }
@@ -0,0 +1,371 @@
+//! Registry of managers for several workspaces
+//!
+//! A daemon hosting isolated project workspaces keeps one [`CronManager`]
+//! per workspace, each with its own store. [`CronService`] owns them by
+//! name, starts and shuts them down together, and merges their events into
+//! one stream tagged with the workspace each came from. Jobs are looked up
+//! within a workspace, so two workspaces may have jobs of the same name.
+
+use crate::scheduler::{CronManager, EventEnvelope, SchedulerEvent, DEFAULT_EVENT_CAPACITY};
+use crate::types::{CronError, CronJob, JobExecution, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, Mutex};
+use tokio::task::JoinHandle;
+
+/// An event of one of the service's managers, tagged with its workspace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceEvent {
+    /// Name of the workspace whose manager emitted the event
+    pub workspace: String,
+    /// The event
+    pub event: EventEnvelope,
+}
+
+/// A registered manager and the task forwarding its events
+struct Workspace {
+    manager: Arc<CronManager>,
+    forwarder: JoinHandle<()>,
+}
+
+/// Managers of several workspaces behind one facade
+pub struct CronService {
+    /// Managers by workspace name
+    workspaces: Mutex<BTreeMap<String, Workspace>>,
+    /// Merged events of every manager
+    event_tx: broadcast::Sender<WorkspaceEvent>,
+    /// Whether managers are started as they are added
+    running: Mutex<bool>,
+}
+
+impl Default for CronService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CronService {
+    /// Create a service without workspaces
+    pub fn new() -> Self {
+        let (event_tx, _) = broadcast::channel(DEFAULT_EVENT_CAPACITY);
+        Self {
+            workspaces: Mutex::new(BTreeMap::new()),
+            event_tx,
+            running: Mutex::new(false),
+        }
+    }
+
+    /// Get the manager of the workspace at `path`, named after the path,
+    /// creating it with file-based storage in the workspace if there is
+    /// none yet. A manager created while the service runs is started.
+    pub async fn get_or_create(&self, path: impl AsRef<Path>) -> Result<Arc<CronManager>> {
+        let name = path.as_ref().to_string_lossy().to_string();
+        let mut workspaces = self.workspaces.lock().await;
+        if let Some(workspace) = workspaces.get(&name) {
+            return Ok(workspace.manager.clone());
+        }
+        let manager = CronManager::new(path).await?;
+        self.register(&mut workspaces, name, manager).await
+    }
+
+    /// Add a manager built elsewhere under the workspace name `name`. Fails
+    /// if the name is taken. A manager added while the service runs is
+    /// started.
+    pub async fn insert(
+        &self,
+        name: impl Into<String>,
+        manager: CronManager,
+    ) -> Result<Arc<CronManager>> {
+        let name = name.into();
+        let mut workspaces = self.workspaces.lock().await;
+        if workspaces.contains_key(&name) {
+            return Err(CronError::InvalidConfig(format!(
+                "workspace {} is already registered",
+                name
+            )));
+        }
+        self.register(&mut workspaces, name, manager).await
+    }
+
+    /// Forward the manager's events, start it if the service runs and keep
+    /// it under `name`
+    async fn register(
+        &self,
+        workspaces: &mut BTreeMap<String, Workspace>,
+        name: String,
+        manager: CronManager,
+    ) -> Result<Arc<CronManager>> {
+        let manager = Arc::new(manager);
+        let forwarder = self.forward(&name, &manager);
+        if *self.running.lock().await && !manager.is_running().await {
+            if let Err(e) = manager.start().await {
+                forwarder.abort();
+                return Err(e);
+            }
+        }
+        tracing::info!("Registered cron workspace {}", name);
+        workspaces.insert(
+            name,
+            Workspace {
+                manager: manager.clone(),
+                forwarder,
+            },
+        );
+        Ok(manager)
+    }
+
+    /// Start a task passing the manager's events on, tagged with `name`
+    fn forward(&self, name: &str, manager: &CronManager) -> JoinHandle<()> {
+        let mut rx = manager.subscribe();
+        let event_tx = self.event_tx.clone();
+        let workspace = name.to_string();
+        tokio::spawn(async move {
+            loop {
+                let event = match rx.recv().await {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(count)) => {
+                        EventEnvelope::new(SchedulerEvent::EventsDropped {
+                            count,
+                            timestamp: Utc::now(),
+                        })
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                let _ = event_tx.send(WorkspaceEvent {
+                    workspace: workspace.clone(),
+                    event,
+                });
+            }
+        })
+    }
+
+    /// Get the manager of the workspace named `name`
+    pub async fn get(&self, name: &str) -> Option<Arc<CronManager>> {
+        self.workspaces
+            .lock()
+            .await
+            .get(name)
+            .map(|workspace| workspace.manager.clone())
+    }
+
+    /// Get the manager of the workspace named `name`, failing with
+    /// [`CronError::WorkspaceNotFound`] if there is none
+    async fn manager(&self, name: &str) -> Result<Arc<CronManager>> {
+        self.get(name)
+            .await
+            .ok_or_else(|| CronError::WorkspaceNotFound(name.to_string()))
+    }
+
+    /// Get the names of the workspaces, in order
+    pub async fn workspaces(&self) -> Vec<String> {
+        self.workspaces.lock().await.keys().cloned().collect()
+    }
+
+    /// Shut the workspace's manager down, waiting up to `timeout` for its
+    /// executions, and remove it. Returns the IDs of the executions
+    /// cancelled.
+    pub async fn remove(&self, name: &str, timeout: Duration) -> Result<Vec<String>> {
+        let workspace = self
+            .workspaces
+            .lock()
+            .await
+            .remove(name)
+            .ok_or_else(|| CronError::WorkspaceNotFound(name.to_string()))?;
+        let cancelled = workspace.manager.shutdown(timeout).await;
+        workspace.forwarder.abort();
+        tracing::info!("Removed cron workspace {}", name);
+        Ok(cancelled)
+    }
+
+    /// Start the scheduler of every workspace not already running, and of
+    /// workspaces added from now on. Every manager is tried; the first
+    /// failure is returned.
+    pub async fn start(&self) -> Result<()> {
+        *self.running.lock().await = true;
+        let managers = self.managers().await;
+        let mut result = Ok(());
+        for (name, manager) in managers {
+            if manager.is_running().await {
+                continue;
+            }
+            if let Err(e) = manager.start().await {
+                tracing::error!("Failed to start cron workspace {}: {}", name, e);
+                if result.is_ok() {
+                    result = Err(e);
+                }
+            }
+        }
+        result
+    }
+
+    /// Shut every workspace's manager down at once, each waiting up to
+    /// `timeout` for its executions. Returns the IDs of the executions
+    /// cancelled, by workspace.
+    pub async fn shutdown(&self, timeout: Duration) -> BTreeMap<String, Vec<String>> {
+        *self.running.lock().await = false;
+        let managers = self.managers().await;
+        let shutdowns = managers
+            .iter()
+            .map(|(_, manager)| manager.shutdown(timeout));
+        let cancelled = futures_util::future::join_all(shutdowns).await;
+        managers
+            .into_iter()
+            .map(|(name, _)| name)
+            .zip(cancelled)
+            .collect()
+    }
+
+    /// Subscribe to the events of every workspace
+    pub fn subscribe(&self) -> broadcast::Receiver<WorkspaceEvent> {
+        self.event_tx.subscribe()
+    }
+
+    /// Get a job of the workspace by name, with its secrets masked
+    pub async fn get_job_by_name(&self, workspace: &str, name: &str) -> Result<Option<CronJob>> {
+        self.manager(workspace).await?.get_job_by_name(name).await
+    }
+
+    /// Manually run the workspace's job with the given name
+    pub async fn run_job_by_name(&self, workspace: &str, name: &str) -> Result<JobExecution> {
+        self.manager(workspace).await?.run_job_by_name(name).await
+    }
+
+    /// Snapshot of the registered managers, so none is held locked while
+    /// they are used
+    async fn managers(&self) -> Vec<(String, Arc<CronManager>)> {
+        self.workspaces
+            .lock()
+            .await
+            .iter()
+            .map(|(name, workspace)| (name.clone(), workspace.manager.clone()))
+            .collect()
+    }
+}
+
+impl Drop for CronService {
+    /// Stop forwarding events; the managers stop as they are dropped
+    fn drop(&mut self) {
+        for workspace in self.workspaces.get_mut().values() {
+            workspace.forwarder.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::MemoryCronStore;
+
+    /// Wait for the next event of a completed job, returning its workspace
+    /// and job ID
+    async fn next_completion(rx: &mut broadcast::Receiver<WorkspaceEvent>) -> (String, String) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let WorkspaceEvent { workspace, event } = rx.recv().await.unwrap();
+                if let SchedulerEvent::JobCompleted { job_id, .. } = event.kind {
+                    return (workspace, job_id);
+                }
+            }
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_workspaces_are_isolated() {
+        let dirs = [tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap()];
+        let service = CronService::new();
+        let mut rx = service.subscribe();
+
+        let mut jobs = Vec::new();
+        for dir in &dirs {
+            let manager = service.get_or_create(dir.path()).await.unwrap();
+            let again = service.get_or_create(dir.path()).await.unwrap();
+            assert!(Arc::ptr_eq(&manager, &again));
+            // The same name in both workspaces
+            let job = manager
+                .add_job("backup", "0 0 1 1 *", "echo backup")
+                .await
+                .unwrap();
+            jobs.push(job);
+        }
+        let names: Vec<String> = dirs
+            .iter()
+            .map(|dir| dir.path().to_string_lossy().to_string())
+            .collect();
+        let mut sorted = names.clone();
+        sorted.sort();
+        assert_eq!(service.workspaces().await, sorted);
+
+        for (name, job) in names.iter().zip(&jobs) {
+            let found = service.get_job_by_name(name, "backup").await.unwrap();
+            assert_eq!(found.unwrap().id, job.id);
+        }
+        assert!(matches!(
+            service.get_job_by_name("elsewhere", "backup").await,
+            Err(CronError::WorkspaceNotFound(_))
+        ));
+
+        // Events carry the workspace of the job that ran
+        for (name, job) in names.iter().zip(&jobs) {
+            let execution = service.run_job_by_name(name, "backup").await.unwrap();
+            assert_eq!(execution.job_id, job.id);
+            assert_eq!(
+                next_completion(&mut rx).await,
+                (name.clone(), job.id.clone())
+            );
+        }
+        let history = service
+            .get(&names[0])
+            .await
+            .unwrap()
+            .get_history(&jobs[1].id, 10)
+            .await
+            .unwrap();
+        assert!(history.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_start_and_shutdown_fan_out() {
+        let service = CronService::new();
+        let memory = || CronManager::with_store(Arc::new(MemoryCronStore::new()), "/tmp".into());
+        let first = service.insert("first", memory()).await.unwrap();
+        assert!(matches!(
+            service.insert("first", memory()).await,
+            Err(CronError::InvalidConfig(_))
+        ));
+
+        service.start().await.unwrap();
+        assert!(first.is_running().await);
+        // Workspaces added while running are started
+        let second = service.insert("second", memory()).await.unwrap();
+        assert!(second.is_running().await);
+        second
+            .add_job("slow", "0 0 1 1 *", "sleep 5")
+            .await
+            .unwrap();
+        let slow = second.get_job_by_name("slow").await.unwrap().unwrap();
+        let execution_id = second.run_job_detached(&slow.id).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let cancelled = service.shutdown(Duration::from_millis(100)).await;
+        assert_eq!(cancelled["first"], Vec::<String>::new());
+        assert_eq!(cancelled["second"], vec![execution_id]);
+        assert!(!first.is_running().await);
+        assert!(!second.is_running().await);
+
+        service
+            .remove("second", Duration::from_millis(100))
+            .await
+            .unwrap();
+        assert_eq!(service.workspaces().await, ["first"]);
+        assert!(matches!(
+            service.remove("second", Duration::ZERO).await,
+            Err(CronError::WorkspaceNotFound(_))
+        ));
+    }
+}
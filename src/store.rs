@@ -2,10 +2,14 @@
 //!
 //! Provides pluggable storage backends for cron jobs and execution history.
 
-use crate::types::{CronJob, JobExecution, Result};
+use crate::scheduler::EventEnvelope;
+use crate::types::{CronJob, ExecutionStatus, JobExecution, JobFilter, Result, TriggerKind};
 use async_trait::async_trait;
-use std::collections::HashMap;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
 use tokio::sync::RwLock;
@@ -19,12 +23,20 @@ pub trait CronStore: Send + Sync {
     /// Load a job by ID
     async fn load_job(&self, id: &str) -> Result<Option<CronJob>>;
 
-    /// Delete a job
+    /// Delete a job, keeping its executions
     async fn delete_job(&self, id: &str) -> Result<()>;
 
     /// List all jobs
     async fn list_jobs(&self) -> Result<Vec<CronJob>>;
 
+    /// List the jobs matching `filter`. Stores that can index jobs should
+    /// override this to avoid loading every job.
+    async fn list_jobs_filtered(&self, filter: &JobFilter) -> Result<Vec<CronJob>> {
+        let mut jobs = self.list_jobs().await?;
+        jobs.retain(|job| filter.matches(job));
+        Ok(jobs)
+    }
+
     /// Check if a job exists
     async fn job_exists(&self, id: &str) -> Result<bool>;
 
@@ -37,8 +49,233 @@ pub trait CronStore: Send + Sync {
     /// Load execution history for a job
     async fn load_executions(&self, job_id: &str, limit: usize) -> Result<Vec<JobExecution>>;
 
+    /// Load the executions of a job matching `filter`, most recent first
+    async fn query_executions(
+        &self,
+        job_id: &str,
+        filter: &ExecutionFilter,
+    ) -> Result<Vec<JobExecution>>;
+
+    /// Load an execution by ID
+    async fn load_execution(&self, execution_id: &str) -> Result<Option<JobExecution>>;
+
     /// Delete all executions for a job
     async fn delete_executions(&self, job_id: &str) -> Result<()>;
+
+    /// Delete all but the `keep_last` most recent executions of a job,
+    /// returning how many were deleted
+    async fn prune_executions(&self, job_id: &str, keep_last: usize) -> Result<usize>;
+
+    /// Delete the executions of a job started before `cutoff`, returning how
+    /// many were deleted
+    async fn prune_executions_before(&self, job_id: &str, cutoff: DateTime<Utc>) -> Result<usize>;
+
+    /// Append an event to the audit log. Stores without an audit log
+    /// discard events.
+    async fn append_event(&self, _event: &EventEnvelope) -> Result<()> {
+        Ok(())
+    }
+
+    /// Load audit log events matching `filter`, most recent first
+    async fn load_events(
+        &self,
+        _filter: &EventFilter,
+        _limit: usize,
+    ) -> Result<Vec<EventEnvelope>> {
+        Ok(Vec::new())
+    }
+
+    /// Take the execution lease of a job for `ttl`, returning whether
+    /// `holder` now holds it. Succeeds when the lease is free, expired or
+    /// already held by `holder`. Stores shared by several managers must
+    /// implement leases; the default grants every request.
+    async fn try_acquire_lease(
+        &self,
+        _job_id: &str,
+        _holder: &str,
+        _ttl: Duration,
+    ) -> Result<bool> {
+        Ok(true)
+    }
+
+    /// Extend a lease held by `holder` to `ttl` from now, returning false if
+    /// `holder` no longer holds it
+    async fn renew_lease(&self, _job_id: &str, _holder: &str, _ttl: Duration) -> Result<bool> {
+        Ok(true)
+    }
+
+    /// Give up a lease if `holder` holds it
+    async fn release_lease(&self, _job_id: &str, _holder: &str) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A job's execution lease, held until `expires_at` unless renewed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Lease {
+    holder: String,
+    expires_at: DateTime<Utc>,
+}
+
+impl Lease {
+    fn new(holder: &str, ttl: Duration) -> Self {
+        let now = Utc::now();
+        let expires_at = chrono::Duration::from_std(ttl)
+            .ok()
+            .and_then(|ttl| now.checked_add_signed(ttl))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC);
+        Self {
+            holder: holder.to_string(),
+            expires_at,
+        }
+    }
+
+    fn is_expired(&self) -> bool {
+        self.expires_at <= Utc::now()
+    }
+}
+
+/// Make a job ID safe to use as a file name
+fn safe_file_name(job_id: &str) -> String {
+    job_id.replace(['/', '\\'], "_").replace("..", "_")
+}
+
+/// Selects events from the audit log
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    /// Only events about this job
+    pub job_id: Option<String>,
+    /// Only events at or after this time
+    pub since: Option<DateTime<Utc>>,
+}
+
+impl EventFilter {
+    /// Create a filter matching every event
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only match events about a job
+    pub fn with_job_id(mut self, job_id: impl Into<String>) -> Self {
+        self.job_id = Some(job_id.into());
+        self
+    }
+
+    /// Only match events at or after `since`
+    pub fn with_since(mut self, since: DateTime<Utc>) -> Self {
+        self.since = Some(since);
+        self
+    }
+
+    /// Check if an event matches the filter
+    pub fn matches(&self, event: &EventEnvelope) -> bool {
+        let job_matches = self
+            .job_id
+            .as_deref()
+            .is_none_or(|job_id| event.kind.job_id() == Some(job_id));
+        let time_matches = self.since.is_none_or(|since| event.at >= since);
+        job_matches && time_matches
+    }
+}
+
+/// Selects executions from a job's history
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionFilter {
+    /// Only executions with one of these statuses
+    pub statuses: Option<Vec<ExecutionStatus>>,
+    /// Only executions started at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Only executions started before this time
+    pub until: Option<DateTime<Utc>>,
+    /// Only executions started by one of these kinds of trigger; fields of
+    /// the triggers are ignored
+    pub triggers: Option<Vec<TriggerKind>>,
+    /// Most executions returned; `None` returns all
+    pub limit: Option<usize>,
+    /// Matching executions skipped, most recent first, before returning any
+    pub offset: usize,
+}
+
+impl ExecutionFilter {
+    /// Create a filter matching every execution
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only match executions with `status`, besides any statuses already
+    /// chosen
+    pub fn with_status(mut self, status: ExecutionStatus) -> Self {
+        self.statuses.get_or_insert_with(Vec::new).push(status);
+        self
+    }
+
+    /// Only match executions started at or after `since`
+    pub fn with_since(mut self, since: DateTime<Utc>) -> Self {
+        self.since = Some(since);
+        self
+    }
+
+    /// Only match executions started before `until`
+    pub fn with_until(mut self, until: DateTime<Utc>) -> Self {
+        self.until = Some(until);
+        self
+    }
+
+    /// Only match executions started by the same kind of trigger as
+    /// `trigger`, besides any triggers already chosen. Its fields are
+    /// ignored, so `TriggerKind::Retry { attempt: 0 }` matches every retry.
+    pub fn with_trigger(mut self, trigger: TriggerKind) -> Self {
+        self.triggers.get_or_insert_with(Vec::new).push(trigger);
+        self
+    }
+
+    /// Return at most `limit` executions
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Skip the `offset` most recent matching executions
+    pub fn with_offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Check if an execution matches the filter's criteria, ignoring
+    /// `limit` and `offset`
+    pub fn matches(&self, execution: &JobExecution) -> bool {
+        let status_matches = self
+            .statuses
+            .as_ref()
+            .is_none_or(|statuses| statuses.contains(&execution.status));
+        let since_matches = self.since.is_none_or(|since| execution.started_at >= since);
+        let until_matches = self.until.is_none_or(|until| execution.started_at < until);
+        let trigger_matches = self.triggers.as_ref().is_none_or(|triggers| {
+            triggers
+                .iter()
+                .any(|trigger| trigger.is_same_kind(&execution.trigger))
+        });
+        status_matches && since_matches && until_matches && trigger_matches
+    }
+
+    /// Select the matching executions, sorting them most recent first (ties
+    /// broken by ID) and applying `offset` and `limit`
+    pub fn apply(&self, executions: impl IntoIterator<Item = JobExecution>) -> Vec<JobExecution> {
+        let mut matching: Vec<JobExecution> = executions
+            .into_iter()
+            .filter(|execution| self.matches(execution))
+            .collect();
+        matching.sort_by(|a, b| {
+            b.started_at
+                .cmp(&a.started_at)
+                .then_with(|| b.id.cmp(&a.id))
+        });
+        matching
+            .into_iter()
+            .skip(self.offset)
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect()
+    }
 }
 
 // ============================================================================
@@ -53,13 +290,25 @@ pub trait CronStore: Send + Sync {
 ///   jobs.json           # All job definitions
 ///   history/
 ///     {job-id}/
-///       {timestamp}.json  # Execution records
+///       {timestamp}-{execution-id}.json  # Execution records
+///   events/
+///     {date}.jsonl        # Audit log, one file per day (UTC)
+///   leases/
+///     {job-id}.lock       # Execution leases
 /// ```
+///
+/// Leases are lock files created atomically, so managers on several hosts
+/// can share a store on a network filesystem; an expired lock file is taken
+/// over by the next manager to ask.
 pub struct FileCronStore {
     /// Jobs file path
     jobs_file: PathBuf,
     /// History directory path
     history_dir: PathBuf,
+    /// Audit log directory path
+    events_dir: PathBuf,
+    /// Lease lock file directory path
+    leases_dir: PathBuf,
 }
 
 impl FileCronStore {
@@ -68,6 +317,8 @@ impl FileCronStore {
         let base_dir = workspace.as_ref().join(".a3s").join("cron");
         let jobs_file = base_dir.join("jobs.json");
         let history_dir = base_dir.join("history");
+        let events_dir = base_dir.join("events");
+        let leases_dir = base_dir.join("leases");
 
         // Create directories
         fs::create_dir_all(&base_dir).await?;
@@ -83,6 +334,8 @@ impl FileCronStore {
         Ok(Self {
             jobs_file,
             history_dir,
+            events_dir,
+            leases_dir,
         })
     }
 
@@ -109,9 +362,79 @@ impl FileCronStore {
 
     /// Get the history directory for a job
     fn job_history_dir(&self, job_id: &str) -> PathBuf {
-        // Sanitize job ID
-        let safe_id = job_id.replace(['/', '\\'], "_").replace("..", "_");
-        self.history_dir.join(safe_id)
+        self.history_dir.join(safe_file_name(job_id))
+    }
+
+    /// Get the lease lock file of a job
+    fn lease_file(&self, job_id: &str) -> PathBuf {
+        self.leases_dir
+            .join(format!("{}.lock", safe_file_name(job_id)))
+    }
+
+    /// Read a lease lock file; `None` if it is missing or unreadable
+    async fn read_lease(path: &Path) -> Option<Lease> {
+        let content = fs::read(path).await.ok()?;
+        serde_json::from_slice(&content).ok()
+    }
+
+    /// Write `lease` to a new file beside `path`, returning that file
+    async fn write_lease_temp(path: &Path, lease: &Lease) -> Result<PathBuf> {
+        let temp_path = path.with_extension(format!("{}.tmp", uuid::Uuid::new_v4()));
+        let mut file = fs::File::create(&temp_path).await?;
+        file.write_all(&serde_json::to_vec(lease)?).await?;
+        file.sync_all().await?;
+        Ok(temp_path)
+    }
+
+    /// Create the lock file at `path` holding `lease`, unless one exists.
+    /// The complete file is linked into place, so readers never see a
+    /// partly written lease.
+    async fn create_lease(path: &Path, lease: &Lease) -> Result<bool> {
+        let temp_path = Self::write_lease_temp(path, lease).await?;
+        let linked = fs::hard_link(&temp_path, path).await;
+        fs::remove_file(&temp_path).await?;
+        match linked {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Load every execution of a job with the file it is stored in, most
+    /// recent first
+    async fn load_execution_files(&self, job_id: &str) -> Result<Vec<(PathBuf, JobExecution)>> {
+        let job_dir = self.job_history_dir(job_id);
+
+        if !job_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut executions = Vec::new();
+        let mut entries = fs::read_dir(&job_dir).await?;
+
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                let content = fs::read_to_string(&path).await?;
+                if let Ok(exec) = serde_json::from_str::<JobExecution>(&content) {
+                    executions.push((path, exec));
+                }
+            }
+        }
+
+        // Sort by start time descending (most recent first)
+        executions.sort_by_key(|(_, e)| std::cmp::Reverse(e.started_at));
+
+        Ok(executions)
+    }
+
+    /// Delete the files of the given executions, returning how many there were
+    async fn delete_execution_files(&self, files: Vec<(PathBuf, JobExecution)>) -> Result<usize> {
+        let count = files.len();
+        for (path, _) in files {
+            fs::remove_file(&path).await?;
+        }
+        Ok(count)
     }
 }
 
@@ -138,12 +461,7 @@ impl CronStore for FileCronStore {
     async fn delete_job(&self, id: &str) -> Result<()> {
         let mut jobs = self.load_all_jobs().await?;
         jobs.retain(|j| j.id != id);
-        self.save_all_jobs(&jobs).await?;
-
-        // Also delete execution history
-        self.delete_executions(id).await?;
-
-        Ok(())
+        self.save_all_jobs(&jobs).await
     }
 
     async fn list_jobs(&self) -> Result<Vec<CronJob>> {
@@ -164,7 +482,11 @@ impl CronStore for FileCronStore {
         let job_dir = self.job_history_dir(&execution.job_id);
         fs::create_dir_all(&job_dir).await?;
 
-        let filename = format!("{}.json", execution.started_at.timestamp_millis());
+        let filename = format!(
+            "{}-{}.json",
+            execution.started_at.timestamp_millis(),
+            execution.id
+        );
         let path = job_dir.join(filename);
 
         let json = serde_json::to_string_pretty(execution)?;
@@ -174,32 +496,62 @@ impl CronStore for FileCronStore {
     }
 
     async fn load_executions(&self, job_id: &str, limit: usize) -> Result<Vec<JobExecution>> {
-        let job_dir = self.job_history_dir(job_id);
+        let executions = self.load_execution_files(job_id).await?;
+        Ok(executions
+            .into_iter()
+            .take(limit)
+            .map(|(_, exec)| exec)
+            .collect())
+    }
 
-        if !job_dir.exists() {
-            return Ok(Vec::new());
-        }
+    async fn query_executions(
+        &self,
+        job_id: &str,
+        filter: &ExecutionFilter,
+    ) -> Result<Vec<JobExecution>> {
+        let executions = self.load_execution_files(job_id).await?;
+        Ok(filter.apply(executions.into_iter().map(|(_, exec)| exec)))
+    }
 
-        let mut executions = Vec::new();
-        let mut entries = fs::read_dir(&job_dir).await?;
+    async fn load_execution(&self, execution_id: &str) -> Result<Option<JobExecution>> {
+        if !self.history_dir.exists() {
+            return Ok(None);
+        }
 
-        while let Some(entry) = entries.next_entry().await? {
-            let path = entry.path();
-            if path.extension().is_some_and(|ext| ext == "json") {
-                let content = fs::read_to_string(&path).await?;
-                if let Ok(exec) = serde_json::from_str::<JobExecution>(&content) {
-                    executions.push(exec);
+        let suffix = format!("-{}.json", execution_id);
+        let mut legacy = Vec::new();
+        let mut job_dirs = fs::read_dir(&self.history_dir).await?;
+        while let Some(job_dir) = job_dirs.next_entry().await? {
+            if !job_dir.file_type().await?.is_dir() {
+                continue;
+            }
+            let mut entries = fs::read_dir(job_dir.path()).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let name = entry.file_name();
+                let name = name.to_string_lossy();
+                if name.ends_with(&suffix) {
+                    let content = fs::read_to_string(entry.path()).await?;
+                    return Ok(Some(serde_json::from_str(&content)?));
+                }
+                // Records saved before the file name held the execution ID
+                let is_legacy = name
+                    .strip_suffix(".json")
+                    .is_some_and(|stem| stem.bytes().all(|b| b.is_ascii_digit()));
+                if is_legacy {
+                    legacy.push(entry.path());
                 }
             }
         }
 
-        // Sort by start time descending (most recent first)
-        executions.sort_by(|a, b| b.started_at.cmp(&a.started_at));
-
-        // Limit results
-        executions.truncate(limit);
-
-        Ok(executions)
+        for path in legacy {
+            let content = fs::read_to_string(&path).await?;
+            if let Ok(exec) = serde_json::from_str::<JobExecution>(&content) {
+                if exec.id == execution_id {
+                    return Ok(Some(exec));
+                }
+            }
+        }
+        Ok(None)
     }
 
     async fn delete_executions(&self, job_id: &str) -> Result<()> {
@@ -211,16 +563,154 @@ impl CronStore for FileCronStore {
 
         Ok(())
     }
+
+    async fn prune_executions(&self, job_id: &str, keep_last: usize) -> Result<usize> {
+        let mut executions = self.load_execution_files(job_id).await?;
+        let pruned = executions.split_off(keep_last.min(executions.len()));
+        self.delete_execution_files(pruned).await
+    }
+
+    async fn prune_executions_before(&self, job_id: &str, cutoff: DateTime<Utc>) -> Result<usize> {
+        let mut executions = self.load_execution_files(job_id).await?;
+        executions.retain(|(_, exec)| exec.started_at < cutoff);
+        self.delete_execution_files(executions).await
+    }
+
+    async fn append_event(&self, event: &EventEnvelope) -> Result<()> {
+        fs::create_dir_all(&self.events_dir).await?;
+        let path = self
+            .events_dir
+            .join(format!("{}.jsonl", event.at.format("%Y-%m-%d")));
+
+        let mut line = serde_json::to_string(event)?;
+        line.push('\n');
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await?;
+        file.write_all(line.as_bytes()).await?;
+
+        Ok(())
+    }
+
+    async fn load_events(&self, filter: &EventFilter, limit: usize) -> Result<Vec<EventEnvelope>> {
+        if !self.events_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut files = Vec::new();
+        let mut entries = fs::read_dir(&self.events_dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "jsonl") {
+                files.push(path);
+            }
+        }
+
+        // Newest day first; days before `since` can't match
+        files.sort_by(|a, b| b.cmp(a));
+        let since_day = filter
+            .since
+            .map(|since| format!("{}.jsonl", since.format("%Y-%m-%d")));
+
+        let mut events = Vec::new();
+        for path in files {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            if since_day.as_deref().is_some_and(|day| *name < *day) {
+                break;
+            }
+            let content = fs::read_to_string(&path).await?;
+            let mut day: Vec<EventEnvelope> = content
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .filter(|event| filter.matches(event))
+                .collect();
+            day.reverse();
+            events.extend(day);
+            if events.len() >= limit {
+                break;
+            }
+        }
+
+        events.truncate(limit);
+        Ok(events)
+    }
+
+    async fn try_acquire_lease(&self, job_id: &str, holder: &str, ttl: Duration) -> Result<bool> {
+        fs::create_dir_all(&self.leases_dir).await?;
+        let path = self.lease_file(job_id);
+        let lease = Lease::new(holder, ttl);
+        if Self::create_lease(&path, &lease).await? {
+            return Ok(true);
+        }
+
+        match Self::read_lease(&path).await {
+            Some(current) if current.holder == holder => {
+                self.renew_lease(job_id, holder, ttl).await
+            }
+            Some(current) if !current.is_expired() => Ok(false),
+            _ => {
+                // Move the stale file aside before replacing it, so that of
+                // several managers finding it only one takes over
+                let aside = path.with_extension(format!("{}.stale", uuid::Uuid::new_v4()));
+                if fs::rename(&path, &aside).await.is_err() {
+                    return Ok(false);
+                }
+                let moved = Self::read_lease(&aside).await;
+                if moved.is_some_and(|moved| moved.holder != holder && !moved.is_expired()) {
+                    // Another manager took it over in between
+                    let _ = fs::rename(&aside, &path).await;
+                    return Ok(false);
+                }
+                let _ = fs::remove_file(&aside).await;
+                Self::create_lease(&path, &lease).await
+            }
+        }
+    }
+
+    async fn renew_lease(&self, job_id: &str, holder: &str, ttl: Duration) -> Result<bool> {
+        let path = self.lease_file(job_id);
+        if Self::read_lease(&path)
+            .await
+            .is_none_or(|current| current.holder != holder)
+        {
+            return Ok(false);
+        }
+        let temp_path = Self::write_lease_temp(&path, &Lease::new(holder, ttl)).await?;
+        fs::rename(&temp_path, &path).await?;
+        Ok(true)
+    }
+
+    async fn release_lease(&self, job_id: &str, holder: &str) -> Result<()> {
+        let path = self.lease_file(job_id);
+        if Self::read_lease(&path)
+            .await
+            .is_some_and(|current| current.holder == holder)
+        {
+            match fs::remove_file(&path).await {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
 }
 
 // ============================================================================
 // In-Memory Store (for testing)
 // ============================================================================
 
+/// Most audit log events kept by a [`MemoryCronStore`]; older ones are
+/// dropped
+pub const MEMORY_EVENT_LIMIT: usize = 10_000;
+
 /// In-memory cron store for testing
 pub struct MemoryCronStore {
     jobs: RwLock<HashMap<String, CronJob>>,
     executions: RwLock<HashMap<String, Vec<JobExecution>>>,
+    events: RwLock<VecDeque<EventEnvelope>>,
+    leases: RwLock<HashMap<String, Lease>>,
 }
 
 impl MemoryCronStore {
@@ -229,6 +719,8 @@ impl MemoryCronStore {
         Self {
             jobs: RwLock::new(HashMap::new()),
             executions: RwLock::new(HashMap::new()),
+            events: RwLock::new(VecDeque::new()),
+            leases: RwLock::new(HashMap::new()),
         }
     }
 }
@@ -253,12 +745,7 @@ impl CronStore for MemoryCronStore {
     }
 
     async fn delete_job(&self, id: &str) -> Result<()> {
-        let mut jobs = self.jobs.write().await;
-        jobs.remove(id);
-
-        let mut executions = self.executions.write().await;
-        executions.remove(id);
-
+        self.jobs.write().await.remove(id);
         Ok(())
     }
 
@@ -267,6 +754,15 @@ impl CronStore for MemoryCronStore {
         Ok(jobs.values().cloned().collect())
     }
 
+    async fn list_jobs_filtered(&self, filter: &JobFilter) -> Result<Vec<CronJob>> {
+        let jobs = self.jobs.read().await;
+        Ok(jobs
+            .values()
+            .filter(|job| filter.matches(job))
+            .cloned()
+            .collect())
+    }
+
     async fn job_exists(&self, id: &str) -> Result<bool> {
         let jobs = self.jobs.read().await;
         Ok(jobs.contains_key(id))
@@ -289,16 +785,109 @@ impl CronStore for MemoryCronStore {
     async fn load_executions(&self, job_id: &str, limit: usize) -> Result<Vec<JobExecution>> {
         let executions = self.executions.read().await;
         let mut result = executions.get(job_id).cloned().unwrap_or_default();
-        result.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+        result.sort_by_key(|e| std::cmp::Reverse(e.started_at));
         result.truncate(limit);
         Ok(result)
     }
 
+    async fn query_executions(
+        &self,
+        job_id: &str,
+        filter: &ExecutionFilter,
+    ) -> Result<Vec<JobExecution>> {
+        let executions = self.executions.read().await;
+        Ok(filter.apply(executions.get(job_id).into_iter().flatten().cloned()))
+    }
+
+    async fn load_execution(&self, execution_id: &str) -> Result<Option<JobExecution>> {
+        let executions = self.executions.read().await;
+        Ok(executions
+            .values()
+            .flatten()
+            .find(|e| e.id == execution_id)
+            .cloned())
+    }
+
     async fn delete_executions(&self, job_id: &str) -> Result<()> {
         let mut executions = self.executions.write().await;
         executions.remove(job_id);
         Ok(())
     }
+
+    async fn prune_executions(&self, job_id: &str, keep_last: usize) -> Result<usize> {
+        let mut executions = self.executions.write().await;
+        let Some(job_executions) = executions.get_mut(job_id) else {
+            return Ok(0);
+        };
+        job_executions.sort_by_key(|e| std::cmp::Reverse(e.started_at));
+        let pruned = job_executions.len().saturating_sub(keep_last);
+        job_executions.truncate(keep_last);
+        Ok(pruned)
+    }
+
+    async fn prune_executions_before(&self, job_id: &str, cutoff: DateTime<Utc>) -> Result<usize> {
+        let mut executions = self.executions.write().await;
+        let Some(job_executions) = executions.get_mut(job_id) else {
+            return Ok(0);
+        };
+        let before = job_executions.len();
+        job_executions.retain(|e| e.started_at >= cutoff);
+        Ok(before - job_executions.len())
+    }
+
+    async fn append_event(&self, event: &EventEnvelope) -> Result<()> {
+        let mut events = self.events.write().await;
+        if events.len() == MEMORY_EVENT_LIMIT {
+            events.pop_front();
+        }
+        events.push_back(event.clone());
+        Ok(())
+    }
+
+    async fn load_events(&self, filter: &EventFilter, limit: usize) -> Result<Vec<EventEnvelope>> {
+        let events = self.events.read().await;
+        Ok(events
+            .iter()
+            .rev()
+            .filter(|event| filter.matches(event))
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    async fn try_acquire_lease(&self, job_id: &str, holder: &str, ttl: Duration) -> Result<bool> {
+        let mut leases = self.leases.write().await;
+        if leases
+            .get(job_id)
+            .is_some_and(|lease| lease.holder != holder && !lease.is_expired())
+        {
+            return Ok(false);
+        }
+        leases.insert(job_id.to_string(), Lease::new(holder, ttl));
+        Ok(true)
+    }
+
+    async fn renew_lease(&self, job_id: &str, holder: &str, ttl: Duration) -> Result<bool> {
+        let mut leases = self.leases.write().await;
+        match leases.get_mut(job_id) {
+            Some(lease) if lease.holder == holder => {
+                *lease = Lease::new(holder, ttl);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn release_lease(&self, job_id: &str, holder: &str) -> Result<()> {
+        let mut leases = self.leases.write().await;
+        if leases
+            .get(job_id)
+            .is_some_and(|lease| lease.holder == holder)
+        {
+            leases.remove(job_id);
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -379,6 +968,202 @@ mod tests {
         // Test limit
         let limited = store.load_executions(&job.id, 2).await.unwrap();
         assert_eq!(limited.len(), 2);
+
+        let loaded = store.load_execution(&executions[3].id).await.unwrap();
+        assert_eq!(loaded.unwrap().id, executions[3].id);
+        assert!(store.load_execution("missing").await.unwrap().is_none());
+    }
+
+    /// Save executions of a job started 0 to 3 hours ago
+    async fn save_hourly_executions(store: &dyn CronStore, job_id: &str) {
+        for hours_ago in 0..4 {
+            let mut exec = JobExecution::new(job_id);
+            exec.started_at = Utc::now() - chrono::Duration::hours(hours_ago);
+            store.save_execution(&exec).await.unwrap();
+        }
+    }
+
+    /// Check pruning by count and by age, including the boundary cases
+    async fn check_prune_executions(store: &dyn CronStore) {
+        save_hourly_executions(store, "job").await;
+        assert_eq!(store.prune_executions("job", 10).await.unwrap(), 0);
+        assert_eq!(store.prune_executions("job", 2).await.unwrap(), 2);
+        let kept = store.load_executions("job", 10).await.unwrap();
+        assert_eq!(kept.len(), 2);
+        assert!(kept[1].started_at > Utc::now() - chrono::Duration::minutes(90));
+        assert_eq!(store.prune_executions("job", 0).await.unwrap(), 2);
+        assert!(store.load_executions("job", 10).await.unwrap().is_empty());
+
+        save_hourly_executions(store, "job").await;
+        let cutoff = Utc::now() - chrono::Duration::minutes(90);
+        assert_eq!(
+            store.prune_executions_before("job", cutoff).await.unwrap(),
+            2
+        );
+        assert_eq!(store.load_executions("job", 10).await.unwrap().len(), 2);
+        let future = Utc::now() + chrono::Duration::hours(1);
+        assert_eq!(
+            store.prune_executions_before("job", future).await.unwrap(),
+            2
+        );
+        assert!(store.load_executions("job", 10).await.unwrap().is_empty());
+
+        // Unknown jobs have nothing to prune
+        assert_eq!(store.prune_executions("missing", 0).await.unwrap(), 0);
+        assert_eq!(
+            store
+                .prune_executions_before("missing", future)
+                .await
+                .unwrap(),
+            0
+        );
+    }
+
+    /// Check querying a history of successes, failures and timeouts
+    async fn check_query_executions(store: &dyn CronStore) {
+        use ExecutionStatus::{Failed, Success, Timeout};
+        let statuses = [Success, Failed, Timeout, Success, Failed, Success];
+        let now = Utc::now();
+        for (hours_ago, status) in statuses.into_iter().enumerate() {
+            let mut exec = JobExecution::new("job");
+            exec.status = status;
+            exec.started_at = now - chrono::Duration::hours(hours_ago as i64);
+            store.save_execution(&exec).await.unwrap();
+        }
+        let hours_ago = |execs: Vec<JobExecution>| -> Vec<i64> {
+            execs
+                .iter()
+                .map(|e| (now - e.started_at).num_hours())
+                .collect()
+        };
+
+        let all = store
+            .query_executions("job", &ExecutionFilter::new())
+            .await
+            .unwrap();
+        assert_eq!(hours_ago(all), [0, 1, 2, 3, 4, 5]);
+
+        let failures = ExecutionFilter::new()
+            .with_status(Failed)
+            .with_status(Timeout);
+        let found = store.query_executions("job", &failures).await.unwrap();
+        assert_eq!(hours_ago(found), [1, 2, 4]);
+
+        // Since is inclusive, until exclusive
+        let window = failures
+            .clone()
+            .with_since(now - chrono::Duration::hours(4))
+            .with_until(now - chrono::Duration::hours(1));
+        let found = store.query_executions("job", &window).await.unwrap();
+        assert_eq!(hours_ago(found), [2, 4]);
+
+        let page = ExecutionFilter::new().with_offset(1).with_limit(2);
+        let found = store.query_executions("job", &page).await.unwrap();
+        assert_eq!(hours_ago(found), [1, 2]);
+
+        let found = store
+            .query_executions("missing", &ExecutionFilter::new())
+            .await
+            .unwrap();
+        assert!(found.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_memory_store_query_executions() {
+        check_query_executions(&MemoryCronStore::new()).await;
+    }
+
+    #[tokio::test]
+    async fn test_memory_store_prune_executions() {
+        check_prune_executions(&MemoryCronStore::new()).await;
+    }
+
+    /// An event about `job_id` emitted `days_ago` days ago
+    fn event(job_id: &str, days_ago: i64) -> EventEnvelope {
+        EventEnvelope::new(crate::scheduler::SchedulerEvent::JobPaused {
+            job_id: job_id.to_string(),
+            job_name: job_id.to_string(),
+            timestamp: Utc::now() - chrono::Duration::days(days_ago),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_memory_store_events() {
+        let store = MemoryCronStore::new();
+        let events = [event("a", 2), event("b", 1), event("a", 0)];
+        for event in &events {
+            store.append_event(event).await.unwrap();
+        }
+
+        let all = store.load_events(&EventFilter::new(), 10).await.unwrap();
+        let ids: Vec<_> = all.iter().map(|event| event.id).collect();
+        assert_eq!(ids, vec![events[2].id, events[1].id, events[0].id]);
+
+        let filter = EventFilter::new().with_job_id("a");
+        let ids: Vec<_> = store
+            .load_events(&filter, 10)
+            .await
+            .unwrap()
+            .iter()
+            .map(|event| event.id)
+            .collect();
+        assert_eq!(ids, vec![events[2].id, events[0].id]);
+
+        let filter = EventFilter::new().with_since(events[1].at);
+        assert_eq!(store.load_events(&filter, 10).await.unwrap().len(), 2);
+        assert_eq!(
+            store
+                .load_events(&EventFilter::new(), 1)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_memory_store_events_bounded() {
+        let store = MemoryCronStore::new();
+        let first = event("a", 0);
+        store.append_event(&first).await.unwrap();
+        for _ in 0..MEMORY_EVENT_LIMIT {
+            store.append_event(&event("a", 0)).await.unwrap();
+        }
+
+        let events = store
+            .load_events(&EventFilter::new(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(events.len(), MEMORY_EVENT_LIMIT);
+        assert!(events.iter().all(|event| event.id != first.id));
+    }
+
+    #[tokio::test]
+    async fn test_memory_store_leases() {
+        let store = MemoryCronStore::new();
+        let ttl = Duration::from_secs(30);
+
+        assert!(store.try_acquire_lease("job", "a", ttl).await.unwrap());
+        assert!(!store.try_acquire_lease("job", "b", ttl).await.unwrap());
+        assert!(store.try_acquire_lease("job", "a", ttl).await.unwrap());
+        assert!(store.try_acquire_lease("other", "b", ttl).await.unwrap());
+        assert!(store.renew_lease("job", "a", ttl).await.unwrap());
+        assert!(!store.renew_lease("job", "b", ttl).await.unwrap());
+
+        // Only the holder can release
+        store.release_lease("job", "b").await.unwrap();
+        assert!(!store.try_acquire_lease("job", "b", ttl).await.unwrap());
+        store.release_lease("job", "a").await.unwrap();
+        assert!(store.try_acquire_lease("job", "b", ttl).await.unwrap());
+
+        // Expired leases are taken over
+        store.release_lease("job", "b").await.unwrap();
+        assert!(store
+            .try_acquire_lease("job", "a", Duration::ZERO)
+            .await
+            .unwrap());
+        assert!(store.try_acquire_lease("job", "b", ttl).await.unwrap());
+        assert!(!store.renew_lease("job", "a", ttl).await.unwrap());
     }
 
     // ========================================================================
@@ -470,9 +1255,127 @@ mod tests {
         let executions = store.load_executions(&job.id, 10).await.unwrap();
         assert_eq!(executions.len(), 3);
 
-        // Delete job should also delete executions
+        // Deleting the job keeps its executions
         store.delete_job(&job.id).await.unwrap();
         let executions = store.load_executions(&job.id, 10).await.unwrap();
+        assert_eq!(executions.len(), 3);
+
+        store.delete_executions(&job.id).await.unwrap();
+        let executions = store.load_executions(&job.id, 10).await.unwrap();
         assert!(executions.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_file_store_load_execution() {
+        let dir = tempdir().unwrap();
+        let store = FileCronStore::new(dir.path()).await.unwrap();
+        let exec = JobExecution::new("job");
+        store.save_execution(&exec).await.unwrap();
+
+        let loaded = store.load_execution(&exec.id).await.unwrap().unwrap();
+        assert_eq!(loaded.job_id, "job");
+        assert!(store.load_execution("missing").await.unwrap().is_none());
+
+        // Records named by start time alone are still found
+        let legacy = JobExecution::new("job");
+        let path = dir.path().join(".a3s/cron/history/job/1.json");
+        std::fs::write(path, serde_json::to_string(&legacy).unwrap()).unwrap();
+        let loaded = store.load_execution(&legacy.id).await.unwrap().unwrap();
+        assert_eq!(loaded.id, legacy.id);
+    }
+
+    #[tokio::test]
+    async fn test_file_store_query_executions() {
+        let dir = tempdir().unwrap();
+        check_query_executions(&FileCronStore::new(dir.path()).await.unwrap()).await;
+    }
+
+    #[tokio::test]
+    async fn test_file_store_prune_executions() {
+        let dir = tempdir().unwrap();
+        let store = FileCronStore::new(dir.path()).await.unwrap();
+        check_prune_executions(&store).await;
+
+        // The files themselves are gone
+        let job_dir = dir.path().join(".a3s/cron/history/job");
+        assert_eq!(std::fs::read_dir(job_dir).unwrap().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_file_store_events() {
+        let dir = tempdir().unwrap();
+        let store = FileCronStore::new(dir.path()).await.unwrap();
+        let events = [event("a", 2), event("b", 1), event("a", 0), event("b", 0)];
+        for event in &events {
+            store.append_event(event).await.unwrap();
+        }
+
+        // One file per day
+        let files = std::fs::read_dir(dir.path().join(".a3s/cron/events"))
+            .unwrap()
+            .count();
+        assert_eq!(files, 3);
+
+        let all = store.load_events(&EventFilter::new(), 10).await.unwrap();
+        let ids: Vec<_> = all.iter().map(|event| event.id).collect();
+        let expected: Vec<_> = events.iter().rev().map(|event| event.id).collect();
+        assert_eq!(ids, expected);
+
+        let filter = EventFilter::new().with_job_id("a");
+        let ids: Vec<_> = store
+            .load_events(&filter, 10)
+            .await
+            .unwrap()
+            .iter()
+            .map(|event| event.id)
+            .collect();
+        assert_eq!(ids, vec![events[2].id, events[0].id]);
+
+        let filter = EventFilter::new().with_since(events[1].at);
+        assert_eq!(store.load_events(&filter, 10).await.unwrap().len(), 3);
+        assert_eq!(
+            store
+                .load_events(&EventFilter::new(), 2)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_file_store_leases() {
+        let dir = tempdir().unwrap();
+        let store = FileCronStore::new(dir.path()).await.unwrap();
+        // A second store on the same workspace stands in for another host
+        let other = FileCronStore::new(dir.path()).await.unwrap();
+        let ttl = Duration::from_secs(30);
+
+        assert!(store.try_acquire_lease("job", "a", ttl).await.unwrap());
+        assert!(!other.try_acquire_lease("job", "b", ttl).await.unwrap());
+        assert!(store.try_acquire_lease("job", "a", ttl).await.unwrap());
+        assert!(store.renew_lease("job", "a", ttl).await.unwrap());
+        assert!(!other.renew_lease("job", "b", ttl).await.unwrap());
+
+        other.release_lease("job", "b").await.unwrap();
+        assert!(!other.try_acquire_lease("job", "b", ttl).await.unwrap());
+        store.release_lease("job", "a").await.unwrap();
+        assert!(other.try_acquire_lease("job", "b", ttl).await.unwrap());
+        other.release_lease("job", "b").await.unwrap();
+
+        // A stale lock file is taken over
+        assert!(store
+            .try_acquire_lease("job", "a", Duration::ZERO)
+            .await
+            .unwrap());
+        assert!(other.try_acquire_lease("job", "b", ttl).await.unwrap());
+        assert!(!store.renew_lease("job", "a", ttl).await.unwrap());
+
+        // Only the lock files remain
+        let files: Vec<_> = std::fs::read_dir(dir.path().join(".a3s/cron/leases"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(files, vec!["job.lock"]);
+    }
 }
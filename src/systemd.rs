@@ -0,0 +1,471 @@
+//! systemd unit export
+//!
+//! Renders a job as a `.timer`/`.service` unit pair, so that jobs can be
+//! moved to systemd one at a time. The cron schedule becomes one or more
+//! `OnCalendar=` expressions in UTC, the time zone schedules are evaluated in:
+//!
+//! ```text
+//! 0 2 * * 1-5       Mon..Fri *-*-* 02:00:00 UTC
+//! */15 * * * *      *-*-* *:00/15:00 UTC
+//! 0 9-17 * JAN,JUL  *-01,07-* 09..17:00:00 UTC
+//! 0 0 L-2 * *       *-*~03 00:00:00 UTC
+//! 0 0 13 * 5        *-*-13 00:00:00 UTC  and  Fri *-*-* 00:00:00 UTC
+//! ```
+//!
+//! Steps, ranges, lists and names all convert. When the day-of-month and
+//! day-of-week fields are both restricted and either may match, each gets
+//! its own `OnCalendar=` line, since systemd requires both to match. `L` and
+//! `L-n` count back from the end of the month with `~`.
+//!
+//! Nearest-weekday days (`15W`, `LW`, `L-3W`) have no systemd equivalent and
+//! are rejected, as are trigger-only jobs, agent and native jobs (which need
+//! the scheduler to run) and templated jobs.
+
+use crate::parser::{CronExpression, MonthDay};
+use crate::types::{CronError, CronJob, JobType, MisfirePolicy, Result, ScheduleKind};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Day-of-week names as used by systemd, Monday first
+const WEEKDAY_NAMES: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// A job rendered as a systemd timer and the service it starts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SystemdUnit {
+    /// Unit name without suffix; install as `<name>.timer` and
+    /// `<name>.service`
+    pub name: String,
+    /// Contents of the `.timer` unit
+    pub timer: String,
+    /// Contents of the `.service` unit
+    pub service: String,
+}
+
+/// Render `job` as a systemd unit pair. Shell commands run through the job's
+/// shell, or `default_shell` when it has none. Secret env values are masked,
+/// so supply them separately, e.g. with an `EnvironmentFile=`. The timeout is
+/// the job's effective one when the manager has resolved it.
+pub fn render_unit(job: &CronJob, default_shell: &str) -> Result<SystemdUnit> {
+    if job.job_type != JobType::Shell {
+        return Err(CronError::InvalidConfig(format!(
+            "{} job {} needs the scheduler to run and can't be exported to systemd",
+            job.job_type, job.name
+        )));
+    }
+    if job.templated {
+        return Err(CronError::InvalidConfig(format!(
+            "templated job {} can't be exported to systemd",
+            job.name
+        )));
+    }
+    let calendars = match &job.schedule_kind {
+        ScheduleKind::Cron => on_calendar(&CronExpression::parse(&job.schedule)?)?,
+        ScheduleKind::At(at) => vec![at.format("%Y-%m-%d %H:%M:%S UTC").to_string()],
+        ScheduleKind::Never => {
+            return Err(CronError::InvalidConfig(format!(
+                "trigger-only job {} has no schedule to export to systemd",
+                job.name
+            )))
+        }
+    };
+    let job = job.redacted();
+
+    let mut service = String::from("[Unit]\n");
+    service.push_str(&format!(
+        "Description=a3s-cron job {}\n",
+        escape_specifiers(&job.name)
+    ));
+    service.push_str("\n[Service]\nType=oneshot\n");
+    if let Some(dir) = &job.working_dir {
+        service.push_str(&format!("WorkingDirectory={}\n", escape_specifiers(dir)));
+    }
+    for (name, value) in &job.env {
+        service.push_str(&format!(
+            "Environment={}\n",
+            quote(&format!("{}={}", name, value))
+        ));
+    }
+    let argv: Vec<&str> = if job.is_exec() {
+        std::iter::once(job.program.as_str())
+            .chain(job.args.iter().map(String::as_str))
+            .collect()
+    } else {
+        let shell = job.shell.as_deref().unwrap_or(default_shell);
+        vec![shell, "-c", job.command.as_str()]
+    };
+    let argv: Vec<String> = argv.into_iter().map(exec_quote).collect();
+    service.push_str(&format!("ExecStart={}\n", argv.join(" ")));
+    service.push_str(&format!(
+        "TimeoutStartSec={}\n",
+        format_duration(
+            job.effective_timeout_ms
+                .unwrap_or_else(|| job.resolve_timeout_ms(None))
+        )
+    ));
+
+    let mut timer = String::from("[Unit]\n");
+    timer.push_str(&format!(
+        "Description=Timer for a3s-cron job {}\n",
+        escape_specifiers(&job.name)
+    ));
+    timer.push_str("\n[Timer]\n");
+    for calendar in &calendars {
+        timer.push_str(&format!("OnCalendar={}\n", calendar));
+    }
+    timer.push_str("AccuracySec=1s\n");
+    if let Some(jitter_ms) = job.jitter_ms.filter(|ms| *ms > 0) {
+        timer.push_str(&format!(
+            "RandomizedDelaySec={}\n",
+            format_duration(jitter_ms)
+        ));
+    }
+    if job.misfire_policy != MisfirePolicy::Skip {
+        timer.push_str("Persistent=true\n");
+    }
+    timer.push_str("\n[Install]\nWantedBy=timers.target\n");
+
+    Ok(SystemdUnit {
+        name: unit_name(&job.name),
+        timer,
+        service,
+    })
+}
+
+/// Convert a cron expression into equivalent `OnCalendar=` expressions, any
+/// of which may match
+///
+/// # Examples
+///
+/// ```
+/// use a3s_cron::systemd::on_calendar;
+/// use a3s_cron::CronExpression;
+///
+/// let expr = CronExpression::parse("*/15 9-17 * * MON-FRI").unwrap();
+/// assert_eq!(
+///     on_calendar(&expr).unwrap(),
+///     vec!["Mon..Fri *-*-* 09..17:00/15:00 UTC"]
+/// );
+/// ```
+pub fn on_calendar(expr: &CronExpression) -> Result<Vec<String>> {
+    let [minutes, hours, days, months, weekdays] = expr.fields();
+
+    let mut day_parts = Vec::new();
+    if !days.is_empty() {
+        day_parts.push(format!("-{}", render_values(days, 1, 31)));
+    }
+    for month_day in expr.month_days() {
+        match month_day {
+            MonthDay::Last {
+                offset,
+                weekday: false,
+            } => day_parts.push(format!("~{:02}", offset + 1)),
+            _ => {
+                return Err(CronError::InvalidExpression(format!(
+                    "'{}' uses {}, which systemd calendars can't express",
+                    expr.expression, month_day
+                )))
+            }
+        }
+    }
+
+    let time = format!(
+        "{}:{}:00",
+        render_values(hours, 0, 23),
+        render_values(minutes, 0, 59)
+    );
+    let months = render_values(months, 1, 12);
+    let weekdays = render_weekdays(weekdays);
+    let line = |weekdays: Option<&str>, day_part: &str| match weekdays {
+        Some(weekdays) => format!("{} *-{}{} {} UTC", weekdays, months, day_part, time),
+        None => format!("*-{}{} {} UTC", months, day_part, time),
+    };
+
+    let mut calendars: Vec<String> = if expr.matches_either_day() {
+        day_parts
+            .iter()
+            .map(|day_part| line(None, day_part))
+            .chain(std::iter::once(line(weekdays.as_deref(), "-*")))
+            .collect()
+    } else {
+        day_parts
+            .iter()
+            .map(|day_part| line(weekdays.as_deref(), day_part))
+            .collect()
+    };
+    calendars.dedup();
+    Ok(calendars)
+}
+
+/// Unit name for a job: its name with characters systemd doesn't allow in
+/// unit names replaced by `-`
+pub fn unit_name(job_name: &str) -> String {
+    job_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || ":_.-".contains(c) {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// Render a field's values as `*`, a repetition (`00/15`) or a list of
+/// values and ranges (`01,05..09`)
+fn render_values(values: &BTreeSet<u32>, min: u32, max: u32) -> String {
+    if values.len() as u32 == max - min + 1 {
+        return "*".to_string();
+    }
+    let values: Vec<u32> = values.iter().copied().collect();
+    if let Some(step) = repetition(&values, max) {
+        return format!("{:02}/{}", values[0], step);
+    }
+    runs(&values)
+        .into_iter()
+        .map(|(start, end)| match end - start {
+            0 => format!("{:02}", start),
+            1 => format!("{:02},{:02}", start, end),
+            _ => format!("{:02}..{:02}", start, end),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// The step of values repeating evenly up to the end of the field, as
+/// systemd's `start/step` does
+fn repetition(values: &[u32], max: u32) -> Option<u32> {
+    if values.len() < 3 {
+        return None;
+    }
+    let step = values[1] - values[0];
+    let even = values.windows(2).all(|pair| pair[1] - pair[0] == step);
+    (step > 1 && even && values[values.len() - 1] + step > max).then_some(step)
+}
+
+/// Group sorted values into runs of consecutive values
+fn runs(values: &[u32]) -> Vec<(u32, u32)> {
+    let mut runs: Vec<(u32, u32)> = Vec::new();
+    for &value in values {
+        match runs.last_mut() {
+            Some((_, end)) if *end + 1 == value => *end = value,
+            _ => runs.push((value, value)),
+        }
+    }
+    runs
+}
+
+/// Render days of the week (0=Sunday) by name, or `None` for every day
+fn render_weekdays(weekdays: &BTreeSet<u32>) -> Option<String> {
+    if weekdays.len() == 7 {
+        return None;
+    }
+    // systemd weeks start on Monday
+    let mut days: Vec<u32> = weekdays.iter().map(|d| (d + 6) % 7).collect();
+    days.sort_unstable();
+    let names = runs(&days)
+        .into_iter()
+        .map(|(start, end)| {
+            let (start, end) = (WEEKDAY_NAMES[start as usize], WEEKDAY_NAMES[end as usize]);
+            if start == end {
+                start.to_string()
+            } else {
+                format!("{}..{}", start, end)
+            }
+        })
+        .collect::<Vec<_>>();
+    Some(names.join(","))
+}
+
+/// Format milliseconds as a systemd time span
+fn format_duration(ms: u64) -> String {
+    if ms.is_multiple_of(1000) {
+        format!("{}s", ms / 1000)
+    } else {
+        format!("{}ms", ms)
+    }
+}
+
+/// Escape `%`, which systemd expands as a specifier
+fn escape_specifiers(value: &str) -> String {
+    value.replace('%', "%%")
+}
+
+/// Double-quote a value with C-style escapes
+fn quote(value: &str) -> String {
+    let mut out = String::from("\"");
+    for c in escape_specifiers(value).chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            _ => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Quote an `ExecStart=` word if needed, escaping `$`, which systemd
+/// expands as an environment variable
+fn exec_quote(word: &str) -> String {
+    let plain = !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@".contains(c));
+    if plain {
+        word.to_string()
+    } else {
+        quote(word).replace('$', "$$")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn calendars(expression: &str) -> Vec<String> {
+        on_calendar(&CronExpression::parse(expression).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_on_calendar_fields() {
+        assert_eq!(calendars("* * * * *"), vec!["*-*-* *:*:00 UTC"]);
+        assert_eq!(calendars("30 2 * * *"), vec!["*-*-* 02:30:00 UTC"]);
+        assert_eq!(calendars("*/15 * * * *"), vec!["*-*-* *:00/15:00 UTC"]);
+        assert_eq!(calendars("5/20 */6 * * *"), vec!["*-*-* 00/6:05/20:00 UTC"]);
+        assert_eq!(
+            calendars("0,30 9-17 * * MON-FRI"),
+            vec!["Mon..Fri *-*-* 09..17:00,30:00 UTC"]
+        );
+        assert_eq!(
+            calendars("0-30/10 0 1,15 JAN,JUL *"),
+            vec!["*-01,07-01,15 00:00,10,20,30:00 UTC"]
+        );
+        assert_eq!(calendars("0 0 */2 * *"), vec!["*-*-01/2 00:00:00 UTC"]);
+        assert_eq!(
+            calendars("0 12 * * SAT,SUN,5"),
+            vec!["Fri..Sun *-*-* 12:00:00 UTC"]
+        );
+        assert_eq!(
+            calendars("0 12 * * 0,1,3"),
+            vec!["Mon,Wed,Sun *-*-* 12:00:00 UTC"]
+        );
+    }
+
+    #[test]
+    fn test_on_calendar_day_fields() {
+        // Either day field may match: one line each
+        assert_eq!(
+            calendars("0 0 13 * 5"),
+            vec!["*-*-13 00:00:00 UTC", "Fri *-*-* 00:00:00 UTC"]
+        );
+        // Both must match: one line
+        assert_eq!(calendars("0 0 13 * &5"), vec!["Fri *-*-13 00:00:00 UTC"]);
+        assert_eq!(calendars("0 0 L * *"), vec!["*-*~01 00:00:00 UTC"]);
+        assert_eq!(
+            calendars("0 6 1,L-2 2 *"),
+            vec!["*-02-01 06:00:00 UTC", "*-02~03 06:00:00 UTC"]
+        );
+
+        for expression in ["0 0 15W * *", "0 0 LW * *", "0 0 L-3W * *"] {
+            let expr = CronExpression::parse(expression).unwrap();
+            assert!(matches!(
+                on_calendar(&expr),
+                Err(CronError::InvalidExpression(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_unit_name() {
+        assert_eq!(unit_name("nightly-backup"), "nightly-backup");
+        assert_eq!(unit_name("db backup/daily"), "db-backup-daily");
+    }
+
+    #[test]
+    fn test_golden_weekday_backup() {
+        let job = CronJob::new("weekday-backup", "30 2 * * 1-5", "backup.sh --full")
+            .with_env("TARGET", "s3://backups/db")
+            .with_env("MESSAGE", "50% \"done\"")
+            .with_working_dir("/srv/backup");
+        let unit = render_unit(&job, "sh").unwrap();
+        assert_eq!(unit.name, "weekday-backup");
+        assert_eq!(
+            unit.timer,
+            include_str!("../testdata/systemd/weekday-backup.timer")
+        );
+        assert_eq!(
+            unit.service,
+            include_str!("../testdata/systemd/weekday-backup.service")
+        );
+    }
+
+    #[test]
+    fn test_golden_friday_13th_report() {
+        let mut job = CronJob::new("report", "*/10 8-18 13 * FRI", "report --since=$HOME")
+            .with_shell(Some("bash".to_string()))
+            .with_jitter(Some(30_000))
+            .with_misfire_policy(MisfirePolicy::Skip);
+        job.timeout_ms = Some(1_500);
+        let unit = render_unit(&job, "sh").unwrap();
+        assert_eq!(unit.timer, include_str!("../testdata/systemd/report.timer"));
+        assert_eq!(
+            unit.service,
+            include_str!("../testdata/systemd/report.service")
+        );
+    }
+
+    #[test]
+    fn test_golden_month_end_exec() {
+        let job = CronJob::new("month end", "0 23 L * *", "").with_exec(
+            "/usr/bin/close-books",
+            vec!["--ledger".to_string(), "main ledger".to_string()],
+        );
+        let unit = render_unit(&job, "sh").unwrap();
+        assert_eq!(unit.name, "month-end");
+        assert_eq!(
+            unit.timer,
+            include_str!("../testdata/systemd/month-end.timer")
+        );
+        assert_eq!(
+            unit.service,
+            include_str!("../testdata/systemd/month-end.service")
+        );
+    }
+
+    #[test]
+    fn test_golden_one_shot() {
+        let at = Utc.with_ymd_and_hms(2026, 12, 1, 10, 30, 0).unwrap();
+        let job = CronJob::one_shot("migrate", at, "migrate.sh");
+        let unit = render_unit(&job, "sh").unwrap();
+        assert_eq!(
+            unit.timer,
+            include_str!("../testdata/systemd/migrate.timer")
+        );
+    }
+
+    #[test]
+    fn test_render_unit_rejects_unsupported_jobs() {
+        let mut agent = CronJob::new("agent", "0 * * * *", "summarize");
+        agent.job_type = JobType::Agent;
+        assert!(matches!(
+            render_unit(&agent, "sh"),
+            Err(CronError::InvalidConfig(msg)) if msg.contains("agent job agent")
+        ));
+
+        let templated = CronJob::new("t", "0 * * * *", "echo {{ now }}").with_templated(true);
+        assert!(render_unit(&templated, "sh").is_err());
+
+        let never = CronJob::trigger_only("hook", "echo");
+        assert!(render_unit(&never, "sh").is_err());
+
+        let nearest = CronJob::new("w", "0 0 15W * *", "echo");
+        assert!(matches!(
+            render_unit(&nearest, "sh"),
+            Err(CronError::InvalidExpression(_))
+        ));
+    }
+}
@@ -2,12 +2,21 @@
 //!
 //! Provides structured spans, attribute constants, and OTLP metrics
 //! for cron job scheduling and execution observability.
+//!
+//! Metrics are recorded through the `record_*` functions into every
+//! initialized backend: the OpenTelemetry recorder set up by
+//! [`init_cron_metrics`] and, with the `prometheus` feature, the Prometheus
+//! registry set up by [`prometheus::init_prometheus_metrics`]. Either, both
+//! or neither may be initialized.
 
 use opentelemetry::global;
-use opentelemetry::metrics::{Counter, Histogram};
+use opentelemetry::metrics::{Counter, Histogram, UpDownCounter};
 use opentelemetry::KeyValue;
 use std::sync::OnceLock;
 
+#[cfg(feature = "prometheus")]
+pub mod prometheus;
+
 // ============================================================================
 // Span Constants
 // ============================================================================
@@ -40,6 +49,37 @@ pub const ATTR_JOB_DURATION_MS: &str = "a3s.cron.job_duration_ms";
 
 static METRICS: OnceLock<CronMetricsRecorder> = OnceLock::new();
 
+/// A backend the scheduler's metrics are recorded into
+pub(crate) trait MetricsSink: Send + Sync {
+    /// A finished execution of a job, with its status and duration
+    fn job_execution(&self, job_name: &str, status: &str, duration_secs: f64);
+    /// A scheduler tick
+    fn scheduler_tick(&self);
+    /// A change in the number of executing jobs
+    fn jobs_running(&self, delta: i64);
+    /// A change in the number of jobs waiting for a concurrency permit
+    fn jobs_queued(&self, delta: i64);
+    /// A change in the number of occurrences queued behind a job's running
+    /// execution
+    fn job_queue_depth(&self, job_name: &str, delta: i64);
+    /// An execution running longer than its job's expected duration
+    fn job_running_long(&self, job_name: &str);
+    /// An execution that took anomalously long for its job
+    fn job_duration_anomaly(&self, job_name: &str);
+    /// How late a scheduled run started
+    fn schedule_delay(&self, job_name: &str, delay_secs: f64);
+}
+
+/// Every initialized backend
+fn sinks() -> impl Iterator<Item = &'static dyn MetricsSink> {
+    let otel = metrics().map(|m| m as &dyn MetricsSink);
+    #[cfg(feature = "prometheus")]
+    let prometheus = prometheus::metrics().map(|m| m as &dyn MetricsSink);
+    #[cfg(not(feature = "prometheus"))]
+    let prometheus = None;
+    otel.into_iter().chain(prometheus)
+}
+
 /// Holds OpenTelemetry metric instruments for cron scheduling.
 pub struct CronMetricsRecorder {
     /// Total job executions, with attributes: job_name, status
@@ -48,6 +88,22 @@ pub struct CronMetricsRecorder {
     pub job_duration_seconds: Histogram<f64>,
     /// Total scheduler ticks
     pub scheduler_ticks_total: Counter<u64>,
+    /// Jobs currently executing
+    pub jobs_running: UpDownCounter<i64>,
+    /// Due jobs waiting for a concurrency permit
+    pub jobs_queued: UpDownCounter<i64>,
+    /// Occurrences queued behind a running execution of the same job, with
+    /// attribute: job_name
+    pub job_queue_depth: UpDownCounter<i64>,
+    /// Executions that ran longer than their job's expected duration, with
+    /// attribute: job_name
+    pub jobs_running_long_total: Counter<u64>,
+    /// Successful executions that took anomalously long for their job, with
+    /// attribute: job_name
+    pub job_duration_anomalies_total: Counter<u64>,
+    /// Delay between a scheduled run's fire time and its start in seconds,
+    /// with attribute: job_name
+    pub schedule_delay_seconds: Histogram<f64>,
 }
 
 /// Get the global cron metrics recorder (None if not initialized).
@@ -74,32 +130,153 @@ pub fn init_cron_metrics() {
             .u64_counter("a3s_cron_scheduler_ticks_total")
             .with_description("Total scheduler tick cycles")
             .init(),
+        jobs_running: meter
+            .i64_up_down_counter("a3s_cron_jobs_running")
+            .with_description("Cron jobs currently executing")
+            .init(),
+        jobs_queued: meter
+            .i64_up_down_counter("a3s_cron_jobs_queued")
+            .with_description("Cron jobs waiting for a concurrency permit")
+            .init(),
+        job_queue_depth: meter
+            .i64_up_down_counter("a3s_cron_job_queue_depth")
+            .with_description("Occurrences queued behind a running execution of the same job")
+            .init(),
+        jobs_running_long_total: meter
+            .u64_counter("a3s_cron_jobs_running_long_total")
+            .with_description("Cron job executions that ran longer than expected")
+            .init(),
+        job_duration_anomalies_total: meter
+            .u64_counter("a3s_cron_job_duration_anomalies_total")
+            .with_description("Cron job executions that took anomalously long")
+            .init(),
+        schedule_delay_seconds: meter
+            .f64_histogram("a3s_cron_schedule_delay_seconds")
+            .with_description("Delay between a scheduled run's fire time and its start")
+            .init(),
     };
 
     let _ = METRICS.set(recorder);
 }
 
-/// Record a job execution with name, status, and duration.
-///
-/// No-op if metrics have not been initialized.
-pub fn record_job_execution(job_name: &str, status: &str, duration_secs: f64) {
-    if let Some(m) = metrics() {
+impl MetricsSink for CronMetricsRecorder {
+    fn job_execution(&self, job_name: &str, status: &str, duration_secs: f64) {
         let attrs = [
             KeyValue::new("job_name", job_name.to_string()),
             KeyValue::new("status", status.to_string()),
         ];
-        m.jobs_executed_total.add(1, &attrs);
-        m.job_duration_seconds
-            .record(duration_secs, &[KeyValue::new("job_name", job_name.to_string())]);
+        self.jobs_executed_total.add(1, &attrs);
+        self.job_duration_seconds.record(
+            duration_secs,
+            &[KeyValue::new("job_name", job_name.to_string())],
+        );
+    }
+
+    fn scheduler_tick(&self) {
+        self.scheduler_ticks_total.add(1, &[]);
+    }
+
+    fn jobs_running(&self, delta: i64) {
+        self.jobs_running.add(delta, &[]);
+    }
+
+    fn jobs_queued(&self, delta: i64) {
+        self.jobs_queued.add(delta, &[]);
+    }
+
+    fn job_queue_depth(&self, job_name: &str, delta: i64) {
+        self.job_queue_depth
+            .add(delta, &[KeyValue::new("job_name", job_name.to_string())]);
+    }
+
+    fn job_running_long(&self, job_name: &str) {
+        self.jobs_running_long_total
+            .add(1, &[KeyValue::new("job_name", job_name.to_string())]);
+    }
+
+    fn job_duration_anomaly(&self, job_name: &str) {
+        self.job_duration_anomalies_total
+            .add(1, &[KeyValue::new("job_name", job_name.to_string())]);
+    }
+
+    fn schedule_delay(&self, job_name: &str, delay_secs: f64) {
+        self.schedule_delay_seconds.record(
+            delay_secs,
+            &[KeyValue::new("job_name", job_name.to_string())],
+        );
+    }
+}
+
+/// Record a job execution with name, status, and duration.
+///
+/// No-op if no metrics backend has been initialized.
+pub fn record_job_execution(job_name: &str, status: &str, duration_secs: f64) {
+    for sink in sinks() {
+        sink.job_execution(job_name, status, duration_secs);
     }
 }
 
 /// Record a scheduler tick.
 ///
-/// No-op if metrics have not been initialized.
+/// No-op if no metrics backend has been initialized.
 pub fn record_scheduler_tick() {
-    if let Some(m) = metrics() {
-        m.scheduler_ticks_total.add(1, &[]);
+    for sink in sinks() {
+        sink.scheduler_tick();
+    }
+}
+
+/// Record a change in the number of executing jobs.
+///
+/// No-op if no metrics backend has been initialized.
+pub fn record_jobs_running(delta: i64) {
+    for sink in sinks() {
+        sink.jobs_running(delta);
+    }
+}
+
+/// Record a change in the number of jobs waiting for a concurrency permit.
+///
+/// No-op if no metrics backend has been initialized.
+pub fn record_jobs_queued(delta: i64) {
+    for sink in sinks() {
+        sink.jobs_queued(delta);
+    }
+}
+
+/// Record a change in the number of occurrences of a job queued behind its
+/// running execution.
+///
+/// No-op if no metrics backend has been initialized.
+pub fn record_job_queue_depth(job_name: &str, delta: i64) {
+    for sink in sinks() {
+        sink.job_queue_depth(job_name, delta);
+    }
+}
+
+/// Record an execution running longer than its job's expected duration.
+///
+/// No-op if no metrics backend has been initialized.
+pub fn record_job_running_long(job_name: &str) {
+    for sink in sinks() {
+        sink.job_running_long(job_name);
+    }
+}
+
+/// Record an execution that took anomalously long for its job.
+///
+/// No-op if no metrics backend has been initialized.
+pub fn record_job_duration_anomaly(job_name: &str) {
+    for sink in sinks() {
+        sink.job_duration_anomaly(job_name);
+    }
+}
+
+/// Record how late a scheduled run started.
+///
+/// No-op if no metrics backend has been initialized.
+pub fn record_schedule_delay(job_name: &str, delay_secs: f64) {
+    for sink in sinks() {
+        sink.schedule_delay(job_name, delay_secs);
     }
 }
 
@@ -119,7 +296,12 @@ mod tests {
 
     #[test]
     fn test_attribute_keys_follow_convention() {
-        let attrs = [ATTR_JOB_ID, ATTR_JOB_NAME, ATTR_JOB_STATUS, ATTR_JOB_DURATION_MS];
+        let attrs = [
+            ATTR_JOB_ID,
+            ATTR_JOB_NAME,
+            ATTR_JOB_STATUS,
+            ATTR_JOB_DURATION_MS,
+        ];
         for attr in &attrs {
             assert!(
                 attr.starts_with("a3s.cron."),
@@ -131,7 +313,12 @@ mod tests {
 
     #[test]
     fn test_attribute_keys_are_unique() {
-        let keys = vec![ATTR_JOB_ID, ATTR_JOB_NAME, ATTR_JOB_STATUS, ATTR_JOB_DURATION_MS];
+        let keys = [
+            ATTR_JOB_ID,
+            ATTR_JOB_NAME,
+            ATTR_JOB_STATUS,
+            ATTR_JOB_DURATION_MS,
+        ];
         let unique: std::collections::HashSet<&str> = keys.iter().copied().collect();
         assert_eq!(keys.len(), unique.len(), "Attribute keys must be unique");
     }
@@ -148,6 +335,19 @@ mod tests {
         record_scheduler_tick();
     }
 
+    #[test]
+    fn test_record_gauges_no_panic_without_init() {
+        record_jobs_running(1);
+        record_jobs_running(-1);
+        record_jobs_queued(1);
+        record_jobs_queued(-1);
+        record_job_queue_depth("test-job", 1);
+        record_job_queue_depth("test-job", -1);
+        record_job_running_long("test-job");
+        record_job_duration_anomaly("test-job");
+        record_schedule_delay("test-job", 0.25);
+    }
+
     #[test]
     fn test_metrics_returns_none_without_init() {
         // In test context, the global meter provider is not set up for OTLP,
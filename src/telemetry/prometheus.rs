@@ -0,0 +1,369 @@
+//! Prometheus metrics
+//!
+//! Keeps the same metrics as the OpenTelemetry recorder, under the same
+//! names and labels, in a Prometheus registry, for deployments that only
+//! need a `/metrics` endpoint. Initialize with [`init_prometheus_metrics`]
+//! and serve [`render`], or mount [`handler`] with the `http` feature:
+//!
+//! ```ignore
+//! a3s_cron::telemetry::prometheus::init_prometheus_metrics();
+//! let app = a3s_cron::http::router(manager).route(
+//!     "/metrics",
+//!     axum::routing::get(a3s_cron::telemetry::prometheus::handler),
+//! );
+//! ```
+//!
+//! Gauges count from zero when initialized, so initialize before starting
+//! the scheduler.
+
+use super::MetricsSink;
+use ::prometheus::core::Collector;
+use ::prometheus::{
+    HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry,
+    TextEncoder,
+};
+use std::sync::OnceLock;
+
+/// Buckets of the job duration histogram, in seconds
+const DURATION_BUCKETS: [f64; 12] = [
+    0.1, 0.5, 1.0, 5.0, 10.0, 30.0, 60.0, 300.0, 600.0, 1800.0, 3600.0, 7200.0,
+];
+
+/// Buckets of the schedule delay histogram, in seconds
+const DELAY_BUCKETS: [f64; 10] = [0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 10.0, 30.0, 60.0, 300.0];
+
+static PROMETHEUS: OnceLock<PrometheusMetrics> = OnceLock::new();
+
+/// The scheduler's metrics in a Prometheus registry
+pub struct PrometheusMetrics {
+    registry: Registry,
+    jobs_executed_total: IntCounterVec,
+    job_duration_seconds: HistogramVec,
+    scheduler_ticks_total: IntCounter,
+    jobs_running: IntGauge,
+    jobs_queued: IntGauge,
+    job_queue_depth: IntGaugeVec,
+    jobs_running_long_total: IntCounterVec,
+    job_duration_anomalies_total: IntCounterVec,
+    schedule_delay_seconds: HistogramVec,
+}
+
+impl PrometheusMetrics {
+    /// Create the metrics in a registry of their own
+    pub fn new() -> Self {
+        let registry = Registry::new();
+        let counter = |name: &str, help: &str| register(&registry, IntCounter::new(name, help));
+        let counter_vec = |name: &str, help: &str, labels: &[&str]| {
+            register(&registry, IntCounterVec::new(Opts::new(name, help), labels))
+        };
+        let gauge = |name: &str, help: &str| register(&registry, IntGauge::new(name, help));
+        let gauge_vec = |name: &str, help: &str, labels: &[&str]| {
+            register(&registry, IntGaugeVec::new(Opts::new(name, help), labels))
+        };
+        let histogram_vec = |name: &str, help: &str, buckets: &[f64]| {
+            let opts = HistogramOpts::new(name, help).buckets(buckets.to_vec());
+            register(&registry, HistogramVec::new(opts, &["job_name"]))
+        };
+
+        Self {
+            jobs_executed_total: counter_vec(
+                "a3s_cron_jobs_executed_total",
+                "Total cron job executions",
+                &["job_name", "status"],
+            ),
+            job_duration_seconds: histogram_vec(
+                "a3s_cron_job_duration_seconds",
+                "Cron job execution duration in seconds",
+                &DURATION_BUCKETS,
+            ),
+            scheduler_ticks_total: counter(
+                "a3s_cron_scheduler_ticks_total",
+                "Total scheduler tick cycles",
+            ),
+            jobs_running: gauge("a3s_cron_jobs_running", "Cron jobs currently executing"),
+            jobs_queued: gauge(
+                "a3s_cron_jobs_queued",
+                "Cron jobs waiting for a concurrency permit",
+            ),
+            job_queue_depth: gauge_vec(
+                "a3s_cron_job_queue_depth",
+                "Occurrences queued behind a running execution of the same job",
+                &["job_name"],
+            ),
+            jobs_running_long_total: counter_vec(
+                "a3s_cron_jobs_running_long_total",
+                "Cron job executions that ran longer than expected",
+                &["job_name"],
+            ),
+            job_duration_anomalies_total: counter_vec(
+                "a3s_cron_job_duration_anomalies_total",
+                "Cron job executions that took anomalously long",
+                &["job_name"],
+            ),
+            schedule_delay_seconds: histogram_vec(
+                "a3s_cron_schedule_delay_seconds",
+                "Delay between a scheduled run's fire time and its start",
+                &DELAY_BUCKETS,
+            ),
+            registry,
+        }
+    }
+
+    /// The registry holding the metrics, to add an application's own
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+
+    /// Render every metric in the registry in the text exposition format
+    pub fn render(&self) -> String {
+        TextEncoder::new()
+            .encode_to_string(&self.registry.gather())
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to render Prometheus metrics: {}", e);
+                String::new()
+            })
+    }
+}
+
+impl Default for PrometheusMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricsSink for PrometheusMetrics {
+    fn job_execution(&self, job_name: &str, status: &str, duration_secs: f64) {
+        self.jobs_executed_total
+            .with_label_values(&[job_name, status])
+            .inc();
+        self.job_duration_seconds
+            .with_label_values(&[job_name])
+            .observe(duration_secs);
+    }
+
+    fn scheduler_tick(&self) {
+        self.scheduler_ticks_total.inc();
+    }
+
+    fn jobs_running(&self, delta: i64) {
+        self.jobs_running.add(delta);
+    }
+
+    fn jobs_queued(&self, delta: i64) {
+        self.jobs_queued.add(delta);
+    }
+
+    fn job_queue_depth(&self, job_name: &str, delta: i64) {
+        self.job_queue_depth
+            .with_label_values(&[job_name])
+            .add(delta);
+    }
+
+    fn job_running_long(&self, job_name: &str) {
+        self.jobs_running_long_total
+            .with_label_values(&[job_name])
+            .inc();
+    }
+
+    fn job_duration_anomaly(&self, job_name: &str) {
+        self.job_duration_anomalies_total
+            .with_label_values(&[job_name])
+            .inc();
+    }
+
+    fn schedule_delay(&self, job_name: &str, delay_secs: f64) {
+        self.schedule_delay_seconds
+            .with_label_values(&[job_name])
+            .observe(delay_secs);
+    }
+}
+
+/// Register a metric built from constant, valid options in a registry
+/// holding only this module's uniquely named metrics, so neither step fails
+fn register<M>(registry: &Registry, metric: ::prometheus::Result<M>) -> M
+where
+    M: Collector + Clone + 'static,
+{
+    let metric = metric.expect("metric options are valid");
+    registry
+        .register(Box::new(metric.clone()))
+        .expect("metric names are unique");
+    metric
+}
+
+/// Get the global Prometheus metrics (None if not initialized).
+pub fn metrics() -> Option<&'static PrometheusMetrics> {
+    PROMETHEUS.get()
+}
+
+/// Initialize the global Prometheus metrics, which the scheduler then
+/// records into alongside any OpenTelemetry recorder.
+///
+/// Safe to call multiple times; only the first call takes effect.
+pub fn init_prometheus_metrics() -> &'static PrometheusMetrics {
+    PROMETHEUS.get_or_init(PrometheusMetrics::new)
+}
+
+/// Render the global metrics in the text exposition format.
+///
+/// Empty if metrics have not been initialized.
+pub fn render() -> String {
+    metrics().map(PrometheusMetrics::render).unwrap_or_default()
+}
+
+/// Axum handler serving [`render`] with the exposition format's content type
+#[cfg(feature = "http")]
+pub async fn handler() -> impl axum::response::IntoResponse {
+    (
+        [(axum::http::header::CONTENT_TYPE, ::prometheus::TEXT_FORMAT)],
+        render(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prometheus_parse::{Scrape, Value};
+
+    fn parse(text: &str) -> Scrape {
+        Scrape::parse(text.lines().map(|line| Ok(line.to_string()))).unwrap()
+    }
+
+    #[test]
+    fn test_render() {
+        let metrics = PrometheusMetrics::new();
+        metrics.job_execution("backup", "success", 2.5);
+        metrics.job_execution("backup", "failed", 0.2);
+        metrics.job_execution("report", "success", 40.0);
+        metrics.scheduler_tick();
+        metrics.scheduler_tick();
+        metrics.jobs_running(2);
+        metrics.jobs_running(-1);
+        metrics.jobs_queued(1);
+        metrics.job_queue_depth("backup", 3);
+        metrics.job_running_long("report");
+        metrics.job_duration_anomaly("report");
+        metrics.schedule_delay("backup", 0.75);
+
+        let text = metrics.render();
+        let scrape = parse(&text);
+        let sample = |name: &str, labels: &[(&str, &str)]| {
+            scrape
+                .samples
+                .iter()
+                .find(|sample| {
+                    sample.metric == name
+                        && labels
+                            .iter()
+                            .all(|(key, value)| sample.labels.get(key) == Some(value))
+                })
+                .unwrap_or_else(|| panic!("no sample {} {:?} in\n{}", name, labels, text))
+        };
+
+        let executed = sample(
+            "a3s_cron_jobs_executed_total",
+            &[("job_name", "backup"), ("status", "failed")],
+        );
+        assert_eq!(executed.value, Value::Counter(1.0));
+        let executed = sample(
+            "a3s_cron_jobs_executed_total",
+            &[("job_name", "report"), ("status", "success")],
+        );
+        assert_eq!(executed.value, Value::Counter(1.0));
+        assert_eq!(
+            sample("a3s_cron_scheduler_ticks_total", &[]).value,
+            Value::Counter(2.0)
+        );
+        assert_eq!(
+            sample("a3s_cron_jobs_running", &[]).value,
+            Value::Gauge(1.0)
+        );
+        assert_eq!(sample("a3s_cron_jobs_queued", &[]).value, Value::Gauge(1.0));
+        assert_eq!(
+            sample("a3s_cron_job_queue_depth", &[("job_name", "backup")]).value,
+            Value::Gauge(3.0)
+        );
+        assert_eq!(
+            sample(
+                "a3s_cron_jobs_running_long_total",
+                &[("job_name", "report")]
+            )
+            .value,
+            Value::Counter(1.0)
+        );
+        assert_eq!(
+            sample(
+                "a3s_cron_job_duration_anomalies_total",
+                &[("job_name", "report")]
+            )
+            .value,
+            Value::Counter(1.0)
+        );
+
+        let Value::Histogram(buckets) =
+            &sample("a3s_cron_job_duration_seconds", &[("job_name", "backup")]).value
+        else {
+            panic!("job duration is not a histogram");
+        };
+        let count = |le: f64| {
+            buckets
+                .iter()
+                .find(|bucket| bucket.less_than == le)
+                .unwrap()
+                .count
+        };
+        assert_eq!(count(0.5), 1.0);
+        assert_eq!(count(5.0), 2.0);
+        assert_eq!(count(f64::INFINITY), 2.0);
+        assert!(matches!(
+            sample("a3s_cron_schedule_delay_seconds", &[("job_name", "backup")]).value,
+            Value::Histogram(_)
+        ));
+
+        assert_eq!(
+            scrape.docs.get("a3s_cron_jobs_executed_total").unwrap(),
+            "Total cron job executions"
+        );
+    }
+
+    #[test]
+    fn test_global_metrics() {
+        let metrics = init_prometheus_metrics();
+        assert!(std::ptr::eq(metrics, init_prometheus_metrics()));
+
+        crate::telemetry::record_job_execution("prometheus-global", "timeout", 1.0);
+        let scrape = parse(&render());
+        assert!(scrape.samples.iter().any(|sample| {
+            sample.metric == "a3s_cron_jobs_executed_total"
+                && sample.labels.get("job_name") == Some("prometheus-global")
+                && sample.labels.get("status") == Some("timeout")
+        }));
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_handler() {
+        use axum::body::Body;
+        use axum::http::{header, Request, StatusCode};
+        use tower::ServiceExt;
+
+        init_prometheus_metrics();
+        crate::telemetry::record_scheduler_tick();
+        let app = axum::Router::new().route("/metrics", axum::routing::get(handler));
+        let response = app
+            .oneshot(Request::get("/metrics").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            ::prometheus::TEXT_FORMAT
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        assert!(text.contains("a3s_cron_scheduler_ticks_total"));
+        parse(&text);
+    }
+}
@@ -0,0 +1,262 @@
+//! Command templating
+//!
+//! Expands `{{ ... }}` tags in the command and environment values of jobs
+//! marked `templated` when they run. A tag names a variable, optionally
+//! passed through filters:
+//!
+//! ```text
+//! backup.sh --date={{ now | date '%Y-%m-%d' }} --job={{ job.name }}
+//! report.sh --since={{ last_run | default 'never' }}
+//! ```
+//!
+//! Variables are `job.id`, `job.name`, `job.schedule`, `execution.id`,
+//! `now`, `last_run` and `workspace`. Times render as RFC 3339 unless
+//! formatted with `date`. Any error fails the execution instead of running a
+//! half-rendered command.
+
+use crate::types::{CommandKind, CronError, CronJob, Result};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Utc};
+
+/// Values available to a job's templates
+pub(crate) struct TemplateContext<'a> {
+    /// The job being run
+    pub(crate) job: &'a CronJob,
+    /// ID of the execution being started
+    pub(crate) execution_id: &'a str,
+    /// When the execution started
+    pub(crate) now: DateTime<Utc>,
+    /// Default working directory of the manager
+    pub(crate) workspace: &'a str,
+}
+
+/// Value of a variable while filters are applied
+enum Value {
+    Text(String),
+    Time(DateTime<Utc>),
+    /// A variable without a value, such as `last_run` before the first run
+    Missing(&'static str),
+}
+
+/// Render the command, arguments and environment values of a templated job
+pub(crate) fn render_job(context: &TemplateContext<'_>) -> Result<CronJob> {
+    let mut job = context.job.clone();
+    match job.command_kind {
+        CommandKind::Shell => job.command = render(&job.command, context)?,
+        CommandKind::Exec => {
+            for arg in &mut job.args {
+                *arg = render(arg, context)?;
+            }
+        }
+    }
+    for (key, value) in &mut job.env {
+        *value = render(value, context)
+            .map_err(|e| CronError::Template(format!("in env {}: {}", key, message(e))))?;
+    }
+    Ok(job)
+}
+
+/// Expand every tag in `template`
+pub(crate) fn render(template: &str, context: &TemplateContext<'_>) -> Result<String> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .ok_or_else(|| error(format!("unclosed tag: {{{{{}", after)))?;
+        out.push_str(&expand(after[..end].trim(), context)?);
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Evaluate the expression inside one tag
+fn expand(expression: &str, context: &TemplateContext<'_>) -> Result<String> {
+    let mut parts = split_pipes(expression)?.into_iter();
+    let name = parts.next().unwrap_or_default();
+    let mut value = variable(name, context)?;
+    for filter in parts {
+        value = apply(filter, value)?;
+    }
+    match value {
+        Value::Text(text) => Ok(text),
+        Value::Time(time) => Ok(time.to_rfc3339()),
+        Value::Missing(name) => Err(error(format!(
+            "{} has no value; add a fallback with `| default '...'`",
+            name
+        ))),
+    }
+}
+
+fn variable(name: &str, context: &TemplateContext<'_>) -> Result<Value> {
+    let job = context.job;
+    Ok(match name {
+        "job.id" => Value::Text(job.id.clone()),
+        "job.name" => Value::Text(job.name.clone()),
+        "job.schedule" => Value::Text(job.schedule.clone()),
+        "execution.id" => Value::Text(context.execution_id.to_string()),
+        "now" => Value::Time(context.now),
+        "last_run" => job.last_run.map_or(Value::Missing("last_run"), Value::Time),
+        "workspace" => Value::Text(context.workspace.to_string()),
+        "" => return Err(error("empty tag".to_string())),
+        other => return Err(error(format!("unknown variable: {}", other))),
+    })
+}
+
+/// Apply a filter such as `date '%Y-%m-%d'`
+fn apply(filter: &str, value: Value) -> Result<Value> {
+    let (name, argument) = match filter.split_once(char::is_whitespace) {
+        Some((name, argument)) => (name, Some(unquote(argument.trim())?)),
+        None => (filter, None),
+    };
+    match (name, argument, value) {
+        ("date", Some(format), Value::Time(time)) => {
+            let items = StrftimeItems::new(format).collect::<Vec<_>>();
+            if items.iter().any(|item| matches!(item, Item::Error)) {
+                return Err(error(format!("invalid date format: {}", format)));
+            }
+            Ok(Value::Text(
+                time.format_with_items(items.into_iter()).to_string(),
+            ))
+        }
+        ("date", Some(_), Value::Missing(name)) => Ok(Value::Missing(name)),
+        ("date", Some(_), Value::Text(_)) => Err(error(
+            "date filter applied to a value that isn't a time".to_string(),
+        )),
+        ("default", Some(fallback), Value::Missing(_)) => Ok(Value::Text(fallback.to_string())),
+        ("default", Some(_), value) => Ok(value),
+        ("date" | "default", None, _) => {
+            Err(error(format!("{} filter needs a quoted argument", name)))
+        }
+        (other, _, _) => Err(error(format!("unknown filter: {}", other))),
+    }
+}
+
+/// Split an expression on `|` outside quotes, trimming each part
+fn split_pipes(expression: &str) -> Result<Vec<&str>> {
+    let mut parts = Vec::new();
+    let mut quote = None;
+    let mut start = 0;
+    for (i, c) in expression.char_indices() {
+        match (quote, c) {
+            (None, '\'' | '"') => quote = Some(c),
+            (Some(open), c) if c == open => quote = None,
+            (None, '|') => {
+                parts.push(expression[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if quote.is_some() {
+        return Err(error(format!("unclosed quote in: {}", expression)));
+    }
+    parts.push(expression[start..].trim());
+    Ok(parts)
+}
+
+/// Strip the quotes around a filter argument
+fn unquote(argument: &str) -> Result<&str> {
+    let quoted = argument.len() >= 2
+        && (argument.starts_with('\'') && argument.ends_with('\'')
+            || argument.starts_with('"') && argument.ends_with('"'));
+    if quoted {
+        Ok(&argument[1..argument.len() - 1])
+    } else {
+        Err(error(format!(
+            "filter argument must be quoted: {}",
+            argument
+        )))
+    }
+}
+
+fn error(message: String) -> CronError {
+    CronError::Template(message)
+}
+
+/// Inner message of a template error, without the variant prefix
+fn message(err: CronError) -> String {
+    match err {
+        CronError::Template(msg) => msg,
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render_for(template: &str, job: &CronJob) -> Result<String> {
+        let now = DateTime::parse_from_rfc3339("2026-03-01T09:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let context = TemplateContext {
+            job,
+            execution_id: "exec-1",
+            now,
+            workspace: "/srv/work",
+        };
+        render(template, &context)
+    }
+
+    #[test]
+    fn test_render_variables() {
+        let job = CronJob::new("backup", "0 2 * * *", "");
+        let rendered = render_for(
+            "backup.sh --date={{ now | date '%Y-%m-%d' }} --job={{job.name}} {{ execution.id }} {{ workspace }}",
+            &job,
+        )
+        .unwrap();
+        assert_eq!(
+            rendered,
+            "backup.sh --date=2026-03-01 --job=backup exec-1 /srv/work"
+        );
+        assert_eq!(
+            render_for("{{ now }}", &job).unwrap(),
+            "2026-03-01T09:30:00+00:00"
+        );
+        assert_eq!(render_for("no tags }}", &job).unwrap(), "no tags }}");
+    }
+
+    #[test]
+    fn test_render_last_run() {
+        let mut job = CronJob::new("report", "0 2 * * *", "");
+        assert!(render_for("{{ last_run }}", &job).is_err());
+        assert_eq!(
+            render_for("{{ last_run | date '%H:%M' | default 'never' }}", &job).unwrap(),
+            "never"
+        );
+
+        job.last_run = Some(
+            DateTime::parse_from_rfc3339("2026-02-28T02:00:00Z")
+                .unwrap()
+                .with_timezone(&Utc),
+        );
+        assert_eq!(
+            render_for("{{ last_run | date '%H:%M' | default 'never' }}", &job).unwrap(),
+            "02:00"
+        );
+    }
+
+    #[test]
+    fn test_render_errors() {
+        let job = CronJob::new("job", "0 2 * * *", "");
+        for template in [
+            "{{ nope }}",
+            "{{ now",
+            "{{ }}",
+            "{{ now | upper }}",
+            "{{ now | date }}",
+            "{{ now | date %Y }}",
+            "{{ now | date '%Q' }}",
+            "{{ job.name | date '%Y' }}",
+            "{{ now | date '%Y }}",
+        ] {
+            let err = render_for(template, &job).unwrap_err();
+            assert!(matches!(err, CronError::Template(_)), "{}", template);
+        }
+    }
+}
@@ -1,7 +1,12 @@
 //! Core types for the cron library
 
+use crate::calendar::Calendar;
+use crate::parser::CronExpression;
+use crate::window::TimeWindow;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 use uuid::Uuid;
 
@@ -23,6 +28,55 @@ pub trait AgentExecutor: Send + Sync {
     ) -> std::result::Result<String, String>;
 }
 
+/// Handler for native jobs, run in-process instead of forking a command.
+///
+/// Register handlers with `CronManager::register_handler`; a native job's
+/// `command` names the handler it runs. The handler's future is dropped if
+/// the job times out or is cancelled.
+#[async_trait::async_trait]
+pub trait NativeJobHandler: Send + Sync {
+    /// Run the job, returning its output or an error message
+    async fn run(&self, ctx: JobContext) -> std::result::Result<JobOutput, String>;
+}
+
+/// What a native job handler is told about the run
+#[derive(Debug, Clone)]
+pub struct JobContext {
+    /// Job ID
+    pub job_id: String,
+    /// Job name
+    pub job_name: String,
+    /// ID of this execution
+    pub execution_id: String,
+    /// Nominal fire time, or the start time of runs not started by the schedule
+    pub scheduled_for: DateTime<Utc>,
+    /// How the run was started (`scheduled`, `manual`, `triggered` or `hook`)
+    pub trigger: String,
+    /// Working directory of the job
+    pub working_dir: String,
+    /// The job's environment variables
+    pub env: Vec<(String, String)>,
+}
+
+/// Output of a successful native job run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JobOutput {
+    /// Recorded as the execution's stdout
+    pub stdout: String,
+    /// Recorded as the execution's stderr
+    pub stderr: String,
+}
+
+impl JobOutput {
+    /// Output with the given stdout
+    pub fn new(stdout: impl Into<String>) -> Self {
+        Self {
+            stdout: stdout.into(),
+            stderr: String::new(),
+        }
+    }
+}
+
 /// Cron library errors
 #[derive(Debug, Error)]
 pub enum CronError {
@@ -46,6 +100,68 @@ pub enum CronError {
     #[error("Execution error: {0}")]
     Execution(String),
 
+    /// Schedule fires more often than the configured minimum interval
+    #[error("Schedule fires every {interval_secs}s, more often than the minimum interval of {min_interval_secs}s")]
+    ScheduleTooFrequent {
+        /// Shortest observed gap between fire times, in seconds
+        interval_secs: u64,
+        /// Configured minimum interval, in seconds
+        min_interval_secs: u64,
+    },
+
+    /// Job has completed and cannot be resumed as-is
+    #[error("Job has completed: {0}")]
+    JobCompleted(String),
+
+    /// Job is disabled and must be enabled first
+    #[error("Job is disabled: {0}")]
+    JobDisabled(String),
+
+    /// Job is paused and must be resumed, or the run forced
+    #[error("Job is paused: {0}; resume it or set force_if_paused to run it anyway")]
+    JobPaused(String),
+
+    /// Job triggers would form a cycle
+    #[error("Trigger cycle detected: {0}")]
+    TriggerCycle(String),
+
+    /// Job is in a blackout window
+    #[error("Job is in a blackout window: {0}")]
+    InBlackout(String),
+
+    /// Execution is unknown or has already finished
+    #[error("Execution not found or not running: {0}")]
+    ExecutionNotRunning(String),
+
+    /// No execution has this ID
+    #[error("Execution not found: {0}")]
+    ExecutionNotFound(String),
+
+    /// No workspace of a service has this name
+    #[error("Workspace not found: {0}")]
+    WorkspaceNotFound(String),
+
+    /// Invalid scheduler configuration
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+
+    /// The scheduler was started while already running
+    #[error("Scheduler is already running")]
+    AlreadyRunning,
+
+    /// Command template could not be rendered
+    #[error("Template error: {0}")]
+    Template(String),
+
+    /// A control socket request failed on the server
+    #[error("Control request failed ({code}): {message}")]
+    Control {
+        /// JSON-RPC error code
+        code: i64,
+        /// What went wrong
+        message: String,
+    },
+
     /// Timeout error
     #[error("Job execution timed out after {0}ms")]
     Timeout(u64),
@@ -69,22 +185,23 @@ pub enum JobStatus {
     Paused,
     /// Job is currently running
     Running,
+    /// Job's date window has closed and it will not run again
+    Completed,
+    /// Job is switched off and kept for reference until enabled again
+    Disabled,
 }
 
 /// Job type — determines how the command is executed
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum JobType {
-    /// Execute as a shell command via `sh -c`
+    /// Execute as a shell command, via `sh -c` unless another shell is chosen
+    #[default]
     Shell,
     /// Execute as an agent prompt via `Agent::send()`
     Agent,
-}
-
-impl Default for JobType {
-    fn default() -> Self {
-        Self::Shell
-    }
+    /// Run the in-process handler registered under the name in `command`
+    Native,
 }
 
 impl std::fmt::Display for JobType {
@@ -92,12 +209,189 @@ impl std::fmt::Display for JobType {
         match self {
             JobType::Shell => write!(f, "shell"),
             JobType::Agent => write!(f, "agent"),
+            JobType::Native => write!(f, "native"),
+        }
+    }
+}
+
+/// How a shell job's command is started
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CommandKind {
+    /// `command` is a script run by a shell
+    #[default]
+    Shell,
+    /// `program` is spawned directly with `args`, without a shell
+    Exec,
+}
+
+impl std::fmt::Display for CommandKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommandKind::Shell => write!(f, "shell"),
+            CommandKind::Exec => write!(f, "exec"),
+        }
+    }
+}
+
+/// Output stream of a job's process
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputStream {
+    /// Standard output
+    Stdout,
+    /// Standard error
+    Stderr,
+}
+
+impl std::fmt::Display for OutputStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutputStream::Stdout => write!(f, "stdout"),
+            OutputStream::Stderr => write!(f, "stderr"),
+        }
+    }
+}
+
+/// Which of the scheduler's environment variables a job's process inherits
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnvMode {
+    /// Inherit the whole environment
+    #[default]
+    Inherit,
+    /// Inherit nothing; only `PATH`, `HOME` and the job's own variables are set
+    Clean,
+    /// Inherit only the listed variables
+    InheritAllow(Vec<String>),
+}
+
+/// Operating system limits applied to a job's process. Only enforced on
+/// Unix; elsewhere they are ignored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceLimits {
+    /// Scheduling priority (`nice` value, -20 to 19)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nice: Option<i32>,
+    /// Largest address space in bytes (`RLIMIT_AS`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_memory_bytes: Option<u64>,
+    /// Largest file the process may write in bytes (`RLIMIT_FSIZE`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_file_size_bytes: Option<u64>,
+    /// Most file descriptors open at once (`RLIMIT_NOFILE`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_open_files: Option<u64>,
+}
+
+impl ResourceLimits {
+    /// Create limits that restrict nothing
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run at the given `nice` value
+    pub fn with_nice(mut self, nice: i32) -> Self {
+        self.nice = Some(nice);
+        self
+    }
+
+    /// Cap the process's address space
+    pub fn with_max_memory_bytes(mut self, bytes: u64) -> Self {
+        self.max_memory_bytes = Some(bytes);
+        self
+    }
+
+    /// Cap the size of files the process writes
+    pub fn with_max_file_size_bytes(mut self, bytes: u64) -> Self {
+        self.max_file_size_bytes = Some(bytes);
+        self
+    }
+
+    /// Cap the number of files the process has open
+    pub fn with_max_open_files(mut self, count: u64) -> Self {
+        self.max_open_files = Some(count);
+        self
+    }
+
+    /// Whether no limit is set
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// What to do when a job is due while a previous run is still in flight
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConcurrencyPolicy {
+    /// Start another run alongside the existing one
+    #[default]
+    Allow,
+    /// Skip the new run and record it as skipped
+    Forbid,
+    /// Cancel the running execution and start a fresh one
+    Replace,
+    /// Run after the execution in flight finishes, in fire-time order
+    Queue {
+        /// Most occurrences waiting at once; later ones are skipped
+        max_queue: usize,
+    },
+}
+
+impl std::fmt::Display for ConcurrencyPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConcurrencyPolicy::Allow => write!(f, "allow"),
+            ConcurrencyPolicy::Forbid => write!(f, "forbid"),
+            ConcurrencyPolicy::Replace => write!(f, "replace"),
+            ConcurrencyPolicy::Queue { max_queue } => write!(f, "queue({})", max_queue),
         }
     }
 }
 
+/// How a job's fire times are determined
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduleKind {
+    /// Recurring runs from the cron expression in `schedule`
+    #[default]
+    Cron,
+    /// A single run at the given time
+    At(DateTime<Utc>),
+    /// No scheduled runs; the job only runs when triggered or run manually
+    Never,
+}
+
+/// What to do with runs that came due while the scheduler was stopped
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MisfirePolicy {
+    /// Run once for all missed occurrences
+    #[default]
+    FireOnce,
+    /// Skip missed occurrences and wait for the next fire time
+    Skip,
+    /// Run up to `max` missed occurrences back to back
+    CatchUp {
+        /// Maximum number of missed runs to execute
+        max: u32,
+    },
+}
+
+/// What to do with a run whose fire time falls in a blackout window, or on
+/// a date of the job's excluded calendar
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BlackoutPolicy {
+    /// Record the run as skipped and wait for the next fire time
+    #[default]
+    Skip,
+    /// Run once the blackout window ends
+    Defer,
+}
+
 /// Agent configuration for agent-mode cron jobs
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AgentJobConfig {
     /// LLM model identifier (e.g., "claude-sonnet-4-20250514")
     pub model: String,
@@ -120,10 +414,16 @@ impl std::fmt::Display for JobStatus {
             JobStatus::Active => write!(f, "active"),
             JobStatus::Paused => write!(f, "paused"),
             JobStatus::Running => write!(f, "running"),
+            JobStatus::Completed => write!(f, "completed"),
+            JobStatus::Disabled => write!(f, "disabled"),
         }
     }
 }
 
+/// Timeout of jobs that don't set their own when the manager has no default
+/// either, in milliseconds
+pub const DEFAULT_TIMEOUT_MS: u64 = 60_000;
+
 /// A cron job definition
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CronJob {
@@ -136,9 +436,27 @@ pub struct CronJob {
     /// Cron schedule expression (5 fields: min hour day month weekday)
     pub schedule: String,
 
-    /// Command to execute (shell command or agent prompt, depending on job_type)
+    /// Command to execute (shell command, agent prompt or native handler name,
+    /// depending on job_type).
+    /// For exec jobs, a shell-quoted rendering of `program` and `args`.
     pub command: String,
 
+    /// Whether `command` runs through a shell or `program` is spawned directly
+    #[serde(default, skip_serializing_if = "is_shell_kind")]
+    pub command_kind: CommandKind,
+
+    /// Program spawned by exec jobs
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub program: String,
+
+    /// Arguments passed to `program` as-is
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+
+    /// Whether the job recurs on `schedule` or runs once
+    #[serde(default, skip_serializing_if = "is_cron_kind")]
+    pub schedule_kind: ScheduleKind,
+
     /// Job type: shell (default) or agent
     #[serde(default)]
     pub job_type: JobType,
@@ -150,8 +468,10 @@ pub struct CronJob {
     /// Current job status
     pub status: JobStatus,
 
-    /// Execution timeout in milliseconds (default: 60000)
-    pub timeout_ms: u64,
+    /// Execution timeout in milliseconds; `None` inherits the manager's
+    /// default timeout
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
 
     /// Creation timestamp
     pub created_at: DateTime<Utc>,
@@ -180,6 +500,232 @@ pub struct CronJob {
     /// Environment variables for command execution
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub env: Vec<(String, String)>,
+
+    /// Names of environment variables holding secrets. Their values are
+    /// masked in stored output, events and the job as returned by the
+    /// manager.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub secret_env: Vec<String>,
+
+    /// Whether `{{ ... }}` tags in the command and env values are expanded
+    /// when the job runs
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub templated: bool,
+
+    /// Environment inherited by the command; the manager's default when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env_mode: Option<EnvMode>,
+
+    /// Shell running the command, e.g. `bash`; the manager's default when
+    /// unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shell: Option<String>,
+
+    /// Order among jobs due at the same time, higher first (default 0)
+    #[serde(default)]
+    pub priority: i32,
+
+    /// Least time between the starts of two runs, however they are
+    /// triggered; runs coming sooner are skipped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_interval_ms: Option<u64>,
+
+    /// How long a run normally takes at most; runs still going after this
+    /// long are reported, but left running
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_duration_ms: Option<u64>,
+
+    /// Limits applied to the job's process
+    #[serde(default, skip_serializing_if = "ResourceLimits::is_empty")]
+    pub resource_limits: ResourceLimits,
+
+    /// Number of times a failed or timed-out execution is retried
+    #[serde(default)]
+    pub max_retries: u32,
+
+    /// Delay before the first retry in milliseconds (default: 1000)
+    #[serde(default = "default_retry_delay_ms")]
+    pub retry_delay_ms: u64,
+
+    /// Factor applied to the retry delay after each attempt (default: 1.0)
+    #[serde(default = "default_retry_backoff")]
+    pub retry_backoff: f64,
+
+    /// Earliest time the job may run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_before: Option<DateTime<Utc>>,
+
+    /// Latest time the job may run; the job completes once it passes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_after: Option<DateTime<Utc>>,
+
+    /// Complete the job after this many runs (successful or failed)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_runs: Option<u64>,
+
+    /// Whether manual runs count toward `max_runs` (default: true)
+    #[serde(default = "default_count_manual")]
+    pub count_manual: bool,
+
+    /// Total manual runs, included in `run_count` and `fail_count`
+    #[serde(default)]
+    pub manual_run_count: u64,
+
+    /// Whether executions are saved to the job's history (default: true).
+    /// Statistics and events are kept up to date either way.
+    #[serde(default = "default_record_history")]
+    pub record_history: bool,
+
+    /// Failed runs since the last success
+    #[serde(default)]
+    pub consecutive_failures: u32,
+
+    /// Error of the most recent failed run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+
+    /// Pause the job after this many consecutive failed runs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_pause_after: Option<u32>,
+
+    /// Handling of runs missed while the scheduler was stopped
+    #[serde(default)]
+    pub misfire_policy: MisfirePolicy,
+
+    /// Behavior when the job is due while a previous run is still in flight
+    #[serde(default)]
+    pub concurrency_policy: ConcurrencyPolicy,
+
+    /// Grace period between SIGTERM and SIGKILL on timeout (0 = kill immediately)
+    #[serde(default)]
+    pub kill_grace_ms: u64,
+
+    /// Upper bound of a random delay added to each scheduled run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jitter_ms: Option<u64>,
+
+    /// Time windows during which the job must not run
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blackouts: Vec<TimeWindow>,
+
+    /// Handling of fire times that fall in a blackout window
+    #[serde(default)]
+    pub blackout_policy: BlackoutPolicy,
+
+    /// Name of the calendar whose dates the job doesn't run on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exclude_calendar: Option<String>,
+
+    /// Handling of fire times on the excluded calendar's dates: skipped, or
+    /// deferred to the start of the next day not excluded
+    #[serde(default)]
+    pub holiday_policy: BlackoutPolicy,
+
+    /// Only fire times inside this window are run; others are passed over
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_window: Option<TimeWindow>,
+
+    /// Jobs (by id or name) to run after each successful run of this one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub triggers: Vec<String>,
+
+    /// Job (by name) or inline command to run after a failed or timed-out run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_failure: Option<String>,
+
+    /// Labels for grouping and filtering jobs, e.g. `env:prod`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// Number of upcoming scheduled runs to skip
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub snoozed_runs: u32,
+
+    /// Scheduled runs before this time are skipped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snoozed_until: Option<DateTime<Utc>>,
+
+    /// Whether successful runs report if their output differs from the
+    /// previous successful run's
+    #[serde(default)]
+    pub notify_on_change: bool,
+
+    /// Whether output differing only in whitespace counts as unchanged
+    #[serde(default)]
+    pub ignore_whitespace_changes: bool,
+
+    /// Hash of the output of the latest successful run, when
+    /// `notify_on_change` is set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_output_hash: Option<String>,
+
+    /// Running statistics of the durations of successful runs, against
+    /// which each new run is checked for being unusually slow
+    #[serde(default, skip_serializing_if = "DurationStats::is_empty")]
+    pub duration_stats: DurationStats,
+
+    /// Schedule analysis computed when the job was added (not persisted)
+    #[serde(skip)]
+    pub schedule_info: Option<ScheduleInfo>,
+
+    /// Health, computed by listings that assess it (not persisted)
+    #[serde(skip)]
+    pub health: Option<JobHealth>,
+
+    /// Timeout that applies to the job's runs, resolved from its own and
+    /// the manager's default when the job is read (not persisted)
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub effective_timeout_ms: Option<u64>,
+}
+
+fn is_cron_kind(kind: &ScheduleKind) -> bool {
+    *kind == ScheduleKind::Cron
+}
+
+fn is_shell_kind(kind: &CommandKind) -> bool {
+    *kind == CommandKind::Shell
+}
+
+fn is_zero(value: &u32) -> bool {
+    *value == 0
+}
+
+fn default_count_manual() -> bool {
+    true
+}
+
+fn default_record_history() -> bool {
+    true
+}
+
+fn default_retry_delay_ms() -> u64 {
+    1_000
+}
+
+fn default_retry_backoff() -> f64 {
+    1.0
+}
+
+/// Analysis of a validated schedule expression
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduleInfo {
+    /// Cron expression, as given
+    pub expression: String,
+
+    /// Human-readable description
+    pub description: String,
+
+    /// Next fire time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_run: Option<DateTime<Utc>>,
+
+    /// Average interval between the next few runs, in seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interval_secs: Option<u64>,
+
+    /// Shortest interval between the next few runs, in seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_interval_secs: Option<u64>,
 }
 
 impl CronJob {
@@ -195,10 +741,14 @@ impl CronJob {
             name: name.into(),
             schedule: schedule.into(),
             command: command.into(),
+            schedule_kind: ScheduleKind::default(),
+            command_kind: CommandKind::default(),
+            program: String::new(),
+            args: Vec::new(),
             job_type: JobType::default(),
             agent_config: None,
             status: JobStatus::Active,
-            timeout_ms: 60_000,
+            timeout_ms: None,
             created_at: now,
             updated_at: now,
             last_run: None,
@@ -207,15 +757,62 @@ impl CronJob {
             fail_count: 0,
             working_dir: None,
             env: Vec::new(),
+            secret_env: Vec::new(),
+            templated: false,
+            env_mode: None,
+            shell: None,
+            priority: 0,
+            min_interval_ms: None,
+            expected_duration_ms: None,
+            resource_limits: ResourceLimits::default(),
+            max_retries: 0,
+            retry_delay_ms: default_retry_delay_ms(),
+            retry_backoff: default_retry_backoff(),
+            not_before: None,
+            not_after: None,
+            max_runs: None,
+            count_manual: default_count_manual(),
+            manual_run_count: 0,
+            record_history: default_record_history(),
+            consecutive_failures: 0,
+            last_error: None,
+            auto_pause_after: None,
+            misfire_policy: MisfirePolicy::default(),
+            concurrency_policy: ConcurrencyPolicy::default(),
+            kill_grace_ms: 0,
+            jitter_ms: None,
+            blackouts: Vec::new(),
+            blackout_policy: BlackoutPolicy::default(),
+            exclude_calendar: None,
+            holiday_policy: BlackoutPolicy::default(),
+            allowed_window: None,
+            triggers: Vec::new(),
+            on_failure: None,
+            tags: Vec::new(),
+            snoozed_runs: 0,
+            snoozed_until: None,
+            notify_on_change: false,
+            ignore_whitespace_changes: false,
+            last_output_hash: None,
+            duration_stats: DurationStats::default(),
+            schedule_info: None,
+            health: None,
+            effective_timeout_ms: None,
         }
     }
 
     /// Set the timeout in milliseconds
     pub fn with_timeout(mut self, timeout_ms: u64) -> Self {
-        self.timeout_ms = timeout_ms;
+        self.timeout_ms = Some(timeout_ms);
         self
     }
 
+    /// Timeout of the job's runs in milliseconds: its own, else
+    /// `default_ms`, else [`DEFAULT_TIMEOUT_MS`]
+    pub fn resolve_timeout_ms(&self, default_ms: Option<u64>) -> u64 {
+        self.timeout_ms.or(default_ms).unwrap_or(DEFAULT_TIMEOUT_MS)
+    }
+
     /// Set the working directory
     pub fn with_working_dir(mut self, dir: impl Into<String>) -> Self {
         self.working_dir = Some(dir.into());
@@ -228,101 +825,2194 @@ impl CronJob {
         self
     }
 
-    /// Check if the job is active
-    pub fn is_active(&self) -> bool {
-        self.status == JobStatus::Active
+    /// Run before lower-priority jobs due at the same time
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
     }
 
-    /// Check if the job is paused
-    pub fn is_paused(&self) -> bool {
-        self.status == JobStatus::Paused
+    /// Skip runs starting less than `min_interval_ms` after the last one
+    pub fn with_min_interval(mut self, min_interval_ms: Option<u64>) -> Self {
+        self.min_interval_ms = min_interval_ms;
+        self
     }
 
-    /// Check if the job is running
-    pub fn is_running(&self) -> bool {
-        self.status == JobStatus::Running
+    /// Report runs still going after `expected_duration_ms`
+    pub fn with_expected_duration(mut self, expected_duration_ms: Option<u64>) -> Self {
+        self.expected_duration_ms = expected_duration_ms;
+        self
     }
-}
 
-/// Execution result status
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
-pub enum ExecutionStatus {
-    /// Execution succeeded
-    Success,
-    /// Execution failed
-    Failed,
-    /// Execution timed out
-    Timeout,
-    /// Execution was cancelled
-    Cancelled,
-}
+    /// Set the job's tags
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
 
-impl std::fmt::Display for ExecutionStatus {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ExecutionStatus::Success => write!(f, "success"),
-            ExecutionStatus::Failed => write!(f, "failed"),
-            ExecutionStatus::Timeout => write!(f, "timeout"),
-            ExecutionStatus::Cancelled => write!(f, "cancelled"),
-        }
+    /// Check if the job has `tag`
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
     }
-}
 
-/// A job execution record
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct JobExecution {
-    /// Execution ID
-    pub id: String,
+    /// Set the limits applied to the job's process
+    pub fn with_resource_limits(mut self, limits: ResourceLimits) -> Self {
+        self.resource_limits = limits;
+        self
+    }
 
-    /// Job ID
-    pub job_id: String,
+    /// Why a run starting at `now` would come too soon after the last one,
+    /// if it would
+    pub(crate) fn rate_limit(&self, now: DateTime<Utc>) -> Option<String> {
+        let min_interval_ms = self.min_interval_ms?;
+        let elapsed_ms = (now - self.last_run?).num_milliseconds().max(0) as u64;
+        (elapsed_ms < min_interval_ms).then(|| {
+            format!(
+                "rate limited: last run {}ms ago, minimum interval {}ms",
+                elapsed_ms, min_interval_ms
+            )
+        })
+    }
 
-    /// Execution status
-    pub status: ExecutionStatus,
+    /// Treat the values of these environment variables as secrets
+    pub fn with_secret_env(mut self, names: Vec<String>) -> Self {
+        self.secret_env = names;
+        self
+    }
 
-    /// Start timestamp
-    pub started_at: DateTime<Utc>,
+    /// Values of the job's secret variables, from its own `env` or else the
+    /// scheduler's environment
+    pub(crate) fn secret_values(&self) -> Vec<String> {
+        self.secret_env
+            .iter()
+            .filter_map(|name| match self.env.iter().find(|(key, _)| key == name) {
+                Some((_, value)) => Some(value.clone()),
+                None => std::env::var(name).ok(),
+            })
+            .filter(|value| !value.is_empty())
+            .collect()
+    }
 
-    /// End timestamp
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub ended_at: Option<DateTime<Utc>>,
+    /// Copy of the job with its secret values masked in the command and env
+    pub fn redacted(&self) -> CronJob {
+        let mut job = self.clone();
+        let secrets = self.secret_values();
+        if secrets.is_empty() {
+            return job;
+        }
+        job.command = mask_secrets(&job.command, &secrets);
+        for (_, value) in &mut job.env {
+            *value = mask_secrets(value, &secrets);
+        }
+        job
+    }
 
-    /// Duration in milliseconds
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub duration_ms: Option<u64>,
+    /// Retry failed or timed-out executions up to `max_retries` times, waiting
+    /// `retry_delay_ms` before the first retry and multiplying the delay by
+    /// `backoff` after each further attempt. Each attempt gets the full timeout.
+    pub fn with_retries(mut self, max_retries: u32, retry_delay_ms: u64, backoff: f64) -> Self {
+        self.max_retries = max_retries;
+        self.retry_delay_ms = retry_delay_ms;
+        self.retry_backoff = backoff;
+        self
+    }
 
-    /// Exit code (if available)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub exit_code: Option<i32>,
+    /// Delay before retrying after the given (1-based) attempt failed
+    pub fn retry_delay(&self, attempt: u32) -> std::time::Duration {
+        let factor = self
+            .retry_backoff
+            .max(0.0)
+            .powi(attempt.saturating_sub(1) as i32);
+        let delay_ms = (self.retry_delay_ms as f64 * factor).min(u64::MAX as f64);
+        std::time::Duration::from_millis(delay_ms as u64)
+    }
 
-    /// Standard output (truncated if too long)
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub stdout: String,
+    /// Only run the job between `not_before` and `not_after` (inclusive)
+    pub fn with_window(
+        mut self,
+        not_before: Option<DateTime<Utc>>,
+        not_after: Option<DateTime<Utc>>,
+    ) -> Self {
+        self.not_before = not_before;
+        self.not_after = not_after;
+        self
+    }
 
-    /// Standard error (truncated if too long)
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub stderr: String,
+    /// Create a job that runs once at `when`
+    pub fn one_shot(
+        name: impl Into<String>,
+        when: DateTime<Utc>,
+        command: impl Into<String>,
+    ) -> Self {
+        let mut job = Self::new(name, format!("@at {}", when.to_rfc3339()), command);
+        job.schedule_kind = ScheduleKind::At(when);
+        job
+    }
 
-    /// Error message (if failed)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub error: Option<String>,
-}
+    /// Check if the job runs only once
+    pub fn is_one_shot(&self) -> bool {
+        matches!(self.schedule_kind, ScheduleKind::At(_))
+    }
 
-impl JobExecution {
-    /// Create a new execution record
+    /// Create a job with no schedule that runs only when triggered by
+    /// another job or run manually
+    pub fn trigger_only(name: impl Into<String>, command: impl Into<String>) -> Self {
+        let mut job = Self::new(name, NEVER_SCHEDULE, command);
+        job.schedule_kind = ScheduleKind::Never;
+        job
+    }
+
+    /// Check if the job has no schedule of its own
+    pub fn is_trigger_only(&self) -> bool {
+        self.schedule_kind == ScheduleKind::Never
+    }
+
+    /// Spawn `program` with `args` directly instead of running `command`
+    /// through a shell. Replaces `command` with a shell-quoted rendering for
+    /// display and crontab export.
+    pub fn with_exec(mut self, program: impl Into<String>, args: Vec<String>) -> Self {
+        self.command_kind = CommandKind::Exec;
+        self.program = program.into();
+        self.command = std::iter::once(self.program.as_str())
+            .chain(args.iter().map(String::as_str))
+            .map(shell_quote)
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.args = args;
+        self
+    }
+
+    /// Check if the job spawns its program without a shell
+    pub fn is_exec(&self) -> bool {
+        self.command_kind == CommandKind::Exec
+    }
+
+    /// Run the given jobs (by id or name) after each successful run
+    pub fn with_triggers(mut self, triggers: Vec<String>) -> Self {
+        self.triggers = triggers;
+        self
+    }
+
+    /// Expand `{{ ... }}` tags in the command and env values when the job runs
+    pub fn with_templated(mut self, templated: bool) -> Self {
+        self.templated = templated;
+        self
+    }
+
+    /// Save executions to the job's history, or only keep its statistics
+    pub fn with_record_history(mut self, record_history: bool) -> Self {
+        self.record_history = record_history;
+        self
+    }
+
+    /// Report whether each successful run's output differs from the
+    /// previous one's, optionally ignoring whitespace-only differences
+    pub fn with_notify_on_change(mut self, enabled: bool, ignore_whitespace: bool) -> Self {
+        self.notify_on_change = enabled;
+        self.ignore_whitespace_changes = ignore_whitespace;
+        self
+    }
+
+    /// Control the environment the command inherits instead of using the
+    /// manager's default
+    pub fn with_env_mode(mut self, env_mode: Option<EnvMode>) -> Self {
+        self.env_mode = env_mode;
+        self
+    }
+
+    /// Run the command with `shell` instead of the manager's default
+    pub fn with_shell(mut self, shell: Option<String>) -> Self {
+        self.shell = shell;
+        self
+    }
+
+    /// Run a job (by name) or inline command after each failed or timed-out
+    /// run
+    pub fn with_on_failure(mut self, hook: Option<String>) -> Self {
+        self.on_failure = hook;
+        self
+    }
+
+    /// Next fire time after `after` that falls within the job's date window
+    pub fn next_run_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.next_run_after_with(after, &[])
+    }
+
+    /// Like [`next_run_after`](Self::next_run_after), also honoring
+    /// `blackouts` on top of the job's own
+    pub fn next_run_after_with(
+        &self,
+        after: DateTime<Utc>,
+        blackouts: &[TimeWindow],
+    ) -> Option<DateTime<Utc>> {
+        self.next_run_after_excluding(after, blackouts, None)
+    }
+
+    /// Like [`next_run_after_with`](Self::next_run_after_with), also passing
+    /// over the dates of `calendar`, the job's excluded calendar
+    pub fn next_run_after_excluding(
+        &self,
+        after: DateTime<Utc>,
+        blackouts: &[TimeWindow],
+        calendar: Option<&Calendar>,
+    ) -> Option<DateTime<Utc>> {
+        let next = match calendar {
+            Some(calendar) => self.fire_time_outside(calendar, after)?,
+            None => self.fire_time_after(after)?,
+        };
+        if self.blackout_policy != BlackoutPolicy::Defer {
+            return Some(next);
+        }
+        let next = self.defer_past_blackouts(next, blackouts);
+        match self.not_after {
+            Some(end) if next > end => None,
+            _ => Some(next),
+        }
+    }
+
+    /// Next fire time after `after` not on a date of `calendar`, or the
+    /// start of the day it is deferred to
+    fn fire_time_outside(
+        &self,
+        calendar: &Calendar,
+        mut after: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        for _ in 0..MAX_WINDOW_SEARCHES {
+            let next = self.fire_time_after(after)?;
+            if !calendar.excludes(next) {
+                return Some(next);
+            }
+            let open = calendar.next_open_day(next);
+            if self.holiday_policy == BlackoutPolicy::Defer {
+                return Some(open).filter(|open| self.not_after.is_none_or(|end| *open <= end));
+            }
+            // Jump past the excluded days instead of walking every fire time
+            after = open - chrono::Duration::seconds(1);
+        }
+        None
+    }
+
+    /// Next time the schedule fires after `after`, ignoring blackouts
+    fn fire_time_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self.schedule_kind {
+            ScheduleKind::At(when) => return Some(when).filter(|when| *when > after),
+            ScheduleKind::Never => return None,
+            ScheduleKind::Cron => {}
+        }
+        let expr = CronExpression::parse(&self.schedule).ok()?;
+        let after = match self.not_before {
+            // Make `not_before` itself eligible
+            Some(start) if start > after => start - chrono::Duration::seconds(1),
+            _ => after,
+        };
+        let next = match &self.allowed_window {
+            Some(window) => Self::fire_time_in_window(&expr, window, after)?,
+            None => expr.next_after(after)?,
+        };
+        match self.not_after {
+            Some(end) if next > end => None,
+            _ => Some(next),
+        }
+    }
+
+    /// Next fire time after `after` that falls inside `window`
+    fn fire_time_in_window(
+        expr: &CronExpression,
+        window: &TimeWindow,
+        mut after: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        for _ in 0..MAX_WINDOW_SEARCHES {
+            let next = expr.next_after(after)?;
+            if window.contains(next) {
+                return Some(next);
+            }
+            // Jump to the window's next opening instead of walking every
+            // fire time in between
+            let opens = window.next_start_after(next)?;
+            after = opens - chrono::Duration::seconds(1);
+        }
+        None
+    }
+
+    /// Only run at fire times inside `window`
+    pub fn with_allowed_window(mut self, window: Option<TimeWindow>) -> Self {
+        self.allowed_window = window;
+        self
+    }
+
+    /// Set the job's blackout windows and how runs inside them are handled
+    pub fn with_blackouts(mut self, blackouts: Vec<TimeWindow>, policy: BlackoutPolicy) -> Self {
+        self.blackouts = blackouts;
+        self.blackout_policy = policy;
+        self
+    }
+
+    /// The blackout window containing `at`, from the job's own windows or
+    /// `blackouts`
+    pub fn blackout_at<'a>(
+        &'a self,
+        at: DateTime<Utc>,
+        blackouts: &'a [TimeWindow],
+    ) -> Option<&'a TimeWindow> {
+        self.blackouts
+            .iter()
+            .chain(blackouts)
+            .find(|window| window.contains(at))
+    }
+
+    /// Move `at` to the end of any blackout windows it falls in
+    pub fn defer_past_blackouts(
+        &self,
+        mut at: DateTime<Utc>,
+        blackouts: &[TimeWindow],
+    ) -> DateTime<Utc> {
+        // Back-to-back windows can chain; each step moves forward at least
+        // a minute, so a bounded number of steps covers any sane setup
+        for _ in 0..MAX_BLACKOUT_DEFERRALS {
+            match self
+                .blackout_at(at, blackouts)
+                .and_then(|w| w.end_after(at))
+            {
+                Some(end) => at = end,
+                None => break,
+            }
+        }
+        at
+    }
+
+    /// Complete the job after `max_runs` runs; manual runs count unless
+    /// `count_manual` is false
+    pub fn with_max_runs(mut self, max_runs: Option<u64>, count_manual: bool) -> Self {
+        self.max_runs = max_runs;
+        self.count_manual = count_manual;
+        self
+    }
+
+    /// Runs that count toward `max_runs`
+    pub fn counted_runs(&self) -> u64 {
+        let total = self.run_count + self.fail_count;
+        if self.count_manual {
+            total
+        } else {
+            total.saturating_sub(self.manual_run_count)
+        }
+    }
+
+    /// Check if the job has used up its `max_runs`
+    pub fn runs_exhausted(&self) -> bool {
+        self.max_runs
+            .is_some_and(|max_runs| self.counted_runs() >= max_runs)
+    }
+
+    /// Recompute `next_run`, completing the job when it has used up its runs,
+    /// its date window has none left or its one-shot time has passed (and
+    /// reactivating it if the window was reopened)
+    pub fn reschedule(&mut self, after: DateTime<Utc>) {
+        self.reschedule_with(after, &[]);
+    }
+
+    /// Recalculate `next_run` honoring `blackouts` on top of the job's own;
+    /// see [`reschedule`](Self::reschedule)
+    pub fn reschedule_with(&mut self, after: DateTime<Utc>, blackouts: &[TimeWindow]) {
+        self.reschedule_excluding(after, blackouts, None);
+    }
+
+    /// Like [`reschedule_with`](Self::reschedule_with), also passing over
+    /// the dates of `calendar`, the job's excluded calendar
+    pub fn reschedule_excluding(
+        &mut self,
+        after: DateTime<Utc>,
+        blackouts: &[TimeWindow],
+        calendar: Option<&Calendar>,
+    ) {
+        if self.runs_exhausted() {
+            self.next_run = None;
+            self.status = JobStatus::Completed;
+            return;
+        }
+        self.next_run = self.next_run_after_excluding(after, blackouts, calendar);
+        match self.next_run {
+            None if self.not_after.is_some() || self.is_one_shot() => {
+                self.status = JobStatus::Completed
+            }
+            Some(_) if self.status == JobStatus::Completed => self.status = JobStatus::Active,
+            _ => {}
+        }
+    }
+
+    /// Pause the job after the given number of consecutive failed runs
+    pub fn with_auto_pause_after(mut self, failures: Option<u32>) -> Self {
+        self.auto_pause_after = failures;
+        self
+    }
+
+    /// Set the misfire policy
+    pub fn with_misfire_policy(mut self, policy: MisfirePolicy) -> Self {
+        self.misfire_policy = policy;
+        self
+    }
+
+    /// Set the concurrency policy
+    pub fn with_concurrency_policy(mut self, policy: ConcurrencyPolicy) -> Self {
+        self.concurrency_policy = policy;
+        self
+    }
+
+    /// Set the grace period a timed-out process gets to exit after SIGTERM
+    pub fn with_kill_grace(mut self, kill_grace_ms: u64) -> Self {
+        self.kill_grace_ms = kill_grace_ms;
+        self
+    }
+
+    /// Delay each scheduled run by a random amount up to `jitter_ms`
+    pub fn with_jitter(mut self, jitter_ms: Option<u64>) -> Self {
+        self.jitter_ms = jitter_ms;
+        self
+    }
+
+    /// Time the scheduler starts the run nominally due at `fire_time`.
+    ///
+    /// The jitter is derived from the job id and fire time rather than drawn
+    /// at random, so it is the same for an occurrence across restarts.
+    pub fn dispatch_time(&self, fire_time: DateTime<Utc>) -> DateTime<Utc> {
+        let Some(jitter_ms) = self.jitter_ms.filter(|ms| *ms > 0) else {
+            return fire_time;
+        };
+        // FNV-1a, stable across builds unlike the std hasher
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        let key = format!("{}@{}", self.id, fire_time.timestamp_millis());
+        for byte in key.bytes() {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+        fire_time + chrono::Duration::milliseconds((hash % (jitter_ms + 1)) as i64)
+    }
+
+    /// Check if the job is active
+    pub fn is_active(&self) -> bool {
+        self.status == JobStatus::Active
+    }
+
+    /// Check if the job is paused
+    pub fn is_paused(&self) -> bool {
+        self.status == JobStatus::Paused
+    }
+
+    /// Check if a scheduled run at `at` is skipped by a snooze
+    pub fn is_snoozed_at(&self, at: DateTime<Utc>) -> bool {
+        self.snoozed_runs > 0 || self.snoozed_until.is_some_and(|until| at < until)
+    }
+
+    /// Use up one snoozed run after a skip, and the snooze time once the
+    /// next run reaches it
+    pub fn consume_snooze(&mut self) {
+        self.snoozed_runs = self.snoozed_runs.saturating_sub(1);
+        if let Some(until) = self.snoozed_until {
+            if self.next_run.is_none_or(|next_run| next_run >= until) {
+                self.snoozed_until = None;
+            }
+        }
+    }
+
+    /// Check if the job is disabled
+    pub fn is_disabled(&self) -> bool {
+        self.status == JobStatus::Disabled
+    }
+
+    /// Check if the job is running
+    pub fn is_running(&self) -> bool {
+        self.status == JobStatus::Running
+    }
+
+    /// Check if the job has completed
+    pub fn is_completed(&self) -> bool {
+        self.status == JobStatus::Completed
+    }
+}
+
+/// Schedule string of jobs that never run on a schedule
+pub const NEVER_SCHEDULE: &str = "@never";
+
+/// Shell running commands of jobs that don't choose one
+pub const DEFAULT_SHELL: &str = "sh";
+
+/// Most window openings searched for a fire time inside an allowed window
+const MAX_WINDOW_SEARCHES: usize = 1000;
+
+/// Most blackout windows a deferred run is moved past
+const MAX_BLACKOUT_DEFERRALS: usize = 64;
+
+/// Optional settings for a new job
+#[derive(Debug, Clone, Default)]
+pub struct JobOptions {
+    /// Earliest time the job may run
+    pub not_before: Option<DateTime<Utc>>,
+    /// Latest time the job may run
+    pub not_after: Option<DateTime<Utc>>,
+    /// Upper bound of a random delay added to each scheduled run
+    pub jitter_ms: Option<u64>,
+    /// Time windows during which the job must not run
+    pub blackouts: Vec<TimeWindow>,
+    /// Handling of fire times that fall in a blackout window
+    pub blackout_policy: BlackoutPolicy,
+    /// Name of the calendar whose dates the job doesn't run on
+    pub exclude_calendar: Option<String>,
+    /// Handling of fire times on the excluded calendar's dates
+    pub holiday_policy: BlackoutPolicy,
+    /// Only fire times inside this window are run
+    pub allowed_window: Option<TimeWindow>,
+    /// Jobs (by id or name) to run after each successful run
+    pub triggers: Vec<String>,
+    /// Job (by name) or inline command to run after a failed run
+    pub on_failure: Option<String>,
+    /// Labels for grouping and filtering jobs
+    pub tags: Vec<String>,
+    /// Shell running the command
+    pub shell: Option<String>,
+    /// Environment inherited by the command
+    pub env_mode: Option<EnvMode>,
+    /// Names of environment variables holding secrets
+    pub secret_env: Vec<String>,
+    /// Order among jobs due at the same time, higher first
+    pub priority: i32,
+    /// Least time between the starts of two runs
+    pub min_interval_ms: Option<u64>,
+    /// How long a run normally takes at most
+    pub expected_duration_ms: Option<u64>,
+    /// Limits applied to the job's process
+    pub resource_limits: ResourceLimits,
+    /// Whether the command and env values are templates
+    pub templated: bool,
+    /// How the command is executed
+    pub job_type: JobType,
+    /// Agent configuration, for agent jobs
+    pub agent_config: Option<AgentJobConfig>,
+    /// Program and arguments spawned directly instead of the command
+    pub exec: Option<(String, Vec<String>)>,
+    /// Environment variables for the command
+    pub env: Vec<(String, String)>,
+    /// Working directory (defaults to the manager's workspace)
+    pub working_dir: Option<String>,
+    /// Timeout in milliseconds
+    pub timeout_ms: Option<u64>,
+    /// Grace period between SIGTERM and SIGKILL on timeout, in milliseconds
+    pub kill_grace_ms: Option<u64>,
+    /// Retries of failed runs: (max retries, first delay in ms, backoff)
+    pub retries: Option<(u32, u64, f64)>,
+    /// Number of runs after which the job completes
+    pub max_runs: Option<u64>,
+    /// Whether manual runs count toward `max_runs`
+    pub count_manual: Option<bool>,
+    /// Pause after this many consecutive failures
+    pub auto_pause_after: Option<u32>,
+    /// Handling of runs missed while the scheduler was stopped
+    pub misfire_policy: MisfirePolicy,
+    /// Handling of runs due while a previous one is in flight
+    pub concurrency_policy: ConcurrencyPolicy,
+    /// Run the job once as soon as it is added, then follow the schedule
+    pub run_immediately: bool,
+    /// Whether executions are saved to the job's history (default: true)
+    pub record_history: Option<bool>,
+    /// Report whether successful runs' output changed
+    pub notify_on_change: bool,
+    /// Ignore whitespace-only differences when comparing output
+    pub ignore_whitespace_changes: bool,
+}
+
+impl JobOptions {
+    /// Create default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Make an agent job sending the command as a prompt with `config`
+    pub fn with_agent_config(mut self, config: AgentJobConfig) -> Self {
+        self.job_type = JobType::Agent;
+        self.agent_config = Some(config);
+        self
+    }
+
+    /// Set how the command is executed
+    pub fn with_job_type(mut self, job_type: JobType) -> Self {
+        self.job_type = job_type;
+        self
+    }
+
+    /// Spawn `program` with `args` directly instead of running the command
+    /// through a shell
+    pub fn with_exec(mut self, program: impl Into<String>, args: Vec<String>) -> Self {
+        self.exec = Some((program.into(), args));
+        self
+    }
+
+    /// Add an environment variable for the command
+    pub fn with_env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+
+    /// Run the command in `dir`
+    pub fn with_working_dir(mut self, dir: impl Into<String>) -> Self {
+        self.working_dir = Some(dir.into());
+        self
+    }
+
+    /// Stop runs lasting longer than `timeout_ms`
+    pub fn with_timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.timeout_ms = Some(timeout_ms);
+        self
+    }
+
+    /// Give timed-out processes `kill_grace_ms` to exit after SIGTERM
+    pub fn with_kill_grace_ms(mut self, kill_grace_ms: u64) -> Self {
+        self.kill_grace_ms = Some(kill_grace_ms);
+        self
+    }
+
+    /// Retry failed runs, as [`CronJob::with_retries`]
+    pub fn with_retries(mut self, max_retries: u32, retry_delay_ms: u64, backoff: f64) -> Self {
+        self.retries = Some((max_retries, retry_delay_ms, backoff));
+        self
+    }
+
+    /// Complete the job after `max_runs` runs, counting manual runs if
+    /// `count_manual`
+    pub fn with_max_runs(mut self, max_runs: u64, count_manual: bool) -> Self {
+        self.max_runs = Some(max_runs);
+        self.count_manual = Some(count_manual);
+        self
+    }
+
+    /// Pause the job after `failures` consecutive failures
+    pub fn with_auto_pause_after(mut self, failures: u32) -> Self {
+        self.auto_pause_after = Some(failures);
+        self
+    }
+
+    /// Set how runs missed while the scheduler was stopped are handled
+    pub fn with_misfire_policy(mut self, policy: MisfirePolicy) -> Self {
+        self.misfire_policy = policy;
+        self
+    }
+
+    /// Set how runs due while a previous one is in flight are handled
+    pub fn with_concurrency_policy(mut self, policy: ConcurrencyPolicy) -> Self {
+        self.concurrency_policy = policy;
+        self
+    }
+
+    /// Set the earliest time the job may run
+    pub fn with_not_before(mut self, not_before: DateTime<Utc>) -> Self {
+        self.not_before = Some(not_before);
+        self
+    }
+
+    /// Set the latest time the job may run
+    pub fn with_not_after(mut self, not_after: DateTime<Utc>) -> Self {
+        self.not_after = Some(not_after);
+        self
+    }
+
+    /// Delay each scheduled run by a random amount up to `jitter_ms`
+    pub fn with_jitter_ms(mut self, jitter_ms: u64) -> Self {
+        self.jitter_ms = Some(jitter_ms);
+        self
+    }
+
+    /// Add a time window during which the job must not run
+    pub fn with_blackout(mut self, window: TimeWindow) -> Self {
+        self.blackouts.push(window);
+        self
+    }
+
+    /// Set how runs falling in a blackout window are handled
+    pub fn with_blackout_policy(mut self, policy: BlackoutPolicy) -> Self {
+        self.blackout_policy = policy;
+        self
+    }
+
+    /// Don't run on the dates of the calendar registered as `name`
+    pub fn with_exclude_calendar(mut self, name: impl Into<String>) -> Self {
+        self.exclude_calendar = Some(name.into());
+        self
+    }
+
+    /// Set how fire times on the excluded calendar's dates are handled
+    pub fn with_holiday_policy(mut self, policy: BlackoutPolicy) -> Self {
+        self.holiday_policy = policy;
+        self
+    }
+
+    /// Only run at fire times inside `window`
+    pub fn with_allowed_window(mut self, window: TimeWindow) -> Self {
+        self.allowed_window = Some(window);
+        self
+    }
+
+    /// Run the given job (by id or name) after each successful run
+    pub fn with_trigger(mut self, job: impl Into<String>) -> Self {
+        self.triggers.push(job.into());
+        self
+    }
+
+    /// Run a job (by name) or inline command after each failed run
+    pub fn with_on_failure(mut self, hook: impl Into<String>) -> Self {
+        self.on_failure = Some(hook.into());
+        self
+    }
+
+    /// Run the command with `shell`, e.g. `bash`
+    pub fn with_shell(mut self, shell: impl Into<String>) -> Self {
+        self.shell = Some(shell.into());
+        self
+    }
+
+    /// Control the environment the command inherits
+    pub fn with_env_mode(mut self, env_mode: EnvMode) -> Self {
+        self.env_mode = Some(env_mode);
+        self
+    }
+
+    /// Run before lower-priority jobs due at the same time
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Skip runs starting less than `min_interval_ms` after the last one
+    pub fn with_min_interval_ms(mut self, min_interval_ms: u64) -> Self {
+        self.min_interval_ms = Some(min_interval_ms);
+        self
+    }
+
+    /// Report runs still going after `expected_duration_ms`
+    pub fn with_expected_duration_ms(mut self, expected_duration_ms: u64) -> Self {
+        self.expected_duration_ms = Some(expected_duration_ms);
+        self
+    }
+
+    /// Add a tag, e.g. `team:payments`
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Set the limits applied to the job's process
+    pub fn with_resource_limits(mut self, limits: ResourceLimits) -> Self {
+        self.resource_limits = limits;
+        self
+    }
+
+    /// Treat the value of the environment variable `name` as a secret
+    pub fn with_secret_env(mut self, name: impl Into<String>) -> Self {
+        self.secret_env.push(name.into());
+        self
+    }
+
+    /// Expand `{{ ... }}` tags in the command and env values when the job runs
+    pub fn with_templated(mut self) -> Self {
+        self.templated = true;
+        self
+    }
+
+    /// Run the job once as soon as it is added, then follow the schedule
+    pub fn with_run_immediately(mut self) -> Self {
+        self.run_immediately = true;
+        self
+    }
+
+    /// Save executions to the job's history, or only keep its statistics
+    pub fn with_record_history(mut self, record_history: bool) -> Self {
+        self.record_history = Some(record_history);
+        self
+    }
+
+    /// Enable reporting whether each successful run's output differs from
+    /// the previous one's, ignoring whitespace-only differences if
+    /// `ignore_whitespace_changes` is set
+    pub fn with_notify_on_change(mut self, ignore_whitespace_changes: bool) -> Self {
+        self.notify_on_change = true;
+        self.ignore_whitespace_changes = ignore_whitespace_changes;
+        self
+    }
+}
+
+/// Longest tag a job may have, in bytes
+pub const MAX_TAG_LEN: usize = 64;
+
+/// Criteria selecting jobs from a listing; the default selects every job
+#[derive(Debug, Clone, Default)]
+pub struct JobFilter {
+    /// Only jobs with this status
+    pub status: Option<JobStatus>,
+    /// Leave out disabled jobs
+    pub exclude_disabled: bool,
+    /// Only jobs with at least one of these tags
+    pub tags_any: Vec<String>,
+    /// Only jobs with all of these tags
+    pub tags_all: Vec<String>,
+    /// Only jobs whose name contains this text
+    pub name_contains: Option<String>,
+    /// Only jobs of this type
+    pub job_type: Option<JobType>,
+}
+
+impl JobFilter {
+    /// Create a filter selecting every job
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only select jobs with `status`
+    pub fn with_status(mut self, status: JobStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Leave out disabled jobs
+    pub fn excluding_disabled(mut self) -> Self {
+        self.exclude_disabled = true;
+        self
+    }
+
+    /// Only select jobs with at least one of `tags`
+    pub fn with_tags_any(mut self, tags: Vec<String>) -> Self {
+        self.tags_any = tags;
+        self
+    }
+
+    /// Only select jobs with all of `tags`
+    pub fn with_tags_all(mut self, tags: Vec<String>) -> Self {
+        self.tags_all = tags;
+        self
+    }
+
+    /// Only select jobs whose name contains `text`
+    pub fn with_name_contains(mut self, text: impl Into<String>) -> Self {
+        self.name_contains = Some(text.into());
+        self
+    }
+
+    /// Only select jobs of type `job_type`
+    pub fn with_job_type(mut self, job_type: JobType) -> Self {
+        self.job_type = Some(job_type);
+        self
+    }
+
+    /// Check if the filter narrows the selection down by anything but
+    /// leaving out disabled jobs
+    pub fn is_empty(&self) -> bool {
+        self.status.is_none()
+            && self.tags_any.is_empty()
+            && self.tags_all.is_empty()
+            && self.name_contains.is_none()
+            && self.job_type.is_none()
+    }
+
+    /// Whether `job` meets every criterion
+    pub fn matches(&self, job: &CronJob) -> bool {
+        self.status.is_none_or(|status| job.status == status)
+            && !(self.exclude_disabled && job.is_disabled())
+            && (self.tags_any.is_empty() || self.tags_any.iter().any(|tag| job.has_tag(tag)))
+            && self.tags_all.iter().all(|tag| job.has_tag(tag))
+            && self
+                .name_contains
+                .as_deref()
+                .is_none_or(|text| job.name.contains(text))
+            && self
+                .job_type
+                .is_none_or(|job_type| job.job_type == job_type)
+    }
+}
+
+/// Version of the document written by [`CronManager::export_jobs`](crate::CronManager::export_jobs)
+pub const EXPORT_VERSION: u32 = 1;
+
+/// Job definitions exported for backup or moving to another environment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobsExport {
+    /// Document format version, [`EXPORT_VERSION`] when written
+    pub version: u32,
+    /// When the jobs were exported
+    pub exported_at: DateTime<Utc>,
+    /// The jobs, with secrets masked
+    pub jobs: Vec<CronJob>,
+}
+
+/// How an import treats jobs whose name is already taken
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    /// Keep the existing job and leave the imported one out
+    #[default]
+    Skip,
+    /// Replace the existing job, keeping its ID and history
+    Overwrite,
+    /// Fail the whole import
+    Fail,
+}
+
+/// Names of the jobs an import created, overwrote and skipped
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportReport {
+    /// Jobs added
+    pub created: Vec<String>,
+    /// Existing jobs replaced
+    pub overwritten: Vec<String>,
+    /// Jobs left out because the name was taken
+    pub skipped: Vec<String>,
+}
+
+/// Jobs declared in a manifest applied by
+/// [`CronManager::apply_manifest`](crate::CronManager::apply_manifest)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct JobManifest {
+    /// The declared jobs, keyed by name
+    #[serde(default)]
+    pub jobs: Vec<ManifestJob>,
+}
+
+/// A job declared in a [`JobManifest`]. Fields left out take the same
+/// defaults as [`CronManager::add_job`](crate::CronManager::add_job).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestJob {
+    /// Job name, which identifies the job across applies
+    pub name: String,
+    /// Cron expression, natural language or `@never`
+    pub schedule: String,
+    /// Shell command, agent prompt or native handler name
+    pub command: String,
+    /// How the command is executed
+    #[serde(default)]
+    pub job_type: JobType,
+    /// Agent configuration, for agent jobs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_config: Option<AgentJobConfig>,
+    /// Environment variables for the command
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Working directory (defaults to the manager's workspace)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<String>,
+    /// Timeout in milliseconds (defaults to the manager's default timeout)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    /// Labels for grouping and filtering jobs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Handling of runs due while a previous one is in flight
+    #[serde(default)]
+    pub concurrency_policy: ConcurrencyPolicy,
+    /// Grace period between SIGTERM and SIGKILL on timeout, in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kill_grace_ms: Option<u64>,
+    /// Maximum number of retries after a failed run
+    #[serde(default)]
+    pub max_retries: u32,
+    /// Delay before the first retry, in milliseconds
+    #[serde(default = "default_retry_delay_ms")]
+    pub retry_delay_ms: u64,
+    /// Factor the retry delay is multiplied by after each retry
+    #[serde(default = "default_retry_backoff")]
+    pub retry_backoff: f64,
+    /// Number of runs after which the job completes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_runs: Option<u64>,
+    /// Whether manual runs count toward `max_runs`
+    #[serde(default = "default_count_manual")]
+    pub count_manual: bool,
+    /// Pause after this many consecutive failures
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_pause_after: Option<u32>,
+    /// Handling of runs missed while the scheduler was stopped
+    #[serde(default)]
+    pub misfire_policy: MisfirePolicy,
+    /// Don't run before this time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_before: Option<DateTime<Utc>>,
+    /// Don't run after this time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_after: Option<DateTime<Utc>>,
+    /// Most a run may be delayed past its fire time, in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jitter_ms: Option<u64>,
+    /// Daily windows during which the job must not run
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blackouts: Vec<TimeWindow>,
+    /// Handling of fire times inside a blackout window
+    #[serde(default)]
+    pub blackout_policy: BlackoutPolicy,
+    /// Registered calendar whose dates the job doesn't run on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exclude_calendar: Option<String>,
+    /// Handling of fire times on excluded calendar dates
+    #[serde(default)]
+    pub holiday_policy: BlackoutPolicy,
+    /// Daily window the job may only run in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_window: Option<TimeWindow>,
+    /// Jobs (by id or name) triggered after each successful run
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub triggers: Vec<String>,
+    /// Job (by name) or command run after each failed run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_failure: Option<String>,
+    /// Shell running the command (defaults to `sh`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shell: Option<String>,
+    /// Environment the command inherits (defaults to the manager's)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env_mode: Option<EnvMode>,
+    /// Names of environment variables masked as secrets
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub secret_env: Vec<String>,
+    /// Whether `{{ ... }}` tags in the command and env values are expanded
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub templated: bool,
+    /// Priority among jobs due at the same time, higher first
+    #[serde(default)]
+    pub priority: i32,
+    /// Least time between the starts of two runs, in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_interval_ms: Option<u64>,
+    /// How long runs normally take at most, in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_duration_ms: Option<u64>,
+    /// Operating system limits applied to the job's process
+    #[serde(default, skip_serializing_if = "ResourceLimits::is_empty")]
+    pub resource_limits: ResourceLimits,
+    /// Whether executions are saved to the job's history
+    #[serde(default = "default_record_history")]
+    pub record_history: bool,
+    /// Whether to report output changes between successful runs
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub notify_on_change: bool,
+    /// Whether output changes ignore whitespace-only differences
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub ignore_whitespace_changes: bool,
+}
+
+impl ManifestJob {
+    /// Declare a shell job
+    pub fn new(
+        name: impl Into<String>,
+        schedule: impl Into<String>,
+        command: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            schedule: schedule.into(),
+            command: command.into(),
+            job_type: JobType::Shell,
+            agent_config: None,
+            env: BTreeMap::new(),
+            working_dir: None,
+            timeout_ms: None,
+            tags: Vec::new(),
+            concurrency_policy: ConcurrencyPolicy::default(),
+            kill_grace_ms: None,
+            max_retries: 0,
+            retry_delay_ms: default_retry_delay_ms(),
+            retry_backoff: default_retry_backoff(),
+            max_runs: None,
+            count_manual: default_count_manual(),
+            auto_pause_after: None,
+            misfire_policy: MisfirePolicy::default(),
+            not_before: None,
+            not_after: None,
+            jitter_ms: None,
+            blackouts: Vec::new(),
+            blackout_policy: BlackoutPolicy::default(),
+            exclude_calendar: None,
+            holiday_policy: BlackoutPolicy::default(),
+            allowed_window: None,
+            triggers: Vec::new(),
+            on_failure: None,
+            shell: None,
+            env_mode: None,
+            secret_env: Vec::new(),
+            templated: false,
+            priority: 0,
+            min_interval_ms: None,
+            expected_duration_ms: None,
+            resource_limits: ResourceLimits::default(),
+            record_history: default_record_history(),
+            notify_on_change: false,
+            ignore_whitespace_changes: false,
+        }
+    }
+
+    /// Make an agent job sending the command as a prompt with `config`
+    pub fn with_agent_config(mut self, config: AgentJobConfig) -> Self {
+        self.job_type = JobType::Agent;
+        self.agent_config = Some(config);
+        self
+    }
+
+    /// Set an environment variable
+    pub fn with_env(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.insert(name.into(), value.into());
+        self
+    }
+
+    /// Set the working directory
+    pub fn with_working_dir(mut self, dir: impl Into<String>) -> Self {
+        self.working_dir = Some(dir.into());
+        self
+    }
+
+    /// Set the timeout
+    pub fn with_timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.timeout_ms = Some(timeout_ms);
+        self
+    }
+
+    /// Add a tag
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Set the concurrency policy
+    pub fn with_concurrency_policy(mut self, policy: ConcurrencyPolicy) -> Self {
+        self.concurrency_policy = policy;
+        self
+    }
+
+    /// Retry failed runs up to `max_retries` times, waiting `retry_delay_ms`
+    /// before the first retry and multiplying the delay by `backoff` after
+    /// each
+    pub fn with_retries(mut self, max_retries: u32, retry_delay_ms: u64, backoff: f64) -> Self {
+        self.max_retries = max_retries;
+        self.retry_delay_ms = retry_delay_ms;
+        self.retry_backoff = backoff;
+        self
+    }
+
+    /// Complete the job after `max_runs` runs, counting manual runs if
+    /// `count_manual`
+    pub fn with_max_runs(mut self, max_runs: u64, count_manual: bool) -> Self {
+        self.max_runs = Some(max_runs);
+        self.count_manual = count_manual;
+        self
+    }
+
+    /// Add a daily window during which the job must not run
+    pub fn with_blackout(mut self, window: TimeWindow) -> Self {
+        self.blackouts.push(window);
+        self
+    }
+
+    /// Set how fire times inside a blackout window are handled
+    pub fn with_blackout_policy(mut self, policy: BlackoutPolicy) -> Self {
+        self.blackout_policy = policy;
+        self
+    }
+
+    /// Only run inside a daily window
+    pub fn with_allowed_window(mut self, window: TimeWindow) -> Self {
+        self.allowed_window = Some(window);
+        self
+    }
+
+    /// Trigger a job (by id or name) after each successful run
+    pub fn with_trigger(mut self, job: impl Into<String>) -> Self {
+        self.triggers.push(job.into());
+        self
+    }
+
+    /// Run a job (by name) or command after each failed run
+    pub fn with_on_failure(mut self, hook: impl Into<String>) -> Self {
+        self.on_failure = Some(hook.into());
+        self
+    }
+
+    /// Set the priority among jobs due at the same time
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Options creating the declared job
+    pub(crate) fn options(&self) -> JobOptions {
+        let mut options = JobOptions::new()
+            .with_concurrency_policy(self.concurrency_policy)
+            .with_retries(self.max_retries, self.retry_delay_ms, self.retry_backoff)
+            .with_misfire_policy(self.misfire_policy)
+            .with_resource_limits(self.resource_limits)
+            .with_record_history(self.record_history);
+        options.timeout_ms = self.timeout_ms;
+        options.kill_grace_ms = self.kill_grace_ms;
+        options.env = self.env.clone().into_iter().collect();
+        options.job_type = self.job_type;
+        options.agent_config = self.agent_config.clone();
+        options.working_dir = self.working_dir.clone();
+        options.tags = self.tags.clone();
+        options.max_runs = self.max_runs;
+        options.count_manual = Some(self.count_manual);
+        options.auto_pause_after = self.auto_pause_after;
+        options.not_before = self.not_before;
+        options.not_after = self.not_after;
+        options.jitter_ms = self.jitter_ms;
+        options.blackouts = self.blackouts.clone();
+        options.blackout_policy = self.blackout_policy;
+        options.exclude_calendar = self.exclude_calendar.clone();
+        options.holiday_policy = self.holiday_policy;
+        options.allowed_window = self.allowed_window.clone();
+        options.triggers = self.triggers.clone();
+        options.on_failure = self.on_failure.clone();
+        options.shell = self.shell.clone();
+        options.env_mode = self.env_mode.clone();
+        options.secret_env = self.secret_env.clone();
+        options.templated = self.templated;
+        options.priority = self.priority;
+        options.min_interval_ms = self.min_interval_ms;
+        options.expected_duration_ms = self.expected_duration_ms;
+        options.notify_on_change = self.notify_on_change;
+        options.ignore_whitespace_changes = self.ignore_whitespace_changes;
+        options
+    }
+}
+
+/// Changes made, or planned on a dry run, by applying a [`JobManifest`],
+/// as job names
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestPlan {
+    /// Declared jobs that did not exist
+    pub created: Vec<String>,
+    /// Existing jobs whose definition differed
+    pub updated: Vec<String>,
+    /// Jobs missing from the manifest, removed when pruning
+    pub removed: Vec<String>,
+    /// Existing jobs matching their declaration
+    pub unchanged: Vec<String>,
+}
+
+impl ManifestPlan {
+    /// Check if applying the manifest changes nothing
+    pub fn is_noop(&self) -> bool {
+        self.created.is_empty() && self.updated.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Outcome of an operation applied to many jobs, job by job
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BulkResult {
+    /// IDs of the jobs the operation succeeded for
+    pub succeeded: Vec<String>,
+    /// IDs of the jobs the operation failed for, with the error
+    pub failed: Vec<(String, String)>,
+}
+
+impl BulkResult {
+    /// Record the outcome for one job
+    pub fn record<T>(&mut self, job_id: &str, result: &Result<T>) {
+        match result {
+            Ok(_) => self.succeeded.push(job_id.to_string()),
+            Err(e) => self.failed.push((job_id.to_string(), e.to_string())),
+        }
+    }
+
+    /// Check if the operation succeeded for every job
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// Changes to make to a job; fields left `None` are kept
+#[derive(Debug, Clone, Default)]
+pub struct UpdateJobRequest {
+    /// New name, which must not be taken by another job
+    pub name: Option<String>,
+    /// New schedule (cron expression, natural language or `@never`)
+    pub schedule: Option<String>,
+    /// New command (shell command, agent prompt or native handler name)
+    pub command: Option<String>,
+    /// New timeout in milliseconds; `Some(None)` falls back to the manager's
+    /// default timeout
+    pub timeout_ms: Option<Option<u64>>,
+    /// Environment variables replacing the job's own
+    pub env: Option<Vec<(String, String)>>,
+    /// New working directory; `Some(None)` falls back to the workspace
+    pub working_dir: Option<Option<String>>,
+    /// New agent configuration, for agent jobs only
+    pub agent_config: Option<AgentJobConfig>,
+    /// New concurrency policy
+    pub concurrency_policy: Option<ConcurrencyPolicy>,
+    /// Tags replacing the job's own
+    pub tags: Option<Vec<String>>,
+    /// New start of the date window the job may run in; `Some(None)` clears
+    /// it
+    pub not_before: Option<Option<DateTime<Utc>>>,
+    /// New end of the date window the job may run in; `Some(None)` clears it
+    pub not_after: Option<Option<DateTime<Utc>>>,
+    /// Blackout windows replacing the job's own
+    pub blackouts: Option<Vec<TimeWindow>>,
+    /// New handling of fire times inside a blackout window
+    pub blackout_policy: Option<BlackoutPolicy>,
+    /// New calendar whose dates the job doesn't run on; `Some(None)` clears
+    /// it
+    pub exclude_calendar: Option<Option<String>>,
+    /// New handling of fire times on excluded calendar dates
+    pub holiday_policy: Option<BlackoutPolicy>,
+    /// Jobs (by id or name) replacing those triggered after each successful
+    /// run
+    pub triggers: Option<Vec<String>>,
+    /// New job or command run after each failed run; `Some(None)` clears it
+    pub on_failure: Option<Option<String>>,
+    /// New shell running the command; `Some(None)` uses the default
+    pub shell: Option<Option<String>>,
+    /// New environment the command inherits; `Some(None)` uses the manager's
+    /// default
+    pub env_mode: Option<Option<EnvMode>>,
+    /// Names replacing those of the environment variables masked as secrets
+    pub secret_env: Option<Vec<String>>,
+    /// Whether to expand `{{ ... }}` tags in the command and env values
+    pub templated: Option<bool>,
+    /// New priority among jobs due at the same time
+    pub priority: Option<i32>,
+    /// New least time between the starts of two runs; `Some(None)` clears it
+    pub min_interval_ms: Option<Option<u64>>,
+    /// New duration after which a run is reported as running long;
+    /// `Some(None)` clears it
+    pub expected_duration_ms: Option<Option<u64>>,
+    /// New operating system limits applied to the job's process
+    pub resource_limits: Option<ResourceLimits>,
+    /// Whether to save executions to the job's history
+    pub record_history: Option<bool>,
+    /// Whether to report output changes between successful runs
+    pub notify_on_change: Option<bool>,
+    /// Whether output changes ignore whitespace-only differences
+    pub ignore_whitespace_changes: Option<bool>,
+    /// New daily window the job may only run in; `Some(None)` clears it
+    pub allowed_window: Option<Option<TimeWindow>>,
+    /// New most a run may be delayed past its fire time; `Some(None)` clears
+    /// it
+    pub jitter_ms: Option<Option<u64>>,
+    /// New retries of failed runs: (max retries, first delay in ms, backoff)
+    pub retries: Option<(u32, u64, f64)>,
+    /// New number of runs after which the job completes; `Some(None)` clears
+    /// it
+    pub max_runs: Option<Option<u64>>,
+    /// Whether manual runs count toward `max_runs`
+    pub count_manual: Option<bool>,
+    /// New number of consecutive failures pausing the job; `Some(None)`
+    /// clears it
+    pub auto_pause_after: Option<Option<u32>>,
+    /// New handling of runs missed while the scheduler was stopped
+    pub misfire_policy: Option<MisfirePolicy>,
+    /// New grace period between SIGTERM and SIGKILL on timeout
+    pub kill_grace_ms: Option<u64>,
+}
+
+impl UpdateJobRequest {
+    /// Create a request changing nothing
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rename the job
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Change the schedule
+    pub fn with_schedule(mut self, schedule: impl Into<String>) -> Self {
+        self.schedule = Some(schedule.into());
+        self
+    }
+
+    /// Change the command
+    pub fn with_command(mut self, command: impl Into<String>) -> Self {
+        self.command = Some(command.into());
+        self
+    }
+
+    /// Change the timeout
+    pub fn with_timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.timeout_ms = Some(Some(timeout_ms));
+        self
+    }
+
+    /// Reset the timeout to the manager's default
+    pub fn with_default_timeout(mut self) -> Self {
+        self.timeout_ms = Some(None);
+        self
+    }
+
+    /// Replace the job's environment variables
+    pub fn with_env(mut self, env: Vec<(String, String)>) -> Self {
+        self.env = Some(env);
+        self
+    }
+
+    /// Change the working directory, or reset it to the workspace with `None`
+    pub fn with_working_dir(mut self, dir: Option<String>) -> Self {
+        self.working_dir = Some(dir);
+        self
+    }
+
+    /// Change the agent configuration of an agent job
+    pub fn with_agent_config(mut self, config: AgentJobConfig) -> Self {
+        self.agent_config = Some(config);
+        self
+    }
+
+    /// Change the concurrency policy
+    pub fn with_concurrency_policy(mut self, policy: ConcurrencyPolicy) -> Self {
+        self.concurrency_policy = Some(policy);
+        self
+    }
+
+    /// Replace the job's tags
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = Some(tags);
+        self
+    }
+
+    /// Set or clear the date window the job may run in
+    pub fn with_window(
+        mut self,
+        not_before: Option<DateTime<Utc>>,
+        not_after: Option<DateTime<Utc>>,
+    ) -> Self {
+        self.not_before = Some(not_before);
+        self.not_after = Some(not_after);
+        self
+    }
+
+    /// Replace the job's blackout windows and their policy
+    pub fn with_blackouts(mut self, blackouts: Vec<TimeWindow>, policy: BlackoutPolicy) -> Self {
+        self.blackouts = Some(blackouts);
+        self.blackout_policy = Some(policy);
+        self
+    }
+
+    /// Set or clear the calendar whose dates the job doesn't run on, and
+    /// how fire times on them are handled
+    pub fn with_calendar(mut self, calendar: Option<String>, policy: BlackoutPolicy) -> Self {
+        self.exclude_calendar = Some(calendar);
+        self.holiday_policy = Some(policy);
+        self
+    }
+
+    /// Replace the jobs triggered after each successful run
+    pub fn with_triggers(mut self, triggers: Vec<String>) -> Self {
+        self.triggers = Some(triggers);
+        self
+    }
+
+    /// Set or clear the job or command run after each failed run
+    pub fn with_on_failure(mut self, hook: Option<String>) -> Self {
+        self.on_failure = Some(hook);
+        self
+    }
+
+    /// Set or clear the shell running the command
+    pub fn with_shell(mut self, shell: Option<String>) -> Self {
+        self.shell = Some(shell);
+        self
+    }
+
+    /// Set or clear the environment the command inherits
+    pub fn with_env_mode(mut self, env_mode: Option<EnvMode>) -> Self {
+        self.env_mode = Some(env_mode);
+        self
+    }
+
+    /// Replace the names of the environment variables masked as secrets
+    pub fn with_secret_env(mut self, names: Vec<String>) -> Self {
+        self.secret_env = Some(names);
+        self
+    }
+
+    /// Turn expansion of `{{ ... }}` tags on or off
+    pub fn with_templated(mut self, templated: bool) -> Self {
+        self.templated = Some(templated);
+        self
+    }
+
+    /// Change the priority among jobs due at the same time
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Set or clear the least time between the starts of two runs
+    pub fn with_min_interval(mut self, min_interval_ms: Option<u64>) -> Self {
+        self.min_interval_ms = Some(min_interval_ms);
+        self
+    }
+
+    /// Set or clear how long runs normally take at most
+    pub fn with_expected_duration(mut self, expected_duration_ms: Option<u64>) -> Self {
+        self.expected_duration_ms = Some(expected_duration_ms);
+        self
+    }
+
+    /// Replace the operating system limits applied to the job's process
+    pub fn with_resource_limits(mut self, limits: ResourceLimits) -> Self {
+        self.resource_limits = Some(limits);
+        self
+    }
+
+    /// Turn saving of executions to the job's history on or off
+    pub fn with_record_history(mut self, record_history: bool) -> Self {
+        self.record_history = Some(record_history);
+        self
+    }
+
+    /// Turn reporting of output changes on or off, optionally ignoring
+    /// whitespace-only differences
+    pub fn with_notify_on_change(mut self, enabled: bool, ignore_whitespace: bool) -> Self {
+        self.notify_on_change = Some(enabled);
+        self.ignore_whitespace_changes = Some(ignore_whitespace);
+        self
+    }
+
+    /// Set or clear the daily window the job may only run in
+    pub fn with_allowed_window(mut self, window: Option<TimeWindow>) -> Self {
+        self.allowed_window = Some(window);
+        self
+    }
+
+    /// Set or clear the most a run may be delayed past its fire time
+    pub fn with_jitter(mut self, jitter_ms: Option<u64>) -> Self {
+        self.jitter_ms = Some(jitter_ms);
+        self
+    }
+
+    /// Change the retries of failed runs
+    pub fn with_retries(mut self, max_retries: u32, retry_delay_ms: u64, backoff: f64) -> Self {
+        self.retries = Some((max_retries, retry_delay_ms, backoff));
+        self
+    }
+
+    /// Set or clear the number of runs after which the job completes, and
+    /// whether manual runs count toward it
+    pub fn with_max_runs(mut self, max_runs: Option<u64>, count_manual: bool) -> Self {
+        self.max_runs = Some(max_runs);
+        self.count_manual = Some(count_manual);
+        self
+    }
+
+    /// Set or clear the number of consecutive failures pausing the job
+    pub fn with_auto_pause_after(mut self, failures: Option<u32>) -> Self {
+        self.auto_pause_after = Some(failures);
+        self
+    }
+
+    /// Change how runs missed while the scheduler was stopped are handled
+    pub fn with_misfire_policy(mut self, policy: MisfirePolicy) -> Self {
+        self.misfire_policy = Some(policy);
+        self
+    }
+
+    /// Change the grace period between SIGTERM and SIGKILL on timeout
+    pub fn with_kill_grace_ms(mut self, kill_grace_ms: u64) -> Self {
+        self.kill_grace_ms = Some(kill_grace_ms);
+        self
+    }
+}
+
+/// Execution result status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExecutionStatus {
+    /// Execution succeeded
+    Success,
+    /// Execution failed
+    Failed,
+    /// Execution timed out
+    Timeout,
+    /// Execution was cancelled
+    Cancelled,
+    /// Run was skipped without executing
+    Skipped,
+}
+
+impl std::fmt::Display for ExecutionStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExecutionStatus::Success => write!(f, "success"),
+            ExecutionStatus::Failed => write!(f, "failed"),
+            ExecutionStatus::Timeout => write!(f, "timeout"),
+            ExecutionStatus::Cancelled => write!(f, "cancelled"),
+            ExecutionStatus::Skipped => write!(f, "skipped"),
+        }
+    }
+}
+
+/// An execution that is currently in flight
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunningExecution {
+    /// Execution ID
+    pub execution_id: String,
+
+    /// Job ID
+    pub job_id: String,
+
+    /// Job name
+    pub job_name: String,
+
+    /// When the current attempt started
+    pub started_at: DateTime<Utc>,
+
+    /// Time spent running so far, in milliseconds
+    pub elapsed_ms: u64,
+}
+
+/// A run the scheduler would start, found by
+/// [`CronManager::simulate`](crate::CronManager::simulate)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SimulatedRun {
+    /// Job ID
+    pub job_id: String,
+
+    /// Job name
+    pub job_name: String,
+
+    /// Nominal fire time, before any jitter
+    pub fire_time: DateTime<Utc>,
+}
+
+/// An occurrence waiting for the execution of its job in flight to finish
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedExecution {
+    /// ID the execution will have when it runs
+    pub execution_id: String,
+
+    /// Job ID
+    pub job_id: String,
+
+    /// Job name
+    pub job_name: String,
+
+    /// Nominal fire time of the occurrence
+    pub scheduled_for: DateTime<Utc>,
+
+    /// When the occurrence was queued
+    pub queued_at: DateTime<Utc>,
+}
+
+/// How a timed-out process was stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Termination {
+    /// The process exited within the grace period after SIGTERM
+    Graceful,
+    /// The process was killed with SIGKILL
+    Forced,
+}
+
+impl std::fmt::Display for Termination {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Termination::Graceful => write!(f, "graceful"),
+            Termination::Forced => write!(f, "forced"),
+        }
+    }
+}
+
+/// What started an execution
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TriggerKind {
+    /// The job came due on its schedule
+    #[default]
+    Scheduled,
+    /// Run through the API
+    Manual,
+    /// Retry of a failed or timed-out attempt
+    Retry {
+        /// Attempt number of this run
+        attempt: u32,
+    },
+    /// Started by the success of an upstream job, or by the failure of the
+    /// job it runs as the `on_failure` hook of
+    Dependency {
+        /// Execution that triggered this one
+        parent_execution_id: String,
+    },
+    /// Run missed while the scheduler was stopped, caught up on start
+    CatchUp,
+    /// Run right after the job was added, by its `run_immediately` option
+    OnCreate,
+}
+
+impl TriggerKind {
+    /// Check whether `other` is the same kind of trigger, ignoring fields
+    pub fn is_same_kind(&self, other: &TriggerKind) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+}
+
+/// A job execution record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobExecution {
+    /// Execution ID
+    pub id: String,
+
+    /// Job ID
+    pub job_id: String,
+
+    /// Execution status
+    pub status: ExecutionStatus,
+
+    /// Attempt number within a run, starting at 1 (greater after retries)
+    #[serde(default = "default_attempt")]
+    pub attempt: u32,
+
+    /// What started the execution; records saved before it was tracked read
+    /// as scheduled
+    #[serde(default)]
+    pub trigger: TriggerKind,
+
+    /// Nominal fire time of the occurrence the execution serves, for
+    /// scheduled and caught-up runs and their retries
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scheduled_for: Option<DateTime<Utc>>,
+
+    /// Start timestamp
+    pub started_at: DateTime<Utc>,
+
+    /// How long after its nominal fire time a scheduled or caught-up run
+    /// started, in milliseconds, including any jitter
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lag_ms: Option<u64>,
+
+    /// End timestamp
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ended_at: Option<DateTime<Utc>>,
+
+    /// Duration in milliseconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+
+    /// Exit code (if available)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+
+    /// Standard output (truncated if too long)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub stdout: String,
+
+    /// Standard error (truncated if too long)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub stderr: String,
+
+    /// Error message (if failed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+
+    /// How the process was stopped, if it had to be
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub termination: Option<Termination>,
+
+    /// Execution of the upstream job whose success triggered this one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub triggered_by: Option<String>,
+
+    /// Failed execution this run handled as an `on_failure` hook. Hook runs
+    /// don't count towards any job's statistics.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hook_for: Option<String>,
+
+    /// Job fields overridden for this manual run only (`command`,
+    /// `timeout_ms`, `env.NAME`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub overrides: Vec<String>,
+
+    /// Files holding the output when it is stored outside the record, in
+    /// which case `stdout` and `stderr` are empty
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_files: Option<OutputFiles>,
+
+    /// Hash of the full standard output of a successful run of a job with
+    /// `notify_on_change` set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_hash: Option<String>,
+
+    /// Whether the output differs from the job's previous successful run,
+    /// for successful runs of jobs with `notify_on_change` set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_changed: Option<bool>,
+}
+
+/// How a job has been faring
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobHealth {
+    /// Recent runs succeeded and the job is firing on time
+    Healthy,
+    /// Some recent runs failed
+    Degraded,
+    /// The latest runs all failed
+    Failing,
+    /// The job is active but overdue, so the scheduler isn't firing it
+    Stale,
+}
+
+impl std::fmt::Display for JobHealth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JobHealth::Healthy => write!(f, "healthy"),
+            JobHealth::Degraded => write!(f, "degraded"),
+            JobHealth::Failing => write!(f, "failing"),
+            JobHealth::Stale => write!(f, "stale"),
+        }
+    }
+}
+
+/// Thresholds classifying a job's health
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HealthThresholds {
+    /// Recent runs considered (default 10)
+    pub window: usize,
+    /// A job whose latest runs failed this many times in a row is failing
+    /// (default 3)
+    pub failing_after: usize,
+    /// An active job whose next run is overdue by more than this is stale
+    /// (default 5 minutes)
+    pub stale_after: std::time::Duration,
+}
+
+impl Default for HealthThresholds {
+    fn default() -> Self {
+        Self {
+            window: 10,
+            failing_after: 3,
+            stale_after: std::time::Duration::from_secs(300),
+        }
+    }
+}
+
+impl HealthThresholds {
+    /// Set how many recent runs are considered
+    pub fn with_window(mut self, window: usize) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Set how many failed runs in a row make a job failing
+    pub fn with_failing_after(mut self, failing_after: usize) -> Self {
+        self.failing_after = failing_after;
+        self
+    }
+
+    /// Set how overdue an active job must be to be stale
+    pub fn with_stale_after(mut self, stale_after: std::time::Duration) -> Self {
+        self.stale_after = stale_after;
+        self
+    }
+
+    /// Classify `job` from its executions, most recent first. Failing takes
+    /// precedence over stale, and stale over degraded.
+    pub fn assess(
+        &self,
+        job: &CronJob,
+        executions: &[JobExecution],
+        now: DateTime<Utc>,
+    ) -> JobHealth {
+        // Skipped and cancelled runs say nothing about the job's health
+        let failed: Vec<bool> = executions
+            .iter()
+            .filter(|e| {
+                !matches!(
+                    e.status,
+                    ExecutionStatus::Skipped | ExecutionStatus::Cancelled
+                )
+            })
+            .take(self.window)
+            .map(|e| e.status != ExecutionStatus::Success)
+            .collect();
+
+        let latest_failures = failed.iter().take_while(|failed| **failed).count();
+        if self.failing_after > 0 && latest_failures >= self.failing_after {
+            return JobHealth::Failing;
+        }
+        let stale_after =
+            chrono::Duration::from_std(self.stale_after).unwrap_or(chrono::TimeDelta::MAX);
+        let overdue = job
+            .next_run
+            .is_some_and(|next_run| now.signed_duration_since(next_run) > stale_after);
+        if job.status == JobStatus::Active && overdue {
+            return JobHealth::Stale;
+        }
+        if failed.contains(&true) {
+            return JobHealth::Degraded;
+        }
+        JobHealth::Healthy
+    }
+}
+
+/// Successful runs a job needs before its durations are checked for
+/// anomalies
+pub const MIN_ANOMALY_SAMPLES: u64 = 10;
+
+/// Standard deviations above the mean duration at which a run is reported as
+/// anomalous, unless the manager sets its own
+pub const DEFAULT_ANOMALY_THRESHOLD: f64 = 3.0;
+
+/// Running mean and variance of a job's successful run durations, updated
+/// one run at a time with Welford's algorithm
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct DurationStats {
+    /// Runs recorded
+    pub count: u64,
+    /// Mean duration in milliseconds
+    pub mean_ms: f64,
+    /// Sum of squared differences from the mean
+    pub m2: f64,
+}
+
+impl DurationStats {
+    /// Check whether no run has been recorded
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Add a run's duration
+    pub fn record(&mut self, duration_ms: u64) {
+        let duration = duration_ms as f64;
+        self.count += 1;
+        let delta = duration - self.mean_ms;
+        self.mean_ms += delta / self.count as f64;
+        self.m2 += delta * (duration - self.mean_ms);
+    }
+
+    /// Sample variance of the recorded durations
+    pub fn variance(&self) -> f64 {
+        if self.count < 2 {
+            return 0.0;
+        }
+        self.m2 / (self.count - 1) as f64
+    }
+
+    /// Sample standard deviation of the recorded durations
+    pub fn stddev(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    /// Check whether a run lasting `duration_ms` took more than `threshold`
+    /// standard deviations longer than the mean. Never true before
+    /// [`MIN_ANOMALY_SAMPLES`] runs are recorded.
+    pub fn is_anomalous(&self, duration_ms: u64, threshold: f64) -> bool {
+        self.count >= MIN_ANOMALY_SAMPLES
+            && duration_ms as f64 > self.mean_ms + threshold * self.stddev()
+    }
+}
+
+/// Aggregates of a job's recent executions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobStats {
+    /// Job identifier
+    pub job_id: String,
+    /// Total runs, successful or failed
+    pub total_runs: u64,
+    /// Executions the remaining figures are computed over, skipped runs
+    /// excluded
+    pub sampled: usize,
+    /// Share of sampled executions that succeeded, from 0.0 to 1.0
+    pub success_rate: f64,
+    /// Failed runs since the last success
+    pub consecutive_failures: u32,
+    /// Mean duration of the sampled executions in milliseconds
+    pub avg_duration_ms: Option<u64>,
+    /// 95th percentile duration of the sampled executions in milliseconds
+    pub p95_duration_ms: Option<u64>,
+    /// Mean lag of the sampled scheduled runs behind their fire times, in
+    /// milliseconds
+    pub avg_lag_ms: Option<u64>,
+    /// Longest lag of the sampled scheduled runs behind their fire times, in
+    /// milliseconds
+    pub max_lag_ms: Option<u64>,
+    /// Start of the latest sampled success
+    pub last_success: Option<DateTime<Utc>>,
+    /// Start of the latest sampled failure
+    pub last_failure: Option<DateTime<Utc>>,
+    /// Error of the most recent failed run
+    pub last_error: Option<String>,
+}
+
+impl JobStats {
+    /// Compute the stats of `job` from its executions, most recent first
+    pub fn compute(job: &CronJob, executions: &[JobExecution]) -> Self {
+        let sampled: Vec<&JobExecution> = executions
+            .iter()
+            .filter(|e| e.status != ExecutionStatus::Skipped)
+            .collect();
+        let successes = sampled
+            .iter()
+            .filter(|e| e.status == ExecutionStatus::Success)
+            .count();
+        let success_rate = if sampled.is_empty() {
+            0.0
+        } else {
+            successes as f64 / sampled.len() as f64
+        };
+
+        let mut durations: Vec<u64> = sampled.iter().filter_map(|e| e.duration_ms).collect();
+        durations.sort_unstable();
+        let avg_duration_ms =
+            (!durations.is_empty()).then(|| durations.iter().sum::<u64>() / durations.len() as u64);
+        // Nearest-rank percentile
+        let p95_duration_ms =
+            (!durations.is_empty()).then(|| durations[(durations.len() * 95).div_ceil(100) - 1]);
+        let lags: Vec<u64> = sampled.iter().filter_map(|e| e.lag_ms).collect();
+        let avg_lag_ms = (!lags.is_empty()).then(|| lags.iter().sum::<u64>() / lags.len() as u64);
+
+        Self {
+            job_id: job.id.clone(),
+            total_runs: job.run_count + job.fail_count,
+            sampled: sampled.len(),
+            success_rate,
+            consecutive_failures: job.consecutive_failures,
+            avg_duration_ms,
+            p95_duration_ms,
+            avg_lag_ms,
+            max_lag_ms: lags.iter().max().copied(),
+            last_success: sampled
+                .iter()
+                .find(|e| e.status == ExecutionStatus::Success)
+                .map(|e| e.started_at),
+            last_failure: sampled
+                .iter()
+                .find(|e| e.status != ExecutionStatus::Success)
+                .map(|e| e.started_at),
+            last_error: job.last_error.clone(),
+        }
+    }
+}
+
+/// Where an execution's output was written
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutputFiles {
+    /// File holding standard output
+    pub stdout_path: PathBuf,
+    /// Size of standard output in bytes
+    pub stdout_bytes: u64,
+    /// File holding standard error
+    pub stderr_path: PathBuf,
+    /// Size of standard error in bytes
+    pub stderr_bytes: u64,
+}
+
+/// Where execution output is kept
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputStorage {
+    /// In the execution record, truncated to 10,000 bytes per stream
+    #[default]
+    Inline,
+    /// In full, in `<dir>/<job_id>/<execution_id>.{out,err}`. The oldest
+    /// files are deleted once they take more than `max_total_bytes`.
+    Files {
+        /// Directory holding the files
+        dir: PathBuf,
+        /// Most space the files may take
+        max_total_bytes: u64,
+    },
+}
+
+impl OutputStorage {
+    /// Store output in files under `<workspace>/cron/logs`, keeping at most
+    /// 1 GiB
+    pub fn files_in(workspace: impl AsRef<Path>) -> Self {
+        OutputStorage::Files {
+            dir: workspace.as_ref().join("cron").join("logs"),
+            max_total_bytes: DEFAULT_MAX_LOG_BYTES,
+        }
+    }
+}
+
+/// Default space kept for output files
+pub const DEFAULT_MAX_LOG_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// How managers sharing a store avoid running a job twice
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Coordination {
+    /// Every manager schedules on its own
+    #[default]
+    None,
+    /// Managers take a job's lease, lasting `lease_ttl` between renewals,
+    /// before running it
+    PerJobLease { lease_ttl: std::time::Duration },
+    /// The manager holding the leader lease, lasting `lease_ttl` between
+    /// renewals, schedules every job; the others stand by and take over
+    /// when its lease expires
+    LeaderElection { lease_ttl: std::time::Duration },
+}
+
+impl Coordination {
+    /// Lease duration of the mode, if it uses leases
+    pub fn lease_ttl(&self) -> Option<std::time::Duration> {
+        match self {
+            Coordination::None => None,
+            Coordination::PerJobLease { lease_ttl }
+            | Coordination::LeaderElection { lease_ttl } => Some(*lease_ttl),
+        }
+    }
+}
+
+/// How much execution history is kept for each job; the default keeps all
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    /// Most executions kept per job, the oldest being deleted first
+    pub max_executions_per_job: Option<usize>,
+    /// Executions started longer ago than this are deleted
+    pub max_age: Option<std::time::Duration>,
+}
+
+impl RetentionPolicy {
+    /// Create a policy keeping all history
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep at most `max` executions per job
+    pub fn with_max_executions_per_job(mut self, max: usize) -> Self {
+        self.max_executions_per_job = Some(max);
+        self
+    }
+
+    /// Delete executions started longer ago than `max_age`
+    pub fn with_max_age(mut self, max_age: std::time::Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Check if the policy keeps all history
+    pub fn is_empty(&self) -> bool {
+        self.max_executions_per_job.is_none() && self.max_age.is_none()
+    }
+}
+
+impl JobExecution {
+    /// Create a new execution record
     pub fn new(job_id: impl Into<String>) -> Self {
         Self {
             id: Uuid::new_v4().to_string(),
             job_id: job_id.into(),
             status: ExecutionStatus::Success,
+            attempt: default_attempt(),
+            trigger: TriggerKind::default(),
+            scheduled_for: None,
             started_at: Utc::now(),
             ended_at: None,
+            lag_ms: None,
             duration_ms: None,
             exit_code: None,
             stdout: String::new(),
             stderr: String::new(),
             error: None,
+            termination: None,
+            triggered_by: None,
+            hook_for: None,
+            overrides: Vec::new(),
+            output_files: None,
+            output_hash: None,
+            output_changed: None,
         }
     }
 
@@ -371,9 +3061,88 @@ impl JobExecution {
         self.error = Some("Execution cancelled".to_string());
         self
     }
+
+    /// Attach output captured before the execution ended early
+    pub fn with_output(mut self, stdout: String, stderr: String) -> Self {
+        self.stdout = truncate_output(stdout, 10_000);
+        self.stderr = truncate_output(stderr, 10_000);
+        self
+    }
+
+    /// Record how the process was stopped
+    pub fn with_termination(mut self, termination: Termination) -> Self {
+        self.termination = Some(termination);
+        self
+    }
+
+    /// Check if this run was an `on_failure` hook
+    pub fn is_hook(&self) -> bool {
+        self.hook_for.is_some()
+    }
+
+    /// Mark execution as skipped
+    pub fn skip(mut self, reason: impl Into<String>) -> Self {
+        self.ended_at = Some(self.started_at);
+        self.duration_ms = Some(0);
+        self.status = ExecutionStatus::Skipped;
+        self.error = Some(reason.into());
+        self
+    }
+}
+
+fn default_attempt() -> u32 {
+    1
 }
 
 /// Truncate output to a maximum length
+/// Text replacing secret values
+pub const SECRET_MASK: &str = "***";
+
+/// Replace every occurrence of each secret in `text` with [`SECRET_MASK`].
+/// Longer secrets are replaced first so one containing another is masked
+/// whole.
+pub(crate) fn mask_secrets(text: &str, secrets: &[String]) -> String {
+    let mut secrets: Vec<&String> = secrets.iter().filter(|s| !s.is_empty()).collect();
+    secrets.sort_by_key(|secret| std::cmp::Reverse(secret.len()));
+    secrets.into_iter().fold(text.to_string(), |text, secret| {
+        text.replace(secret.as_str(), SECRET_MASK)
+    })
+}
+
+/// Stable hash of a run's output (64-bit FNV-1a, in hex), optionally
+/// ignoring differences in whitespace
+pub(crate) fn output_hash(output: &str, ignore_whitespace: bool) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut feed = |bytes: &[u8]| {
+        for byte in bytes {
+            hash ^= u64::from(*byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    };
+    if ignore_whitespace {
+        for word in output.split_whitespace() {
+            feed(word.as_bytes());
+            feed(b" ");
+        }
+    } else {
+        feed(output.as_bytes());
+    }
+    format!("{:016x}", hash)
+}
+
+/// Quote `word` for a POSIX shell, leaving plain words as they are
+fn shell_quote(word: &str) -> String {
+    let plain = !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c));
+    if plain {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', "'\\''"))
+    }
+}
+
 fn truncate_output(s: String, max_len: usize) -> String {
     if s.len() <= max_len {
         s
@@ -393,7 +3162,9 @@ mod tests {
         assert_eq!(job.schedule, "*/5 * * * *");
         assert_eq!(job.command, "echo hello");
         assert_eq!(job.status, JobStatus::Active);
-        assert_eq!(job.timeout_ms, 60_000);
+        assert_eq!(job.timeout_ms, None);
+        assert_eq!(job.resolve_timeout_ms(None), DEFAULT_TIMEOUT_MS);
+        assert_eq!(job.resolve_timeout_ms(Some(5_000)), 5_000);
         assert!(job.is_active());
     }
 
@@ -404,7 +3175,8 @@ mod tests {
             .with_working_dir("/tmp")
             .with_env("KEY", "VALUE");
 
-        assert_eq!(job.timeout_ms, 30_000);
+        assert_eq!(job.timeout_ms, Some(30_000));
+        assert_eq!(job.resolve_timeout_ms(Some(5_000)), 30_000);
         assert_eq!(job.working_dir, Some("/tmp".to_string()));
         assert_eq!(job.env, vec![("KEY".to_string(), "VALUE".to_string())]);
     }
@@ -414,6 +3186,8 @@ mod tests {
         assert_eq!(JobStatus::Active.to_string(), "active");
         assert_eq!(JobStatus::Paused.to_string(), "paused");
         assert_eq!(JobStatus::Running.to_string(), "running");
+        assert_eq!(JobStatus::Completed.to_string(), "completed");
+        assert_eq!(JobStatus::Disabled.to_string(), "disabled");
     }
 
     #[test]
@@ -433,6 +3207,367 @@ mod tests {
         assert_eq!(exec.error, Some("Something went wrong".to_string()));
     }
 
+    #[test]
+    fn test_job_execution_skip() {
+        let exec = JobExecution::new("job-1").skip("Previous run still in progress");
+
+        assert_eq!(exec.status, ExecutionStatus::Skipped);
+        assert_eq!(exec.duration_ms, Some(0));
+        assert_eq!(
+            exec.error,
+            Some("Previous run still in progress".to_string())
+        );
+    }
+
+    #[test]
+    fn test_cron_job_timeout_serde() {
+        let job = CronJob::new("test", "* * * * *", "cmd");
+        let json = serde_json::to_value(&job).unwrap();
+        assert!(json.get("timeout_ms").is_none());
+
+        // Jobs saved when the timeout was required keep it as an override
+        let mut json = json;
+        json["timeout_ms"] = serde_json::json!(60_000);
+        json["effective_timeout_ms"] = serde_json::json!(1);
+        let legacy: CronJob = serde_json::from_value(json).unwrap();
+        assert_eq!(legacy.timeout_ms, Some(60_000));
+        assert_eq!(legacy.effective_timeout_ms, None);
+    }
+
+    #[test]
+    fn test_job_execution_trigger_serde() {
+        // Records saved before triggers were tracked read as scheduled
+        let legacy = r#"{"id":"e1","job_id":"job-1","status":"success","started_at":"2026-01-01T00:00:00Z"}"#;
+        let exec: JobExecution = serde_json::from_str(legacy).unwrap();
+        assert_eq!(exec.trigger, TriggerKind::Scheduled);
+        assert_eq!(exec.scheduled_for, None);
+
+        let mut exec = JobExecution::new("job-1");
+        exec.trigger = TriggerKind::Dependency {
+            parent_execution_id: "e0".to_string(),
+        };
+        let json = serde_json::to_value(&exec).unwrap();
+        assert_eq!(
+            json["trigger"],
+            serde_json::json!({"kind": "dependency", "parent_execution_id": "e0"})
+        );
+        let parsed: JobExecution = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.trigger, exec.trigger);
+
+        assert!(TriggerKind::Retry { attempt: 2 }.is_same_kind(&TriggerKind::Retry { attempt: 0 }));
+        assert!(!TriggerKind::Manual.is_same_kind(&TriggerKind::CatchUp));
+    }
+
+    #[test]
+    fn test_concurrency_policy_default() {
+        let job = CronJob::new("test", "* * * * *", "cmd");
+        assert_eq!(job.concurrency_policy, ConcurrencyPolicy::Allow);
+
+        let json = serde_json::to_string(&job).unwrap();
+        assert!(json.contains("\"concurrency_policy\":\"allow\""));
+
+        let job = job.with_concurrency_policy(ConcurrencyPolicy::Forbid);
+        let json = serde_json::to_string(&job).unwrap();
+        let parsed: CronJob = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.concurrency_policy, ConcurrencyPolicy::Forbid);
+
+        let job = job.with_concurrency_policy(ConcurrencyPolicy::Queue { max_queue: 2 });
+        let json = serde_json::to_string(&job).unwrap();
+        assert!(json.contains("\"concurrency_policy\":{\"queue\":{\"max_queue\":2}}"));
+        let parsed: CronJob = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.concurrency_policy, job.concurrency_policy);
+    }
+
+    #[test]
+    fn test_retry_delay_backoff() {
+        let job = CronJob::new("test", "* * * * *", "cmd");
+        assert_eq!(job.max_retries, 0);
+        assert_eq!(job.retry_delay(1).as_millis(), 1_000);
+
+        let job = job.with_retries(3, 500, 2.0);
+        assert_eq!(job.max_retries, 3);
+        assert_eq!(job.retry_delay(1).as_millis(), 500);
+        assert_eq!(job.retry_delay(2).as_millis(), 1_000);
+        assert_eq!(job.retry_delay(3).as_millis(), 2_000);
+    }
+
+    #[test]
+    fn test_retry_fields_default_when_missing() {
+        let job = CronJob::new("test", "* * * * *", "cmd").with_retries(2, 250, 3.0);
+        let mut value = serde_json::to_value(&job).unwrap();
+        let map = value.as_object_mut().unwrap();
+        assert_eq!(map["max_retries"], 2);
+        map.remove("max_retries");
+        map.remove("retry_delay_ms");
+        map.remove("retry_backoff");
+
+        let parsed: CronJob = serde_json::from_value(value).unwrap();
+        assert_eq!(parsed.max_retries, 0);
+        assert_eq!(parsed.retry_delay_ms, 1_000);
+        assert_eq!(parsed.retry_backoff, 1.0);
+
+        let exec: JobExecution = serde_json::from_str(
+            r#"{"id":"e","job_id":"j","status":"success","started_at":"2026-01-01T00:00:00Z"}"#,
+        )
+        .unwrap();
+        assert_eq!(exec.attempt, 1);
+    }
+
+    #[test]
+    fn test_misfire_policy_serialization() {
+        let job = CronJob::new("test", "* * * * *", "cmd");
+        assert_eq!(job.misfire_policy, MisfirePolicy::FireOnce);
+
+        let job = job.with_misfire_policy(MisfirePolicy::CatchUp { max: 3 });
+        let json = serde_json::to_string(&job).unwrap();
+        assert!(json.contains(r#""misfire_policy":{"catch_up":{"max":3}}"#));
+        let parsed: CronJob = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.misfire_policy, MisfirePolicy::CatchUp { max: 3 });
+
+        assert_eq!(
+            serde_json::to_string(&MisfirePolicy::Skip).unwrap(),
+            r#""skip""#
+        );
+    }
+
+    #[test]
+    fn test_next_run_within_window() {
+        use chrono::TimeZone;
+
+        let start = Utc.with_ymd_and_hms(2026, 12, 1, 0, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2026, 12, 31, 23, 59, 59).unwrap();
+        let mut job =
+            CronJob::new("december", "0 * * * *", "cmd").with_window(Some(start), Some(end));
+
+        let before = Utc.with_ymd_and_hms(2026, 10, 15, 8, 30, 0).unwrap();
+        assert_eq!(job.next_run_after(before), Some(start));
+
+        let during = Utc.with_ymd_and_hms(2026, 12, 10, 8, 30, 0).unwrap();
+        assert_eq!(
+            job.next_run_after(during),
+            Some(Utc.with_ymd_and_hms(2026, 12, 10, 9, 0, 0).unwrap())
+        );
+
+        let last = Utc.with_ymd_and_hms(2026, 12, 31, 23, 0, 0).unwrap();
+        assert_eq!(job.next_run_after(last), None);
+
+        job.reschedule(last);
+        assert_eq!(job.status, JobStatus::Completed);
+        assert!(job.next_run.is_none());
+
+        // Reopening the window reactivates the job
+        job.not_after = None;
+        job.reschedule(last);
+        assert_eq!(job.status, JobStatus::Active);
+        assert!(job.next_run.is_some());
+    }
+
+    #[test]
+    fn test_max_runs() {
+        let mut job = CronJob::new("limited", "* * * * *", "cmd").with_max_runs(Some(3), false);
+        job.run_count = 2;
+        job.fail_count = 1;
+        job.manual_run_count = 1;
+        assert_eq!(job.counted_runs(), 2);
+        assert!(!job.runs_exhausted());
+
+        job.count_manual = true;
+        assert!(job.runs_exhausted());
+        job.reschedule(Utc::now());
+        assert_eq!(job.status, JobStatus::Completed);
+        assert!(job.next_run.is_none());
+    }
+
+    #[test]
+    fn test_one_shot_job() {
+        use chrono::TimeZone;
+
+        let when = Utc.with_ymd_and_hms(2026, 3, 1, 9, 0, 0).unwrap();
+        let mut job = CronJob::one_shot("once", when, "cmd");
+        assert!(job.is_one_shot());
+        assert_eq!(job.schedule, "@at 2026-03-01T09:00:00+00:00");
+
+        let before = Utc.with_ymd_and_hms(2026, 2, 28, 0, 0, 0).unwrap();
+        assert_eq!(job.next_run_after(before), Some(when));
+        assert_eq!(job.next_run_after(when), None);
+
+        job.reschedule(when);
+        assert_eq!(job.status, JobStatus::Completed);
+    }
+
+    #[test]
+    fn test_dispatch_time_jitter() {
+        use chrono::TimeZone;
+
+        let start = Utc.with_ymd_and_hms(2026, 3, 1, 0, 0, 0).unwrap();
+        let job = CronJob::new("test", "0 * * * *", "cmd");
+        assert_eq!(job.dispatch_time(start), start);
+
+        let job = job.with_jitter(Some(60_000));
+        let mut delays = std::collections::HashSet::new();
+        for hour in 0..100 {
+            let fire_time = start + chrono::Duration::hours(hour);
+            let delay = job.dispatch_time(fire_time) - fire_time;
+            assert!(delay >= chrono::Duration::zero());
+            assert!(delay <= chrono::Duration::milliseconds(60_000));
+            // Stable for the same occurrence
+            assert_eq!(job.dispatch_time(fire_time), fire_time + delay);
+            delays.insert(delay);
+        }
+        assert!(delays.len() > 50);
+    }
+
+    #[test]
+    fn test_blackout_defer() {
+        use chrono::TimeZone;
+
+        let nightly = TimeWindow::parse("01:00-02:00").unwrap();
+        let job = CronJob::new("test", "30 * * * *", "cmd")
+            .with_blackouts(vec![nightly.clone()], BlackoutPolicy::Defer);
+        let start = Utc.with_ymd_and_hms(2026, 3, 1, 0, 45, 0).unwrap();
+
+        // 01:30 is deferred to the end of the window
+        assert_eq!(
+            job.next_run_after(start),
+            Some(Utc.with_ymd_and_hms(2026, 3, 1, 2, 0, 0).unwrap())
+        );
+
+        // Windows passed in by the manager apply too
+        let job = job.with_blackouts(Vec::new(), BlackoutPolicy::Defer);
+        assert_eq!(
+            job.next_run_after_with(start, std::slice::from_ref(&nightly)),
+            Some(Utc.with_ymd_and_hms(2026, 3, 1, 2, 0, 0).unwrap())
+        );
+
+        // Skipped runs keep their nominal time
+        let job = job.with_blackouts(vec![nightly], BlackoutPolicy::Skip);
+        assert_eq!(
+            job.next_run_after(start),
+            Some(Utc.with_ymd_and_hms(2026, 3, 1, 1, 30, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_blackout_defer_across_midnight() {
+        use chrono::TimeZone;
+
+        let job = CronJob::new("test", "0 0 * * *", "cmd").with_blackouts(
+            vec![TimeWindow::parse("23:30-00:30").unwrap()],
+            BlackoutPolicy::Defer,
+        );
+        let start = Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap();
+        assert_eq!(
+            job.next_run_after(start),
+            Some(Utc.with_ymd_and_hms(2026, 3, 2, 0, 30, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_allowed_window() {
+        use chrono::{TimeZone, Weekday};
+
+        let business_hours = TimeWindow::parse("09:00-17:00").unwrap().on_days(&[
+            Weekday::Mon,
+            Weekday::Tue,
+            Weekday::Wed,
+            Weekday::Thu,
+            Weekday::Fri,
+        ]);
+        let job =
+            CronJob::new("test", "*/10 * * * *", "cmd").with_allowed_window(Some(business_hours));
+
+        // The last run on Friday is at 16:50; the next is Monday 09:00
+        let friday = Utc.with_ymd_and_hms(2026, 3, 6, 16, 45, 0).unwrap();
+        assert_eq!(
+            job.next_run_after(friday),
+            Some(Utc.with_ymd_and_hms(2026, 3, 6, 16, 50, 0).unwrap())
+        );
+        let last = Utc.with_ymd_and_hms(2026, 3, 6, 16, 50, 0).unwrap();
+        assert_eq!(
+            job.next_run_after(last),
+            Some(Utc.with_ymd_and_hms(2026, 3, 9, 9, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_trigger_only_job() {
+        let mut job = CronJob::trigger_only("downstream", "cmd");
+        assert!(job.is_trigger_only());
+        assert_eq!(job.schedule, NEVER_SCHEDULE);
+        assert_eq!(job.next_run_after(Utc::now()), None);
+
+        job.reschedule(Utc::now());
+        assert_eq!(job.status, JobStatus::Active);
+        assert!(job.next_run.is_none());
+
+        let json = serde_json::to_string(&job).unwrap();
+        assert!(json.contains(r#""schedule_kind":"never""#));
+        let parsed: CronJob = serde_json::from_str(&json).unwrap();
+        assert!(parsed.is_trigger_only());
+    }
+
+    #[test]
+    fn test_schedule_kind_serialization() {
+        // Recurring jobs keep the existing file format
+        let job = CronJob::new("test", "* * * * *", "cmd");
+        let json = serde_json::to_string(&job).unwrap();
+        assert!(!json.contains("schedule_kind"));
+        let parsed: CronJob = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.schedule_kind, ScheduleKind::Cron);
+
+        let when = DateTime::parse_from_rfc3339("2026-03-01T09:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let job = CronJob::one_shot("once", when, "cmd");
+        let json = serde_json::to_string(&job).unwrap();
+        assert!(json.contains(r#""schedule_kind":{"at":"2026-03-01T09:00:00Z"}"#));
+        let parsed: CronJob = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.schedule_kind, ScheduleKind::At(when));
+    }
+
+    #[test]
+    fn test_exec_job() {
+        let job = CronJob::new("test", "* * * * *", "").with_exec(
+            "printf",
+            vec!["%s\\n".to_string(), "it's; rm -rf /".to_string()],
+        );
+        assert!(job.is_exec());
+        assert_eq!(job.command, r#"printf '%s\n' 'it'\''s; rm -rf /'"#);
+
+        let json = serde_json::to_string(&job).unwrap();
+        assert!(json.contains(r#""command_kind":"exec""#));
+        let parsed: CronJob = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.program, "printf");
+        assert_eq!(parsed.args, job.args);
+
+        // Job files written before exec jobs existed load as shell jobs
+        let json = serde_json::to_string(&CronJob::new("test", "* * * * *", "cmd")).unwrap();
+        assert!(!json.contains("command_kind"));
+        let parsed: CronJob = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.command_kind, CommandKind::Shell);
+        assert_eq!(parsed.command, "cmd");
+        assert!(parsed.program.is_empty());
+    }
+
+    #[test]
+    fn test_mask_secrets() {
+        let secrets = vec!["abc".to_string(), "abcdef".to_string(), String::new()];
+        assert_eq!(
+            mask_secrets("abcdef abc xabcx abc", &secrets),
+            "*** *** x***x ***"
+        );
+        assert_eq!(mask_secrets("nothing here", &secrets), "nothing here");
+
+        let job = CronJob::new("job", "* * * * *", "curl -H 'Authorization: tok123'")
+            .with_env("TOKEN", "tok123")
+            .with_env("USER", "tok123-user")
+            .with_secret_env(vec!["TOKEN".into()]);
+        let redacted = job.redacted();
+        assert_eq!(redacted.command, "curl -H 'Authorization: ***'");
+        assert_eq!(redacted.env[0].1, "***");
+        assert_eq!(redacted.env[1].1, "***-user");
+    }
+
     #[test]
     fn test_truncate_output() {
         let short = "hello".to_string();
@@ -443,4 +3578,141 @@ mod tests {
         assert!(truncated.ends_with("...[truncated]"));
         assert!(truncated.len() < 200);
     }
+
+    #[test]
+    fn test_job_stats() {
+        let mut job = CronJob::new("stats", "* * * * *", "echo");
+        job.run_count = 19;
+        job.fail_count = 2;
+        job.consecutive_failures = 1;
+        job.last_error = Some("exit 1".to_string());
+
+        // Most recent first: a failure, then durations 1..=20 with one
+        // failure among them, and a skipped run that isn't sampled
+        let mut executions = Vec::new();
+        let mut failed = JobExecution::new(&job.id).fail("exit 1".to_string());
+        failed.duration_ms = Some(100);
+        executions.push(failed);
+        for ms in (1..=20).rev() {
+            let mut exec = JobExecution::new(&job.id);
+            exec.status = if ms == 10 {
+                ExecutionStatus::Failed
+            } else {
+                ExecutionStatus::Success
+            };
+            exec.duration_ms = Some(ms);
+            executions.push(exec);
+        }
+        executions.push(JobExecution::new(&job.id).skip("busy"));
+
+        let stats = JobStats::compute(&job, &executions);
+        assert_eq!(stats.total_runs, 21);
+        assert_eq!(stats.sampled, 21);
+        assert!((stats.success_rate - 19.0 / 21.0).abs() < 1e-9);
+        assert_eq!(stats.consecutive_failures, 1);
+        assert_eq!(stats.avg_duration_ms, Some((210 + 100) / 21));
+        assert_eq!(stats.p95_duration_ms, Some(20));
+        assert_eq!(stats.last_success, Some(executions[1].started_at));
+        assert_eq!(stats.last_failure, Some(executions[0].started_at));
+        assert_eq!(stats.last_error.as_deref(), Some("exit 1"));
+
+        let empty = JobStats::compute(&job, &[]);
+        assert_eq!(empty.sampled, 0);
+        assert_eq!(empty.success_rate, 0.0);
+        assert_eq!(empty.avg_duration_ms, None);
+        assert_eq!(empty.p95_duration_ms, None);
+    }
+
+    #[test]
+    fn test_snooze() {
+        let now = Utc::now();
+        let mut job = CronJob::new("snoozed", "0 * * * *", "echo");
+        assert!(!job.is_snoozed_at(now));
+
+        job.snoozed_runs = 2;
+        assert!(job.is_snoozed_at(now));
+        job.consume_snooze();
+        job.consume_snooze();
+        assert!(!job.is_snoozed_at(now));
+
+        job.snoozed_until = Some(now + chrono::Duration::hours(2));
+        assert!(job.is_snoozed_at(now + chrono::Duration::hours(1)));
+        assert!(!job.is_snoozed_at(now + chrono::Duration::hours(2)));
+        job.next_run = Some(now + chrono::Duration::hours(1));
+        job.consume_snooze();
+        assert!(job.snoozed_until.is_some());
+        job.next_run = Some(now + chrono::Duration::hours(2));
+        job.consume_snooze();
+        assert!(job.snoozed_until.is_none());
+
+        // Snooze state persists with the job
+        job.snoozed_runs = 3;
+        let json = serde_json::to_string(&job).unwrap();
+        let restored: CronJob = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.snoozed_runs, 3);
+        let json = serde_json::to_string(&CronJob::new("plain", "* * * * *", "echo")).unwrap();
+        assert!(!json.contains("snoozed"));
+    }
+
+    #[test]
+    fn test_bulk_result() {
+        let mut result = BulkResult::default();
+        result.record("a", &Ok(()));
+        result.record::<()>("b", &Err(CronError::JobNotFound("b".to_string())));
+        assert!(!result.is_success());
+        assert_eq!(result.succeeded, ["a"]);
+        assert_eq!(
+            result.failed,
+            [("b".to_string(), "Job not found: b".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_duration_stats() {
+        let durations = [120u64, 95, 130, 110, 105, 98, 125, 117, 102, 108];
+        let mut stats = DurationStats::default();
+        for duration in durations {
+            stats.record(duration);
+        }
+        let n = durations.len() as f64;
+        let mean = durations.iter().sum::<u64>() as f64 / n;
+        let variance = durations
+            .iter()
+            .map(|d| (*d as f64 - mean).powi(2))
+            .sum::<f64>()
+            / (n - 1.0);
+        assert_eq!(stats.count, 10);
+        assert!((stats.mean_ms - mean).abs() < 1e-9);
+        assert!((stats.variance() - variance).abs() < 1e-9);
+
+        // mean 111, stddev ~11.6
+        assert!(!stats.is_anomalous(140, DEFAULT_ANOMALY_THRESHOLD));
+        assert!(stats.is_anomalous(200, DEFAULT_ANOMALY_THRESHOLD));
+        assert!(stats.is_anomalous(140, 2.0));
+
+        // Too few samples to judge
+        let mut few = DurationStats::default();
+        for duration in &durations[..9] {
+            few.record(*duration);
+        }
+        assert!(!few.is_anomalous(10_000, DEFAULT_ANOMALY_THRESHOLD));
+        assert_eq!(DurationStats::default().variance(), 0.0);
+
+        // Small spreads around a large mean are kept exactly
+        let mut large = DurationStats::default();
+        for i in 0..1000u64 {
+            large.record(1_000_000_000 + i % 4);
+        }
+        assert!((large.mean_ms - 1_000_000_001.5).abs() < 1e-6);
+        assert!((large.variance() - 1.2512512512512513).abs() < 1e-6);
+
+        // Stats persist with the job, and are left out until recorded
+        let mut job = CronJob::new("stats", "* * * * *", "echo");
+        let json = serde_json::to_string(&job).unwrap();
+        assert!(!json.contains("duration_stats"));
+        job.duration_stats = stats;
+        let json = serde_json::to_string(&job).unwrap();
+        let restored: CronJob = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.duration_stats, stats);
+    }
 }
@@ -0,0 +1,198 @@
+//! Daily time windows
+//!
+//! A window is a time-of-day range in UTC, optionally limited to certain
+//! weekdays. Windows whose end is earlier than their start cross midnight.
+
+use crate::types::{CronError, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveTime, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+
+/// A daily time range such as 01:00–02:00 UTC
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeWindow {
+    /// Start of the window (inclusive)
+    pub start: NaiveTime,
+
+    /// End of the window (exclusive); earlier than `start` to cross midnight
+    pub end: NaiveTime,
+
+    /// Weekdays the window starts on; empty for every day
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub days: Vec<Weekday>,
+}
+
+impl TimeWindow {
+    /// Create a window from `start` to `end`. Fails if the window is empty.
+    pub fn new(start: NaiveTime, end: NaiveTime) -> Result<Self> {
+        if start == end {
+            return Err(CronError::InvalidConfig(format!(
+                "time window {} is empty",
+                format_range(start, end)
+            )));
+        }
+        Ok(Self {
+            start,
+            end,
+            days: Vec::new(),
+        })
+    }
+
+    /// Parse a window written as `HH:MM-HH:MM`
+    pub fn parse(range: &str) -> Result<Self> {
+        let invalid = || {
+            CronError::InvalidConfig(format!(
+                "invalid time window '{}', expected HH:MM-HH:MM",
+                range
+            ))
+        };
+        let (start, end) = range.split_once('-').ok_or_else(invalid)?;
+        let parse_time =
+            |time: &str| NaiveTime::parse_from_str(time.trim(), "%H:%M").map_err(|_| invalid());
+        Self::new(parse_time(start)?, parse_time(end)?)
+    }
+
+    /// Limit the window to the given weekdays
+    pub fn on_days(mut self, days: &[Weekday]) -> Self {
+        self.days = days.to_vec();
+        self
+    }
+
+    /// Check if the window crosses midnight
+    pub fn crosses_midnight(&self) -> bool {
+        self.end < self.start
+    }
+
+    /// Check if `at` falls inside the window
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        self.opened_at(at).is_some()
+    }
+
+    /// End of the window containing `at`, if any
+    pub fn end_after(&self, at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let opened = self.opened_at(at)?;
+        let mut end = opened.date_naive().and_time(self.end).and_utc();
+        if self.crosses_midnight() {
+            end += Duration::days(1);
+        }
+        Some(end)
+    }
+
+    /// First time the window opens strictly after `at`
+    pub fn next_start_after(&self, at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        // A window opens at least once a week when it has any days at all
+        (0..=7)
+            .filter_map(|days| at.date_naive().checked_add_signed(Duration::days(days)))
+            .filter(|date| self.runs_on(date.weekday()))
+            .map(|date| date.and_time(self.start).and_utc())
+            .find(|start| *start > at)
+    }
+
+    /// Start of the window containing `at`, if any
+    fn opened_at(&self, at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let time = at.time();
+        let today = at.date_naive();
+        let opened = if !self.crosses_midnight() {
+            (self.start <= time && time < self.end).then_some(today)
+        } else if time >= self.start {
+            Some(today)
+        } else if time < self.end {
+            today.pred_opt()
+        } else {
+            None
+        }?;
+        self.runs_on(opened.weekday())
+            .then(|| opened.and_time(self.start).and_utc())
+    }
+
+    /// Check if the window opens on `day`
+    fn runs_on(&self, day: Weekday) -> bool {
+        self.days.is_empty() || self.days.contains(&day)
+    }
+}
+
+impl std::fmt::Display for TimeWindow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", format_range(self.start, self.end))?;
+        if !self.days.is_empty() {
+            let days: Vec<String> = self.days.iter().map(|day| day.to_string()).collect();
+            write!(f, " on {}", days.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+fn format_range(start: NaiveTime, end: NaiveTime) -> String {
+    format!("{}-{}", start.format("%H:%M"), end.format("%H:%M"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        // March 2026: the 2nd is a Monday
+        Utc.with_ymd_and_hms(2026, 3, day, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let window = TimeWindow::parse("01:00-02:30").unwrap();
+        assert_eq!(window.start, NaiveTime::from_hms_opt(1, 0, 0).unwrap());
+        assert_eq!(window.end, NaiveTime::from_hms_opt(2, 30, 0).unwrap());
+        assert_eq!(window.to_string(), "01:00-02:30");
+
+        assert!(TimeWindow::parse("01:00").is_err());
+        assert!(TimeWindow::parse("25:00-02:00").is_err());
+        assert!(TimeWindow::parse("01:00-01:00").is_err());
+    }
+
+    #[test]
+    fn test_contains() {
+        let window = TimeWindow::parse("01:00-02:00").unwrap();
+        assert!(!window.contains(at(2, 0, 59)));
+        assert!(window.contains(at(2, 1, 0)));
+        assert!(window.contains(at(2, 1, 59)));
+        assert!(!window.contains(at(2, 2, 0)));
+        assert_eq!(window.end_after(at(2, 1, 30)), Some(at(2, 2, 0)));
+        assert_eq!(window.end_after(at(2, 3, 0)), None);
+    }
+
+    #[test]
+    fn test_crosses_midnight() {
+        let window = TimeWindow::parse("23:30-00:30").unwrap();
+        assert!(window.crosses_midnight());
+        assert!(!window.contains(at(2, 23, 29)));
+        assert!(window.contains(at(2, 23, 30)));
+        assert!(window.contains(at(3, 0, 15)));
+        assert!(!window.contains(at(3, 0, 30)));
+        assert_eq!(window.end_after(at(2, 23, 45)), Some(at(3, 0, 30)));
+        assert_eq!(window.end_after(at(3, 0, 15)), Some(at(3, 0, 30)));
+    }
+
+    #[test]
+    fn test_next_start_after() {
+        let window = TimeWindow::parse("09:00-17:00")
+            .unwrap()
+            .on_days(&[Weekday::Mon, Weekday::Fri]);
+        // Monday 08:00 -> Monday 09:00
+        assert_eq!(window.next_start_after(at(2, 8, 0)), Some(at(2, 9, 0)));
+        // Monday 09:00 -> Friday 09:00
+        assert_eq!(window.next_start_after(at(2, 9, 0)), Some(at(6, 9, 0)));
+        // Friday 18:00 -> next Monday
+        assert_eq!(window.next_start_after(at(6, 18, 0)), Some(at(9, 9, 0)));
+    }
+
+    #[test]
+    fn test_days() {
+        let window = TimeWindow::parse("23:00-01:00")
+            .unwrap()
+            .on_days(&[Weekday::Mon]);
+        assert_eq!(window.to_string(), "23:00-01:00 on Mon");
+        assert!(window.contains(at(2, 23, 30)));
+        // Monday's window runs into Tuesday
+        assert!(window.contains(at(3, 0, 30)));
+        assert!(!window.contains(at(3, 23, 30)));
+        assert!(!window.contains(at(2, 0, 30)));
+    }
+}